@@ -24,5 +24,12 @@ fn main() -> Result<()> {
 			.compile_protos(&[matching_proto], &["../matching/proto/"])
 			.context("Failed to compile matching.proto")?;
 	}
+
+	// Compile the gateway's own order entry service
+	tonic_prost_build::configure()
+		.build_server(true)
+		.build_client(false)
+		.compile_protos(&["proto/gateway.proto"], &["proto/"])
+		.context("Failed to compile gateway.proto")?;
 	Ok(())
 }
@@ -29,20 +29,24 @@
 //! - User-level rate limiting should be handled by the application layer
 //!
 //! Gateway does NOT perform:
-//! - User account balance checks (handled by settlement service)
+//! - User account balance checks (owned by the matching engine's ledger;
+//!   Gateway only surfaces its `INSUFFICIENT_BALANCE` disposition)
 //! - Business-level user identity verification
 //! - KYC or compliance checks
 
 use std::{
 	num::NonZeroU32,
 	sync::{
-		Arc,
+		Arc, Mutex,
 		atomic::{AtomicBool, AtomicU8, AtomicU64, Ordering},
 	},
 	time::{Duration, Instant},
 };
 
-use anvil_sdk::types::{OrderType, PlaceOrderRequest};
+use anvil_sdk::{
+	MarketSpec, Price, Quantity,
+	types::{OrderType, PlaceOrderRequest, Side},
+};
 use dashmap::DashMap;
 use governor::{Quota, RateLimiter};
 use moka::sync::Cache;
@@ -52,7 +56,7 @@ use crate::{
 	auth::Principal,
 	config::{
 		DEFAULT_NONCE_TTL_SECS, DEFAULT_RATE_LIMIT_BURST, DEFAULT_RATE_LIMIT_RPS,
-		DEFAULT_REPLAY_CACHE_MAX_CAPACITY, DEFAULT_REPLAY_WINDOW_SECS,
+		DEFAULT_REPLAY_CACHE_MAX_CAPACITY, DEFAULT_REPLAY_WINDOW_SECS, RateLimitConfig,
 	},
 };
 
@@ -69,9 +73,8 @@ pub enum AdmissionError {
 	TimestampOutsideWindow,
 	#[error("Market not available: {0}")]
 	MarketNotAvailable(String),
-	#[error("Insufficient balance")]
-	#[allow(dead_code)]
-	InsufficientBalance,
+	#[error("Insufficient balance: {0}")]
+	InsufficientBalance(String),
 }
 
 /// Market availability tracker
@@ -155,7 +158,6 @@ impl AdmissionController {
 	}
 
 	/// Mark market as available
-	#[allow(dead_code)]
 	pub fn set_market_available(&self, market: &str, available: bool) {
 		self.markets.insert(
 			market.to_string(),
@@ -173,20 +175,6 @@ impl AdmissionController {
 			.map(|m| m.available)
 			.unwrap_or(true) // Default to available if not tracked
 	}
-
-	/// Check balance (placeholder - would query blockchain)
-	///
-	/// Note: This function is intentionally minimal. Balance checking is
-	/// business logic that should be handled by the application layer or
-	/// settlement service, not by Gateway. Gateway only performs protocol-level
-	/// admission control.
-	#[allow(dead_code)]
-	pub async fn check_balance(&self, _market: &str, _required: u64) -> Result<(), AdmissionError> {
-		// TODO: Query blockchain for balance if needed
-		// For now, assume sufficient balance
-		// In production, this should be handled by settlement service
-		Ok(())
-	}
 }
 
 /// Global admission controller instance
@@ -220,6 +208,9 @@ struct ReplayEntry {
 	state: AtomicU8,
 	retry_consumed: AtomicBool,
 	token: AtomicU64,
+	/// Order dispatched under this nonce that was accepted or whose outcome
+	/// is unknown
+	order: Mutex<Option<RecordedOrder>>,
 }
 
 impl ReplayEntry {
@@ -228,16 +219,48 @@ impl ReplayEntry {
 			state: AtomicU8::new(ReplayState::InFlight as u8),
 			retry_consumed: AtomicBool::new(false),
 			token: AtomicU64::new(token),
+			order: Mutex::new(None),
 		}
 	}
 }
 
+/// Order dispatched under a `(principal, nonce)`, answered to duplicates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedOrder {
+	pub market: String,
+	pub order_id: String,
+}
+
+/// How to handle an order submitted under a `(principal, nonce)`
+#[derive(Debug)]
+pub enum OrderAttempt {
+	/// Dispatch the order. `previous` is the order an earlier attempt
+	/// dispatched without learning the outcome; if the matching engine knows
+	/// it, that attempt went through and its result is the answer.
+	Dispatch {
+		guard: ReplayGuard,
+		previous: Option<RecordedOrder>,
+	},
+	/// An earlier attempt dispatched this order and no retry is left:
+	/// answer with the order's current state instead of dispatching again
+	Replay(RecordedOrder),
+}
+
 #[derive(Debug, Clone)]
 pub struct ReplayGuard {
 	entry: Arc<ReplayEntry>,
 }
 
 impl ReplayGuard {
+	/// Remember the order dispatched under this nonce, so that duplicates
+	/// are answered with it
+	///
+	/// Call this once the order was accepted or its outcome is unknown, not
+	/// when it was rejected.
+	pub fn record_order(&self, order: RecordedOrder) {
+		*self.entry.order.lock().unwrap() = Some(order);
+	}
+
 	pub fn finish(self, outcome: ReplayOutcome) {
 		match outcome {
 			ReplayOutcome::RetryableFailure => {
@@ -313,6 +336,18 @@ impl ReplayCache {
 		timestamp: u64,
 		nonce: &str,
 	) -> Result<ReplayGuard, AdmissionError> {
+		match self.begin_order(principal_id, timestamp, nonce)? {
+			OrderAttempt::Dispatch { guard, .. } => Ok(guard),
+			OrderAttempt::Replay(_) => Err(AdmissionError::ReplayDetected),
+		}
+	}
+
+	fn begin_order(
+		&self,
+		principal_id: &str,
+		timestamp: u64,
+		nonce: &str,
+	) -> Result<OrderAttempt, AdmissionError> {
 		self.check_timestamp(timestamp)?;
 		let key = (principal_id.to_string(), nonce.to_string());
 		let token = self.next_token.fetch_add(1, Ordering::Relaxed);
@@ -323,24 +358,35 @@ impl ReplayCache {
 
 		// If we created the entry, allow immediately.
 		if entry.token.load(Ordering::Relaxed) == token {
-			return Ok(ReplayGuard { entry });
+			return Ok(OrderAttempt::Dispatch {
+				guard: ReplayGuard { entry },
+				previous: None,
+			});
 		}
 
 		let state = ReplayState::from_u8(entry.state.load(Ordering::SeqCst));
+		let recorded = entry.order.lock().unwrap().clone();
 
 		match state {
 			ReplayState::InFlight => Err(AdmissionError::ReplayDetected),
-			ReplayState::Terminal => Err(AdmissionError::ReplayDetected),
+			ReplayState::Terminal => recorded
+				.map(OrderAttempt::Replay)
+				.ok_or(AdmissionError::ReplayDetected),
 			ReplayState::RetryableReady => {
 				// Allow a single retry after a retryable failure.
 				let already_used = entry.retry_consumed.swap(true, Ordering::SeqCst);
 				if already_used {
-					Err(AdmissionError::ReplayDetected)
+					recorded
+						.map(OrderAttempt::Replay)
+						.ok_or(AdmissionError::ReplayDetected)
 				} else {
 					entry
 						.state
 						.store(ReplayState::InFlight as u8, Ordering::SeqCst);
-					Ok(ReplayGuard { entry })
+					Ok(OrderAttempt::Dispatch {
+						guard: ReplayGuard { entry },
+						previous: recorded,
+					})
 				}
 			}
 		}
@@ -349,23 +395,23 @@ impl ReplayCache {
 
 static REPLAY_CACHE: std::sync::OnceLock<ReplayCache> = std::sync::OnceLock::new();
 
-fn get_admission_controller() -> &'static AdmissionController {
-	ADMISSION_CONTROLLER.get_or_init(|| {
-		let rps = std::env::var("GATEWAY_RATE_LIMIT_RPS")
-			.map(|v| {
-				v.parse::<u32>()
-					.expect("GATEWAY_RATE_LIMIT_RPS must be a valid u32")
-			})
-			.unwrap_or(DEFAULT_RATE_LIMIT_RPS);
-		let burst = std::env::var("GATEWAY_RATE_LIMIT_BURST")
-			.map(|v| {
-				v.parse::<u32>()
-					.expect("GATEWAY_RATE_LIMIT_BURST must be a valid u32")
-			})
-			.unwrap_or(DEFAULT_RATE_LIMIT_BURST);
+/// Configure the per-principal rate limit
+///
+/// Must run before the first request is admitted; until then, and if it
+/// never runs, the default limit applies. Returns false if the limit was
+/// already in effect.
+pub fn init_rate_limit(rate_limit: &RateLimitConfig) -> bool {
+	ADMISSION_CONTROLLER
+		.set(AdmissionController::new(
+			rate_limit.requests_per_second,
+			rate_limit.burst,
+		))
+		.is_ok()
+}
 
-		AdmissionController::new(rps, burst)
-	})
+fn get_admission_controller() -> &'static AdmissionController {
+	ADMISSION_CONTROLLER
+		.get_or_init(|| AdmissionController::new(DEFAULT_RATE_LIMIT_RPS, DEFAULT_RATE_LIMIT_BURST))
 }
 
 fn get_replay_cache() -> &'static ReplayCache {
@@ -394,11 +440,46 @@ fn get_replay_cache() -> &'static ReplayCache {
 	})
 }
 
+/// Order that passed admission, with price and size converted to market units
+#[derive(Debug, Clone)]
+pub struct AdmittedOrder {
+	pub market: String,
+	pub side: Side,
+	pub price: Option<Price>,
+	pub size: Quantity,
+	pub client_order_id: Option<String>,
+}
+
+/// Longest accepted client order ID, in bytes
+pub const MAX_CLIENT_ORDER_ID_LEN: usize = 64;
+
+/// Check that a client order ID is 1 to [`MAX_CLIENT_ORDER_ID_LEN`] printable
+/// ASCII characters without spaces
+///
+/// This keeps IDs safe to carry in URL paths, FIX fields and signed messages.
+pub fn validate_client_order_id(client_order_id: &str) -> Result<(), AdmissionError> {
+	if client_order_id.is_empty()
+		|| client_order_id.len() > MAX_CLIENT_ORDER_ID_LEN
+		|| !client_order_id.bytes().all(|b| b.is_ascii_graphic())
+	{
+		return Err(AdmissionError::InvalidOrder(format!(
+			"Client order ID must be 1 to {} printable ASCII characters",
+			MAX_CLIENT_ORDER_ID_LEN
+		)));
+	}
+	Ok(())
+}
+
 /// Validate and admit an order request
 ///
 /// This function performs basic syntactic validation and protocol-level
-/// admission checks before forwarding to the matching engine.
-pub fn validate_and_admit(request: &PlaceOrderRequest) -> Result<(), AdmissionError> {
+/// admission checks before forwarding to the matching engine. Decimal
+/// prices and sizes are converted exactly using the market's `spec`; a
+/// market without a spec is not tradable through this gateway.
+pub fn validate_and_admit(
+	request: &PlaceOrderRequest,
+	spec: Option<&MarketSpec>,
+) -> Result<AdmittedOrder, AdmissionError> {
 	// Validate market identifier
 	if request.market.is_empty() {
 		return Err(AdmissionError::InvalidOrder(
@@ -406,29 +487,45 @@ pub fn validate_and_admit(request: &PlaceOrderRequest) -> Result<(), AdmissionEr
 		));
 	}
 
+	let spec = spec.ok_or_else(|| AdmissionError::MarketNotAvailable(request.market.clone()))?;
+
 	// Validate size
-	if request.size == 0 {
+	let size = spec
+		.quantity(&request.size)
+		.map_err(|e| AdmissionError::InvalidOrder(format!("Invalid size: {}", e)))?;
+	if size.is_zero() {
 		return Err(AdmissionError::InvalidOrder(
 			"Order size must be greater than zero".to_string(),
 		));
 	}
 
 	// Validate price for limit orders
+	let price = request
+		.price
+		.as_ref()
+		.map(|price| spec.price(price))
+		.transpose()
+		.map_err(|e| AdmissionError::InvalidOrder(format!("Invalid price: {}", e)))?;
 	if matches!(request.order_type, OrderType::Limit) {
-		if request.price.is_none() {
-			return Err(AdmissionError::InvalidOrder(
-				"Limit orders require a price".to_string(),
-			));
-		}
-		if let Some(price) = request.price
-			&& price == 0
-		{
-			return Err(AdmissionError::InvalidOrder(
-				"Price must be greater than zero".to_string(),
-			));
+		match price {
+			None => {
+				return Err(AdmissionError::InvalidOrder(
+					"Limit orders require a price".to_string(),
+				));
+			}
+			Some(price) if price.is_zero() => {
+				return Err(AdmissionError::InvalidOrder(
+					"Price must be greater than zero".to_string(),
+				));
+			}
+			Some(_) => {}
 		}
 	}
 
+	if let Some(client_order_id) = &request.client_order_id {
+		validate_client_order_id(client_order_id)?;
+	}
+
 	// Check market availability
 	if !get_admission_controller().is_market_available(&request.market) {
 		return Err(AdmissionError::MarketNotAvailable(request.market.clone()));
@@ -437,7 +534,13 @@ pub fn validate_and_admit(request: &PlaceOrderRequest) -> Result<(), AdmissionEr
 	// Rate limiting is checked per principal in the handler
 	// Balance checking would be async and done in handler if needed
 
-	Ok(())
+	Ok(AdmittedOrder {
+		market: request.market.clone(),
+		side: request.side,
+		price,
+		size,
+		client_order_id: request.client_order_id.clone(),
+	})
 }
 
 /// Check rate limit for a principal (public key)
@@ -448,6 +551,13 @@ pub fn check_rate_limit(principal: &Principal) -> Result<(), AdmissionError> {
 	get_admission_controller().check_rate_limit(principal)
 }
 
+/// Mark a market as available or not for new orders
+///
+/// The health prober calls this as its matching engine comes and goes.
+pub fn set_market_available(market: &str, available: bool) {
+	get_admission_controller().set_market_available(market, available)
+}
+
 /// Begin replay tracking for `(principal, nonce)`.
 ///
 /// This records the request as in-flight. If the nonce is already in-flight or
@@ -463,21 +573,24 @@ pub fn begin_replay(
 	cache.begin(&principal.id(), timestamp, nonce)
 }
 
-/// Check balance (async)
+/// Begin replay tracking for an order submitted under `(principal, nonce)`
 ///
-/// Note: This is intentionally minimal. Balance checking is business logic
-/// that should be handled by the application layer or settlement service.
-#[allow(dead_code)]
-pub async fn check_balance(market: &str, required: u64) -> Result<(), AdmissionError> {
-	get_admission_controller()
-		.check_balance(market, required)
-		.await
+/// Like [`begin_replay`], except that a duplicate of an order that was
+/// accepted, or whose outcome is unknown, is not rejected: it is answered
+/// with the recorded order, or retried once under the same order ID.
+pub fn begin_order_replay(
+	principal: &Principal,
+	timestamp: u64,
+	nonce: &str,
+) -> Result<OrderAttempt, AdmissionError> {
+	get_replay_cache().begin_order(&principal.id(), timestamp, nonce)
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
 	use crate::auth::{Principal, SignatureAlgorithm};
+	use anvil_sdk::Amount;
 
 	fn principal() -> Principal {
 		Principal::new(vec![0u8; 32], SignatureAlgorithm::Ed25519)
@@ -527,4 +640,119 @@ mod tests {
 			Err(AdmissionError::ReplayDetected)
 		));
 	}
+
+	#[test]
+	fn replay_answers_duplicates_with_the_recorded_order() {
+		let cache = ReplayCache::new(30, 60, 100);
+		let principal = principal();
+		let recorded = RecordedOrder {
+			market: "BTC-USDT".to_string(),
+			order_id: "o1".to_string(),
+		};
+
+		// Accepted: duplicates are answered with the order
+		let Ok(OrderAttempt::Dispatch { guard, .. }) =
+			cache.begin_order(&principal.id(), now_secs(), "nonce-3")
+		else {
+			panic!("first attempt allowed");
+		};
+		guard.record_order(recorded.clone());
+		guard.finish(ReplayOutcome::Terminal);
+		assert!(matches!(
+			cache.begin_order(&principal.id(), now_secs(), "nonce-3"),
+			Ok(OrderAttempt::Replay(order)) if order == recorded
+		));
+		// Other requests reusing the nonce are still rejected
+		assert!(cache.begin(&principal.id(), now_secs(), "nonce-3").is_err());
+
+		// Unknown outcome: the retry learns the earlier order, then duplicates
+		// are answered with it
+		let Ok(OrderAttempt::Dispatch { guard, .. }) =
+			cache.begin_order(&principal.id(), now_secs(), "nonce-4")
+		else {
+			panic!("first attempt allowed");
+		};
+		guard.record_order(recorded.clone());
+		guard.finish(ReplayOutcome::RetryableFailure);
+		let Ok(OrderAttempt::Dispatch { guard, previous }) =
+			cache.begin_order(&principal.id(), now_secs(), "nonce-4")
+		else {
+			panic!("retry allowed");
+		};
+		assert_eq!(previous, Some(recorded.clone()));
+		guard.finish(ReplayOutcome::RetryableFailure);
+		assert!(matches!(
+			cache.begin_order(&principal.id(), now_secs(), "nonce-4"),
+			Ok(OrderAttempt::Replay(_))
+		));
+	}
+
+	fn order(price: Option<Amount>, size: Amount) -> PlaceOrderRequest {
+		PlaceOrderRequest {
+			market: "BTC-USDT".to_string(),
+			side: Side::Buy,
+			order_type: OrderType::Limit,
+			price,
+			size,
+			client_order_id: None,
+		}
+	}
+
+	#[test]
+	fn admission_converts_decimal_strings_exactly() {
+		let spec = MarketSpec::new("BTC-USDT", 2, 8).unwrap();
+
+		let admitted =
+			validate_and_admit(&order(Some("50000.25".into()), "0.5".into()), Some(&spec))
+				.expect("decimal order admitted");
+		assert_eq!(admitted.price.map(|p| p.units()), Some(5_000_025));
+		assert_eq!(admitted.size.units(), 50_000_000);
+
+		let admitted = validate_and_admit(&order(Some(Amount::Units(7)), 3.into()), Some(&spec))
+			.expect("raw-unit order admitted");
+		assert_eq!(admitted.price.map(|p| p.units()), Some(7));
+		assert_eq!(admitted.size.units(), 3);
+	}
+
+	#[test]
+	fn admission_rejects_lossy_decimals_and_unknown_markets() {
+		let spec = MarketSpec::new("BTC-USDT", 2, 8).unwrap();
+
+		assert!(matches!(
+			validate_and_admit(&order(Some("50000.001".into()), "1".into()), Some(&spec)),
+			Err(AdmissionError::InvalidOrder(_))
+		));
+		assert!(matches!(
+			validate_and_admit(&order(Some("1".into()), "0.000".into()), Some(&spec)),
+			Err(AdmissionError::InvalidOrder(_))
+		));
+		assert!(matches!(
+			validate_and_admit(&order(Some("1".into()), "1".into()), None),
+			Err(AdmissionError::MarketNotAvailable(_))
+		));
+	}
+
+	#[test]
+	fn admission_checks_client_order_ids() {
+		let spec = MarketSpec::new("BTC-USDT", 2, 8).unwrap();
+		let with_id = |client_order_id: &str| PlaceOrderRequest {
+			client_order_id: Some(client_order_id.to_string()),
+			..order(Some("1".into()), "1".into())
+		};
+
+		let admitted = validate_and_admit(&with_id("my-order_1"), Some(&spec)).unwrap();
+		assert_eq!(admitted.client_order_id.as_deref(), Some("my-order_1"));
+
+		for invalid in [
+			"",
+			"has space",
+			"ü",
+			&"x".repeat(MAX_CLIENT_ORDER_ID_LEN + 1),
+		] {
+			assert!(matches!(
+				validate_and_admit(&with_id(invalid), Some(&spec)),
+				Err(AdmissionError::InvalidOrder(_))
+			));
+		}
+	}
 }
@@ -89,6 +89,8 @@ pub enum AuthError {
 	UnsupportedAlgorithm(String),
 	#[error("Signature format error: {0}")]
 	SignatureFormatError(String),
+	#[error("Not logged in")]
+	NotLoggedIn,
 }
 
 /// Signature algorithm type
@@ -214,14 +216,11 @@ impl<'a> AuthContext<'a> {
 
 	/// Create a new AuthContext for gRPC requests
 	///
-	/// This will be used when implementing gRPC handlers in the future.
-	///
 	/// Authentication materials must be in gRPC metadata:
 	/// - Public key: `public-key` metadata key
 	/// - Signature: `signature` metadata key
 	/// - Timestamp: `timestamp` metadata key (unix seconds)
 	/// - Nonce: `nonce` metadata key (opaque string)
-	#[allow(dead_code)]
 	pub fn from_grpc(metadata: &'a tonic::metadata::MetadataMap) -> Self {
 		Self {
 			http_headers: None,
@@ -497,6 +496,17 @@ pub fn authenticate_order(
 	principal: &Principal,
 	timestamp: u64,
 	nonce: &str,
+) -> Result<(), AuthError> {
+	// Serialize payload for signing (business data only) + anti-replay metadata.
+	let message = serialize_for_signing(payload, timestamp, nonce);
+	verify_signature(&message, signature, principal)
+}
+
+/// Verify a signature over a prepared signing message
+fn verify_signature(
+	message: &[u8],
+	signature: &str,
+	principal: &Principal,
 ) -> Result<(), AuthError> {
 	if signature.is_empty() {
 		return Err(AuthError::MissingSignature);
@@ -505,10 +515,10 @@ pub fn authenticate_order(
 	// Verify signature based on algorithm
 	match principal.scheme {
 		SignatureAlgorithm::Ed25519 => {
-			verify_ed25519_signature(payload, signature, &principal.public_key, timestamp, nonce)
+			verify_ed25519_signature(message, signature, &principal.public_key)
 		}
 		SignatureAlgorithm::Ecdsa => {
-			verify_ecdsa_signature(payload, signature, &principal.public_key, timestamp, nonce)
+			verify_ecdsa_signature(message, signature, &principal.public_key)
 		}
 	}
 }
@@ -555,19 +565,72 @@ pub fn authenticate_with_provider(
 	})
 }
 
+/// Authenticate a batch of orders signed once with [`batch_signing_message`]
+pub fn authenticate_batch(
+	ctx: &AuthContext,
+	orders: &[PlaceOrderRequest],
+	provider: &dyn AuthProvider,
+) -> Result<AuthenticatedPrincipal, AuthError> {
+	let public_key = provider.extract_public_key(ctx)?;
+	let signature = provider.extract_signature(ctx)?;
+	let algorithm = provider.detect_algorithm(ctx, &public_key, &signature)?;
+	let timestamp = extract_timestamp(ctx)?;
+	let nonce = extract_nonce(ctx)?;
+	let principal = Principal::new(public_key, algorithm);
+
+	verify_signature(
+		&batch_signing_message(orders, timestamp, &nonce),
+		&signature,
+		&principal,
+	)?;
+
+	Ok(AuthenticatedPrincipal {
+		principal,
+		timestamp,
+		nonce,
+	})
+}
+
+/// Authenticate a signed read request (e.g. `GET /api/v1/fills`)
+///
+/// Reads have no body, so the signature covers the method and the request
+/// path including its query string, as built by
+/// [`anvil_sdk::signing::query_signing_message`].
+pub fn authenticate_query(
+	ctx: &AuthContext,
+	method: &str,
+	path_and_query: &str,
+	provider: &dyn AuthProvider,
+) -> Result<AuthenticatedPrincipal, AuthError> {
+	let public_key = provider.extract_public_key(ctx)?;
+	let signature = provider.extract_signature(ctx)?;
+	let algorithm = provider.detect_algorithm(ctx, &public_key, &signature)?;
+	let timestamp = extract_timestamp(ctx)?;
+	let nonce = extract_nonce(ctx)?;
+	let principal = Principal::new(public_key, algorithm);
+
+	let message =
+		anvil_sdk::signing::query_signing_message(method, path_and_query, timestamp, &nonce);
+	verify_signature(&message, &signature, &principal)?;
+
+	Ok(AuthenticatedPrincipal {
+		principal,
+		timestamp,
+		nonce,
+	})
+}
+
 /// Verify Ed25519 signature
 ///
 /// # Arguments
 ///
-/// * `payload` - Order payload (business data only, no authentication materials)
+/// * `message` - Canonical signing message (business data and anti-replay metadata)
 /// * `signature` - Signature extracted from request metadata (hex-encoded)
 /// * `public_key` - Public key bytes for verification
 fn verify_ed25519_signature(
-	payload: &PlaceOrderRequest,
+	message: &[u8],
 	signature: &str,
 	public_key: &[u8],
-	timestamp: u64,
-	nonce: &str,
 ) -> Result<(), AuthError> {
 	// Parse verifying key
 	let verifying_key = VerifyingKey::from_bytes(
@@ -586,12 +649,9 @@ fn verify_ed25519_signature(
 			AuthError::SignatureFormatError("Invalid signature length".to_string())
 		})?);
 
-	// Serialize payload for signing (business data only) + anti-replay metadata.
-	let message = serialize_for_signing(payload, timestamp, nonce);
-
 	// Verify signature
 	verifying_key
-		.verify(&message, &signature)
+		.verify(message, &signature)
 		.map_err(|e| AuthError::InvalidSignature(format!("Ed25519 verification failed: {}", e)))?;
 
 	Ok(())
@@ -601,15 +661,13 @@ fn verify_ed25519_signature(
 ///
 /// # Arguments
 ///
-/// * `payload` - Order payload (business data only, no authentication materials)
+/// * `message` - Canonical signing message (business data and anti-replay metadata)
 /// * `signature` - Signature extracted from request metadata (hex-encoded)
 /// * `public_key` - Public key bytes for verification
 fn verify_ecdsa_signature(
-	payload: &PlaceOrderRequest,
+	message: &[u8],
 	signature: &str,
 	public_key: &[u8],
-	timestamp: u64,
-	nonce: &str,
 ) -> Result<(), AuthError> {
 	use k256::ecdsa::signature::Verifier;
 
@@ -635,11 +693,8 @@ fn verify_ecdsa_signature(
 			.map_err(|e| AuthError::SignatureFormatError(format!("Invalid DER signature: {}", e)))?
 	};
 
-	// Serialize payload for signing (business data only) + anti-replay metadata.
-	let message = serialize_for_signing(payload, timestamp, nonce);
-
 	// Hash message
-	let message_hash = Sha256::digest(&message);
+	let message_hash = Sha256::digest(message);
 
 	// Verify signature
 	verifying_key
@@ -665,6 +720,29 @@ fn serialize_for_signing(request: &PlaceOrderRequest, timestamp: u64, nonce: &st
 	//
 	// This must match the client's signing format exactly.
 	let mut message = Vec::new();
+	write_order_signing_bytes(request, &mut message);
+	write_replay_metadata(timestamp, nonce, &mut message);
+	message
+}
+
+/// Canonical signing message of a batch of orders
+///
+/// Each order's business data, as signed for a single order, is prefixed
+/// with its length as a big-endian u32 so that order boundaries are
+/// unambiguous; the anti-replay metadata follows once for the whole batch.
+pub fn batch_signing_message(orders: &[PlaceOrderRequest], timestamp: u64, nonce: &str) -> Vec<u8> {
+	let mut message = Vec::new();
+	for order in orders {
+		let mut bytes = Vec::new();
+		write_order_signing_bytes(order, &mut bytes);
+		message.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+		message.extend_from_slice(&bytes);
+	}
+	write_replay_metadata(timestamp, nonce, &mut message);
+	message
+}
+
+fn write_order_signing_bytes(request: &PlaceOrderRequest, message: &mut Vec<u8>) {
 	message.extend_from_slice(request.market.as_bytes());
 	message.push(0);
 	match request.side {
@@ -675,19 +753,19 @@ fn serialize_for_signing(request: &PlaceOrderRequest, timestamp: u64, nonce: &st
 		anvil_sdk::types::OrderType::Limit => message.push(0),
 		anvil_sdk::types::OrderType::Market => message.push(1),
 	}
-	if let Some(price) = request.price {
-		message.extend_from_slice(&price.to_be_bytes());
+	if let Some(ref price) = request.price {
+		price.write_signing_bytes(message);
 	}
-	message.extend_from_slice(&request.size.to_be_bytes());
+	request.size.write_signing_bytes(message);
 	if let Some(ref client_order_id) = request.client_order_id {
 		message.extend_from_slice(client_order_id.as_bytes());
 	}
+}
 
+fn write_replay_metadata(timestamp: u64, nonce: &str, message: &mut Vec<u8>) {
 	// Separator before metadata fields
 	message.push(0);
 	message.extend_from_slice(&timestamp.to_be_bytes());
 	message.push(0);
 	message.extend_from_slice(nonce.as_bytes());
-
-	message
 }
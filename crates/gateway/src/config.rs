@@ -12,9 +12,16 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{collections::HashMap, env, net::SocketAddr};
-
+use std::{
+	collections::{HashMap, HashSet},
+	env, fs,
+	net::SocketAddr,
+	path::{Path, PathBuf},
+};
+
+use anvil_sdk::MarketSpec;
 use anyhow::{Context, Result};
+use config::FileFormat;
 use serde::{Deserialize, Serialize};
 
 // Logging configuration constants
@@ -31,6 +38,15 @@ pub const DEFAULT_LOG_TO_CONSOLE: bool = false;
 /// Default HTTP server bind address (can be overridden by GATEWAY_BIND_ADDR environment variable)
 pub const DEFAULT_BIND_ADDR: &str = "0.0.0.0:8080";
 
+/// Default gRPC order entry bind address (can be overridden by GATEWAY_GRPC_BIND_ADDR environment variable)
+pub const DEFAULT_GRPC_BIND_ADDR: &str = "0.0.0.0:8081";
+
+/// Default FIX CompID of the gateway (can be overridden by GATEWAY_FIX_COMP_ID environment variable)
+pub const DEFAULT_FIX_COMP_ID: &str = "ANVIL";
+
+/// Default directory of the FIX session stores (can be overridden by GATEWAY_FIX_STORE_DIR environment variable)
+pub const DEFAULT_FIX_STORE_DIR: &str = "data/fix";
+
 // Admission / anti-abuse configuration constants
 /// Default requests-per-second limit per principal (can be overridden by GATEWAY_RATE_LIMIT_RPS)
 pub const DEFAULT_RATE_LIMIT_RPS: u32 = 100;
@@ -54,21 +70,84 @@ pub const DEFAULT_MAX_BODY_BYTES: usize = 64 * 1024;
 /// Default matching-engine RPC timeout in milliseconds (can be overridden by GATEWAY_MATCHING_RPC_TIMEOUT_MS)
 pub const DEFAULT_MATCHING_RPC_TIMEOUT_MS: u64 = 1_500;
 
+/// Default maximum orders or cancels per batch request (can be overridden by GATEWAY_MAX_BATCH_ORDERS)
+pub const DEFAULT_MAX_BATCH_ORDERS: usize = 20;
+
 /// Default dispatch queue capacity for the matching dispatcher
 pub const DEFAULT_DISPATCH_QUEUE_CAPACITY: usize = 1_024;
 
 /// Default dispatch queue timeout (ms) while waiting in bounded queue
 pub const DEFAULT_DISPATCH_QUEUE_TIMEOUT_MS: u64 = 1_000;
 
+/// Default interval between checks of the configuration file for changes (ms) (can be overridden by GATEWAY_CONFIG_RELOAD_INTERVAL_MS)
+pub const DEFAULT_CONFIG_RELOAD_INTERVAL_MS: u64 = 1_000;
+
+/// Default interval between health probes of each matching engine (ms) (can be overridden by GATEWAY_HEALTH_CHECK_INTERVAL_MS)
+pub const DEFAULT_HEALTH_CHECK_INTERVAL_MS: u64 = 1_000;
+
+/// Default health probe timeout (ms) (can be overridden by GATEWAY_HEALTH_CHECK_TIMEOUT_MS)
+pub const DEFAULT_HEALTH_CHECK_TIMEOUT_MS: u64 = 500;
+
+/// Default consecutive failed probes that open a matching engine's circuit (can be overridden by GATEWAY_HEALTH_FAILURE_THRESHOLD)
+pub const DEFAULT_HEALTH_FAILURE_THRESHOLD: u32 = 3;
+
+/// Default time an open circuit refuses requests before a half-open probe (ms) (can be overridden by GATEWAY_CIRCUIT_OPEN_MS)
+pub const DEFAULT_CIRCUIT_OPEN_MS: u64 = 5_000;
+
+/// Default for failing markets over to caught-up standby engines (can be overridden by GATEWAY_FAILOVER_ENABLED)
+pub const DEFAULT_FAILOVER_ENABLED: bool = true;
+
+/// Default interval between WebSocket server pings (ms) (can be overridden by GATEWAY_WS_HEARTBEAT_INTERVAL_MS)
+pub const DEFAULT_WS_HEARTBEAT_INTERVAL_MS: u64 = 15_000;
+
+/// Default WebSocket idle timeout (ms) before the server closes a silent connection (can be overridden by GATEWAY_WS_CLIENT_TIMEOUT_MS)
+pub const DEFAULT_WS_CLIENT_TIMEOUT_MS: u64 = 45_000;
+
+/// Default sustained messages per second per WebSocket connection (can be overridden by GATEWAY_WS_MESSAGES_PER_SECOND)
+pub const DEFAULT_WS_MESSAGES_PER_SECOND: u32 = 50;
+
+/// Default message burst per WebSocket connection (can be overridden by GATEWAY_WS_MESSAGE_BURST)
+pub const DEFAULT_WS_MESSAGE_BURST: u32 = 100;
+
+/// Default market data messages buffered per WebSocket connection before it is dropped as a slow consumer (can be overridden by GATEWAY_WS_FEED_BUFFER)
+pub const DEFAULT_WS_FEED_BUFFER: usize = 1_024;
+
 #[derive(Debug, Clone)]
 pub struct GatewayRuntimeConfig {
 	pub bind_addr: SocketAddr,
+	pub grpc_bind_addr: SocketAddr,
+	/// FIX acceptor address; the acceptor is disabled when unset
+	pub fix_bind_addr: Option<SocketAddr>,
+	pub fix_comp_id: String,
+	/// Counterparty CompID -> hex-encoded public key it trades as
+	pub fix_sessions: HashMap<String, String>,
+	pub fix_store_dir: PathBuf,
 	pub workers: usize,
 	pub max_body_bytes: usize,
-	pub matching_engines: HashMap<String, String>,
+	/// Orders or cancels per batch request, at most the matching engine's
+	/// own batch limit
+	pub max_batch_orders: usize,
+	/// Gateway configuration file, watched for market changes
+	pub config_file: Option<PathBuf>,
+	pub config_reload_interval_ms: u64,
+	/// Markets served at startup
+	pub markets: MarketRoutes,
+	pub rate_limit: RateLimitConfig,
 	pub dispatch_queue_capacity: usize,
 	pub dispatch_queue_timeout_ms: u64,
 	pub matching_rpc_timeout_ms: u64,
+	pub health_check_interval_ms: u64,
+	pub health_check_timeout_ms: u64,
+	pub health_failure_threshold: u32,
+	pub circuit_open_ms: u64,
+	/// Whether markets fail over from an unhealthy engine to a standby that
+	/// has caught up
+	pub failover_enabled: bool,
+	pub ws_heartbeat_interval_ms: u64,
+	pub ws_client_timeout_ms: u64,
+	pub ws_messages_per_second: u32,
+	pub ws_message_burst: u32,
+	pub ws_feed_buffer: usize,
 }
 
 impl GatewayRuntimeConfig {
@@ -81,6 +160,31 @@ impl GatewayRuntimeConfig {
 			.parse()
 			.with_context(|| format!("Invalid bind address: {}", bind_addr_str))?;
 
+		let grpc_bind_addr_str = env::var("GATEWAY_GRPC_BIND_ADDR")
+			.unwrap_or_else(|_| DEFAULT_GRPC_BIND_ADDR.to_string());
+		let grpc_bind_addr = grpc_bind_addr_str
+			.parse()
+			.with_context(|| format!("Invalid gRPC bind address: {}", grpc_bind_addr_str))?;
+
+		let fix_bind_addr = env::var("GATEWAY_FIX_BIND_ADDR")
+			.ok()
+			.map(|addr| {
+				addr.parse()
+					.with_context(|| format!("Invalid FIX bind address: {}", addr))
+			})
+			.transpose()?;
+
+		let fix_comp_id =
+			env::var("GATEWAY_FIX_COMP_ID").unwrap_or_else(|_| DEFAULT_FIX_COMP_ID.to_string());
+
+		let fix_sessions = env::var("GATEWAY_FIX_SESSIONS")
+			.map(|sessions| parse_fix_sessions(&sessions))
+			.unwrap_or_else(|_| Ok(HashMap::new()))?;
+
+		let fix_store_dir = env::var("GATEWAY_FIX_STORE_DIR")
+			.unwrap_or_else(|_| DEFAULT_FIX_STORE_DIR.to_string())
+			.into();
+
 		let workers = env::var("GATEWAY_WORKERS")
 			.ok()
 			.and_then(|w| w.parse().ok())
@@ -91,6 +195,17 @@ impl GatewayRuntimeConfig {
 			.and_then(|v| v.parse().ok())
 			.unwrap_or(DEFAULT_MAX_BODY_BYTES);
 
+		let max_batch_orders = env::var("GATEWAY_MAX_BATCH_ORDERS")
+			.ok()
+			.and_then(|v| v.parse().ok())
+			.unwrap_or(DEFAULT_MAX_BATCH_ORDERS);
+		if max_batch_orders == 0 || max_batch_orders > anvil_matching::server::MAX_BATCH_SIZE {
+			anyhow::bail!(
+				"GATEWAY_MAX_BATCH_ORDERS must be between 1 and {}",
+				anvil_matching::server::MAX_BATCH_SIZE
+			);
+		}
+
 		let matching_rpc_timeout_ms = env::var("GATEWAY_MATCHING_RPC_TIMEOUT_MS")
 			.ok()
 			.and_then(|v| v.parse().ok())
@@ -106,100 +221,383 @@ impl GatewayRuntimeConfig {
 			.and_then(|v| v.parse().ok())
 			.unwrap_or(DEFAULT_DISPATCH_QUEUE_TIMEOUT_MS);
 
-		let matching_engines = default_matching_engines();
+		let health_check_interval_ms = env::var("GATEWAY_HEALTH_CHECK_INTERVAL_MS")
+			.ok()
+			.and_then(|v| v.parse().ok())
+			.unwrap_or(DEFAULT_HEALTH_CHECK_INTERVAL_MS);
+
+		let health_check_timeout_ms = env::var("GATEWAY_HEALTH_CHECK_TIMEOUT_MS")
+			.ok()
+			.and_then(|v| v.parse().ok())
+			.unwrap_or(DEFAULT_HEALTH_CHECK_TIMEOUT_MS);
+
+		let health_failure_threshold = env::var("GATEWAY_HEALTH_FAILURE_THRESHOLD")
+			.ok()
+			.and_then(|v| v.parse().ok())
+			.filter(|threshold| *threshold > 0)
+			.unwrap_or(DEFAULT_HEALTH_FAILURE_THRESHOLD);
+
+		let circuit_open_ms = env::var("GATEWAY_CIRCUIT_OPEN_MS")
+			.ok()
+			.and_then(|v| v.parse().ok())
+			.unwrap_or(DEFAULT_CIRCUIT_OPEN_MS);
+
+		let failover_enabled = env::var("GATEWAY_FAILOVER_ENABLED")
+			.map(|v| v == "true" || v == "1" || v == "yes")
+			.unwrap_or(DEFAULT_FAILOVER_ENABLED);
+
+		let ws_heartbeat_interval_ms = env::var("GATEWAY_WS_HEARTBEAT_INTERVAL_MS")
+			.ok()
+			.and_then(|v| v.parse().ok())
+			.unwrap_or(DEFAULT_WS_HEARTBEAT_INTERVAL_MS);
+
+		let ws_client_timeout_ms = env::var("GATEWAY_WS_CLIENT_TIMEOUT_MS")
+			.ok()
+			.and_then(|v| v.parse().ok())
+			.unwrap_or(DEFAULT_WS_CLIENT_TIMEOUT_MS);
+
+		let ws_messages_per_second = env::var("GATEWAY_WS_MESSAGES_PER_SECOND")
+			.ok()
+			.and_then(|v| v.parse().ok())
+			.unwrap_or(DEFAULT_WS_MESSAGES_PER_SECOND);
+
+		let ws_message_burst = env::var("GATEWAY_WS_MESSAGE_BURST")
+			.ok()
+			.and_then(|v| v.parse().ok())
+			.unwrap_or(DEFAULT_WS_MESSAGE_BURST);
+
+		let ws_feed_buffer = env::var("GATEWAY_WS_FEED_BUFFER")
+			.ok()
+			.and_then(|v| v.parse().ok())
+			.unwrap_or(DEFAULT_WS_FEED_BUFFER);
+
+		let config_file = env::var("GATEWAY_CONFIG_FILE").ok().map(PathBuf::from);
+		let gateway_config = GatewayConfig::load(config_file.as_deref())
+			.context("Failed to load gateway configuration")?;
+		let markets = MarketRoutes::from_config(&gateway_config)?;
+
+		let config_reload_interval_ms = env::var("GATEWAY_CONFIG_RELOAD_INTERVAL_MS")
+			.ok()
+			.and_then(|v| v.parse().ok())
+			.unwrap_or(DEFAULT_CONFIG_RELOAD_INTERVAL_MS);
 
 		Ok(Self {
 			bind_addr,
+			grpc_bind_addr,
+			fix_bind_addr,
+			fix_comp_id,
+			fix_sessions,
+			fix_store_dir,
 			workers,
 			max_body_bytes,
-			matching_engines,
+			max_batch_orders,
+			config_file,
+			config_reload_interval_ms,
+			markets,
+			rate_limit: gateway_config.rate_limit,
 			dispatch_queue_capacity,
 			dispatch_queue_timeout_ms,
 			matching_rpc_timeout_ms,
+			health_check_interval_ms,
+			health_check_timeout_ms,
+			health_failure_threshold,
+			circuit_open_ms,
+			failover_enabled,
+			ws_heartbeat_interval_ms,
+			ws_client_timeout_ms,
+			ws_messages_per_second,
+			ws_message_burst,
+			ws_feed_buffer,
 		})
 	}
 }
 
-fn default_matching_engines() -> HashMap<String, String> {
-	let mut map = HashMap::new();
-	// TODO: Load from configuration file or service discovery.
-	map.insert("BTC-USDT".to_string(), "http://localhost:50051".to_string());
-	map
+/// Parse `COMPID=hexkey,COMPID=hexkey`
+fn parse_fix_sessions(value: &str) -> Result<HashMap<String, String>> {
+	value
+		.split(',')
+		.map(str::trim)
+		.filter(|entry| !entry.is_empty())
+		.map(|entry| {
+			let (comp_id, public_key) = entry
+				.split_once('=')
+				.with_context(|| format!("Invalid FIX session entry: {}", entry))?;
+			hex::decode(public_key.trim())
+				.with_context(|| format!("Invalid public key for FIX session {}", comp_id))?;
+			Ok((comp_id.trim().to_string(), public_key.trim().to_string()))
+		})
+		.collect()
+}
+
+/// Market routing and parameters, as served by the dispatcher
+///
+/// Built from a [`GatewayConfig`] and replaced as a whole when the
+/// configuration file changes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MarketRoutes {
+	/// Market -> matching engine endpoints, the primary first and then
+	/// standbys in failover order
+	pub matching_engines: HashMap<String, Vec<String>>,
+	/// Market -> price/size scales used to convert decimal strings at the edge
+	pub market_specs: HashMap<String, MarketSpec>,
+}
+
+impl MarketRoutes {
+	/// Validate the configured markets
+	pub fn from_config(config: &GatewayConfig) -> Result<Self> {
+		if config.markets.is_empty() {
+			anyhow::bail!("No markets configured");
+		}
+
+		let mut routes = Self::default();
+		for (market, market_config) in &config.markets {
+			let endpoints: Vec<_> = market_config
+				.endpoints
+				.iter()
+				.map(|endpoint| endpoint.trim().to_string())
+				.collect();
+			if endpoints.is_empty() || endpoints.iter().any(String::is_empty) {
+				anyhow::bail!("Market {} has an empty matching engine endpoint", market);
+			}
+			if endpoints.iter().collect::<HashSet<_>>().len() != endpoints.len() {
+				anyhow::bail!("Market {} lists a matching engine endpoint twice", market);
+			}
+			let spec = MarketSpec::new(
+				market.as_str(),
+				market_config.price_decimals,
+				market_config.size_decimals,
+			)
+			.with_context(|| format!("Invalid market {}", market))?;
+			routes.matching_engines.insert(market.clone(), endpoints);
+			routes.market_specs.insert(market.clone(), spec);
+		}
+		Ok(routes)
+	}
 }
 
-/// Gateway service configuration
+/// Gateway configuration file
+///
+/// Read from `GATEWAY_CONFIG_FILE` as TOML, YAML or JSON, by extension:
+///
+/// ```toml
+/// [rate_limit]
+/// requests_per_second = 100
+/// burst = 200
+///
+/// [markets.BTC-USDT]
+/// endpoints = ["http://localhost:50051", "http://localhost:50052"]
+/// price_decimals = 2
+/// size_decimals = 8
+/// ```
+///
+/// `GATEWAY_MATCHING_ENGINES` (JSON market -> endpoint or endpoints) and
+/// `GATEWAY_RATE_LIMIT_RPS` / `GATEWAY_RATE_LIMIT_BURST` override the file.
+/// Without a file, the gateway serves BTC-USDT from `localhost:50051`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[allow(dead_code)]
 pub struct GatewayConfig {
-	/// HTTP server bind address
-	pub bind_addr: SocketAddr,
-	/// Number of worker threads
-	pub workers: Option<usize>,
-	/// Matching engine endpoints (market -> endpoint)
-	pub matching_engines: HashMap<String, String>,
-	/// Rate limiting configuration
+	/// Markets served by the gateway
+	#[serde(default)]
+	pub markets: HashMap<String, MarketConfig>,
+	/// Rate limiting configuration, read once at startup
+	#[serde(default)]
 	pub rate_limit: RateLimitConfig,
-	/// Authentication configuration
-	pub auth: AuthConfig,
+}
+
+/// Routing and parameters of one market
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketConfig {
+	/// Matching engine endpoints serving the market: the primary first, then
+	/// standbys in failover order
+	pub endpoints: Vec<String>,
+	/// Number of decimals in a price
+	pub price_decimals: u32,
+	/// Number of decimals in a size
+	pub size_decimals: u32,
+}
+
+/// Endpoints of a market in `GATEWAY_MATCHING_ENGINES`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum EndpointsOverride {
+	Primary(String),
+	Ordered(Vec<String>),
 }
 
 /// Rate limiting configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[allow(dead_code)]
 pub struct RateLimitConfig {
-	/// Requests per second per user
+	/// Requests per second per principal
 	pub requests_per_second: u32,
 	/// Burst capacity
 	pub burst: u32,
 }
 
-/// Authentication configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[allow(dead_code)]
-pub struct AuthConfig {
-	/// Require signature verification
-	pub require_signature: bool,
-	/// Supported signature algorithms
-	pub supported_algorithms: Vec<String>,
+impl Default for RateLimitConfig {
+	fn default() -> Self {
+		Self {
+			requests_per_second: DEFAULT_RATE_LIMIT_RPS,
+			burst: DEFAULT_RATE_LIMIT_BURST,
+		}
+	}
 }
 
 impl Default for GatewayConfig {
 	fn default() -> Self {
 		Self {
-			bind_addr: "0.0.0.0:8080".parse().unwrap(),
-			workers: None,
-			matching_engines: default_matching_engines(),
-			rate_limit: RateLimitConfig {
-				requests_per_second: 100,
-				burst: 200,
-			},
-			auth: AuthConfig {
-				require_signature: true,
-				supported_algorithms: vec!["ed25519".to_string(), "ecdsa".to_string()],
-			},
+			markets: HashMap::from([(
+				"BTC-USDT".to_string(),
+				MarketConfig {
+					endpoints: vec!["http://localhost:50051".to_string()],
+					price_decimals: 2,
+					size_decimals: 8,
+				},
+			)]),
+			rate_limit: RateLimitConfig::default(),
 		}
 	}
 }
 
 impl GatewayConfig {
-	/// Load configuration from environment variables
-	#[allow(dead_code)]
-	pub fn from_env() -> Result<Self, config::ConfigError> {
-		let cfg = config::Config::builder()
-			.add_source(config::Environment::with_prefix("GATEWAY"))
-			.build()?;
-
-		cfg.try_deserialize()
+	/// Load the configuration file, if any, and apply environment overrides
+	pub fn load(path: Option<&Path>) -> Result<Self> {
+		let mut config = match path {
+			Some(path) => Self::from_file(path)?,
+			None => Self::default(),
+		};
+		config.apply_env_overrides()?;
+
+		if config.rate_limit.requests_per_second == 0 || config.rate_limit.burst == 0 {
+			anyhow::bail!("Rate limit requests per second and burst must be > 0");
+		}
+		Ok(config)
+	}
+
+	/// Load configuration from file, without environment overrides
+	pub fn from_file(path: &Path) -> Result<Self> {
+		let format = match path.extension().and_then(|ext| ext.to_str()) {
+			Some("toml") => FileFormat::Toml,
+			Some("yaml" | "yml") => FileFormat::Yaml,
+			Some("json") => FileFormat::Json,
+			_ => anyhow::bail!("Unsupported configuration file format: {}", path.display()),
+		};
+		let contents = fs::read_to_string(path)
+			.with_context(|| format!("Failed to read {}", path.display()))?;
+		Self::parse(&contents, format)
+			.with_context(|| format!("Invalid configuration file {}", path.display()))
 	}
 
-	/// Load configuration from file
-	#[allow(dead_code)]
-	pub fn from_file(path: &str) -> Result<Self, config::ConfigError> {
-		let cfg = config::Config::builder()
-			.add_source(config::File::with_name(path))
-			.add_source(config::Environment::with_prefix("GATEWAY"))
-			.build()?;
+	/// Parse configuration file contents
+	pub fn parse(contents: &str, format: FileFormat) -> Result<Self> {
+		Ok(config::Config::builder()
+			.add_source(config::File::from_str(contents, format))
+			.build()?
+			.try_deserialize()?)
+	}
 
-		cfg.try_deserialize()
+	fn apply_env_overrides(&mut self) -> Result<()> {
+		if let Ok(engines) = env::var("GATEWAY_MATCHING_ENGINES") {
+			let engines: HashMap<String, EndpointsOverride> = serde_json::from_str(&engines)
+				.context("GATEWAY_MATCHING_ENGINES must be a JSON object")?;
+			for (market, endpoints) in engines {
+				self.markets
+					.get_mut(&market)
+					.with_context(|| {
+						format!("GATEWAY_MATCHING_ENGINES names unknown market {}", market)
+					})?
+					.endpoints = match endpoints {
+					EndpointsOverride::Primary(endpoint) => vec![endpoint],
+					EndpointsOverride::Ordered(endpoints) => endpoints,
+				};
+			}
+		}
+		if let Ok(rps) = env::var("GATEWAY_RATE_LIMIT_RPS") {
+			self.rate_limit.requests_per_second = rps
+				.parse()
+				.context("GATEWAY_RATE_LIMIT_RPS must be a valid u32")?;
+		}
+		if let Ok(burst) = env::var("GATEWAY_RATE_LIMIT_BURST") {
+			self.rate_limit.burst = burst
+				.parse()
+				.context("GATEWAY_RATE_LIMIT_BURST must be a valid u32")?;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_markets_from_toml_and_yaml() {
+		let toml = r#"
+			[markets.BTC-USDT]
+			endpoints = ["http://engine-1:50051"]
+			price_decimals = 2
+			size_decimals = 8
+
+			[markets.ETH-USDT]
+			endpoints = ["http://engine-2:50051", "http://engine-2b:50051"]
+			price_decimals = 2
+			size_decimals = 6
+		"#;
+		let yaml = "
+markets:
+  BTC-USDT:
+    endpoints: [http://engine-1:50051]
+    price_decimals: 2
+    size_decimals: 8
+  ETH-USDT:
+    endpoints:
+      - http://engine-2:50051
+      - http://engine-2b:50051
+    price_decimals: 2
+    size_decimals: 6
+rate_limit:
+  requests_per_second: 10
+  burst: 20
+";
+
+		for (contents, format) in [(toml, FileFormat::Toml), (yaml, FileFormat::Yaml)] {
+			let config = GatewayConfig::parse(contents, format).unwrap();
+			let routes = MarketRoutes::from_config(&config).unwrap();
+			assert_eq!(
+				routes.matching_engines["ETH-USDT"],
+				["http://engine-2:50051", "http://engine-2b:50051"]
+			);
+			assert_eq!(routes.market_specs["ETH-USDT"].size_decimals, 6);
+			assert_eq!(routes.market_specs["BTC-USDT"].base_asset, "BTC");
+		}
+		let config = GatewayConfig::parse(yaml, FileFormat::Yaml).unwrap();
+		assert_eq!(config.rate_limit.burst, 20);
+	}
+
+	#[test]
+	fn example_file_loads() {
+		let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../deploy/gateway.example.toml");
+		let config = GatewayConfig::from_file(&path).unwrap();
+		assert_eq!(
+			MarketRoutes::from_config(&config).unwrap(),
+			MarketRoutes::from_config(&GatewayConfig::default()).unwrap()
+		);
+	}
+
+	#[test]
+	fn rejects_invalid_markets() {
+		let invalid = [
+			"",
+			"[markets.BTC-USDT]\nendpoints = [\"\"]\nprice_decimals = 2\nsize_decimals = 8",
+			"[markets.BTC-USDT]\nendpoints = []\nprice_decimals = 2\nsize_decimals = 8",
+			"[markets.BTC-USDT]\nendpoints = [\"http://e:1\", \"http://e:1\"]\nprice_decimals = 2\nsize_decimals = 8",
+			"[markets.BTCUSDT]\nendpoints = [\"http://e:1\"]\nprice_decimals = 2\nsize_decimals = 8",
+			"[markets.BTC-USDT]\nendpoints = [\"http://e:1\"]\nprice_decimals = 99\nsize_decimals = 8",
+		];
+		for contents in invalid {
+			let config = GatewayConfig::parse(contents, FileFormat::Toml).unwrap();
+			assert!(MarketRoutes::from_config(&config).is_err(), "{}", contents);
+		}
+		assert!(
+			GatewayConfig::parse("[markets.BTC-USDT]\nendpoints = 1", FileFormat::Toml).is_err()
+		);
 	}
 }
@@ -0,0 +1,140 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Hot reload of market routing from the gateway configuration file
+//!
+//! The file is polled every `GATEWAY_CONFIG_RELOAD_INTERVAL_MS` and
+//! reloaded when its contents change. A valid file replaces the
+//! dispatcher's markets in one step; an invalid one is logged and the
+//! previous markets stay in effect. Only markets are reloaded; the rest of
+//! the configuration is read once at startup.
+
+use std::{
+	fs,
+	path::{Path, PathBuf},
+	sync::Arc,
+	time::Duration,
+};
+
+use anyhow::Result;
+use tokio::time::MissedTickBehavior;
+
+use crate::{
+	config::{GatewayConfig, MarketRoutes},
+	dispatcher::MatchingDispatcher,
+};
+
+/// Detects changes to the configuration file
+pub struct ConfigWatcher {
+	path: PathBuf,
+	/// Contents last checked; `None` if the file could not be read
+	contents: Option<Vec<u8>>,
+}
+
+impl ConfigWatcher {
+	/// Watch `path`, taking its current contents as already applied
+	pub fn new(path: &Path) -> Self {
+		Self {
+			path: path.to_path_buf(),
+			contents: fs::read(path).ok(),
+		}
+	}
+
+	/// The file's markets, if its contents changed since the last check
+	pub fn check(&mut self) -> Option<Result<MarketRoutes>> {
+		let contents = match fs::read(&self.path) {
+			Ok(contents) => contents,
+			Err(e) => {
+				// Report an unreadable file once, not on every check
+				return self.contents.take().map(|_| {
+					Err(anyhow::Error::new(e)
+						.context(format!("Failed to read {}", self.path.display())))
+				});
+			}
+		};
+		if self.contents.as_ref() == Some(&contents) {
+			return None;
+		}
+		self.contents = Some(contents);
+
+		Some(
+			GatewayConfig::load(Some(&self.path))
+				.and_then(|config| MarketRoutes::from_config(&config)),
+		)
+	}
+
+	/// Apply changes to `dispatcher` every `interval`, forever
+	pub async fn run(mut self, interval: Duration, dispatcher: Arc<MatchingDispatcher>) {
+		let mut ticker = tokio::time::interval(interval);
+		ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+		loop {
+			ticker.tick().await;
+			match self.check() {
+				Some(Ok(routes)) => {
+					tracing::info!(
+						target: "server::config",
+						"Reloaded {}",
+						self.path.display()
+					);
+					dispatcher.update_markets(routes).await;
+				}
+				Some(Err(e)) => {
+					tracing::warn!(
+						target: "server::config",
+						"Keeping previous markets, reload failed: {:#}",
+						e
+					);
+				}
+				None => {}
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const MARKETS: &str = r#"
+		[markets.BTC-USDT]
+		endpoints = ["http://engine-1:50051"]
+		price_decimals = 2
+		size_decimals = 8
+	"#;
+
+	#[test]
+	fn reports_each_change_once() {
+		let path =
+			std::env::temp_dir().join(format!("anvil-gateway-{}.toml", uuid::Uuid::new_v4()));
+		fs::write(&path, MARKETS).unwrap();
+		let mut watcher = ConfigWatcher::new(&path);
+		assert!(watcher.check().is_none());
+
+		fs::write(&path, MARKETS.replace("engine-1", "engine-2")).unwrap();
+		let routes = watcher.check().unwrap().unwrap();
+		assert_eq!(
+			routes.matching_engines["BTC-USDT"],
+			["http://engine-2:50051"]
+		);
+		assert!(watcher.check().is_none());
+
+		fs::write(&path, "[markets.BTC-USDT]\nendpoints = [\"\"]").unwrap();
+		assert!(watcher.check().unwrap().is_err());
+		assert!(watcher.check().is_none());
+
+		fs::remove_file(&path).unwrap();
+		assert!(watcher.check().unwrap().is_err());
+		assert!(watcher.check().is_none());
+	}
+}
@@ -13,19 +13,28 @@
 // limitations under the License.
 
 use std::{
-	collections::HashMap,
-	sync::Arc,
+	collections::{HashMap, HashSet},
+	sync::{Arc, RwLock},
 	time::{Duration, Instant},
 };
 
 use anvil_matching::types::Order as MatchingOrder;
-use anvil_sdk::types::PlaceOrderRequest;
+use anvil_sdk::MarketSpec;
 use thiserror::Error;
 use tokio::sync::{Mutex, mpsc, oneshot};
 
 use crate::{
-	config::GatewayRuntimeConfig,
-	grpc_client::{GrpcClientError, MatchingGrpcClient, proto::SubmitDisposition},
+	admission::AdmittedOrder,
+	config::{GatewayRuntimeConfig, MarketRoutes},
+	grpc_client::{
+		GrpcClientError, MatchingGrpcClient,
+		proto::{
+			EngineRole, ExecutionReport, GetCandlesResponse, GetEngineStatusResponse,
+			GetOrderBookResponse, GetTickerResponse, ListFillsRequest, ListFillsResponse,
+			ListTradesRequest, ListTradesResponse, MarketDataUpdate, Order, SubmitDisposition,
+		},
+	},
+	health::CircuitState,
 	request_context::RequestContext,
 };
 
@@ -34,6 +43,8 @@ use crate::{
 pub enum DispatcherError {
 	#[error("Matching engine not found for market: {0}")]
 	MatchingEngineNotFound(String),
+	#[error("Market unavailable: {0}")]
+	MarketUnavailable(String),
 	#[error("Gateway dispatch queue is overloaded")]
 	GatewayOverloaded,
 	#[error("Timed out while waiting in dispatch queue")]
@@ -44,12 +55,16 @@ pub enum DispatcherError {
 	MatchingOverloaded(String),
 	#[error("Matching rejected order: {0}")]
 	MatchingRejected(String),
+	#[error("Insufficient balance: {0}")]
+	InsufficientBalance(String),
 	#[error("Matching internal error: {0}")]
 	MatchingInternal(String),
 	#[error("Dispatching error: {0}")]
 	DispatchingError(String),
 	#[error("Invalid response from matching engine: {0}")]
 	InvalidResponse(String),
+	#[error("Order not found: {0}")]
+	OrderNotFound(String),
 }
 
 #[derive(Debug, Clone)]
@@ -64,8 +79,30 @@ pub struct DispatchResult {
 	pub timings: DispatchTimings,
 }
 
+/// Circuit state of the engine serving a market
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarketCircuit {
+	pub market: String,
+	/// Active matching engine endpoint of the market
+	pub endpoint: String,
+	pub circuit: CircuitState,
+}
+
+/// Matching engine currently serving a market
+#[derive(Debug, Clone)]
+struct ActiveEngine {
+	endpoint: String,
+	/// Highest event sequence seen committed by the market's active engines;
+	/// a standby must have reached it to take over
+	acknowledged_sequence: u64,
+	/// Highest fencing epoch seen from the market's active engines
+	epoch: u64,
+}
+
 struct DispatchJob {
 	order: MatchingOrder,
+	replaces: Option<String>,
+	nonce: String,
 	endpoint: String,
 	context: RequestContext,
 	enqueued_at: Instant,
@@ -76,9 +113,16 @@ struct DispatchJob {
 ///
 /// Uses gRPC to communicate with matching engines.
 pub struct MatchingDispatcher {
-	/// Market -> Matching engine endpoint mapping
-	matching_engines: HashMap<String, String>,
-	/// Market -> gRPC client mapping (with mutex for async access)
+	/// Market -> matching engine endpoints and price/size scales, replaced
+	/// as a whole when the configuration is reloaded
+	markets: RwLock<Arc<MarketRoutes>>,
+	/// Market -> engine requests are routed to, the primary until a failover
+	active: RwLock<HashMap<String, ActiveEngine>>,
+	failover_enabled: bool,
+	/// Endpoint -> circuit state, kept by the health prober; endpoints it
+	/// has not reported on are closed
+	circuits: RwLock<HashMap<String, CircuitState>>,
+	/// Endpoint -> gRPC client mapping (with mutex for async access)
 	clients: Arc<Mutex<HashMap<String, MatchingGrpcClient>>>,
 	queue_tx: mpsc::Sender<DispatchJob>,
 	queue_timeout: Duration,
@@ -91,7 +135,10 @@ impl MatchingDispatcher {
 		let (queue_tx, queue_rx) = mpsc::channel(config.dispatch_queue_capacity);
 
 		let dispatcher = Self {
-			matching_engines: config.matching_engines.clone(),
+			markets: RwLock::new(Arc::new(config.markets.clone())),
+			active: RwLock::new(Self::active_engines(&config.markets, &HashMap::new())),
+			failover_enabled: config.failover_enabled,
+			circuits: RwLock::new(HashMap::new()),
 			clients: Arc::new(Mutex::new(HashMap::new())),
 			queue_tx,
 			queue_timeout: Duration::from_millis(config.dispatch_queue_timeout_ms),
@@ -103,7 +150,7 @@ impl MatchingDispatcher {
 		tracing::info!(
 			target: "server::dispatcher",
 			"MatchingDispatcher initialized with {} markets (queue cap={}, queue timeout={}ms, rpc timeout={}ms)",
-			dispatcher.matching_engines.len(),
+			config.markets.matching_engines.len(),
 			config.dispatch_queue_capacity,
 			config.dispatch_queue_timeout_ms,
 			config.matching_rpc_timeout_ms,
@@ -112,6 +159,275 @@ impl MatchingDispatcher {
 		Ok(dispatcher)
 	}
 
+	/// Markets currently served
+	fn routes(&self) -> Arc<MarketRoutes> {
+		self.markets.read().unwrap().clone()
+	}
+
+	/// Serve `routes` from now on
+	///
+	/// Requests already routed finish against the previous endpoints. A
+	/// market keeps its active engine while that engine is still listed for
+	/// it, and otherwise starts over on its primary. Clients of endpoints
+	/// that are no longer used are dropped.
+	pub async fn update_markets(&self, routes: MarketRoutes) {
+		let endpoints: HashSet<_> = routes
+			.matching_engines
+			.values()
+			.flatten()
+			.cloned()
+			.collect();
+		let markets = routes.matching_engines.len();
+		{
+			let mut active = self.active.write().unwrap();
+			*active = Self::active_engines(&routes, &active);
+			*self.markets.write().unwrap() = Arc::new(routes);
+		}
+		self.clients
+			.lock()
+			.await
+			.retain(|endpoint, _| endpoints.contains(endpoint));
+
+		tracing::info!(
+			target: "server::dispatcher",
+			"MatchingDispatcher now serves {} markets",
+			markets
+		);
+	}
+
+	/// Active engine of every market in `routes`, carried over from
+	/// `previous` where that engine is still listed for the market
+	fn active_engines(
+		routes: &MarketRoutes,
+		previous: &HashMap<String, ActiveEngine>,
+	) -> HashMap<String, ActiveEngine> {
+		routes
+			.matching_engines
+			.iter()
+			.filter_map(|(market, endpoints)| {
+				let previous = previous.get(market);
+				let active = match previous {
+					Some(active) if endpoints.contains(&active.endpoint) => active.clone(),
+					_ => ActiveEngine {
+						endpoint: endpoints.first()?.clone(),
+						acknowledged_sequence: previous
+							.map_or(0, |active| active.acknowledged_sequence),
+						epoch: previous.map_or(0, |active| active.epoch),
+					},
+				};
+				Some((market.clone(), active))
+			})
+			.collect()
+	}
+
+	/// Configured matching engine endpoints, primaries and standbys, and the
+	/// markets each serves
+	pub fn endpoints(&self) -> HashMap<String, Vec<String>> {
+		let mut endpoints: HashMap<String, Vec<String>> = HashMap::new();
+		for (market, market_endpoints) in &self.routes().matching_engines {
+			for endpoint in market_endpoints {
+				endpoints
+					.entry(endpoint.clone())
+					.or_default()
+					.push(market.clone());
+			}
+		}
+		endpoints
+	}
+
+	/// Record the circuit state of an endpoint
+	///
+	/// Requests for markets behind a circuit that is not closed fail with
+	/// [`DispatcherError::MarketUnavailable`] without reaching the engine.
+	pub fn set_circuit(&self, endpoint: &str, state: CircuitState) {
+		let mut circuits = self.circuits.write().unwrap();
+		if state == CircuitState::Closed {
+			circuits.remove(endpoint);
+		} else {
+			circuits.insert(endpoint.to_string(), state);
+		}
+	}
+
+	fn circuit(&self, endpoint: &str) -> CircuitState {
+		self.circuits
+			.read()
+			.unwrap()
+			.get(endpoint)
+			.copied()
+			.unwrap_or(CircuitState::Closed)
+	}
+
+	/// Active engine and its circuit state for every configured market,
+	/// sorted by market
+	pub fn market_circuits(&self) -> Vec<MarketCircuit> {
+		let mut markets: Vec<_> = self
+			.active
+			.read()
+			.unwrap()
+			.iter()
+			.map(|(market, active)| MarketCircuit {
+				market: market.clone(),
+				endpoint: active.endpoint.clone(),
+				circuit: self.circuit(&active.endpoint),
+			})
+			.collect();
+		markets.sort_by(|a, b| a.market.cmp(&b.market));
+		markets
+	}
+
+	/// Follow the active engines' sequences, and fail markets whose active
+	/// engine is unhealthy over to a standby that has caught up
+	///
+	/// Called by the health prober after each round of probes, once circuit
+	/// states are recorded. Each round reads the last committed sequence of
+	/// every healthy active engine as the market's acknowledged sequence. A
+	/// standby, tried in configuration order, takes over only if its circuit
+	/// is closed and it has committed at least that sequence, so two engines
+	/// never serve a market from diverging histories; until one qualifies
+	/// the market stays unavailable. A standby replicating as a follower is
+	/// promoted under an epoch newer than any seen for the market before it
+	/// takes over, and fenced engines are skipped. There is no failback: a
+	/// recovered primary serves again only once the engine that replaced it
+	/// fails.
+	pub async fn fail_over(&self) {
+		let routes = self.routes();
+		// Engines can serve several markets; ask each at most once per round
+		let mut statuses: HashMap<String, Option<GetEngineStatusResponse>> = HashMap::new();
+
+		for (market, endpoints) in &routes.matching_engines {
+			let Some(active) = self.active.read().unwrap().get(market).cloned() else {
+				continue;
+			};
+
+			if self.circuit(&active.endpoint) == CircuitState::Closed {
+				if let Some(status) = self.engine_status(&mut statuses, &active.endpoint).await
+					&& let Some(current) = self.active.write().unwrap().get_mut(market)
+					&& current.endpoint == active.endpoint
+				{
+					current.acknowledged_sequence =
+						current.acknowledged_sequence.max(status.last_sequence);
+					current.epoch = current.epoch.max(status.epoch);
+				}
+				continue;
+			}
+			if !self.failover_enabled {
+				continue;
+			}
+
+			for standby in endpoints.iter().filter(|endpoint| {
+				**endpoint != active.endpoint && self.circuit(endpoint) == CircuitState::Closed
+			}) {
+				let Some(status) = self.engine_status(&mut statuses, standby).await else {
+					continue;
+				};
+				let sequence = status.last_sequence;
+				if status.role() == EngineRole::Fenced {
+					continue;
+				}
+				if sequence < active.acknowledged_sequence {
+					tracing::warn!(
+						target: "server::dispatcher",
+						"Not failing market {} over to {}: at sequence {}, behind acknowledged {}",
+						market,
+						standby,
+						sequence,
+						active.acknowledged_sequence
+					);
+					continue;
+				}
+				if self.active.read().unwrap().get(market).map(|a| &a.endpoint)
+					!= Some(&active.endpoint)
+				{
+					break;
+				}
+
+				let mut epoch = status.epoch;
+				if status.role() == EngineRole::Follower {
+					epoch = active.epoch.max(status.epoch) + 1;
+					let promoted = match Self::get_client(&self.clients, standby, self.rpc_timeout)
+						.await
+					{
+						Ok(mut client) => client.promote(epoch).await.map_err(|e| e.to_string()),
+						Err(e) => Err(e.to_string()),
+					};
+					if let Err(e) = promoted {
+						tracing::warn!(
+							target: "server::dispatcher",
+							"Not failing market {} over to {}: promotion failed: {}",
+							market,
+							standby,
+							e
+						);
+						continue;
+					}
+				}
+
+				let mut engines = self.active.write().unwrap();
+				let Some(current) = engines.get_mut(market) else {
+					break;
+				};
+				if current.endpoint != active.endpoint {
+					break;
+				}
+				current.endpoint = standby.clone();
+				current.acknowledged_sequence = current.acknowledged_sequence.max(sequence);
+				current.epoch = current.epoch.max(epoch);
+				tracing::warn!(
+					target: "server::dispatcher",
+					"Market {} failed over from {} to {} at sequence {}, epoch {}",
+					market,
+					active.endpoint,
+					standby,
+					sequence,
+					epoch
+				);
+				break;
+			}
+		}
+	}
+
+	/// Sequence and replication role of an engine, `None` if it could not
+	/// be read
+	async fn engine_status(
+		&self,
+		statuses: &mut HashMap<String, Option<GetEngineStatusResponse>>,
+		endpoint: &str,
+	) -> Option<GetEngineStatusResponse> {
+		if let Some(status) = statuses.get(endpoint) {
+			return status.clone();
+		}
+		let status = match Self::get_client(&self.clients, endpoint, self.rpc_timeout).await {
+			Ok(mut client) => client.engine_status().await.map_err(|e| e.to_string()),
+			Err(e) => Err(e.to_string()),
+		};
+		let status = match status {
+			Ok(status) => Some(status),
+			Err(e) => {
+				tracing::debug!(
+					target: "server::dispatcher",
+					"Failed to read the status of {}: {}",
+					endpoint,
+					e
+				);
+				None
+			}
+		};
+		statuses.insert(endpoint.to_string(), status.clone());
+		status
+	}
+
+	/// Price/size scales for a market, if the market is configured
+	pub fn market_spec(&self, market: &str) -> Option<MarketSpec> {
+		self.routes().market_specs.get(market).cloned()
+	}
+
+	/// All configured market specs, sorted by market identifier
+	pub fn market_specs(&self) -> Vec<MarketSpec> {
+		let mut specs: Vec<_> = self.routes().market_specs.values().cloned().collect();
+		specs.sort_by(|a, b| a.market.cmp(&b.market));
+		specs
+	}
+
 	/// Get or create gRPC client for a market
 	async fn get_client(
 		clients: &Arc<Mutex<HashMap<String, MatchingGrpcClient>>>,
@@ -134,51 +450,375 @@ impl MatchingDispatcher {
 		Ok(clone)
 	}
 
+	/// Latest candles of `interval` ("1m", "5m", "1h" or "1d") for a market
+	///
+	/// Market data reads go straight to the market's matching engine and do
+	/// not take a dispatch queue slot.
+	pub async fn candles(
+		&self,
+		market: &str,
+		interval: &str,
+		limit: u32,
+	) -> Result<GetCandlesResponse, DispatcherError> {
+		self.market_client(market)
+			.await?
+			.get_candles(market, interval, limit)
+			.await
+			.map_err(Self::map_read_error)
+	}
+
+	/// Rolling 24h ticker statistics for a market
+	pub async fn ticker(&self, market: &str) -> Result<GetTickerResponse, DispatcherError> {
+		self.market_client(market)
+			.await?
+			.get_ticker(market)
+			.await
+			.map_err(Self::map_read_error)
+	}
+
+	/// The top `depth` L2 levels per side of a market (0 for the full book)
+	pub async fn order_book(
+		&self,
+		market: &str,
+		depth: u32,
+	) -> Result<GetOrderBookResponse, DispatcherError> {
+		self.market_client(market)
+			.await?
+			.get_order_book(market, depth)
+			.await
+			.map_err(Self::map_read_error)
+	}
+
+	/// Follow every public market data change of a market
+	pub async fn market_data(
+		&self,
+		market: &str,
+	) -> Result<tonic::Streaming<MarketDataUpdate>, DispatcherError> {
+		self.market_client(market)
+			.await?
+			.stream_market_data(market)
+			.await
+			.map_err(Self::map_read_error)
+	}
+
+	/// One page of a market's trades, newest first
+	pub async fn trades(
+		&self,
+		request: ListTradesRequest,
+	) -> Result<ListTradesResponse, DispatcherError> {
+		self.market_client(&request.market)
+			.await?
+			.list_trades(request)
+			.await
+			.map_err(Self::map_read_error)
+	}
+
+	/// One page of a principal's fills in a market, newest first
+	pub async fn fills(
+		&self,
+		request: ListFillsRequest,
+	) -> Result<ListFillsResponse, DispatcherError> {
+		self.market_client(&request.market)
+			.await?
+			.list_fills(request)
+			.await
+			.map_err(Self::map_read_error)
+	}
+
+	async fn market_client(&self, market: &str) -> Result<MatchingGrpcClient, DispatcherError> {
+		let endpoint = self.endpoint(market)?;
+		Self::get_client(&self.clients, &endpoint, self.rpc_timeout).await
+	}
+
+	fn endpoint(&self, market: &str) -> Result<String, DispatcherError> {
+		let endpoint = self
+			.active
+			.read()
+			.unwrap()
+			.get(market)
+			.map(|active| active.endpoint.clone())
+			.ok_or_else(|| DispatcherError::MatchingEngineNotFound(market.to_string()))?;
+		if self.circuits.read().unwrap().contains_key(&endpoint) {
+			return Err(DispatcherError::MarketUnavailable(market.to_string()));
+		}
+		Ok(endpoint)
+	}
+
+	fn map_read_error(err: GrpcClientError) -> DispatcherError {
+		match err {
+			// Reads have no side effects, so a timeout is a plain retryable failure
+			GrpcClientError::Timeout => {
+				DispatcherError::DispatchingError("Matching engine read timed out".to_string())
+			}
+			GrpcClientError::Transport(e) | GrpcClientError::Serialization(e) => {
+				DispatcherError::DispatchingError(e)
+			}
+			GrpcClientError::Status(e) | GrpcClientError::NotFound(e) => {
+				DispatcherError::MatchingInternal(e)
+			}
+		}
+	}
+
+	/// Current state of one of a principal's orders in a market
+	///
+	/// Orders of other principals are reported as not found.
+	pub async fn get_order(
+		&self,
+		market: &str,
+		order_id: &str,
+		principal_id: &str,
+	) -> Result<Order, DispatcherError> {
+		self.market_client(market)
+			.await?
+			.get_order(order_id, principal_id)
+			.await
+			.map_err(|e| match e {
+				GrpcClientError::NotFound(_) => {
+					DispatcherError::OrderNotFound(order_id.to_string())
+				}
+				e => Self::map_read_error(e),
+			})
+	}
+
+	/// Current state of a principal's latest order with `client_order_id`
+	pub async fn get_order_by_client_id(
+		&self,
+		market: &str,
+		client_order_id: &str,
+		principal_id: &str,
+	) -> Result<Order, DispatcherError> {
+		self.market_client(market)
+			.await?
+			.get_order_by_client_id(client_order_id, principal_id)
+			.await
+			.map_err(|e| match e {
+				GrpcClientError::NotFound(_) => {
+					DispatcherError::OrderNotFound(client_order_id.to_string())
+				}
+				e => Self::map_read_error(e),
+			})
+	}
+
 	/// Dispatch an order to the appropriate matching engine
 	///
-	/// This converts the gateway's PlaceOrderRequest into the matching
-	/// engine's internal Order format and forwards it via gRPC.
+	/// This converts the admitted order (already scaled to market units)
+	/// into the matching engine's internal Order format and forwards it via gRPC.
+	/// The caller mints `order_id`, so that a retry of an unconfirmed
+	/// dispatch can reuse it. The engine deduplicates on `principal_id` and
+	/// the request's `nonce`: a repeated request resolves to the order ID it
+	/// was first accepted under, which the result carries.
 	///
 	/// Note: The `principal_id` parameter is the cryptographic principal
 	/// identifier (hex-encoded public key), NOT a business user ID.
 	/// Gateway only understands cryptographic identity, not business user identity.
 	pub async fn dispatch_order(
 		&self,
-		request: PlaceOrderRequest,
+		request: AdmittedOrder,
+		order_id: String,
 		principal_id: String,
+		nonce: String,
 		context: RequestContext,
 	) -> Result<DispatchResult, DispatcherError> {
-		let endpoint = self
-			.matching_engines
-			.get(&request.market)
-			.cloned()
-			.ok_or_else(|| DispatcherError::MatchingEngineNotFound(request.market.clone()))?;
+		self.enqueue(request, order_id, None, principal_id, nonce, context)
+			.await
+	}
 
-		// Convert PlaceOrderRequest to MatchingOrder
-		let price = request.price.ok_or_else(|| {
-			DispatcherError::DispatchingError("Limit orders require a price".to_string())
-		})?;
+	/// Dispatch an order that replaces one of the principal's resting orders
+	///
+	/// The matching engine cancels `replaces` and only then submits the new
+	/// order, which gets a fresh order ID and loses time priority.
+	pub async fn dispatch_replace(
+		&self,
+		request: AdmittedOrder,
+		order_id: String,
+		replaces: String,
+		principal_id: String,
+		nonce: String,
+		context: RequestContext,
+	) -> Result<DispatchResult, DispatcherError> {
+		self.enqueue(
+			request,
+			order_id,
+			Some(replaces),
+			principal_id,
+			nonce,
+			context,
+		)
+		.await
+	}
 
-		let order = MatchingOrder {
-			order_id: uuid::Uuid::new_v4().to_string(),
-			market: request.market.clone(),
-			side: request.side,
-			price,
-			size: request.size,
-			remaining_size: request.size,
-			timestamp: std::time::SystemTime::now()
-				.duration_since(std::time::UNIX_EPOCH)
-				.unwrap()
-				.as_secs(),
-			// Matching engine's Order struct uses `public_key` field to store
-			// the cryptographic principal identifier (hex-encoded public key).
-			// Gateway only understands cryptographic identity, not business user identity.
-			public_key: principal_id,
-		};
+	/// Ask the market's matching engine to cancel a principal's order
+	///
+	/// Cancels skip the dispatch queue. Success means the cancel was
+	/// sequenced; whether it applied is reported on the event stream.
+	pub async fn cancel_order(
+		&self,
+		market: &str,
+		order_id: &str,
+		principal_id: &str,
+		context: &RequestContext,
+	) -> Result<(), DispatcherError> {
+		let response = self
+			.market_client(market)
+			.await?
+			.cancel_order(market, order_id, principal_id, context)
+			.await
+			.map_err(Self::map_write_error)?;
+		Self::disposition_result(response.disposition, response.reason)
+	}
+
+	/// Ask the market's matching engine to cancel a principal's resting order
+	/// with `client_order_id`
+	///
+	/// Like [`Self::cancel_order`]; the engine resolves the client order ID
+	/// when it sequences the cancel. Returns the order ID the client order ID
+	/// referred to on admission, if the engine knew it.
+	pub async fn cancel_order_by_client_id(
+		&self,
+		market: &str,
+		client_order_id: &str,
+		principal_id: &str,
+		context: &RequestContext,
+	) -> Result<Option<String>, DispatcherError> {
+		let response = self
+			.market_client(market)
+			.await?
+			.cancel_order_by_client_id(market, client_order_id, principal_id, context)
+			.await
+			.map_err(Self::map_write_error)?;
+		Self::disposition_result(response.disposition, response.reason)?;
+		Ok(Some(response.order_id).filter(|order_id| !order_id.is_empty()))
+	}
+
+	/// Dispatch orders of one market that its engine sequences back to back
+	///
+	/// Batches skip the dispatch queue; the engine's ingress queue takes the
+	/// whole batch or none of it. Returns one outcome per order, in order.
+	/// An `Err` means the batch as a whole failed.
+	///
+	/// Each order's engine idempotency nonce is the batch `nonce` suffixed
+	/// with its position, so a repeated batch resolves to the same orders.
+	pub async fn dispatch_batch(
+		&self,
+		market: &str,
+		requests: Vec<AdmittedOrder>,
+		principal_id: &str,
+		nonce: &str,
+		context: &RequestContext,
+	) -> Result<Vec<Result<MatchingOrder, DispatcherError>>, DispatcherError> {
+		let mut client = self.market_client(market).await?;
+
+		let mut outcomes: Vec<_> = requests
+			.into_iter()
+			.map(|request| {
+				Self::matching_order(
+					request,
+					uuid::Uuid::new_v4().to_string(),
+					principal_id.to_string(),
+				)
+			})
+			.collect();
+		let orders: Vec<_> = outcomes
+			.iter()
+			.enumerate()
+			.filter_map(|(index, outcome)| {
+				let order = outcome.as_ref().ok()?.clone();
+				Some((order, format!("{nonce}/{index}")))
+			})
+			.collect();
+		if orders.is_empty() {
+			return Ok(outcomes);
+		}
+
+		let mut responses = client
+			.submit_orders(orders, context)
+			.await
+			.map_err(Self::map_write_error)?
+			.into_iter();
+		for outcome in outcomes.iter_mut().filter(|outcome| outcome.is_ok()) {
+			let response = responses.next().ok_or_else(|| {
+				DispatcherError::InvalidResponse("Missing batch result".to_string())
+			})?;
+			match Self::disposition_result(response.disposition, response.reason) {
+				Ok(()) => {
+					if let Ok(order) = outcome {
+						order.order_id = response.order_id;
+					}
+				}
+				Err(err) => *outcome = Err(err),
+			}
+		}
+		Ok(outcomes)
+	}
+
+	/// Ask the market's matching engine to cancel several of a principal's
+	/// orders back to back
+	///
+	/// Returns one outcome per order ID, in order. An `Err` means the batch
+	/// as a whole failed.
+	pub async fn cancel_batch(
+		&self,
+		market: &str,
+		order_ids: &[String],
+		principal_id: &str,
+		context: &RequestContext,
+	) -> Result<Vec<Result<(), DispatcherError>>, DispatcherError> {
+		let responses = self
+			.market_client(market)
+			.await?
+			.cancel_orders(market, order_ids, principal_id, context)
+			.await
+			.map_err(Self::map_write_error)?;
+		if responses.len() != order_ids.len() {
+			return Err(DispatcherError::InvalidResponse(
+				"Batch result count mismatch".to_string(),
+			));
+		}
+		Ok(responses
+			.into_iter()
+			.map(|response| Self::disposition_result(response.disposition, response.reason))
+			.collect())
+	}
+
+	/// Follow a principal's execution reports in a market from `from_sequence`
+	/// (0 for live reports only)
+	pub async fn execution_reports(
+		&self,
+		market: &str,
+		principal_id: &str,
+		from_sequence: u64,
+	) -> Result<tonic::Streaming<ExecutionReport>, DispatcherError> {
+		self.market_client(market)
+			.await?
+			.stream_execution_reports(market, principal_id, from_sequence)
+			.await
+			.map_err(Self::map_read_error)
+	}
+
+	/// Markets with a configured matching engine, sorted
+	pub fn markets(&self) -> Vec<String> {
+		let mut markets: Vec<_> = self.routes().matching_engines.keys().cloned().collect();
+		markets.sort();
+		markets
+	}
+
+	async fn enqueue(
+		&self,
+		request: AdmittedOrder,
+		order_id: String,
+		replaces: Option<String>,
+		principal_id: String,
+		nonce: String,
+		context: RequestContext,
+	) -> Result<DispatchResult, DispatcherError> {
+		let endpoint = self.endpoint(&request.market)?;
+		let order = Self::matching_order(request, order_id, principal_id)?;
 
 		let (response_tx, response_rx) = oneshot::channel();
 		let job = DispatchJob {
 			order,
+			replaces,
+			nonce,
 			endpoint,
 			context,
 			enqueued_at: Instant::now(),
@@ -201,6 +841,35 @@ impl MatchingDispatcher {
 		})?
 	}
 
+	/// Convert an admitted order into the matching engine's order format
+	fn matching_order(
+		request: AdmittedOrder,
+		order_id: String,
+		principal_id: String,
+	) -> Result<MatchingOrder, DispatcherError> {
+		let price = request.price.map(|p| p.units()).ok_or_else(|| {
+			DispatcherError::DispatchingError("Limit orders require a price".to_string())
+		})?;
+
+		Ok(MatchingOrder {
+			order_id,
+			market: request.market.clone(),
+			side: request.side,
+			price,
+			size: request.size.units(),
+			remaining_size: request.size.units(),
+			timestamp: std::time::SystemTime::now()
+				.duration_since(std::time::UNIX_EPOCH)
+				.unwrap()
+				.as_secs(),
+			// Matching engine's Order struct uses `public_key` field to store
+			// the cryptographic principal identifier (hex-encoded public key).
+			// Gateway only understands cryptographic identity, not business user identity.
+			public_key: principal_id,
+			client_order_id: request.client_order_id,
+		})
+	}
+
 	fn spawn_workers(&self, mut queue_rx: mpsc::Receiver<DispatchJob>) {
 		let clients = self.clients.clone();
 		let rpc_timeout = self.rpc_timeout;
@@ -220,7 +889,14 @@ impl MatchingDispatcher {
 				};
 
 				let rpc_start = Instant::now();
-				let result = client.submit_order(job.order.clone(), &context).await;
+				let result = client
+					.submit_order(
+						job.order.clone(),
+						job.replaces.as_deref(),
+						&job.nonce,
+						&context,
+					)
+					.await;
 				let rpc_elapsed = rpc_start.elapsed();
 
 				let timings = DispatchTimings {
@@ -228,36 +904,13 @@ impl MatchingDispatcher {
 					rpc_ms: rpc_elapsed.as_millis(),
 				};
 
-				let outcome = match result {
-					Ok(response) => {
-						let disposition = SubmitDisposition::try_from(response.disposition).ok();
-						match disposition {
-							Some(SubmitDisposition::AcceptedOk) => Ok(DispatchResult {
-								order: job.order.clone(),
-								timings,
-							}),
-							Some(SubmitDisposition::OverloadedEngine) => {
-								Err(DispatcherError::MatchingOverloaded(response.reason.clone()))
-							}
-							Some(SubmitDisposition::RejectedOrder)
-							| Some(SubmitDisposition::InvalidOrder) => {
-								Err(DispatcherError::MatchingRejected(response.reason.clone()))
-							}
-							Some(SubmitDisposition::InternalError) => {
-								Err(DispatcherError::MatchingInternal(response.reason.clone()))
-							}
-							None => Err(DispatcherError::InvalidResponse(
-								"Missing disposition".to_string(),
-							)),
-						}
-					}
-					Err(GrpcClientError::Timeout) => Err(DispatcherError::MatchingTimeout),
-					Err(GrpcClientError::Transport(e)) => Err(DispatcherError::DispatchingError(e)),
-					Err(GrpcClientError::Status(e)) => Err(DispatcherError::MatchingInternal(e)),
-					Err(GrpcClientError::Serialization(e)) => {
-						Err(DispatcherError::DispatchingError(e))
-					}
-				};
+				let outcome = result.map_err(Self::map_write_error).and_then(|response| {
+					Self::disposition_result(response.disposition, response.reason)?;
+					// A repeated request resolves to its original order
+					let mut order = job.order.clone();
+					order.order_id = response.order_id;
+					Ok(DispatchResult { order, timings })
+				});
 
 				let _ = job.response_tx.send(outcome);
 			}
@@ -265,8 +918,304 @@ impl MatchingDispatcher {
 	}
 }
 
+impl MatchingDispatcher {
+	/// Map a matching engine disposition to the dispatch outcome
+	fn disposition_result(disposition: i32, reason: String) -> Result<(), DispatcherError> {
+		match SubmitDisposition::try_from(disposition).ok() {
+			Some(SubmitDisposition::AcceptedOk) => Ok(()),
+			Some(SubmitDisposition::OverloadedEngine) => {
+				Err(DispatcherError::MatchingOverloaded(reason))
+			}
+			Some(SubmitDisposition::RejectedOrder) | Some(SubmitDisposition::InvalidOrder) => {
+				Err(DispatcherError::MatchingRejected(reason))
+			}
+			Some(SubmitDisposition::InsufficientBalance) => {
+				Err(DispatcherError::InsufficientBalance(reason))
+			}
+			Some(SubmitDisposition::InternalError) => {
+				Err(DispatcherError::MatchingInternal(reason))
+			}
+			None => Err(DispatcherError::InvalidResponse(
+				"Missing disposition".to_string(),
+			)),
+		}
+	}
+
+	/// Map a failed write RPC; a timeout leaves the outcome unknown
+	fn map_write_error(err: GrpcClientError) -> DispatcherError {
+		match err {
+			GrpcClientError::Timeout => DispatcherError::MatchingTimeout,
+			GrpcClientError::Transport(e) | GrpcClientError::Serialization(e) => {
+				DispatcherError::DispatchingError(e)
+			}
+			GrpcClientError::Status(e) | GrpcClientError::NotFound(e) => {
+				DispatcherError::MatchingInternal(e)
+			}
+		}
+	}
+}
+
 impl Default for MatchingDispatcher {
 	fn default() -> Self {
 		panic!("Use MatchingDispatcher::new with configuration")
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Mutex as StdMutex;
+
+	use anvil_matching::{
+		EventHub, EventHubConfig, EventStorage, IngressQueue, MatchingEvent, MemoryEventStorage,
+		MemoryOrderJournal, OrderJournal, QueueReceiver, ReplicationState, SharedEventStorage,
+		event::EventBatch, server::MatchingServiceImpl,
+	};
+	use anvil_sdk::types::Side;
+	use tonic::transport::{Server, server::TcpIncoming};
+
+	use super::*;
+	use crate::config::RateLimitConfig;
+
+	const MARKET: &str = "BTC-USDT";
+
+	/// Matching engine serving the real matching gRPC service in process,
+	/// at a sequence the test advances
+	struct FakeEngine {
+		endpoint: String,
+		storage: SharedEventStorage,
+		_ingress: QueueReceiver,
+	}
+
+	impl FakeEngine {
+		fn start() -> Self {
+			Self::start_with(ReplicationState::primary(0))
+		}
+
+		fn start_with(replication: ReplicationState) -> Self {
+			let storage = SharedEventStorage::new(Box::new(MemoryEventStorage::new()));
+			let (queue_sender, ingress) = IngressQueue::new(16).split();
+			let journal: Box<dyn OrderJournal> = Box::new(MemoryOrderJournal::new());
+			let service = MatchingServiceImpl::new(
+				queue_sender,
+				Arc::new(StdMutex::new(journal)),
+				MARKET.into(),
+			)
+			.with_event_hub(EventHub::new(storage.clone(), EventHubConfig::default()))
+			.with_replication(replication);
+
+			let incoming = TcpIncoming::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+			let endpoint = format!("http://{}", incoming.local_addr().unwrap());
+			tokio::spawn(
+				Server::builder()
+					.add_service(service.into_server())
+					.serve_with_incoming(incoming),
+			);
+
+			Self {
+				endpoint,
+				storage,
+				_ingress: ingress,
+			}
+		}
+
+		/// Commit events up to `sequence`
+		fn commit_through(&mut self, sequence: u64) {
+			let events = (self.storage.last_sequence() + 1..=sequence)
+				.map(|seq| MatchingEvent::OrderAccepted {
+					seq,
+					order_id: format!("order_{}", seq),
+					market: MARKET.to_string(),
+					public_key: "test_key".to_string(),
+					client_order_id: None,
+					side: Side::Buy,
+					price: 100,
+					size: 1,
+					timestamp: 0,
+				})
+				.collect();
+			self.storage.append_batch(EventBatch::new(events)).unwrap();
+		}
+	}
+
+	fn routes(endpoints: &[&FakeEngine]) -> MarketRoutes {
+		MarketRoutes {
+			matching_engines: HashMap::from([(
+				MARKET.to_string(),
+				endpoints
+					.iter()
+					.map(|engine| engine.endpoint.clone())
+					.collect(),
+			)]),
+			market_specs: HashMap::from([(
+				MARKET.to_string(),
+				MarketSpec::new(MARKET, 2, 8).unwrap(),
+			)]),
+		}
+	}
+
+	fn runtime_config(markets: MarketRoutes, failover_enabled: bool) -> GatewayRuntimeConfig {
+		GatewayRuntimeConfig {
+			bind_addr: "127.0.0.1:0".parse().unwrap(),
+			grpc_bind_addr: "127.0.0.1:0".parse().unwrap(),
+			fix_bind_addr: None,
+			fix_comp_id: "ANVIL".to_string(),
+			fix_sessions: HashMap::new(),
+			fix_store_dir: "data/fix".into(),
+			workers: 1,
+			max_body_bytes: 64 * 1024,
+			max_batch_orders: 20,
+			config_file: None,
+			config_reload_interval_ms: 1_000,
+			markets,
+			rate_limit: RateLimitConfig::default(),
+			dispatch_queue_capacity: 16,
+			dispatch_queue_timeout_ms: 1_000,
+			matching_rpc_timeout_ms: 1_000,
+			health_check_interval_ms: 1_000,
+			health_check_timeout_ms: 500,
+			health_failure_threshold: 3,
+			circuit_open_ms: 5_000,
+			failover_enabled,
+			ws_heartbeat_interval_ms: 15_000,
+			ws_client_timeout_ms: 45_000,
+			ws_messages_per_second: 50,
+			ws_message_burst: 100,
+			ws_feed_buffer: 1_024,
+		}
+	}
+
+	fn active_endpoint(dispatcher: &MatchingDispatcher) -> Result<String, DispatcherError> {
+		dispatcher.endpoint(MARKET)
+	}
+
+	#[tokio::test]
+	async fn fails_over_once_the_standby_has_caught_up() {
+		let mut primary = FakeEngine::start();
+		let mut standby = FakeEngine::start();
+		primary.commit_through(5);
+		standby.commit_through(3);
+		let dispatcher =
+			MatchingDispatcher::new(&runtime_config(routes(&[&primary, &standby]), true))
+				.await
+				.unwrap();
+
+		// A healthy primary's sequence becomes the acknowledged sequence
+		dispatcher.fail_over().await;
+		assert_eq!(active_endpoint(&dispatcher).unwrap(), primary.endpoint);
+
+		// The standby is behind, so the market waits rather than lose events
+		dispatcher.set_circuit(&primary.endpoint, CircuitState::Open);
+		dispatcher.fail_over().await;
+		assert!(matches!(
+			active_endpoint(&dispatcher),
+			Err(DispatcherError::MarketUnavailable(_))
+		));
+		assert_eq!(dispatcher.market_circuits()[0].endpoint, primary.endpoint);
+
+		standby.commit_through(5);
+		dispatcher.fail_over().await;
+		assert_eq!(active_endpoint(&dispatcher).unwrap(), standby.endpoint);
+		assert_eq!(
+			dispatcher.market_circuits(),
+			[MarketCircuit {
+				market: MARKET.to_string(),
+				endpoint: standby.endpoint.clone(),
+				circuit: CircuitState::Closed,
+			}]
+		);
+
+		// No failback to the recovered primary
+		dispatcher.set_circuit(&primary.endpoint, CircuitState::Closed);
+		dispatcher.fail_over().await;
+		assert_eq!(active_endpoint(&dispatcher).unwrap(), standby.endpoint);
+
+		// The new active engine carries the acknowledged sequence forward
+		standby.commit_through(8);
+		dispatcher.fail_over().await;
+		dispatcher.set_circuit(&standby.endpoint, CircuitState::Open);
+		dispatcher.fail_over().await;
+		assert!(active_endpoint(&dispatcher).is_err());
+		primary.commit_through(8);
+		dispatcher.fail_over().await;
+		assert_eq!(active_endpoint(&dispatcher).unwrap(), primary.endpoint);
+	}
+
+	#[tokio::test]
+	async fn does_not_fail_over_when_disabled_or_without_a_healthy_standby() {
+		let primary = FakeEngine::start();
+		let standby = FakeEngine::start();
+		let dispatcher =
+			MatchingDispatcher::new(&runtime_config(routes(&[&primary, &standby]), false))
+				.await
+				.unwrap();
+		dispatcher.set_circuit(&primary.endpoint, CircuitState::Open);
+		dispatcher.fail_over().await;
+		assert!(active_endpoint(&dispatcher).is_err());
+
+		let dispatcher =
+			MatchingDispatcher::new(&runtime_config(routes(&[&primary, &standby]), true))
+				.await
+				.unwrap();
+		dispatcher.set_circuit(&primary.endpoint, CircuitState::Open);
+		dispatcher.set_circuit(&standby.endpoint, CircuitState::HalfOpen);
+		dispatcher.fail_over().await;
+		assert!(active_endpoint(&dispatcher).is_err());
+		dispatcher.set_circuit(&standby.endpoint, CircuitState::Closed);
+		dispatcher.fail_over().await;
+		assert_eq!(active_endpoint(&dispatcher).unwrap(), standby.endpoint);
+	}
+
+	#[tokio::test]
+	async fn reload_keeps_a_listed_active_engine() {
+		let primary = FakeEngine::start();
+		let standby = FakeEngine::start();
+		let replacement = FakeEngine::start();
+		let dispatcher =
+			MatchingDispatcher::new(&runtime_config(routes(&[&primary, &standby]), true))
+				.await
+				.unwrap();
+		dispatcher.set_circuit(&primary.endpoint, CircuitState::Open);
+		dispatcher.fail_over().await;
+		assert_eq!(active_endpoint(&dispatcher).unwrap(), standby.endpoint);
+
+		dispatcher
+			.update_markets(routes(&[&primary, &standby, &replacement]))
+			.await;
+		assert_eq!(active_endpoint(&dispatcher).unwrap(), standby.endpoint);
+
+		dispatcher
+			.update_markets(routes(&[&replacement, &primary]))
+			.await;
+		assert_eq!(active_endpoint(&dispatcher).unwrap(), replacement.endpoint);
+	}
+
+	#[tokio::test]
+	async fn promotes_a_following_standby_and_skips_fenced_ones() {
+		let mut primary = FakeEngine::start_with(ReplicationState::primary(2));
+		let fenced = ReplicationState::primary(0);
+		fenced.fence(1).await.unwrap();
+		let mut fenced = FakeEngine::start_with(fenced);
+		let follower = ReplicationState::follower(1);
+		let mut standby = FakeEngine::start_with(follower.clone());
+		for engine in [&mut primary, &mut fenced, &mut standby] {
+			engine.commit_through(4);
+		}
+		let dispatcher = MatchingDispatcher::new(&runtime_config(
+			routes(&[&primary, &fenced, &standby]),
+			true,
+		))
+		.await
+		.unwrap();
+		dispatcher.fail_over().await;
+
+		// The follower is promoted past the primary's epoch before it serves
+		dispatcher.set_circuit(&primary.endpoint, CircuitState::Open);
+		dispatcher.fail_over().await;
+		assert_eq!(active_endpoint(&dispatcher).unwrap(), standby.endpoint);
+		let status = follower.status();
+		assert_eq!(
+			(status.role, status.epoch),
+			(anvil_matching::ReplicationRole::Primary, 3)
+		);
+	}
+}
@@ -0,0 +1,654 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! FIX application layer: order messages and execution reports
+//!
+//! [`Application`] translates NewOrderSingle, OrderCancelRequest and
+//! OrderCancelReplaceRequest into gateway requests and follows the orders
+//! entered through the session, so that execution reports from the matching
+//! engine can be answered with the counterparty's ClOrdIDs. Reports for
+//! orders entered through other APIs are not sent.
+//!
+//! An order is acknowledged (ExecType New) on its first execution report. A
+//! replacement is acknowledged with ExecType Replaced once the engine has
+//! cancelled the original order; if the cancel is rejected the replacement
+//! is answered with an OrderCancelReject instead.
+
+use std::collections::{HashMap, HashSet};
+
+use anvil_sdk::{
+	Amount,
+	decimal::{format_units, parse_units},
+	types::{ExecutionReport, ExecutionType, LiquidityRole, OrderType, PlaceOrderRequest, Side},
+};
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+use super::{
+	message::{Message, format_timestamp, msg_type, tag},
+	session::reject_reason,
+};
+
+/// A required field is missing or has a value the acceptor does not support
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum FieldError {
+	#[error("Required tag {0} missing")]
+	Missing(u32),
+	#[error("Value is incorrect (out of range) for tag {0}")]
+	Incorrect(u32),
+}
+
+impl FieldError {
+	pub fn tag(&self) -> u32 {
+		match self {
+			Self::Missing(tag) | Self::Incorrect(tag) => *tag,
+		}
+	}
+
+	/// SessionRejectReason for the session-level Reject
+	pub fn reject_reason(&self) -> u32 {
+		match self {
+			Self::Missing(_) => reject_reason::REQUIRED_TAG_MISSING,
+			Self::Incorrect(_) => reject_reason::VALUE_INCORRECT,
+		}
+	}
+}
+
+/// A NewOrderSingle or the replacement of an OrderCancelReplaceRequest
+#[derive(Debug, Clone)]
+pub struct OrderEntry {
+	pub cl_ord_id: String,
+	pub request: PlaceOrderRequest,
+}
+
+/// Cancel or replace awaiting the engine's outcome
+#[derive(Debug, Clone)]
+enum Pending {
+	Cancel { cl_ord_id: String },
+	Replace { cl_ord_id: String, order_id: String },
+}
+
+/// An order entered through the session
+#[derive(Debug, Clone)]
+struct FixOrder {
+	order_id: String,
+	cl_ord_id: String,
+	/// ClOrdID of the order this one replaced
+	orig_cl_ord_id: Option<String>,
+	symbol: String,
+	side: Side,
+	order_qty: u64,
+	price: Option<String>,
+	price_decimals: u32,
+	size_decimals: u32,
+	cum_qty: u64,
+	/// Sum of price times size over all fills, in market units
+	notional: u128,
+	acknowledged: bool,
+	pending: Option<Pending>,
+}
+
+impl FixOrder {
+	fn leaves_qty(&self) -> u64 {
+		self.order_qty.saturating_sub(self.cum_qty)
+	}
+
+	fn ord_status(&self) -> &'static str {
+		if self.cum_qty == 0 {
+			ord_status::NEW
+		} else if self.leaves_qty() == 0 {
+			ord_status::FILLED
+		} else {
+			ord_status::PARTIALLY_FILLED
+		}
+	}
+
+	fn avg_px(&self) -> String {
+		let avg = self.notional.checked_div(self.cum_qty as u128).unwrap_or(0);
+		format_units(avg, self.price_decimals)
+	}
+
+	fn qty(&self, units: u64) -> String {
+		format_units(units as u128, self.size_decimals)
+	}
+}
+
+/// OrdStatus and ExecType values
+mod ord_status {
+	pub const NEW: &str = "0";
+	pub const PARTIALLY_FILLED: &str = "1";
+	pub const FILLED: &str = "2";
+	pub const CANCELLED: &str = "4";
+	pub const REPLACED: &str = "5";
+	pub const REJECTED: &str = "8";
+	pub const TRADE: &str = "F";
+}
+
+/// CxlRejReason values
+const CXL_REJ_TOO_LATE: u32 = 0;
+const CXL_REJ_UNKNOWN_ORDER: u32 = 1;
+const CXL_REJ_ALREADY_PENDING: u32 = 3;
+pub const CXL_REJ_OTHER: u32 = 99;
+
+/// CxlRejResponseTo values
+pub const RESPONSE_TO_CANCEL: u32 = 1;
+pub const RESPONSE_TO_REPLACE: u32 = 2;
+
+/// Orders of one FIX session, kept across reconnects
+#[derive(Debug, Default)]
+pub struct Application {
+	orders: HashMap<String, FixOrder>,
+	/// ClOrdID -> order ID of open orders
+	cl_ord_ids: HashMap<String, String>,
+	/// Replacements whose reports are answered by an OrderCancelReject
+	suppressed: HashSet<String>,
+	/// Last report sequence handled per market, to resume after reconnects
+	last_sequence: HashMap<String, u64>,
+}
+
+impl Application {
+	/// Report sequence to resume `market` from; 0 for live reports only
+	pub fn resume_sequence(&self, market: &str) -> u64 {
+		self.last_sequence
+			.get(market)
+			.map(|sequence| sequence + 1)
+			.unwrap_or(0)
+	}
+
+	/// Parse a NewOrderSingle or the order fields of an
+	/// OrderCancelReplaceRequest
+	pub fn order_entry(message: &Message) -> Result<OrderEntry, FieldError> {
+		let cl_ord_id = required(message, tag::CL_ORD_ID)?;
+		let side = match required(message, tag::SIDE)? {
+			"1" => Side::Buy,
+			"2" => Side::Sell,
+			_ => return Err(FieldError::Incorrect(tag::SIDE)),
+		};
+		let order_type = match required(message, tag::ORD_TYPE)? {
+			"1" => OrderType::Market,
+			"2" => OrderType::Limit,
+			_ => return Err(FieldError::Incorrect(tag::ORD_TYPE)),
+		};
+		let price = match order_type {
+			OrderType::Limit => Some(Amount::Decimal(required(message, tag::PRICE)?.to_string())),
+			OrderType::Market => None,
+		};
+		Ok(OrderEntry {
+			cl_ord_id: cl_ord_id.to_string(),
+			request: PlaceOrderRequest {
+				market: required(message, tag::SYMBOL)?.to_string(),
+				side,
+				order_type,
+				price,
+				size: Amount::Decimal(required(message, tag::ORDER_QTY)?.to_string()),
+				client_order_id: Some(cl_ord_id.to_string()),
+			},
+		})
+	}
+
+	/// Whether `cl_ord_id` is already used by an open order
+	pub fn is_duplicate(&self, cl_ord_id: &str) -> bool {
+		self.cl_ord_ids.contains_key(cl_ord_id)
+	}
+
+	/// Resolve the order a cancel or replace refers to, by OrderID or
+	/// OrigClOrdID
+	///
+	/// Returns the order ID and market, or an OrderCancelReject when the
+	/// order is not known or already has a cancel or replace in flight.
+	pub fn target(&self, message: &Message, response_to: u32) -> Result<(String, String), Message> {
+		let order_id = message.get(tag::ORDER_ID).map(str::to_string).or_else(|| {
+			message
+				.get(tag::ORIG_CL_ORD_ID)
+				.and_then(|orig| self.cl_ord_ids.get(orig).cloned())
+		});
+		match order_id.as_deref().map(|id| (id, self.orders.get(id))) {
+			Some((order_id, Some(order))) if order.pending.is_none() => {
+				Ok((order_id.to_string(), order.symbol.clone()))
+			}
+			Some((_, Some(_))) => Err(cancel_reject(
+				message,
+				response_to,
+				CXL_REJ_ALREADY_PENDING,
+				"Order already has a cancel or replace pending",
+			)),
+			_ => Err(cancel_reject(
+				message,
+				response_to,
+				CXL_REJ_UNKNOWN_ORDER,
+				"Unknown order",
+			)),
+		}
+	}
+
+	/// Follow an order the gateway accepted for `entry`
+	pub fn order_accepted(
+		&mut self,
+		order_id: &str,
+		entry: &OrderEntry,
+		price_decimals: u32,
+		size_decimals: u32,
+	) {
+		let request = &entry.request;
+		let amount = |amount: &Amount, decimals: u32| match amount {
+			Amount::Units(units) => *units,
+			Amount::Decimal(value) => parse_units(value, decimals).unwrap_or(0),
+		};
+		let order = FixOrder {
+			order_id: order_id.to_string(),
+			cl_ord_id: entry.cl_ord_id.clone(),
+			orig_cl_ord_id: None,
+			symbol: request.market.clone(),
+			side: request.side,
+			order_qty: amount(&request.size, size_decimals),
+			price: request
+				.price
+				.as_ref()
+				.map(|price| format_units(amount(price, price_decimals) as u128, price_decimals)),
+			price_decimals,
+			size_decimals,
+			cum_qty: 0,
+			notional: 0,
+			acknowledged: false,
+			pending: None,
+		};
+		self.cl_ord_ids
+			.insert(entry.cl_ord_id.clone(), order_id.to_string());
+		self.orders.insert(order_id.to_string(), order);
+	}
+
+	/// Record a cancel the gateway accepted for `order_id`
+	pub fn cancel_accepted(&mut self, order_id: &str, cl_ord_id: &str) {
+		if let Some(order) = self.orders.get_mut(order_id) {
+			order.pending = Some(Pending::Cancel {
+				cl_ord_id: cl_ord_id.to_string(),
+			});
+		}
+	}
+
+	/// Record a replace the gateway accepted: `order_id` is replaced by
+	/// `replacement_id`, already followed through [`Self::order_accepted`]
+	pub fn replace_accepted(&mut self, order_id: &str, replacement_id: &str) {
+		let Some(orig_cl_ord_id) = self.orders.get(order_id).map(|o| o.cl_ord_id.clone()) else {
+			return;
+		};
+		let Some(replacement) = self.orders.get_mut(replacement_id) else {
+			return;
+		};
+		replacement.orig_cl_ord_id = Some(orig_cl_ord_id);
+		// Acknowledged by the Replaced report
+		replacement.acknowledged = true;
+		let cl_ord_id = replacement.cl_ord_id.clone();
+		if let Some(order) = self.orders.get_mut(order_id) {
+			order.pending = Some(Pending::Replace {
+				cl_ord_id,
+				order_id: replacement_id.to_string(),
+			});
+		}
+	}
+
+	/// Translate a matching execution report into the messages to send
+	pub fn on_report(&mut self, report: &ExecutionReport) -> Vec<Message> {
+		self.last_sequence
+			.insert(report.market.clone(), report.sequence);
+		if self.suppressed.remove(&report.order_id) {
+			return Vec::new();
+		}
+		let Some(mut order) = self.orders.get(&report.order_id).cloned() else {
+			return Vec::new();
+		};
+		let exec_id = format!("{}-{}", report.market, report.sequence);
+		let transact_time = DateTime::<Utc>::from_timestamp(report.timestamp as i64, 0)
+			.map(format_timestamp)
+			.unwrap_or_default();
+
+		let mut out = Vec::new();
+		if !order.acknowledged
+			&& !matches!(
+				report.exec_type,
+				ExecutionType::Rejected | ExecutionType::CancelRejected
+			) {
+			order.acknowledged = true;
+			out.push(
+				execution_report(&order, ord_status::NEW, &exec_id, &transact_time)
+					.with(tag::ORD_STATUS, ord_status::NEW),
+			);
+		}
+
+		match report.exec_type {
+			ExecutionType::Trade => {
+				let decimals = (order.price_decimals, order.size_decimals);
+				let price = report.price.as_deref().unwrap_or("0");
+				let size = report.size.as_deref().unwrap_or("0");
+				let price_units = parse_units(price, decimals.0).unwrap_or(0);
+				let size_units = parse_units(size, decimals.1).unwrap_or(0);
+				order.cum_qty += size_units;
+				order.notional += price_units as u128 * size_units as u128;
+				let liquidity = match report.role {
+					Some(LiquidityRole::Maker) => Some(1),
+					Some(LiquidityRole::Taker) => Some(2),
+					None => None,
+				};
+				out.push(
+					execution_report(&order, ord_status::TRADE, &exec_id, &transact_time)
+						.with(tag::ORD_STATUS, order.ord_status())
+						.with(tag::LAST_PX, price)
+						.with(tag::LAST_QTY, size)
+						.with_opt(tag::LAST_LIQUIDITY_IND, liquidity),
+				);
+			}
+			ExecutionType::Cancelled => match order.pending.take() {
+				Some(Pending::Replace { order_id, .. }) => {
+					if let Some(replacement) = self.orders.get(&order_id) {
+						out.push(
+							execution_report(
+								replacement,
+								ord_status::REPLACED,
+								&exec_id,
+								&transact_time,
+							)
+							.with(tag::ORD_STATUS, replacement.ord_status()),
+						);
+					}
+					self.remove(&report.order_id);
+					return out;
+				}
+				pending => {
+					if let Some(Pending::Cancel { cl_ord_id }) = pending {
+						order.orig_cl_ord_id =
+							Some(std::mem::replace(&mut order.cl_ord_id, cl_ord_id));
+					}
+					out.push(
+						execution_report(&order, ord_status::CANCELLED, &exec_id, &transact_time)
+							.with(tag::ORD_STATUS, ord_status::CANCELLED),
+					);
+					self.remove(&report.order_id);
+					return out;
+				}
+			},
+			ExecutionType::CancelRejected => {
+				let reason = report.reason.as_deref().unwrap_or("Cancel rejected");
+				let (cl_ord_id, response_to) = match order.pending.take() {
+					Some(Pending::Cancel { cl_ord_id }) => (cl_ord_id, RESPONSE_TO_CANCEL),
+					Some(Pending::Replace {
+						cl_ord_id,
+						order_id,
+					}) => {
+						self.remove(&order_id);
+						self.suppressed.insert(order_id);
+						(cl_ord_id, RESPONSE_TO_REPLACE)
+					}
+					None => (order.cl_ord_id.clone(), RESPONSE_TO_CANCEL),
+				};
+				out.push(
+					Message::new(msg_type::ORDER_CANCEL_REJECT)
+						.with(tag::ORDER_ID, &report.order_id)
+						.with(tag::CL_ORD_ID, cl_ord_id)
+						.with(tag::ORIG_CL_ORD_ID, &order.cl_ord_id)
+						.with(tag::ORD_STATUS, order.ord_status())
+						.with(tag::CXL_REJ_RESPONSE_TO, response_to)
+						.with(tag::CXL_REJ_REASON, CXL_REJ_TOO_LATE)
+						.with(tag::TEXT, reason),
+				);
+			}
+			ExecutionType::Rejected => {
+				let reason = report.reason.as_deref().unwrap_or("Order rejected");
+				out.push(
+					execution_report(&order, ord_status::REJECTED, &exec_id, &transact_time)
+						.with(tag::ORD_STATUS, ord_status::REJECTED)
+						.with(tag::TEXT, reason),
+				);
+				self.remove(&report.order_id);
+				return out;
+			}
+			// Fill totals are tracked from the trades
+			ExecutionType::New | ExecutionType::PartiallyFilled | ExecutionType::Filled => {}
+		}
+
+		if order.leaves_qty() == 0 {
+			self.remove(&report.order_id);
+		} else {
+			self.orders.insert(report.order_id.clone(), order);
+		}
+		out
+	}
+
+	fn remove(&mut self, order_id: &str) {
+		if let Some(order) = self.orders.remove(order_id) {
+			self.cl_ord_ids.remove(&order.cl_ord_id);
+		}
+	}
+}
+
+/// ExecutionReport for `order` with ExecType `exec_type`; OrdStatus is
+/// added by the caller
+fn execution_report(
+	order: &FixOrder,
+	exec_type: &str,
+	exec_id: &str,
+	transact_time: &str,
+) -> Message {
+	Message::new(msg_type::EXECUTION_REPORT)
+		.with(tag::ORDER_ID, &order.order_id)
+		.with(tag::CL_ORD_ID, &order.cl_ord_id)
+		.with_opt(tag::ORIG_CL_ORD_ID, order.orig_cl_ord_id.as_ref())
+		.with(tag::EXEC_ID, exec_id)
+		.with(tag::EXEC_TYPE, exec_type)
+		.with(tag::SYMBOL, &order.symbol)
+		.with(
+			tag::SIDE,
+			match order.side {
+				Side::Buy => "1",
+				Side::Sell => "2",
+			},
+		)
+		.with(tag::ORDER_QTY, order.qty(order.order_qty))
+		.with_opt(tag::PRICE, order.price.as_ref())
+		.with(tag::LEAVES_QTY, order.qty(order.leaves_qty()))
+		.with(tag::CUM_QTY, order.qty(order.cum_qty))
+		.with(tag::AVG_PX, order.avg_px())
+		.with(tag::TRANSACT_TIME, transact_time)
+}
+
+/// ExecutionReport rejecting an order entry the gateway refused
+pub fn order_rejected(entry: &OrderEntry, reason: &str) -> Message {
+	let request = &entry.request;
+	let amount = |amount: &Amount| match amount {
+		Amount::Units(units) => units.to_string(),
+		Amount::Decimal(value) => value.clone(),
+	};
+	Message::new(msg_type::EXECUTION_REPORT)
+		.with(tag::ORDER_ID, "NONE")
+		.with(tag::CL_ORD_ID, &entry.cl_ord_id)
+		.with(tag::EXEC_ID, format!("REJ-{}", entry.cl_ord_id))
+		.with(tag::EXEC_TYPE, ord_status::REJECTED)
+		.with(tag::ORD_STATUS, ord_status::REJECTED)
+		.with(tag::SYMBOL, &request.market)
+		.with(
+			tag::SIDE,
+			match request.side {
+				Side::Buy => "1",
+				Side::Sell => "2",
+			},
+		)
+		.with(tag::ORDER_QTY, amount(&request.size))
+		.with(tag::LEAVES_QTY, 0)
+		.with(tag::CUM_QTY, 0)
+		.with(tag::AVG_PX, 0)
+		.with(tag::TEXT, reason)
+}
+
+/// OrderCancelReject answering `message` directly, without an engine
+/// outcome
+pub fn cancel_reject(message: &Message, response_to: u32, reason: u32, text: &str) -> Message {
+	Message::new(msg_type::ORDER_CANCEL_REJECT)
+		.with(tag::ORDER_ID, message.get(tag::ORDER_ID).unwrap_or("NONE"))
+		.with(
+			tag::CL_ORD_ID,
+			message.get(tag::CL_ORD_ID).unwrap_or_default(),
+		)
+		.with(
+			tag::ORIG_CL_ORD_ID,
+			message.get(tag::ORIG_CL_ORD_ID).unwrap_or_default(),
+		)
+		.with(tag::ORD_STATUS, ord_status::REJECTED)
+		.with(tag::CXL_REJ_RESPONSE_TO, response_to)
+		.with(tag::CXL_REJ_REASON, reason)
+		.with(tag::TEXT, text)
+}
+
+/// Non-empty value of a required field
+pub fn required(message: &Message, tag: u32) -> Result<&str, FieldError> {
+	message
+		.get(tag)
+		.filter(|value| !value.is_empty())
+		.ok_or(FieldError::Missing(tag))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn entry(cl_ord_id: &str, price: &str, qty: &str) -> OrderEntry {
+		let message = Message::new(msg_type::NEW_ORDER_SINGLE)
+			.with(tag::CL_ORD_ID, cl_ord_id)
+			.with(tag::SYMBOL, "BTC-USDT")
+			.with(tag::SIDE, "1")
+			.with(tag::ORDER_QTY, qty)
+			.with(tag::ORD_TYPE, "2")
+			.with(tag::PRICE, price);
+		Application::order_entry(&message).unwrap()
+	}
+
+	fn report(sequence: u64, order_id: &str, exec_type: ExecutionType) -> ExecutionReport {
+		ExecutionReport {
+			sequence,
+			market: "BTC-USDT".to_string(),
+			order_id: order_id.to_string(),
+			client_order_id: None,
+			exec_type,
+			price: None,
+			size: None,
+			filled_size: None,
+			remaining_size: None,
+			trade_id: None,
+			role: None,
+			reason: None,
+			timestamp: 1_700_000_000,
+		}
+	}
+
+	fn trade(sequence: u64, order_id: &str, price: &str, size: &str) -> ExecutionReport {
+		ExecutionReport {
+			price: Some(price.to_string()),
+			size: Some(size.to_string()),
+			role: Some(LiquidityRole::Taker),
+			..report(sequence, order_id, ExecutionType::Trade)
+		}
+	}
+
+	#[test]
+	fn rejects_entries_without_required_fields() {
+		let message = Message::new(msg_type::NEW_ORDER_SINGLE)
+			.with(tag::CL_ORD_ID, "c1")
+			.with(tag::SIDE, "1")
+			.with(tag::ORD_TYPE, "2");
+		assert_eq!(
+			Application::order_entry(&message).unwrap_err(),
+			FieldError::Missing(tag::PRICE)
+		);
+		let message = message.with(tag::PRICE, "1").with(tag::SYMBOL, "BTC-USDT");
+		assert_eq!(
+			Application::order_entry(&message).unwrap_err(),
+			FieldError::Missing(tag::ORDER_QTY)
+		);
+	}
+
+	#[test]
+	fn acknowledges_then_reports_fills_with_average_price() {
+		let mut app = Application::default();
+		app.order_accepted("o1", &entry("c1", "101.00", "2"), 2, 8);
+
+		let out = app.on_report(&trade(4, "o1", "100.00", "0.5"));
+		assert_eq!(out.len(), 2);
+		assert_eq!(out[0].get(tag::EXEC_TYPE), Some(ord_status::NEW));
+		assert_eq!(out[1].get(tag::EXEC_TYPE), Some(ord_status::TRADE));
+		assert_eq!(
+			out[1].get(tag::ORD_STATUS),
+			Some(ord_status::PARTIALLY_FILLED)
+		);
+		assert_eq!(out[1].get(tag::EXEC_ID), Some("BTC-USDT-4"));
+		assert_eq!(out[1].get(tag::CUM_QTY), Some("0.50000000"));
+		assert_eq!(out[1].get(tag::LEAVES_QTY), Some("1.50000000"));
+		assert_eq!(out[1].get(tag::LAST_LIQUIDITY_IND), Some("2"));
+
+		// Resting and fill summary reports add nothing
+		assert!(
+			app.on_report(&report(5, "o1", ExecutionType::New))
+				.is_empty()
+		);
+
+		let out = app.on_report(&trade(6, "o1", "101.00", "1.5"));
+		assert_eq!(out[0].get(tag::ORD_STATUS), Some(ord_status::FILLED));
+		assert_eq!(out[0].get(tag::AVG_PX), Some("100.75"));
+		assert!(!app.is_duplicate("c1"));
+		assert_eq!(app.resume_sequence("BTC-USDT"), 7);
+	}
+
+	#[test]
+	fn replace_is_reported_once_the_original_is_cancelled() {
+		let mut app = Application::default();
+		app.order_accepted("o1", &entry("c1", "100", "1"), 2, 8);
+		app.on_report(&report(1, "o1", ExecutionType::New));
+		app.order_accepted("o2", &entry("c2", "99", "1"), 2, 8);
+		app.replace_accepted("o1", "o2");
+
+		let out = app.on_report(&report(2, "o1", ExecutionType::Cancelled));
+		assert_eq!(out.len(), 1);
+		assert_eq!(out[0].get(tag::EXEC_TYPE), Some(ord_status::REPLACED));
+		assert_eq!(out[0].get(tag::ORDER_ID), Some("o2"));
+		assert_eq!(out[0].get(tag::CL_ORD_ID), Some("c2"));
+		assert_eq!(out[0].get(tag::ORIG_CL_ORD_ID), Some("c1"));
+		assert!(
+			app.on_report(&report(3, "o2", ExecutionType::New))
+				.is_empty()
+		);
+		assert!(!app.is_duplicate("c1"));
+	}
+
+	#[test]
+	fn failed_replace_is_answered_with_a_cancel_reject() {
+		let mut app = Application::default();
+		app.order_accepted("o1", &entry("c1", "100", "1"), 2, 8);
+		app.order_accepted("o2", &entry("c2", "99", "1"), 2, 8);
+		app.replace_accepted("o1", "o2");
+
+		let rejected = ExecutionReport {
+			reason: Some("Order not found".to_string()),
+			..report(2, "o1", ExecutionType::CancelRejected)
+		};
+		let out = app.on_report(&rejected);
+		assert_eq!(out[0].msg_type(), msg_type::ORDER_CANCEL_REJECT);
+		assert_eq!(out[0].get(tag::CL_ORD_ID), Some("c2"));
+		assert_eq!(
+			out[0].get_u64(tag::CXL_REJ_RESPONSE_TO),
+			Some(RESPONSE_TO_REPLACE as u64)
+		);
+		assert!(
+			app.on_report(&report(3, "o2", ExecutionType::Rejected))
+				.is_empty()
+		);
+		assert!(app.is_duplicate("c1"));
+	}
+}
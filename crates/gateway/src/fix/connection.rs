@@ -0,0 +1,459 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! One FIX connection: Logon, then a loop over socket reads, execution
+//! reports and timer ticks
+//!
+//! Inbound messages are handled one at a time, so an order is recorded by
+//! the application layer before any execution report for it is processed.
+
+use std::{
+	io,
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+use tokio::{
+	io::{AsyncReadExt, AsyncWriteExt},
+	net::{TcpStream, tcp::OwnedReadHalf, tcp::OwnedWriteHalf},
+	sync::mpsc,
+	task::JoinHandle,
+};
+
+use crate::{
+	auth::AuthenticatedPrincipal,
+	handlers,
+	server::GatewayState,
+	ws::{self, LoginMaterials},
+};
+
+use super::{
+	FixConfig, LOGON_METHOD, Shared,
+	application::{
+		self, Application, CXL_REJ_OTHER, FieldError, OrderEntry, RESPONSE_TO_CANCEL,
+		RESPONSE_TO_REPLACE, required,
+	},
+	message::{self, Frame, Message, msg_type, tag},
+	session::{Inbound, Session},
+	store::SessionStore,
+};
+
+/// Time allowed between connecting and the Logon
+const LOGON_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Interval of heartbeat and liveness checks
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Largest accepted unparsed input in bytes
+const MAX_MESSAGE_BYTES: usize = 64 * 1024;
+
+/// BusinessRejectReason: unsupported message type
+const UNSUPPORTED_MESSAGE_TYPE: u32 = 3;
+
+/// Execution report forwarders, aborted with the connection
+struct Forwarders(Vec<JoinHandle<()>>);
+
+impl Drop for Forwarders {
+	fn drop(&mut self) {
+		for forwarder in &self.0 {
+			forwarder.abort();
+		}
+	}
+}
+
+/// A Logon that passed authentication
+struct Logon {
+	message: Message,
+	comp_id: String,
+	principal: AuthenticatedPrincipal,
+	heartbeat_interval: Duration,
+}
+
+pub(super) async fn run(stream: TcpStream, shared: Arc<Shared>) {
+	let (mut reader, mut writer) = stream.into_split();
+	let mut buf = Vec::new();
+
+	let message =
+		match tokio::time::timeout(LOGON_TIMEOUT, read_message(&mut reader, &mut buf)).await {
+			Ok(Some(message)) => message,
+			_ => return,
+		};
+	let logon = match authenticate(&shared, message) {
+		Ok(logon) => logon,
+		Err(reason) => {
+			tracing::warn!(target: "server::fix", reason = %reason, "FIX Logon refused");
+			return;
+		}
+	};
+	let Some(mut application) = shared.claim(&logon.comp_id) else {
+		tracing::warn!(
+			target: "server::fix",
+			comp_id = %logon.comp_id,
+			"FIX Logon refused: session already logged on"
+		);
+		return;
+	};
+
+	let comp_id = logon.comp_id.clone();
+	tracing::info!(target: "server::fix", comp_id = %comp_id, "FIX session logged on");
+	let result = run_session(
+		&shared,
+		logon,
+		&mut reader,
+		&mut writer,
+		&mut buf,
+		&mut application,
+	)
+	.await;
+	shared.release(&comp_id, application);
+
+	match result {
+		Ok(reason) => {
+			tracing::info!(target: "server::fix", comp_id = %comp_id, reason = %reason, "FIX session ended")
+		}
+		Err(e) => {
+			tracing::warn!(target: "server::fix", comp_id = %comp_id, error = %e, "FIX session failed")
+		}
+	}
+	let _ = writer.shutdown().await;
+}
+
+/// Check a Logon's CompIDs and heartbeat interval and verify its signature
+fn authenticate(shared: &Shared, message: Message) -> Result<Logon, String> {
+	if message.msg_type() != msg_type::LOGON {
+		return Err(format!("Expected Logon, received {}", message.msg_type()));
+	}
+	let config = &shared.config;
+	if message.get(tag::TARGET_COMP_ID) != Some(config.comp_id.as_str()) {
+		return Err("Unknown TargetCompID".to_string());
+	}
+	let comp_id = message
+		.get(tag::SENDER_COMP_ID)
+		.ok_or("SenderCompID missing")?
+		.to_string();
+	let public_key = config
+		.sessions
+		.get(&comp_id)
+		.ok_or_else(|| format!("Unknown SenderCompID {}", comp_id))?;
+	let heartbeat_interval = message
+		.get_u64(tag::HEART_BT_INT)
+		.filter(|secs| *secs > 0)
+		.map(Duration::from_secs)
+		.ok_or("HeartBtInt missing or invalid")?;
+	let sending_time = message
+		.get(tag::SENDING_TIME)
+		.ok_or("SendingTime missing")?;
+	let timestamp = message::parse_timestamp(sending_time)
+		.and_then(|time| u64::try_from(time.timestamp()).ok())
+		.ok_or("Invalid SendingTime")?;
+	let signature = message.get(tag::RAW_DATA).ok_or("RawData missing")?;
+
+	let materials = LoginMaterials {
+		public_key: public_key.clone(),
+		signature: signature.to_string(),
+		timestamp,
+		nonce: sending_time.to_string(),
+		algorithm: None,
+	};
+	let principal = ws::authenticate_login(
+		&materials,
+		LOGON_METHOD,
+		&FixConfig::logon_path(&comp_id),
+		shared.state.auth_provider.as_ref(),
+		&ws::new_context(),
+	)
+	.map_err(|e| e.to_string())?;
+
+	Ok(Logon {
+		message,
+		comp_id,
+		principal,
+		heartbeat_interval,
+	})
+}
+
+/// Run a logged-on session until it ends, returning why
+async fn run_session(
+	shared: &Arc<Shared>,
+	logon: Logon,
+	reader: &mut OwnedReadHalf,
+	writer: &mut OwnedWriteHalf,
+	buf: &mut Vec<u8>,
+	application: &mut Application,
+) -> io::Result<String> {
+	let config = &shared.config;
+	let store = SessionStore::open(&config.store_dir, &config.comp_id, &logon.comp_id)?;
+	let now = Instant::now();
+	let mut session = Session::new(
+		&config.comp_id,
+		&logon.comp_id,
+		store,
+		logon.heartbeat_interval,
+		now,
+	);
+	let inbound = session.accept_logon(&logon.message, now)?;
+	flush(writer, &mut session).await?;
+	if let Inbound::Disconnect(reason) = inbound {
+		return Ok(reason);
+	}
+
+	let state = &shared.state;
+	let principal_id = logon.principal.principal.id();
+	let (reports_tx, mut reports_rx) = mpsc::channel(256);
+	let _forwarders = Forwarders(
+		state
+			.dispatcher
+			.markets()
+			.into_iter()
+			.map(|market| {
+				let from_sequence = application.resume_sequence(&market);
+				tokio::spawn(ws::forward_reports(
+					state.clone(),
+					market,
+					principal_id.clone(),
+					from_sequence,
+					reports_tx.clone(),
+				))
+			})
+			.collect(),
+	);
+	let mut tick = tokio::time::interval(TICK_INTERVAL);
+
+	loop {
+		let disconnect = tokio::select! {
+			read = reader.read_buf(buf) => {
+				if read? == 0 {
+					return Ok("Connection closed".to_string());
+				}
+				let mut disconnect = None;
+				while let Some((frame, used)) = message::decode(buf) {
+					buf.drain(..used);
+					let message = match frame {
+						Frame::Message(message) => message,
+						Frame::Garbled(reason) => {
+							tracing::debug!(target: "server::fix", reason = %reason, "Garbled FIX message ignored");
+							continue;
+						}
+					};
+					match session.on_message(message, Instant::now())? {
+						Inbound::Application(message) => {
+							handle_application(state, &mut session, application, &logon.principal, message)
+								.await?
+						}
+						Inbound::Handled => {}
+						Inbound::Disconnect(reason) => {
+							disconnect = Some(reason);
+							break;
+						}
+					}
+				}
+				if disconnect.is_none() && buf.len() > MAX_MESSAGE_BYTES {
+					disconnect = Some("Message too large".to_string());
+				}
+				disconnect
+			}
+			Some(report) = reports_rx.recv() => {
+				for message in application.on_report(&report) {
+					session.send(message, Instant::now())?;
+				}
+				None
+			}
+			_ = tick.tick() => session.on_tick(Instant::now())?,
+		};
+		flush(writer, &mut session).await?;
+		if let Some(reason) = disconnect {
+			return Ok(reason);
+		}
+	}
+}
+
+/// Handle an in-sequence application message
+async fn handle_application(
+	state: &Arc<GatewayState>,
+	session: &mut Session,
+	application: &mut Application,
+	principal: &AuthenticatedPrincipal,
+	message: Message,
+) -> io::Result<()> {
+	let replies = match message.msg_type() {
+		msg_type::NEW_ORDER_SINGLE => new_order(state, application, principal, &message).await,
+		msg_type::ORDER_CANCEL_REQUEST => cancel(state, application, principal, &message).await,
+		msg_type::ORDER_CANCEL_REPLACE_REQUEST => {
+			replace(state, application, principal, &message).await
+		}
+		other => Ok(vec![
+			Message::new(msg_type::BUSINESS_MESSAGE_REJECT)
+				.with_opt(tag::REF_SEQ_NUM, message.seq_num())
+				.with(tag::REF_MSG_TYPE, other)
+				.with(tag::BUSINESS_REJECT_REASON, UNSUPPORTED_MESSAGE_TYPE)
+				.with(tag::TEXT, "Unsupported Message Type"),
+		]),
+	};
+
+	match replies {
+		Ok(replies) => {
+			for reply in replies {
+				session.send(reply, Instant::now())?;
+			}
+			Ok(())
+		}
+		Err(e) => session.reject(
+			&message,
+			e.reject_reason(),
+			Some(e.tag()),
+			&e.to_string(),
+			Instant::now(),
+		),
+	}
+}
+
+async fn new_order(
+	state: &Arc<GatewayState>,
+	application: &mut Application,
+	principal: &AuthenticatedPrincipal,
+	message: &Message,
+) -> Result<Vec<Message>, FieldError> {
+	let entry = Application::order_entry(message)?;
+	if application.is_duplicate(&entry.cl_ord_id) {
+		return Ok(vec![application::order_rejected(
+			&entry,
+			"Duplicate ClOrdID",
+		)]);
+	}
+
+	let context = ws::new_context();
+	let authenticated = with_replay_key(principal, &entry.cl_ord_id);
+	match handlers::submit_order(state, &entry.request, &authenticated, None, &context).await {
+		Ok(response) => {
+			accept(state, application, &response.order_id, &entry);
+			Ok(Vec::new())
+		}
+		Err(err) => Ok(vec![application::order_rejected(&entry, &err.to_string())]),
+	}
+}
+
+async fn cancel(
+	state: &Arc<GatewayState>,
+	application: &mut Application,
+	principal: &AuthenticatedPrincipal,
+	message: &Message,
+) -> Result<Vec<Message>, FieldError> {
+	let cl_ord_id = required(message, tag::CL_ORD_ID)?;
+	required(message, tag::ORIG_CL_ORD_ID)?;
+	let (order_id, market) = match application.target(message, RESPONSE_TO_CANCEL) {
+		Ok(target) => target,
+		Err(reject) => return Ok(vec![reject]),
+	};
+
+	let context = ws::new_context();
+	let authenticated = with_replay_key(principal, cl_ord_id);
+	match handlers::submit_cancel(state, &market, &order_id, &authenticated, &context).await {
+		Ok(_) => {
+			application.cancel_accepted(&order_id, cl_ord_id);
+			Ok(Vec::new())
+		}
+		Err(err) => Ok(vec![application::cancel_reject(
+			message,
+			RESPONSE_TO_CANCEL,
+			CXL_REJ_OTHER,
+			&err.to_string(),
+		)]),
+	}
+}
+
+async fn replace(
+	state: &Arc<GatewayState>,
+	application: &mut Application,
+	principal: &AuthenticatedPrincipal,
+	message: &Message,
+) -> Result<Vec<Message>, FieldError> {
+	let entry = Application::order_entry(message)?;
+	required(message, tag::ORIG_CL_ORD_ID)?;
+	let reject =
+		|text: &str| application::cancel_reject(message, RESPONSE_TO_REPLACE, CXL_REJ_OTHER, text);
+	if application.is_duplicate(&entry.cl_ord_id) {
+		return Ok(vec![reject("Duplicate ClOrdID")]);
+	}
+	let (order_id, market) = match application.target(message, RESPONSE_TO_REPLACE) {
+		Ok(target) => target,
+		Err(reject) => return Ok(vec![reject]),
+	};
+	if entry.request.market != market {
+		return Ok(vec![reject("Symbol does not match the original order")]);
+	}
+
+	let context = ws::new_context();
+	let authenticated = with_replay_key(principal, &entry.cl_ord_id);
+	match handlers::submit_order(
+		state,
+		&entry.request,
+		&authenticated,
+		Some(order_id.clone()),
+		&context,
+	)
+	.await
+	{
+		Ok(response) => {
+			accept(state, application, &response.order_id, &entry);
+			application.replace_accepted(&order_id, &response.order_id);
+			Ok(Vec::new())
+		}
+		Err(err) => Ok(vec![reject(&err.to_string())]),
+	}
+}
+
+fn accept(state: &GatewayState, application: &mut Application, order_id: &str, entry: &OrderEntry) {
+	let (price_decimals, size_decimals) = handlers::market_decimals(state, &entry.request.market);
+	application.order_accepted(order_id, entry, price_decimals, size_decimals);
+}
+
+/// The session's principal with a request's replay key: the current time
+/// and the request's ClOrdID
+fn with_replay_key(principal: &AuthenticatedPrincipal, cl_ord_id: &str) -> AuthenticatedPrincipal {
+	let timestamp = std::time::SystemTime::now()
+		.duration_since(std::time::UNIX_EPOCH)
+		.map(|elapsed| elapsed.as_secs())
+		.unwrap_or_default();
+	AuthenticatedPrincipal {
+		principal: principal.principal.clone(),
+		timestamp,
+		nonce: format!("fix-{}", cl_ord_id),
+	}
+}
+
+/// Read the first well-formed message, skipping garbled input
+async fn read_message(reader: &mut OwnedReadHalf, buf: &mut Vec<u8>) -> Option<Message> {
+	loop {
+		while let Some((frame, used)) = message::decode(buf) {
+			buf.drain(..used);
+			if let Frame::Message(message) = frame {
+				return Some(message);
+			}
+		}
+		if buf.len() > MAX_MESSAGE_BYTES {
+			return None;
+		}
+		match reader.read_buf(buf).await {
+			Ok(0) | Err(_) => return None,
+			Ok(_) => {}
+		}
+	}
+}
+
+async fn flush(writer: &mut OwnedWriteHalf, session: &mut Session) -> io::Result<()> {
+	for message in session.drain_outbox() {
+		writer.write_all(&message.encode()).await?;
+	}
+	Ok(())
+}
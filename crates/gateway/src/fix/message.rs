@@ -0,0 +1,359 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! FIX tag=value messages and framing
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+/// Field delimiter
+pub const SOH: u8 = 0x01;
+
+/// The only supported protocol version
+pub const BEGIN_STRING: &str = "FIX.4.4";
+
+/// Field tags used by the acceptor
+pub mod tag {
+	pub const AVG_PX: u32 = 6;
+	pub const BEGIN_SEQ_NO: u32 = 7;
+	pub const BEGIN_STRING: u32 = 8;
+	pub const BODY_LENGTH: u32 = 9;
+	pub const CHECKSUM: u32 = 10;
+	pub const CL_ORD_ID: u32 = 11;
+	pub const CUM_QTY: u32 = 14;
+	pub const END_SEQ_NO: u32 = 16;
+	pub const EXEC_ID: u32 = 17;
+	pub const LAST_PX: u32 = 31;
+	pub const LAST_QTY: u32 = 32;
+	pub const MSG_SEQ_NUM: u32 = 34;
+	pub const MSG_TYPE: u32 = 35;
+	pub const NEW_SEQ_NO: u32 = 36;
+	pub const ORDER_ID: u32 = 37;
+	pub const ORDER_QTY: u32 = 38;
+	pub const ORD_STATUS: u32 = 39;
+	pub const ORD_TYPE: u32 = 40;
+	pub const ORIG_CL_ORD_ID: u32 = 41;
+	pub const POSS_DUP_FLAG: u32 = 43;
+	pub const PRICE: u32 = 44;
+	pub const REF_SEQ_NUM: u32 = 45;
+	pub const SENDER_COMP_ID: u32 = 49;
+	pub const SENDING_TIME: u32 = 52;
+	pub const SIDE: u32 = 54;
+	pub const SYMBOL: u32 = 55;
+	pub const TARGET_COMP_ID: u32 = 56;
+	pub const TEXT: u32 = 58;
+	pub const TRANSACT_TIME: u32 = 60;
+	pub const RAW_DATA: u32 = 96;
+	pub const ENCRYPT_METHOD: u32 = 98;
+	pub const CXL_REJ_REASON: u32 = 102;
+	pub const HEART_BT_INT: u32 = 108;
+	pub const TEST_REQ_ID: u32 = 112;
+	pub const ORIG_SENDING_TIME: u32 = 122;
+	pub const GAP_FILL_FLAG: u32 = 123;
+	pub const RESET_SEQ_NUM_FLAG: u32 = 141;
+	pub const EXEC_TYPE: u32 = 150;
+	pub const LEAVES_QTY: u32 = 151;
+	pub const REF_TAG_ID: u32 = 371;
+	pub const REF_MSG_TYPE: u32 = 372;
+	pub const SESSION_REJECT_REASON: u32 = 373;
+	pub const BUSINESS_REJECT_REASON: u32 = 380;
+	pub const CXL_REJ_RESPONSE_TO: u32 = 434;
+	pub const LAST_LIQUIDITY_IND: u32 = 851;
+}
+
+/// Message types used by the acceptor
+pub mod msg_type {
+	pub const HEARTBEAT: &str = "0";
+	pub const TEST_REQUEST: &str = "1";
+	pub const RESEND_REQUEST: &str = "2";
+	pub const REJECT: &str = "3";
+	pub const SEQUENCE_RESET: &str = "4";
+	pub const LOGOUT: &str = "5";
+	pub const EXECUTION_REPORT: &str = "8";
+	pub const ORDER_CANCEL_REJECT: &str = "9";
+	pub const LOGON: &str = "A";
+	pub const NEW_ORDER_SINGLE: &str = "D";
+	pub const ORDER_CANCEL_REQUEST: &str = "F";
+	pub const ORDER_CANCEL_REPLACE_REQUEST: &str = "G";
+	pub const BUSINESS_MESSAGE_REJECT: &str = "j";
+
+	/// Session-level messages; every other type carries application data
+	pub fn is_admin(msg_type: &str) -> bool {
+		matches!(
+			msg_type,
+			HEARTBEAT | TEST_REQUEST | RESEND_REQUEST | REJECT | SEQUENCE_RESET | LOGOUT | LOGON
+		)
+	}
+}
+
+/// Header fields stamped by the session, in the order they are written
+const HEADER_TAGS: [u32; 6] = [
+	tag::SENDER_COMP_ID,
+	tag::TARGET_COMP_ID,
+	tag::MSG_SEQ_NUM,
+	tag::POSS_DUP_FLAG,
+	tag::SENDING_TIME,
+	tag::ORIG_SENDING_TIME,
+];
+
+/// A FIX message: its type plus header and body fields in wire order
+///
+/// BeginString, BodyLength and CheckSum are produced by [`Message::encode`]
+/// and are not part of `fields`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+	msg_type: String,
+	fields: Vec<(u32, String)>,
+}
+
+impl Message {
+	pub fn new(msg_type: &str) -> Self {
+		Self {
+			msg_type: msg_type.to_string(),
+			fields: Vec::new(),
+		}
+	}
+
+	pub fn msg_type(&self) -> &str {
+		&self.msg_type
+	}
+
+	/// Append a field
+	pub fn with(mut self, tag: u32, value: impl ToString) -> Self {
+		self.fields.push((tag, value.to_string()));
+		self
+	}
+
+	/// Append a field if `value` is present
+	pub fn with_opt(self, tag: u32, value: Option<impl ToString>) -> Self {
+		match value {
+			Some(value) => self.with(tag, value),
+			None => self,
+		}
+	}
+
+	/// First value of `tag`
+	pub fn get(&self, tag: u32) -> Option<&str> {
+		self.fields
+			.iter()
+			.find(|(t, _)| *t == tag)
+			.map(|(_, value)| value.as_str())
+	}
+
+	pub fn get_u64(&self, tag: u32) -> Option<u64> {
+		self.get(tag).and_then(|value| value.parse().ok())
+	}
+
+	/// A `Y`/`N` field; absent means `N`
+	pub fn get_flag(&self, tag: u32) -> bool {
+		self.get(tag) == Some("Y")
+	}
+
+	pub fn seq_num(&self) -> Option<u64> {
+		self.get_u64(tag::MSG_SEQ_NUM)
+	}
+
+	/// Replace the header fields, keeping the body
+	pub fn set_header(&mut self, header: &[(u32, String)]) {
+		self.fields.retain(|(tag, _)| !HEADER_TAGS.contains(tag));
+		let mut header = header.to_vec();
+		header.sort_by_key(|(tag, _)| HEADER_TAGS.iter().position(|t| t == tag));
+		self.fields.splice(0..0, header);
+	}
+
+	/// Wire encoding with BeginString, BodyLength and CheckSum
+	pub fn encode(&self) -> Vec<u8> {
+		let mut body = Vec::new();
+		write_field(&mut body, tag::MSG_TYPE, &self.msg_type);
+		for (tag, value) in &self.fields {
+			write_field(&mut body, *tag, value);
+		}
+
+		let mut out = Vec::with_capacity(body.len() + 32);
+		write_field(&mut out, tag::BEGIN_STRING, BEGIN_STRING);
+		write_field(&mut out, tag::BODY_LENGTH, &body.len().to_string());
+		out.extend_from_slice(&body);
+		let checksum = checksum(&out);
+		write_field(&mut out, tag::CHECKSUM, &format!("{:03}", checksum));
+		out
+	}
+}
+
+fn write_field(out: &mut Vec<u8>, tag: u32, value: &str) {
+	out.extend_from_slice(tag.to_string().as_bytes());
+	out.push(b'=');
+	out.extend_from_slice(value.as_bytes());
+	out.push(SOH);
+}
+
+fn checksum(bytes: &[u8]) -> u8 {
+	bytes.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+}
+
+/// Result of reading one frame from the front of a buffer
+#[derive(Debug, PartialEq, Eq)]
+pub enum Frame {
+	/// A well-formed message
+	Message(Message),
+	/// Bytes that do not form a valid message; they are discarded
+	Garbled(String),
+}
+
+/// Read the first frame of `buf`
+///
+/// Returns the frame and the number of bytes it used, or `None` when more
+/// bytes are needed.
+pub fn decode(buf: &[u8]) -> Option<(Frame, usize)> {
+	let prefix = format!("8={}\x019=", BEGIN_STRING);
+	if buf.len() < prefix.len() {
+		return None;
+	}
+	if !buf.starts_with(prefix.as_bytes()) {
+		// Resynchronise on the next BeginString
+		let skip = find(&buf[1..], b"8=FIX")
+			.map(|i| i + 1)
+			.unwrap_or(buf.len());
+		return Some((Frame::Garbled("Invalid BeginString".to_string()), skip));
+	}
+
+	let length_end = prefix.len() + find(&buf[prefix.len()..], &[SOH])?;
+	let Some(body_length) = std::str::from_utf8(&buf[prefix.len()..length_end])
+		.ok()
+		.and_then(|length| length.parse::<usize>().ok())
+	else {
+		return Some((
+			Frame::Garbled("Invalid BodyLength".to_string()),
+			length_end + 1,
+		));
+	};
+
+	let body_start = length_end + 1;
+	let body_end = body_start + body_length;
+	// CheckSum is always "10=NNN<SOH>"
+	let total = body_end + 7;
+	if buf.len() < total {
+		return None;
+	}
+
+	let trailer = &buf[body_end..total];
+	let expected = format!("10={:03}\x01", checksum(&buf[..body_end]));
+	if trailer != expected.as_bytes() {
+		return Some((Frame::Garbled("Invalid CheckSum".to_string()), total));
+	}
+
+	let frame = match parse_fields(&buf[body_start..body_end]) {
+		Ok(message) => Frame::Message(message),
+		Err(reason) => Frame::Garbled(reason),
+	};
+	Some((frame, total))
+}
+
+fn parse_fields(body: &[u8]) -> Result<Message, String> {
+	let body = std::str::from_utf8(body).map_err(|_| "Body is not UTF-8".to_string())?;
+	let mut fields = body
+		.strip_suffix('\x01')
+		.ok_or_else(|| "Body does not end with a delimiter".to_string())?
+		.split('\x01')
+		.map(|field| {
+			let (tag, value) = field
+				.split_once('=')
+				.ok_or_else(|| format!("Malformed field: {}", field))?;
+			let tag = tag
+				.parse::<u32>()
+				.map_err(|_| format!("Malformed tag: {}", tag))?;
+			Ok((tag, value.to_string()))
+		})
+		.collect::<Result<Vec<_>, String>>()?;
+
+	if fields.first().map(|(tag, _)| *tag) != Some(tag::MSG_TYPE) {
+		return Err("MsgType must be the third field".to_string());
+	}
+	let (_, msg_type) = fields.remove(0);
+	Ok(Message { msg_type, fields })
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+	haystack
+		.windows(needle.len())
+		.position(|window| window == needle)
+}
+
+/// UTCTimestamp with milliseconds, e.g. `20250101-12:30:00.000`
+pub fn format_timestamp(time: DateTime<Utc>) -> String {
+	time.format("%Y%m%d-%H:%M:%S%.3f").to_string()
+}
+
+/// Parse a UTCTimestamp with or without fractional seconds
+pub fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+	NaiveDateTime::parse_from_str(value, "%Y%m%d-%H:%M:%S%.f")
+		.ok()
+		.map(|time| time.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn wire(text: &str) -> Vec<u8> {
+		text.replace('|', "\x01").into_bytes()
+	}
+
+	#[test]
+	fn encodes_length_and_checksum() {
+		let mut message = Message::new(msg_type::HEARTBEAT);
+		message.set_header(&[
+			(tag::MSG_SEQ_NUM, "1".to_string()),
+			(tag::SENDER_COMP_ID, "ANVIL".to_string()),
+			(tag::TARGET_COMP_ID, "CLIENT".to_string()),
+			(tag::SENDING_TIME, "20250101-00:00:00.000".to_string()),
+		]);
+
+		let bytes = message.encode();
+		let body = "35=0|49=ANVIL|56=CLIENT|34=1|52=20250101-00:00:00.000|";
+		let head = format!("8=FIX.4.4|9={}|{}", body.len(), body);
+		let sum = checksum(&wire(&head));
+		assert_eq!(bytes, wire(&format!("{}10={:03}|", head, sum)));
+
+		let (frame, used) = decode(&bytes).unwrap();
+		assert_eq!(used, bytes.len());
+		assert_eq!(frame, Frame::Message(message));
+	}
+
+	#[test]
+	fn waits_for_a_complete_frame() {
+		let bytes = Message::new(msg_type::HEARTBEAT)
+			.with(tag::TEST_REQ_ID, "t")
+			.encode();
+		assert_eq!(decode(&bytes[..bytes.len() - 1]), None);
+	}
+
+	#[test]
+	fn discards_frames_with_a_bad_checksum() {
+		let mut bytes = Message::new(msg_type::HEARTBEAT).encode();
+		let len = bytes.len();
+		bytes[len - 2] = b'0' + (bytes[len - 2] - b'0' + 1) % 10;
+		bytes.extend(Message::new(msg_type::LOGOUT).encode());
+
+		let (frame, used) = decode(&bytes).unwrap();
+		assert!(matches!(frame, Frame::Garbled(_)));
+		let (frame, _) = decode(&bytes[used..]).unwrap();
+		assert!(matches!(frame, Frame::Message(m) if m.msg_type() == msg_type::LOGOUT));
+	}
+
+	#[test]
+	fn parses_timestamps_with_and_without_millis() {
+		let time = parse_timestamp("20250102-03:04:05.678").unwrap();
+		assert_eq!(format_timestamp(time), "20250102-03:04:05.678");
+		assert!(parse_timestamp("20250102-03:04:05").is_some());
+	}
+}
@@ -0,0 +1,146 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! FIX 4.4 acceptor for institutional order entry
+//!
+//! Counterparties connect over TCP and log on with their registered
+//! SenderCompID; the acceptor's own CompID is the TargetCompID. Each CompID
+//! maps to the public key of the principal it trades as. The Logon carries
+//! a hex-encoded signature in RawData (96) over
+//! [`anvil_sdk::signing::query_signing_message`] for method `LOGON`, path
+//! `FIX.4.4/<SenderCompID>`, the Logon's SendingTime in Unix seconds and the
+//! SendingTime field as sent as nonce. Logons that fail authentication are
+//! disconnected without a reply.
+//!
+//! The session layer handles Heartbeat, TestRequest, ResendRequest,
+//! SequenceReset and Logout, and keeps sequence numbers and sent messages
+//! in a file store so that sessions resume across reconnects and restarts.
+//!
+//! NewOrderSingle, OrderCancelRequest and OrderCancelReplaceRequest go
+//! through the same rate limit, admission, replay and dispatch path as REST
+//! orders. Prices and quantities are decimals in market scale. Execution
+//! reports follow the principal's matching execution reports for the orders
+//! entered through the session.
+
+mod application;
+mod connection;
+mod message;
+mod session;
+mod store;
+
+use std::{
+	collections::HashMap,
+	net::SocketAddr,
+	path::PathBuf,
+	sync::{Arc, Mutex},
+};
+
+use anyhow::Context;
+use tokio::net::TcpListener;
+
+use crate::{config::GatewayRuntimeConfig, server::GatewayState};
+
+use self::application::Application;
+
+/// Method signed by a FIX Logon
+pub const LOGON_METHOD: &str = "LOGON";
+
+/// Acceptor settings
+#[derive(Debug, Clone)]
+pub struct FixConfig {
+	pub bind_addr: SocketAddr,
+	/// Our CompID, the TargetCompID of every session
+	pub comp_id: String,
+	/// Counterparty CompID -> hex-encoded public key it trades as
+	pub sessions: HashMap<String, String>,
+	/// Directory of the session stores
+	pub store_dir: PathBuf,
+}
+
+impl FixConfig {
+	/// `None` when no FIX bind address is configured
+	pub fn from_runtime(config: &GatewayRuntimeConfig) -> Option<Self> {
+		Some(Self {
+			bind_addr: config.fix_bind_addr?,
+			comp_id: config.fix_comp_id.clone(),
+			sessions: config.fix_sessions.clone(),
+			store_dir: config.fix_store_dir.clone(),
+		})
+	}
+
+	/// Path signed by the Logon of `sender_comp_id`
+	fn logon_path(sender_comp_id: &str) -> String {
+		format!("{}/{}", message::BEGIN_STRING, sender_comp_id)
+	}
+}
+
+/// State shared by the acceptor's connections
+struct Shared {
+	state: Arc<GatewayState>,
+	config: FixConfig,
+	/// Application state per counterparty CompID; `None` while a connection
+	/// of that session holds it
+	applications: Mutex<HashMap<String, Option<Application>>>,
+}
+
+impl Shared {
+	/// Take the application state of `comp_id`, unless it is logged on
+	fn claim(&self, comp_id: &str) -> Option<Application> {
+		self.applications
+			.lock()
+			.unwrap()
+			.entry(comp_id.to_string())
+			.or_insert_with(|| Some(Application::default()))
+			.take()
+	}
+
+	fn release(&self, comp_id: &str, application: Application) {
+		self.applications
+			.lock()
+			.unwrap()
+			.insert(comp_id.to_string(), Some(application));
+	}
+}
+
+/// FIX acceptor
+pub struct FixAcceptor {
+	shared: Arc<Shared>,
+}
+
+impl FixAcceptor {
+	pub fn new(state: Arc<GatewayState>, config: FixConfig) -> Self {
+		Self {
+			shared: Arc::new(Shared {
+				state,
+				config,
+				applications: Mutex::new(HashMap::new()),
+			}),
+		}
+	}
+
+	/// Accept connections until the listener fails
+	pub async fn run(self) -> anyhow::Result<()> {
+		let listener = TcpListener::bind(self.shared.config.bind_addr)
+			.await
+			.context("Failed to bind FIX acceptor")?;
+		loop {
+			let (stream, peer) = listener
+				.accept()
+				.await
+				.context("Failed to accept FIX connection")?;
+			tracing::debug!(target: "server::fix", peer = %peer, "FIX connection accepted");
+			tokio::spawn(connection::run(stream, self.shared.clone()));
+		}
+	}
+}
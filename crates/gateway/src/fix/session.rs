@@ -0,0 +1,512 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! FIX session layer: sequence numbers, gap recovery and heartbeats
+//!
+//! [`Session`] is driven by the connection with inbound messages and timer
+//! ticks and queues the stamped messages to write. Application messages are
+//! handed back to the caller once they are in sequence.
+
+use std::{
+	io,
+	time::{Duration, Instant},
+};
+
+use chrono::Utc;
+
+use super::{
+	message::{Message, format_timestamp, msg_type, tag},
+	store::SessionStore,
+};
+
+/// SessionRejectReason values
+pub mod reject_reason {
+	pub const REQUIRED_TAG_MISSING: u32 = 1;
+	pub const VALUE_INCORRECT: u32 = 5;
+	pub const COMP_ID_PROBLEM: u32 = 9;
+}
+
+/// What the connection should do after an inbound message
+#[derive(Debug, PartialEq, Eq)]
+pub enum Inbound {
+	/// An in-sequence application message
+	Application(Message),
+	/// Handled by the session layer
+	Handled,
+	/// Write the queued messages, then close the connection
+	Disconnect(String),
+}
+
+/// State of one logged-on session
+pub struct Session {
+	ours: String,
+	theirs: String,
+	store: SessionStore,
+	heartbeat_interval: Duration,
+	last_sent: Instant,
+	last_received: Instant,
+	/// TestReqID awaiting a heartbeat
+	test_request: Option<String>,
+	/// Highest inbound sequence number seen while a resend is outstanding
+	resend_until: Option<u64>,
+	/// Stamped messages waiting to be written
+	outbox: Vec<Message>,
+}
+
+impl Session {
+	pub fn new(
+		ours: &str,
+		theirs: &str,
+		store: SessionStore,
+		heartbeat_interval: Duration,
+		now: Instant,
+	) -> Self {
+		Self {
+			ours: ours.to_string(),
+			theirs: theirs.to_string(),
+			store,
+			heartbeat_interval,
+			last_sent: now,
+			last_received: now,
+			test_request: None,
+			resend_until: None,
+			outbox: Vec::new(),
+		}
+	}
+
+	/// Take the messages queued for writing
+	pub fn drain_outbox(&mut self) -> Vec<Message> {
+		std::mem::take(&mut self.outbox)
+	}
+
+	/// Stamp `message` with the next sequence number and queue it
+	pub fn send(&mut self, mut message: Message, now: Instant) -> io::Result<()> {
+		message.set_header(&[
+			(tag::SENDER_COMP_ID, self.ours.clone()),
+			(tag::TARGET_COMP_ID, self.theirs.clone()),
+			(tag::MSG_SEQ_NUM, self.store.next_sender_seq().to_string()),
+			(tag::SENDING_TIME, format_timestamp(Utc::now())),
+		]);
+		self.store.record_sent(&message)?;
+		self.last_sent = now;
+		self.outbox.push(message);
+		Ok(())
+	}
+
+	/// Answer the counterparty's Logon
+	///
+	/// The Logon has been authenticated by the caller. A sequence number
+	/// above the expected one is accepted and followed by a resend request.
+	pub fn accept_logon(&mut self, logon: &Message, now: Instant) -> io::Result<Inbound> {
+		self.last_received = now;
+		let reset = logon.get_flag(tag::RESET_SEQ_NUM_FLAG);
+		if reset {
+			self.store.reset()?;
+		}
+
+		let Some(seq) = logon.seq_num() else {
+			return self.logout_and_disconnect("MsgSeqNum missing", now);
+		};
+		let expected = self.store.next_target_seq();
+		if seq < expected {
+			return self.too_low(expected, seq, now);
+		}
+
+		let mut response = Message::new(msg_type::LOGON)
+			.with(tag::ENCRYPT_METHOD, 0)
+			.with(tag::HEART_BT_INT, self.heartbeat_interval.as_secs());
+		if reset {
+			response = response.with(tag::RESET_SEQ_NUM_FLAG, "Y");
+		}
+		self.send(response, now)?;
+
+		if seq > expected {
+			self.request_resend(expected, seq, now)?;
+		} else {
+			self.store.set_next_target_seq(seq + 1)?;
+		}
+		Ok(Inbound::Handled)
+	}
+
+	/// Process an inbound message received after Logon
+	pub fn on_message(&mut self, message: Message, now: Instant) -> io::Result<Inbound> {
+		self.last_received = now;
+		self.test_request = None;
+
+		if message.get(tag::SENDER_COMP_ID) != Some(self.theirs.as_str())
+			|| message.get(tag::TARGET_COMP_ID) != Some(self.ours.as_str())
+		{
+			self.reject(
+				&message,
+				reject_reason::COMP_ID_PROBLEM,
+				None,
+				"CompID problem",
+				now,
+			)?;
+			return self.logout_and_disconnect("CompID problem", now);
+		}
+		let Some(seq) = message.seq_num() else {
+			return self.logout_and_disconnect("MsgSeqNum missing", now);
+		};
+
+		if message.msg_type() == msg_type::SEQUENCE_RESET && !message.get_flag(tag::GAP_FILL_FLAG) {
+			return self.sequence_reset(&message, now);
+		}
+
+		let expected = self.store.next_target_seq();
+		if seq < expected {
+			if message.get_flag(tag::POSS_DUP_FLAG) {
+				return Ok(Inbound::Handled);
+			}
+			return self.too_low(expected, seq, now);
+		}
+		if seq > expected {
+			// Resend requests are served even across a gap; everything else
+			// arrives again once the gap is filled
+			match message.msg_type() {
+				msg_type::RESEND_REQUEST => self.resend(&message, now)?,
+				msg_type::LOGOUT => return self.logout_and_disconnect("Logout", now),
+				_ => {}
+			}
+			if self.resend_until.is_none() {
+				self.request_resend(expected, seq, now)?;
+			} else {
+				self.resend_until = self.resend_until.max(Some(seq));
+			}
+			return Ok(Inbound::Handled);
+		}
+
+		self.store.set_next_target_seq(seq + 1)?;
+		if self.resend_until.is_some_and(|until| seq >= until) {
+			self.resend_until = None;
+		}
+
+		match message.msg_type() {
+			msg_type::HEARTBEAT | msg_type::REJECT => Ok(Inbound::Handled),
+			msg_type::TEST_REQUEST => {
+				let heartbeat = Message::new(msg_type::HEARTBEAT)
+					.with_opt(tag::TEST_REQ_ID, message.get(tag::TEST_REQ_ID));
+				self.send(heartbeat, now)?;
+				Ok(Inbound::Handled)
+			}
+			msg_type::RESEND_REQUEST => {
+				self.resend(&message, now)?;
+				Ok(Inbound::Handled)
+			}
+			msg_type::SEQUENCE_RESET => self.sequence_reset(&message, now),
+			msg_type::LOGOUT => self.logout_and_disconnect("Logout", now),
+			msg_type::LOGON => self.logout_and_disconnect("Unexpected Logon", now),
+			_ => Ok(Inbound::Application(message)),
+		}
+	}
+
+	/// Heartbeat and liveness checks; returns a reason to disconnect
+	pub fn on_tick(&mut self, now: Instant) -> io::Result<Option<String>> {
+		let silence = now.duration_since(self.last_received);
+		if self.test_request.is_some() && silence >= self.heartbeat_interval * 2 {
+			return Ok(Some("Heartbeat timeout".to_string()));
+		}
+		if self.test_request.is_none() && silence >= self.heartbeat_interval.mul_f32(1.2) {
+			let id = format!("TEST-{}", self.store.next_sender_seq());
+			self.send(
+				Message::new(msg_type::TEST_REQUEST).with(tag::TEST_REQ_ID, &id),
+				now,
+			)?;
+			self.test_request = Some(id);
+		}
+		if now.duration_since(self.last_sent) >= self.heartbeat_interval {
+			self.send(Message::new(msg_type::HEARTBEAT), now)?;
+		}
+		Ok(None)
+	}
+
+	/// Queue a session-level Reject of `message`
+	pub fn reject(
+		&mut self,
+		message: &Message,
+		reason: u32,
+		ref_tag: Option<u32>,
+		text: &str,
+		now: Instant,
+	) -> io::Result<()> {
+		let reject = Message::new(msg_type::REJECT)
+			.with_opt(tag::REF_SEQ_NUM, message.seq_num())
+			.with_opt(tag::REF_TAG_ID, ref_tag)
+			.with(tag::REF_MSG_TYPE, message.msg_type())
+			.with(tag::SESSION_REJECT_REASON, reason)
+			.with(tag::TEXT, text);
+		self.send(reject, now)
+	}
+
+	/// Queue a Logout and ask the connection to close
+	pub fn logout_and_disconnect(&mut self, text: &str, now: Instant) -> io::Result<Inbound> {
+		self.send(Message::new(msg_type::LOGOUT).with(tag::TEXT, text), now)?;
+		Ok(Inbound::Disconnect(text.to_string()))
+	}
+
+	fn too_low(&mut self, expected: u64, received: u64, now: Instant) -> io::Result<Inbound> {
+		let text = format!(
+			"MsgSeqNum too low, expecting {} but received {}",
+			expected, received
+		);
+		self.logout_and_disconnect(&text, now)
+	}
+
+	fn request_resend(&mut self, from: u64, received: u64, now: Instant) -> io::Result<()> {
+		self.resend_until = Some(received);
+		let request = Message::new(msg_type::RESEND_REQUEST)
+			.with(tag::BEGIN_SEQ_NO, from)
+			.with(tag::END_SEQ_NO, 0);
+		self.send(request, now)
+	}
+
+	/// SequenceReset: gap fill when in sequence, or reset mode at any time
+	fn sequence_reset(&mut self, message: &Message, now: Instant) -> io::Result<Inbound> {
+		let Some(new_seq) = message.get_u64(tag::NEW_SEQ_NO) else {
+			self.reject(
+				message,
+				reject_reason::REQUIRED_TAG_MISSING,
+				Some(tag::NEW_SEQ_NO),
+				"NewSeqNo missing",
+				now,
+			)?;
+			return Ok(Inbound::Handled);
+		};
+		let expected = self.store.next_target_seq();
+		if new_seq < expected {
+			self.reject(
+				message,
+				reject_reason::VALUE_INCORRECT,
+				Some(tag::NEW_SEQ_NO),
+				"NewSeqNo below the expected sequence number",
+				now,
+			)?;
+			return Ok(Inbound::Handled);
+		}
+		self.store.set_next_target_seq(new_seq)?;
+		if self.resend_until.is_some_and(|until| new_seq > until) {
+			self.resend_until = None;
+		}
+		Ok(Inbound::Handled)
+	}
+
+	/// Serve a ResendRequest: stored application messages go out again as
+	/// possible duplicates, everything else is covered by gap fills
+	fn resend(&mut self, request: &Message, now: Instant) -> io::Result<()> {
+		let (Some(begin), Some(end)) = (
+			request.get_u64(tag::BEGIN_SEQ_NO),
+			request.get_u64(tag::END_SEQ_NO),
+		) else {
+			return self.reject(
+				request,
+				reject_reason::REQUIRED_TAG_MISSING,
+				Some(tag::BEGIN_SEQ_NO),
+				"BeginSeqNo and EndSeqNo are required",
+				now,
+			);
+		};
+		let last_sent = self.store.next_sender_seq() - 1;
+		let end = if end == 0 || end > last_sent {
+			last_sent
+		} else {
+			end
+		};
+
+		let mut gap_start = None;
+		for seq in begin.max(1)..=end {
+			let Some(stored) = self.store.sent(seq).cloned() else {
+				gap_start.get_or_insert(seq);
+				continue;
+			};
+			if let Some(start) = gap_start.take() {
+				self.gap_fill(start, seq);
+			}
+			let original_time = stored
+				.get(tag::SENDING_TIME)
+				.unwrap_or_default()
+				.to_string();
+			let mut resent = stored;
+			resent.set_header(&[
+				(tag::SENDER_COMP_ID, self.ours.clone()),
+				(tag::TARGET_COMP_ID, self.theirs.clone()),
+				(tag::MSG_SEQ_NUM, seq.to_string()),
+				(tag::POSS_DUP_FLAG, "Y".to_string()),
+				(tag::SENDING_TIME, format_timestamp(Utc::now())),
+				(tag::ORIG_SENDING_TIME, original_time),
+			]);
+			self.outbox.push(resent);
+		}
+		if let Some(start) = gap_start {
+			self.gap_fill(start, end + 1);
+		}
+		self.last_sent = now;
+		Ok(())
+	}
+
+	fn gap_fill(&mut self, seq: u64, new_seq: u64) {
+		let now = format_timestamp(Utc::now());
+		let mut gap_fill = Message::new(msg_type::SEQUENCE_RESET)
+			.with(tag::GAP_FILL_FLAG, "Y")
+			.with(tag::NEW_SEQ_NO, new_seq);
+		gap_fill.set_header(&[
+			(tag::SENDER_COMP_ID, self.ours.clone()),
+			(tag::TARGET_COMP_ID, self.theirs.clone()),
+			(tag::MSG_SEQ_NUM, seq.to_string()),
+			(tag::POSS_DUP_FLAG, "Y".to_string()),
+			(tag::SENDING_TIME, now.clone()),
+			(tag::ORIG_SENDING_TIME, now),
+		]);
+		self.outbox.push(gap_fill);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const INTERVAL: Duration = Duration::from_secs(30);
+
+	struct Fixture {
+		session: Session,
+		dir: std::path::PathBuf,
+		now: Instant,
+	}
+
+	impl Drop for Fixture {
+		fn drop(&mut self) {
+			let _ = std::fs::remove_dir_all(&self.dir);
+		}
+	}
+
+	fn logged_on() -> Fixture {
+		let dir = std::env::temp_dir().join(format!("anvil-fix-{}", uuid::Uuid::new_v4()));
+		let store = SessionStore::open(&dir, "ANVIL", "CLIENT").unwrap();
+		let now = Instant::now();
+		let mut session = Session::new("ANVIL", "CLIENT", store, INTERVAL, now);
+		let logon = inbound(msg_type::LOGON, 1);
+		assert_eq!(session.accept_logon(&logon, now).unwrap(), Inbound::Handled);
+		assert_eq!(session.drain_outbox()[0].msg_type(), msg_type::LOGON);
+		Fixture { session, dir, now }
+	}
+
+	fn inbound(msg_type: &str, seq: u64) -> Message {
+		let mut message = Message::new(msg_type);
+		message.set_header(&[
+			(tag::SENDER_COMP_ID, "CLIENT".to_string()),
+			(tag::TARGET_COMP_ID, "ANVIL".to_string()),
+			(tag::MSG_SEQ_NUM, seq.to_string()),
+		]);
+		message
+	}
+
+	#[test]
+	fn requests_resend_on_gap_and_accepts_gap_fill() {
+		let mut f = logged_on();
+		let ahead = inbound(msg_type::NEW_ORDER_SINGLE, 5);
+		assert_eq!(
+			f.session.on_message(ahead.clone(), f.now).unwrap(),
+			Inbound::Handled
+		);
+		let sent = f.session.drain_outbox();
+		assert_eq!(sent.len(), 1);
+		assert_eq!(sent[0].msg_type(), msg_type::RESEND_REQUEST);
+		assert_eq!(sent[0].get_u64(tag::BEGIN_SEQ_NO), Some(2));
+
+		// No second request while the first is outstanding
+		f.session
+			.on_message(inbound(msg_type::HEARTBEAT, 6), f.now)
+			.unwrap();
+		assert!(f.session.drain_outbox().is_empty());
+
+		let gap_fill = inbound(msg_type::SEQUENCE_RESET, 2)
+			.with(tag::GAP_FILL_FLAG, "Y")
+			.with(tag::NEW_SEQ_NO, 5);
+		f.session.on_message(gap_fill, f.now).unwrap();
+		let mut resent = ahead;
+		resent.set_header(&[
+			(tag::SENDER_COMP_ID, "CLIENT".to_string()),
+			(tag::TARGET_COMP_ID, "ANVIL".to_string()),
+			(tag::MSG_SEQ_NUM, "5".to_string()),
+			(tag::POSS_DUP_FLAG, "Y".to_string()),
+		]);
+		assert!(matches!(
+			f.session.on_message(resent, f.now).unwrap(),
+			Inbound::Application(_)
+		));
+	}
+
+	#[test]
+	fn too_low_without_poss_dup_logs_out() {
+		let mut f = logged_on();
+		f.session
+			.on_message(inbound(msg_type::HEARTBEAT, 2), f.now)
+			.unwrap();
+
+		let duplicate = inbound(msg_type::HEARTBEAT, 2).with(tag::POSS_DUP_FLAG, "Y");
+		assert_eq!(
+			f.session.on_message(duplicate, f.now).unwrap(),
+			Inbound::Handled
+		);
+		assert!(matches!(
+			f.session
+				.on_message(inbound(msg_type::HEARTBEAT, 2), f.now)
+				.unwrap(),
+			Inbound::Disconnect(_)
+		));
+		assert_eq!(f.session.drain_outbox()[0].msg_type(), msg_type::LOGOUT);
+	}
+
+	#[test]
+	fn resends_application_messages_and_gap_fills_the_rest() {
+		let mut f = logged_on();
+		// 1 was the Logon response
+		f.session
+			.send(Message::new(msg_type::HEARTBEAT), f.now)
+			.unwrap();
+		f.session
+			.send(
+				Message::new(msg_type::EXECUTION_REPORT).with(tag::ORDER_ID, "o1"),
+				f.now,
+			)
+			.unwrap();
+		f.session.drain_outbox();
+
+		let request = inbound(msg_type::RESEND_REQUEST, 2)
+			.with(tag::BEGIN_SEQ_NO, 1)
+			.with(tag::END_SEQ_NO, 0);
+		f.session.on_message(request, f.now).unwrap();
+		let sent = f.session.drain_outbox();
+
+		assert_eq!(sent.len(), 2);
+		assert_eq!(sent[0].msg_type(), msg_type::SEQUENCE_RESET);
+		assert_eq!(sent[0].seq_num(), Some(1));
+		assert_eq!(sent[0].get_u64(tag::NEW_SEQ_NO), Some(3));
+		assert_eq!(sent[1].msg_type(), msg_type::EXECUTION_REPORT);
+		assert_eq!(sent[1].seq_num(), Some(3));
+		assert!(sent[1].get_flag(tag::POSS_DUP_FLAG));
+		assert!(sent[1].get(tag::ORIG_SENDING_TIME).is_some());
+	}
+
+	#[test]
+	fn sends_test_request_then_times_out() {
+		let mut f = logged_on();
+		let later = f.now + INTERVAL.mul_f32(1.3);
+		assert_eq!(f.session.on_tick(later).unwrap(), None);
+		let sent = f.session.drain_outbox();
+		assert_eq!(sent.len(), 1);
+		assert_eq!(sent[0].msg_type(), msg_type::TEST_REQUEST);
+
+		assert!(f.session.on_tick(f.now + INTERVAL * 2).unwrap().is_some());
+	}
+}
@@ -0,0 +1,187 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Persistent FIX session state
+//!
+//! Each session keeps two files in the store directory, named after the
+//! CompID pair:
+//!
+//! - `{ours}-{theirs}.seqnums`: the next outbound and next expected inbound
+//!   sequence numbers, rewritten through a temporary file on every change
+//! - `{ours}-{theirs}.messages`: every outbound application message as sent,
+//!   for resend requests
+//!
+//! Both survive reconnects and restarts until the counterparty logs on with
+//! ResetSeqNumFlag.
+
+use std::{
+	collections::BTreeMap,
+	fs::{self, File, OpenOptions},
+	io::{self, Write},
+	path::{Path, PathBuf},
+};
+
+use super::message::{self, Frame, Message};
+
+/// Sequence numbers and sent messages of one session
+#[derive(Debug)]
+pub struct SessionStore {
+	seqnums_path: PathBuf,
+	messages_path: PathBuf,
+	messages_file: File,
+	next_sender_seq: u64,
+	next_target_seq: u64,
+	/// Sent application messages by sequence number
+	messages: BTreeMap<u64, Message>,
+}
+
+impl SessionStore {
+	/// Open or create the store of the `ours`/`theirs` session in `dir`
+	pub fn open(dir: &Path, ours: &str, theirs: &str) -> io::Result<Self> {
+		fs::create_dir_all(dir)?;
+		let seqnums_path = dir.join(format!("{}-{}.seqnums", ours, theirs));
+		let messages_path = dir.join(format!("{}-{}.messages", ours, theirs));
+
+		let (next_sender_seq, next_target_seq) = match fs::read_to_string(&seqnums_path) {
+			Ok(contents) => parse_seqnums(&contents).ok_or_else(|| {
+				io::Error::new(
+					io::ErrorKind::InvalidData,
+					format!("Corrupt sequence file {}", seqnums_path.display()),
+				)
+			})?,
+			Err(e) if e.kind() == io::ErrorKind::NotFound => (1, 1),
+			Err(e) => return Err(e),
+		};
+
+		let mut messages = BTreeMap::new();
+		if let Ok(bytes) = fs::read(&messages_path) {
+			let mut offset = 0;
+			while let Some((frame, used)) = message::decode(&bytes[offset..]) {
+				offset += used;
+				if let Frame::Message(message) = frame
+					&& let Some(seq) = message.seq_num()
+				{
+					messages.insert(seq, message);
+				}
+			}
+		}
+
+		let messages_file = OpenOptions::new()
+			.create(true)
+			.append(true)
+			.open(&messages_path)?;
+
+		Ok(Self {
+			seqnums_path,
+			messages_path,
+			messages_file,
+			next_sender_seq,
+			next_target_seq,
+			messages,
+		})
+	}
+
+	/// Sequence number of the next outbound message
+	pub fn next_sender_seq(&self) -> u64 {
+		self.next_sender_seq
+	}
+
+	/// Sequence number expected on the next inbound message
+	pub fn next_target_seq(&self) -> u64 {
+		self.next_target_seq
+	}
+
+	/// Record an outbound message that used the next sender sequence number
+	pub fn record_sent(&mut self, message: &Message) -> io::Result<()> {
+		if !message::msg_type::is_admin(message.msg_type()) {
+			self.messages_file.write_all(&message.encode())?;
+			self.messages.insert(self.next_sender_seq, message.clone());
+		}
+		self.next_sender_seq += 1;
+		self.persist()
+	}
+
+	pub fn set_next_target_seq(&mut self, seq: u64) -> io::Result<()> {
+		self.next_target_seq = seq;
+		self.persist()
+	}
+
+	/// Stored application message sent with `seq`
+	pub fn sent(&self, seq: u64) -> Option<&Message> {
+		self.messages.get(&seq)
+	}
+
+	/// Start both directions over at 1 and drop the sent messages
+	pub fn reset(&mut self) -> io::Result<()> {
+		self.messages_file = File::create(&self.messages_path)?;
+		self.messages.clear();
+		self.next_sender_seq = 1;
+		self.next_target_seq = 1;
+		self.persist()
+	}
+
+	fn persist(&self) -> io::Result<()> {
+		let tmp = self.seqnums_path.with_extension("seqnums.tmp");
+		fs::write(
+			&tmp,
+			format!("{} {}\n", self.next_sender_seq, self.next_target_seq),
+		)?;
+		fs::rename(&tmp, &self.seqnums_path)
+	}
+}
+
+fn parse_seqnums(contents: &str) -> Option<(u64, u64)> {
+	let mut parts = contents.split_whitespace().map(|part| part.parse().ok());
+	Some((parts.next()??, parts.next()??))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::fix::message::{msg_type, tag};
+
+	fn stamped(msg_type: &str, seq: u64) -> Message {
+		let mut message = Message::new(msg_type).with(tag::TEXT, "x");
+		message.set_header(&[(tag::MSG_SEQ_NUM, seq.to_string())]);
+		message
+	}
+
+	#[test]
+	fn survives_reopen_and_reset() {
+		let dir = std::env::temp_dir().join(format!("anvil-fix-{}", uuid::Uuid::new_v4()));
+		{
+			let mut store = SessionStore::open(&dir, "ANVIL", "CLIENT").unwrap();
+			store.record_sent(&stamped(msg_type::HEARTBEAT, 1)).unwrap();
+			store
+				.record_sent(&stamped(msg_type::EXECUTION_REPORT, 2))
+				.unwrap();
+			store.set_next_target_seq(7).unwrap();
+		}
+
+		let mut store = SessionStore::open(&dir, "ANVIL", "CLIENT").unwrap();
+		assert_eq!((store.next_sender_seq(), store.next_target_seq()), (3, 7));
+		assert!(store.sent(1).is_none());
+		assert_eq!(
+			store.sent(2).map(|m| m.msg_type()),
+			Some(msg_type::EXECUTION_REPORT)
+		);
+
+		store.reset().unwrap();
+		let store = SessionStore::open(&dir, "ANVIL", "CLIENT").unwrap();
+		assert_eq!((store.next_sender_seq(), store.next_target_seq()), (1, 1));
+		assert!(store.sent(2).is_none());
+
+		fs::remove_dir_all(&dir).unwrap();
+	}
+}
@@ -21,10 +21,15 @@ pub mod proto {
 
 use std::time::Duration;
 
-use anvil_sdk::types::{Order, OrderStatus, Side};
+use anvil_sdk::types::Side;
 use proto::{
-	OrderSide as ProtoOrderSide, OrderStatus as ProtoOrderStatus, SubmitOrderRequest,
-	SubmitOrderResponse, matching_service_client::MatchingServiceClient,
+	CancelOrderRequest, CancelOrderResponse, CancelOrdersRequest, ExecutionReport,
+	GetCandlesRequest, GetCandlesResponse, GetEngineStatusRequest, GetEngineStatusResponse,
+	GetOrderBookRequest, GetOrderBookResponse, GetOrderRequest, GetTickerRequest,
+	GetTickerResponse, ListFillsRequest, ListFillsResponse, ListTradesRequest, ListTradesResponse,
+	MarketDataUpdate, Order, OrderSide as ProtoOrderSide, PromoteRequest, PromoteResponse,
+	StreamExecutionReportsRequest, StreamMarketDataRequest, SubmitOrderRequest,
+	SubmitOrderResponse, SubmitOrdersRequest, matching_service_client::MatchingServiceClient,
 };
 use thiserror::Error;
 use tonic::{
@@ -43,8 +48,9 @@ pub enum GrpcClientError {
 	Timeout,
 	#[error("gRPC status error: {0}")]
 	Status(String),
+	#[error("Not found: {0}")]
+	NotFound(String),
 	#[error("Serialization error: {0}")]
-	#[allow(dead_code)]
 	Serialization(String),
 }
 
@@ -91,6 +97,8 @@ impl MatchingGrpcClient {
 	/// # Arguments
 	///
 	/// * `order` - The order to submit to the matching engine
+	/// * `replaces_order_id` - Resting order the engine cancels first, for amends
+	/// * `nonce` - Client request nonce, the engine's idempotency key with the principal
 	/// * `ctx` - Request context containing tracing and request identification information
 	///
 	/// # Returns
@@ -100,11 +108,58 @@ impl MatchingGrpcClient {
 	pub async fn submit_order(
 		&mut self,
 		order: anvil_matching::types::Order,
+		replaces_order_id: Option<&str>,
+		nonce: &str,
 		ctx: &RequestContext,
 	) -> Result<SubmitOrderResponse, GrpcClientError> {
-		let request = SubmitOrderRequest {
-			order_id: order.order_id.clone(),
-			market: order.market.clone(),
+		let mut req = tonic::Request::new(Self::order_request(order, replaces_order_id, nonce));
+		req.set_timeout(self.rpc_timeout);
+		Self::propagate_context(&mut req, ctx);
+
+		let response = self
+			.client
+			.submit_order(req)
+			.await
+			.map_err(Self::map_status)?
+			.into_inner();
+
+		Ok(response)
+	}
+
+	/// Submit orders that the engine sequences back to back
+	///
+	/// Each order comes with its request nonce. Returns one response per
+	/// order, in order.
+	pub async fn submit_orders(
+		&mut self,
+		orders: Vec<(anvil_matching::types::Order, String)>,
+		ctx: &RequestContext,
+	) -> Result<Vec<SubmitOrderResponse>, GrpcClientError> {
+		let mut req = tonic::Request::new(SubmitOrdersRequest {
+			orders: orders
+				.into_iter()
+				.map(|(order, nonce)| Self::order_request(order, None, &nonce))
+				.collect(),
+		});
+		req.set_timeout(self.rpc_timeout);
+		Self::propagate_context(&mut req, ctx);
+
+		let response = self
+			.client
+			.submit_orders(req)
+			.await
+			.map_err(Self::map_status)?
+			.into_inner();
+
+		Ok(response.results)
+	}
+
+	fn order_request(
+		order: anvil_matching::types::Order,
+		replaces_order_id: Option<&str>,
+		nonce: &str,
+	) -> SubmitOrderRequest {
+		SubmitOrderRequest {
 			side: match order.side {
 				Side::Buy => ProtoOrderSide::Buy as i32,
 				Side::Sell => ProtoOrderSide::Sell as i32,
@@ -113,11 +168,17 @@ impl MatchingGrpcClient {
 			size: order.size,
 			remaining_size: order.remaining_size,
 			timestamp: order.timestamp,
-			public_key: order.public_key.clone(),
-		};
+			order_id: order.order_id,
+			market: order.market,
+			public_key: order.public_key,
+			client_order_id: order.client_order_id.unwrap_or_default(),
+			replaces_order_id: replaces_order_id.unwrap_or_default().to_string(),
+			nonce: nonce.to_string(),
+		}
+	}
 
-		let mut req = tonic::Request::new(request);
-		req.set_timeout(self.rpc_timeout);
+	/// Propagate tracing and request identification as gRPC metadata
+	fn propagate_context<T>(req: &mut tonic::Request<T>, ctx: &RequestContext) {
 		let metadata = req.metadata_mut();
 
 		// Propagate W3C Trace Context headers as gRPC metadata
@@ -140,97 +201,330 @@ impl MatchingGrpcClient {
 		if let Ok(value) = MetadataValue::try_from(ctx.trace_id.as_str()) {
 			metadata.insert("trace-id", value);
 		}
+	}
+
+	/// Get the latest `limit` candles of `interval` for a market
+	pub async fn get_candles(
+		&mut self,
+		market: &str,
+		interval: &str,
+		limit: u32,
+	) -> Result<GetCandlesResponse, GrpcClientError> {
+		let mut req = tonic::Request::new(GetCandlesRequest {
+			market: market.to_string(),
+			interval: interval.to_string(),
+			limit,
+		});
+		req.set_timeout(self.rpc_timeout);
 
 		let response = self
 			.client
-			.submit_order(req)
+			.get_candles(req)
 			.await
-			.map_err(|status| {
-				if status.code() == tonic::Code::DeadlineExceeded {
-					GrpcClientError::Timeout
-				} else {
-					GrpcClientError::Status(format!("gRPC error: {}", status))
-				}
-			})?
+			.map_err(Self::map_status)?
 			.into_inner();
 
 		Ok(response)
 	}
 
-	/// Get order status
-	#[allow(dead_code)]
-	pub async fn get_order(&mut self, order_id: &str) -> Result<Order, GrpcClientError> {
-		use proto::GetOrderRequest;
-		let request = GetOrderRequest {
-			order_id: order_id.to_string(),
-		};
+	/// Get rolling 24h ticker statistics for a market
+	pub async fn get_ticker(&mut self, market: &str) -> Result<GetTickerResponse, GrpcClientError> {
+		let mut req = tonic::Request::new(GetTickerRequest {
+			market: market.to_string(),
+		});
+		req.set_timeout(self.rpc_timeout);
 
 		let response = self
 			.client
-			.get_order(tonic::Request::new(request))
+			.get_ticker(req)
 			.await
-			.map_err(|e| GrpcClientError::Status(format!("gRPC error: {}", e)))?
+			.map_err(Self::map_status)?
 			.into_inner();
 
-		// Convert proto order to SDK order
-		let proto_order = response
-			.order
-			.ok_or_else(|| GrpcClientError::Serialization("Order not found".to_string()))?;
+		Ok(response)
+	}
 
-		let market = proto_order.market.clone();
-		let side = match proto_order.side() {
-			ProtoOrderSide::Buy => Side::Buy,
-			ProtoOrderSide::Sell => Side::Sell,
-		};
-		let status = match proto_order.status() {
-			ProtoOrderStatus::Pending => OrderStatus::Pending,
-			ProtoOrderStatus::Accepted => OrderStatus::Accepted,
-			ProtoOrderStatus::PartiallyFilled => OrderStatus::PartiallyFilled,
-			ProtoOrderStatus::Filled => OrderStatus::Filled,
-			ProtoOrderStatus::Cancelled => OrderStatus::Cancelled,
-			ProtoOrderStatus::Rejected => OrderStatus::Rejected,
-		};
+	/// Get the top `depth` L2 levels per side of a market (0 for the full book)
+	pub async fn get_order_book(
+		&mut self,
+		market: &str,
+		depth: u32,
+	) -> Result<GetOrderBookResponse, GrpcClientError> {
+		let mut req = tonic::Request::new(GetOrderBookRequest {
+			market: market.to_string(),
+			depth,
+		});
+		req.set_timeout(self.rpc_timeout);
+
+		let response = self
+			.client
+			.get_order_book(req)
+			.await
+			.map_err(Self::map_status)?
+			.into_inner();
+
+		Ok(response)
+	}
+
+	/// Page through a market's trades, newest first
+	pub async fn list_trades(
+		&mut self,
+		request: ListTradesRequest,
+	) -> Result<ListTradesResponse, GrpcClientError> {
+		let mut req = tonic::Request::new(request);
+		req.set_timeout(self.rpc_timeout);
+
+		let response = self
+			.client
+			.list_trades(req)
+			.await
+			.map_err(Self::map_status)?
+			.into_inner();
+
+		Ok(response)
+	}
+
+	/// Page through a principal's fills, newest first
+	pub async fn list_fills(
+		&mut self,
+		request: ListFillsRequest,
+	) -> Result<ListFillsResponse, GrpcClientError> {
+		let mut req = tonic::Request::new(request);
+		req.set_timeout(self.rpc_timeout);
+
+		let response = self
+			.client
+			.list_fills(req)
+			.await
+			.map_err(Self::map_status)?
+			.into_inner();
+
+		Ok(response)
+	}
+
+	/// The engine's position in its event sequence
+	pub async fn engine_status(&mut self) -> Result<GetEngineStatusResponse, GrpcClientError> {
+		let mut req = tonic::Request::new(GetEngineStatusRequest {});
+		req.set_timeout(self.rpc_timeout);
+
+		let response = self
+			.client
+			.get_engine_status(req)
+			.await
+			.map_err(Self::map_status)?
+			.into_inner();
+
+		Ok(response)
+	}
+
+	/// Promote a follower engine to primary under a newer fencing `epoch`
+	pub async fn promote(&mut self, epoch: u64) -> Result<PromoteResponse, GrpcClientError> {
+		let mut req = tonic::Request::new(PromoteRequest { epoch });
+		req.set_timeout(self.rpc_timeout);
+
+		let response = self
+			.client
+			.promote(req)
+			.await
+			.map_err(Self::map_status)?
+			.into_inner();
+
+		Ok(response)
+	}
+
+	fn map_status(status: tonic::Status) -> GrpcClientError {
+		match status.code() {
+			tonic::Code::DeadlineExceeded => GrpcClientError::Timeout,
+			tonic::Code::NotFound => GrpcClientError::NotFound(status.message().to_string()),
+			_ => GrpcClientError::Status(format!("gRPC error: {}", status)),
+		}
+	}
+
+	/// Current state of one of `public_key`'s orders
+	pub async fn get_order(
+		&mut self,
+		order_id: &str,
+		public_key: &str,
+	) -> Result<Order, GrpcClientError> {
+		self.query_order(GetOrderRequest {
+			order_id: order_id.to_string(),
+			public_key: public_key.to_string(),
+			..Default::default()
+		})
+		.await
+	}
 
-		Ok(Order {
-			order_id: proto_order.order_id,
-			market,
-			side,
-			order_type: anvil_sdk::types::OrderType::Limit, // TODO: Add to proto
-			price: Some(proto_order.price),
-			size: proto_order.size,
-			filled_size: proto_order.filled_size,
-			remaining_size: proto_order.remaining_size,
-			status,
-			created_at: proto_order.created_at,
+	/// Current state of `public_key`'s latest order with `client_order_id`
+	pub async fn get_order_by_client_id(
+		&mut self,
+		client_order_id: &str,
+		public_key: &str,
+	) -> Result<Order, GrpcClientError> {
+		self.query_order(GetOrderRequest {
+			public_key: public_key.to_string(),
+			client_order_id: client_order_id.to_string(),
+			..Default::default()
 		})
+		.await
+	}
+
+	async fn query_order(&mut self, request: GetOrderRequest) -> Result<Order, GrpcClientError> {
+		let mut req = tonic::Request::new(request);
+		req.set_timeout(self.rpc_timeout);
+
+		let response = self
+			.client
+			.get_order(req)
+			.await
+			.map_err(Self::map_status)?
+			.into_inner();
+
+		response
+			.order
+			.ok_or_else(|| GrpcClientError::Serialization("Missing order".to_string()))
 	}
 
-	/// Cancel an order
-	#[allow(dead_code)]
+	/// Cancel an order on behalf of its owner
+	///
+	/// The engine only checks the market and owner, so the request's side is
+	/// left at its default.
 	pub async fn cancel_order(
 		&mut self,
 		market: &str,
-		side: Side,
 		order_id: &str,
-	) -> Result<bool, GrpcClientError> {
-		use proto::CancelOrderRequest;
+		public_key: &str,
+		ctx: &RequestContext,
+	) -> Result<CancelOrderResponse, GrpcClientError> {
+		self.send_cancel(
+			CancelOrderRequest {
+				order_id: order_id.to_string(),
+				market: market.to_string(),
+				public_key: public_key.to_string(),
+				..Default::default()
+			},
+			ctx,
+		)
+		.await
+	}
 
-		let request = CancelOrderRequest {
-			order_id: order_id.to_string(),
-			market: market.to_string(),
-			side: match side {
-				Side::Buy => ProtoOrderSide::Buy as i32,
-				Side::Sell => ProtoOrderSide::Sell as i32,
+	/// Cancel the owner's resting order with `client_order_id`
+	pub async fn cancel_order_by_client_id(
+		&mut self,
+		market: &str,
+		client_order_id: &str,
+		public_key: &str,
+		ctx: &RequestContext,
+	) -> Result<CancelOrderResponse, GrpcClientError> {
+		self.send_cancel(
+			CancelOrderRequest {
+				market: market.to_string(),
+				public_key: public_key.to_string(),
+				client_order_id: client_order_id.to_string(),
+				..Default::default()
 			},
-		};
+			ctx,
+		)
+		.await
+	}
+
+	async fn send_cancel(
+		&mut self,
+		request: CancelOrderRequest,
+		ctx: &RequestContext,
+	) -> Result<CancelOrderResponse, GrpcClientError> {
+		let mut req = tonic::Request::new(request);
+		req.set_timeout(self.rpc_timeout);
+		Self::propagate_context(&mut req, ctx);
 
 		let response = self
 			.client
-			.cancel_order(tonic::Request::new(request))
+			.cancel_order(req)
 			.await
-			.map_err(|e| GrpcClientError::Status(format!("gRPC error: {}", e)))?
+			.map_err(Self::map_status)?
 			.into_inner();
 
-		Ok(response.success)
+		Ok(response)
+	}
+
+	/// Cancel several orders of one owner, sequenced back to back
+	///
+	/// Returns one response per order ID, in order.
+	pub async fn cancel_orders(
+		&mut self,
+		market: &str,
+		order_ids: &[String],
+		public_key: &str,
+		ctx: &RequestContext,
+	) -> Result<Vec<CancelOrderResponse>, GrpcClientError> {
+		let mut req = tonic::Request::new(CancelOrdersRequest {
+			cancels: order_ids
+				.iter()
+				.map(|order_id| CancelOrderRequest {
+					order_id: order_id.clone(),
+					market: market.to_string(),
+					public_key: public_key.to_string(),
+					..Default::default()
+				})
+				.collect(),
+		});
+		req.set_timeout(self.rpc_timeout);
+		Self::propagate_context(&mut req, ctx);
+
+		let response = self
+			.client
+			.cancel_orders(req)
+			.await
+			.map_err(Self::map_status)?
+			.into_inner();
+
+		Ok(response.results)
+	}
+
+	/// Subscribe to a principal's execution reports in a market
+	///
+	/// Only establishing the stream is bounded by the RPC timeout.
+	pub async fn stream_execution_reports(
+		&mut self,
+		market: &str,
+		public_key: &str,
+		from_sequence: u64,
+	) -> Result<tonic::Streaming<ExecutionReport>, GrpcClientError> {
+		let request = StreamExecutionReportsRequest {
+			market: market.to_string(),
+			public_key: public_key.to_string(),
+			from_sequence,
+		};
+
+		let response = tokio::time::timeout(
+			self.rpc_timeout,
+			self.client.stream_execution_reports(request),
+		)
+		.await
+		.map_err(|_| GrpcClientError::Timeout)?
+		.map_err(Self::map_status)?
+		.into_inner();
+
+		Ok(response)
+	}
+
+	/// Subscribe to every public market data change of a market
+	///
+	/// Only establishing the stream is bounded by the RPC timeout.
+	pub async fn stream_market_data(
+		&mut self,
+		market: &str,
+	) -> Result<tonic::Streaming<MarketDataUpdate>, GrpcClientError> {
+		let request = StreamMarketDataRequest {
+			market: market.to_string(),
+		};
+
+		let response =
+			tokio::time::timeout(self.rpc_timeout, self.client.stream_market_data(request))
+				.await
+				.map_err(|_| GrpcClientError::Timeout)?
+				.map_err(Self::map_status)?
+				.into_inner();
+
+		Ok(response)
 	}
 }
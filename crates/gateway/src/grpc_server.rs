@@ -0,0 +1,590 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! gRPC order entry
+//!
+//! [`OrderGatewayService`] serves `anvil.gateway.OrderGateway` (see
+//! `proto/gateway.proto`) next to the REST API. Orders and cancels go
+//! through the same authentication, rate limit, admission and replay checks
+//! as REST, and failures map to the gRPC status equivalent of the REST
+//! error response.
+
+use std::{
+	pin::Pin,
+	sync::Arc,
+	task::{Context, Poll},
+};
+
+use anvil_sdk::{
+	Amount,
+	decimal::format_units,
+	types::{
+		BatchItemError, ExecutionReport, ExecutionType, LiquidityRole, OrderStatus, OrderType,
+		PlaceOrderRequest, Side,
+	},
+};
+use tokio::sync::mpsc;
+use tokio_stream::Stream;
+use tonic::{Request, Response, Status, metadata::MetadataMap};
+
+use crate::{
+	admission::{self, ReplayOutcome},
+	auth::{self, AuthContext, AuthenticatedPrincipal},
+	grpc_client::proto::{
+		Order as MatchingOrder, OrderSide as MatchingSide, OrderStatus as MatchingStatus,
+	},
+	handlers::{self, GatewayError},
+	request_context::RequestContext,
+	server::GatewayState,
+	ws,
+};
+
+pub mod proto {
+	tonic::include_proto!("anvil.gateway");
+}
+
+use proto::order_gateway_server::{OrderGateway, OrderGatewayServer};
+
+/// Path prefix of the service's calls, signed by every call but PlaceOrder
+pub const SERVICE_PATH: &str = "/anvil.gateway.OrderGateway";
+
+/// Method signed by every call but PlaceOrder; gRPC calls are HTTP/2 POSTs
+pub const QUERY_METHOD: &str = "POST";
+
+/// Execution reports buffered per stream before forwarders wait
+const REPORT_BUFFER: usize = 256;
+
+/// `anvil.gateway.OrderGateway` implementation
+pub struct OrderGatewayService {
+	state: Arc<GatewayState>,
+}
+
+impl OrderGatewayService {
+	pub fn new(state: GatewayState) -> Self {
+		Self {
+			state: Arc::new(state),
+		}
+	}
+
+	/// Wrap the service in a tonic server
+	pub fn into_server(self) -> OrderGatewayServer<Self> {
+		OrderGatewayServer::new(self)
+	}
+
+	/// Authenticate a call whose signature covers its path and fields
+	fn authenticate(
+		&self,
+		metadata: &MetadataMap,
+		method: &str,
+		fields: &[(&str, &str)],
+		context: &RequestContext,
+	) -> Result<AuthenticatedPrincipal, GatewayError> {
+		auth::authenticate_query(
+			&AuthContext::from_grpc(metadata),
+			QUERY_METHOD,
+			&signed_path(method, fields),
+			self.state.auth_provider.as_ref(),
+		)
+		.map_err(|e| GatewayError::auth(e, context))
+	}
+}
+
+#[tonic::async_trait]
+impl OrderGateway for OrderGatewayService {
+	async fn place_order(
+		&self,
+		request: Request<proto::PlaceOrderRequest>,
+	) -> Result<Response<proto::PlaceOrderResponse>, Status> {
+		let context = RequestContext::from_grpc(request.metadata());
+		let order = place_order_request(request.get_ref())
+			.map_err(|e| GatewayError::invalid_request(e, &context).to_status())?;
+		let authenticated = auth::authenticate_with_provider(
+			&AuthContext::from_grpc(request.metadata()),
+			&order,
+			self.state.auth_provider.as_ref(),
+		)
+		.map_err(|e| GatewayError::auth(e, &context).to_status())?;
+
+		let response = handlers::submit_order(&self.state, &order, &authenticated, None, &context)
+			.await
+			.map_err(|e| e.to_status())?;
+		Ok(Response::new(proto::PlaceOrderResponse {
+			order_id: response.order_id,
+			status: order_status(response.status) as i32,
+			client_order_id: response.client_order_id,
+		}))
+	}
+
+	async fn cancel_order(
+		&self,
+		request: Request<proto::CancelOrderRequest>,
+	) -> Result<Response<proto::CancelOrderResponse>, Status> {
+		let context = RequestContext::from_grpc(request.metadata());
+		let req = request.get_ref();
+		let authenticated = self
+			.authenticate(
+				request.metadata(),
+				"CancelOrder",
+				&[("market", &req.market), ("order_id", &req.order_id)],
+				&context,
+			)
+			.map_err(|e| e.to_status())?;
+
+		let response = handlers::submit_cancel(
+			&self.state,
+			&req.market,
+			&req.order_id,
+			&authenticated,
+			&context,
+		)
+		.await
+		.map_err(|e| e.to_status())?;
+		Ok(Response::new(proto::CancelOrderResponse {
+			order_id: response.order_id,
+		}))
+	}
+
+	async fn submit_orders(
+		&self,
+		request: Request<proto::SubmitOrdersRequest>,
+	) -> Result<Response<proto::SubmitOrdersResponse>, Status> {
+		let context = RequestContext::from_grpc(request.metadata());
+		let orders = request
+			.get_ref()
+			.orders
+			.iter()
+			.map(place_order_request)
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(|e| GatewayError::invalid_request(e, &context).to_status())?;
+		let authenticated = auth::authenticate_batch(
+			&AuthContext::from_grpc(request.metadata()),
+			&orders,
+			self.state.auth_provider.as_ref(),
+		)
+		.map_err(|e| GatewayError::auth(e, &context).to_status())?;
+
+		let response = handlers::submit_orders(&self.state, &orders, &authenticated, &context)
+			.await
+			.map_err(|e| e.to_status())?;
+		Ok(Response::new(proto::SubmitOrdersResponse {
+			results: response
+				.results
+				.into_iter()
+				.map(|result| proto::OrderResult {
+					order_id: result.order_id.unwrap_or_default(),
+					status: order_status(result.status) as i32,
+					client_order_id: result.client_order_id,
+					error: result.error.map(batch_error),
+				})
+				.collect(),
+		}))
+	}
+
+	async fn cancel_orders(
+		&self,
+		request: Request<proto::CancelOrdersRequest>,
+	) -> Result<Response<proto::CancelOrdersResponse>, Status> {
+		let context = RequestContext::from_grpc(request.metadata());
+		let req = request.get_ref();
+		let order_ids = req.order_ids.join(",");
+		let authenticated = self
+			.authenticate(
+				request.metadata(),
+				"CancelOrders",
+				&[("market", &req.market), ("order_ids", &order_ids)],
+				&context,
+			)
+			.map_err(|e| e.to_status())?;
+
+		let response = handlers::submit_cancels(
+			&self.state,
+			&req.market,
+			&req.order_ids,
+			&authenticated,
+			&context,
+		)
+		.await
+		.map_err(|e| e.to_status())?;
+		Ok(Response::new(proto::CancelOrdersResponse {
+			results: response
+				.results
+				.into_iter()
+				.map(|result| proto::CancelResult {
+					order_id: result.order_id,
+					error: result.error.map(batch_error),
+				})
+				.collect(),
+		}))
+	}
+
+	async fn get_order(
+		&self,
+		request: Request<proto::GetOrderRequest>,
+	) -> Result<Response<proto::Order>, Status> {
+		let context = RequestContext::from_grpc(request.metadata());
+		let req = request.get_ref();
+		let authenticated = self
+			.authenticate(
+				request.metadata(),
+				"GetOrder",
+				&[("market", &req.market), ("order_id", &req.order_id)],
+				&context,
+			)
+			.map_err(|e| e.to_status())?;
+		let principal = &authenticated.principal;
+
+		admission::check_rate_limit(principal)
+			.map_err(|e| GatewayError::admission(e, &context).to_status())?;
+		let replay_guard =
+			admission::begin_replay(principal, authenticated.timestamp, &authenticated.nonce)
+				.map_err(|e| GatewayError::admission(e, &context).to_status())?;
+
+		let order = match self
+			.state
+			.dispatcher
+			.get_order(&req.market, &req.order_id, &principal.id())
+			.await
+		{
+			Ok(order) => {
+				replay_guard.finish(ReplayOutcome::Terminal);
+				order
+			}
+			Err(err) => {
+				// Reads have no side effects, so the nonce may be retried
+				replay_guard.finish(ReplayOutcome::RetryableFailure);
+				return Err(GatewayError::dispatch(err, &context).to_status());
+			}
+		};
+
+		let (price_decimals, size_decimals) = handlers::market_decimals(&self.state, &req.market);
+		Ok(Response::new(order_view(
+			order,
+			price_decimals,
+			size_decimals,
+		)))
+	}
+
+	type StreamOrdersStream = ReportStream;
+
+	async fn stream_orders(
+		&self,
+		request: Request<proto::StreamOrdersRequest>,
+	) -> Result<Response<Self::StreamOrdersStream>, Status> {
+		let context = RequestContext::from_grpc(request.metadata());
+		let req = request.get_ref();
+		let from_sequence = req.from_sequence.to_string();
+		let authenticated = self
+			.authenticate(
+				request.metadata(),
+				"StreamOrders",
+				&[("market", &req.market), ("from_sequence", &from_sequence)],
+				&context,
+			)
+			.map_err(|e| e.to_status())?;
+
+		let markets = if req.market.is_empty() {
+			if req.from_sequence != 0 {
+				return Err(GatewayError::invalid_request(
+					"from_sequence requires a market",
+					&context,
+				)
+				.to_status());
+			}
+			self.state.dispatcher.markets()
+		} else {
+			vec![req.market.clone()]
+		};
+
+		let principal = &authenticated.principal;
+		admission::check_rate_limit(principal)
+			.map_err(|e| GatewayError::admission(e, &context).to_status())?;
+		admission::begin_replay(principal, authenticated.timestamp, &authenticated.nonce)
+			.map_err(|e| GatewayError::admission(e, &context).to_status())?
+			.finish(ReplayOutcome::Terminal);
+
+		let (reports_tx, reports) = mpsc::channel(REPORT_BUFFER);
+		let forwarders = markets
+			.into_iter()
+			.map(|market| {
+				tokio::spawn(ws::forward_reports(
+					self.state.clone(),
+					market,
+					principal.id(),
+					req.from_sequence,
+					reports_tx.clone(),
+				))
+			})
+			.collect();
+
+		Ok(Response::new(ReportStream {
+			reports,
+			forwarders,
+		}))
+	}
+}
+
+/// Execution reports of one StreamOrders call
+///
+/// Dropping the stream (the client went away) stops its forwarders.
+pub struct ReportStream {
+	reports: mpsc::Receiver<ExecutionReport>,
+	forwarders: Vec<tokio::task::JoinHandle<()>>,
+}
+
+impl Stream for ReportStream {
+	type Item = Result<proto::ExecutionReport, Status>;
+
+	fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		self.reports
+			.poll_recv(cx)
+			.map(|report| report.map(|report| Ok(execution_report(report))))
+	}
+}
+
+impl Drop for ReportStream {
+	fn drop(&mut self) {
+		for forwarder in &self.forwarders {
+			forwarder.abort();
+		}
+	}
+}
+
+/// Path and fields signed by a call, as `SERVICE_PATH/method?name=value&...`
+fn signed_path(method: &str, fields: &[(&str, &str)]) -> String {
+	let query: Vec<String> = fields
+		.iter()
+		.map(|(name, value)| format!("{}={}", name, value))
+		.collect();
+	format!("{}/{}?{}", SERVICE_PATH, method, query.join("&"))
+}
+
+/// The REST order body equivalent to a gRPC order, which is what the
+/// client signs
+fn place_order_request(request: &proto::PlaceOrderRequest) -> Result<PlaceOrderRequest, String> {
+	let side = match proto::Side::try_from(request.side) {
+		Ok(proto::Side::Buy) => Side::Buy,
+		Ok(proto::Side::Sell) => Side::Sell,
+		Err(_) => return Err(format!("Unknown side: {}", request.side)),
+	};
+	let order_type = match proto::OrderType::try_from(request.order_type) {
+		Ok(proto::OrderType::Limit) => OrderType::Limit,
+		Ok(proto::OrderType::Market) => OrderType::Market,
+		Err(_) => return Err(format!("Unknown order type: {}", request.order_type)),
+	};
+	let size = request
+		.size
+		.as_ref()
+		.and_then(amount)
+		.ok_or_else(|| "Missing size".to_string())?;
+
+	Ok(PlaceOrderRequest {
+		market: request.market.clone(),
+		side,
+		order_type,
+		price: request.price.as_ref().and_then(amount),
+		size,
+		client_order_id: request.client_order_id.clone(),
+	})
+}
+
+fn batch_error(error: BatchItemError) -> proto::BatchError {
+	proto::BatchError {
+		code: error.code,
+		reason: error.reason,
+		retryable: error.retryable,
+		unconfirmed: error.unconfirmed,
+	}
+}
+
+fn amount(amount: &proto::Amount) -> Option<Amount> {
+	match amount.value.as_ref()? {
+		proto::amount::Value::Units(units) => Some(Amount::Units(*units)),
+		proto::amount::Value::Decimal(decimal) => Some(Amount::Decimal(decimal.clone())),
+	}
+}
+
+fn order_status(status: OrderStatus) -> proto::OrderStatus {
+	match status {
+		OrderStatus::Pending => proto::OrderStatus::Pending,
+		OrderStatus::Accepted => proto::OrderStatus::Accepted,
+		OrderStatus::PartiallyFilled => proto::OrderStatus::PartiallyFilled,
+		OrderStatus::Filled => proto::OrderStatus::Filled,
+		OrderStatus::Cancelled => proto::OrderStatus::Cancelled,
+		OrderStatus::Rejected => proto::OrderStatus::Rejected,
+	}
+}
+
+/// Format a matching order with the market's decimals
+fn order_view(order: MatchingOrder, price_decimals: u32, size_decimals: u32) -> proto::Order {
+	let size = |units: u64| format_units(units as u128, size_decimals);
+	proto::Order {
+		side: match order.side() {
+			MatchingSide::Buy => proto::Side::Buy,
+			MatchingSide::Sell => proto::Side::Sell,
+		} as i32,
+		status: match order.status() {
+			MatchingStatus::Pending => proto::OrderStatus::Pending,
+			MatchingStatus::Accepted => proto::OrderStatus::Accepted,
+			MatchingStatus::PartiallyFilled => proto::OrderStatus::PartiallyFilled,
+			MatchingStatus::Filled => proto::OrderStatus::Filled,
+			MatchingStatus::Cancelled => proto::OrderStatus::Cancelled,
+			MatchingStatus::Rejected => proto::OrderStatus::Rejected,
+		} as i32,
+		price: Some(order.price)
+			.filter(|price| *price != 0)
+			.map(|price| format_units(price as u128, price_decimals)),
+		size: size(order.size),
+		filled_size: size(order.filled_size),
+		remaining_size: size(order.remaining_size),
+		order_id: order.order_id,
+		market: order.market,
+		created_at: order.created_at,
+		client_order_id: Some(order.client_order_id).filter(|id| !id.is_empty()),
+	}
+}
+
+fn execution_report(report: ExecutionReport) -> proto::ExecutionReport {
+	proto::ExecutionReport {
+		sequence: report.sequence,
+		market: report.market,
+		order_id: report.order_id,
+		exec_type: match report.exec_type {
+			ExecutionType::New => proto::ExecutionType::New,
+			ExecutionType::Rejected => proto::ExecutionType::Rejected,
+			ExecutionType::Trade => proto::ExecutionType::Trade,
+			ExecutionType::PartiallyFilled => proto::ExecutionType::PartiallyFilled,
+			ExecutionType::Filled => proto::ExecutionType::Filled,
+			ExecutionType::Cancelled => proto::ExecutionType::Cancelled,
+			ExecutionType::CancelRejected => proto::ExecutionType::CancelRejected,
+		} as i32,
+		price: report.price,
+		size: report.size,
+		filled_size: report.filled_size,
+		remaining_size: report.remaining_size,
+		trade_id: report.trade_id,
+		role: report.role.map(|role| match role {
+			LiquidityRole::Maker => proto::LiquidityRole::Maker as i32,
+			LiquidityRole::Taker => proto::LiquidityRole::Taker as i32,
+		}),
+		reason: report.reason,
+		timestamp: report.timestamp,
+		client_order_id: report.client_order_id,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn order_keeps_the_signed_amount_representation() {
+		let order = place_order_request(&proto::PlaceOrderRequest {
+			market: "BTC-USDT".to_string(),
+			side: proto::Side::Sell as i32,
+			order_type: proto::OrderType::Limit as i32,
+			price: Some(proto::Amount {
+				value: Some(proto::amount::Value::Decimal("50000.10".to_string())),
+			}),
+			size: Some(proto::Amount {
+				value: Some(proto::amount::Value::Units(150)),
+			}),
+			client_order_id: None,
+		})
+		.unwrap();
+
+		assert_eq!(order.side, Side::Sell);
+		assert_eq!(order.price, Some(Amount::Decimal("50000.10".to_string())));
+		assert_eq!(order.size, Amount::Units(150));
+	}
+
+	#[test]
+	fn order_without_size_is_invalid() {
+		let request = proto::PlaceOrderRequest {
+			market: "BTC-USDT".to_string(),
+			..Default::default()
+		};
+		assert!(place_order_request(&request).is_err());
+	}
+
+	#[test]
+	fn batch_signature_covers_every_order() {
+		use anvil_sdk::signing::{SignatureAlgorithm, sign_message};
+		use ed25519_dalek::SigningKey;
+
+		let private_key = [9u8; 32];
+		let public_key = SigningKey::from_bytes(&private_key)
+			.verifying_key()
+			.to_bytes();
+		let order = |size| PlaceOrderRequest {
+			market: "BTC-USDT".to_string(),
+			side: Side::Buy,
+			order_type: OrderType::Limit,
+			price: Some(Amount::Decimal("50000.00".to_string())),
+			size: Amount::Units(size),
+			client_order_id: None,
+		};
+		let orders = vec![order(1), order(2)];
+		let signature = sign_message(
+			&auth::batch_signing_message(&orders, 1_700_000_000, "n1"),
+			&private_key,
+			SignatureAlgorithm::Ed25519,
+		)
+		.unwrap();
+
+		let mut metadata = MetadataMap::new();
+		metadata.insert("public-key", hex::encode(public_key).parse().unwrap());
+		metadata.insert("signature", signature.parse().unwrap());
+		metadata.insert("timestamp", "1700000000".parse().unwrap());
+		metadata.insert("nonce", "n1".parse().unwrap());
+		let context = AuthContext::from_grpc(&metadata);
+		let provider = auth::SignatureAuthProvider;
+
+		assert!(auth::authenticate_batch(&context, &orders, &provider).is_ok());
+		assert!(auth::authenticate_batch(&context, &[order(1), order(3)], &provider).is_err());
+		assert!(auth::authenticate_batch(&context, &orders[..1], &provider).is_err());
+	}
+
+	#[test]
+	fn signed_path_lists_fields_in_order() {
+		assert_eq!(
+			signed_path("CancelOrder", &[("market", "BTC-USDT"), ("order_id", "o1")]),
+			"/anvil.gateway.OrderGateway/CancelOrder?market=BTC-USDT&order_id=o1"
+		);
+	}
+
+	#[test]
+	fn order_view_formats_market_units() {
+		let order = order_view(
+			MatchingOrder {
+				order_id: "o1".to_string(),
+				market: "BTC-USDT".to_string(),
+				side: MatchingSide::Sell as i32,
+				price: 5_000_012,
+				size: 150_000_000,
+				filled_size: 50_000_000,
+				remaining_size: 100_000_000,
+				status: MatchingStatus::PartiallyFilled as i32,
+				..Default::default()
+			},
+			2,
+			8,
+		);
+		assert_eq!(order.price.as_deref(), Some("50000.12"));
+		assert_eq!(order.remaining_size, "1.00000000");
+		assert_eq!(order.status(), proto::OrderStatus::PartiallyFilled);
+
+		let filled_on_arrival = order_view(MatchingOrder::default(), 2, 8);
+		assert_eq!(filled_on_arrival.price, None);
+	}
+}
@@ -13,7 +13,16 @@
 // limitations under the License.
 
 use actix_web::{HttpRequest, HttpResponse, Responder, web};
-use anvil_sdk::types::{OrderStatus, PlaceOrderRequest, PlaceOrderResponse};
+use anvil_matching::history::TradeCursor;
+use anvil_matching::market_data::{CANDLE_RETENTION, CandleInterval};
+use anvil_sdk::decimal::format_units;
+use anvil_sdk::types::{
+	BatchCancelResponse, BatchCancelResult, BatchItemError, BatchOrderResult,
+	BatchPlaceOrderRequest, BatchPlaceOrderResponse, CancelOrderResponse, Candle, CandlesResponse,
+	Fill, FillsPage, LiquidityRole, Order, OrderStatus, OrderType, PlaceOrderRequest,
+	PlaceOrderResponse, PublicTrade, Side, Ticker, TradesPage,
+};
+use serde::Deserialize;
 use std::fmt;
 use thiserror::Error;
 use tracing::field;
@@ -22,10 +31,16 @@ use uuid::Uuid;
 use crate::{
 	admission,
 	admission::AdmissionError,
-	admission::{ReplayGuard, ReplayOutcome},
+	admission::{OrderAttempt, RecordedOrder, ReplayGuard, ReplayOutcome},
 	auth,
-	auth::{AuthContext, AuthError},
+	auth::{AuthContext, AuthError, AuthenticatedPrincipal},
 	dispatcher::{DispatchResult, DispatcherError},
+	grpc_client::proto::{
+		Candle as ProtoCandle, GetTickerResponse, LiquidityRole as ProtoLiquidityRole,
+		ListFillsRequest, ListTradesRequest, Order as ProtoOrder, OrderSide as ProtoOrderSide,
+		OrderStatus as ProtoOrderStatus, Trade as ProtoTrade,
+	},
+	health::CircuitState,
 	request_context::RequestContext,
 	server::GatewayState,
 };
@@ -39,6 +54,8 @@ pub enum GatewayErrorKind {
 	Admission(AdmissionError),
 	#[error("Dispatching error: {0}")]
 	Dispatching(DispatcherError),
+	#[error("Invalid request: {0}")]
+	InvalidRequest(String),
 	#[error("Internal error: {0}")]
 	Internal(String),
 }
@@ -62,39 +79,90 @@ enum Retryability {
 	Unconfirmed,
 }
 
+impl Retryability {
+	/// `(retryable, unconfirmed)` as reported to clients
+	fn flags(self) -> (bool, bool) {
+		match self {
+			Retryability::Retryable => (true, false),
+			Retryability::NonRetryable => (false, false),
+			Retryability::Unconfirmed => (true, true),
+		}
+	}
+}
+
 impl GatewayError {
-	fn auth(err: AuthError, ctx: &RequestContext) -> Self {
+	pub(crate) fn auth(err: AuthError, ctx: &RequestContext) -> Self {
 		Self {
 			kind: GatewayErrorKind::Auth(err),
 			request_id: ctx.request_id.clone(),
 		}
 	}
 
-	fn admission(err: AdmissionError, ctx: &RequestContext) -> Self {
+	pub(crate) fn admission(err: AdmissionError, ctx: &RequestContext) -> Self {
 		Self {
 			kind: GatewayErrorKind::Admission(err),
 			request_id: ctx.request_id.clone(),
 		}
 	}
 
-	fn dispatch(err: DispatcherError, ctx: &RequestContext) -> Self {
+	pub(crate) fn dispatch(err: DispatcherError, ctx: &RequestContext) -> Self {
 		Self {
 			kind: GatewayErrorKind::Dispatching(err),
 			request_id: ctx.request_id.clone(),
 		}
 	}
 
+	pub(crate) fn invalid_request(msg: impl Into<String>, ctx: &RequestContext) -> Self {
+		Self {
+			kind: GatewayErrorKind::InvalidRequest(msg.into()),
+			request_id: ctx.request_id.clone(),
+		}
+	}
+
 	fn internal(msg: impl Into<String>, ctx: &RequestContext) -> Self {
 		Self {
 			kind: GatewayErrorKind::Internal(msg.into()),
 			request_id: ctx.request_id.clone(),
 		}
 	}
-}
 
-impl actix_web::ResponseError for GatewayError {
-	fn error_response(&self) -> HttpResponse {
-		let (status, code, retryability, reason) = match &self.kind {
+	/// Error of one entry of a batch, the batch form of [`Self::to_json`]
+	pub(crate) fn to_batch_error(&self) -> BatchItemError {
+		let (_, code, retryability, reason) = self.describe();
+		let (retryable, unconfirmed) = retryability.flags();
+		BatchItemError {
+			code: code.to_string(),
+			reason,
+			retryable,
+			unconfirmed,
+		}
+	}
+
+	/// JSON error body shared by every front end
+	///
+	/// `{code, reason, retryable, unconfirmed, request_id}`
+	pub(crate) fn to_json(&self) -> serde_json::Value {
+		let (_, code, retryability, reason) = self.describe();
+		let (retryable, unconfirmed) = retryability.flags();
+
+		serde_json::json!({
+			"code": code,
+			"reason": reason,
+			"retryable": retryable,
+			"unconfirmed": unconfirmed,
+			"request_id": self.request_id,
+		})
+	}
+
+	fn describe(
+		&self,
+	) -> (
+		actix_web::http::StatusCode,
+		&'static str,
+		Retryability,
+		String,
+	) {
+		match &self.kind {
 			GatewayErrorKind::Auth(e) => (
 				actix_web::http::StatusCode::UNAUTHORIZED,
 				"AUTH_FAILED",
@@ -131,11 +199,16 @@ impl actix_web::ResponseError for GatewayError {
 				Retryability::NonRetryable,
 				reason.clone(),
 			),
-			GatewayErrorKind::Admission(AdmissionError::InsufficientBalance) => (
+			GatewayErrorKind::Admission(AdmissionError::InsufficientBalance(reason))
+			| GatewayErrorKind::Dispatching(DispatcherError::InsufficientBalance(reason)) => (
 				actix_web::http::StatusCode::BAD_REQUEST,
 				"INSUFFICIENT_BALANCE",
 				Retryability::NonRetryable,
-				"Insufficient balance".to_string(),
+				if reason.is_empty() {
+					"Insufficient balance".to_string()
+				} else {
+					reason.clone()
+				},
 			),
 			GatewayErrorKind::Dispatching(DispatcherError::GatewayOverloaded) => (
 				actix_web::http::StatusCode::SERVICE_UNAVAILABLE,
@@ -185,6 +258,12 @@ impl actix_web::ResponseError for GatewayError {
 					reason.clone()
 				},
 			),
+			GatewayErrorKind::Dispatching(DispatcherError::MarketUnavailable(market)) => (
+				actix_web::http::StatusCode::SERVICE_UNAVAILABLE,
+				"MARKET_UNAVAILABLE",
+				Retryability::Retryable,
+				format!("Market not available: {}", market),
+			),
 			GatewayErrorKind::Dispatching(DispatcherError::MatchingEngineNotFound(market)) => (
 				actix_web::http::StatusCode::SERVICE_UNAVAILABLE,
 				"NO_MATCHING_ENGINE",
@@ -198,38 +277,386 @@ impl actix_web::ResponseError for GatewayError {
 				Retryability::Retryable,
 				reason.clone(),
 			),
+			GatewayErrorKind::Dispatching(DispatcherError::OrderNotFound(order_id)) => (
+				actix_web::http::StatusCode::NOT_FOUND,
+				"ORDER_NOT_FOUND",
+				Retryability::NonRetryable,
+				format!("Order not found: {}", order_id),
+			),
+			GatewayErrorKind::InvalidRequest(reason) => (
+				actix_web::http::StatusCode::BAD_REQUEST,
+				"INVALID_REQUEST",
+				Retryability::NonRetryable,
+				reason.clone(),
+			),
 			GatewayErrorKind::Internal(reason) => (
 				actix_web::http::StatusCode::INTERNAL_SERVER_ERROR,
 				"INTERNAL",
 				Retryability::NonRetryable,
 				reason.clone(),
 			),
-		};
+		}
+	}
+}
 
-		let (retryable, unconfirmed) = match retryability {
-			Retryability::Retryable => (true, false),
-			Retryability::NonRetryable => (false, false),
-			Retryability::Unconfirmed => (true, true),
+impl actix_web::ResponseError for GatewayError {
+	fn error_response(&self) -> HttpResponse {
+		let (status, ..) = self.describe();
+		HttpResponse::build(status).json(self.to_json())
+	}
+}
+
+impl GatewayError {
+	/// gRPC status equivalent to [`actix_web::ResponseError::error_response`]
+	///
+	/// The code is the closest match of the HTTP status and the message is
+	/// the reason; the rest of the JSON error body travels as `error-code`,
+	/// `request-id`, `retryable` and `unconfirmed` metadata.
+	pub(crate) fn to_status(&self) -> tonic::Status {
+		use actix_web::http::StatusCode;
+
+		let (status, code, retryability, reason) = self.describe();
+		let grpc_code = match status {
+			StatusCode::UNAUTHORIZED => tonic::Code::Unauthenticated,
+			StatusCode::TOO_MANY_REQUESTS => tonic::Code::ResourceExhausted,
+			StatusCode::BAD_REQUEST => tonic::Code::InvalidArgument,
+			StatusCode::NOT_FOUND => tonic::Code::NotFound,
+			// The order may have been accepted; clients must not assume either way
+			StatusCode::ACCEPTED => tonic::Code::DeadlineExceeded,
+			StatusCode::SERVICE_UNAVAILABLE | StatusCode::BAD_GATEWAY => tonic::Code::Unavailable,
+			_ => tonic::Code::Internal,
 		};
+		let (retryable, unconfirmed) = retryability.flags();
 
-		HttpResponse::build(status).json(serde_json::json!({
-			"code": code,
-			"reason": reason,
-			"retryable": retryable,
-			"unconfirmed": unconfirmed,
-			"request_id": self.request_id,
-		}))
+		let mut metadata = tonic::metadata::MetadataMap::new();
+		metadata.insert(
+			"error-code",
+			tonic::metadata::MetadataValue::from_static(code),
+		);
+		if let Ok(request_id) = self.request_id.parse() {
+			metadata.insert("request-id", request_id);
+		}
+		metadata.insert("retryable", bool_metadata(retryable));
+		metadata.insert("unconfirmed", bool_metadata(unconfirmed));
+		tonic::Status::with_metadata(grpc_code, reason, metadata)
 	}
 }
 
+fn bool_metadata(value: bool) -> tonic::metadata::MetadataValue<tonic::metadata::Ascii> {
+	tonic::metadata::MetadataValue::from_static(if value { "true" } else { "false" })
+}
+
 /// Health check endpoint
-pub async fn health() -> impl Responder {
+///
+/// Lists every market with its active matching engine and that engine's
+/// circuit state. The status is `degraded` while any market is unavailable.
+pub async fn health(state: web::Data<GatewayState>) -> impl Responder {
+	let markets: Vec<_> = state
+		.dispatcher
+		.market_circuits()
+		.into_iter()
+		.map(|market| {
+			serde_json::json!({
+				"market": market.market,
+				"endpoint": market.endpoint,
+				"available": market.circuit == CircuitState::Closed,
+				"circuit": market.circuit,
+			})
+		})
+		.collect();
+	let degraded = markets.iter().any(|market| market["available"] == false);
+
 	HttpResponse::Ok().json(serde_json::json!({
-		"status": "ok",
-		"service": "anvil-gateway"
+		"status": if degraded { "degraded" } else { "ok" },
+		"service": "anvil-gateway",
+		"markets": markets,
 	}))
 }
 
+/// List tradable markets with their price and size scales
+pub async fn list_markets(state: web::Data<GatewayState>) -> impl Responder {
+	HttpResponse::Ok().json(state.dispatcher.market_specs())
+}
+
+/// Query parameters for candle requests
+#[derive(Debug, Deserialize)]
+pub struct CandlesQuery {
+	/// Candle width: "1m" (default), "5m", "1h" or "1d"
+	#[serde(default)]
+	pub interval: Option<String>,
+	/// Most recent candles to return (default 100)
+	#[serde(default)]
+	pub limit: Option<u32>,
+}
+
+const DEFAULT_CANDLE_LIMIT: u32 = 100;
+
+/// OHLCV candles for a market, oldest first
+///
+/// Prices and volume are formatted with the market's decimals.
+pub async fn get_candles(
+	state: web::Data<GatewayState>,
+	path: web::Path<String>,
+	query: web::Query<CandlesQuery>,
+	req: HttpRequest,
+) -> Result<HttpResponse, GatewayError> {
+	let context = request_context(&req);
+	let market = path.into_inner();
+	let interval: CandleInterval = query.interval.as_deref().unwrap_or("1m").parse().map_err(
+		|e: anvil_matching::market_data::InvalidInterval| {
+			GatewayError::invalid_request(e.to_string(), &context)
+		},
+	)?;
+	let limit = query
+		.limit
+		.unwrap_or(DEFAULT_CANDLE_LIMIT)
+		.clamp(1, CANDLE_RETENTION as u32);
+
+	let response = state
+		.dispatcher
+		.candles(&market, interval.as_str(), limit)
+		.await
+		.map_err(|e| GatewayError::dispatch(e, &context))?;
+
+	let (price_decimals, size_decimals) = market_decimals(&state, &market);
+	Ok(HttpResponse::Ok().json(CandlesResponse {
+		market: response.market,
+		interval: response.interval,
+		candles: response
+			.candles
+			.into_iter()
+			.map(|c| candle(c, price_decimals, size_decimals))
+			.collect(),
+	}))
+}
+
+/// Rolling 24h ticker statistics for a market
+pub async fn get_ticker(
+	state: web::Data<GatewayState>,
+	path: web::Path<String>,
+	req: HttpRequest,
+) -> Result<HttpResponse, GatewayError> {
+	let context = request_context(&req);
+	let market = path.into_inner();
+
+	let ticker = state
+		.dispatcher
+		.ticker(&market)
+		.await
+		.map_err(|e| GatewayError::dispatch(e, &context))?;
+
+	let (price_decimals, size_decimals) = market_decimals(&state, &market);
+	Ok(HttpResponse::Ok().json(self::ticker(ticker, price_decimals, size_decimals)))
+}
+
+/// Format a matching candle with the market's decimals
+pub(crate) fn candle(c: ProtoCandle, price_decimals: u32, size_decimals: u32) -> Candle {
+	Candle {
+		open_time: c.open_time,
+		open: format_units(c.open as u128, price_decimals),
+		high: format_units(c.high as u128, price_decimals),
+		low: format_units(c.low as u128, price_decimals),
+		close: format_units(c.close as u128, price_decimals),
+		volume: format_units(c.volume as u128, size_decimals),
+		trade_count: c.trade_count,
+	}
+}
+
+/// Format matching ticker statistics with the market's decimals
+pub(crate) fn ticker(ticker: GetTickerResponse, price_decimals: u32, size_decimals: u32) -> Ticker {
+	let change = format_units(ticker.change.unsigned_abs() as u128, price_decimals);
+	Ticker {
+		market: ticker.market,
+		last: format_units(ticker.last as u128, price_decimals),
+		open: format_units(ticker.open as u128, price_decimals),
+		high: format_units(ticker.high as u128, price_decimals),
+		low: format_units(ticker.low as u128, price_decimals),
+		volume: format_units(ticker.volume as u128, size_decimals),
+		change: if ticker.change < 0 {
+			format!("-{}", change)
+		} else {
+			change
+		},
+		trade_count: ticker.trade_count,
+		timestamp: ticker.timestamp,
+	}
+}
+
+/// Format a matching trade for public consumption, without order IDs
+pub(crate) fn public_trade(t: ProtoTrade, price_decimals: u32, size_decimals: u32) -> PublicTrade {
+	PublicTrade {
+		side: proto_side(t.side()),
+		trade_id: t.trade_id,
+		price: format_units(t.price as u128, price_decimals),
+		size: format_units(t.size as u128, size_decimals),
+		timestamp: t.timestamp,
+	}
+}
+
+/// Pagination and time range parameters for history requests
+#[derive(Debug, Deserialize)]
+pub struct HistoryParams {
+	/// `next_cursor` of the previous page
+	#[serde(default)]
+	pub cursor: Option<String>,
+	/// Trades per page (default 100, at most 1000)
+	#[serde(default)]
+	pub limit: Option<u32>,
+	/// Earliest trade timestamp to include (Unix seconds)
+	#[serde(default)]
+	pub start_time: Option<u64>,
+	/// Latest trade timestamp to include (Unix seconds)
+	#[serde(default)]
+	pub end_time: Option<u64>,
+}
+
+/// Query parameters for fill requests; the rest as in [`HistoryParams`]
+#[derive(Debug, Deserialize)]
+pub struct FillsQuery {
+	/// Market identifier
+	pub market: String,
+	#[serde(default)]
+	pub cursor: Option<String>,
+	#[serde(default)]
+	pub limit: Option<u32>,
+	#[serde(default)]
+	pub start_time: Option<u64>,
+	#[serde(default)]
+	pub end_time: Option<u64>,
+}
+
+/// Validate a pagination cursor; absent cursors start at the newest trade
+fn history_cursor(cursor: Option<&str>, ctx: &RequestContext) -> Result<String, GatewayError> {
+	match cursor {
+		Some(cursor) => cursor
+			.parse::<TradeCursor>()
+			.map(|cursor| cursor.to_string())
+			.map_err(|e| GatewayError::invalid_request(e.to_string(), ctx)),
+		None => Ok(String::new()),
+	}
+}
+
+/// Public trade history of a market, newest first, with cursor pagination
+pub async fn get_trades(
+	state: web::Data<GatewayState>,
+	path: web::Path<String>,
+	query: web::Query<HistoryParams>,
+	req: HttpRequest,
+) -> Result<HttpResponse, GatewayError> {
+	let context = request_context(&req);
+	let market = path.into_inner();
+
+	let response = state
+		.dispatcher
+		.trades(ListTradesRequest {
+			market: market.clone(),
+			start_time: query.start_time.unwrap_or(0),
+			end_time: query.end_time.unwrap_or(0),
+			cursor: history_cursor(query.cursor.as_deref(), &context)?,
+			limit: query.limit.unwrap_or(0),
+		})
+		.await
+		.map_err(|e| GatewayError::dispatch(e, &context))?;
+
+	let (price_decimals, size_decimals) = market_decimals(&state, &market);
+	Ok(HttpResponse::Ok().json(TradesPage {
+		market,
+		trades: response
+			.trades
+			.into_iter()
+			.map(|t| public_trade(t, price_decimals, size_decimals))
+			.collect(),
+		next_cursor: Some(response.next_cursor).filter(|cursor| !cursor.is_empty()),
+	}))
+}
+
+/// The calling principal's fills in a market, newest first
+///
+/// Signed like an order, except that the signature covers
+/// [`anvil_sdk::signing::query_signing_message`] for `GET` and the request
+/// path with its query string.
+pub async fn get_fills(
+	state: web::Data<GatewayState>,
+	query: web::Query<FillsQuery>,
+	req: HttpRequest,
+) -> Result<HttpResponse, GatewayError> {
+	let context = request_context(&req);
+	let authenticated = authenticate_query(&state, &req, &context)?;
+	let principal = authenticated.principal;
+
+	admission::check_rate_limit(&principal).map_err(|e| GatewayError::admission(e, &context))?;
+	let replay_guard =
+		admission::begin_replay(&principal, authenticated.timestamp, &authenticated.nonce)
+			.map_err(|e| GatewayError::admission(e, &context))?;
+
+	let request = ListFillsRequest {
+		market: query.market.clone(),
+		public_key: principal.id(),
+		start_time: query.start_time.unwrap_or(0),
+		end_time: query.end_time.unwrap_or(0),
+		cursor: history_cursor(query.cursor.as_deref(), &context)?,
+		limit: query.limit.unwrap_or(0),
+	};
+	let response = match state.dispatcher.fills(request).await {
+		Ok(response) => {
+			replay_guard.finish(ReplayOutcome::Terminal);
+			response
+		}
+		Err(err) => {
+			// Reads have no side effects, so the nonce may be retried
+			replay_guard.finish(ReplayOutcome::RetryableFailure);
+			return Err(GatewayError::dispatch(err, &context));
+		}
+	};
+
+	let (price_decimals, size_decimals) = market_decimals(&state, &query.market);
+	Ok(HttpResponse::Ok().json(FillsPage {
+		fills: response
+			.fills
+			.into_iter()
+			.map(|f| Fill {
+				side: proto_side(f.side()),
+				role: match f.role() {
+					ProtoLiquidityRole::Maker => LiquidityRole::Maker,
+					ProtoLiquidityRole::Taker => LiquidityRole::Taker,
+				},
+				trade_id: f.trade_id,
+				market: f.market,
+				order_id: f.order_id,
+				price: format_units(f.price as u128, price_decimals),
+				size: format_units(f.size as u128, size_decimals),
+				timestamp: f.timestamp,
+			})
+			.collect(),
+		next_cursor: Some(response.next_cursor).filter(|cursor| !cursor.is_empty()),
+	}))
+}
+
+fn proto_side(side: ProtoOrderSide) -> Side {
+	match side {
+		ProtoOrderSide::Buy => Side::Buy,
+		ProtoOrderSide::Sell => Side::Sell,
+	}
+}
+
+/// Price and size decimals of a market; integer units if it has no spec
+pub(crate) fn market_decimals(state: &GatewayState, market: &str) -> (u32, u32) {
+	state
+		.dispatcher
+		.market_spec(market)
+		.map(|spec| (spec.price_decimals, spec.size_decimals))
+		.unwrap_or((0, 0))
+}
+
+fn request_context(req: &HttpRequest) -> RequestContext {
+	RequestContext::from_http(req).unwrap_or_else(|| RequestContext {
+		request_id: Uuid::new_v4().to_string(),
+		trace_id: Uuid::new_v4().to_string(),
+		traceparent: None,
+		tracestate: None,
+	})
+}
+
 /// Handle order placement request
 ///
 /// Gateway performs cryptographic authentication and protocol-level admission control.
@@ -267,38 +694,522 @@ pub async fn place_order(
 	let authenticated =
 		auth::authenticate_with_provider(&auth_ctx, &request, state.auth_provider.as_ref())
 			.map_err(|e| GatewayError::auth(e, &context))?;
-	let principal = authenticated.principal;
-	tracing::Span::current().record("principal_id", field::display(principal.id()));
+	tracing::Span::current().record("principal_id", field::display(authenticated.principal.id()));
+
+	let response = submit_order(&state, &request, &authenticated, None, &context).await?;
+	Ok(HttpResponse::Ok().json(response))
+}
+
+/// Admit an authenticated order and dispatch it to its matching engine
+///
+/// Every order entry front end goes through here, so all of them apply the
+/// same rate limit, admission and replay checks. With `replaces`, the order
+/// amends that resting order of the principal.
+///
+/// A duplicate `(principal, nonce)` of an order that was accepted, or whose
+/// outcome is unknown, is answered with that order's current state from the
+/// matching engine instead of `REPLAY_DETECTED`. A retry after an unknown
+/// outcome is only dispatched if the engine does not know the earlier
+/// order, and reuses its order ID.
+pub(crate) async fn submit_order(
+	state: &GatewayState,
+	request: &PlaceOrderRequest,
+	authenticated: &AuthenticatedPrincipal,
+	replaces: Option<String>,
+	context: &RequestContext,
+) -> Result<PlaceOrderResponse, GatewayError> {
+	let principal = &authenticated.principal;
 
 	// Check rate limit by principal (public key)
 	// Gateway only performs rate limiting at the cryptographic principal level,
 	// not at the business user level.
-	admission::check_rate_limit(&principal).map_err(|e| GatewayError::admission(e, &context))?;
+	admission::check_rate_limit(principal).map_err(|e| GatewayError::admission(e, context))?;
 
-	// Validate and admit the order (protocol-level checks)
-	admission::validate_and_admit(&request).map_err(|e| GatewayError::admission(e, &context))?;
+	// Validate and admit the order (protocol-level checks), converting
+	// decimal prices and sizes to market units
+	let admitted = admission::validate_and_admit(
+		request,
+		state.dispatcher.market_spec(&request.market).as_ref(),
+	)
+	.map_err(|e| GatewayError::admission(e, context))?;
 
-	let replay_guard: ReplayGuard =
-		admission::begin_replay(&principal, authenticated.timestamp, &authenticated.nonce)
-			.map_err(|e| GatewayError::admission(e, &context))?;
+	let (replay_guard, order_id): (ReplayGuard, String) = match admission::begin_order_replay(
+		principal,
+		authenticated.timestamp,
+		&authenticated.nonce,
+	)
+	.map_err(|e| GatewayError::admission(e, context))?
+	{
+		OrderAttempt::Replay(recorded) => {
+			return match recorded_order_state(state, &recorded, principal, context).await {
+				Ok(Some(response)) => Ok(response),
+				// The order never reached the engine, and its retry is used up
+				Ok(None) => Err(GatewayError::admission(
+					AdmissionError::ReplayDetected,
+					context,
+				)),
+				Err(err) => Err(err),
+			};
+		}
+		OrderAttempt::Dispatch {
+			guard,
+			previous: Some(previous),
+		} => match recorded_order_state(state, &previous, principal, context).await {
+			Ok(Some(response)) => {
+				guard.finish(ReplayOutcome::Terminal);
+				return Ok(response);
+			}
+			Ok(None) => (guard, previous.order_id),
+			Err(err) => {
+				guard.finish(ReplayOutcome::RetryableFailure);
+				return Err(err);
+			}
+		},
+		OrderAttempt::Dispatch {
+			guard,
+			previous: None,
+		} => (guard, Uuid::new_v4().to_string()),
+	};
+	let recorded = RecordedOrder {
+		market: request.market.clone(),
+		order_id: order_id.clone(),
+	};
 
 	// Dispatch to matching engine (use principal.id() as identifier)
 	// Note: principal.id() returns hex-encoded public key, which is passed
 	// to matching engine as the principal identifier (not a business user ID).
-	let dispatch_result = state
-		.dispatcher
-		.dispatch_order(request.into_inner(), principal.id(), context.clone())
-		.await;
+	let dispatch_result = match replaces {
+		Some(replaces) => {
+			state
+				.dispatcher
+				.dispatch_replace(
+					admitted,
+					order_id,
+					replaces,
+					principal.id(),
+					authenticated.nonce.clone(),
+					context.clone(),
+				)
+				.await
+		}
+		None => {
+			state
+				.dispatcher
+				.dispatch_order(
+					admitted,
+					order_id,
+					principal.id(),
+					authenticated.nonce.clone(),
+					context.clone(),
+				)
+				.await
+		}
+	};
 
 	match dispatch_result {
 		Ok(DispatchResult { order, timings, .. }) => {
 			tracing::Span::current().record("queue_wait_ms", field::display(timings.queue_wait_ms));
 			tracing::Span::current().record("rpc_ms", field::display(timings.rpc_ms));
+			replay_guard.record_order(recorded);
 			replay_guard.finish(ReplayOutcome::Terminal);
-			Ok(HttpResponse::Ok().json(PlaceOrderResponse {
+			Ok(PlaceOrderResponse {
 				order_id: order.order_id,
 				status: OrderStatus::Accepted,
+				client_order_id: order.client_order_id,
+			})
+		}
+		Err(err) => {
+			let (gateway_err, outcome) = map_dispatch_error(err, context);
+			// After a retryable failure the engine may still have received
+			// the order; a duplicate must look it up before dispatching again
+			if matches!(outcome, ReplayOutcome::RetryableFailure) {
+				replay_guard.record_order(recorded);
+			}
+			replay_guard.finish(outcome);
+			Err(gateway_err)
+		}
+	}
+}
+
+/// Current state of an order dispatched under a replayed nonce
+///
+/// `None` if the matching engine does not know the order.
+async fn recorded_order_state(
+	state: &GatewayState,
+	recorded: &RecordedOrder,
+	principal: &auth::Principal,
+	context: &RequestContext,
+) -> Result<Option<PlaceOrderResponse>, GatewayError> {
+	match state
+		.dispatcher
+		.get_order(&recorded.market, &recorded.order_id, &principal.id())
+		.await
+	{
+		Ok(order) => Ok(Some(PlaceOrderResponse {
+			status: order_status(order.status()),
+			order_id: order.order_id,
+			client_order_id: Some(order.client_order_id).filter(|id| !id.is_empty()),
+		})),
+		Err(DispatcherError::OrderNotFound(_)) => Ok(None),
+		Err(err) => Err(GatewayError::dispatch(err, context)),
+	}
+}
+
+fn order_status(status: ProtoOrderStatus) -> OrderStatus {
+	match status {
+		ProtoOrderStatus::Pending => OrderStatus::Pending,
+		ProtoOrderStatus::Accepted => OrderStatus::Accepted,
+		ProtoOrderStatus::PartiallyFilled => OrderStatus::PartiallyFilled,
+		ProtoOrderStatus::Filled => OrderStatus::Filled,
+		ProtoOrderStatus::Cancelled => OrderStatus::Cancelled,
+		ProtoOrderStatus::Rejected => OrderStatus::Rejected,
+	}
+}
+
+/// Rate limit, replay-check and dispatch an authenticated cancel
+pub(crate) async fn submit_cancel(
+	state: &GatewayState,
+	market: &str,
+	order_id: &str,
+	authenticated: &AuthenticatedPrincipal,
+	context: &RequestContext,
+) -> Result<CancelOrderResponse, GatewayError> {
+	let principal = &authenticated.principal;
+	admission::check_rate_limit(principal).map_err(|e| GatewayError::admission(e, context))?;
+	let replay_guard =
+		admission::begin_replay(principal, authenticated.timestamp, &authenticated.nonce)
+			.map_err(|e| GatewayError::admission(e, context))?;
+
+	match state
+		.dispatcher
+		.cancel_order(market, order_id, &principal.id(), context)
+		.await
+	{
+		Ok(()) => {
+			replay_guard.finish(ReplayOutcome::Terminal);
+			Ok(CancelOrderResponse {
+				order_id: order_id.to_string(),
 				client_order_id: None,
+			})
+		}
+		Err(err) => {
+			let (gateway_err, outcome) = map_dispatch_error(err, context);
+			replay_guard.finish(outcome);
+			Err(gateway_err)
+		}
+	}
+}
+
+/// Query parameters of a batch cancel
+#[derive(Debug, Deserialize)]
+pub struct BatchCancelQuery {
+	pub market: String,
+	/// Comma-separated order IDs
+	pub order_ids: String,
+}
+
+/// Place several orders of one market under a single signature
+///
+/// The signature covers [`auth::batch_signing_message`] over the orders in
+/// request order. The response holds one result per order, in order.
+pub async fn place_orders(
+	state: web::Data<GatewayState>,
+	request: web::Json<BatchPlaceOrderRequest>,
+	req: HttpRequest,
+) -> Result<HttpResponse, GatewayError> {
+	let context = request_context(&req);
+	let authenticated = auth::authenticate_batch(
+		&AuthContext::from_http(req.headers()),
+		&request.orders,
+		state.auth_provider.as_ref(),
+	)
+	.map_err(|e| GatewayError::auth(e, &context))?;
+	tracing::Span::current().record("principal_id", field::display(authenticated.principal.id()));
+
+	let response = submit_orders(&state, &request.orders, &authenticated, &context).await?;
+	Ok(HttpResponse::Ok().json(response))
+}
+
+/// Cancel several orders of one market under a single signature
+///
+/// `DELETE /api/v1/orders/batch?market=...&order_ids=a,b`, signed like
+/// [`get_fills`]. The response holds one result per order ID, in order.
+pub async fn cancel_orders(
+	state: web::Data<GatewayState>,
+	query: web::Query<BatchCancelQuery>,
+	req: HttpRequest,
+) -> Result<HttpResponse, GatewayError> {
+	let context = request_context(&req);
+	let authenticated = authenticate_query(&state, &req, &context)?;
+
+	let order_ids: Vec<String> = query
+		.order_ids
+		.split(',')
+		.filter(|order_id| !order_id.is_empty())
+		.map(str::to_string)
+		.collect();
+	let response =
+		submit_cancels(&state, &query.market, &order_ids, &authenticated, &context).await?;
+	Ok(HttpResponse::Ok().json(response))
+}
+
+/// Reject batches that are empty or larger than the configured limit
+fn check_batch_size(
+	state: &GatewayState,
+	len: usize,
+	ctx: &RequestContext,
+) -> Result<(), GatewayError> {
+	if len == 0 || len > state.max_batch_orders {
+		return Err(GatewayError::invalid_request(
+			format!("Batch must contain 1 to {} entries", state.max_batch_orders),
+			ctx,
+		));
+	}
+	Ok(())
+}
+
+/// Admit an authenticated batch of orders and dispatch it in one piece
+///
+/// The batch counts as one request for the rate limit and replay checks.
+/// Orders that fail admission or that the engine rejects get their own
+/// error; the others are sequenced back to back. The nonce may only be
+/// retried if no order was accepted and a failure was retryable.
+pub(crate) async fn submit_orders(
+	state: &GatewayState,
+	orders: &[PlaceOrderRequest],
+	authenticated: &AuthenticatedPrincipal,
+	context: &RequestContext,
+) -> Result<BatchPlaceOrderResponse, GatewayError> {
+	check_batch_size(state, orders.len(), context)?;
+	let market = &orders[0].market;
+	if orders.iter().any(|order| &order.market != market) {
+		return Err(GatewayError::invalid_request(
+			"Batch orders must share one market",
+			context,
+		));
+	}
+
+	let principal = &authenticated.principal;
+	admission::check_rate_limit(principal).map_err(|e| GatewayError::admission(e, context))?;
+	let replay_guard =
+		admission::begin_replay(principal, authenticated.timestamp, &authenticated.nonce)
+			.map_err(|e| GatewayError::admission(e, context))?;
+
+	// Admission errors by position; `None` marks the orders to dispatch
+	let mut rejections = Vec::with_capacity(orders.len());
+	let mut admitted = Vec::new();
+	for order in orders {
+		match admission::validate_and_admit(order, state.dispatcher.market_spec(market).as_ref()) {
+			Ok(order) => {
+				admitted.push(order);
+				rejections.push(None);
+			}
+			Err(e) => rejections.push(Some(GatewayError::admission(e, context))),
+		}
+	}
+
+	let mut outcomes = if admitted.is_empty() {
+		Vec::new()
+	} else {
+		match state
+			.dispatcher
+			.dispatch_batch(
+				market,
+				admitted,
+				&principal.id(),
+				&authenticated.nonce,
+				context,
+			)
+			.await
+		{
+			Ok(outcomes) => outcomes,
+			Err(err) => {
+				let (gateway_err, outcome) = map_dispatch_error(err, context);
+				replay_guard.finish(outcome);
+				return Err(gateway_err);
+			}
+		}
+	}
+	.into_iter();
+
+	let mut accepted = false;
+	let mut retryable = false;
+	let mut results = Vec::with_capacity(orders.len());
+	for (order, rejection) in orders.iter().zip(rejections) {
+		let error = match rejection {
+			Some(error) => error,
+			None => match outcomes.next() {
+				Some(Ok(dispatched)) => {
+					accepted = true;
+					results.push(BatchOrderResult {
+						order_id: Some(dispatched.order_id),
+						status: OrderStatus::Accepted,
+						client_order_id: order.client_order_id.clone(),
+						error: None,
+					});
+					continue;
+				}
+				Some(Err(err)) => {
+					let (error, outcome) = map_dispatch_error(err, context);
+					retryable |= matches!(outcome, ReplayOutcome::RetryableFailure);
+					error
+				}
+				None => GatewayError::dispatch(
+					DispatcherError::InvalidResponse("Missing batch result".to_string()),
+					context,
+				),
+			},
+		};
+		results.push(BatchOrderResult {
+			order_id: None,
+			status: OrderStatus::Rejected,
+			client_order_id: order.client_order_id.clone(),
+			error: Some(error.to_batch_error()),
+		});
+	}
+
+	replay_guard.finish(if retryable && !accepted {
+		ReplayOutcome::RetryableFailure
+	} else {
+		ReplayOutcome::Terminal
+	});
+	Ok(BatchPlaceOrderResponse { results })
+}
+
+/// Rate limit, replay-check and dispatch an authenticated batch cancel
+///
+/// Replay handling follows [`submit_orders`].
+pub(crate) async fn submit_cancels(
+	state: &GatewayState,
+	market: &str,
+	order_ids: &[String],
+	authenticated: &AuthenticatedPrincipal,
+	context: &RequestContext,
+) -> Result<BatchCancelResponse, GatewayError> {
+	check_batch_size(state, order_ids.len(), context)?;
+	let principal = &authenticated.principal;
+	admission::check_rate_limit(principal).map_err(|e| GatewayError::admission(e, context))?;
+	let replay_guard =
+		admission::begin_replay(principal, authenticated.timestamp, &authenticated.nonce)
+			.map_err(|e| GatewayError::admission(e, context))?;
+
+	let outcomes = match state
+		.dispatcher
+		.cancel_batch(market, order_ids, &principal.id(), context)
+		.await
+	{
+		Ok(outcomes) => outcomes,
+		Err(err) => {
+			let (gateway_err, outcome) = map_dispatch_error(err, context);
+			replay_guard.finish(outcome);
+			return Err(gateway_err);
+		}
+	};
+
+	let mut accepted = false;
+	let mut retryable = false;
+	let results = order_ids
+		.iter()
+		.zip(outcomes)
+		.map(|(order_id, outcome)| BatchCancelResult {
+			order_id: order_id.clone(),
+			error: match outcome {
+				Ok(()) => {
+					accepted = true;
+					None
+				}
+				Err(err) => {
+					let (error, outcome) = map_dispatch_error(err, context);
+					retryable |= matches!(outcome, ReplayOutcome::RetryableFailure);
+					Some(error.to_batch_error())
+				}
+			},
+		})
+		.collect();
+
+	replay_guard.finish(if retryable && !accepted {
+		ReplayOutcome::RetryableFailure
+	} else {
+		ReplayOutcome::Terminal
+	});
+	Ok(BatchCancelResponse { results })
+}
+
+/// Query parameters of the by-client-order-ID endpoints
+#[derive(Debug, Deserialize)]
+pub struct ClientOrderQuery {
+	pub market: String,
+}
+
+/// The calling principal's latest order with a client order ID
+///
+/// `GET /api/v1/orders/by-client-id/{client_order_id}?market=...`, signed
+/// like [`get_fills`]. Prices and sizes are in market units.
+pub async fn get_order_by_client_id(
+	state: web::Data<GatewayState>,
+	path: web::Path<String>,
+	query: web::Query<ClientOrderQuery>,
+	req: HttpRequest,
+) -> Result<HttpResponse, GatewayError> {
+	let context = request_context(&req);
+	let client_order_id = path.into_inner();
+	let authenticated = authenticate_query(&state, &req, &context)?;
+	let principal = &authenticated.principal;
+
+	admission::check_rate_limit(principal).map_err(|e| GatewayError::admission(e, &context))?;
+	let replay_guard =
+		admission::begin_replay(principal, authenticated.timestamp, &authenticated.nonce)
+			.map_err(|e| GatewayError::admission(e, &context))?;
+
+	let order = match state
+		.dispatcher
+		.get_order_by_client_id(&query.market, &client_order_id, &principal.id())
+		.await
+	{
+		Ok(order) => {
+			replay_guard.finish(ReplayOutcome::Terminal);
+			order
+		}
+		Err(err) => {
+			// Reads have no side effects, so the nonce may be retried
+			replay_guard.finish(ReplayOutcome::RetryableFailure);
+			return Err(GatewayError::dispatch(err, &context));
+		}
+	};
+	Ok(HttpResponse::Ok().json(sdk_order(order)))
+}
+
+/// Cancel the calling principal's resting order with a client order ID
+///
+/// `DELETE /api/v1/orders/by-client-id/{client_order_id}?market=...`,
+/// signed like [`get_fills`]. As with other cancels, the outcome is
+/// reported on the execution report stream.
+pub async fn cancel_order_by_client_id(
+	state: web::Data<GatewayState>,
+	path: web::Path<String>,
+	query: web::Query<ClientOrderQuery>,
+	req: HttpRequest,
+) -> Result<HttpResponse, GatewayError> {
+	let context = request_context(&req);
+	let client_order_id = path.into_inner();
+	let authenticated = authenticate_query(&state, &req, &context)?;
+	let principal = &authenticated.principal;
+
+	admission::check_rate_limit(principal).map_err(|e| GatewayError::admission(e, &context))?;
+	let replay_guard =
+		admission::begin_replay(principal, authenticated.timestamp, &authenticated.nonce)
+			.map_err(|e| GatewayError::admission(e, &context))?;
+
+	match state
+		.dispatcher
+		.cancel_order_by_client_id(&query.market, &client_order_id, &principal.id(), &context)
+		.await
+	{
+		Ok(order_id) => {
+			replay_guard.finish(ReplayOutcome::Terminal);
+			Ok(HttpResponse::Ok().json(CancelOrderResponse {
+				order_id: order_id.unwrap_or_default(),
+				client_order_id: Some(client_order_id),
 			}))
 		}
 		Err(err) => {
@@ -309,6 +1220,45 @@ pub async fn place_order(
 	}
 }
 
+/// Authenticate a signed request without a body, such as a query
+fn authenticate_query(
+	state: &GatewayState,
+	req: &HttpRequest,
+	context: &RequestContext,
+) -> Result<AuthenticatedPrincipal, GatewayError> {
+	let path_and_query = req
+		.uri()
+		.path_and_query()
+		.map(|pq| pq.as_str())
+		.unwrap_or_else(|| req.uri().path());
+	let authenticated = auth::authenticate_query(
+		&AuthContext::from_http(req.headers()),
+		req.method().as_str(),
+		path_and_query,
+		state.auth_provider.as_ref(),
+	)
+	.map_err(|e| GatewayError::auth(e, context))?;
+	tracing::Span::current().record("principal_id", field::display(authenticated.principal.id()));
+	Ok(authenticated)
+}
+
+/// Convert a matching engine order to its client form
+fn sdk_order(order: ProtoOrder) -> Order {
+	Order {
+		side: proto_side(order.side()),
+		status: order_status(order.status()),
+		order_type: OrderType::Limit,
+		price: Some(order.price).filter(|price| *price != 0),
+		size: order.size,
+		filled_size: order.filled_size,
+		remaining_size: order.remaining_size,
+		created_at: order.created_at,
+		client_order_id: Some(order.client_order_id).filter(|id| !id.is_empty()),
+		order_id: order.order_id,
+		market: order.market,
+	}
+}
+
 /// Handle order query request
 pub async fn get_order(
 	_state: web::Data<GatewayState>,
@@ -350,6 +1300,13 @@ pub async fn cancel_order(
 }
 
 fn map_dispatch_error(err: DispatcherError, ctx: &RequestContext) -> (GatewayError, ReplayOutcome) {
+	if let DispatcherError::InsufficientBalance(reason) = err {
+		return (
+			GatewayError::admission(AdmissionError::InsufficientBalance(reason), ctx),
+			ReplayOutcome::Terminal,
+		);
+	}
+
 	let outcome = match err {
 		DispatcherError::GatewayOverloaded
 		| DispatcherError::QueueTimeout
@@ -357,10 +1314,12 @@ fn map_dispatch_error(err: DispatcherError, ctx: &RequestContext) -> (GatewayErr
 		| DispatcherError::MatchingOverloaded(_)
 		| DispatcherError::MatchingInternal(_)
 		| DispatcherError::InvalidResponse(_)
+		| DispatcherError::MarketUnavailable(_)
 		| DispatcherError::DispatchingError(_) => ReplayOutcome::RetryableFailure,
-		DispatcherError::MatchingRejected(_) | DispatcherError::MatchingEngineNotFound(_) => {
-			ReplayOutcome::Terminal
-		}
+		DispatcherError::MatchingRejected(_)
+		| DispatcherError::InsufficientBalance(_)
+		| DispatcherError::MatchingEngineNotFound(_)
+		| DispatcherError::OrderNotFound(_) => ReplayOutcome::Terminal,
 	};
 
 	(GatewayError::dispatch(err, ctx), outcome)
@@ -405,4 +1364,38 @@ mod tests {
 		assert_eq!(json["code"], "GATEWAY_OVERLOADED");
 		assert_eq!(json["retryable"], true);
 	}
+
+	#[test]
+	fn grpc_status_carries_the_error_body() {
+		let err = GatewayError::dispatch(DispatcherError::MatchingTimeout, &ctx());
+		let status = err.to_status();
+		assert_eq!(status.code(), tonic::Code::DeadlineExceeded);
+		assert_eq!(status.metadata().get("error-code").unwrap(), "UNCONFIRMED");
+		assert_eq!(status.metadata().get("request-id").unwrap(), "req-test");
+		assert_eq!(status.metadata().get("unconfirmed").unwrap(), "true");
+
+		let err = GatewayError::dispatch(DispatcherError::OrderNotFound("o1".to_string()), &ctx());
+		assert_eq!(err.to_status().code(), tonic::Code::NotFound);
+	}
+
+	#[test]
+	fn batch_errors_mirror_the_error_body() {
+		let err =
+			GatewayError::dispatch(DispatcherError::MatchingOverloaded(String::new()), &ctx());
+		let error = err.to_batch_error();
+		assert_eq!(error.code, "UNCONFIRMED");
+		assert_eq!(error.reason, "Matching engine overloaded");
+		assert!(error.retryable && error.unconfirmed);
+	}
+
+	#[actix_rt::test]
+	async fn invalid_request_is_bad_request() {
+		let err = GatewayError::invalid_request("Unsupported candle interval: 2m", &ctx());
+		let resp = err.error_response();
+		assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+		let body = to_bytes(resp.into_body()).await.unwrap();
+		let json: Value = serde_json::from_slice(&body).unwrap();
+		assert_eq!(json["code"], "INVALID_REQUEST");
+		assert_eq!(json["retryable"], false);
+	}
 }
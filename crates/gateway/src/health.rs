@@ -0,0 +1,320 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Active health checking of matching engine endpoints
+//!
+//! The prober calls the standard gRPC health service of every configured
+//! matching engine. Each endpoint has a circuit breaker:
+//!
+//! - **Closed**: requests flow; consecutive failed probes are counted
+//! - **Open**: after `failure_threshold` failed probes in a row, requests for
+//!   the endpoint's markets fail fast with `MARKET_UNAVAILABLE` and the
+//!   endpoint is not probed for `open_duration`
+//! - **Half-open**: the open period is over; requests are still refused
+//!   and the next probe decides whether the circuit closes or opens again
+//!
+//! Circuit states are published to the dispatcher after every round of
+//! probes. The dispatcher then fails markets over where needed, and the
+//! availability of each market's active engine is published to admission.
+
+use std::{
+	collections::HashMap,
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+use anvil_matching::server::proto::matching_service_server::SERVICE_NAME;
+use serde::Serialize;
+use tokio::{task::JoinSet, time::MissedTickBehavior};
+use tonic::transport::{Channel, Endpoint};
+use tonic_health::pb::{
+	HealthCheckRequest, health_check_response::ServingStatus, health_client::HealthClient,
+};
+
+use crate::{admission, config::GatewayRuntimeConfig, dispatcher::MatchingDispatcher};
+
+/// Circuit breaker state of a matching engine endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+	Closed,
+	Open,
+	HalfOpen,
+}
+
+/// Circuit breaker driven by health probes
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+	state: CircuitState,
+	consecutive_failures: u32,
+	opened_at: Instant,
+	failure_threshold: u32,
+	open_duration: Duration,
+}
+
+impl CircuitBreaker {
+	pub fn new(failure_threshold: u32, open_duration: Duration) -> Self {
+		Self {
+			state: CircuitState::Closed,
+			consecutive_failures: 0,
+			opened_at: Instant::now(),
+			failure_threshold,
+			open_duration,
+		}
+	}
+
+	pub fn state(&self) -> CircuitState {
+		self.state
+	}
+
+	/// Whether the endpoint should be probed at `now`
+	///
+	/// An open circuit whose open period is over becomes half-open.
+	pub fn should_probe(&mut self, now: Instant) -> bool {
+		if self.state == CircuitState::Open
+			&& now.duration_since(self.opened_at) >= self.open_duration
+		{
+			self.state = CircuitState::HalfOpen;
+		}
+		self.state != CircuitState::Open
+	}
+
+	pub fn record_success(&mut self) {
+		self.consecutive_failures = 0;
+		self.state = CircuitState::Closed;
+	}
+
+	pub fn record_failure(&mut self, now: Instant) {
+		self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+		if self.state == CircuitState::HalfOpen
+			|| self.consecutive_failures >= self.failure_threshold
+		{
+			self.state = CircuitState::Open;
+			self.opened_at = now;
+		}
+	}
+}
+
+/// Health probing settings
+#[derive(Debug, Clone)]
+pub struct HealthConfig {
+	/// Interval between probes of each endpoint
+	pub interval: Duration,
+	/// Probe timeout, including connection setup
+	pub timeout: Duration,
+	/// Consecutive failed probes that open a circuit
+	pub failure_threshold: u32,
+	/// How long an open circuit refuses requests before a half-open probe
+	pub open_duration: Duration,
+}
+
+impl HealthConfig {
+	pub fn from_runtime(config: &GatewayRuntimeConfig) -> Self {
+		Self {
+			interval: Duration::from_millis(config.health_check_interval_ms),
+			timeout: Duration::from_millis(config.health_check_timeout_ms),
+			failure_threshold: config.health_failure_threshold,
+			open_duration: Duration::from_millis(config.circuit_open_ms),
+		}
+	}
+}
+
+struct ProbedEndpoint {
+	breaker: CircuitBreaker,
+	/// `None` if the endpoint is not a valid URI
+	client: Option<HealthClient<Channel>>,
+}
+
+/// Background prober of the dispatcher's matching engine endpoints
+pub struct HealthProber {
+	dispatcher: Arc<MatchingDispatcher>,
+	config: HealthConfig,
+	endpoints: HashMap<String, ProbedEndpoint>,
+}
+
+impl HealthProber {
+	pub fn new(dispatcher: Arc<MatchingDispatcher>, config: HealthConfig) -> Self {
+		Self {
+			dispatcher,
+			config,
+			endpoints: HashMap::new(),
+		}
+	}
+
+	/// Probe every `interval`, forever
+	pub async fn run(mut self) {
+		let mut ticker = tokio::time::interval(self.config.interval);
+		ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+		loop {
+			ticker.tick().await;
+			self.probe_all().await;
+		}
+	}
+
+	/// Probe the endpoints that are due and publish the resulting states
+	async fn probe_all(&mut self) {
+		// Endpoints can change with a configuration reload
+		let routes = self.dispatcher.endpoints();
+		self.endpoints
+			.retain(|endpoint, _| routes.contains_key(endpoint));
+
+		let now = Instant::now();
+		let mut probes = JoinSet::new();
+		for endpoint in routes.keys() {
+			let probed = self
+				.endpoints
+				.entry(endpoint.clone())
+				.or_insert_with(|| ProbedEndpoint {
+					breaker: CircuitBreaker::new(
+						self.config.failure_threshold,
+						self.config.open_duration,
+					),
+					client: Endpoint::from_shared(endpoint.clone()).ok().map(|e| {
+						HealthClient::new(e.connect_timeout(self.config.timeout).connect_lazy())
+					}),
+				});
+			if probed.breaker.should_probe(now) {
+				let client = probed.client.clone();
+				let endpoint = endpoint.clone();
+				let timeout = self.config.timeout;
+				probes.spawn(async move {
+					let result = match client {
+						Some(client) => probe(client, timeout).await,
+						None => Err("Invalid endpoint".to_string()),
+					};
+					(endpoint, result)
+				});
+			}
+		}
+
+		while let Some(joined) = probes.join_next().await {
+			let Ok((endpoint, result)) = joined else {
+				continue;
+			};
+			let Some(probed) = self.endpoints.get_mut(&endpoint) else {
+				continue;
+			};
+			let before = probed.breaker.state();
+			match result {
+				Ok(()) => probed.breaker.record_success(),
+				Err(reason) => {
+					tracing::debug!(
+						target: "server::health",
+						"Health probe of {} failed: {}",
+						endpoint,
+						reason
+					);
+					probed.breaker.record_failure(Instant::now());
+				}
+			}
+			let after = probed.breaker.state();
+			if before != after {
+				let markets = routes
+					.get(&endpoint)
+					.map(|m| m.join(","))
+					.unwrap_or_default();
+				match after {
+					CircuitState::Closed => tracing::info!(
+						target: "server::health",
+						"Matching engine {} ({}) recovered, circuit closed",
+						endpoint,
+						markets
+					),
+					_ => tracing::warn!(
+						target: "server::health",
+						"Matching engine {} ({}) unhealthy, circuit {:?}",
+						endpoint,
+						markets,
+						after
+					),
+				}
+			}
+		}
+
+		for endpoint in routes.keys() {
+			let state = self
+				.endpoints
+				.get(endpoint)
+				.map(|probed| probed.breaker.state())
+				.unwrap_or(CircuitState::Closed);
+			self.dispatcher.set_circuit(endpoint, state);
+		}
+		self.dispatcher.fail_over().await;
+		for market in self.dispatcher.market_circuits() {
+			admission::set_market_available(&market.market, market.circuit == CircuitState::Closed);
+		}
+	}
+}
+
+/// Ask an engine's health service whether the matching service is serving
+async fn probe(mut client: HealthClient<Channel>, timeout: Duration) -> Result<(), String> {
+	let mut request = tonic::Request::new(HealthCheckRequest {
+		service: SERVICE_NAME.to_string(),
+	});
+	request.set_timeout(timeout);
+
+	let response = tokio::time::timeout(timeout, client.check(request))
+		.await
+		.map_err(|_| "Timed out".to_string())?
+		.map_err(|status| status.message().to_string())?
+		.into_inner();
+	match response.status() {
+		ServingStatus::Serving => Ok(()),
+		status => Err(format!("Status {}", status.as_str_name())),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn opens_after_consecutive_failures_and_recovers_half_open() {
+		let open_duration = Duration::from_secs(5);
+		let mut breaker = CircuitBreaker::new(3, open_duration);
+		let start = Instant::now();
+
+		breaker.record_failure(start);
+		breaker.record_failure(start);
+		breaker.record_success();
+		breaker.record_failure(start);
+		breaker.record_failure(start);
+		assert_eq!(breaker.state(), CircuitState::Closed);
+		breaker.record_failure(start);
+		assert_eq!(breaker.state(), CircuitState::Open);
+		assert!(!breaker.should_probe(start + Duration::from_secs(1)));
+
+		// A failed half-open probe opens the circuit again at once
+		assert!(breaker.should_probe(start + open_duration));
+		assert_eq!(breaker.state(), CircuitState::HalfOpen);
+		breaker.record_failure(start + open_duration);
+		assert_eq!(breaker.state(), CircuitState::Open);
+
+		assert!(breaker.should_probe(start + open_duration * 2));
+		breaker.record_success();
+		assert_eq!(breaker.state(), CircuitState::Closed);
+	}
+
+	#[tokio::test]
+	async fn unreachable_engine_fails_the_probe() {
+		let channel = Endpoint::from_static("http://127.0.0.1:1")
+			.connect_timeout(Duration::from_millis(100))
+			.connect_lazy();
+		assert!(
+			probe(HealthClient::new(channel), Duration::from_millis(200))
+				.await
+				.is_err()
+		);
+	}
+}
@@ -18,6 +18,10 @@
 //! authentication and protocol-level validation, and routes orders to the
 //! appropriate matching engine.
 //!
+//! Clients reach it over REST and WebSocket (`GATEWAY_BIND_ADDR`), the
+//! gRPC order entry service (`GATEWAY_GRPC_BIND_ADDR`) or, when configured,
+//! FIX 4.4 (`GATEWAY_FIX_BIND_ADDR`).
+//!
 //! # Identity Model
 //!
 //! Gateway only understands **cryptographic identity** (public keys and signatures),
@@ -33,16 +37,22 @@
 mod admission;
 mod auth;
 mod config;
+mod config_reload;
 mod dispatcher;
+mod fix;
 mod grpc_client;
+mod grpc_server;
 mod handlers;
+mod health;
 mod logging;
+mod market_feed;
 mod middleware;
 mod otel;
 mod request_context;
 mod routes;
 mod server;
 mod trace_context;
+mod ws;
 
 use anyhow::{Context, Result};
 use tracing::info;
@@ -0,0 +1,847 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Public market data channels
+//!
+//! [`MarketFeed`] fans public market data out to WebSocket clients on four
+//! kinds of channel:
+//!
+//! - `book.{market}`: an L2 snapshot on subscribe, then changed levels
+//! - `trades.{market}`: public trades, without order IDs or owners
+//! - `ticker.{market}`: rolling 24h statistics after every batch of trades
+//! - `candles.{market}.{interval}`: the current candle of `interval`
+//!
+//! Each market has at most one upstream `StreamMarketData` subscription to
+//! its matching engine, opened by the first subscriber and closed when the
+//! last one leaves. Updates are serialized once and offered to every
+//! subscriber's bounded buffer; a client whose buffer is full is dropped as
+//! a slow consumer rather than holding up the others.
+//!
+//! Every update carries a per-channel `sequence` that grows by one per
+//! message, so a skipped value means a message was lost. Book sequences are
+//! the matching engine's L2 update sequences and a snapshot carries the
+//! sequence it reflects. When the upstream stream breaks, the feed
+//! re-subscribes, sends every book subscriber a fresh snapshot and skips
+//! one trade sequence to signal that trades may have been missed.
+
+use std::{
+	collections::{BTreeMap, HashMap},
+	fmt,
+	str::FromStr,
+	sync::{
+		Arc, Mutex,
+		atomic::{AtomicU64, Ordering},
+	},
+	time::Duration,
+};
+
+use anvil_matching::market_data::CandleInterval;
+use anvil_sdk::{
+	decimal::format_units,
+	types::{Candle, PublicTrade, Ticker},
+};
+use serde::Serialize;
+use thiserror::Error;
+use tokio::sync::{Notify, mpsc, mpsc::error::TrySendError};
+
+use crate::{
+	dispatcher::{DispatcherError, MatchingDispatcher},
+	grpc_client::proto::{
+		BookUpdate, CandleUpdate, GetOrderBookResponse, MarketDataUpdate, PriceLevel,
+		market_data_update::Body,
+	},
+	handlers,
+};
+
+/// Delay before re-subscribing to a market's upstream stream
+const UPSTREAM_RESUBSCRIBE_DELAY: Duration = Duration::from_secs(1);
+
+/// Serialized `update` frame shared by every subscriber of a channel
+pub type FeedFrame = Arc<str>;
+
+/// Error types for channel subscriptions
+#[derive(Debug, Error)]
+pub enum FeedError {
+	#[error("Unknown channel: {0}")]
+	UnknownChannel(String),
+	#[error("Unknown market: {0}")]
+	UnknownMarket(String),
+	#[error("Not subscribed to channel: {0}")]
+	NotSubscribed(String),
+}
+
+/// A public market data channel
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Channel {
+	Book(String),
+	Trades(String),
+	Ticker(String),
+	Candles(String, CandleInterval),
+}
+
+impl Channel {
+	pub fn market(&self) -> &str {
+		match self {
+			Channel::Book(market)
+			| Channel::Trades(market)
+			| Channel::Ticker(market)
+			| Channel::Candles(market, _) => market,
+		}
+	}
+}
+
+impl fmt::Display for Channel {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Channel::Book(market) => write!(f, "book.{}", market),
+			Channel::Trades(market) => write!(f, "trades.{}", market),
+			Channel::Ticker(market) => write!(f, "ticker.{}", market),
+			Channel::Candles(market, interval) => write!(f, "candles.{}.{}", market, interval),
+		}
+	}
+}
+
+impl FromStr for Channel {
+	type Err = FeedError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let unknown = || FeedError::UnknownChannel(s.to_string());
+		let (kind, market) = s.split_once('.').ok_or_else(unknown)?;
+		let channel = match kind {
+			"book" => Channel::Book(market.to_string()),
+			"trades" => Channel::Trades(market.to_string()),
+			"ticker" => Channel::Ticker(market.to_string()),
+			"candles" => {
+				let (market, interval) = market.rsplit_once('.').ok_or_else(unknown)?;
+				let interval = interval.parse().map_err(|_| unknown())?;
+				Channel::Candles(market.to_string(), interval)
+			}
+			_ => return Err(unknown()),
+		};
+		if channel.market().is_empty() {
+			return Err(unknown());
+		}
+		Ok(channel)
+	}
+}
+
+/// Aggregated size at one price; prices and sizes are decimal strings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BookLevel {
+	pub price: String,
+	/// Zero in a `delta` removes the level
+	pub size: String,
+}
+
+/// Payload of a channel update, tagged by `kind`
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChannelData {
+	/// The whole book; replaces any book the client holds
+	Snapshot {
+		/// Best (highest) first
+		bids: Vec<BookLevel>,
+		/// Best (lowest) first
+		asks: Vec<BookLevel>,
+	},
+	/// Levels changed by one command
+	Delta {
+		bids: Vec<BookLevel>,
+		asks: Vec<BookLevel>,
+	},
+	Trade(PublicTrade),
+	Ticker(Ticker),
+	Candle(Candle),
+}
+
+/// `update` frame sent to channel subscribers
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename = "update")]
+pub struct ChannelUpdate {
+	pub channel: String,
+	/// Grows by one per message on the channel
+	pub sequence: u64,
+	#[serde(flatten)]
+	pub data: ChannelData,
+}
+
+impl ChannelUpdate {
+	fn frame(&self) -> FeedFrame {
+		serde_json::to_string(self)
+			.expect("Channel updates always serialize")
+			.into()
+	}
+}
+
+/// One client's delivery buffer
+#[derive(Clone)]
+struct Subscriber {
+	tx: mpsc::Sender<FeedFrame>,
+	/// Notified when the client is dropped for falling behind
+	overflow: Arc<Notify>,
+}
+
+/// The gateway's L2 book of a market, kept to serve snapshots
+#[derive(Debug, Default)]
+struct LocalBook {
+	sequence: u64,
+	bids: BTreeMap<u64, u64>,
+	asks: BTreeMap<u64, u64>,
+}
+
+/// An update does not follow the local book's sequence
+#[derive(Debug)]
+struct BookGap {
+	expected: u64,
+	received: u64,
+}
+
+/// Channel state and subscribers of one market
+struct MarketChannels {
+	market: String,
+	price_decimals: u32,
+	size_decimals: u32,
+	/// Absent until the upstream snapshot arrives
+	book: Option<LocalBook>,
+	/// Last sequence sent on each non-book channel
+	sequences: HashMap<Channel, u64>,
+	/// Latest ticker and candle frames, sent to new subscribers
+	latest: HashMap<Channel, FeedFrame>,
+	subscribers: HashMap<Channel, HashMap<u64, Subscriber>>,
+}
+
+impl MarketChannels {
+	fn new(market: String, price_decimals: u32, size_decimals: u32) -> Self {
+		Self {
+			market,
+			price_decimals,
+			size_decimals,
+			book: None,
+			sequences: HashMap::new(),
+			latest: HashMap::new(),
+			subscribers: HashMap::new(),
+		}
+	}
+
+	fn is_empty(&self) -> bool {
+		self.subscribers.is_empty()
+	}
+
+	/// Add a subscriber and send it the channel's current state, if any
+	fn subscribe(&mut self, channel: Channel, client: u64, subscriber: Subscriber) {
+		let current = match &channel {
+			Channel::Book(_) => self.book.as_ref().map(|book| self.snapshot_frame(book)),
+			Channel::Trades(_) => None,
+			Channel::Ticker(_) | Channel::Candles(..) => self.latest.get(&channel).cloned(),
+		};
+		if let Some(frame) = current {
+			// The buffer is rarely full this early; the next update will tell
+			let _ = subscriber.tx.try_send(frame);
+		}
+		self.subscribers
+			.entry(channel)
+			.or_default()
+			.insert(client, subscriber);
+	}
+
+	fn unsubscribe(&mut self, channel: &Channel, client: u64) {
+		if let Some(subscribers) = self.subscribers.get_mut(channel) {
+			subscribers.remove(&client);
+			if subscribers.is_empty() {
+				self.subscribers.remove(channel);
+			}
+		}
+	}
+
+	fn remove_client(&mut self, client: u64) {
+		self.subscribers.retain(|_, subscribers| {
+			subscribers.remove(&client);
+			!subscribers.is_empty()
+		});
+	}
+
+	/// Replace the local book and send the snapshot to book subscribers
+	fn reset_book(&mut self, snapshot: GetOrderBookResponse) {
+		let levels =
+			|levels: Vec<PriceLevel>| levels.into_iter().map(|l| (l.price, l.size)).collect();
+		let book = LocalBook {
+			sequence: snapshot.sequence,
+			bids: levels(snapshot.bids),
+			asks: levels(snapshot.asks),
+		};
+		let frame = self.snapshot_frame(&book);
+		self.book = Some(book);
+		self.deliver(&Channel::Book(self.market.clone()), frame);
+	}
+
+	/// Apply one upstream update and fan it out
+	fn apply(&mut self, update: MarketDataUpdate) -> Result<(), BookGap> {
+		let (price_decimals, size_decimals) = (self.price_decimals, self.size_decimals);
+		match update.body {
+			Some(Body::Book(update)) => self.apply_book(update)?,
+			Some(Body::Trade(trade)) => {
+				let data = ChannelData::Trade(handlers::public_trade(
+					trade,
+					price_decimals,
+					size_decimals,
+				));
+				self.publish(Channel::Trades(self.market.clone()), data);
+			}
+			Some(Body::Ticker(ticker)) => {
+				let data =
+					ChannelData::Ticker(handlers::ticker(ticker, price_decimals, size_decimals));
+				self.publish(Channel::Ticker(self.market.clone()), data);
+			}
+			Some(Body::Candle(CandleUpdate {
+				interval,
+				candle: Some(candle),
+			})) => {
+				if let Ok(interval) = interval.parse() {
+					let data = ChannelData::Candle(handlers::candle(
+						candle,
+						price_decimals,
+						size_decimals,
+					));
+					self.publish(Channel::Candles(self.market.clone(), interval), data);
+				}
+			}
+			_ => {}
+		}
+		Ok(())
+	}
+
+	fn apply_book(&mut self, update: BookUpdate) -> Result<(), BookGap> {
+		// Updates arriving before the snapshot are already reflected in it
+		let Some(book) = self.book.as_mut() else {
+			return Ok(());
+		};
+		if update.sequence <= book.sequence {
+			return Ok(());
+		}
+		if update.sequence != book.sequence + 1 {
+			return Err(BookGap {
+				expected: book.sequence + 1,
+				received: update.sequence,
+			});
+		}
+
+		book.sequence = update.sequence;
+		for (levels, changes) in [
+			(&mut book.bids, &update.bids),
+			(&mut book.asks, &update.asks),
+		] {
+			for level in changes {
+				if level.size == 0 {
+					levels.remove(&level.price);
+				} else {
+					levels.insert(level.price, level.size);
+				}
+			}
+		}
+
+		let frame = ChannelUpdate {
+			channel: Channel::Book(self.market.clone()).to_string(),
+			sequence: update.sequence,
+			data: ChannelData::Delta {
+				bids: self.levels(update.bids.iter().map(|l| (&l.price, &l.size))),
+				asks: self.levels(update.asks.iter().map(|l| (&l.price, &l.size))),
+			},
+		}
+		.frame();
+		self.deliver(&Channel::Book(self.market.clone()), frame);
+		Ok(())
+	}
+
+	/// Forget the book and skip a trade sequence after upstream data was lost
+	fn mark_gap(&mut self) {
+		self.book = None;
+		*self
+			.sequences
+			.entry(Channel::Trades(self.market.clone()))
+			.or_default() += 1;
+	}
+
+	fn publish(&mut self, channel: Channel, data: ChannelData) {
+		let sequence = self.sequences.entry(channel.clone()).or_default();
+		*sequence += 1;
+		let frame = ChannelUpdate {
+			channel: channel.to_string(),
+			sequence: *sequence,
+			data,
+		}
+		.frame();
+		if !matches!(channel, Channel::Trades(_)) {
+			self.latest.insert(channel.clone(), frame.clone());
+		}
+		self.deliver(&channel, frame);
+	}
+
+	/// Offer `frame` to every subscriber of `channel`, dropping clients whose
+	/// buffer is full or closed
+	fn deliver(&mut self, channel: &Channel, frame: FeedFrame) {
+		let Some(subscribers) = self.subscribers.get(channel) else {
+			return;
+		};
+		let mut dropped = Vec::new();
+		for (client, subscriber) in subscribers {
+			match subscriber.tx.try_send(frame.clone()) {
+				Ok(()) => {}
+				Err(TrySendError::Full(_)) => {
+					subscriber.overflow.notify_one();
+					dropped.push(*client);
+				}
+				Err(TrySendError::Closed(_)) => dropped.push(*client),
+			}
+		}
+		for client in dropped {
+			self.remove_client(client);
+		}
+	}
+
+	fn snapshot_frame(&self, book: &LocalBook) -> FeedFrame {
+		ChannelUpdate {
+			channel: Channel::Book(self.market.clone()).to_string(),
+			sequence: book.sequence,
+			data: ChannelData::Snapshot {
+				bids: self.levels(book.bids.iter().rev()),
+				asks: self.levels(book.asks.iter()),
+			},
+		}
+		.frame()
+	}
+
+	fn levels<'a>(&self, levels: impl Iterator<Item = (&'a u64, &'a u64)>) -> Vec<BookLevel> {
+		levels
+			.map(|(&price, &size)| BookLevel {
+				price: format_units(price as u128, self.price_decimals),
+				size: format_units(size as u128, self.size_decimals),
+			})
+			.collect()
+	}
+}
+
+/// A market with subscribers and its upstream task
+struct MarketEntry {
+	channels: Arc<Mutex<MarketChannels>>,
+	upstream: actix_rt::task::JoinHandle<()>,
+}
+
+/// Fan-out hub for public market data channels
+pub struct MarketFeed {
+	dispatcher: Arc<MatchingDispatcher>,
+	/// Frames buffered per client before it is dropped
+	client_buffer: usize,
+	next_client: AtomicU64,
+	markets: Mutex<HashMap<String, MarketEntry>>,
+}
+
+impl MarketFeed {
+	pub fn new(dispatcher: Arc<MatchingDispatcher>, client_buffer: usize) -> Self {
+		Self {
+			dispatcher,
+			client_buffer: client_buffer.max(1),
+			next_client: AtomicU64::new(1),
+			markets: Mutex::new(HashMap::new()),
+		}
+	}
+
+	/// Register a client; its frames arrive on the returned receiver
+	pub fn client(self: &Arc<Self>) -> (FeedClient, mpsc::Receiver<FeedFrame>) {
+		let (tx, rx) = mpsc::channel(self.client_buffer);
+		let client = FeedClient {
+			id: self.next_client.fetch_add(1, Ordering::Relaxed),
+			feed: self.clone(),
+			subscriber: Subscriber {
+				tx,
+				overflow: Arc::new(Notify::new()),
+			},
+			channels: Vec::new(),
+		};
+		(client, rx)
+	}
+
+	fn attach(&self, channel: Channel, client: u64, subscriber: Subscriber) {
+		let mut markets = self.markets.lock().unwrap();
+		let market = channel.market().to_string();
+		let entry = markets.entry(market.clone()).or_insert_with(|| {
+			let (price_decimals, size_decimals) = self
+				.dispatcher
+				.market_spec(&market)
+				.map(|spec| (spec.price_decimals, spec.size_decimals))
+				.unwrap_or((0, 0));
+			let channels = Arc::new(Mutex::new(MarketChannels::new(
+				market.clone(),
+				price_decimals,
+				size_decimals,
+			)));
+			let upstream = actix_rt::spawn(follow_market(
+				self.dispatcher.clone(),
+				market,
+				channels.clone(),
+			));
+			MarketEntry { channels, upstream }
+		});
+		entry
+			.channels
+			.lock()
+			.unwrap()
+			.subscribe(channel, client, subscriber);
+	}
+
+	fn detach(&self, channel: &Channel, client: u64) {
+		let mut markets = self.markets.lock().unwrap();
+		let Some(entry) = markets.get(channel.market()) else {
+			return;
+		};
+		let idle = {
+			let mut channels = entry.channels.lock().unwrap();
+			channels.unsubscribe(channel, client);
+			channels.is_empty()
+		};
+		if idle && let Some(entry) = markets.remove(channel.market()) {
+			entry.upstream.abort();
+			tracing::debug!(
+				target: "server::market_feed",
+				market = %channel.market(),
+				"Closed upstream market data subscription"
+			);
+		}
+	}
+}
+
+/// One WebSocket connection's channel subscriptions
+///
+/// Dropping the client unsubscribes from everything.
+pub struct FeedClient {
+	id: u64,
+	feed: Arc<MarketFeed>,
+	subscriber: Subscriber,
+	channels: Vec<Channel>,
+}
+
+impl FeedClient {
+	/// Subscribe to a channel by name; subscribing twice is a no-op
+	pub fn subscribe(&mut self, name: &str) -> Result<Channel, FeedError> {
+		let channel: Channel = name.parse()?;
+		if self.feed.dispatcher.market_spec(channel.market()).is_none() {
+			return Err(FeedError::UnknownMarket(channel.market().to_string()));
+		}
+		if !self.channels.contains(&channel) {
+			self.feed
+				.attach(channel.clone(), self.id, self.subscriber.clone());
+			self.channels.push(channel.clone());
+		}
+		Ok(channel)
+	}
+
+	/// Unsubscribe from a channel by name
+	pub fn unsubscribe(&mut self, name: &str) -> Result<Channel, FeedError> {
+		let channel: Channel = name.parse()?;
+		let Some(index) = self.channels.iter().position(|c| *c == channel) else {
+			return Err(FeedError::NotSubscribed(name.to_string()));
+		};
+		self.channels.swap_remove(index);
+		self.feed.detach(&channel, self.id);
+		Ok(channel)
+	}
+
+	/// Resolves once the client has been dropped for falling behind
+	pub async fn overflowed(&self) {
+		self.subscriber.overflow.notified().await
+	}
+}
+
+impl Drop for FeedClient {
+	fn drop(&mut self) {
+		for channel in &self.channels {
+			self.feed.detach(channel, self.id);
+		}
+	}
+}
+
+/// Follow a market's upstream stream for as long as it has subscribers
+///
+/// The stream is opened before the snapshots are fetched, so every update
+/// after the snapshot is seen; updates already in the snapshot are skipped
+/// by sequence.
+async fn follow_market(
+	dispatcher: Arc<MatchingDispatcher>,
+	market: String,
+	channels: Arc<Mutex<MarketChannels>>,
+) {
+	loop {
+		match dispatcher.market_data(&market).await {
+			Ok(mut updates) => match seed(&dispatcher, &market, &channels).await {
+				Ok(()) => loop {
+					match updates.message().await {
+						Ok(Some(update)) => {
+							if let Err(gap) = channels.lock().unwrap().apply(update) {
+								tracing::warn!(
+									target: "server::market_feed",
+									market = %market,
+									expected = gap.expected,
+									received = gap.received,
+									"Book update gap, resyncing"
+								);
+								break;
+							}
+						}
+						Ok(None) => break,
+						Err(status) => {
+							tracing::warn!(
+								target: "server::market_feed",
+								market = %market,
+								status = %status,
+								"Market data stream interrupted"
+							);
+							break;
+						}
+					}
+				},
+				Err(e) => {
+					tracing::warn!(
+						target: "server::market_feed",
+						market = %market,
+						error = %e,
+						"Failed to fetch book snapshot"
+					);
+				}
+			},
+			Err(e) => {
+				tracing::warn!(
+					target: "server::market_feed",
+					market = %market,
+					error = %e,
+					"Failed to subscribe to market data"
+				);
+			}
+		}
+
+		channels.lock().unwrap().mark_gap();
+		tokio::time::sleep(UPSTREAM_RESUBSCRIBE_DELAY).await;
+	}
+}
+
+/// Fetch the book, ticker and current candles that updates build on
+///
+/// Only the book is required; the ticker and candles also arrive with the
+/// next trade.
+async fn seed(
+	dispatcher: &MatchingDispatcher,
+	market: &str,
+	channels: &Mutex<MarketChannels>,
+) -> Result<(), DispatcherError> {
+	let snapshot = dispatcher.order_book(market, 0).await?;
+	channels.lock().unwrap().reset_book(snapshot);
+
+	let mut seeds = Vec::new();
+	if let Ok(ticker) = dispatcher.ticker(market).await {
+		seeds.push(Body::Ticker(ticker));
+	}
+	for interval in CandleInterval::ALL {
+		if let Ok(response) = dispatcher.candles(market, interval.as_str(), 1).await
+			&& let Some(candle) = response.candles.into_iter().last()
+		{
+			seeds.push(Body::Candle(CandleUpdate {
+				interval: interval.to_string(),
+				candle: Some(candle),
+			}));
+		}
+	}
+
+	let mut channels = channels.lock().unwrap();
+	for body in seeds {
+		let _ = channels.apply(MarketDataUpdate {
+			market: market.to_string(),
+			event_sequence: 0,
+			body: Some(body),
+		});
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::grpc_client::proto::{GetTickerResponse, OrderSide, Trade};
+
+	fn subscriber(capacity: usize) -> (Subscriber, mpsc::Receiver<FeedFrame>) {
+		let (tx, rx) = mpsc::channel(capacity);
+		(
+			Subscriber {
+				tx,
+				overflow: Arc::new(Notify::new()),
+			},
+			rx,
+		)
+	}
+
+	fn next(rx: &mut mpsc::Receiver<FeedFrame>) -> serde_json::Value {
+		serde_json::from_str(&rx.try_recv().expect("frame")).unwrap()
+	}
+
+	fn book_update(sequence: u64, bids: Vec<PriceLevel>) -> MarketDataUpdate {
+		MarketDataUpdate {
+			market: "BTC-USDT".to_string(),
+			event_sequence: sequence,
+			body: Some(Body::Book(BookUpdate {
+				market: "BTC-USDT".to_string(),
+				sequence,
+				event_sequence: sequence,
+				bids,
+				asks: Vec::new(),
+			})),
+		}
+	}
+
+	#[test]
+	fn parses_channel_names() {
+		assert_eq!(
+			"book.BTC-USDT".parse::<Channel>().unwrap(),
+			Channel::Book("BTC-USDT".to_string())
+		);
+		let candles: Channel = "candles.BTC-USDT.5m".parse().unwrap();
+		assert_eq!(
+			candles,
+			Channel::Candles("BTC-USDT".to_string(), CandleInterval::FiveMinutes)
+		);
+		assert_eq!(candles.to_string(), "candles.BTC-USDT.5m");
+		assert!("candles.BTC-USDT.2m".parse::<Channel>().is_err());
+		assert!("orders.BTC-USDT".parse::<Channel>().is_err());
+		assert!("book.".parse::<Channel>().is_err());
+	}
+
+	#[test]
+	fn book_subscribers_get_snapshot_then_contiguous_deltas() {
+		let mut channels = MarketChannels::new("BTC-USDT".to_string(), 2, 0);
+		let book = Channel::Book("BTC-USDT".to_string());
+		let (early, mut early_rx) = subscriber(8);
+		channels.subscribe(book.clone(), 1, early);
+
+		// Updates before the snapshot are dropped, those it covers skipped
+		channels.apply(book_update(4, vec![])).unwrap();
+		channels.reset_book(GetOrderBookResponse {
+			market: "BTC-USDT".to_string(),
+			sequence: 5,
+			event_sequence: 9,
+			bids: vec![PriceLevel {
+				price: 100,
+				size: 3,
+			}],
+			asks: Vec::new(),
+		});
+		channels.apply(book_update(5, vec![])).unwrap();
+		channels
+			.apply(book_update(
+				6,
+				vec![PriceLevel {
+					price: 100,
+					size: 0,
+				}],
+			))
+			.unwrap();
+
+		let snapshot = next(&mut early_rx);
+		assert_eq!(snapshot["type"], "update");
+		assert_eq!(snapshot["channel"], "book.BTC-USDT");
+		assert_eq!(snapshot["kind"], "snapshot");
+		assert_eq!(snapshot["sequence"], 5);
+		assert_eq!(snapshot["bids"][0]["price"], "1.00");
+		let delta = next(&mut early_rx);
+		assert_eq!(delta["kind"], "delta");
+		assert_eq!(delta["sequence"], 6);
+		assert_eq!(delta["bids"][0]["size"], "0");
+		assert!(early_rx.try_recv().is_err());
+
+		// Late subscribers start from the current book
+		let (late, mut late_rx) = subscriber(8);
+		channels.subscribe(book, 2, late);
+		let snapshot = next(&mut late_rx);
+		assert_eq!(snapshot["sequence"], 6);
+		assert_eq!(snapshot["bids"], serde_json::json!([]));
+
+		assert!(matches!(
+			channels.apply(book_update(8, vec![])),
+			Err(BookGap {
+				expected: 7,
+				received: 8
+			})
+		));
+	}
+
+	#[actix_rt::test]
+	async fn slow_clients_are_dropped_without_affecting_others() {
+		let mut channels = MarketChannels::new("BTC-USDT".to_string(), 0, 0);
+		let trades = Channel::Trades("BTC-USDT".to_string());
+		let (slow, _slow_rx) = subscriber(1);
+		let overflow = slow.overflow.clone();
+		let (fast, mut fast_rx) = subscriber(8);
+		channels.subscribe(trades.clone(), 1, slow);
+		channels.subscribe(trades.clone(), 2, fast);
+
+		let trade = |id: &str| MarketDataUpdate {
+			market: "BTC-USDT".to_string(),
+			event_sequence: 1,
+			body: Some(Body::Trade(Trade {
+				trade_id: id.to_string(),
+				market: "BTC-USDT".to_string(),
+				price: 100,
+				size: 1,
+				side: OrderSide::Buy as i32,
+				timestamp: 0,
+				maker_order_id: "m".to_string(),
+				taker_order_id: "t".to_string(),
+			})),
+		};
+		channels.apply(trade("t1")).unwrap();
+		channels.apply(trade("t2")).unwrap();
+		channels.mark_gap();
+		channels.apply(trade("t3")).unwrap();
+
+		assert!(
+			tokio::time::timeout(Duration::from_millis(100), overflow.notified())
+				.await
+				.is_ok(),
+			"slow client not told"
+		);
+		assert_eq!(channels.subscribers[&trades].len(), 1);
+		let sequences: Vec<_> = (0..3)
+			.map(|_| next(&mut fast_rx)["sequence"].clone())
+			.collect();
+		assert_eq!(sequences, vec![1, 2, 4]);
+	}
+
+	#[test]
+	fn ticker_subscribers_get_the_latest_value() {
+		let mut channels = MarketChannels::new("BTC-USDT".to_string(), 1, 0);
+		channels
+			.apply(MarketDataUpdate {
+				market: "BTC-USDT".to_string(),
+				event_sequence: 3,
+				body: Some(Body::Ticker(GetTickerResponse {
+					market: "BTC-USDT".to_string(),
+					last: 105,
+					..Default::default()
+				})),
+			})
+			.unwrap();
+
+		let (late, mut rx) = subscriber(4);
+		channels.subscribe(Channel::Ticker("BTC-USDT".to_string()), 1, late);
+		let ticker = next(&mut rx);
+		assert_eq!(ticker["kind"], "ticker");
+		assert_eq!(ticker["sequence"], 1);
+		assert_eq!(ticker["last"], "10.5");
+	}
+}
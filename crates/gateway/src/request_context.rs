@@ -153,20 +153,38 @@ impl RequestContext {
 			return ctx.clone();
 		}
 
-		let request_id =
-			extract_header(req, HEADER_REQUEST_ID).unwrap_or_else(|| Uuid::new_v4().to_string());
+		let ctx = Self::from_headers(|name| extract_header(req, name));
+		req.extensions_mut().insert(ctx.clone());
+		ctx
+	}
+
+	/// Create a RequestContext for a gRPC request from its metadata
+	///
+	/// Follows the same priority as [`RequestContext::ensure`], reading the
+	/// header names as (lowercase) metadata keys.
+	pub fn from_grpc(metadata: &tonic::metadata::MetadataMap) -> Self {
+		Self::from_headers(|name| {
+			metadata
+				.get(name.to_ascii_lowercase().as_str())
+				.and_then(|v| v.to_str().ok())
+				.map(|s| s.to_string())
+		})
+	}
+
+	fn from_headers(header: impl Fn(&str) -> Option<String>) -> Self {
+		let request_id = header(HEADER_REQUEST_ID).unwrap_or_else(|| Uuid::new_v4().to_string());
 
 		// 1) 优先 traceparent
 		let mut trace_id_bytes = None;
 		let mut incoming_traceparent = None;
 		let mut incoming_tracestate = None;
 
-		if let Some(raw_tp) = extract_header(req, HEADER_TRACEPARENT) {
+		if let Some(raw_tp) = header(HEADER_TRACEPARENT) {
 			match parse_traceparent(&raw_tp) {
 				Ok(tp) => {
 					trace_id_bytes = Some(tp.trace_id);
 					incoming_traceparent = Some(raw_tp);
-					incoming_tracestate = extract_header(req, HEADER_TRACESTATE)
+					incoming_tracestate = header(HEADER_TRACESTATE)
 						.and_then(|ts| parse_tracestate(&ts).ok().map(|_| ts));
 				}
 				Err(e) => {
@@ -182,7 +200,7 @@ impl RequestContext {
 
 		// 2) 兼容 X-Trace-Id
 		if trace_id_bytes.is_none()
-			&& let Some(raw_xtid) = extract_header(req, HEADER_TRACE_ID)
+			&& let Some(raw_xtid) = header(HEADER_TRACE_ID)
 		{
 			match validate_hex_trace_id(&raw_xtid) {
 				Ok(bytes) => {
@@ -201,14 +219,12 @@ impl RequestContext {
 		let trace_id_bytes = trace_id_bytes.unwrap_or_else(generate_trace_id);
 		let trace_id = hex::encode(trace_id_bytes);
 
-		let ctx = RequestContext {
+		RequestContext {
 			request_id,
 			trace_id,
 			traceparent: incoming_traceparent,
 			tracestate: incoming_tracestate,
-		};
-		req.extensions_mut().insert(ctx.clone());
-		ctx
+		}
 	}
 
 	/// Extract RequestContext from an HTTP request if it exists
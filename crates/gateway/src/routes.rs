@@ -14,17 +14,49 @@
 
 use actix_web::web;
 
-use crate::handlers;
+use crate::{handlers, ws};
 
 /// Configure API routes for the gateway
 ///
 /// This function sets up all HTTP routes for the gateway service:
 /// - `/api/v1/orders` - Order management endpoints
+/// - `/api/v1/orders/batch` - Batch order placement and cancellation
+/// - `/api/v1/orders/by-client-id/{id}` - Order lookup and cancel by client order ID
+/// - `/api/v1/markets` - Market specifications (price/size scales)
+/// - `/api/v1/markets/{market}/candles` and `/ticker` - Candles and 24h stats
+/// - `/api/v1/markets/{market}/trades` - Public trade history
+/// - `/api/v1/fills` - The signing principal's fills
+/// - `/api/v1/ws` - WebSocket order entry and execution reports
 /// - `/health` - Health check endpoint
 pub fn configure_routes(cfg: &mut web::ServiceConfig) {
 	cfg.service(
 		web::scope("/api/v1")
+			.route("/markets", web::get().to(handlers::list_markets))
+			.route(
+				"/markets/{market}/candles",
+				web::get().to(handlers::get_candles),
+			)
+			.route(
+				"/markets/{market}/ticker",
+				web::get().to(handlers::get_ticker),
+			)
+			.route(
+				"/markets/{market}/trades",
+				web::get().to(handlers::get_trades),
+			)
+			.route("/fills", web::get().to(handlers::get_fills))
+			.route("/ws", web::get().to(ws::connect))
 			.route("/orders", web::post().to(handlers::place_order))
+			.route("/orders/batch", web::post().to(handlers::place_orders))
+			.route("/orders/batch", web::delete().to(handlers::cancel_orders))
+			.route(
+				"/orders/by-client-id/{client_order_id}",
+				web::get().to(handlers::get_order_by_client_id),
+			)
+			.route(
+				"/orders/by-client-id/{client_order_id}",
+				web::delete().to(handlers::cancel_order_by_client_id),
+			)
 			.route("/orders/{order_id}", web::get().to(handlers::get_order))
 			.route(
 				"/orders/{order_id}",
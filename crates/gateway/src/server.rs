@@ -12,17 +12,24 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use actix_web::{App, HttpServer, web};
 use anyhow::Context;
 
 use crate::{
+	admission,
 	auth::{AuthProvider, SignatureAuthProvider},
 	config::GatewayRuntimeConfig,
+	config_reload::ConfigWatcher,
 	dispatcher::MatchingDispatcher,
+	fix::{FixAcceptor, FixConfig},
+	grpc_server::OrderGatewayService,
+	health::{HealthConfig, HealthProber},
+	market_feed::MarketFeed,
 	middleware::{CorsMiddleware, LoggingMiddleware},
 	routes,
+	ws::WsConfig,
 };
 
 /// Gateway server state
@@ -36,6 +43,12 @@ pub struct GatewayState {
 	/// systems should provide their own implementation based on their
 	/// authentication requirements.
 	pub auth_provider: Arc<dyn AuthProvider>,
+	/// WebSocket session settings
+	pub ws: WsConfig,
+	/// Public market data channels shared by all WebSocket sessions
+	pub market_feed: Arc<MarketFeed>,
+	/// Orders or cancels accepted per batch request
+	pub max_batch_orders: usize,
 }
 
 /// Gateway server
@@ -51,12 +64,19 @@ impl GatewayServer {
 	/// Production systems should create their own AuthProvider implementation
 	/// and pass it to GatewayState.
 	pub async fn new(config: GatewayRuntimeConfig) -> anyhow::Result<Self> {
+		if !admission::init_rate_limit(&config.rate_limit) {
+			anyhow::bail!("Rate limit initialized before the gateway server");
+		}
 		let dispatcher = Arc::new(MatchingDispatcher::new(&config).await?);
 		let auth_provider: Arc<dyn AuthProvider> = Arc::new(SignatureAuthProvider);
+		let market_feed = Arc::new(MarketFeed::new(dispatcher.clone(), config.ws_feed_buffer));
 		Ok(Self {
 			state: GatewayState {
 				dispatcher,
 				auth_provider,
+				ws: WsConfig::from_runtime(&config),
+				market_feed,
+				max_batch_orders: config.max_batch_orders,
 			},
 			config,
 		})
@@ -73,7 +93,9 @@ impl GatewayServer {
 		(workers, max_body_bytes)
 	}
 
-	/// Start the HTTP server with actix-web
+	/// Start the HTTP server with actix-web, the gRPC order entry server
+	/// with tonic and, if configured, the FIX acceptor, returning when any
+	/// of them stops
 	pub async fn serve(&self) -> anyhow::Result<()> {
 		let state = self.state.clone();
 
@@ -92,7 +114,54 @@ impl GatewayServer {
 			max_body_bytes
 		);
 
-		HttpServer::new(move || {
+		tokio::spawn(
+			HealthProber::new(
+				self.state.dispatcher.clone(),
+				HealthConfig::from_runtime(&self.config),
+			)
+			.run(),
+		);
+
+		if let Some(path) = &self.config.config_file {
+			tracing::info!(
+				target: "server::server",
+				"Watching {} for market changes",
+				path.display()
+			);
+			tokio::spawn(ConfigWatcher::new(path).run(
+				Duration::from_millis(self.config.config_reload_interval_ms),
+				self.state.dispatcher.clone(),
+			));
+		}
+
+		let grpc_addr = self.config.grpc_bind_addr;
+		tracing::info!(
+			target: "server::server",
+			"Starting gRPC order entry server on {}",
+			grpc_addr
+		);
+		let grpc_server = tonic::transport::Server::builder()
+			.add_service(OrderGatewayService::new(self.state.clone()).into_server())
+			.serve(grpc_addr);
+
+		let fix_config = FixConfig::from_runtime(&self.config);
+		let fix_state = Arc::new(self.state.clone());
+		let fix_acceptor = async move {
+			match fix_config {
+				Some(config) => {
+					tracing::info!(
+						target: "server::server",
+						"Starting FIX acceptor on {} as {}",
+						config.bind_addr,
+						config.comp_id
+					);
+					FixAcceptor::new(fix_state, config).run().await
+				}
+				None => std::future::pending().await,
+			}
+		};
+
+		let http_server = HttpServer::new(move || {
 			App::new()
 				.app_data(web::Data::new(state.clone()))
 				.app_data(web::JsonConfig::default().limit(max_body_bytes))
@@ -103,9 +172,13 @@ impl GatewayServer {
 		.workers(workers)
 		.bind(addr)
 		.context("Failed to bind to address")?
-		.run()
-		.await
-		.context("HTTP server error")?;
+		.run();
+
+		tokio::select! {
+			result = http_server => result.context("HTTP server error")?,
+			result = grpc_server => result.context("gRPC server error")?,
+			result = fix_acceptor => result.context("FIX acceptor error")?,
+		}
 
 		Ok(())
 	}
@@ -0,0 +1,767 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! WebSocket API for order entry, private execution reports and public
+//! market data
+//!
+//! Clients connect to `/api/v1/ws` and exchange JSON text frames. Client
+//! requests are tagged by `op` and carry a client-chosen `id` that is echoed
+//! in the reply:
+//!
+//! - `login`: `public_key`, `signature`, `timestamp`, `nonce` and an optional
+//!   `algorithm`, the materials REST takes from its `X-*` headers. They are
+//!   verified through the configured [`AuthProvider`]; the signature covers
+//!   [`anvil_sdk::signing::query_signing_message`] for `LOGIN` and
+//!   `/api/v1/ws`.
+//! - `place`: `order` (a `PlaceOrderRequest`), `timestamp`, `nonce`
+//! - `cancel`: `market`, `order_id`, `timestamp`, `nonce`
+//! - `amend`: `order_id` and its replacement `order`, `timestamp`, `nonce`
+//! - `subscribe` / `unsubscribe`: `channel`, one of the public channels of
+//!   [`crate::market_feed`]; no login needed
+//!
+//! The session is authenticated by the login, so order operations are not
+//! signed individually, but they pass the same rate limit, admission and
+//! replay checks as REST orders.
+//!
+//! Server messages are tagged by `type`: `login`, `result`, `error` (with the
+//! REST error body), `execution_report` and `update`. Reports for the
+//! logged-in principal are pushed from every configured market; `update`
+//! frames carry channel data. A connection that cannot keep up with its
+//! channels is closed.
+//!
+//! The server pings every heartbeat interval and closes connections that
+//! send nothing, not even a pong, within the client timeout. Each connection
+//! also has its own message rate limit on top of the per-principal one.
+
+use std::{num::NonZeroU32, sync::Arc, time::Duration};
+
+use actix_web::{
+	HttpRequest, HttpResponse,
+	http::header::{HeaderMap, HeaderName, HeaderValue},
+	web,
+};
+use actix_ws::{AggregatedMessage, CloseCode, CloseReason, Session};
+use anvil_sdk::{
+	decimal::format_units,
+	types::{ExecutionReport, ExecutionType, LiquidityRole, PlaceOrderRequest},
+};
+use governor::{DefaultDirectRateLimiter, Quota, RateLimiter};
+use serde::{Deserialize, Serialize};
+use tokio::{sync::mpsc, time::Instant};
+use uuid::Uuid;
+
+use crate::{
+	admission::{self, ReplayOutcome},
+	auth::{self, AuthContext, AuthError, AuthProvider, AuthenticatedPrincipal},
+	config::GatewayRuntimeConfig,
+	dispatcher::MatchingDispatcher,
+	grpc_client::proto::{
+		ExecutionReport as ProtoExecutionReport, ExecutionType as ProtoExecutionType,
+		LiquidityRole as ProtoLiquidityRole,
+	},
+	handlers::{self, GatewayError},
+	market_feed::FeedClient,
+	request_context::RequestContext,
+	server::GatewayState,
+};
+
+/// Path of the WebSocket endpoint, also signed by `login`
+pub const WS_PATH: &str = "/api/v1/ws";
+
+/// Method signed by `login`
+pub const LOGIN_METHOD: &str = "LOGIN";
+
+/// Delay before re-subscribing to a market's execution reports
+const REPORT_RESUBSCRIBE_DELAY: Duration = Duration::from_secs(1);
+
+/// Per-connection WebSocket settings
+#[derive(Debug, Clone)]
+pub struct WsConfig {
+	/// Interval between server pings
+	pub heartbeat_interval: Duration,
+	/// Close the connection after this long without a client frame
+	pub client_timeout: Duration,
+	/// Sustained client messages per second per connection
+	pub messages_per_second: u32,
+	/// Burst capacity of the per-connection limit
+	pub message_burst: u32,
+	/// Largest accepted frame in bytes
+	pub max_frame_bytes: usize,
+}
+
+impl WsConfig {
+	pub fn from_runtime(config: &GatewayRuntimeConfig) -> Self {
+		Self {
+			heartbeat_interval: Duration::from_millis(config.ws_heartbeat_interval_ms),
+			client_timeout: Duration::from_millis(config.ws_client_timeout_ms),
+			messages_per_second: config.ws_messages_per_second,
+			message_burst: config.ws_message_burst,
+			max_frame_bytes: config.max_body_bytes,
+		}
+	}
+
+	fn rate_limiter(&self) -> DefaultDirectRateLimiter {
+		let rate = NonZeroU32::new(self.messages_per_second).unwrap_or(NonZeroU32::MIN);
+		let burst = NonZeroU32::new(self.message_burst).unwrap_or(rate);
+		RateLimiter::direct(Quota::per_second(rate).allow_burst(burst))
+	}
+}
+
+/// Client request frame
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum ClientMessage {
+	Login {
+		id: String,
+		public_key: String,
+		signature: String,
+		timestamp: u64,
+		nonce: String,
+		#[serde(default)]
+		algorithm: Option<String>,
+	},
+	Place {
+		id: String,
+		order: PlaceOrderRequest,
+		timestamp: u64,
+		nonce: String,
+	},
+	Cancel {
+		id: String,
+		market: String,
+		order_id: String,
+		timestamp: u64,
+		nonce: String,
+	},
+	Amend {
+		id: String,
+		order_id: String,
+		order: PlaceOrderRequest,
+		timestamp: u64,
+		nonce: String,
+	},
+	Subscribe {
+		id: String,
+		channel: String,
+	},
+	Unsubscribe {
+		id: String,
+		channel: String,
+	},
+}
+
+/// Server frame
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ServerMessage {
+	Login {
+		id: String,
+		/// Hex-encoded public key of the logged-in principal
+		principal: String,
+	},
+	Result {
+		id: String,
+		result: serde_json::Value,
+	},
+	Error {
+		/// Absent when the frame could not be parsed
+		id: Option<String>,
+		error: serde_json::Value,
+	},
+	ExecutionReport(ExecutionReport),
+}
+
+/// Upgrade to a WebSocket session
+pub async fn connect(
+	state: web::Data<GatewayState>,
+	req: HttpRequest,
+	body: web::Payload,
+) -> actix_web::Result<HttpResponse> {
+	let (response, session, stream) = actix_ws::handle(&req, body)?;
+	let stream = stream
+		.max_frame_size(state.ws.max_frame_bytes)
+		.aggregate_continuations();
+
+	actix_rt::spawn(run_session(state.into_inner(), session, stream));
+	Ok(response)
+}
+
+/// Authenticated state of a connection
+struct Login {
+	principal: AuthenticatedPrincipal,
+	/// Execution report forwarders, one per market
+	forwarders: Vec<actix_rt::task::JoinHandle<()>>,
+}
+
+impl Drop for Login {
+	fn drop(&mut self) {
+		for forwarder in &self.forwarders {
+			forwarder.abort();
+		}
+	}
+}
+
+async fn run_session(
+	state: Arc<GatewayState>,
+	mut session: Session,
+	mut stream: actix_ws::AggregatedMessageStream,
+) {
+	let config = state.ws.clone();
+	let limiter = config.rate_limiter();
+	let mut heartbeat = tokio::time::interval(config.heartbeat_interval);
+	let mut last_seen = Instant::now();
+	let (reports_tx, mut reports_rx) = mpsc::channel::<ExecutionReport>(256);
+	let mut login: Option<Login> = None;
+	let (mut feed, mut frames) = state.market_feed.client();
+
+	let close_reason = loop {
+		tokio::select! {
+			frame = stream.recv() => {
+				let text = match frame {
+					Some(Ok(AggregatedMessage::Text(text))) => text,
+					Some(Ok(AggregatedMessage::Ping(bytes))) => {
+						last_seen = Instant::now();
+						if session.pong(&bytes).await.is_err() {
+							break None;
+						}
+						continue;
+					}
+					Some(Ok(AggregatedMessage::Pong(_))) => {
+						last_seen = Instant::now();
+						continue;
+					}
+					Some(Ok(AggregatedMessage::Binary(_))) => {
+						break Some(CloseReason {
+							code: CloseCode::Unsupported,
+							description: Some("Only text frames are supported".to_string()),
+						});
+					}
+					Some(Ok(AggregatedMessage::Close(reason))) => break reason,
+					Some(Err(e)) => {
+						tracing::debug!(target: "server::ws", error = %e, "WebSocket protocol error");
+						break Some(CloseCode::Protocol.into());
+					}
+					None => break None,
+				};
+				last_seen = Instant::now();
+
+				let context = new_context();
+				let reply = if limiter.check().is_err() {
+					error_message(
+						None,
+						GatewayError::admission(admission::AdmissionError::RateLimitExceeded, &context),
+					)
+				} else {
+					match serde_json::from_str::<ClientMessage>(&text) {
+						Ok(message) => {
+							handle_message(&state, message, &mut login, &mut feed, &reports_tx, &context)
+								.await
+						}
+						Err(e) => error_message(
+							None,
+							GatewayError::invalid_request(format!("Malformed message: {}", e), &context),
+						),
+					}
+				};
+				if send(&mut session, &reply).await.is_err() {
+					break None;
+				}
+			}
+			Some(report) = reports_rx.recv() => {
+				if send(&mut session, &ServerMessage::ExecutionReport(report)).await.is_err() {
+					break None;
+				}
+			}
+			Some(frame) = frames.recv() => {
+				if session.text(frame.to_string()).await.is_err() {
+					break None;
+				}
+			}
+			_ = feed.overflowed() => {
+				break Some(CloseReason {
+					code: CloseCode::Policy,
+					description: Some("Slow consumer".to_string()),
+				});
+			}
+			_ = heartbeat.tick() => {
+				if last_seen.elapsed() > config.client_timeout {
+					break Some(CloseReason {
+						code: CloseCode::Policy,
+						description: Some("Heartbeat timeout".to_string()),
+					});
+				}
+				if session.ping(b"").await.is_err() {
+					break None;
+				}
+			}
+		}
+	};
+
+	drop(login);
+	drop(feed);
+	let _ = session.close(close_reason).await;
+}
+
+async fn handle_message(
+	state: &Arc<GatewayState>,
+	message: ClientMessage,
+	login: &mut Option<Login>,
+	feed: &mut FeedClient,
+	reports_tx: &mpsc::Sender<ExecutionReport>,
+	context: &RequestContext,
+) -> ServerMessage {
+	match message {
+		ClientMessage::Login {
+			id,
+			public_key,
+			signature,
+			timestamp,
+			nonce,
+			algorithm,
+		} => {
+			if login.is_some() {
+				return error_message(
+					Some(id),
+					GatewayError::invalid_request("Already logged in", context),
+				);
+			}
+			let materials = LoginMaterials {
+				public_key,
+				signature,
+				timestamp,
+				nonce,
+				algorithm,
+			};
+			match authenticate_login(
+				&materials,
+				LOGIN_METHOD,
+				WS_PATH,
+				state.auth_provider.as_ref(),
+				context,
+			) {
+				Ok(principal) => {
+					let principal_id = principal.principal.id();
+					let forwarders = state
+						.dispatcher
+						.markets()
+						.into_iter()
+						.map(|market| {
+							actix_rt::spawn(forward_reports(
+								state.clone(),
+								market,
+								principal_id.clone(),
+								0,
+								reports_tx.clone(),
+							))
+						})
+						.collect();
+					*login = Some(Login {
+						principal,
+						forwarders,
+					});
+					ServerMessage::Login {
+						id,
+						principal: principal_id,
+					}
+				}
+				Err(err) => error_message(Some(id), err),
+			}
+		}
+		ClientMessage::Place {
+			id,
+			order,
+			timestamp,
+			nonce,
+		} => {
+			let Some(session) = login.as_ref() else {
+				return not_logged_in(id, context);
+			};
+			let authenticated = session.with_replay_key(timestamp, nonce);
+			into_reply(
+				id,
+				handlers::submit_order(state, &order, &authenticated, None, context).await,
+			)
+		}
+		ClientMessage::Cancel {
+			id,
+			market,
+			order_id,
+			timestamp,
+			nonce,
+		} => {
+			let Some(session) = login.as_ref() else {
+				return not_logged_in(id, context);
+			};
+			let authenticated = session.with_replay_key(timestamp, nonce);
+			into_reply(
+				id,
+				handlers::submit_cancel(state, &market, &order_id, &authenticated, context).await,
+			)
+		}
+		ClientMessage::Amend {
+			id,
+			order_id,
+			order,
+			timestamp,
+			nonce,
+		} => {
+			let Some(session) = login.as_ref() else {
+				return not_logged_in(id, context);
+			};
+			let authenticated = session.with_replay_key(timestamp, nonce);
+			into_reply(
+				id,
+				handlers::submit_order(state, &order, &authenticated, Some(order_id), context)
+					.await,
+			)
+		}
+		ClientMessage::Subscribe { id, channel } => into_reply(
+			id,
+			feed.subscribe(&channel)
+				.map(|channel| serde_json::json!({ "subscribed": channel.to_string() }))
+				.map_err(|e| GatewayError::invalid_request(e.to_string(), context)),
+		),
+		ClientMessage::Unsubscribe { id, channel } => into_reply(
+			id,
+			feed.unsubscribe(&channel)
+				.map(|channel| serde_json::json!({ "unsubscribed": channel.to_string() }))
+				.map_err(|e| GatewayError::invalid_request(e.to_string(), context)),
+		),
+	}
+}
+
+impl Login {
+	/// The logged-in principal with an operation's own replay key
+	fn with_replay_key(&self, timestamp: u64, nonce: String) -> AuthenticatedPrincipal {
+		AuthenticatedPrincipal {
+			principal: self.principal.principal.clone(),
+			timestamp,
+			nonce,
+		}
+	}
+}
+
+/// Authentication materials of a session login: a `login` frame here, a
+/// FIX Logon in [`crate::fix`]
+pub(crate) struct LoginMaterials {
+	pub(crate) public_key: String,
+	pub(crate) signature: String,
+	pub(crate) timestamp: u64,
+	pub(crate) nonce: String,
+	pub(crate) algorithm: Option<String>,
+}
+
+impl LoginMaterials {
+	/// Present the materials under the REST header names so any
+	/// [`AuthProvider`] can extract them
+	fn to_headers(&self) -> Result<HeaderMap, AuthError> {
+		let mut headers = HeaderMap::new();
+		let mut insert = |name: &'static str, value: &str| {
+			let value = HeaderValue::from_str(value)
+				.map_err(|e| AuthError::SignatureFormatError(format!("Invalid {}: {}", name, e)))?;
+			headers.insert(HeaderName::from_static(name), value);
+			Ok::<_, AuthError>(())
+		};
+		insert("x-public-key", &self.public_key)?;
+		insert("x-signature", &self.signature)?;
+		insert("x-timestamp", &self.timestamp.to_string())?;
+		insert("x-nonce", &self.nonce)?;
+		if let Some(algorithm) = &self.algorithm {
+			insert("x-signature-alg", algorithm)?;
+		}
+		Ok(headers)
+	}
+}
+
+/// Verify a session login signed over the query signing message of
+/// `method` and `path`, then rate limit and consume its nonce
+pub(crate) fn authenticate_login(
+	materials: &LoginMaterials,
+	method: &str,
+	path: &str,
+	provider: &dyn AuthProvider,
+	context: &RequestContext,
+) -> Result<AuthenticatedPrincipal, GatewayError> {
+	let headers = materials
+		.to_headers()
+		.map_err(|e| GatewayError::auth(e, context))?;
+	let authenticated =
+		auth::authenticate_query(&AuthContext::from_http(&headers), method, path, provider)
+			.map_err(|e| GatewayError::auth(e, context))?;
+
+	admission::check_rate_limit(&authenticated.principal)
+		.map_err(|e| GatewayError::admission(e, context))?;
+	admission::begin_replay(
+		&authenticated.principal,
+		authenticated.timestamp,
+		&authenticated.nonce,
+	)
+	.map_err(|e| GatewayError::admission(e, context))?
+	.finish(ReplayOutcome::Terminal);
+
+	Ok(authenticated)
+}
+
+/// Follow a principal's execution reports in one market from
+/// `from_sequence` (0 for live reports only)
+///
+/// The matching stream ends when this connection lags too far behind or the
+/// engine restarts; the forwarder then resumes after the last report it
+/// delivered, or where it started if it has delivered none.
+pub(crate) async fn forward_reports(
+	state: Arc<GatewayState>,
+	market: String,
+	principal_id: String,
+	mut from_sequence: u64,
+	reports_tx: mpsc::Sender<ExecutionReport>,
+) {
+	let dispatcher: &MatchingDispatcher = &state.dispatcher;
+	let (price_decimals, size_decimals) = handlers::market_decimals(&state, &market);
+
+	loop {
+		match dispatcher
+			.execution_reports(&market, &principal_id, from_sequence)
+			.await
+		{
+			Ok(mut reports) => loop {
+				match reports.message().await {
+					Ok(Some(report)) => {
+						from_sequence = report.sequence + 1;
+						let report = execution_report(report, price_decimals, size_decimals);
+						if reports_tx.send(report).await.is_err() {
+							return;
+						}
+					}
+					Ok(None) => break,
+					Err(status) => {
+						tracing::warn!(
+							target: "server::ws",
+							market = %market,
+							status = %status,
+							"Execution report stream interrupted"
+						);
+						break;
+					}
+				}
+			},
+			Err(e) => {
+				tracing::warn!(
+					target: "server::ws",
+					market = %market,
+					error = %e,
+					"Failed to subscribe to execution reports"
+				);
+			}
+		}
+
+		if reports_tx.is_closed() {
+			return;
+		}
+		tokio::time::sleep(REPORT_RESUBSCRIBE_DELAY).await;
+	}
+}
+
+/// Convert a matching report to its client form, formatting units with the
+/// market's decimals
+fn execution_report(
+	report: ProtoExecutionReport,
+	price_decimals: u32,
+	size_decimals: u32,
+) -> ExecutionReport {
+	let price = || Some(format_units(report.price as u128, price_decimals));
+	let size = |units: u64| Some(format_units(units as u128, size_decimals));
+	let reason = || Some(report.reason.clone());
+
+	let mut out = ExecutionReport {
+		sequence: report.sequence,
+		market: report.market.clone(),
+		order_id: report.order_id.clone(),
+		client_order_id: Some(report.client_order_id.clone()).filter(|id| !id.is_empty()),
+		exec_type: ExecutionType::New,
+		price: None,
+		size: None,
+		filled_size: None,
+		remaining_size: None,
+		trade_id: None,
+		role: None,
+		reason: None,
+		timestamp: report.timestamp,
+	};
+	match report.exec_type() {
+		ProtoExecutionType::Resting => {
+			out.price = price();
+			out.size = size(report.size);
+			out.remaining_size = size(report.remaining_size);
+		}
+		ProtoExecutionType::OrderRejected => {
+			out.exec_type = ExecutionType::Rejected;
+			out.reason = reason();
+		}
+		ProtoExecutionType::Trade => {
+			out.exec_type = ExecutionType::Trade;
+			out.price = price();
+			out.size = size(report.size);
+			out.trade_id = Some(report.trade_id.clone());
+			out.role = Some(match report.role() {
+				ProtoLiquidityRole::Maker => LiquidityRole::Maker,
+				ProtoLiquidityRole::Taker => LiquidityRole::Taker,
+			});
+		}
+		ProtoExecutionType::PartialFill => {
+			out.exec_type = ExecutionType::PartiallyFilled;
+			out.filled_size = size(report.filled_size);
+			out.remaining_size = size(report.remaining_size);
+		}
+		ProtoExecutionType::FullFill => {
+			out.exec_type = ExecutionType::Filled;
+			out.filled_size = size(report.filled_size);
+		}
+		ProtoExecutionType::OrderCancelled => {
+			out.exec_type = ExecutionType::Cancelled;
+			out.remaining_size = size(report.remaining_size);
+		}
+		ProtoExecutionType::CancelRejected => {
+			out.exec_type = ExecutionType::CancelRejected;
+			out.reason = reason();
+		}
+	}
+	out
+}
+
+fn into_reply<T: Serialize>(id: String, result: Result<T, GatewayError>) -> ServerMessage {
+	match result.map(|value| serde_json::to_value(value).unwrap_or_default()) {
+		Ok(result) => ServerMessage::Result { id, result },
+		Err(err) => error_message(Some(id), err),
+	}
+}
+
+fn error_message(id: Option<String>, err: GatewayError) -> ServerMessage {
+	ServerMessage::Error {
+		id,
+		error: err.to_json(),
+	}
+}
+
+fn not_logged_in(id: String, context: &RequestContext) -> ServerMessage {
+	error_message(
+		Some(id),
+		GatewayError::auth(AuthError::NotLoggedIn, context),
+	)
+}
+
+async fn send(session: &mut Session, message: &ServerMessage) -> Result<(), actix_ws::Closed> {
+	match serde_json::to_string(message) {
+		Ok(text) => session.text(text).await,
+		Err(e) => {
+			tracing::error!(target: "server::ws", error = %e, "Failed to serialize message");
+			Ok(())
+		}
+	}
+}
+
+/// Each WebSocket operation is its own request for tracing and errors
+pub(crate) fn new_context() -> RequestContext {
+	RequestContext {
+		request_id: Uuid::new_v4().to_string(),
+		trace_id: Uuid::new_v4().to_string(),
+		traceparent: None,
+		tracestate: None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anvil_sdk::signing::{SignatureAlgorithm, query_signing_message, sign_message};
+	use ed25519_dalek::SigningKey;
+
+	fn context() -> RequestContext {
+		new_context()
+	}
+
+	#[test]
+	fn parses_tagged_client_messages() {
+		let message: ClientMessage = serde_json::from_str(
+			r#"{"op":"cancel","id":"1","market":"BTC-USDT","order_id":"o1","timestamp":5,"nonce":"n"}"#,
+		)
+		.unwrap();
+		assert!(matches!(
+			message,
+			ClientMessage::Cancel { ref order_id, timestamp: 5, .. } if order_id == "o1"
+		));
+	}
+
+	#[test]
+	fn login_verifies_signature_through_provider() {
+		let private_key = [7u8; 32];
+		let public_key = SigningKey::from_bytes(&private_key)
+			.verifying_key()
+			.to_bytes();
+		let timestamp = std::time::SystemTime::now()
+			.duration_since(std::time::UNIX_EPOCH)
+			.unwrap()
+			.as_secs();
+		let nonce = Uuid::new_v4().to_string();
+		let signature = sign_message(
+			&query_signing_message(LOGIN_METHOD, WS_PATH, timestamp, &nonce),
+			&private_key,
+			SignatureAlgorithm::Ed25519,
+		)
+		.unwrap();
+
+		let mut materials = LoginMaterials {
+			public_key: hex::encode(public_key),
+			signature,
+			timestamp,
+			nonce,
+			algorithm: None,
+		};
+		let provider = auth::SignatureAuthProvider;
+		let login = |materials: &LoginMaterials| {
+			authenticate_login(materials, LOGIN_METHOD, WS_PATH, &provider, &context())
+		};
+		let authenticated = login(&materials).unwrap();
+		assert_eq!(authenticated.principal.id(), hex::encode(public_key));
+
+		// The nonce cannot be reused for another login
+		assert!(login(&materials).is_err());
+
+		materials.nonce = Uuid::new_v4().to_string();
+		assert!(login(&materials).is_err());
+	}
+
+	#[test]
+	fn execution_report_formats_market_units() {
+		let report = execution_report(
+			ProtoExecutionReport {
+				sequence: 9,
+				market: "BTC-USDT".to_string(),
+				order_id: "o1".to_string(),
+				exec_type: ProtoExecutionType::Trade as i32,
+				price: 5_000_012,
+				size: 150_000_000,
+				trade_id: "t1".to_string(),
+				role: ProtoLiquidityRole::Taker as i32,
+				..Default::default()
+			},
+			2,
+			8,
+		);
+		assert_eq!(report.exec_type, ExecutionType::Trade);
+		assert_eq!(report.price.as_deref(), Some("50000.12"));
+		assert_eq!(report.size.as_deref(), Some("1.50000000"));
+		assert_eq!(report.role, Some(LiquidityRole::Taker));
+		assert_eq!(report.remaining_size, None);
+	}
+}
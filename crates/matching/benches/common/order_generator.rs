@@ -52,6 +52,8 @@ impl OrderGenerator {
 						size: 1,
 						timestamp: now(),
 						public_key: format!("bench_{}", self.thread_id),
+						client_order_id: None,
+						nonce: String::new(),
 					}
 				} else {
 					OrderCommand {
@@ -62,6 +64,8 @@ impl OrderGenerator {
 						size: 1,
 						timestamp: now(),
 						public_key: format!("bench_{}", self.thread_id),
+						client_order_id: None,
+						nonce: String::new(),
 					}
 				}
 			}
@@ -77,6 +81,8 @@ impl OrderGenerator {
 				size: 10,
 				timestamp: now(),
 				public_key: format!("bench_{}", self.thread_id),
+				client_order_id: None,
+				nonce: String::new(),
 			},
 			Scenario::DeepBook => {
 				// “插针式扫深度”负载模型：
@@ -107,6 +113,8 @@ impl OrderGenerator {
 						size: 10_000_000,
 						timestamp: now(),
 						public_key: format!("bench_{}", self.thread_id),
+						client_order_id: None,
+						nonce: String::new(),
 					}
 				} else {
 					let mid: u64 = 50_000;
@@ -128,6 +136,8 @@ impl OrderGenerator {
 						size: 1_000,
 						timestamp: now(),
 						public_key: format!("bench_{}", self.thread_id),
+						client_order_id: None,
+						nonce: String::new(),
 					}
 				}
 			}
@@ -164,6 +174,8 @@ impl OrderGenerator {
 					size: 1_000,
 					timestamp: now(),
 					public_key: "warmup".to_string(),
+					client_order_id: None,
+					nonce: String::new(),
 				}
 			})
 			.collect()
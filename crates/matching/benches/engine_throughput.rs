@@ -83,6 +83,7 @@ fn run_benchmark(num_producers: usize, scenario: &Scenario) -> Duration {
 	let engine_config = EngineConfig {
 		market: "BTC-USDT".to_string(),
 		verbose_logging: false,
+		..Default::default()
 	};
 
 	let _matching_engine = MatchingEngine::start(
@@ -0,0 +1,176 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Matching latency while snapshots are taken
+//!
+//! `snapshot_stall` compares what the matching loop spends on a snapshot
+//! request (capturing a view of its state) with the full cost of a
+//! snapshot, which is paid on the requesting thread. `matching_during_snapshots`
+//! times bursts of resting orders on a deep book, with and without a
+//! thread taking snapshots back to back; the two should be flat as long as
+//! the snapshotting thread has a core of its own, otherwise it takes CPU
+//! time from the matching loop like any other busy thread.
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anvil_matching::{
+	EventBuffer, EventWriter, EventWriterConfig, IngressQueue, MatchingEngine, MemoryEventStorage,
+	MemoryOrderJournal, OrderJournal, QueueSender, engine::EngineConfig,
+};
+
+// Shared with the throughput benchmark, which uses the rest of it
+#[allow(dead_code)]
+mod common;
+use common::order_generator::{OrderGenerator, Scenario};
+
+const BOOK_SIZES: &[usize] = &[10_000, 100_000];
+const BURST_ORDERS: usize = 1_000;
+
+struct Setup {
+	queue_sender: QueueSender,
+	engine: Arc<MatchingEngine>,
+	_event_writer: EventWriter,
+}
+
+/// Start an engine with `book_size` resting orders
+fn setup(book_size: usize) -> Setup {
+	let journal: Box<dyn OrderJournal> = Box::new(MemoryOrderJournal::new());
+	let journal = Arc::new(Mutex::new(journal));
+
+	let (queue_sender, queue_receiver) = IngressQueue::new(1_000_000).split();
+	let (event_producer, event_consumer) = EventBuffer::new(1_000_000).split();
+
+	let event_writer = EventWriter::start(
+		event_consumer,
+		Box::new(MemoryEventStorage::new()),
+		journal.clone(),
+		EventWriterConfig {
+			batch_size: 1000,
+			batch_timeout_ms: 50,
+			verbose_logging: false,
+		},
+	);
+
+	let engine = Arc::new(MatchingEngine::start(
+		EngineConfig {
+			market: "BTC-USDT".to_string(),
+			verbose_logging: false,
+			..Default::default()
+		},
+		queue_receiver,
+		event_producer,
+		journal,
+	));
+
+	let generator = OrderGenerator::new(0, Scenario::DeepBook);
+	for order in generator.warmup_orders(book_size) {
+		queue_sender.try_enqueue(order).unwrap();
+	}
+	wait_for_orders(&engine, book_size);
+
+	Setup {
+		queue_sender,
+		engine,
+		_event_writer: event_writer,
+	}
+}
+
+/// Wait until `count` orders rest on the book
+fn wait_for_orders(engine: &MatchingEngine, count: usize) {
+	while engine.capture_state().unwrap().orderbook.order_count() < count {
+		thread::sleep(Duration::from_micros(100));
+	}
+}
+
+fn bench_snapshot_stall(c: &mut Criterion) {
+	let mut group = c.benchmark_group("snapshot_stall");
+	group.sample_size(10);
+
+	for &book_size in BOOK_SIZES {
+		let setup = setup(book_size);
+
+		group.bench_with_input(
+			BenchmarkId::new("capture_state", book_size),
+			&setup.engine,
+			|b, engine| b.iter(|| engine.capture_state().unwrap()),
+		);
+		group.bench_with_input(
+			BenchmarkId::new("create_snapshot", book_size),
+			&setup.engine,
+			|b, engine| b.iter(|| engine.create_snapshot().unwrap()),
+		);
+	}
+
+	group.finish();
+}
+
+fn bench_matching_during_snapshots(c: &mut Criterion) {
+	let mut group = c.benchmark_group("matching_during_snapshots");
+	group.sample_size(10);
+
+	let book_size = *BOOK_SIZES.last().unwrap();
+	for snapshotting in [false, true] {
+		let setup = setup(book_size);
+		let stop = Arc::new(AtomicBool::new(false));
+		let snapshotter = snapshotting.then(|| {
+			let engine = setup.engine.clone();
+			let stop = stop.clone();
+			thread::spawn(move || {
+				while !stop.load(Ordering::Relaxed) {
+					engine.create_snapshot().unwrap();
+				}
+			})
+		});
+
+		let name = if snapshotting { "snapshotting" } else { "idle" };
+		let mut generator = OrderGenerator::new(1, Scenario::NoCross);
+		let mut resting = book_size;
+		group.bench_function(BenchmarkId::new(name, book_size), |b| {
+			b.iter_custom(|iters| {
+				let mut total = Duration::ZERO;
+				for _ in 0..iters {
+					let start = Instant::now();
+					for _ in 0..BURST_ORDERS {
+						setup
+							.queue_sender
+							.try_enqueue(generator.next_order())
+							.unwrap();
+					}
+					resting += BURST_ORDERS;
+					wait_for_orders(&setup.engine, resting);
+					total += start.elapsed();
+				}
+				total
+			});
+		});
+
+		stop.store(true, Ordering::Relaxed);
+		if let Some(handle) = snapshotter {
+			handle.join().unwrap();
+		}
+	}
+
+	group.finish();
+}
+
+criterion_group!(
+	benches,
+	bench_snapshot_stall,
+	bench_matching_during_snapshots
+);
+criterion_main!(benches);
@@ -64,6 +64,7 @@ async fn main() -> Result<()> {
 	let engine_config = EngineConfig {
 		market: config.market.clone(),
 		verbose_logging: false,
+		..Default::default()
 	};
 
 	let _matching_engine = MatchingEngine::start(
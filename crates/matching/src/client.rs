@@ -29,7 +29,8 @@ pub mod proto {
 
 use proto::settlement_service_client::SettlementServiceClient;
 use proto::{
-	Chain as ProtoChain, OrderSide as ProtoOrderSide, SubmitTradesRequest, Trade as ProtoTrade,
+	Chain as ProtoChain, OrderSide as ProtoOrderSide, SubmissionStatus as ProtoSubmissionStatus,
+	SubmitTradesRequest, Trade as ProtoTrade,
 };
 
 /// Error types for gRPC client operations
@@ -39,6 +40,10 @@ pub enum SettlementClientError {
 	Transport(String),
 	#[error("gRPC status error: {0}")]
 	Status(String),
+	/// The settlement service refused the trades; resubmitting the same
+	/// batch will not succeed
+	#[error("Trades rejected: {0}")]
+	Rejected(String),
 	#[error("Serialization error: {0}")]
 	Serialization(String),
 }
@@ -64,6 +69,21 @@ impl SettlementGrpcClient {
 		})
 	}
 
+	/// Create a client that connects on first use
+	///
+	/// Useful for long-running callers that retry, since the settlement
+	/// service does not have to be reachable at startup.
+	pub fn connect_lazy(endpoint: &str) -> Result<Self, SettlementClientError> {
+		let channel = Endpoint::from_shared(endpoint.to_string())
+			.map_err(|e| SettlementClientError::Transport(format!("Invalid endpoint: {}", e)))?
+			.timeout(Duration::from_secs(10))
+			.connect_lazy();
+
+		Ok(Self {
+			client: SettlementServiceClient::new(channel),
+		})
+	}
+
 	/// Submit matched trades for settlement
 	pub async fn submit_trades(
 		&mut self,
@@ -102,9 +122,19 @@ impl SettlementGrpcClient {
 			.client
 			.submit_trades(tonic::Request::new(request))
 			.await
-			.map_err(|e| SettlementClientError::Status(format!("gRPC error: {}", e)))?
+			.map_err(|e| match e.code() {
+				// Validation failures are permanent for this batch
+				tonic::Code::InvalidArgument | tonic::Code::FailedPrecondition => {
+					SettlementClientError::Rejected(e.message().to_string())
+				}
+				_ => SettlementClientError::Status(format!("gRPC error: {}", e)),
+			})?
 			.into_inner();
 
+		if response.status == ProtoSubmissionStatus::Failed as i32 {
+			return Err(SettlementClientError::Rejected(response.error));
+		}
+
 		Ok(response.tx_hash)
 	}
 }
@@ -16,6 +16,12 @@ use std::{net::SocketAddr, path::PathBuf};
 
 use serde::{Deserialize, Serialize};
 
+use crate::{
+	event::{EventHubConfig, EventRetention},
+	risk::LimitsConfig,
+	settlement::SettlementForwarderConfig,
+};
+
 // Logging configuration constants
 /// Default log level (can be overridden by RUST_LOG environment variable)
 pub const DEFAULT_LOG_LEVEL: &str = "info";
@@ -27,7 +33,10 @@ pub const LOG_COMPONENT_NAME: &str = "matching";
 pub const DEFAULT_LOG_TO_CONSOLE: bool = false;
 
 /// Matching engine configuration
+///
+/// Every field has a default, so a deployment only sets what it changes.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct MatchingConfig {
 	/// gRPC server bind address
 	pub bind_addr: SocketAddr,
@@ -49,12 +58,83 @@ pub struct MatchingConfig {
 	pub max_snapshots_to_keep: usize,
 	/// Journal path (optional, for future file-based journal)
 	pub journal_path: Option<PathBuf>,
-	/// Event storage path (optional, for future file-based events)
+	/// Event log directory; events are kept in memory only when unset
 	pub event_storage_path: Option<PathBuf>,
-	/// Snapshot path (optional, for future file-based snapshots)
+	/// Snapshot directory; the engine starts from the latest snapshot in it
+	/// (default: snapshots are kept in memory)
 	pub snapshot_path: Option<PathBuf>,
 	/// Enable verbose logging
 	pub verbose_logging: bool,
+	/// Reject orders that exceed the owner's ledger balance
+	#[serde(default)]
+	pub enforce_balances: bool,
+	/// Per-principal risk limits (defaults plus public key overrides)
+	#[serde(default)]
+	pub risk_limits: LimitsConfig,
+	/// Book updates a market data subscriber may fall behind before resync
+	#[serde(default = "default_market_data_capacity")]
+	pub market_data_capacity: usize,
+	/// Committed event feeds (trade stream, L3 feed): buffering and
+	/// slow-consumer handling
+	#[serde(default)]
+	pub event_feed: EventHubConfig,
+	/// Most recent trades kept for trade and fill history queries
+	#[serde(default = "default_trade_history_capacity")]
+	pub trade_history_capacity: usize,
+	/// Completed orders kept for order queries, besides every open order
+	#[serde(default = "default_order_history_capacity")]
+	pub order_history_capacity: usize,
+	/// How long a request's `(principal, nonce)` resolves to the order it was
+	/// accepted as (seconds)
+	#[serde(default = "default_idempotency_window_secs")]
+	pub idempotency_window_secs: u64,
+	/// Forwarding of committed trades to `settlement_endpoint`
+	#[serde(default)]
+	pub settlement: SettlementForwarderConfig,
+	/// Primary engine to replicate; when set, the engine starts as a
+	/// follower and accepts no orders until promoted
+	#[serde(default)]
+	pub replicate_from: Option<String>,
+	/// Fencing epoch the engine starts at
+	#[serde(default)]
+	pub epoch: u64,
+	/// Lease file for electing the leader among instances that share
+	/// `event_storage_path`; when set, the engine starts as a follower and
+	/// leads while it holds the lease
+	#[serde(default)]
+	pub lease_path: Option<PathBuf>,
+	/// Name of this instance in the lease (a random ID when unset)
+	#[serde(default)]
+	pub instance_id: Option<String>,
+	/// How long a lease lasts without renewal (milliseconds)
+	#[serde(default = "default_lease_ttl_ms")]
+	pub lease_ttl_ms: u64,
+	/// What happens to events once the oldest retained snapshot covers them
+	#[serde(default)]
+	pub event_retention: EventRetention,
+	/// Directory of the event archive, for `event_retention = archive`
+	#[serde(default)]
+	pub event_archive_path: Option<PathBuf>,
+}
+
+fn default_market_data_capacity() -> usize {
+	4096
+}
+
+fn default_trade_history_capacity() -> usize {
+	100_000
+}
+
+fn default_order_history_capacity() -> usize {
+	100_000
+}
+
+fn default_lease_ttl_ms() -> u64 {
+	5000
+}
+
+fn default_idempotency_window_secs() -> u64 {
+	crate::journal::DEFAULT_IDEMPOTENCY_WINDOW.as_secs()
 }
 
 impl Default for MatchingConfig {
@@ -73,6 +153,21 @@ impl Default for MatchingConfig {
 			event_storage_path: None,
 			snapshot_path: None,
 			verbose_logging: false,
+			enforce_balances: false,
+			risk_limits: LimitsConfig::default(),
+			market_data_capacity: default_market_data_capacity(),
+			event_feed: EventHubConfig::default(),
+			trade_history_capacity: default_trade_history_capacity(),
+			order_history_capacity: default_order_history_capacity(),
+			idempotency_window_secs: default_idempotency_window_secs(),
+			settlement: SettlementForwarderConfig::default(),
+			replicate_from: None,
+			epoch: 0,
+			lease_path: None,
+			instance_id: None,
+			lease_ttl_ms: default_lease_ttl_ms(),
+			event_retention: EventRetention::Keep,
+			event_archive_path: None,
 		}
 	}
 }
@@ -80,20 +175,100 @@ impl Default for MatchingConfig {
 impl MatchingConfig {
 	/// Load configuration from environment variables
 	pub fn from_env() -> Result<Self, config::ConfigError> {
-		let cfg = config::Config::builder()
-			.add_source(config::Environment::with_prefix("MATCHING"))
-			.build()?;
-
-		cfg.try_deserialize()
+		Self::load(config::Config::builder().add_source(environment()))
 	}
 
 	/// Load configuration from file
 	pub fn from_file(path: &str) -> Result<Self, config::ConfigError> {
-		let cfg = config::Config::builder()
-			.add_source(config::File::with_name(path))
-			.add_source(config::Environment::with_prefix("MATCHING"))
-			.build()?;
+		Self::load(
+			config::Config::builder()
+				.add_source(config::File::with_name(path))
+				.add_source(environment()),
+		)
+	}
+
+	fn load(
+		builder: config::ConfigBuilder<config::builder::DefaultState>,
+	) -> Result<Self, config::ConfigError> {
+		builder.build()?.try_deserialize()
+	}
+}
+
+/// `MATCHING_` environment variables
+///
+/// Nested fields are separated by `__`, as in
+/// `MATCHING_RISK_LIMITS__DEFAULTS__MAX_OPEN_ORDERS`.
+fn environment() -> config::Environment {
+	config::Environment::with_prefix("MATCHING")
+		.prefix_separator("_")
+		.separator("__")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn from_vars(vars: &[(&str, &str)]) -> Result<MatchingConfig, config::ConfigError> {
+		let vars = vars
+			.iter()
+			.map(|(key, value)| (key.to_string(), value.to_string()))
+			.collect();
+		MatchingConfig::load(config::Config::builder().add_source(environment().source(Some(vars))))
+	}
+
+	#[test]
+	fn unset_fields_take_their_defaults() {
+		let config = from_vars(&[("MATCHING_REPLICATE_FROM", "http://primary:50051")]).unwrap();
+		assert_eq!(
+			config.replicate_from.as_deref(),
+			Some("http://primary:50051")
+		);
+		assert_eq!(config.market, MatchingConfig::default().market);
+		assert_eq!(config.lease_ttl_ms, default_lease_ttl_ms());
+	}
+
+	#[test]
+	fn lease_variables_alone_enable_leader_election() {
+		let config = from_vars(&[
+			("MATCHING_LEASE_PATH", "/data/matching.lease"),
+			("MATCHING_INSTANCE_ID", "matching-1"),
+			("MATCHING_LEASE_TTL_MS", "2000"),
+		])
+		.unwrap();
+		assert_eq!(
+			config.lease_path,
+			Some(PathBuf::from("/data/matching.lease"))
+		);
+		assert_eq!(config.instance_id.as_deref(), Some("matching-1"));
+		assert_eq!(config.lease_ttl_ms, 2000);
+		assert_eq!(config.replicate_from, None);
+		assert_eq!(config.epoch, 0);
+	}
+
+	#[test]
+	fn risk_limits_are_read_from_nested_variables() {
+		let config = from_vars(&[
+			("MATCHING_ENFORCE_BALANCES", "true"),
+			("MATCHING_RISK_LIMITS__DEFAULTS__MAX_NET_POSITION", "10"),
+			("MATCHING_RISK_LIMITS__DEFAULTS__MAX_OPEN_ORDERS", "5"),
+			(
+				"MATCHING_RISK_LIMITS__OVERRIDES__ABCD__MAX_NET_POSITION",
+				"100",
+			),
+		])
+		.unwrap();
+		assert!(config.enforce_balances);
+		let defaults = config.risk_limits.limits_for("ef01");
+		assert_eq!(defaults.max_net_position, Some(10));
+		assert_eq!(defaults.max_open_orders, Some(5));
+		// Keys are read in lower case, as public keys are hex-encoded
+		let overridden = config.risk_limits.limits_for("abcd");
+		assert_eq!(overridden.max_net_position, Some(100));
+		assert_eq!(overridden.max_open_orders, Some(5));
+	}
 
-		cfg.try_deserialize()
+	#[test]
+	fn invalid_values_are_errors() {
+		assert!(from_vars(&[("MATCHING_EPOCH", "one")]).is_err());
 	}
 }
@@ -0,0 +1,120 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use imbl::OrdMap;
+use serde::{Deserialize, Serialize};
+
+use crate::event::MatchingEvent;
+
+/// Client order IDs of resting orders, per principal
+///
+/// Like the exposure tracker, it is mutated only through
+/// [`ClientOrderIndex::apply`], so snapshots plus replay rebuild it exactly.
+/// An ID is taken from the moment its order rests until the order is filled
+/// or cancelled.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClientOrderIndex {
+	/// public_key -> client order ID -> order ID
+	open: OrdMap<String, OrdMap<String, String>>,
+	/// order ID -> (public_key, client order ID)
+	owners: OrdMap<String, (String, String)>,
+}
+
+impl ClientOrderIndex {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Resting order of `public_key` with `client_order_id`
+	pub fn order_id(&self, public_key: &str, client_order_id: &str) -> Option<&str> {
+		self.open
+			.get(public_key)?
+			.get(client_order_id)
+			.map(String::as_str)
+	}
+
+	/// Apply a matching event to the index
+	pub fn apply(&mut self, event: &MatchingEvent) {
+		match event {
+			MatchingEvent::OrderAccepted {
+				order_id,
+				public_key,
+				client_order_id: Some(client_order_id),
+				..
+			} => {
+				self.open
+					.entry(public_key.clone())
+					.or_default()
+					.insert(client_order_id.clone(), order_id.clone());
+				self.owners.insert(
+					order_id.clone(),
+					(public_key.clone(), client_order_id.clone()),
+				);
+			}
+			MatchingEvent::MakerOrderFilled { order_id, .. }
+			| MatchingEvent::OrderCancelled { order_id, .. } => {
+				if let Some((public_key, client_order_id)) = self.owners.remove(order_id)
+					&& let Some(orders) = self.open.get_mut(&public_key)
+				{
+					orders.remove(&client_order_id);
+					if orders.is_empty() {
+						self.open.remove(&public_key);
+					}
+				}
+			}
+			_ => {}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use anvil_sdk::types::Side;
+
+	use super::*;
+
+	fn accepted(order_id: &str, client_order_id: Option<&str>) -> MatchingEvent {
+		MatchingEvent::OrderAccepted {
+			seq: 0,
+			order_id: order_id.to_string(),
+			market: "BTC-USDT".to_string(),
+			public_key: "alice".to_string(),
+			client_order_id: client_order_id.map(str::to_string),
+			side: Side::Buy,
+			price: 100,
+			size: 1,
+			timestamp: 0,
+		}
+	}
+
+	#[test]
+	fn ids_are_taken_while_the_order_rests() {
+		let mut index = ClientOrderIndex::new();
+		index.apply(&accepted("o1", Some("c1")));
+		index.apply(&accepted("o2", None));
+
+		assert_eq!(index.order_id("alice", "c1"), Some("o1"));
+		assert_eq!(index.order_id("bob", "c1"), None);
+
+		index.apply(&MatchingEvent::MakerOrderFilled {
+			seq: 0,
+			order_id: "o1".to_string(),
+			market: "BTC-USDT".to_string(),
+			filled_size: 1,
+			timestamp: 0,
+		});
+		assert_eq!(index.order_id("alice", "c1"), None);
+		assert!(index.open.is_empty() && index.owners.is_empty());
+	}
+}
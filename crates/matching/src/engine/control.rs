@@ -14,8 +14,8 @@
 
 use tokio::sync::oneshot;
 
-use crate::event::MatchingEvent;
-use crate::snapshot::Snapshot;
+use super::MatchingEngineState;
+use crate::event::{MatchingEvent, SequenceNumber};
 
 /// Control messages for the matching engine
 ///
@@ -26,20 +26,22 @@ use crate::snapshot::Snapshot;
 /// order commands, ensuring thread-safe access to engine state.
 #[derive(Debug)]
 pub enum EngineControlMessage {
-	/// Request a snapshot of the current engine state
+	/// Request a view of the current engine state for a snapshot
 	///
-	/// The matching loop will create a snapshot and send it back via the oneshot channel.
-	/// This is non-blocking from the requester's perspective.
-	CreateSnapshot {
-		respond_to: oneshot::Sender<Result<Snapshot, String>>,
+	/// The matching loop only clones its state, which shares structure with
+	/// the live state, and sends it back via the oneshot channel; the
+	/// requester serializes it without holding up matching.
+	CaptureState {
+		respond_to: oneshot::Sender<MatchingEngineState>,
 	},
 
-	/// Request to restore engine state from a snapshot
+	/// Request to restore engine state decoded from a snapshot
 	///
-	/// Used during crash recovery to restore orderbook state.
-	RestoreSnapshot {
-		snapshot: Snapshot,
-		respond_to: oneshot::Sender<Result<(), String>>,
+	/// Used during crash recovery to restore orderbook state. The requester
+	/// decodes the snapshot; the matching loop only swaps the state in.
+	RestoreState {
+		state: Box<MatchingEngineState>,
+		respond_to: oneshot::Sender<()>,
 	},
 
 	/// Request to replay events to rebuild orderbook state
@@ -50,6 +52,14 @@ pub enum EngineControlMessage {
 		respond_to: oneshot::Sender<Result<(), String>>,
 	},
 
+	/// Apply events committed by the primary, on a follower replica
+	///
+	/// Responds with the sequence of the last event applied.
+	ApplyReplicated {
+		events: Vec<MatchingEvent>,
+		respond_to: oneshot::Sender<Result<SequenceNumber, String>>,
+	},
+
 	/// Request the engine to shut down gracefully
 	Shutdown,
 }
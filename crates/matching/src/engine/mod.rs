@@ -12,13 +12,16 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod client_orders;
 mod control;
 mod state;
 
+pub use client_orders::ClientOrderIndex;
 pub use control::EngineControlMessage;
 pub use state::MatchingEngineState;
 
 use std::{
+	io::{Read, Write},
 	sync::{
 		Arc,
 		atomic::{AtomicBool, Ordering},
@@ -34,17 +37,23 @@ use tracing::{debug, error, info, warn};
 
 use crate::{
 	OrderBook,
-	event::{EventProducer, MatchingEvent},
+	event::{EventProducer, MatchingEvent, RejectReason, SequenceNumber},
 	journal::OrderJournal,
 	queue::QueueReceiver,
-	snapshot::{Snapshot, SnapshotMetadata},
-	types::{Order, OrderCommand},
+	risk::{LedgerError, LimitsConfig, RiskConfig},
+	snapshot::{Snapshot, SnapshotMetadata, format::SNAPSHOT_FORMAT_VERSION},
+	types::{
+		BalanceAdjustment, BalanceCommand, CancelCommand, EngineCommand, Order, OrderCommand,
+		ReplaceCommand,
+	},
 };
 
 /// Result of a match operation including trade and maker order info
 struct MatchResult {
 	trade: Trade,
 	maker_order_id: String,
+	maker_public_key: String,
+	maker_client_order_id: Option<String>,
 	maker_was_fully_filled: bool,
 	maker_remaining_size: u64,
 }
@@ -67,6 +76,8 @@ pub enum EngineError {
 pub struct EngineConfig {
 	pub market: String,
 	pub verbose_logging: bool,
+	/// Pre-trade risk checks applied in the matching loop
+	pub risk: RiskConfig,
 }
 
 impl Default for EngineConfig {
@@ -74,6 +85,7 @@ impl Default for EngineConfig {
 		Self {
 			market: "BTC-USDT".to_string(),
 			verbose_logging: false,
+			risk: RiskConfig::default(),
 		}
 	}
 }
@@ -115,7 +127,7 @@ impl MatchingEngine {
 		// Create control channel for snapshot requests and shutdown
 		let (control_tx, control_rx) = mpsc::channel(16);
 
-		let state = MatchingEngineState::new(config.market.clone());
+		let state = MatchingEngineState::with_ledger(config.market.clone(), config.risk.ledger());
 
 		let thread_handle = thread::Builder::new()
 			.name("matching-loop".to_string())
@@ -166,24 +178,33 @@ impl MatchingEngine {
 
 			// Check for control messages (non-blocking)
 			match control_rx.try_recv() {
-				Ok(EngineControlMessage::CreateSnapshot { respond_to }) => {
-					// Create snapshot directly - we own the state
-					let snapshot_result = Self::create_snapshot_internal(&state);
-					let _ = respond_to.send(snapshot_result);
+				Ok(EngineControlMessage::CaptureState { respond_to }) => {
+					// Hand out a copy-on-write view; serialization happens on
+					// the requesting thread
+					let _ = respond_to.send(state.clone());
 				}
-				Ok(EngineControlMessage::RestoreSnapshot {
-					snapshot,
+				Ok(EngineControlMessage::RestoreState {
+					state: restored,
 					respond_to,
 				}) => {
-					// Restore state from snapshot
-					let result = Self::restore_snapshot_internal(&mut state, snapshot);
-					let _ = respond_to.send(result);
+					// The snapshot was decoded by the requester; swap it in
+					state.restore(*restored);
+					info!(
+						"Restored engine state from snapshot at seq={}",
+						state.next_sequence
+					);
+					let _ = respond_to.send(());
 				}
 				Ok(EngineControlMessage::ReplayEvents { events, respond_to }) => {
 					// Replay events to rebuild state
 					let result = Self::replay_events_internal(&mut state, events);
 					let _ = respond_to.send(result);
 				}
+				Ok(EngineControlMessage::ApplyReplicated { events, respond_to }) => {
+					let result =
+						Self::apply_replicated_internal(&mut state, events, event_producer);
+					let _ = respond_to.send(result);
+				}
 				Ok(EngineControlMessage::Shutdown) => {
 					info!(target: "engine", "Received shutdown signal via control channel");
 					break;
@@ -219,22 +240,57 @@ impl MatchingEngine {
 				}
 			};
 
-			if config.verbose_logging {
-				debug!(
-					"Processing order: {} {:?} {} @ {}",
-					cmd.order_id, cmd.side, cmd.size, cmd.price
-				);
-			}
+			Self::process_command(&mut state, cmd, config, event_producer, journal);
+		}
+	}
 
-			// Process the order command
-			if let Err(e) = Self::process_order(&mut state, cmd.clone(), event_producer, journal) {
-				error!(
-					target: "engine",
-					order_id = %cmd.order_id,
-					error = %e,
-					"Failed to process order"
-				);
+	/// Process one dequeued command; a batch's commands run back to back
+	fn process_command(
+		state: &mut MatchingEngineState,
+		cmd: EngineCommand,
+		config: &EngineConfig,
+		event_producer: &EventProducer,
+		journal: &Arc<std::sync::Mutex<Box<dyn OrderJournal>>>,
+	) {
+		let result = match cmd {
+			EngineCommand::Submit(cmd) => {
+				if config.verbose_logging {
+					debug!(
+						"Processing order: {} {:?} {} @ {}",
+						cmd.order_id, cmd.side, cmd.size, cmd.price
+					);
+				}
+				let order_id = cmd.order_id.clone();
+				Self::process_order(state, cmd, &config.risk.limits, event_producer, journal)
+					.map_err(|e| (order_id, e))
 			}
+			EngineCommand::Cancel(cmd) => {
+				let order_id = cmd.order_id.clone();
+				Self::process_cancel(state, cmd, event_producer).map_err(|e| (order_id, e))
+			}
+			EngineCommand::Replace(cmd) => {
+				let order_id = cmd.order.order_id.clone();
+				Self::process_replace(state, cmd, &config.risk.limits, event_producer, journal)
+					.map_err(|e| (order_id, e))
+			}
+			EngineCommand::AdjustBalance(cmd) => {
+				Self::process_balance(state, cmd, event_producer).map_err(|e| (String::new(), e))
+			}
+			EngineCommand::Batch(commands) => {
+				for cmd in commands {
+					Self::process_command(state, cmd, config, event_producer, journal);
+				}
+				Ok(())
+			}
+		};
+
+		if let Err((order_id, e)) = result {
+			error!(
+				target: "engine",
+				order_id = %order_id,
+				error = %e,
+				"Failed to process command"
+			);
 		}
 	}
 
@@ -242,11 +298,41 @@ impl MatchingEngine {
 	fn process_order(
 		state: &mut MatchingEngineState,
 		cmd: OrderCommand,
+		limits: &LimitsConfig,
 		event_producer: &EventProducer,
 		_journal: &Arc<std::sync::Mutex<Box<dyn OrderJournal>>>,
 	) -> Result<(), EngineError> {
 		let order_size = cmd.size;
 		let order_id = cmd.order_id.clone();
+
+		// Client order IDs are unique among the principal's resting orders,
+		// then pre-trade risk checks: per-principal limits, and the order must
+		// be fully fundable up front
+		if let Err((code, reason)) = Self::check_client_order_id(state, &cmd)
+			.and_then(|()| Self::check_risk(state, limits, &cmd))
+		{
+			state.next_sequence += 1;
+			warn!(
+				order_id = %order_id,
+				public_key = %cmd.public_key,
+				code = code.as_str(),
+				error = %reason,
+				seq = state.next_sequence,
+				"Order rejected by pre-trade checks"
+			);
+			let event = MatchingEvent::OrderRejected {
+				seq: state.next_sequence,
+				order_id: cmd.order_id.clone(),
+				market: cmd.market.clone(),
+				public_key: cmd.public_key.clone(),
+				client_order_id: cmd.client_order_id.clone(),
+				code,
+				reason,
+				timestamp: Self::timestamp(),
+			};
+			return Self::emit(state, event_producer, event);
+		}
+
 		let mut order: Order = cmd.clone().into();
 		let mut trades = Vec::new();
 
@@ -279,11 +365,13 @@ impl MatchingEngine {
 					let trade_event = MatchingEvent::TradeExecuted {
 						seq: state.next_sequence,
 						trade: trade.clone(),
+						maker_public_key: result.maker_public_key.clone(),
+						taker_public_key: order.public_key.clone(),
+						maker_client_order_id: result.maker_client_order_id.clone(),
+						taker_client_order_id: order.client_order_id.clone(),
 						timestamp: Self::timestamp(),
 					};
-					event_producer
-						.push(trade_event)
-						.map_err(|_| EngineError::EventBufferFull)?;
+					Self::emit(state, event_producer, trade_event)?;
 
 					// 2. Emit Maker order status event
 					state.next_sequence += 1;
@@ -318,9 +406,7 @@ impl MatchingEngine {
 							timestamp: Self::timestamp(),
 						}
 					};
-					event_producer
-						.push(maker_event)
-						.map_err(|_| EngineError::EventBufferFull)?;
+					Self::emit(state, event_producer, maker_event)?;
 
 					trades.push(trade);
 				}
@@ -350,9 +436,7 @@ impl MatchingEngine {
 				filled_size: order_size,
 				timestamp: Self::timestamp(),
 			};
-			event_producer
-				.push(event)
-				.map_err(|_| EngineError::EventBufferFull)?;
+			Self::emit(state, event_producer, event)?;
 
 			// Note: mark_completed is now called by EventWriter after commit
 		} else if !trades.is_empty() {
@@ -379,9 +463,7 @@ impl MatchingEngine {
 				remaining_size,
 				timestamp: Self::timestamp(),
 			};
-			event_producer
-				.push(event)
-				.map_err(|_| EngineError::EventBufferFull)?;
+			Self::emit(state, event_producer, event)?;
 
 			// Add remaining to orderbook
 			state.orderbook.add_order(order);
@@ -399,14 +481,14 @@ impl MatchingEngine {
 				seq: state.next_sequence,
 				order_id: cmd.order_id.clone(),
 				market: cmd.market.clone(),
+				public_key: cmd.public_key.clone(),
+				client_order_id: cmd.client_order_id.clone(),
 				side: cmd.side,
 				price: cmd.price,
 				size: remaining_size,
 				timestamp: Self::timestamp(),
 			};
-			event_producer
-				.push(accepted_event)
-				.map_err(|_| EngineError::EventBufferFull)?;
+			Self::emit(state, event_producer, accepted_event)?;
 		} else {
 			// No match, add to orderbook
 			let remaining_size = order.remaining_size;
@@ -426,19 +508,262 @@ impl MatchingEngine {
 				seq: state.next_sequence,
 				order_id: cmd.order_id.clone(),
 				market: cmd.market.clone(),
+				public_key: cmd.public_key.clone(),
+				client_order_id: cmd.client_order_id.clone(),
 				side: cmd.side,
 				price: cmd.price,
 				size: remaining_size,
 				timestamp: Self::timestamp(),
 			};
-			event_producer
-				.push(event)
-				.map_err(|_| EngineError::EventBufferFull)?;
+			Self::emit(state, event_producer, event)?;
 		}
 
 		Ok(())
 	}
 
+	/// Process a cancel request for a resting order
+	///
+	/// Only the principal that placed the order may cancel it. A cancel by
+	/// client order ID targets the requester's resting order with that ID.
+	/// Failed cancels are recorded as `CancelRejected` so the requester
+	/// learns the outcome from the event stream.
+	fn process_cancel(
+		state: &mut MatchingEngineState,
+		mut cmd: CancelCommand,
+		event_producer: &EventProducer,
+	) -> Result<(), EngineError> {
+		if let Some(client_order_id) = &cmd.client_order_id {
+			cmd.order_id = state
+				.client_orders
+				.order_id(&cmd.public_key, client_order_id)
+				.unwrap_or_default()
+				.to_string();
+		}
+		let owner = state
+			.orderbook
+			.find_order_mut(&cmd.order_id)
+			.map(|order| (order.side, order.public_key.clone()));
+
+		let rejection = match owner {
+			Some((side, public_key)) if public_key == cmd.public_key => {
+				let removed = state.orderbook.remove_order(side, &cmd.order_id);
+				let (remaining_size, client_order_id) = removed
+					.map(|o| (o.remaining_size, o.client_order_id))
+					.unwrap_or_default();
+				state.next_sequence += 1;
+
+				info!(
+					order_id = %cmd.order_id,
+					market = %cmd.market,
+					remaining_size = remaining_size,
+					seq = state.next_sequence,
+					"Order cancelled"
+				);
+
+				let event = MatchingEvent::OrderCancelled {
+					seq: state.next_sequence,
+					order_id: cmd.order_id,
+					market: cmd.market,
+					public_key: cmd.public_key,
+					client_order_id,
+					remaining_size,
+					timestamp: Self::timestamp(),
+				};
+				return Self::emit(state, event_producer, event);
+			}
+			Some(_) => "Order belongs to a different principal",
+			None => "Order not found",
+		};
+
+		state.next_sequence += 1;
+		debug!(
+			order_id = %cmd.order_id,
+			reason = rejection,
+			seq = state.next_sequence,
+			"Cancel rejected"
+		);
+
+		let event = MatchingEvent::CancelRejected {
+			seq: state.next_sequence,
+			order_id: cmd.order_id,
+			market: cmd.market,
+			public_key: cmd.public_key,
+			client_order_id: cmd.client_order_id,
+			reason: rejection.to_string(),
+			timestamp: Self::timestamp(),
+		};
+		Self::emit(state, event_producer, event)
+	}
+
+	/// Cancel a resting order, then submit its replacement
+	///
+	/// The replacement is rejected without matching if the cancel fails, so
+	/// a principal never ends up with both orders live.
+	fn process_replace(
+		state: &mut MatchingEngineState,
+		cmd: ReplaceCommand,
+		limits: &LimitsConfig,
+		event_producer: &EventProducer,
+		journal: &Arc<std::sync::Mutex<Box<dyn OrderJournal>>>,
+	) -> Result<(), EngineError> {
+		let owned = state
+			.orderbook
+			.find_order_mut(&cmd.replaces)
+			.is_some_and(|order| order.public_key == cmd.order.public_key);
+
+		Self::process_cancel(
+			state,
+			CancelCommand {
+				order_id: cmd.replaces.clone(),
+				market: cmd.order.market.clone(),
+				public_key: cmd.order.public_key.clone(),
+				timestamp: cmd.order.timestamp,
+				client_order_id: None,
+			},
+			event_producer,
+		)?;
+
+		if owned {
+			return Self::process_order(state, cmd.order, limits, event_producer, journal);
+		}
+
+		state.next_sequence += 1;
+		let event = MatchingEvent::OrderRejected {
+			seq: state.next_sequence,
+			order_id: cmd.order.order_id,
+			market: cmd.order.market,
+			public_key: cmd.order.public_key,
+			client_order_id: cmd.order.client_order_id,
+			code: RejectReason::Unspecified,
+			reason: format!("Order {} could not be replaced", cmd.replaces),
+			timestamp: Self::timestamp(),
+		};
+		Self::emit(state, event_producer, event)
+	}
+
+	/// Process a deposit or withdrawal against the balance ledger
+	fn process_balance(
+		state: &mut MatchingEngineState,
+		cmd: BalanceCommand,
+		event_producer: &EventProducer,
+	) -> Result<(), EngineError> {
+		let checked = match state.ledger.as_ref() {
+			Some(ledger) => ledger.check_adjustment(&cmd).map_err(|e| {
+				let code = match e {
+					LedgerError::InsufficientBalance { .. } => RejectReason::InsufficientBalance,
+					LedgerError::UnknownAsset(_) => RejectReason::Unspecified,
+				};
+				(code, e.to_string())
+			}),
+			None => Err((
+				RejectReason::Unspecified,
+				"Balance checks are disabled".to_string(),
+			)),
+		};
+
+		if let Err((code, reason)) = checked {
+			state.next_sequence += 1;
+			warn!(
+				public_key = %cmd.public_key,
+				asset = %cmd.asset,
+				amount = cmd.amount,
+				error = %reason,
+				seq = state.next_sequence,
+				"Balance adjustment rejected"
+			);
+			let event = MatchingEvent::BalanceRejected {
+				seq: state.next_sequence,
+				market: state.orderbook.market().to_string(),
+				public_key: cmd.public_key,
+				asset: cmd.asset,
+				amount: cmd.amount,
+				adjustment: cmd.adjustment,
+				code,
+				reason,
+				timestamp: Self::timestamp(),
+			};
+			return Self::emit(state, event_producer, event);
+		}
+
+		state.next_sequence += 1;
+		let seq = state.next_sequence;
+		let market = state.orderbook.market().to_string();
+		let timestamp = Self::timestamp();
+		let event = match cmd.adjustment {
+			BalanceAdjustment::Deposit => MatchingEvent::BalanceDeposited {
+				seq,
+				market,
+				public_key: cmd.public_key,
+				asset: cmd.asset,
+				amount: cmd.amount,
+				timestamp,
+			},
+			BalanceAdjustment::Withdrawal => MatchingEvent::BalanceWithdrawn {
+				seq,
+				market,
+				public_key: cmd.public_key,
+				asset: cmd.asset,
+				amount: cmd.amount,
+				timestamp,
+			},
+		};
+		Self::emit(state, event_producer, event)
+	}
+
+	/// Reject a client order ID already used by one of the principal's
+	/// resting orders
+	fn check_client_order_id(
+		state: &MatchingEngineState,
+		cmd: &OrderCommand,
+	) -> Result<(), (RejectReason, String)> {
+		match &cmd.client_order_id {
+			Some(client_order_id)
+				if state
+					.client_orders
+					.order_id(&cmd.public_key, client_order_id)
+					.is_some() =>
+			{
+				Err((
+					RejectReason::DuplicateClientOrderId,
+					format!("Client order ID {} is already in use", client_order_id),
+				))
+			}
+			_ => Ok(()),
+		}
+	}
+
+	/// Run pre-trade risk checks for an incoming order
+	fn check_risk(
+		state: &MatchingEngineState,
+		limits: &LimitsConfig,
+		cmd: &OrderCommand,
+	) -> Result<(), (RejectReason, String)> {
+		let principal_limits = limits.limits_for(&cmd.public_key);
+		state
+			.exposure
+			.check_order(&principal_limits, cmd)
+			.map_err(|e| (e.code(), e.to_string()))?;
+
+		if let Some(ledger) = state.ledger.as_ref() {
+			ledger
+				.check_order(&cmd.public_key, cmd.side, cmd.price, cmd.size)
+				.map_err(|e| (RejectReason::InsufficientBalance, e.to_string()))?;
+		}
+		Ok(())
+	}
+
+	/// Apply an event to derived risk state and hand it to the event buffer
+	fn emit(
+		state: &mut MatchingEngineState,
+		event_producer: &EventProducer,
+		event: MatchingEvent,
+	) -> Result<(), EngineError> {
+		state.apply_derived(&event);
+		event_producer
+			.push(event)
+			.map_err(|_| EngineError::EventBufferFull)
+	}
+
 	/// Try to match a buy order against the ask side
 	fn try_match_buy(orderbook: &mut OrderBook, taker_order: &Order) -> Option<MatchResult> {
 		let best_ask = orderbook.best_ask()?;
@@ -484,6 +809,8 @@ impl MatchingEngine {
 		Some(MatchResult {
 			trade,
 			maker_order_id: maker_order.order_id,
+			maker_public_key: maker_order.public_key,
+			maker_client_order_id: maker_order.client_order_id,
 			maker_was_fully_filled,
 			maker_remaining_size,
 		})
@@ -529,6 +856,8 @@ impl MatchingEngine {
 		Some(MatchResult {
 			trade,
 			maker_order_id: maker_order.order_id,
+			maker_public_key: maker_order.public_key,
+			maker_client_order_id: maker_order.client_order_id,
 			maker_was_fully_filled,
 			maker_remaining_size,
 		})
@@ -541,75 +870,102 @@ impl MatchingEngine {
 			.as_secs()
 	}
 
-	/// Internal helper to create a snapshot from state (called within matching loop)
-	fn create_snapshot_internal(state: &MatchingEngineState) -> Result<Snapshot, String> {
-		// Serialize orderbook to JSON
-		let state_data = serde_json::to_vec(&state.orderbook)
-			.map_err(|e| format!("Failed to serialize orderbook: {}", e))?;
+	/// Internal helper to stream a snapshot of a captured state to `out`
+	/// (called outside the matching loop)
+	fn write_snapshot_internal(
+		state: &MatchingEngineState,
+		out: &mut dyn Write,
+	) -> Result<SnapshotMetadata, String> {
+		// Serialize orderbook and derived state in the binary format
+		let (checksum, size_bytes) = state.encode_snapshot(out)?;
 
-		let metadata = SnapshotMetadata {
+		Ok(SnapshotMetadata {
 			created_at: Self::timestamp(),
 			event_seq: state.next_sequence,
-			size_bytes: state_data.len(),
+			size_bytes,
 			market: state.orderbook.market().to_string(),
-		};
-
-		Ok(Snapshot {
-			metadata,
-			state_data,
+			format_version: SNAPSHOT_FORMAT_VERSION,
+			checksum: Some(checksum),
 		})
 	}
 
-	/// Create a snapshot of current engine state
+	/// Capture a consistent view of the current engine state
 	///
-	/// This method sends a snapshot request to the matching loop and waits for the response.
-	/// It does not block the matching loop - the loop processes the request when convenient.
-	pub fn create_snapshot(&self) -> Result<Snapshot, String> {
+	/// The matching loop answers with a clone of its state, which costs
+	/// next to nothing since the state lives in persistent collections; the
+	/// loop copies whatever it modifies afterwards, leaving the view intact.
+	pub fn capture_state(&self) -> Result<MatchingEngineState, String> {
 		// Create a oneshot channel for the response
 		let (tx, rx) = oneshot::channel();
 
-		// Send snapshot request via control channel
+		// Send capture request via control channel
 		self.control_tx
-			.blocking_send(EngineControlMessage::CreateSnapshot { respond_to: tx })
+			.blocking_send(EngineControlMessage::CaptureState { respond_to: tx })
 			.map_err(|_| "Engine shut down or control channel full".to_string())?;
 
 		// Wait for the response
 		rx.blocking_recv()
-			.map_err(|_| "Snapshot request cancelled or engine stopped".to_string())?
+			.map_err(|_| "Snapshot request cancelled or engine stopped".to_string())
 	}
 
-	/// Restore engine state from a snapshot (internal helper)
-	fn restore_snapshot_internal(
-		state: &mut MatchingEngineState,
-		snapshot: Snapshot,
-	) -> Result<(), String> {
-		let orderbook: OrderBook = serde_json::from_slice(&snapshot.state_data)
-			.map_err(|e| format!("Failed to deserialize orderbook: {}", e))?;
-
-		state.orderbook = orderbook;
-		state.next_sequence = snapshot.metadata.event_seq;
+	/// Stream a snapshot of current engine state to `out`
+	///
+	/// Only capturing the state goes through the matching loop; the state
+	/// is serialized on the calling thread, so a large book does not stall
+	/// matching while its snapshot is taken. Returns the snapshot's
+	/// metadata.
+	pub fn write_snapshot(&self, out: &mut dyn Write) -> Result<SnapshotMetadata, String> {
+		let state = self.capture_state()?;
+		Self::write_snapshot_internal(&state, out)
+	}
 
-		info!(
-			"Restored engine state from snapshot at seq={}",
-			snapshot.metadata.event_seq
-		);
+	/// Create a snapshot of current engine state, held in memory
+	///
+	/// Prefer [`write_snapshot`](Self::write_snapshot) to persist a large
+	/// book.
+	pub fn create_snapshot(&self) -> Result<Snapshot, String> {
+		let mut state_data = Vec::new();
+		let metadata = self.write_snapshot(&mut state_data)?;
+		Ok(Snapshot {
+			metadata,
+			state_data,
+		})
+	}
 
-		Ok(())
+	/// Restore engine state from streamed snapshot data
+	///
+	/// The data is decoded and checked against `metadata` on the calling
+	/// thread; the matching loop only swaps in the decoded state, so a
+	/// snapshot that fails to load leaves the engine untouched.
+	pub fn restore_from_reader(
+		&self,
+		metadata: &SnapshotMetadata,
+		data: impl Read,
+	) -> Result<(), String> {
+		self.restore_state(MatchingEngineState::read_snapshot(metadata, data)?)
 	}
 
-	/// Restore engine state from a snapshot (public API using control channel)
-	pub fn restore_from_snapshot(&self, snapshot: Snapshot) -> Result<(), String> {
+	/// Replace the engine state with one read from a snapshot
+	///
+	/// The ledger follows the engine's setting, see
+	/// [`MatchingEngineState::restore`].
+	pub fn restore_state(&self, state: MatchingEngineState) -> Result<(), String> {
 		let (tx, rx) = oneshot::channel();
 
 		self.control_tx
-			.blocking_send(EngineControlMessage::RestoreSnapshot {
-				snapshot,
+			.blocking_send(EngineControlMessage::RestoreState {
+				state: Box::new(state),
 				respond_to: tx,
 			})
 			.map_err(|_| "Engine shut down or control channel full".to_string())?;
 
 		rx.blocking_recv()
-			.map_err(|_| "Restore request cancelled or engine stopped".to_string())?
+			.map_err(|_| "Restore request cancelled or engine stopped".to_string())
+	}
+
+	/// Restore engine state from a snapshot held in memory
+	pub fn restore_from_snapshot(&self, snapshot: Snapshot) -> Result<(), String> {
+		self.restore_from_reader(&snapshot.metadata, snapshot.state_data.as_slice())
 	}
 
 	/// Replay events to rebuild orderbook state (internal helper)
@@ -618,93 +974,175 @@ impl MatchingEngine {
 	/// last snapshot point to the current state.
 	///
 	/// Events are replayed in sequence order to reconstruct the exact orderbook state.
-	/// Both taker and maker order state changes are handled.
+	/// Both taker and maker order state changes are handled. Events must
+	/// follow on from the state's sequence without a gap: a log whose head
+	/// was compacted away cannot rebuild the book without the snapshot that
+	/// covers it, so nothing is applied.
 	fn replay_events_internal(
 		state: &mut MatchingEngineState,
 		events: Vec<MatchingEvent>,
 	) -> Result<(), String> {
+		let mut expected = state.next_sequence + 1;
+		for event in &events {
+			if event.sequence() > expected {
+				return Err(format!(
+					"Events {} to {} are missing from the replay; restore a snapshot that covers them",
+					expected,
+					event.sequence() - 1
+				));
+			}
+			expected = expected.max(event.sequence() + 1);
+		}
+
 		info!("Replaying {} events...", events.len());
 
 		for event in events {
-			match event {
-				MatchingEvent::OrderAccepted {
+			Self::replay_event(state, event);
+		}
+
+		info!("Event replay complete");
+		Ok(())
+	}
+
+	/// Apply one committed event to the order book and derived state
+	fn replay_event(state: &mut MatchingEngineState, event: MatchingEvent) {
+		state.apply_derived(&event);
+		state.next_sequence = state.next_sequence.max(event.sequence());
+
+		match event {
+			MatchingEvent::OrderAccepted {
+				order_id,
+				market,
+				public_key,
+				client_order_id,
+				side,
+				price,
+				size,
+				timestamp,
+				..
+			} => {
+				// Order was accepted and added to book
+				let order = Order {
 					order_id,
 					market,
 					side,
 					price,
 					size,
+					remaining_size: size,
 					timestamp,
-					..
-				} => {
-					// Order was accepted and added to book
-					let order = Order {
-						order_id,
-						market,
-						side,
-						price,
-						size,
-						remaining_size: size,
-						timestamp,
-						public_key: "recovered".to_string(),
-					};
-					state.orderbook.add_order(order);
-				}
-				MatchingEvent::OrderFilled { order_id, .. } => {
-					// Taker order fully filled, remove from book
-					// Try both sides since we don't know which side it was on
-					let _ = state.orderbook.remove_order(Side::Buy, &order_id);
-					let _ = state.orderbook.remove_order(Side::Sell, &order_id);
-				}
-				MatchingEvent::MakerOrderFilled { order_id, .. } => {
-					// Maker order fully filled, remove from book
-					let _ = state.orderbook.remove_order(Side::Buy, &order_id);
-					let _ = state.orderbook.remove_order(Side::Sell, &order_id);
-				}
-				MatchingEvent::OrderPartiallyFilled {
-					order_id,
-					remaining_size,
-					..
-				} => {
-					// Taker order partially filled
-					// The order will be added to book with correct remaining_size
-					// in a subsequent OrderAccepted event, so we can ignore this
-					// or update if it's already in the book (edge case)
-					if let Some(order) = state.orderbook.find_order_mut(&order_id) {
-						order.remaining_size = remaining_size;
-					}
-				}
-				MatchingEvent::MakerOrderPartiallyFilled {
-					order_id,
-					remaining_size,
-					..
-				} => {
-					// Maker order partially filled, update size in book
-					if let Some(order) = state.orderbook.find_order_mut(&order_id) {
-						order.remaining_size = remaining_size;
-					} else {
-						warn!("Maker order {} not found in book during replay", order_id);
-					}
-				}
-				MatchingEvent::OrderCancelled { order_id, .. } => {
-					// Order cancelled, remove from book
-					let _ = state.orderbook.remove_order(Side::Buy, &order_id);
-					let _ = state.orderbook.remove_order(Side::Sell, &order_id);
-				}
-				MatchingEvent::TradeExecuted { .. } => {
-					// TradeExecuted events are for audit/history
-					// The actual state changes are captured in:
-					// - MakerOrderPartiallyFilled / MakerOrderFilled
-					// - OrderPartiallyFilled / OrderFilled
-					// So we don't need to process TradeExecuted during replay
+					public_key,
+					client_order_id,
+				};
+				state.orderbook.add_order(order);
+			}
+			MatchingEvent::OrderFilled { order_id, .. } => {
+				// Taker order fully filled, remove from book
+				// Try both sides since we don't know which side it was on
+				let _ = state.orderbook.remove_order(Side::Buy, &order_id);
+				let _ = state.orderbook.remove_order(Side::Sell, &order_id);
+			}
+			MatchingEvent::MakerOrderFilled { order_id, .. } => {
+				// Maker order fully filled, remove from book
+				let _ = state.orderbook.remove_order(Side::Buy, &order_id);
+				let _ = state.orderbook.remove_order(Side::Sell, &order_id);
+			}
+			MatchingEvent::OrderPartiallyFilled {
+				order_id,
+				remaining_size,
+				..
+			} => {
+				// Taker order partially filled
+				// The order will be added to book with correct remaining_size
+				// in a subsequent OrderAccepted event, so we can ignore this
+				// or update if it's already in the book (edge case)
+				if let Some(order) = state.orderbook.find_order_mut(&order_id) {
+					order.remaining_size = remaining_size;
 				}
-				MatchingEvent::OrderRejected { .. } => {
-					// Rejected orders never entered the book, no state change
+			}
+			MatchingEvent::MakerOrderPartiallyFilled {
+				order_id,
+				remaining_size,
+				..
+			} => {
+				// Maker order partially filled, update size in book
+				if let Some(order) = state.orderbook.find_order_mut(&order_id) {
+					order.remaining_size = remaining_size;
+				} else {
+					warn!("Maker order {} not found in book during replay", order_id);
 				}
 			}
+			MatchingEvent::OrderCancelled { order_id, .. } => {
+				// Order cancelled, remove from book
+				let _ = state.orderbook.remove_order(Side::Buy, &order_id);
+				let _ = state.orderbook.remove_order(Side::Sell, &order_id);
+			}
+			MatchingEvent::TradeExecuted { .. } => {
+				// TradeExecuted events are for audit/history
+				// The actual state changes are captured in:
+				// - MakerOrderPartiallyFilled / MakerOrderFilled
+				// - OrderPartiallyFilled / OrderFilled
+				// So we don't need to process TradeExecuted during replay
+			}
+			MatchingEvent::OrderRejected { .. }
+			| MatchingEvent::CancelRejected { .. }
+			| MatchingEvent::BalanceRejected { .. } => {
+				// Rejected commands never reached the book, no state change
+			}
+			MatchingEvent::BalanceDeposited { .. } | MatchingEvent::BalanceWithdrawn { .. } => {
+				// Balance changes only affect the ledger, applied above
+			}
 		}
+	}
 
-		info!("Event replay complete");
-		Ok(())
+	/// Apply events committed by a primary engine (internal helper)
+	///
+	/// The events must continue the state's sequence without a gap. They
+	/// are replayed, then pushed to the event writer with their sequences
+	/// unchanged. Returns the sequence of the last event applied.
+	fn apply_replicated_internal(
+		state: &mut MatchingEngineState,
+		events: Vec<MatchingEvent>,
+		event_producer: &EventProducer,
+	) -> Result<SequenceNumber, String> {
+		for event in events {
+			let seq = event.sequence();
+			if seq != state.next_sequence + 1 {
+				return Err(format!(
+					"Replicated event {} does not follow applied sequence {}",
+					seq, state.next_sequence
+				));
+			}
+			Self::replay_event(state, event.clone());
+			event_producer
+				.push(event)
+				.map_err(|_| EngineError::EventBufferFull.to_string())?;
+		}
+		Ok(state.next_sequence)
+	}
+
+	/// Apply events committed by the primary, in sequence order (follower
+	/// replicas)
+	///
+	/// Goes through the replay path, so the order book and ledger follow the
+	/// primary's, and on to this engine's event writer, so storage and
+	/// committed-event sinks do too. Returns the sequence of the last event
+	/// applied.
+	pub async fn apply_replicated(
+		&self,
+		events: Vec<MatchingEvent>,
+	) -> Result<SequenceNumber, String> {
+		let (tx, rx) = oneshot::channel();
+
+		self.control_tx
+			.send(EngineControlMessage::ApplyReplicated {
+				events,
+				respond_to: tx,
+			})
+			.await
+			.map_err(|_| "Engine shut down or control channel full".to_string())?;
+
+		rx.await
+			.map_err(|_| "Replication request cancelled or engine stopped".to_string())?
 	}
 
 	/// Replay events to rebuild orderbook state (public API using control channel)
@@ -722,6 +1160,13 @@ impl MatchingEngine {
 			.map_err(|_| "Replay request cancelled or engine stopped".to_string())?
 	}
 
+	/// Whether the matching loop is still running
+	pub fn is_running(&self) -> bool {
+		self.thread_handle
+			.as_ref()
+			.is_some_and(|handle| !handle.is_finished())
+	}
+
 	/// Shutdown the matching engine gracefully
 	pub fn shutdown(mut self) {
 		info!("Shutting down matching engine");
@@ -12,34 +12,342 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{OrderBook, event::SequenceNumber};
+use std::io::{Read, Write};
+
+use serde::Deserialize;
+
+use super::client_orders::ClientOrderIndex;
+use crate::{
+	OrderBook,
+	event::{MatchingEvent, SequenceNumber},
+	risk::{ExposureTracker, Ledger},
+	snapshot::{
+		Snapshot, SnapshotMetadata,
+		format::{
+			ChecksumReader, JSON_SNAPSHOT_VERSION, SNAPSHOT_FORMAT_VERSION, SnapshotReader,
+			SnapshotWriter,
+		},
+	},
+	types::Order,
+};
 
 /// Matching engine state
 ///
 /// This structure holds the complete state of the matching engine:
 /// - Orderbook (all active orders)
+/// - Balance ledger (when pre-trade balance checks are enabled)
+/// - Per-principal exposure for risk limits
+/// - Client order IDs of resting orders
 /// - Sequence counter for events
 ///
 /// The state is owned by the matching loop and can be snapshotted
-/// for crash recovery.
+/// for crash recovery. Every part is kept in persistent collections, so
+/// cloning the state is cheap and yields a consistent view that another
+/// thread can serialize while the loop keeps matching.
+#[derive(Debug, Clone)]
 pub struct MatchingEngineState {
 	/// The orderbook for this market
 	pub orderbook: OrderBook,
+	/// Balance ledger, `None` when balance checks are disabled
+	pub ledger: Option<Ledger>,
+	/// Open orders, resting notional and net position per principal
+	pub exposure: ExposureTracker,
+	/// Client order IDs of resting orders per principal
+	pub client_orders: ClientOrderIndex,
 	/// Next event sequence number to assign
 	pub next_sequence: SequenceNumber,
 }
 
+/// Engine state carried in a JSON (version 1) snapshot
+#[derive(Deserialize)]
+struct SnapshotState {
+	orderbook: OrderBook,
+	#[serde(default)]
+	ledger: Option<Ledger>,
+	#[serde(default)]
+	exposure: ExposureTracker,
+	#[serde(default)]
+	client_orders: ClientOrderIndex,
+}
+
 impl MatchingEngineState {
 	pub fn new(market: String) -> Self {
 		Self {
 			orderbook: OrderBook::new(market),
+			ledger: None,
+			exposure: ExposureTracker::new(),
+			client_orders: ClientOrderIndex::new(),
 			next_sequence: 1,
 		}
 	}
 
+	/// Create state with an (empty) balance ledger
+	pub fn with_ledger(market: String, ledger: Option<Ledger>) -> Self {
+		Self {
+			ledger,
+			..Self::new(market)
+		}
+	}
+
 	/// Reset state to initial conditions
 	pub fn reset(&mut self, market: String) {
 		self.orderbook = OrderBook::new(market);
+		if let Some(ledger) = self.ledger.as_mut() {
+			*ledger = Ledger::new(ledger.base_asset(), ledger.quote_asset());
+		}
+		self.exposure = ExposureTracker::new();
+		self.client_orders = ClientOrderIndex::new();
 		self.next_sequence = 1;
 	}
+
+	/// Apply an event to the state derived from events (ledger, exposure
+	/// and client order IDs)
+	pub fn apply_derived(&mut self, event: &MatchingEvent) {
+		if let Some(ledger) = self.ledger.as_mut() {
+			ledger.apply(event);
+		}
+		self.exposure.apply(event);
+		self.client_orders.apply(event);
+	}
+
+	/// Serialize the order book and derived state for a snapshot in the
+	/// current format, returning the checksum and size of the data
+	///
+	/// Orders are streamed one at a time to `out`, bids then asks in
+	/// priority order, so no serialized copy of the book is built while
+	/// encoding.
+	pub fn encode_snapshot<W: Write>(&self, out: W) -> Result<(u32, usize), String> {
+		let mut writer = SnapshotWriter::new(out)?;
+		writer.write(self.orderbook.market())?;
+		writer.write(&self.ledger)?;
+		writer.write(&self.exposure)?;
+		writer.write(&self.client_orders)?;
+		writer.write(&(self.orderbook.order_count() as u64))?;
+		for order in self.orderbook.orders() {
+			writer.write(order)?;
+		}
+		writer.finish()
+	}
+
+	/// Replace the order book and derived state with those of `restored`
+	///
+	/// The ledger follows this state's setting: it is dropped when balance
+	/// checks are disabled, and starts empty when the snapshot predates it.
+	pub fn restore(&mut self, restored: MatchingEngineState) {
+		self.orderbook = restored.orderbook;
+		self.exposure = restored.exposure;
+		self.client_orders = restored.client_orders;
+		self.next_sequence = restored.next_sequence;
+		if let Some(ledger) = self.ledger.as_mut() {
+			*ledger = restored
+				.ledger
+				.unwrap_or_else(|| Ledger::new(ledger.base_asset(), ledger.quote_asset()));
+		}
+	}
+
+	/// Build the state held in a snapshot, after checking its checksum
+	///
+	/// The ledger is present only if the snapshot carries one. Useful to
+	/// inspect a snapshot outside of an engine.
+	pub fn from_snapshot(snapshot: &Snapshot) -> Result<Self, String> {
+		Self::read_snapshot(&snapshot.metadata, snapshot.state_data.as_slice())
+	}
+
+	/// Build the state held in snapshot data of the given metadata
+	///
+	/// Binary data is decoded as it is read and checked against the
+	/// checksum in `metadata` once read through. JSON snapshots taken before
+	/// the ledger existed contain a bare order book; they restore with an
+	/// empty exposure and no ledger.
+	pub fn read_snapshot(metadata: &SnapshotMetadata, data: impl Read) -> Result<Self, String> {
+		let state = match metadata.format_version {
+			JSON_SNAPSHOT_VERSION => {
+				// JSON snapshots are only read for compatibility, in one piece
+				let mut reader = ChecksumReader::new(data);
+				let mut json = Vec::with_capacity(metadata.size_bytes);
+				reader
+					.read_to_end(&mut json)
+					.map_err(|e| format!("Failed to read snapshot: {}", e))?;
+				reader.finish(metadata)?;
+				match serde_json::from_slice::<SnapshotState>(&json) {
+					Ok(state) => state,
+					Err(_) => SnapshotState {
+						orderbook: serde_json::from_slice(&json)
+							.map_err(|e| format!("Failed to deserialize orderbook: {}", e))?,
+						ledger: None,
+						exposure: ExposureTracker::new(),
+						client_orders: ClientOrderIndex::new(),
+					},
+				}
+			}
+			SNAPSHOT_FORMAT_VERSION => {
+				let mut reader = SnapshotReader::new(data, metadata.format_version)?;
+				let mut orderbook = OrderBook::new(reader.read()?);
+				let ledger = reader.read()?;
+				let exposure = reader.read()?;
+				let client_orders = reader.read()?;
+				for _ in 0..reader.read::<u64>()? {
+					orderbook.add_order(reader.read::<Order>()?);
+				}
+				reader.finish(metadata)?;
+				SnapshotState {
+					orderbook,
+					ledger,
+					exposure,
+					client_orders,
+				}
+			}
+			other => return Err(format!("Unsupported snapshot format version {}", other)),
+		};
+		Ok(Self {
+			orderbook: state.orderbook,
+			ledger: state.ledger,
+			exposure: state.exposure,
+			client_orders: state.client_orders,
+			next_sequence: metadata.event_seq,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use anvil_sdk::types::Side;
+
+	use super::*;
+	use crate::snapshot::format::checksum;
+
+	fn state_with_orders() -> MatchingEngineState {
+		let mut state = MatchingEngineState::with_ledger(
+			"BTC-USDT".to_string(),
+			Some(Ledger::new("BTC", "USDT")),
+		);
+		for (order_id, side, price) in [
+			("b1", Side::Buy, 99),
+			("b2", Side::Buy, 100),
+			("b3", Side::Buy, 100),
+			("a1", Side::Sell, 101),
+		] {
+			let event = MatchingEvent::OrderAccepted {
+				seq: 0,
+				order_id: order_id.to_string(),
+				market: "BTC-USDT".to_string(),
+				public_key: "alice".to_string(),
+				client_order_id: Some(format!("c_{}", order_id)),
+				side,
+				price,
+				size: 5,
+				timestamp: 0,
+			};
+			state.apply_derived(&event);
+			state.orderbook.add_order(Order {
+				order_id: order_id.to_string(),
+				market: "BTC-USDT".to_string(),
+				side,
+				price,
+				size: 5,
+				remaining_size: 4,
+				timestamp: 0,
+				public_key: "alice".to_string(),
+				client_order_id: Some(format!("c_{}", order_id)),
+			});
+		}
+		state.next_sequence = 9;
+		state
+	}
+
+	fn snapshot_of(state: &MatchingEngineState) -> Snapshot {
+		let mut state_data = Vec::new();
+		let (checksum, size_bytes) = state.encode_snapshot(&mut state_data).unwrap();
+		Snapshot {
+			metadata: SnapshotMetadata {
+				created_at: 0,
+				event_seq: state.next_sequence,
+				size_bytes,
+				market: state.orderbook.market().to_string(),
+				format_version: SNAPSHOT_FORMAT_VERSION,
+				checksum: Some(checksum),
+			},
+			state_data,
+		}
+	}
+
+	fn order_ids(state: &MatchingEngineState) -> Vec<&str> {
+		state
+			.orderbook
+			.orders()
+			.map(|o| o.order_id.as_str())
+			.collect()
+	}
+
+	#[test]
+	fn binary_snapshots_round_trip_in_priority_order() {
+		let state = state_with_orders();
+		let snapshot = snapshot_of(&state);
+		assert_eq!(snapshot.metadata.size_bytes, snapshot.state_data.len());
+
+		let restored = MatchingEngineState::from_snapshot(&snapshot).unwrap();
+		assert_eq!(order_ids(&restored), ["b2", "b3", "b1", "a1"]);
+		assert_eq!(restored.orderbook.market(), "BTC-USDT");
+		assert_eq!(restored.orderbook.get_level_depth(Side::Buy, 100), Some(8));
+		assert_eq!(restored.client_orders.order_id("alice", "c_b3"), Some("b3"));
+		assert_eq!(
+			serde_json::to_value(&restored.exposure).unwrap(),
+			serde_json::to_value(&state.exposure).unwrap()
+		);
+		assert!(restored.ledger.is_some());
+		assert_eq!(restored.next_sequence, 9);
+
+		// Encoding is deterministic
+		assert_eq!(snapshot_of(&restored).state_data, snapshot.state_data);
+	}
+
+	#[test]
+	fn corrupted_and_unknown_snapshots_are_rejected() {
+		let mut snapshot = snapshot_of(&state_with_orders());
+		let last = snapshot.state_data.len() - 1;
+		snapshot.state_data[last] ^= 0xff;
+		assert!(MatchingEngineState::from_snapshot(&snapshot).is_err());
+
+		snapshot.state_data[last] ^= 0xff;
+		snapshot.metadata.format_version = SNAPSHOT_FORMAT_VERSION + 1;
+		assert!(MatchingEngineState::from_snapshot(&snapshot).is_err());
+	}
+
+	#[test]
+	fn json_snapshots_still_load() {
+		let state = state_with_orders();
+		let state_data = serde_json::to_vec(&serde_json::json!({
+			"orderbook": state.orderbook,
+			"ledger": state.ledger,
+			"exposure": state.exposure,
+			"client_orders": state.client_orders,
+		}))
+		.unwrap();
+		let metadata: SnapshotMetadata = serde_json::from_value(serde_json::json!({
+			"created_at": 0,
+			"event_seq": 9,
+			"size_bytes": state_data.len(),
+			"market": "BTC-USDT",
+		}))
+		.unwrap();
+
+		let mut restored = MatchingEngineState::new("BTC-USDT".to_string());
+		restored
+			.restore(MatchingEngineState::read_snapshot(&metadata, state_data.as_slice()).unwrap());
+		assert_eq!(order_ids(&restored), ["b2", "b3", "b1", "a1"]);
+		assert_eq!(restored.client_orders.order_id("alice", "c_a1"), Some("a1"));
+
+		// Bare order books predate the derived state
+		let bare = serde_json::to_vec(&state.orderbook).unwrap();
+		let snapshot = Snapshot {
+			metadata: SnapshotMetadata {
+				checksum: Some(checksum(&bare)),
+				..metadata
+			},
+			state_data: bare,
+		};
+		let restored = MatchingEngineState::from_snapshot(&snapshot).unwrap();
+		assert_eq!(order_ids(&restored), ["b2", "b3", "b1", "a1"]);
+		assert!(restored.ledger.is_none());
+	}
 }
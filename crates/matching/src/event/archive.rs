@@ -0,0 +1,294 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+	fs::{self, File},
+	io::{self, BufRead, BufReader, Write},
+	path::{Path, PathBuf},
+};
+
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use serde::{Deserialize, Serialize};
+
+use super::{EventBatch, EventStorage, MatchingEvent, SequenceNumber, StorageError};
+
+/// What happens to events once a retained snapshot covers them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventRetention {
+	/// Keep every event in the event log
+	#[default]
+	Keep,
+	/// Move them to a compressed [`EventArchive`]
+	Archive,
+	/// Delete them
+	Delete,
+}
+
+/// Compressed archive of events truncated from the event log
+///
+/// The archive is a directory of gzipped segments, one per truncation,
+/// each holding a run of consecutive events as JSON lines. Segment names
+/// carry their first and last sequence, so replay only opens the segments
+/// it needs. A segment is written to a temporary file and renamed into
+/// place, and events already archived are never written again.
+#[derive(Debug, Clone)]
+pub struct EventArchive {
+	dir: PathBuf,
+}
+
+/// One archived run of events
+struct Segment {
+	first: SequenceNumber,
+	last: SequenceNumber,
+	path: PathBuf,
+}
+
+impl EventArchive {
+	/// Open the archive in `dir`, creating it if needed
+	pub fn open(dir: impl Into<PathBuf>) -> Result<Self, StorageError> {
+		let dir = dir.into();
+		fs::create_dir_all(&dir).map_err(|e| io_error(&dir, e))?;
+		Ok(Self { dir })
+	}
+
+	/// Directory of the archive
+	pub fn dir(&self) -> &Path {
+		&self.dir
+	}
+
+	/// Sequence of the last archived event (0 when empty)
+	pub fn last_sequence(&self) -> Result<SequenceNumber, StorageError> {
+		Ok(self.segments()?.last().map_or(0, |s| s.last))
+	}
+
+	/// Archive `events`, skipping those already archived
+	///
+	/// Returns the number of events written.
+	pub fn append(&self, events: &[MatchingEvent]) -> Result<usize, StorageError> {
+		let archived = self.last_sequence()?;
+		let start = events.partition_point(|e| e.sequence() <= archived);
+		let events = &events[start..];
+		let (Some(first), Some(last)) = (events.first(), events.last()) else {
+			return Ok(0);
+		};
+
+		let name = format!(
+			"events-{:020}-{:020}.jsonl.gz",
+			first.sequence(),
+			last.sequence()
+		);
+		let path = self.dir.join(&name);
+		let tmp = self.dir.join(format!("{}.tmp", name));
+		let written = File::create(&tmp)
+			.and_then(|file| {
+				let mut encoder = GzEncoder::new(file, Compression::default());
+				for event in events {
+					serde_json::to_writer(&mut encoder, event)?;
+					encoder.write_all(b"\n")?;
+				}
+				encoder.finish()?.sync_all()
+			})
+			.and_then(|()| fs::rename(&tmp, &path));
+		if let Err(e) = written {
+			let _ = fs::remove_file(&tmp);
+			return Err(StorageError::WriteFailed(format!(
+				"Failed to archive events to {}: {}",
+				path.display(),
+				e
+			)));
+		}
+		Ok(events.len())
+	}
+
+	/// Archived events with sequence >= `from_seq`, in order
+	pub fn replay_from(
+		&self,
+		from_seq: SequenceNumber,
+	) -> Result<Vec<MatchingEvent>, StorageError> {
+		let mut events = Vec::new();
+		for segment in self.segments()? {
+			if segment.last < from_seq {
+				continue;
+			}
+			let file = File::open(&segment.path).map_err(|e| io_error(&segment.path, e))?;
+			for (index, line) in BufReader::new(GzDecoder::new(file)).lines().enumerate() {
+				let line = line.map_err(|e| io_error(&segment.path, e))?;
+				let event: MatchingEvent = serde_json::from_str(&line).map_err(|e| {
+					StorageError::Corrupted(format!(
+						"{} line {}: {}",
+						segment.path.display(),
+						index + 1,
+						e
+					))
+				})?;
+				if event.sequence() >= from_seq {
+					events.push(event);
+				}
+			}
+		}
+		Ok(events)
+	}
+
+	/// Segments ordered by sequence
+	fn segments(&self) -> Result<Vec<Segment>, StorageError> {
+		let entries = fs::read_dir(&self.dir).map_err(|e| io_error(&self.dir, e))?;
+		let mut segments = Vec::new();
+		for entry in entries {
+			let path = entry.map_err(|e| io_error(&self.dir, e))?.path();
+			let range = path
+				.file_name()
+				.and_then(|name| name.to_str())
+				.and_then(|name| name.strip_prefix("events-"))
+				.and_then(|name| name.strip_suffix(".jsonl.gz"))
+				.and_then(|range| range.split_once('-'))
+				.and_then(|(first, last)| Some((first.parse().ok()?, last.parse().ok()?)));
+			if let Some((first, last)) = range {
+				segments.push(Segment { first, last, path });
+			}
+		}
+		segments.sort_by_key(|s| s.first);
+		Ok(segments)
+	}
+}
+
+/// Event storage that moves truncated events to an [`EventArchive`]
+///
+/// Events are archived before they are removed from the live log, and
+/// replay reads the archive for the part of history that is no longer in
+/// the live log, so readers still see the full history.
+pub struct ArchivedEventStorage {
+	live: Box<dyn EventStorage>,
+	archive: EventArchive,
+}
+
+impl ArchivedEventStorage {
+	pub fn new(live: Box<dyn EventStorage>, archive: EventArchive) -> Self {
+		Self { live, archive }
+	}
+}
+
+impl EventStorage for ArchivedEventStorage {
+	fn append_batch(&mut self, batch: EventBatch) -> Result<SequenceNumber, StorageError> {
+		self.live.append_batch(batch)
+	}
+
+	fn replay_from(&self, from_seq: SequenceNumber) -> Result<Vec<MatchingEvent>, StorageError> {
+		let live = self.live.replay_from(from_seq)?;
+		if live.first().is_some_and(|e| e.sequence() <= from_seq) {
+			return Ok(live);
+		}
+		// Events archived but not yet truncated are in both
+		let live_from = live.first().map_or(SequenceNumber::MAX, |e| e.sequence());
+		let mut events = self.archive.replay_from(from_seq)?;
+		events.retain(|e| e.sequence() < live_from);
+		events.extend(live);
+		Ok(events)
+	}
+
+	fn last_sequence(&self) -> SequenceNumber {
+		self.live.last_sequence()
+	}
+
+	fn event_count(&self) -> usize {
+		self.live.event_count()
+	}
+
+	fn fence(&mut self, epoch: u64) -> Result<(), StorageError> {
+		self.live.fence(epoch)
+	}
+
+	fn truncate_before(&mut self, before_seq: SequenceNumber) -> Result<usize, StorageError> {
+		let before_seq = before_seq.min(self.live.last_sequence());
+		let mut events = self.live.replay_from(0)?;
+		events.retain(|e| e.sequence() < before_seq);
+		self.archive.append(&events)?;
+		self.live.truncate_before(before_seq)
+	}
+}
+
+fn io_error(path: &Path, e: io::Error) -> StorageError {
+	StorageError::ReadFailed(format!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::event::MemoryEventStorage;
+	use anvil_sdk::types::Side;
+
+	fn create_test_event(seq: u64) -> MatchingEvent {
+		MatchingEvent::OrderAccepted {
+			seq,
+			order_id: format!("order_{}", seq),
+			market: "BTC-USDT".to_string(),
+			public_key: "test_key".to_string(),
+			client_order_id: None,
+			side: Side::Buy,
+			price: 50000,
+			size: 1,
+			timestamp: 1000,
+		}
+	}
+
+	fn sequences(events: Vec<MatchingEvent>) -> Vec<u64> {
+		events.iter().map(|e| e.sequence()).collect()
+	}
+
+	#[test]
+	fn truncated_events_are_archived_and_still_replayed() {
+		let dir = std::env::temp_dir().join(format!("anvil-archive-{}", uuid::Uuid::new_v4()));
+		let archive = EventArchive::open(&dir).unwrap();
+		let mut storage =
+			ArchivedEventStorage::new(Box::new(MemoryEventStorage::new()), archive.clone());
+		storage
+			.append_batch(EventBatch::new((2..=6).map(create_test_event).collect()))
+			.unwrap();
+
+		assert_eq!(storage.truncate_before(4).unwrap(), 2);
+		storage
+			.append_batch(EventBatch::new((7..=8).map(create_test_event).collect()))
+			.unwrap();
+		assert_eq!(storage.truncate_before(7).unwrap(), 3);
+		assert_eq!(storage.event_count(), 2);
+		assert_eq!(archive.last_sequence().unwrap(), 6);
+		assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+
+		assert_eq!(sequences(archive.replay_from(5).unwrap()), [5, 6]);
+		assert_eq!(
+			sequences(storage.replay_from(0).unwrap()),
+			[2, 3, 4, 5, 6, 7, 8]
+		);
+		assert_eq!(sequences(storage.replay_from(5).unwrap()), [5, 6, 7, 8]);
+		assert_eq!(sequences(storage.replay_from(8).unwrap()), [8]);
+
+		// Events archived before a crash kept them in the live log are
+		// neither archived nor replayed twice
+		let mut live = MemoryEventStorage::new();
+		live.append_batch(EventBatch::new((5..=9).map(create_test_event).collect()))
+			.unwrap();
+		let mut storage = ArchivedEventStorage::new(Box::new(live), archive.clone());
+		assert_eq!(
+			sequences(storage.replay_from(0).unwrap()),
+			[2, 3, 4, 5, 6, 7, 8, 9]
+		);
+		assert_eq!(storage.truncate_before(9).unwrap(), 4);
+		assert_eq!(
+			sequences(archive.replay_from(0).unwrap()),
+			[2, 3, 4, 5, 6, 7, 8]
+		);
+
+		fs::remove_dir_all(dir).unwrap();
+	}
+}
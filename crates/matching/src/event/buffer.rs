@@ -146,6 +146,8 @@ mod tests {
 			seq,
 			order_id: format!("order_{}", seq),
 			market: "BTC-USDT".to_string(),
+			public_key: "test_key".to_string(),
+			client_order_id: None,
 			side: Side::Buy,
 			price: 50000,
 			size: 1,
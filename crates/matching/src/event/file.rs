@@ -0,0 +1,393 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+	fs::{self, File, OpenOptions},
+	io::{self, Write},
+	path::{Path, PathBuf},
+};
+
+use tracing::warn;
+
+use super::{EventBatch, EventStorage, MatchingEvent, SequenceNumber, StorageError};
+
+/// Name of the log file in the event log directory
+const LOG_FILE: &str = "events.log";
+
+/// Event storage in an append-only file
+///
+/// Each committed batch is one line of JSON in `events.log` under the log
+/// directory, synced to disk before the commit is acknowledged. Events are
+/// also kept in memory for replay.
+///
+/// The log can be shared by several engine instances, one of which leads at
+/// a time. Loading the log skips every batch whose epoch is older than an
+/// earlier batch's: such a batch was written by a leader that had already
+/// been replaced. A leader taking over calls [`EventStorage::fence`], which
+/// picks up what the previous leader wrote and appends an empty batch at the
+/// new epoch, so that later writes of the previous leader are skipped.
+///
+/// Truncation rewrites the log as a single batch of the remaining events
+/// and replaces the file, so instances holding the log open reopen it when
+/// they fence it.
+pub struct FileEventStorage {
+	path: PathBuf,
+	file: File,
+	/// Length of the log up to the last complete batch
+	len: u64,
+	events: Vec<MatchingEvent>,
+	epoch: u64,
+}
+
+impl FileEventStorage {
+	/// Open the event log in `dir`, creating it if needed
+	pub fn open(dir: impl AsRef<Path>) -> Result<Self, StorageError> {
+		let dir = dir.as_ref();
+		fs::create_dir_all(dir).map_err(|e| read_error(dir, e))?;
+		let path = dir.join(LOG_FILE);
+		let file = open_log(&path)?;
+		let log = load(&path)?;
+
+		Ok(Self {
+			path,
+			file,
+			len: log.len,
+			events: log.events,
+			epoch: log.epoch,
+		})
+	}
+
+	/// Path of the log file
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	/// Highest epoch of the batches in the log
+	pub fn epoch(&self) -> u64 {
+		self.epoch
+	}
+
+	/// Append one batch as a line, leaving the log as it was on failure
+	fn write_batch(&mut self, batch: &EventBatch) -> Result<(), StorageError> {
+		let mut line = serde_json::to_vec(batch)
+			.map_err(|e| StorageError::WriteFailed(format!("Failed to encode batch: {}", e)))?;
+		line.push(b'\n');
+
+		let written = self
+			.file
+			.write_all(&line)
+			.and_then(|()| self.file.sync_data());
+		if let Err(e) = written {
+			// Drop a partial line so that later batches stay readable
+			let _ = self.file.set_len(self.len);
+			return Err(StorageError::WriteFailed(format!(
+				"Failed to write {}: {}",
+				self.path.display(),
+				e
+			)));
+		}
+		self.len += line.len() as u64;
+		Ok(())
+	}
+}
+
+impl EventStorage for FileEventStorage {
+	fn append_batch(&mut self, batch: EventBatch) -> Result<SequenceNumber, StorageError> {
+		if batch.epoch < self.epoch {
+			return Err(StorageError::StaleEpoch {
+				epoch: batch.epoch,
+				current: self.epoch,
+			});
+		}
+		if batch.is_empty() {
+			return Ok(self.last_sequence());
+		}
+
+		self.write_batch(&batch)?;
+		self.epoch = batch.epoch;
+		self.events.extend(batch.events);
+		Ok(self.last_sequence())
+	}
+
+	fn replay_from(&self, from_seq: SequenceNumber) -> Result<Vec<MatchingEvent>, StorageError> {
+		let start = self.events.partition_point(|e| e.sequence() < from_seq);
+		Ok(self.events[start..].to_vec())
+	}
+
+	fn last_sequence(&self) -> SequenceNumber {
+		self.events.last().map_or(0, |e| e.sequence())
+	}
+
+	fn event_count(&self) -> usize {
+		self.events.len()
+	}
+
+	fn fence(&mut self, epoch: u64) -> Result<(), StorageError> {
+		let log = load(&self.path)?;
+		if epoch < log.epoch {
+			return Err(StorageError::StaleEpoch {
+				epoch,
+				current: log.epoch,
+			});
+		}
+		// The log may have been replaced by a truncation since it was opened
+		self.file = open_log(&self.path)?;
+		// A previous leader may have left a torn batch at the end
+		self.file
+			.set_len(log.len)
+			.map_err(|e| StorageError::WriteFailed(format!("Failed to truncate log: {}", e)))?;
+		self.len = log.len;
+		self.events = log.events;
+		self.epoch = log.epoch;
+
+		self.write_batch(&EventBatch::new(Vec::new()).with_epoch(epoch))?;
+		self.epoch = epoch;
+		Ok(())
+	}
+
+	fn truncate_before(&mut self, before_seq: SequenceNumber) -> Result<usize, StorageError> {
+		let before_seq = before_seq.min(self.last_sequence());
+		let end = self.events.partition_point(|e| e.sequence() < before_seq);
+		if end == 0 {
+			return Ok(0);
+		}
+		// Do not replace a log that a newer leader has taken over
+		let log = load(&self.path)?;
+		if log.epoch > self.epoch {
+			return Err(StorageError::StaleEpoch {
+				epoch: self.epoch,
+				current: log.epoch,
+			});
+		}
+
+		let batch = EventBatch::new(self.events[end..].to_vec()).with_epoch(self.epoch);
+		let mut line = serde_json::to_vec(&batch)
+			.map_err(|e| StorageError::WriteFailed(format!("Failed to encode batch: {}", e)))?;
+		line.push(b'\n');
+		let tmp = self.path.with_extension("log.tmp");
+		let replaced = File::create(&tmp)
+			.and_then(|mut file| {
+				file.write_all(&line)?;
+				file.sync_all()
+			})
+			.and_then(|()| fs::rename(&tmp, &self.path));
+		if let Err(e) = replaced {
+			let _ = fs::remove_file(&tmp);
+			return Err(StorageError::WriteFailed(format!(
+				"Failed to rewrite {}: {}",
+				self.path.display(),
+				e
+			)));
+		}
+
+		self.file = open_log(&self.path)?;
+		self.len = line.len() as u64;
+		self.events.drain(..end);
+		Ok(end)
+	}
+}
+
+fn open_log(path: &Path) -> Result<File, StorageError> {
+	OpenOptions::new()
+		.create(true)
+		.append(true)
+		.open(path)
+		.map_err(|e| read_error(path, e))
+}
+
+/// Contents of a log file
+struct LoadedLog {
+	events: Vec<MatchingEvent>,
+	epoch: u64,
+	/// Length up to the end of the last complete line
+	len: u64,
+}
+
+fn load(path: &Path) -> Result<LoadedLog, StorageError> {
+	let contents = match fs::read(path) {
+		Ok(contents) => contents,
+		Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+		Err(e) => return Err(read_error(path, e)),
+	};
+
+	// A line without its newline is a batch whose write did not finish
+	let len = contents
+		.iter()
+		.rposition(|&b| b == b'\n')
+		.map_or(0, |end| end + 1);
+	if len < contents.len() {
+		warn!(
+			target: "event_storage",
+			path = %path.display(),
+			bytes = contents.len() - len,
+			"Ignoring incomplete batch at the end of the event log"
+		);
+	}
+
+	let mut log = LoadedLog {
+		events: Vec::new(),
+		epoch: 0,
+		len: len as u64,
+	};
+	for (index, line) in contents[..len].split(|&b| b == b'\n').enumerate() {
+		if line.is_empty() {
+			continue;
+		}
+		let batch: EventBatch = serde_json::from_slice(line).map_err(|e| {
+			StorageError::Corrupted(format!("{} line {}: {}", path.display(), index + 1, e))
+		})?;
+		if batch.epoch < log.epoch {
+			warn!(
+				target: "event_storage",
+				path = %path.display(),
+				line = index + 1,
+				epoch = batch.epoch,
+				current_epoch = log.epoch,
+				events = batch.events.len(),
+				"Skipping batch written by a replaced leader"
+			);
+			continue;
+		}
+		log.epoch = batch.epoch;
+		log.events.extend(batch.events);
+	}
+	Ok(log)
+}
+
+fn read_error(path: &Path, e: io::Error) -> StorageError {
+	StorageError::ReadFailed(format!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anvil_sdk::types::Side;
+
+	fn create_test_event(seq: u64) -> MatchingEvent {
+		MatchingEvent::OrderAccepted {
+			seq,
+			order_id: format!("order_{}", seq),
+			market: "BTC-USDT".to_string(),
+			public_key: "test_key".to_string(),
+			client_order_id: None,
+			side: Side::Buy,
+			price: 50000,
+			size: 1,
+			timestamp: 1000,
+		}
+	}
+
+	fn batch(seqs: std::ops::RangeInclusive<u64>, epoch: u64) -> EventBatch {
+		EventBatch::new(seqs.map(create_test_event).collect()).with_epoch(epoch)
+	}
+
+	fn sequences(storage: &FileEventStorage) -> Vec<u64> {
+		storage
+			.replay_from(1)
+			.unwrap()
+			.iter()
+			.map(|e| e.sequence())
+			.collect()
+	}
+
+	#[test]
+	fn survives_reopen_and_ignores_a_torn_batch() {
+		let dir = std::env::temp_dir().join(format!("anvil-events-{}", uuid::Uuid::new_v4()));
+		let mut storage = FileEventStorage::open(&dir).unwrap();
+		assert_eq!(storage.append_batch(batch(2..=3, 0)).unwrap(), 3);
+		assert_eq!(storage.append_batch(batch(4..=4, 0)).unwrap(), 4);
+		drop(storage);
+
+		// A crash in the middle of a write
+		let mut file = OpenOptions::new()
+			.append(true)
+			.open(dir.join(LOG_FILE))
+			.unwrap();
+		file.write_all(b"{\"events\":[").unwrap();
+
+		let mut storage = FileEventStorage::open(&dir).unwrap();
+		assert_eq!(sequences(&storage), [2, 3, 4]);
+		assert_eq!(storage.replay_from(4).unwrap().len(), 1);
+		storage.fence(1).unwrap();
+		storage.append_batch(batch(5..=5, 1)).unwrap();
+		assert_eq!(
+			sequences(&FileEventStorage::open(&dir).unwrap()),
+			[2, 3, 4, 5]
+		);
+
+		fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn skips_writes_of_a_replaced_leader() {
+		let dir = std::env::temp_dir().join(format!("anvil-events-{}", uuid::Uuid::new_v4()));
+		let mut old_leader = FileEventStorage::open(&dir).unwrap();
+		old_leader.fence(1).unwrap();
+		old_leader.append_batch(batch(2..=3, 1)).unwrap();
+
+		// The new leader takes over the events written so far
+		let mut new_leader = FileEventStorage::open(&dir).unwrap();
+		old_leader.append_batch(batch(4..=4, 1)).unwrap();
+		new_leader.fence(2).unwrap();
+		assert_eq!(sequences(&new_leader), [2, 3, 4]);
+		assert!(matches!(
+			new_leader.append_batch(batch(5..=5, 1)),
+			Err(StorageError::StaleEpoch { .. })
+		));
+
+		// The old leader does not know yet, but recovery skips its batch
+		old_leader.append_batch(batch(5..=5, 1)).unwrap();
+		new_leader.append_batch(batch(5..=6, 2)).unwrap();
+		let recovered = FileEventStorage::open(&dir).unwrap();
+		assert_eq!(recovered.epoch(), 2);
+		assert_eq!(sequences(&recovered), [2, 3, 4, 5, 6]);
+		assert!(matches!(
+			FileEventStorage::open(&dir).unwrap().fence(1),
+			Err(StorageError::StaleEpoch { .. })
+		));
+
+		fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn truncation_rewrites_the_log() {
+		let dir = std::env::temp_dir().join(format!("anvil-events-{}", uuid::Uuid::new_v4()));
+		let mut leader = FileEventStorage::open(&dir).unwrap();
+		leader.fence(1).unwrap();
+		leader.append_batch(batch(2..=4, 1)).unwrap();
+		leader.append_batch(batch(5..=6, 1)).unwrap();
+		let mut standby = FileEventStorage::open(&dir).unwrap();
+
+		assert_eq!(leader.truncate_before(5).unwrap(), 3);
+		assert_eq!(sequences(&leader), [5, 6]);
+		leader.append_batch(batch(7..=7, 1)).unwrap();
+		let reopened = FileEventStorage::open(&dir).unwrap();
+		assert_eq!((reopened.epoch(), sequences(&reopened)), (1, vec![5, 6, 7]));
+
+		// The standby picks up the rewritten log when it takes over
+		standby.fence(2).unwrap();
+		assert_eq!(sequences(&standby), [5, 6, 7]);
+		standby.append_batch(batch(8..=8, 2)).unwrap();
+		assert!(matches!(
+			leader.truncate_before(7),
+			Err(StorageError::StaleEpoch { .. })
+		));
+		assert_eq!(
+			sequences(&FileEventStorage::open(&dir).unwrap()),
+			[5, 6, 7, 8]
+		);
+
+		fs::remove_dir_all(dir).unwrap();
+	}
+}
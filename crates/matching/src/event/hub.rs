@@ -0,0 +1,299 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::{broadcast, mpsc};
+use tracing::{debug, warn};
+
+use super::{
+	CommittedEventSink, EventStorage, MatchingEvent, SequenceNumber, SharedEventStorage,
+	StorageError,
+};
+
+/// Error types for event subscriptions
+#[derive(Debug, Error)]
+pub enum SubscriptionError {
+	#[error(transparent)]
+	Storage(#[from] StorageError),
+	#[error("Subscriber fell behind the live stream by {0} events")]
+	Lagged(u64),
+}
+
+/// How a subscriber that falls behind the live stream is handled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlowConsumerPolicy {
+	/// Catch up from event storage and continue without gaps
+	#[default]
+	Backfill,
+	/// End the subscription with `SubscriptionError::Lagged`; the client
+	/// resumes from its last sequence
+	Disconnect,
+}
+
+/// Event hub configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventHubConfig {
+	/// Live events buffered per subscriber before it counts as lagging
+	pub capacity: usize,
+	/// Events queued between a subscription task and its consumer
+	pub subscriber_buffer: usize,
+	/// Handling of subscribers that exceed `capacity`
+	pub slow_consumer: SlowConsumerPolicy,
+}
+
+impl Default for EventHubConfig {
+	fn default() -> Self {
+		Self {
+			capacity: 4096,
+			subscriber_buffer: 128,
+			slow_consumer: SlowConsumerPolicy::Backfill,
+		}
+	}
+}
+
+/// Fan-out of committed events to asynchronous subscribers
+///
+/// Registered as a [`CommittedEventSink`], the hub broadcasts every committed
+/// event. Subscribers combine that live stream with `EventStorage::replay_from`
+/// to start from any sequence and (by default) to backfill whenever they fall
+/// behind, so the events they receive are gap-free and in sequence order.
+/// Each subscription is independent.
+#[derive(Clone)]
+pub struct EventHub {
+	tx: broadcast::Sender<MatchingEvent>,
+	storage: SharedEventStorage,
+	config: EventHubConfig,
+}
+
+impl EventHub {
+	/// Create a hub serving history from `storage`
+	pub fn new(storage: SharedEventStorage, config: EventHubConfig) -> Self {
+		Self {
+			tx: broadcast::channel(config.capacity.max(1)).0,
+			storage,
+			config,
+		}
+	}
+
+	/// Sequence of the last committed event
+	pub fn last_sequence(&self) -> SequenceNumber {
+		self.storage.last_sequence()
+	}
+
+	/// Stream committed events starting at `from_seq`
+	///
+	/// `from_seq == 0` starts with the next event committed after this call.
+	/// Otherwise committed history from `from_seq` is replayed first. The
+	/// stream ends after an error, which is delivered as the last item.
+	///
+	/// Must be called from within a Tokio runtime.
+	pub fn subscribe_from(
+		&self,
+		from_seq: SequenceNumber,
+	) -> mpsc::Receiver<Result<MatchingEvent, SubscriptionError>> {
+		// Subscribe before reading storage so nothing committed in between is
+		// missed; duplicates are dropped by sequence below
+		let mut live = self.tx.subscribe();
+		let storage = self.storage.clone();
+		let policy = self.config.slow_consumer;
+		let (tx, rx) = mpsc::channel(self.config.subscriber_buffer.max(1));
+
+		// Sequence of the last event delivered to the subscriber
+		let mut last = if from_seq == 0 {
+			storage.last_sequence()
+		} else {
+			from_seq - 1
+		};
+
+		tokio::spawn(async move {
+			if from_seq > 0 && !Self::backfill(&storage, &tx, &mut last, None).await {
+				return;
+			}
+
+			loop {
+				let event = match live.recv().await {
+					Ok(event) => event,
+					Err(broadcast::error::RecvError::Lagged(skipped)) => match policy {
+						SlowConsumerPolicy::Backfill => {
+							// The next live event reveals the gap and triggers a backfill
+							debug!(target: "event_hub", skipped, "Subscriber lagged");
+							continue;
+						}
+						SlowConsumerPolicy::Disconnect => {
+							warn!(target: "event_hub", skipped, last, "Disconnecting slow subscriber");
+							let _ = tx.send(Err(SubscriptionError::Lagged(skipped))).await;
+							return;
+						}
+					},
+					Err(broadcast::error::RecvError::Closed) => return,
+				};
+
+				let seq = event.sequence();
+				if seq > last + 1 && !Self::backfill(&storage, &tx, &mut last, Some(seq)).await {
+					return;
+				}
+				if seq <= last {
+					continue;
+				}
+				if tx.send(Ok(event)).await.is_err() {
+					return;
+				}
+				last = seq;
+			}
+		});
+
+		rx
+	}
+
+	/// Deliver stored events after `last` (and before `until`, if given)
+	///
+	/// Returns false when the subscriber is gone or storage failed.
+	async fn backfill(
+		storage: &SharedEventStorage,
+		tx: &mpsc::Sender<Result<MatchingEvent, SubscriptionError>>,
+		last: &mut SequenceNumber,
+		until: Option<SequenceNumber>,
+	) -> bool {
+		let from = *last + 1;
+		let reader = storage.clone();
+		let events = match tokio::task::spawn_blocking(move || reader.replay_from(from)).await {
+			Ok(Ok(events)) => events,
+			Ok(Err(e)) => {
+				let _ = tx.send(Err(e.into())).await;
+				return false;
+			}
+			Err(e) => {
+				let _ = tx
+					.send(Err(StorageError::ReadFailed(e.to_string()).into()))
+					.await;
+				return false;
+			}
+		};
+
+		for event in events {
+			let seq = event.sequence();
+			if until.is_some_and(|until| seq >= until) {
+				break;
+			}
+			if seq <= *last {
+				continue;
+			}
+			if tx.send(Ok(event)).await.is_err() {
+				return false;
+			}
+			*last = seq;
+		}
+		true
+	}
+}
+
+impl CommittedEventSink for EventHub {
+	fn on_committed(&mut self, events: &[MatchingEvent]) {
+		for event in events {
+			// No subscribers is fine; history stays available in storage
+			let _ = self.tx.send(event.clone());
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use anvil_sdk::types::Side;
+
+	use super::*;
+	use crate::event::{EventBatch, MemoryEventStorage};
+
+	fn accepted(seq: SequenceNumber) -> MatchingEvent {
+		MatchingEvent::OrderAccepted {
+			seq,
+			order_id: format!("order_{}", seq),
+			market: "BTC-USDT".to_string(),
+			public_key: "test_key".to_string(),
+			client_order_id: None,
+			side: Side::Buy,
+			price: 100,
+			size: 1,
+			timestamp: 0,
+		}
+	}
+
+	fn commit(storage: &mut SharedEventStorage, hub: &mut EventHub, seqs: &[SequenceNumber]) {
+		let events: Vec<_> = seqs.iter().map(|&seq| accepted(seq)).collect();
+		storage
+			.append_batch(EventBatch::new(events.clone()))
+			.unwrap();
+		hub.on_committed(&events);
+	}
+
+	#[tokio::test]
+	async fn replays_history_then_follows_live_events() {
+		let mut storage = SharedEventStorage::new(Box::new(MemoryEventStorage::new()));
+		let mut hub = EventHub::new(storage.clone(), EventHubConfig::default());
+		commit(&mut storage, &mut hub, &[1, 2, 3]);
+
+		let mut events = hub.subscribe_from(2);
+		commit(&mut storage, &mut hub, &[4]);
+
+		for expected in 2..=4 {
+			let event = events.recv().await.unwrap().unwrap();
+			assert_eq!(event.sequence(), expected);
+		}
+	}
+
+	#[tokio::test]
+	async fn backfills_from_storage_after_lagging() {
+		let mut storage = SharedEventStorage::new(Box::new(MemoryEventStorage::new()));
+		let config = EventHubConfig {
+			capacity: 2,
+			..Default::default()
+		};
+		let mut hub = EventHub::new(storage.clone(), config);
+
+		let mut events = hub.subscribe_from(0);
+		commit(&mut storage, &mut hub, &[1, 2, 3, 4, 5, 6]);
+
+		for expected in 1..=6 {
+			let event = events.recv().await.unwrap().unwrap();
+			assert_eq!(event.sequence(), expected);
+		}
+	}
+
+	#[tokio::test]
+	async fn disconnects_slow_subscribers_when_configured() {
+		let mut storage = SharedEventStorage::new(Box::new(MemoryEventStorage::new()));
+		let config = EventHubConfig {
+			capacity: 2,
+			slow_consumer: SlowConsumerPolicy::Disconnect,
+			..Default::default()
+		};
+		let mut hub = EventHub::new(storage.clone(), config);
+
+		let mut slow = hub.subscribe_from(0);
+		commit(&mut storage, &mut hub, &[1, 2, 3, 4, 5, 6]);
+
+		assert!(matches!(
+			slow.recv().await,
+			Some(Err(SubscriptionError::Lagged(_)))
+		));
+		assert!(slow.recv().await.is_none());
+
+		// A new subscriber resumes from storage independently
+		let mut resumed = hub.subscribe_from(5);
+		assert_eq!(resumed.recv().await.unwrap().unwrap().sequence(), 5);
+		assert_eq!(resumed.recv().await.unwrap().unwrap().sequence(), 6);
+	}
+}
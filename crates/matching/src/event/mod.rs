@@ -12,16 +12,59 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod archive;
 mod buffer;
+mod file;
+mod hub;
 mod storage;
 mod writer;
 
 use anvil_sdk::types::{Side, Trade};
 use serde::{Deserialize, Serialize};
 
+use crate::types::BalanceAdjustment;
+
+pub use archive::{ArchivedEventStorage, EventArchive, EventRetention};
 pub use buffer::{EventBuffer, EventConsumer, EventProducer};
-pub use storage::{EventStorage, MemoryEventStorage};
-pub use writer::{EventWriter, EventWriterConfig};
+pub use file::FileEventStorage;
+pub use hub::{EventHub, EventHubConfig, SlowConsumerPolicy, SubscriptionError};
+pub use storage::{EventStorage, MemoryEventStorage, SharedEventStorage, StorageError};
+pub use writer::{CommittedEventSink, EventWriter, EventWriterConfig};
+
+/// Machine-readable reason carried on `OrderRejected`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectReason {
+	/// Rejected for a reason without a dedicated code
+	#[default]
+	Unspecified,
+	/// Owner cannot fund the order from its ledger balance
+	InsufficientBalance,
+	/// Order size exceeds the principal's limit
+	MaxOrderSize,
+	/// Principal already has the maximum number of resting orders
+	MaxOpenOrders,
+	/// Resting notional would exceed the principal's limit
+	MaxRestingNotional,
+	/// Net position would exceed the principal's limit
+	MaxNetPosition,
+	/// Principal already has an open order with the client order ID
+	DuplicateClientOrderId,
+}
+
+impl RejectReason {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			RejectReason::Unspecified => "unspecified",
+			RejectReason::InsufficientBalance => "insufficient_balance",
+			RejectReason::MaxOrderSize => "max_order_size",
+			RejectReason::MaxOpenOrders => "max_open_orders",
+			RejectReason::MaxRestingNotional => "max_resting_notional",
+			RejectReason::MaxNetPosition => "max_net_position",
+			RejectReason::DuplicateClientOrderId => "duplicate_client_order_id",
+		}
+	}
+}
 
 /// Sequence number for event ordering
 ///
@@ -47,6 +90,12 @@ pub enum MatchingEvent {
 		seq: SequenceNumber,
 		order_id: String,
 		market: String,
+		/// Owner of the order (hex-encoded public key)
+		#[serde(default)]
+		public_key: String,
+		/// Client-assigned order ID
+		#[serde(default)]
+		client_order_id: Option<String>,
 		side: Side,
 		price: u64,
 		size: u64,
@@ -58,6 +107,16 @@ pub enum MatchingEvent {
 		seq: SequenceNumber,
 		order_id: String,
 		market: String,
+		/// Owner of the order (hex-encoded public key)
+		#[serde(default)]
+		public_key: String,
+		/// Client-assigned order ID
+		#[serde(default)]
+		client_order_id: Option<String>,
+		/// Machine-readable rejection code
+		#[serde(default)]
+		code: RejectReason,
+		/// Human-readable detail
 		reason: String,
 		timestamp: u64,
 	},
@@ -86,6 +145,12 @@ pub enum MatchingEvent {
 		seq: SequenceNumber,
 		order_id: String,
 		market: String,
+		/// Owner of the order (hex-encoded public key)
+		#[serde(default)]
+		public_key: String,
+		/// Client-assigned order ID
+		#[serde(default)]
+		client_order_id: Option<String>,
 		remaining_size: u64,
 		timestamp: u64,
 	},
@@ -94,6 +159,18 @@ pub enum MatchingEvent {
 	TradeExecuted {
 		seq: SequenceNumber,
 		trade: Trade,
+		/// Owner of the resting (maker) order
+		#[serde(default)]
+		maker_public_key: String,
+		/// Owner of the incoming (taker) order
+		#[serde(default)]
+		taker_public_key: String,
+		/// Client-assigned ID of the maker order
+		#[serde(default)]
+		maker_client_order_id: Option<String>,
+		/// Client-assigned ID of the taker order
+		#[serde(default)]
+		taker_client_order_id: Option<String>,
 		timestamp: u64,
 	},
 
@@ -115,6 +192,57 @@ pub enum MatchingEvent {
 		filled_size: u64,
 		timestamp: u64,
 	},
+
+	/// Cancel request could not be applied (unknown order or wrong owner)
+	CancelRejected {
+		seq: SequenceNumber,
+		order_id: String,
+		market: String,
+		/// Principal that requested the cancel
+		#[serde(default)]
+		public_key: String,
+		/// Client order ID the cancel targeted, if it named one
+		#[serde(default)]
+		client_order_id: Option<String>,
+		reason: String,
+		timestamp: u64,
+	},
+
+	/// Funds were credited to a principal's available balance
+	BalanceDeposited {
+		seq: SequenceNumber,
+		market: String,
+		public_key: String,
+		asset: String,
+		amount: u64,
+		timestamp: u64,
+	},
+
+	/// Funds were debited from a principal's available balance
+	BalanceWithdrawn {
+		seq: SequenceNumber,
+		market: String,
+		public_key: String,
+		asset: String,
+		amount: u64,
+		timestamp: u64,
+	},
+
+	/// Balance adjustment could not be applied (unknown asset, or a debit
+	/// beyond the available balance)
+	BalanceRejected {
+		seq: SequenceNumber,
+		market: String,
+		public_key: String,
+		asset: String,
+		amount: u64,
+		adjustment: BalanceAdjustment,
+		/// Machine-readable rejection code
+		code: RejectReason,
+		/// Human-readable detail
+		reason: String,
+		timestamp: u64,
+	},
 }
 
 impl MatchingEvent {
@@ -129,6 +257,10 @@ impl MatchingEvent {
 			MatchingEvent::TradeExecuted { seq, .. } => *seq,
 			MatchingEvent::MakerOrderPartiallyFilled { seq, .. } => *seq,
 			MatchingEvent::MakerOrderFilled { seq, .. } => *seq,
+			MatchingEvent::CancelRejected { seq, .. } => *seq,
+			MatchingEvent::BalanceDeposited { seq, .. } => *seq,
+			MatchingEvent::BalanceWithdrawn { seq, .. } => *seq,
+			MatchingEvent::BalanceRejected { seq, .. } => *seq,
 		}
 	}
 
@@ -143,6 +275,10 @@ impl MatchingEvent {
 			MatchingEvent::TradeExecuted { .. } => None,
 			MatchingEvent::MakerOrderPartiallyFilled { order_id, .. } => Some(order_id),
 			MatchingEvent::MakerOrderFilled { order_id, .. } => Some(order_id),
+			MatchingEvent::CancelRejected { order_id, .. } => Some(order_id),
+			MatchingEvent::BalanceDeposited { .. } => None,
+			MatchingEvent::BalanceWithdrawn { .. } => None,
+			MatchingEvent::BalanceRejected { .. } => None,
 		}
 	}
 
@@ -157,6 +293,10 @@ impl MatchingEvent {
 			MatchingEvent::TradeExecuted { trade, .. } => &trade.market,
 			MatchingEvent::MakerOrderPartiallyFilled { market, .. } => market,
 			MatchingEvent::MakerOrderFilled { market, .. } => market,
+			MatchingEvent::CancelRejected { market, .. } => market,
+			MatchingEvent::BalanceDeposited { market, .. } => market,
+			MatchingEvent::BalanceWithdrawn { market, .. } => market,
+			MatchingEvent::BalanceRejected { market, .. } => market,
 		}
 	}
 
@@ -176,16 +316,42 @@ impl MatchingEvent {
 				| MatchingEvent::OrderRejected { .. }
 		)
 	}
+
+	/// Check if this event is the last one emitted for an ingress command
+	///
+	/// Every command produces exactly one final event:
+	/// - Submit: OrderFilled, OrderAccepted (resting remainder) or OrderRejected
+	/// - Cancel: OrderCancelled or CancelRejected
+	/// - Balance adjustment: BalanceDeposited, BalanceWithdrawn or
+	///   BalanceRejected
+	pub fn ends_command(&self) -> bool {
+		matches!(
+			self,
+			MatchingEvent::OrderFilled { .. }
+				| MatchingEvent::OrderAccepted { .. }
+				| MatchingEvent::OrderRejected { .. }
+				| MatchingEvent::OrderCancelled { .. }
+				| MatchingEvent::CancelRejected { .. }
+				| MatchingEvent::BalanceDeposited { .. }
+				| MatchingEvent::BalanceWithdrawn { .. }
+				| MatchingEvent::BalanceRejected { .. }
+		)
+	}
 }
 
 /// Batch of events for efficient processing
 ///
 /// Events are typically processed in batches to reduce overhead
 /// and improve throughput.
+///
+/// A batch carries the fencing epoch of the leader that wrote it, so that
+/// storage can reject batches from a leader that has been replaced.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EventBatch {
 	pub events: Vec<MatchingEvent>,
 	pub batch_timestamp: u64,
+	#[serde(default)]
+	pub epoch: u64,
 }
 
 impl EventBatch {
@@ -198,9 +364,16 @@ impl EventBatch {
 		Self {
 			events,
 			batch_timestamp,
+			epoch: 0,
 		}
 	}
 
+	/// Stamp the batch with the writer's fencing epoch
+	pub fn with_epoch(mut self, epoch: u64) -> Self {
+		self.epoch = epoch;
+		self
+	}
+
 	pub fn is_empty(&self) -> bool {
 		self.events.is_empty()
 	}
@@ -27,6 +27,10 @@ pub enum StorageError {
 	ReadFailed(String),
 	#[error("Storage corrupted: {0}")]
 	Corrupted(String),
+	#[error("Batch epoch {epoch} is older than the storage epoch {current}")]
+	StaleEpoch { epoch: u64, current: u64 },
+	#[error("Not the leader: {0}")]
+	NotLeader(String),
 }
 
 /// Event Storage trait - persistence layer for matching events
@@ -38,6 +42,10 @@ pub enum StorageError {
 /// - Append-only: events are never modified after writing
 /// - Ordered: events can be replayed in sequence order
 /// - Durable: events survive crashes (implementation-dependent)
+/// - Fenced: a batch whose epoch is older than one already accepted is
+///   rejected, so a replaced leader cannot append
+/// - Compactable: events covered by a retained snapshot can be truncated
+///   (see [`EventArchive`](super::EventArchive) to keep them)
 ///
 /// This abstraction allows different backing stores:
 /// - In-memory Vec (MVP, testing)
@@ -61,6 +69,18 @@ pub trait EventStorage: Send {
 
 	/// Get total count of stored events
 	fn event_count(&self) -> usize;
+
+	/// Take over the storage at `epoch`
+	///
+	/// Picks up events other writers committed, then rejects batches with an
+	/// older epoch from now on.
+	fn fence(&mut self, epoch: u64) -> Result<(), StorageError>;
+
+	/// Remove the events with a sequence lower than `before_seq`
+	///
+	/// The last event is always kept, so that `last_sequence` carries over.
+	/// Returns the number of events removed.
+	fn truncate_before(&mut self, before_seq: SequenceNumber) -> Result<usize, StorageError>;
 }
 
 /// In-memory event storage for MVP
@@ -75,6 +95,7 @@ pub trait EventStorage: Send {
 pub struct MemoryEventStorage {
 	events: Arc<Mutex<Vec<MatchingEvent>>>,
 	last_seq: Arc<Mutex<SequenceNumber>>,
+	epoch: u64,
 }
 
 impl MemoryEventStorage {
@@ -82,6 +103,7 @@ impl MemoryEventStorage {
 		Self {
 			events: Arc::new(Mutex::new(Vec::new())),
 			last_seq: Arc::new(Mutex::new(0)),
+			epoch: 0,
 		}
 	}
 
@@ -101,6 +123,13 @@ impl Default for MemoryEventStorage {
 
 impl EventStorage for MemoryEventStorage {
 	fn append_batch(&mut self, batch: EventBatch) -> Result<SequenceNumber, StorageError> {
+		if batch.epoch < self.epoch {
+			return Err(StorageError::StaleEpoch {
+				epoch: batch.epoch,
+				current: self.epoch,
+			});
+		}
+		self.epoch = batch.epoch;
 		if batch.is_empty() {
 			return Ok(self.last_sequence());
 		}
@@ -132,6 +161,62 @@ impl EventStorage for MemoryEventStorage {
 	fn event_count(&self) -> usize {
 		self.events.lock().unwrap().len()
 	}
+
+	fn fence(&mut self, epoch: u64) -> Result<(), StorageError> {
+		self.epoch = self.epoch.max(epoch);
+		Ok(())
+	}
+
+	fn truncate_before(&mut self, before_seq: SequenceNumber) -> Result<usize, StorageError> {
+		let mut events = self.events.lock().unwrap();
+		let before_seq = before_seq.min(*self.last_seq.lock().unwrap());
+		let end = events.partition_point(|e| e.sequence() < before_seq);
+		events.drain(..end);
+		Ok(end)
+	}
+}
+
+/// Event storage shared between the event writer and readers
+///
+/// The writer owns the append path; readers such as feed subscribers use
+/// `replay_from` to catch up on committed history.
+#[derive(Clone)]
+pub struct SharedEventStorage {
+	inner: Arc<Mutex<Box<dyn EventStorage>>>,
+}
+
+impl SharedEventStorage {
+	pub fn new(storage: Box<dyn EventStorage>) -> Self {
+		Self {
+			inner: Arc::new(Mutex::new(storage)),
+		}
+	}
+}
+
+impl EventStorage for SharedEventStorage {
+	fn append_batch(&mut self, batch: EventBatch) -> Result<SequenceNumber, StorageError> {
+		self.inner.lock().unwrap().append_batch(batch)
+	}
+
+	fn replay_from(&self, from_seq: SequenceNumber) -> Result<Vec<MatchingEvent>, StorageError> {
+		self.inner.lock().unwrap().replay_from(from_seq)
+	}
+
+	fn last_sequence(&self) -> SequenceNumber {
+		self.inner.lock().unwrap().last_sequence()
+	}
+
+	fn event_count(&self) -> usize {
+		self.inner.lock().unwrap().event_count()
+	}
+
+	fn fence(&mut self, epoch: u64) -> Result<(), StorageError> {
+		self.inner.lock().unwrap().fence(epoch)
+	}
+
+	fn truncate_before(&mut self, before_seq: SequenceNumber) -> Result<usize, StorageError> {
+		self.inner.lock().unwrap().truncate_before(before_seq)
+	}
 }
 
 #[cfg(test)]
@@ -144,6 +229,8 @@ mod tests {
 			seq,
 			order_id: format!("order_{}", seq),
 			market: "BTC-USDT".to_string(),
+			public_key: "test_key".to_string(),
+			client_order_id: None,
 			side: Side::Buy,
 			price: 50000,
 			size: 1,
@@ -184,4 +271,39 @@ mod tests {
 			.unwrap();
 		assert_eq!(storage.last_sequence(), 10);
 	}
+
+	#[test]
+	fn rejects_batches_from_an_older_epoch() {
+		let mut storage = MemoryEventStorage::new();
+		storage
+			.append_batch(EventBatch::new(vec![create_test_event(1)]).with_epoch(1))
+			.unwrap();
+		storage.fence(2).unwrap();
+
+		assert!(matches!(
+			storage.append_batch(EventBatch::new(vec![create_test_event(2)]).with_epoch(1)),
+			Err(StorageError::StaleEpoch {
+				epoch: 1,
+				current: 2
+			})
+		));
+		storage
+			.append_batch(EventBatch::new(vec![create_test_event(2)]).with_epoch(2))
+			.unwrap();
+		assert_eq!(storage.last_sequence(), 2);
+	}
+
+	#[test]
+	fn truncation_keeps_the_last_event() {
+		let mut storage = MemoryEventStorage::new();
+		storage
+			.append_batch(EventBatch::new((2..=5).map(create_test_event).collect()))
+			.unwrap();
+
+		assert_eq!(storage.truncate_before(4).unwrap(), 2);
+		assert_eq!(storage.replay_from(0).unwrap().len(), 2);
+		assert_eq!(storage.truncate_before(10).unwrap(), 1);
+		assert_eq!(storage.replay_from(0).unwrap()[0].sequence(), 5);
+		assert_eq!(storage.last_sequence(), 5);
+	}
 }
@@ -48,6 +48,16 @@ impl Default for EventWriterConfig {
 	}
 }
 
+/// Consumer of durably committed events
+///
+/// Sinks are invoked on the writer thread after each successful commit, in
+/// sequence order. They must not block for long: slow sinks delay
+/// persistence of subsequent batches.
+pub trait CommittedEventSink: Send {
+	/// Called with each batch of events after it has been committed
+	fn on_committed(&mut self, events: &[MatchingEvent]);
+}
+
 /// Event Writer - consumes events from buffer and persists them
 ///
 /// The Event Writer runs in a separate thread, consuming events produced
@@ -79,10 +89,24 @@ impl EventWriter {
 	/// After successfully committing events, the writer releases idempotency keys
 	/// by calling mark_completed on the journal for all completed orders.
 	pub fn start(
+		consumer: EventConsumer,
+		storage: Box<dyn EventStorage>,
+		journal: Arc<Mutex<Box<dyn OrderJournal>>>,
+		config: EventWriterConfig,
+	) -> Self {
+		Self::start_with_sinks(consumer, storage, journal, config, Vec::new())
+	}
+
+	/// Start the event writer and feed every committed batch to `sinks`
+	///
+	/// Sinks see only events that have been durably written, which makes
+	/// them suitable for read models that must agree with replay.
+	pub fn start_with_sinks(
 		consumer: EventConsumer,
 		mut storage: Box<dyn EventStorage>,
 		journal: Arc<Mutex<Box<dyn OrderJournal>>>,
 		config: EventWriterConfig,
+		mut sinks: Vec<Box<dyn CommittedEventSink>>,
 	) -> Self {
 		let shutdown = Arc::new(AtomicBool::new(false));
 		let shutdown_clone = shutdown.clone();
@@ -95,6 +119,7 @@ impl EventWriter {
 					&consumer,
 					storage.as_mut(),
 					&journal,
+					&mut sinks,
 					&config,
 					&shutdown_clone,
 				);
@@ -113,6 +138,7 @@ impl EventWriter {
 		consumer: &EventConsumer,
 		storage: &mut dyn EventStorage,
 		journal: &Arc<Mutex<Box<dyn OrderJournal>>>,
+		sinks: &mut [Box<dyn CommittedEventSink>],
 		config: &EventWriterConfig,
 		shutdown: &Arc<AtomicBool>,
 	) {
@@ -135,6 +161,7 @@ impl EventWriter {
 					} else {
 						// Release idempotency keys for completed orders
 						Self::release_completed_keys(journal, &pending_events);
+						Self::notify_sinks(sinks, &pending_events);
 						info!(
 							target: "event_writer",
 							batch_size = batch_size,
@@ -178,6 +205,7 @@ impl EventWriter {
 
 						// Commit succeeded, now release idempotency keys for completed orders
 						Self::release_completed_keys(journal, &pending_events);
+						Self::notify_sinks(sinks, &pending_events);
 
 						if config.verbose_logging {
 							debug!(
@@ -256,6 +284,13 @@ impl EventWriter {
 		}
 	}
 
+	/// Hand committed events to every registered sink
+	fn notify_sinks(sinks: &mut [Box<dyn CommittedEventSink>], events: &[MatchingEvent]) {
+		for sink in sinks.iter_mut() {
+			sink.on_committed(events);
+		}
+	}
+
 	/// Shutdown the event writer gracefully
 	pub fn shutdown(mut self) {
 		info!(target: "event_writer", "Shutting down event writer");
@@ -291,6 +326,8 @@ mod tests {
 			seq,
 			order_id: format!("order_{}", seq),
 			market: "BTC-USDT".to_string(),
+			public_key: "test_key".to_string(),
+			client_order_id: None,
 			side: Side::Buy,
 			price: 50000,
 			size: 1,
@@ -0,0 +1,466 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Private execution reports
+//!
+//! [`ExecutionReportFilter`] follows the committed event stream and turns
+//! the events concerning one principal's orders into [`ExecutionReport`]s.
+//!
+//! Fill events do not carry the order owner, so the filter learns which
+//! order IDs belong to the principal, and their client order IDs, from
+//! `OrderAccepted` and `TradeExecuted` and forgets them once the order
+//! completes.
+
+use std::collections::HashMap;
+
+use anvil_sdk::types::LiquidityRole;
+
+use crate::event::{MatchingEvent, SequenceNumber};
+
+/// What happened to the order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionKind {
+	/// The order rests on the book
+	New,
+	/// The order was rejected and never entered the book
+	Rejected,
+	/// The order traded
+	Trade,
+	/// The order has traded part of its size
+	PartiallyFilled,
+	/// The order has traded its whole size
+	Filled,
+	/// The order was removed from the book
+	Cancelled,
+	/// A cancel request for the order failed
+	CancelRejected,
+}
+
+/// One update to a principal's order
+///
+/// Fields that do not apply to `kind` are zero or empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionReport {
+	/// Sequence of the event the report was derived from
+	pub sequence: SequenceNumber,
+	pub market: String,
+	pub order_id: String,
+	/// Client-assigned order ID, if the order has one
+	pub client_order_id: Option<String>,
+	pub kind: ExecutionKind,
+	/// Limit price for `New`, trade price for `Trade`
+	pub price: u64,
+	/// Resting size for `New`, trade size for `Trade`
+	pub size: u64,
+	pub filled_size: u64,
+	pub remaining_size: u64,
+	pub trade_id: String,
+	/// Liquidity role for `Trade`
+	pub role: Option<LiquidityRole>,
+	/// Rejection reason for `Rejected` and `CancelRejected`
+	pub reason: String,
+	pub timestamp: u64,
+}
+
+impl ExecutionReport {
+	fn new(
+		sequence: SequenceNumber,
+		market: &str,
+		order_id: &str,
+		client_order_id: Option<&String>,
+		kind: ExecutionKind,
+		timestamp: u64,
+	) -> Self {
+		Self {
+			sequence,
+			market: market.to_string(),
+			order_id: order_id.to_string(),
+			client_order_id: client_order_id.cloned(),
+			kind,
+			price: 0,
+			size: 0,
+			filled_size: 0,
+			remaining_size: 0,
+			trade_id: String::new(),
+			role: None,
+			reason: String::new(),
+			timestamp,
+		}
+	}
+}
+
+/// Derives one principal's execution reports from committed events
+pub struct ExecutionReportFilter {
+	public_key: String,
+	/// Orders of the principal seen on the stream that have not completed,
+	/// with their client order IDs
+	open_orders: HashMap<String, Option<String>>,
+}
+
+impl ExecutionReportFilter {
+	pub fn new(public_key: impl Into<String>) -> Self {
+		Self {
+			public_key: public_key.into(),
+			open_orders: HashMap::new(),
+		}
+	}
+
+	/// Reports for the principal derived from `event`
+	///
+	/// A self-trade yields two reports, one per side.
+	pub fn reports(&mut self, event: &MatchingEvent) -> Vec<ExecutionReport> {
+		let mut reports = Vec::new();
+		match event {
+			MatchingEvent::OrderAccepted {
+				seq,
+				order_id,
+				market,
+				public_key,
+				client_order_id,
+				price,
+				size,
+				timestamp,
+				..
+			} if *public_key == self.public_key => {
+				self.open_orders
+					.insert(order_id.clone(), client_order_id.clone());
+				let mut report = ExecutionReport::new(
+					*seq,
+					market,
+					order_id,
+					client_order_id.as_ref(),
+					ExecutionKind::New,
+					*timestamp,
+				);
+				report.price = *price;
+				report.size = *size;
+				report.remaining_size = *size;
+				reports.push(report);
+			}
+			MatchingEvent::OrderRejected {
+				seq,
+				order_id,
+				market,
+				public_key,
+				client_order_id,
+				code,
+				reason,
+				timestamp,
+			} if *public_key == self.public_key => {
+				let mut report = ExecutionReport::new(
+					*seq,
+					market,
+					order_id,
+					client_order_id.as_ref(),
+					ExecutionKind::Rejected,
+					*timestamp,
+				);
+				report.reason = format!("{}: {}", code.as_str(), reason);
+				reports.push(report);
+			}
+			MatchingEvent::TradeExecuted {
+				seq,
+				trade,
+				maker_public_key,
+				taker_public_key,
+				maker_client_order_id,
+				taker_client_order_id,
+				timestamp,
+			} => {
+				for (owner, order_id, client_order_id, role) in [
+					(
+						maker_public_key,
+						&trade.maker_order_id,
+						maker_client_order_id,
+						LiquidityRole::Maker,
+					),
+					(
+						taker_public_key,
+						&trade.taker_order_id,
+						taker_client_order_id,
+						LiquidityRole::Taker,
+					),
+				] {
+					if *owner != self.public_key {
+						continue;
+					}
+					self.open_orders
+						.insert(order_id.clone(), client_order_id.clone());
+					let mut report = ExecutionReport::new(
+						*seq,
+						&trade.market,
+						order_id,
+						client_order_id.as_ref(),
+						ExecutionKind::Trade,
+						*timestamp,
+					);
+					report.price = trade.price;
+					report.size = trade.size;
+					report.trade_id = trade.trade_id.clone();
+					report.role = Some(role);
+					reports.push(report);
+				}
+			}
+			MatchingEvent::OrderPartiallyFilled {
+				seq,
+				order_id,
+				market,
+				filled_size,
+				remaining_size,
+				timestamp,
+			}
+			| MatchingEvent::MakerOrderPartiallyFilled {
+				seq,
+				order_id,
+				market,
+				filled_size,
+				remaining_size,
+				timestamp,
+			} if self.open_orders.contains_key(order_id) => {
+				let mut report = ExecutionReport::new(
+					*seq,
+					market,
+					order_id,
+					self.open_orders[order_id].as_ref(),
+					ExecutionKind::PartiallyFilled,
+					*timestamp,
+				);
+				report.filled_size = *filled_size;
+				report.remaining_size = *remaining_size;
+				reports.push(report);
+			}
+			MatchingEvent::OrderFilled {
+				seq,
+				order_id,
+				market,
+				filled_size,
+				timestamp,
+			}
+			| MatchingEvent::MakerOrderFilled {
+				seq,
+				order_id,
+				market,
+				filled_size,
+				timestamp,
+			} if self.open_orders.contains_key(order_id) => {
+				let client_order_id = self.open_orders.remove(order_id).flatten();
+				let mut report = ExecutionReport::new(
+					*seq,
+					market,
+					order_id,
+					client_order_id.as_ref(),
+					ExecutionKind::Filled,
+					*timestamp,
+				);
+				report.filled_size = *filled_size;
+				reports.push(report);
+			}
+			MatchingEvent::OrderCancelled {
+				seq,
+				order_id,
+				market,
+				public_key,
+				client_order_id,
+				remaining_size,
+				timestamp,
+			} if *public_key == self.public_key => {
+				self.open_orders.remove(order_id);
+				let mut report = ExecutionReport::new(
+					*seq,
+					market,
+					order_id,
+					client_order_id.as_ref(),
+					ExecutionKind::Cancelled,
+					*timestamp,
+				);
+				report.remaining_size = *remaining_size;
+				reports.push(report);
+			}
+			MatchingEvent::CancelRejected {
+				seq,
+				order_id,
+				market,
+				public_key,
+				client_order_id,
+				reason,
+				timestamp,
+			} if *public_key == self.public_key => {
+				// Cancels by order ID name the client order ID only if the
+				// order is known
+				let client_order_id = client_order_id
+					.as_ref()
+					.or_else(|| self.open_orders.get(order_id)?.as_ref());
+				let mut report = ExecutionReport::new(
+					*seq,
+					market,
+					order_id,
+					client_order_id,
+					ExecutionKind::CancelRejected,
+					*timestamp,
+				);
+				report.reason = reason.clone();
+				reports.push(report);
+			}
+			_ => {}
+		}
+		reports
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anvil_sdk::types::{Side, Trade};
+
+	fn accepted(seq: SequenceNumber, order_id: &str, public_key: &str) -> MatchingEvent {
+		MatchingEvent::OrderAccepted {
+			seq,
+			order_id: order_id.to_string(),
+			market: "BTC-USDT".to_string(),
+			public_key: public_key.to_string(),
+			client_order_id: None,
+			side: Side::Sell,
+			price: 100,
+			size: 5,
+			timestamp: 0,
+		}
+	}
+
+	fn trade(seq: SequenceNumber, maker: &str, taker: &str) -> MatchingEvent {
+		MatchingEvent::TradeExecuted {
+			seq,
+			trade: Trade {
+				trade_id: format!("t{}", seq),
+				market: "BTC-USDT".to_string(),
+				price: 100,
+				size: 2,
+				side: Side::Buy,
+				timestamp: 0,
+				maker_order_id: "m1".to_string(),
+				taker_order_id: "t1".to_string(),
+			},
+			maker_public_key: maker.to_string(),
+			taker_public_key: taker.to_string(),
+			maker_client_order_id: None,
+			taker_client_order_id: None,
+			timestamp: 0,
+		}
+	}
+
+	#[test]
+	fn follows_own_orders_through_fills() {
+		let mut filter = ExecutionReportFilter::new("alice");
+		let events = [
+			accepted(1, "m1", "alice"),
+			accepted(2, "x1", "bob"),
+			trade(3, "alice", "bob"),
+			MatchingEvent::MakerOrderPartiallyFilled {
+				seq: 4,
+				order_id: "m1".to_string(),
+				market: "BTC-USDT".to_string(),
+				filled_size: 2,
+				remaining_size: 3,
+				timestamp: 0,
+			},
+			MatchingEvent::OrderFilled {
+				seq: 5,
+				order_id: "t1".to_string(),
+				market: "BTC-USDT".to_string(),
+				filled_size: 2,
+				timestamp: 0,
+			},
+			MatchingEvent::OrderCancelled {
+				seq: 6,
+				order_id: "m1".to_string(),
+				market: "BTC-USDT".to_string(),
+				public_key: "alice".to_string(),
+				client_order_id: None,
+				remaining_size: 3,
+				timestamp: 0,
+			},
+		];
+
+		let reports: Vec<_> = events.iter().flat_map(|e| filter.reports(e)).collect();
+		let kinds: Vec<_> = reports.iter().map(|r| (r.sequence, r.kind)).collect();
+		assert_eq!(
+			kinds,
+			vec![
+				(1, ExecutionKind::New),
+				(3, ExecutionKind::Trade),
+				(4, ExecutionKind::PartiallyFilled),
+				(6, ExecutionKind::Cancelled),
+			]
+		);
+		assert_eq!(reports[1].role, Some(LiquidityRole::Maker));
+		assert_eq!(reports[1].order_id, "m1");
+		assert!(filter.open_orders.is_empty());
+	}
+
+	#[test]
+	fn self_trade_reports_both_sides() {
+		let mut filter = ExecutionReportFilter::new("alice");
+		let reports = filter.reports(&trade(1, "alice", "alice"));
+		let roles: Vec<_> = reports
+			.iter()
+			.map(|r| (r.order_id.as_str(), r.role))
+			.collect();
+		assert_eq!(
+			roles,
+			vec![
+				("m1", Some(LiquidityRole::Maker)),
+				("t1", Some(LiquidityRole::Taker))
+			]
+		);
+	}
+
+	#[test]
+	fn reports_echo_client_order_ids() {
+		let mut filter = ExecutionReportFilter::new("alice");
+		let mut accepted = accepted(1, "m1", "alice");
+		if let MatchingEvent::OrderAccepted {
+			client_order_id, ..
+		} = &mut accepted
+		{
+			*client_order_id = Some("c1".to_string());
+		}
+		let mut trade = trade(2, "alice", "bob");
+		if let MatchingEvent::TradeExecuted {
+			maker_client_order_id,
+			..
+		} = &mut trade
+		{
+			*maker_client_order_id = Some("c1".to_string());
+		}
+		let events = [
+			accepted,
+			trade,
+			MatchingEvent::MakerOrderPartiallyFilled {
+				seq: 3,
+				order_id: "m1".to_string(),
+				market: "BTC-USDT".to_string(),
+				filled_size: 2,
+				remaining_size: 3,
+				timestamp: 0,
+			},
+		];
+
+		let reports: Vec<_> = events.iter().flat_map(|e| filter.reports(e)).collect();
+		assert_eq!(reports.len(), 3);
+		assert!(
+			reports
+				.iter()
+				.all(|r| r.client_order_id.as_deref() == Some("c1"))
+		);
+	}
+}
@@ -0,0 +1,499 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Trade history
+//!
+//! [`TradeHistory`] is a committed-event sink that keeps the most recent
+//! trades in a [`TradeStore`], indexed by market, time, `trade_id` and
+//! principal, for paginated trade and fill queries.
+
+use std::{
+	collections::{BTreeMap, BTreeSet, HashMap},
+	fmt,
+	ops::Bound,
+	str::FromStr,
+	sync::{Arc, RwLock},
+};
+
+use anvil_sdk::types::{LiquidityRole, Side, Trade};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::event::{CommittedEventSink, EventStorage, MatchingEvent, SequenceNumber, StorageError};
+
+/// Error types for trade history queries
+#[derive(Debug, Error)]
+pub enum HistoryError {
+	#[error("Invalid cursor: {0}")]
+	InvalidCursor(String),
+}
+
+/// Position of a trade in time order, also used as the pagination cursor
+///
+/// Trades are ordered by timestamp, then by event sequence, which keeps the
+/// order total even when the clock does not advance between trades.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TradeCursor {
+	pub timestamp: u64,
+	pub sequence: SequenceNumber,
+}
+
+impl fmt::Display for TradeCursor {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}-{}", self.timestamp, self.sequence)
+	}
+}
+
+impl FromStr for TradeCursor {
+	type Err = HistoryError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let invalid = || HistoryError::InvalidCursor(s.to_string());
+		let (timestamp, sequence) = s.split_once('-').ok_or_else(invalid)?;
+		Ok(Self {
+			timestamp: timestamp.parse().map_err(|_| invalid())?,
+			sequence: sequence.parse().map_err(|_| invalid())?,
+		})
+	}
+}
+
+/// Committed trade with the owners of both orders
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeRecord {
+	pub sequence: SequenceNumber,
+	pub trade: Trade,
+	pub maker_public_key: String,
+	pub taker_public_key: String,
+}
+
+impl TradeRecord {
+	fn cursor(&self) -> TradeCursor {
+		TradeCursor {
+			timestamp: self.trade.timestamp,
+			sequence: self.sequence,
+		}
+	}
+
+	/// The fills of `public_key` in this trade
+	///
+	/// Roles follow order ownership: the owner of `maker_order_id` is the
+	/// maker and the owner of `taker_order_id` the taker. A self-trade
+	/// yields both fills.
+	pub fn fills_for(&self, public_key: &str) -> Vec<Fill> {
+		let trade = &self.trade;
+		let maker_side = match trade.side {
+			Side::Buy => Side::Sell,
+			Side::Sell => Side::Buy,
+		};
+		let mut fills = Vec::new();
+		for (owner, role, order_id, side) in [
+			(
+				&self.taker_public_key,
+				LiquidityRole::Taker,
+				&trade.taker_order_id,
+				trade.side,
+			),
+			(
+				&self.maker_public_key,
+				LiquidityRole::Maker,
+				&trade.maker_order_id,
+				maker_side,
+			),
+		] {
+			if owner == public_key {
+				fills.push(Fill {
+					sequence: self.sequence,
+					trade_id: trade.trade_id.clone(),
+					market: trade.market.clone(),
+					order_id: order_id.clone(),
+					side,
+					price: trade.price,
+					size: trade.size,
+					role,
+					timestamp: trade.timestamp,
+				});
+			}
+		}
+		fills
+	}
+}
+
+/// One principal's side of a trade, in market units
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fill {
+	pub sequence: SequenceNumber,
+	pub trade_id: String,
+	pub market: String,
+	pub order_id: String,
+	pub side: Side,
+	pub price: u64,
+	pub size: u64,
+	pub role: LiquidityRole,
+	pub timestamp: u64,
+}
+
+/// Filter and page position for history queries
+#[derive(Debug, Clone, Default)]
+pub struct HistoryQuery {
+	/// Earliest trade timestamp to include (Unix seconds)
+	pub start_time: Option<u64>,
+	/// Latest trade timestamp to include (Unix seconds)
+	pub end_time: Option<u64>,
+	/// Only return trades older than this cursor
+	pub cursor: Option<TradeCursor>,
+	/// Maximum number of trades in the page
+	pub limit: usize,
+}
+
+/// One page of results, newest first
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+	pub items: Vec<T>,
+	/// Cursor for the next (older) page; `None` on the last page
+	pub next_cursor: Option<TradeCursor>,
+}
+
+/// In-memory trade store holding the latest `capacity` trades
+#[derive(Debug, Clone)]
+pub struct TradeStore {
+	capacity: usize,
+	trades: BTreeMap<TradeCursor, TradeRecord>,
+	by_id: HashMap<String, TradeCursor>,
+	by_market: HashMap<String, BTreeSet<TradeCursor>>,
+	by_principal: HashMap<String, BTreeSet<TradeCursor>>,
+}
+
+impl TradeStore {
+	pub fn new(capacity: usize) -> Self {
+		Self {
+			capacity: capacity.max(1),
+			trades: BTreeMap::new(),
+			by_id: HashMap::new(),
+			by_market: HashMap::new(),
+			by_principal: HashMap::new(),
+		}
+	}
+
+	pub fn len(&self) -> usize {
+		self.trades.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.trades.is_empty()
+	}
+
+	/// Record the trade carried by `event`, if any
+	pub fn apply(&mut self, event: &MatchingEvent) {
+		let MatchingEvent::TradeExecuted {
+			seq,
+			trade,
+			maker_public_key,
+			taker_public_key,
+			..
+		} = event
+		else {
+			return;
+		};
+
+		let record = TradeRecord {
+			sequence: *seq,
+			trade: trade.clone(),
+			maker_public_key: maker_public_key.clone(),
+			taker_public_key: taker_public_key.clone(),
+		};
+		let key = record.cursor();
+		self.by_id.insert(trade.trade_id.clone(), key);
+		self.by_market
+			.entry(trade.market.clone())
+			.or_default()
+			.insert(key);
+		for owner in [maker_public_key, taker_public_key] {
+			self.by_principal
+				.entry(owner.clone())
+				.or_default()
+				.insert(key);
+		}
+		self.trades.insert(key, record);
+
+		while self.trades.len() > self.capacity {
+			self.evict_oldest();
+		}
+	}
+
+	/// Look up a trade by id
+	pub fn get(&self, trade_id: &str) -> Option<&TradeRecord> {
+		self.by_id
+			.get(trade_id)
+			.and_then(|key| self.trades.get(key))
+	}
+
+	/// Trades of `market`, newest first
+	pub fn trades(&self, market: &str, query: &HistoryQuery) -> Page<TradeRecord> {
+		let page = self.page(self.by_market.get(market), query);
+		Page {
+			items: page.items.into_iter().cloned().collect(),
+			next_cursor: page.next_cursor,
+		}
+	}
+
+	/// Fills of `public_key` in `market`, newest first
+	///
+	/// `limit` counts trades, so a page holding self-trades has more fills.
+	pub fn fills(&self, public_key: &str, market: &str, query: &HistoryQuery) -> Page<Fill> {
+		let keys = self.by_principal.get(public_key).map(|keys| {
+			keys.iter()
+				.filter(|key| self.trades[key].trade.market == market)
+				.copied()
+				.collect::<BTreeSet<_>>()
+		});
+		let page = self.page(keys.as_ref(), query);
+		Page {
+			items: page
+				.items
+				.into_iter()
+				.flat_map(|record| record.fills_for(public_key))
+				.collect(),
+			next_cursor: page.next_cursor,
+		}
+	}
+
+	fn page<'a>(
+		&'a self,
+		keys: Option<&BTreeSet<TradeCursor>>,
+		query: &HistoryQuery,
+	) -> Page<&'a TradeRecord> {
+		let Some(keys) = keys else {
+			return Page {
+				items: Vec::new(),
+				next_cursor: None,
+			};
+		};
+
+		let start = TradeCursor {
+			timestamp: query.start_time.unwrap_or(0),
+			sequence: 0,
+		};
+		let end = TradeCursor {
+			timestamp: query.end_time.unwrap_or(u64::MAX),
+			sequence: SequenceNumber::MAX,
+		};
+		let (upper, upper_key) = match query.cursor {
+			Some(cursor) if cursor <= end => (Bound::Excluded(cursor), cursor),
+			_ => (Bound::Included(end), end),
+		};
+		if start > upper_key {
+			return Page {
+				items: Vec::new(),
+				next_cursor: None,
+			};
+		}
+
+		let limit = query.limit.max(1);
+		let mut range = keys.range((Bound::Included(start), upper)).rev();
+		let items: Vec<_> = range
+			.by_ref()
+			.take(limit)
+			.map(|key| &self.trades[key])
+			.collect();
+		let next_cursor = match range.next() {
+			Some(_) => items.last().map(|record| record.cursor()),
+			None => None,
+		};
+		Page { items, next_cursor }
+	}
+
+	fn evict_oldest(&mut self) {
+		let Some((key, record)) = self.trades.pop_first() else {
+			return;
+		};
+		self.by_id.remove(&record.trade.trade_id);
+		Self::remove_key(&mut self.by_market, &record.trade.market, &key);
+		Self::remove_key(&mut self.by_principal, &record.maker_public_key, &key);
+		Self::remove_key(&mut self.by_principal, &record.taker_public_key, &key);
+	}
+
+	fn remove_key(
+		index: &mut HashMap<String, BTreeSet<TradeCursor>>,
+		name: &str,
+		key: &TradeCursor,
+	) {
+		if let Some(keys) = index.get_mut(name) {
+			keys.remove(key);
+			if keys.is_empty() {
+				index.remove(name);
+			}
+		}
+	}
+}
+
+/// Shared, committed-event fed view of the trade store
+#[derive(Clone)]
+pub struct TradeHistory {
+	inner: Arc<RwLock<TradeStore>>,
+}
+
+impl TradeHistory {
+	/// Keep up to `capacity` of the most recent trades
+	pub fn new(capacity: usize) -> Self {
+		Self {
+			inner: Arc::new(RwLock::new(TradeStore::new(capacity))),
+		}
+	}
+
+	/// Rebuild the store by replaying every committed event from `storage`
+	pub fn rebuild(&self, storage: &dyn EventStorage) -> Result<(), StorageError> {
+		let events = storage.replay_from(0)?;
+		let mut store = self.inner.write().unwrap();
+		*store = TradeStore::new(store.capacity);
+		for event in &events {
+			store.apply(event);
+		}
+		Ok(())
+	}
+
+	pub fn get(&self, trade_id: &str) -> Option<TradeRecord> {
+		self.inner.read().unwrap().get(trade_id).cloned()
+	}
+
+	pub fn trades(&self, market: &str, query: &HistoryQuery) -> Page<TradeRecord> {
+		self.inner.read().unwrap().trades(market, query)
+	}
+
+	pub fn fills(&self, public_key: &str, market: &str, query: &HistoryQuery) -> Page<Fill> {
+		self.inner.read().unwrap().fills(public_key, market, query)
+	}
+}
+
+impl CommittedEventSink for TradeHistory {
+	fn on_committed(&mut self, events: &[MatchingEvent]) {
+		let mut store = self.inner.write().unwrap();
+		for event in events {
+			store.apply(event);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn trade(seq: SequenceNumber, timestamp: u64, maker: &str, taker: &str) -> MatchingEvent {
+		MatchingEvent::TradeExecuted {
+			seq,
+			trade: Trade {
+				trade_id: format!("t{}", seq),
+				market: "BTC-USDT".to_string(),
+				price: 100,
+				size: 1,
+				side: Side::Buy,
+				timestamp,
+				maker_order_id: format!("m{}", seq),
+				taker_order_id: format!("k{}", seq),
+			},
+			maker_public_key: maker.to_string(),
+			taker_public_key: taker.to_string(),
+			maker_client_order_id: None,
+			taker_client_order_id: None,
+			timestamp,
+		}
+	}
+
+	fn ids(page: &Page<TradeRecord>) -> Vec<&str> {
+		page.items
+			.iter()
+			.map(|record| record.trade.trade_id.as_str())
+			.collect()
+	}
+
+	#[test]
+	fn paginates_newest_first_within_time_range() {
+		let mut store = TradeStore::new(100);
+		for seq in 1..=5 {
+			store.apply(&trade(seq, 10 * seq, "alice", "bob"));
+		}
+
+		let mut query = HistoryQuery {
+			limit: 2,
+			..Default::default()
+		};
+		let first = store.trades("BTC-USDT", &query);
+		assert_eq!(ids(&first), ["t5", "t4"]);
+
+		query.cursor = first.next_cursor;
+		let second = store.trades("BTC-USDT", &query);
+		assert_eq!(ids(&second), ["t3", "t2"]);
+
+		// The cursor round-trips through its string form
+		query.cursor = Some(second.next_cursor.unwrap().to_string().parse().unwrap());
+		let last = store.trades("BTC-USDT", &query);
+		assert_eq!(ids(&last), ["t1"]);
+		assert!(last.next_cursor.is_none());
+
+		let ranged = store.trades(
+			"BTC-USDT",
+			&HistoryQuery {
+				start_time: Some(20),
+				end_time: Some(40),
+				limit: 10,
+				..Default::default()
+			},
+		);
+		assert_eq!(ids(&ranged), ["t4", "t3", "t2"]);
+		assert!(store.trades("ETH-USDT", &query).items.is_empty());
+		assert_eq!(store.get("t3").unwrap().sequence, 3);
+	}
+
+	#[test]
+	fn fills_carry_the_principals_role() {
+		let mut store = TradeStore::new(100);
+		store.apply(&trade(1, 10, "alice", "bob"));
+		store.apply(&trade(2, 20, "bob", "alice"));
+		store.apply(&trade(3, 30, "carol", "carol"));
+
+		let query = HistoryQuery {
+			limit: 10,
+			..Default::default()
+		};
+		let fills = store.fills("alice", "BTC-USDT", &query).items;
+		assert_eq!(fills.len(), 2);
+		assert_eq!(
+			(fills[0].trade_id.as_str(), fills[0].role, fills[0].side),
+			("t2", LiquidityRole::Taker, Side::Buy)
+		);
+		assert_eq!(
+			(fills[1].order_id.as_str(), fills[1].role, fills[1].side),
+			("m1", LiquidityRole::Maker, Side::Sell)
+		);
+
+		// A self-trade is both the maker and the taker fill
+		assert_eq!(store.fills("carol", "BTC-USDT", &query).items.len(), 2);
+	}
+
+	#[test]
+	fn evicts_oldest_trades_from_every_index() {
+		let mut store = TradeStore::new(2);
+		for seq in 1..=3 {
+			store.apply(&trade(seq, seq, &format!("maker{}", seq), "taker"));
+		}
+
+		assert_eq!(store.len(), 2);
+		assert!(store.get("t1").is_none());
+		let query = HistoryQuery {
+			limit: 10,
+			..Default::default()
+		};
+		assert!(store.fills("maker1", "BTC-USDT", &query).items.is_empty());
+		assert_eq!(store.fills("taker", "BTC-USDT", &query).items.len(), 2);
+	}
+}
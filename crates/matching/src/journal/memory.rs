@@ -12,11 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
+use std::{
+	collections::{HashMap, VecDeque},
+	time::{Duration, Instant},
+};
 
 use super::{JournalError, OrderJournal};
 use crate::types::OrderCommand;
 
+/// Default time a request's `(public_key, nonce)` keeps resolving to its order
+pub const DEFAULT_IDEMPOTENCY_WINDOW: Duration = Duration::from_secs(300);
+
 /// In-memory implementation of Order Journal for MVP
 ///
 /// This implementation provides a lightweight, non-persistent journal
@@ -27,6 +33,7 @@ use crate::types::OrderCommand;
 /// - No fsync or durability guarantees
 /// - Fast append and lookup operations
 /// - Simple HashMap-based storage
+/// - Request keys expire in acceptance order once the window has passed
 /// - Lifecycle: covers only "received -> completed" interval
 ///
 /// Future evolution paths:
@@ -38,13 +45,38 @@ pub struct MemoryOrderJournal {
 	active_orders: HashMap<String, OrderCommand>,
 	/// Completed order IDs for cleanup tracking
 	completed_orders: Vec<String>,
+	/// Accepted order ID and acceptance time per `(public_key, nonce)`
+	requests: HashMap<(String, String), (String, Instant)>,
+	/// Request keys by acceptance time, oldest first
+	request_expiry: VecDeque<(Instant, (String, String))>,
+	/// How long a request key resolves to its order
+	idempotency_window: Duration,
 }
 
 impl MemoryOrderJournal {
 	pub fn new() -> Self {
+		Self::with_idempotency_window(DEFAULT_IDEMPOTENCY_WINDOW)
+	}
+
+	/// Create a journal whose request keys resolve for `idempotency_window`
+	pub fn with_idempotency_window(idempotency_window: Duration) -> Self {
 		Self {
 			active_orders: HashMap::new(),
 			completed_orders: Vec::new(),
+			requests: HashMap::new(),
+			request_expiry: VecDeque::new(),
+			idempotency_window,
+		}
+	}
+
+	/// Forget request keys accepted before the window
+	fn expire_requests(&mut self, now: Instant) {
+		while let Some((accepted_at, _)) = self.request_expiry.front() {
+			if now.duration_since(*accepted_at) < self.idempotency_window {
+				break;
+			}
+			let (_, key) = self.request_expiry.pop_front().unwrap();
+			self.requests.remove(&key);
 		}
 	}
 
@@ -71,6 +103,18 @@ impl OrderJournal for MemoryOrderJournal {
 			return Err(JournalError::DuplicateOrder(order.order_id.clone()));
 		}
 
+		let now = Instant::now();
+		self.expire_requests(now);
+		if !order.nonce.is_empty() {
+			let key = (order.public_key.clone(), order.nonce.clone());
+			if let Some((order_id, _)) = self.requests.get(&key) {
+				return Err(JournalError::DuplicateRequest(order_id.clone()));
+			}
+			self.requests
+				.insert(key.clone(), (order.order_id.clone(), now));
+			self.request_expiry.push_back((now, key));
+		}
+
 		self.active_orders.insert(order.order_id.clone(), order);
 		Ok(())
 	}
@@ -79,6 +123,21 @@ impl OrderJournal for MemoryOrderJournal {
 		self.active_orders.contains_key(order_id)
 	}
 
+	fn get(&self, order_id: &str) -> Option<OrderCommand> {
+		self.active_orders.get(order_id).cloned()
+	}
+
+	fn find_request(&self, public_key: &str, nonce: &str) -> Option<String> {
+		if nonce.is_empty() {
+			return None;
+		}
+		let (order_id, accepted_at) = self
+			.requests
+			.get(&(public_key.to_string(), nonce.to_string()))?;
+		// Keys only expire on append; skip the ones already past the window
+		(accepted_at.elapsed() < self.idempotency_window).then(|| order_id.clone())
+	}
+
 	fn mark_completed(&mut self, order_id: &str) {
 		if self.active_orders.contains_key(order_id) {
 			self.completed_orders.push(order_id.to_string());
@@ -108,6 +167,15 @@ mod tests {
 			size: 1,
 			timestamp: 1000,
 			public_key: "test_key".to_string(),
+			client_order_id: None,
+			nonce: String::new(),
+		}
+	}
+
+	fn create_request(order_id: &str, nonce: &str) -> OrderCommand {
+		OrderCommand {
+			nonce: nonce.to_string(),
+			..create_test_order(order_id, "BTC-USDT")
 		}
 	}
 
@@ -121,6 +189,7 @@ mod tests {
 		journal.append(order.clone()).unwrap();
 		assert!(journal.is_active("order_1"));
 		assert_eq!(journal.active_count(), 1);
+		assert_eq!(journal.get("order_1").map(|o| o.price), Some(50000));
 	}
 
 	#[test]
@@ -135,6 +204,45 @@ mod tests {
 		assert!(matches!(result, Err(JournalError::DuplicateOrder(_))));
 	}
 
+	#[test]
+	fn test_duplicate_request_resolves_to_original_order() {
+		let mut journal = MemoryOrderJournal::new();
+		journal.append(create_request("order_1", "n1")).unwrap();
+
+		assert_eq!(
+			journal.find_request("test_key", "n1"),
+			Some("order_1".to_string())
+		);
+		assert_eq!(journal.find_request("other_key", "n1"), None);
+		assert!(matches!(
+			journal.append(create_request("order_2", "n1")),
+			Err(JournalError::DuplicateRequest(id)) if id == "order_1"
+		));
+
+		// The key outlives the order's lifecycle
+		journal.mark_completed("order_1");
+		journal.compact();
+		assert_eq!(
+			journal.find_request("test_key", "n1"),
+			Some("order_1".to_string())
+		);
+
+		// Orders without a nonce are only deduplicated by order ID
+		journal.append(create_request("order_3", "")).unwrap();
+		journal.append(create_request("order_4", "")).unwrap();
+		assert_eq!(journal.find_request("test_key", ""), None);
+	}
+
+	#[test]
+	fn test_request_keys_expire_after_window() {
+		let mut journal = MemoryOrderJournal::with_idempotency_window(Duration::ZERO);
+		journal.append(create_request("order_1", "n1")).unwrap();
+
+		assert_eq!(journal.find_request("test_key", "n1"), None);
+		journal.append(create_request("order_2", "n1")).unwrap();
+		assert_eq!(journal.requests.len(), 1);
+	}
+
 	#[test]
 	fn test_mark_completed() {
 		let mut journal = MemoryOrderJournal::new();
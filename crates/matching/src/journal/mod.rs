@@ -17,7 +17,7 @@ mod memory;
 use thiserror::Error;
 
 use crate::types::OrderCommand;
-pub use memory::MemoryOrderJournal;
+pub use memory::{DEFAULT_IDEMPOTENCY_WINDOW, MemoryOrderJournal};
 
 /// Error types for Order Journal operations
 #[derive(Debug, Error)]
@@ -26,6 +26,8 @@ pub enum JournalError {
 	AppendFailed(String),
 	#[error("Order already exists: {0}")]
 	DuplicateOrder(String),
+	#[error("Request already accepted as order {0}")]
+	DuplicateRequest(String),
 	#[error("Journal storage error: {0}")]
 	StorageError(String),
 }
@@ -34,7 +36,9 @@ pub enum JournalError {
 ///
 /// The Order Journal records orders that have been accepted by the system
 /// but have not yet completed their lifecycle. Its primary purpose is to:
-/// - Provide an idempotency anchor (prevent duplicate order processing)
+/// - Provide an idempotency anchor (prevent duplicate order processing),
+///   keyed on the request's `(public_key, nonce)` within an idempotency
+///   window and on the order ID while the order is active
 /// - Enable crash recovery by replaying incomplete orders
 /// - Define the semantic boundary between "received" and "completed"
 ///
@@ -50,7 +54,8 @@ pub trait OrderJournal: Send {
 	/// Append an order to the journal
 	///
 	/// This must complete before ACK is sent to the client.
-	/// Returns error if the order_id already exists in active orders.
+	/// Returns error if the order_id already exists in active orders, or if
+	/// the order's `(public_key, nonce)` was accepted within the window.
 	fn append(&mut self, order: OrderCommand) -> Result<(), JournalError>;
 
 	/// Check if an order is still active (incomplete lifecycle)
@@ -59,6 +64,16 @@ pub trait OrderJournal: Send {
 	/// marked complete via mark_completed.
 	fn is_active(&self, order_id: &str) -> bool;
 
+	/// Active order by ID, as appended
+	fn get(&self, order_id: &str) -> Option<OrderCommand>;
+
+	/// Order ID accepted for a principal's request nonce within the
+	/// idempotency window
+	///
+	/// The key outlives the order's lifecycle: a request repeated after its
+	/// order completed still resolves to it until the window passes.
+	fn find_request(&self, public_key: &str, nonce: &str) -> Option<String>;
+
 	/// Mark an order as completed
 	///
 	/// Called after the order's final state has been committed to the State Journal.
@@ -0,0 +1,177 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+	fs::{self, OpenOptions},
+	io,
+	path::{Path, PathBuf},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::{Lease, LeaseBackend, LeaseError};
+
+/// A lock file older than this was left by a crashed process
+const STALE_LOCK: Duration = Duration::from_secs(10);
+
+/// Lease kept in a file, for instances that share a file system
+///
+/// The lease is a JSON document written to a temporary file that is then
+/// renamed over the lease file. Reading and replacing it happens while
+/// holding a lock file created exclusively next to it, so two instances
+/// never both take the lease.
+pub struct FileLease {
+	path: PathBuf,
+	lock_path: PathBuf,
+}
+
+impl FileLease {
+	pub fn new(path: impl Into<PathBuf>) -> Self {
+		let path = path.into();
+		Self {
+			lock_path: path.with_extension("lock"),
+			path,
+		}
+	}
+
+	fn lock(&self) -> Result<LockFile<'_>, LeaseError> {
+		if let Some(parent) = self.path.parent() {
+			fs::create_dir_all(parent)?;
+		}
+		for _ in 0..2 {
+			match OpenOptions::new()
+				.write(true)
+				.create_new(true)
+				.open(&self.lock_path)
+			{
+				Ok(_) => return Ok(LockFile(&self.lock_path)),
+				Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+					let stale = fs::metadata(&self.lock_path)
+						.and_then(|metadata| metadata.modified())
+						.is_ok_and(|modified| {
+							modified.elapsed().is_ok_and(|age| age >= STALE_LOCK)
+						});
+					if !stale {
+						return Err(LeaseError::Busy);
+					}
+					let _ = fs::remove_file(&self.lock_path);
+				}
+				Err(e) => return Err(e.into()),
+			}
+		}
+		Err(LeaseError::Busy)
+	}
+
+	fn read(&self) -> Result<Option<Lease>, LeaseError> {
+		match fs::read(&self.path) {
+			Ok(contents) => serde_json::from_slice(&contents)
+				.map(Some)
+				.map_err(|e| LeaseError::Corrupted(format!("{}: {}", self.path.display(), e))),
+			Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+			Err(e) => Err(e.into()),
+		}
+	}
+
+	fn write(&self, lease: &Lease) -> Result<(), LeaseError> {
+		let contents =
+			serde_json::to_vec(lease).map_err(|e| LeaseError::Corrupted(e.to_string()))?;
+		let tmp = self.path.with_extension("tmp");
+		fs::write(&tmp, contents)?;
+		fs::rename(&tmp, &self.path)?;
+		Ok(())
+	}
+}
+
+impl LeaseBackend for FileLease {
+	fn acquire(&mut self, holder: &str, ttl: Duration) -> Result<Lease, LeaseError> {
+		let _lock = self.lock()?;
+		let now = now_ms();
+		let lease = match self.read()? {
+			Some(lease) if lease.expires_at_ms > now && lease.holder != holder => {
+				return Ok(lease);
+			}
+			Some(lease) if lease.expires_at_ms > now => Lease {
+				expires_at_ms: now + ttl.as_millis() as u64,
+				..lease
+			},
+			current => Lease {
+				holder: holder.to_string(),
+				epoch: current.map_or(0, |lease| lease.epoch) + 1,
+				expires_at_ms: now + ttl.as_millis() as u64,
+			},
+		};
+		self.write(&lease)?;
+		Ok(lease)
+	}
+
+	fn release(&mut self, holder: &str) -> Result<(), LeaseError> {
+		let _lock = self.lock()?;
+		match self.read()? {
+			// Keep the epoch, so that the next holder's is newer
+			Some(lease) if lease.holder == holder => self.write(&Lease {
+				expires_at_ms: 0,
+				..lease
+			}),
+			_ => Ok(()),
+		}
+	}
+}
+
+/// Exclusive lock on a lease file, released on drop
+struct LockFile<'a>(&'a Path);
+
+impl Drop for LockFile<'_> {
+	fn drop(&mut self) {
+		let _ = fs::remove_file(self.0);
+	}
+}
+
+fn now_ms() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map_or(0, |now| now.as_millis() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn one_holder_at_a_time_with_a_new_epoch_per_holder() {
+		let dir = std::env::temp_dir().join(format!("anvil-lease-{}", uuid::Uuid::new_v4()));
+		let path = dir.join("matching.lease");
+		let ttl = Duration::from_secs(60);
+		let mut a = FileLease::new(&path);
+		let mut b = FileLease::new(&path);
+
+		let lease = a.acquire("a", ttl).unwrap();
+		assert_eq!((lease.holder.as_str(), lease.epoch), ("a", 1));
+		assert_eq!(b.acquire("b", ttl).unwrap(), lease);
+		let renewed = a.acquire("a", ttl).unwrap();
+		assert_eq!((renewed.holder.as_str(), renewed.epoch), ("a", 1));
+
+		// Releasing or expiring frees the lease for the next epoch
+		b.release("b").unwrap();
+		a.release("a").unwrap();
+		let lease = b.acquire("b", Duration::ZERO).unwrap();
+		assert_eq!((lease.holder.as_str(), lease.epoch), ("b", 2));
+		let lease = a.acquire("a", ttl).unwrap();
+		assert_eq!((lease.holder.as_str(), lease.epoch), ("a", 3));
+
+		// A lock left behind by a live process keeps others out
+		let _lock = a.lock().unwrap();
+		assert!(matches!(b.acquire("b", ttl), Err(LeaseError::Busy)));
+
+		fs::remove_dir_all(dir).unwrap();
+	}
+}
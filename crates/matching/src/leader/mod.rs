@@ -0,0 +1,248 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Leader election between matching engine instances that share an event log
+//!
+//! Instances of a market start as followers and compete for a lease. The
+//! lease has a holder, an expiry and an epoch that grows with every new
+//! holder. The holder renews the lease every `renew_interval`; an instance
+//! that takes it over:
+//!
+//! 1. fences the event log at the lease's epoch, picking up the events the
+//!    previous leader committed,
+//! 2. applies those events to its engine, and
+//! 3. becomes the primary at the lease's epoch.
+//!
+//! A primary that sees another holder, or cannot renew before its lease
+//! runs out, is fenced and stops accepting orders. Its event writer stamps
+//! batches with its epoch, so anything it still writes to the log is skipped
+//! on recovery. Expiry is judged by each instance's clock: clocks must agree
+//! to well within the lease TTL.
+//!
+//! Leases come from a [`LeaseBackend`]; [`FileLease`] keeps the lease in a
+//! file next to the shared log.
+
+mod file;
+
+use std::{
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::{task::JoinHandle, time::MissedTickBehavior};
+use tracing::{info, warn};
+
+pub use file::FileLease;
+
+use crate::{
+	MatchingEngine,
+	event::{EventStorage, SharedEventStorage},
+	replication::{ReplicationRole, ReplicationState},
+};
+
+/// Leadership of a market's matching engine
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lease {
+	/// Instance holding the lease
+	pub holder: String,
+	/// Fencing epoch of the holder's leadership
+	pub epoch: u64,
+	/// Unix time (milliseconds) from which the lease is free
+	pub expires_at_ms: u64,
+}
+
+/// Error types for lease operations
+#[derive(Debug, Error)]
+pub enum LeaseError {
+	#[error("Lease I/O error: {0}")]
+	Io(#[from] std::io::Error),
+	#[error("Lease corrupted: {0}")]
+	Corrupted(String),
+	#[error("Lease is being updated by another instance")]
+	Busy,
+}
+
+/// Store of a lease that one instance holds at a time
+pub trait LeaseBackend: Send {
+	/// Take the lease for `holder` until `ttl` from now
+	///
+	/// Renews the lease if `holder` has it; takes it at the next epoch if it
+	/// is free or expired. Returns the lease as it stands, which names
+	/// another holder if the lease could not be taken.
+	fn acquire(&mut self, holder: &str, ttl: Duration) -> Result<Lease, LeaseError>;
+
+	/// Give up the lease if `holder` has it
+	fn release(&mut self, holder: &str) -> Result<(), LeaseError>;
+}
+
+/// Leader election settings
+#[derive(Debug, Clone)]
+pub struct LeaderConfig {
+	/// Name of this instance in the lease
+	pub holder: String,
+	/// How long a lease lasts without renewal
+	pub ttl: Duration,
+	/// Interval between attempts to take or renew the lease
+	pub renew_interval: Duration,
+}
+
+impl LeaderConfig {
+	/// Renew three times per `ttl`
+	pub fn new(holder: impl Into<String>, ttl: Duration) -> Self {
+		Self {
+			holder: holder.into(),
+			ttl,
+			renew_interval: ttl / 3,
+		}
+	}
+}
+
+/// Takes and keeps leadership for an engine
+pub struct LeaderElector {
+	backend: Box<dyn LeaseBackend>,
+	engine: Arc<MatchingEngine>,
+	storage: SharedEventStorage,
+	replication: ReplicationState,
+	config: LeaderConfig,
+}
+
+impl LeaderElector {
+	/// Elect through `backend` for `engine`, whose events go to `storage`
+	///
+	/// The engine must start as a follower that has applied the history in
+	/// `storage` (see [`ReplicationState::record_progress`]).
+	pub fn new(
+		backend: Box<dyn LeaseBackend>,
+		engine: Arc<MatchingEngine>,
+		storage: SharedEventStorage,
+		replication: ReplicationState,
+		config: LeaderConfig,
+	) -> Self {
+		Self {
+			backend,
+			engine,
+			storage,
+			replication,
+			config,
+		}
+	}
+
+	pub fn start(self) -> JoinHandle<()> {
+		tokio::spawn(self.run())
+	}
+
+	/// Take or renew the lease every `renew_interval` until fenced
+	async fn run(mut self) {
+		let mut ticker = tokio::time::interval(self.config.renew_interval);
+		ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+		// Until when this instance holds the lease, by its own clock
+		let mut held_until: Option<Instant> = None;
+
+		loop {
+			ticker.tick().await;
+			let requested_at = Instant::now();
+			match self.backend.acquire(&self.config.holder, self.config.ttl) {
+				Ok(lease) if lease.holder == self.config.holder => {
+					held_until = Some(requested_at + self.config.ttl);
+					if self.replication.status().role == ReplicationRole::Follower
+						&& let Err(e) = self.take_over(lease.epoch).await
+					{
+						warn!(
+							target: "leader",
+							epoch = lease.epoch,
+							error = %e,
+							"Failed to take over leadership"
+						);
+						held_until = None;
+						if let Err(e) = self.backend.release(&self.config.holder) {
+							warn!(target: "leader", error = %e, "Failed to release the lease");
+						}
+					}
+				}
+				Ok(lease) => {
+					held_until = None;
+					if self.replication.status().role == ReplicationRole::Primary {
+						warn!(
+							target: "leader",
+							holder = %lease.holder,
+							epoch = lease.epoch,
+							"Lost leadership"
+						);
+						if self.replication.fence(lease.epoch).await.is_err() {
+							self.replication.step_down().await;
+						}
+					}
+				}
+				Err(e) => warn!(target: "leader", error = %e, "Failed to take or renew the lease"),
+			}
+
+			let status = self.replication.status();
+			if status.role == ReplicationRole::Primary
+				&& held_until.is_none_or(|until| Instant::now() >= until)
+			{
+				warn!(
+					target: "leader",
+					epoch = status.epoch,
+					"Lease ran out, stepping down"
+				);
+				self.replication.step_down().await;
+			}
+			if self.replication.status().role == ReplicationRole::Fenced {
+				info!(
+					target: "leader",
+					epoch = self.replication.status().epoch,
+					"Fenced, leaving leader election"
+				);
+				break;
+			}
+		}
+	}
+
+	/// Pick up what the previous leader committed and become the primary
+	async fn take_over(&mut self, epoch: u64) -> Result<(), String> {
+		let mut storage = self.storage.clone();
+		let from_seq = self.replication.applied_sequence() + 1;
+		let events = tokio::task::spawn_blocking(move || {
+			storage.fence(epoch)?;
+			storage.replay_from(from_seq)
+		})
+		.await
+		.map_err(|e| e.to_string())?
+		.map_err(|e| e.to_string())?;
+
+		if !events.is_empty() {
+			info!(
+				target: "leader",
+				from_sequence = from_seq,
+				events = events.len(),
+				"Applying events of the previous leader"
+			);
+			let applied = self.engine.apply_replicated(events).await?;
+			self.replication.record_progress(applied, applied);
+		}
+		self.replication
+			.promote(epoch)
+			.await
+			.map_err(|e| e.to_string())?;
+		info!(
+			target: "leader",
+			epoch,
+			applied_sequence = self.replication.applied_sequence(),
+			"Leading"
+		);
+		Ok(())
+	}
+}
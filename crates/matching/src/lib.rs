@@ -29,27 +29,48 @@ pub mod client;
 pub mod config;
 pub mod engine;
 pub mod event;
+pub mod execution;
+pub mod history;
 pub mod journal;
+pub mod leader;
 pub mod logging;
+pub mod market_data;
 pub mod matcher;
 pub mod orderbook;
+pub mod orders;
 pub mod otel;
 pub mod queue;
 pub mod recovery;
+pub mod replication;
+pub mod risk;
 pub mod server;
+pub mod settlement;
 pub mod snapshot;
 pub mod types;
 
 pub use engine::{EngineConfig, EngineError, MatchingEngine, MatchingEngineState};
 pub use event::{
-	EventBuffer, EventConsumer, EventProducer, EventStorage, EventWriter, EventWriterConfig,
-	MatchingEvent, MemoryEventStorage,
+	ArchivedEventStorage, CommittedEventSink, EventArchive, EventBuffer, EventConsumer, EventHub,
+	EventHubConfig, EventProducer, EventStorage, EventWriter, EventWriterConfig, FileEventStorage,
+	MatchingEvent, MemoryEventStorage, RejectReason, SharedEventStorage,
 };
+pub use execution::{ExecutionKind, ExecutionReport, ExecutionReportFilter};
+pub use history::{TradeHistory, TradeStore};
 pub use journal::{MemoryOrderJournal, OrderJournal};
+pub use leader::{FileLease, LeaderConfig, LeaderElector, LeaseBackend};
+pub use market_data::{MarketDataHandle, MarketDataPublisher};
 #[allow(deprecated)]
 pub use matcher::Matcher;
 pub use orderbook::OrderBook;
+pub use orders::{OrderRecord, OrderStore, OrderView};
 pub use queue::{IngressQueue, QueueReceiver, QueueSender};
 pub use recovery::RecoveryCoordinator;
-pub use snapshot::{MemorySnapshotStorage, SnapshotProvider, Snapshotter, SnapshotterConfig};
+pub use replication::{
+	FencedEventStorage, Follower, FollowerConfig, ReplicationRole, ReplicationState,
+};
+pub use risk::{Ledger, LedgerView, LimitsConfig, RiskConfig, RiskLimits};
+pub use settlement::{SettlementForwarder, SettlementForwarderConfig};
+pub use snapshot::{
+	FileSnapshotStorage, MemorySnapshotStorage, SnapshotProvider, Snapshotter, SnapshotterConfig,
+};
 pub use types::*;
@@ -19,43 +19,106 @@
 //! - Ingress Queue (MPSC from RPC to matching loop)
 //! - Matching Loop (single-threaded core)
 //! - Event Buffer (SPSC from matching loop to event writer)
-//! - Event Writer (persistence)
-//! - Snapshotter (periodic state capture)
+//! - Event Writer (persistence, to a file log with `MATCHING_EVENT_STORAGE_PATH`)
+//! - Market Data Publisher (L2 book updates, candles, ticker)
+//! - Trade History (trade and fill queries)
+//! - Settlement Forwarder (committed trades to settlement, on the primary)
+//! - Follower (replication of a primary's events, with `MATCHING_REPLICATE_FROM`)
+//! - Leader Elector (leadership over a shared event log, with `MATCHING_LEASE_PATH`)
+//! - Snapshotter (periodic state capture, to `MATCHING_SNAPSHOT_PATH`, and
+//!   event log compaction with `MATCHING_EVENT_RETENTION`)
 //! - RPC Server (multi-threaded ingress)
+//! - gRPC Health Service (serving while the matching loop runs)
 
-use std::sync::{Arc, Mutex};
+use std::{
+	io::Write,
+	sync::{Arc, Mutex},
+	time::Duration,
+};
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use tokio::signal;
 use tonic::transport::Server;
-use tracing::info;
+use tracing::{info, warn};
 
+use anvil_matching::server::proto::matching_service_server::MatchingServiceServer;
 use anvil_matching::{
-	EventBuffer, EventWriter, EventWriterConfig, IngressQueue, MatchingEngine, MemoryEventStorage,
-	MemoryOrderJournal, MemorySnapshotStorage, OrderJournal, SnapshotProvider, Snapshotter,
-	SnapshotterConfig, config::MatchingConfig, engine::EngineConfig, server,
+	ArchivedEventStorage, CommittedEventSink, EventArchive, EventBuffer, EventHub, EventStorage,
+	EventWriter, EventWriterConfig, FencedEventStorage, FileEventStorage, FileLease,
+	FileSnapshotStorage, Follower, FollowerConfig, IngressQueue, LeaderConfig, LeaderElector,
+	LedgerView, MarketDataPublisher, MatchingEngine, MatchingEngineState, MemoryEventStorage,
+	MemoryOrderJournal, MemorySnapshotStorage, OrderJournal, OrderView, ReplicationRole,
+	ReplicationState, RiskConfig, SettlementForwarder, SharedEventStorage, SnapshotProvider,
+	Snapshotter, SnapshotterConfig, TradeHistory,
+	client::SettlementGrpcClient,
+	config::MatchingConfig,
+	engine::EngineConfig,
+	event::EventRetention,
+	server::MatchingServiceImpl,
+	settlement::{
+		DeadLetterStore, FileDeadLetterStore, FileSettlementCursor, MemoryDeadLetterStore,
+		MemorySettlementCursor, SettlementCursor,
+	},
+	snapshot::{SnapshotMetadata, SnapshotStorage},
 };
 
+/// How often the matching loop is checked for the health service
+const ENGINE_HEALTH_INTERVAL: Duration = Duration::from_secs(1);
+
 #[tokio::main]
 async fn main() -> Result<()> {
 	// Initialize logging first
 	anvil_matching::logging::init_logging()?;
 
 	// Load configuration
-	let config = MatchingConfig::from_env().unwrap_or_else(|_| {
-		info!(target: "server", "Using default configuration");
-		MatchingConfig::default()
-	});
+	// A standby must not fall back to running as a primary, so a
+	// configuration that does not load stops startup
+	let config = MatchingConfig::from_env().context("Failed to load configuration")?;
 
 	info!(target: "server", "Starting Anvil Matching Engine");
 	info!(target: "server", "Market: {}", config.market);
 	info!(target: "server", "Listening on: {}", config.bind_addr);
 	info!(target: "server", "Ingress queue size: {}", config.ingress_queue_size);
 	info!(target: "server", "Event buffer size: {}", config.event_buffer_size);
+	info!(target: "server", "Balance enforcement: {}", config.enforce_balances);
+
+	if config.lease_path.is_some() {
+		if config.event_storage_path.is_none() {
+			bail!("Leader election needs an event log shared by the instances");
+		}
+		if config.replicate_from.is_some() {
+			bail!("Leader election and replication from a primary cannot be combined");
+		}
+	}
+	match config.event_retention {
+		EventRetention::Keep => {}
+		_ if config.snapshot_path.is_none() => {
+			bail!(
+				"Event retention needs snapshots that survive a restart (MATCHING_SNAPSHOT_PATH)"
+			);
+		}
+		EventRetention::Archive if config.event_archive_path.is_none() => {
+			bail!("Archiving events needs an archive directory (MATCHING_EVENT_ARCHIVE_PATH)");
+		}
+		EventRetention::Archive | EventRetention::Delete => {}
+	}
+	let replication = match (&config.replicate_from, &config.lease_path) {
+		(Some(primary), _) => {
+			info!(target: "server", "Following primary {} at epoch {}", primary, config.epoch);
+			ReplicationState::follower(config.epoch)
+		}
+		(None, Some(lease_path)) => {
+			info!(target: "server", "Standing by for the lease {}", lease_path.display());
+			ReplicationState::follower(config.epoch)
+		}
+		(None, None) => ReplicationState::primary(config.epoch),
+	};
 
 	// Phase 1: Initialize Order Journal
 	info!(target: "server", "Initializing Order Journal...");
-	let journal: Box<dyn OrderJournal> = Box::new(MemoryOrderJournal::new());
+	let journal: Box<dyn OrderJournal> = Box::new(MemoryOrderJournal::with_idempotency_window(
+		Duration::from_secs(config.idempotency_window_secs),
+	));
 	let journal = Arc::new(Mutex::new(journal));
 
 	// Phase 2: Create Ingress Queue (MPSC)
@@ -69,36 +132,249 @@ async fn main() -> Result<()> {
 	let (event_producer, event_consumer) = event_buffer.split();
 
 	// Phase 4: Start Event Writer
+	// Storage is shared with the event hub, which replays history for feeds
+	let event_storage: Box<dyn EventStorage> = match &config.event_storage_path {
+		Some(path) => {
+			info!(target: "server", "Opening event log {}", path.display());
+			let log = FileEventStorage::open(path).context("Failed to open the event log")?;
+			// An elected leader takes the log over at a newer epoch
+			if config.lease_path.is_none() && log.epoch() > config.epoch {
+				bail!(
+					"Event log is at epoch {}, past the configured epoch {}",
+					log.epoch(),
+					config.epoch
+				);
+			}
+			Box::new(log)
+		}
+		None => Box::new(MemoryEventStorage::new()),
+	};
+	// Readers replay archived events along with the live log
+	let event_storage: Box<dyn EventStorage> =
+		match (config.event_retention, &config.event_archive_path) {
+			(EventRetention::Archive, Some(path)) => {
+				info!(target: "server", "Archiving compacted events to {}", path.display());
+				let archive =
+					EventArchive::open(path).context("Failed to open the event archive")?;
+				Box::new(ArchivedEventStorage::new(event_storage, archive))
+			}
+			_ => event_storage,
+		};
+	let event_storage = SharedEventStorage::new(event_storage);
+	let event_hub = EventHub::new(event_storage.clone(), config.event_feed.clone());
+
+	// The engine starts from the latest snapshot that the event log has
+	// caught up with. A snapshot that fails to load is skipped for an older
+	// one. Without any, the log is replayed from the start, which the engine
+	// refuses if compaction has removed events no loaded snapshot covers.
+	let snapshot_storage: Box<dyn SnapshotStorage> = match &config.snapshot_path {
+		Some(path) => Box::new(
+			FileSnapshotStorage::open(path).context("Failed to open the snapshot directory")?,
+		),
+		None => Box::new(MemorySnapshotStorage::new()),
+	};
+	let mut restored = None;
+	let last_sequence = event_storage.last_sequence();
+	let mut snapshots = snapshot_storage.list_snapshots();
+	snapshots.retain(|metadata| metadata.event_seq <= last_sequence);
+	snapshots.sort_by_key(|metadata| metadata.event_seq);
+	for seq in snapshots
+		.into_iter()
+		.rev()
+		.map(|metadata| metadata.event_seq)
+	{
+		let (metadata, data) = match snapshot_storage.open_at_seq(seq) {
+			Ok(snapshot) => snapshot,
+			Err(e) => {
+				warn!(target: "server", seq = seq, error = %e, "Skipping unreadable snapshot");
+				continue;
+			}
+		};
+		info!(target: "server", "Loading the snapshot at seq={}...", seq);
+		match tokio::task::spawn_blocking(move || {
+			MatchingEngineState::read_snapshot(&metadata, data)
+		})
+		.await?
+		{
+			Ok(state) => {
+				restored = Some(state);
+				break;
+			}
+			Err(e) => {
+				warn!(target: "server", seq = seq, error = %e, "Skipping snapshot that failed to load");
+			}
+		}
+	}
+	// Deleted events are no longer in the log, so views start from the
+	// snapshot the engine restores. Trades and candles only cover the
+	// events the log still holds.
+	let seed = restored
+		.as_ref()
+		.filter(|_| config.event_retention == EventRetention::Delete)
+		.map(|state| (state, state.next_sequence));
+
+	// The committed ledger view mirrors the engine's ledger for RPC reads
+	let risk_config = RiskConfig::for_market(&config.market, config.enforce_balances)
+		.with_limits(config.risk_limits.clone());
+	let ledger_view = risk_config.ledger().map(LedgerView::new);
+	let mut sinks: Vec<Box<dyn CommittedEventSink>> = Vec::new();
+	if let Some(view) = &ledger_view {
+		match seed {
+			Some((state, seq)) => view.rebuild_from(state.ledger.clone(), seq, &event_storage),
+			None => view.rebuild(&event_storage),
+		}
+		.context("Failed to rebuild the ledger view")?;
+		sinks.push(Box::new(view.clone()));
+	}
+	let (market_data_publisher, market_data) =
+		MarketDataPublisher::start(config.market.clone(), config.market_data_capacity);
+	// Book, candles and ticker are derived state; rebuild them from history
+	match seed {
+		Some((state, seq)) => {
+			market_data.rebuild_from(state.orderbook.orders(), seq, &event_storage)
+		}
+		None => market_data.rebuild(&event_storage),
+	}
+	.context("Failed to rebuild market data")?;
+	sinks.push(Box::new(market_data_publisher));
+	let trade_history = TradeHistory::new(config.trade_history_capacity);
+	trade_history
+		.rebuild(&event_storage)
+		.context("Failed to rebuild trade history")?;
+	sinks.push(Box::new(trade_history.clone()));
+	let order_view = OrderView::new(config.order_history_capacity);
+	match seed {
+		Some((state, seq)) => {
+			order_view.rebuild_from(state.orderbook.orders(), seq, &event_storage)
+		}
+		None => order_view.rebuild(&event_storage),
+	}
+	.context("Failed to rebuild order view")?;
+	sinks.push(Box::new(order_view.clone()));
+	sinks.push(Box::new(event_hub.clone()));
+
 	info!(target: "server", "Starting event writer...");
-	let event_storage = Box::new(MemoryEventStorage::new());
 	let event_writer_config = EventWriterConfig {
 		batch_size: config.event_batch_size,
 		batch_timeout_ms: config.event_batch_timeout_ms,
 		verbose_logging: config.verbose_logging,
 	};
-	let _event_writer = EventWriter::start(
+	// Only the primary, or a follower replicating it, appends to storage
+	let _event_writer = EventWriter::start_with_sinks(
 		event_consumer,
-		event_storage,
+		Box::new(FencedEventStorage::new(
+			Box::new(event_storage.clone()),
+			replication.clone(),
+		)),
 		journal.clone(),
 		event_writer_config,
+		sinks,
 	);
 
-	// Phase 5: Start Matching Engine (single-threaded core)
+	// Phase 5: Start Settlement Forwarder
+	// A follower's events are settled by its primary; it forwards trades
+	// only once promoted
+	let _settlement_forwarder = if config.settlement.enabled {
+		info!(target: "server", "Starting settlement forwarder to {}...", config.settlement_endpoint);
+		let cursor: Box<dyn SettlementCursor> = match &config.settlement.cursor_path {
+			Some(path) => Box::new(FileSettlementCursor::new(path)),
+			None => Box::new(MemorySettlementCursor::new()),
+		};
+		let dead_letters: Box<dyn DeadLetterStore> = match &config.settlement.dead_letter_path {
+			Some(path) => Box::new(FileDeadLetterStore::new(path)),
+			None => Box::new(MemoryDeadLetterStore::new()),
+		};
+		let client = SettlementGrpcClient::connect_lazy(&config.settlement_endpoint)
+			.context("Invalid settlement endpoint")?;
+		let forwarder = SettlementForwarder::new(
+			event_hub.clone(),
+			client,
+			cursor,
+			dead_letters,
+			config.settlement.clone(),
+		);
+		let mut role = replication.subscribe();
+		Some(tokio::spawn(async move {
+			if role
+				.wait_for(|status| status.role == ReplicationRole::Primary)
+				.await
+				.is_ok()
+			{
+				let _ = forwarder.start().await;
+			}
+		}))
+	} else {
+		None
+	};
+
+	// Phase 6: Start Matching Engine (single-threaded core)
 	info!(target: "server", "Starting matching engine core...");
 	let engine_config = EngineConfig {
 		market: config.market.clone(),
 		verbose_logging: config.verbose_logging,
+		risk: risk_config,
 	};
-	let matching_engine = MatchingEngine::start(
+	let matching_engine = Arc::new(MatchingEngine::start(
 		engine_config,
 		queue_receiver,
 		event_producer,
 		journal.clone(),
-	);
+	));
+	// Bring the order book up to the committed history, starting from the
+	// snapshot loaded above
+	let mut applied = None;
+	if let Some(state) = restored {
+		let seq = state.next_sequence;
+		info!(target: "server", "Restoring the snapshot at seq={}...", seq);
+		let engine = matching_engine.clone();
+		tokio::task::spawn_blocking(move || engine.restore_state(state))
+			.await?
+			.map_err(anyhow::Error::msg)
+			.context("Failed to restore the snapshot")?;
+		applied = Some(seq);
+	}
+	let history = event_storage
+		.replay_from(applied.map_or(1, |seq| seq + 1))
+		.context("Failed to read the event log")?;
+	if let Some(last_sequence) = history.last().map(|event| event.sequence()) {
+		info!(target: "server", "Replaying {} committed events...", history.len());
+		let engine = matching_engine.clone();
+		tokio::task::spawn_blocking(move || engine.replay_events(history))
+			.await?
+			.map_err(anyhow::Error::msg)
+			.context("Failed to replay the event log")?;
+		applied = Some(last_sequence);
+	}
+	if let Some(applied) = applied {
+		replication.record_progress(applied, applied);
+	}
+	let _follower = config.replicate_from.as_ref().map(|primary| {
+		Follower::new(
+			matching_engine.clone(),
+			replication.clone(),
+			config.market.clone(),
+			FollowerConfig::new(primary.clone()),
+		)
+		.start()
+	});
+	let _leader_elector = config.lease_path.as_ref().map(|lease_path| {
+		let holder = config
+			.instance_id
+			.clone()
+			.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+		info!(target: "server", "Competing for the lease as {}", holder);
+		LeaderElector::new(
+			Box::new(FileLease::new(lease_path)),
+			matching_engine.clone(),
+			event_storage.clone(),
+			replication.clone(),
+			LeaderConfig::new(holder, Duration::from_millis(config.lease_ttl_ms)),
+		)
+		.start()
+	});
 
-	// Phase 6: Start Snapshotter
+	// Phase 7: Start Snapshotter
 	info!(target: "server", "Starting snapshotter...");
-	let snapshot_storage = Box::new(MemorySnapshotStorage::new());
 	let snapshotter_config = SnapshotterConfig {
 		snapshot_interval_secs: config.snapshot_interval_secs,
 		max_snapshots_to_keep: config.max_snapshots_to_keep,
@@ -108,17 +384,63 @@ async fn main() -> Result<()> {
 	let snapshot_provider = Arc::new(EngineSnapshotProvider {
 		engine: matching_engine,
 	});
-	let snapshotter = Snapshotter::start(
-		snapshot_storage,
-		snapshotter_config,
-		snapshot_provider.clone(),
-	);
+	let snapshotter = match config.event_retention {
+		EventRetention::Keep => Snapshotter::start(
+			snapshot_storage,
+			snapshotter_config,
+			snapshot_provider.clone(),
+		),
+		// Only the primary compacts the log it may share with standbys
+		EventRetention::Archive | EventRetention::Delete => Snapshotter::start_with_event_log(
+			snapshot_storage,
+			snapshotter_config,
+			snapshot_provider.clone(),
+			Box::new(FencedEventStorage::new(
+				Box::new(event_storage.clone()),
+				replication.clone(),
+			)),
+		),
+	};
 
-	// Phase 7: Start gRPC server
+	// Phase 8: Start gRPC server
 	info!(target: "server", "Starting gRPC server...");
-	let matching_service = server::create_server(queue_sender, journal, config.market.clone());
+	let mut matching_service =
+		MatchingServiceImpl::new(queue_sender, journal, config.market.clone())
+			.with_market_data(market_data)
+			.with_event_hub(event_hub)
+			.with_trade_history(trade_history)
+			.with_order_view(order_view)
+			.with_replication(replication);
+	if config.lease_path.is_some() {
+		matching_service = matching_service.with_leader_election();
+	}
+	if let Some(view) = ledger_view {
+		matching_service = matching_service.with_ledger(view);
+	}
+	let matching_service = matching_service.into_server();
+
+	// Gateways probe this to route around a stopped matching loop
+	let (health_reporter, health_service) = tonic_health::server::health_reporter();
+	health_reporter
+		.set_serving::<MatchingServiceServer<MatchingServiceImpl>>()
+		.await;
+	let engine_health = snapshot_provider.clone();
+	tokio::spawn(async move {
+		let mut interval = tokio::time::interval(ENGINE_HEALTH_INTERVAL);
+		loop {
+			interval.tick().await;
+			if !engine_health.engine.is_running() {
+				tracing::error!(target: "server", "Matching loop stopped, reporting not serving");
+				health_reporter
+					.set_not_serving::<MatchingServiceServer<MatchingServiceImpl>>()
+					.await;
+				break;
+			}
+		}
+	});
 
 	let server_future = Server::builder()
+		.add_service(health_service)
 		.add_service(matching_service)
 		.serve(config.bind_addr);
 
@@ -145,11 +467,11 @@ async fn main() -> Result<()> {
 
 /// Adapter to provide snapshots from the matching engine
 struct EngineSnapshotProvider {
-	engine: MatchingEngine,
+	engine: Arc<MatchingEngine>,
 }
 
 impl SnapshotProvider for EngineSnapshotProvider {
-	fn create_snapshot(&self) -> Result<anvil_matching::snapshot::Snapshot, String> {
-		self.engine.create_snapshot()
+	fn write_snapshot(&self, out: &mut dyn Write) -> Result<SnapshotMetadata, String> {
+		self.engine.write_snapshot(out)
 	}
 }
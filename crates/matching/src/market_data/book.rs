@@ -0,0 +1,393 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use anvil_sdk::types::Side;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	event::{MatchingEvent, SequenceNumber},
+	types::Order,
+};
+
+/// Aggregated size resting at one price
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PriceLevel {
+	pub price: u64,
+	/// Total remaining size at this price; 0 in an update means the level
+	/// was removed
+	pub size: u64,
+}
+
+/// Price levels changed by a single command
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookUpdate {
+	pub market: String,
+	/// Update sequence, contiguous per publisher
+	pub sequence: u64,
+	/// Sequence of the last matching event reflected in this update
+	pub event_sequence: SequenceNumber,
+	/// Changed bid levels, best first
+	pub bids: Vec<PriceLevel>,
+	/// Changed ask levels, best first
+	pub asks: Vec<PriceLevel>,
+}
+
+/// Top of the aggregated book at an update sequence
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookSnapshot {
+	pub market: String,
+	/// Sequence of the last update reflected in this snapshot
+	pub sequence: u64,
+	/// Sequence of the last matching event reflected in this snapshot
+	pub event_sequence: SequenceNumber,
+	/// Bid levels, best (highest) first
+	pub bids: Vec<PriceLevel>,
+	/// Ask levels, best (lowest) first
+	pub asks: Vec<PriceLevel>,
+}
+
+#[derive(Debug, Clone)]
+struct RestingOrder {
+	side: Side,
+	price: u64,
+	remaining_size: u64,
+}
+
+/// Aggregated price-level book derived from matching events
+///
+/// Levels touched by events are collected until the event that ends the
+/// command, at which point [`L2Book::apply`] returns them as one update.
+#[derive(Debug, Clone)]
+pub struct L2Book {
+	market: String,
+	bids: BTreeMap<u64, u64>,
+	asks: BTreeMap<u64, u64>,
+	orders: HashMap<String, RestingOrder>,
+	sequence: u64,
+	event_sequence: SequenceNumber,
+	dirty_bids: BTreeSet<u64>,
+	dirty_asks: BTreeSet<u64>,
+}
+
+impl L2Book {
+	pub fn new(market: impl Into<String>) -> Self {
+		Self {
+			market: market.into(),
+			bids: BTreeMap::new(),
+			asks: BTreeMap::new(),
+			orders: HashMap::new(),
+			sequence: 0,
+			event_sequence: 0,
+			dirty_bids: BTreeSet::new(),
+			dirty_asks: BTreeSet::new(),
+		}
+	}
+
+	pub fn market(&self) -> &str {
+		&self.market
+	}
+
+	/// Sequence of the last published update
+	pub fn sequence(&self) -> u64 {
+		self.sequence
+	}
+
+	/// Add orders resting in an engine snapshot at `event_sequence`
+	///
+	/// Nothing is published for them; subscribers sync from a snapshot of
+	/// this book as usual.
+	pub fn seed<'a>(
+		&mut self,
+		orders: impl IntoIterator<Item = &'a Order>,
+		event_sequence: SequenceNumber,
+	) {
+		self.event_sequence = self.event_sequence.max(event_sequence);
+		for order in orders {
+			let levels = match order.side {
+				Side::Buy => &mut self.bids,
+				Side::Sell => &mut self.asks,
+			};
+			*levels.entry(order.price).or_default() += order.remaining_size;
+			self.orders.insert(
+				order.order_id.clone(),
+				RestingOrder {
+					side: order.side,
+					price: order.price,
+					remaining_size: order.remaining_size,
+				},
+			);
+		}
+	}
+
+	/// Apply a matching event
+	///
+	/// Returns the accumulated level changes when `event` ends a command and
+	/// at least one level changed.
+	pub fn apply(&mut self, event: &MatchingEvent) -> Option<BookUpdate> {
+		self.event_sequence = self.event_sequence.max(event.sequence());
+
+		match event {
+			MatchingEvent::OrderAccepted {
+				order_id,
+				side,
+				price,
+				size,
+				..
+			} => {
+				self.orders.insert(
+					order_id.clone(),
+					RestingOrder {
+						side: *side,
+						price: *price,
+						remaining_size: *size,
+					},
+				);
+				self.adjust(*side, *price, *size as i128);
+			}
+			MatchingEvent::MakerOrderPartiallyFilled {
+				order_id,
+				remaining_size,
+				..
+			} => {
+				if let Some(order) = self.orders.get_mut(order_id) {
+					let filled = order.remaining_size.saturating_sub(*remaining_size);
+					order.remaining_size = *remaining_size;
+					let (side, price) = (order.side, order.price);
+					self.adjust(side, price, -(filled as i128));
+				}
+			}
+			MatchingEvent::MakerOrderFilled { order_id, .. }
+			| MatchingEvent::OrderCancelled { order_id, .. } => {
+				if let Some(order) = self.orders.remove(order_id) {
+					self.adjust(order.side, order.price, -(order.remaining_size as i128));
+				}
+			}
+			_ => {}
+		}
+
+		if !event.ends_command() || (self.dirty_bids.is_empty() && self.dirty_asks.is_empty()) {
+			return None;
+		}
+
+		self.sequence += 1;
+		let bids = std::mem::take(&mut self.dirty_bids)
+			.into_iter()
+			.rev()
+			.map(|price| PriceLevel {
+				price,
+				size: self.bids.get(&price).copied().unwrap_or(0),
+			})
+			.collect();
+		let asks = std::mem::take(&mut self.dirty_asks)
+			.into_iter()
+			.map(|price| PriceLevel {
+				price,
+				size: self.asks.get(&price).copied().unwrap_or(0),
+			})
+			.collect();
+
+		Some(BookUpdate {
+			market: self.market.clone(),
+			sequence: self.sequence,
+			event_sequence: self.event_sequence,
+			bids,
+			asks,
+		})
+	}
+
+	/// Snapshot of the top `depth` levels per side (0 for the full book)
+	pub fn snapshot(&self, depth: usize) -> BookSnapshot {
+		let depth = if depth == 0 { usize::MAX } else { depth };
+		BookSnapshot {
+			market: self.market.clone(),
+			sequence: self.sequence,
+			event_sequence: self.event_sequence,
+			bids: self
+				.bids
+				.iter()
+				.rev()
+				.take(depth)
+				.map(|(&price, &size)| PriceLevel { price, size })
+				.collect(),
+			asks: self
+				.asks
+				.iter()
+				.take(depth)
+				.map(|(&price, &size)| PriceLevel { price, size })
+				.collect(),
+		}
+	}
+
+	fn adjust(&mut self, side: Side, price: u64, delta: i128) {
+		if delta == 0 {
+			return;
+		}
+		let (levels, dirty) = match side {
+			Side::Buy => (&mut self.bids, &mut self.dirty_bids),
+			Side::Sell => (&mut self.asks, &mut self.dirty_asks),
+		};
+		let size = (levels.get(&price).copied().unwrap_or(0) as i128 + delta).max(0) as u64;
+		if size == 0 {
+			levels.remove(&price);
+		} else {
+			levels.insert(price, size);
+		}
+		dirty.insert(price);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anvil_sdk::types::Trade;
+
+	fn accepted(seq: u64, order_id: &str, side: Side, price: u64, size: u64) -> MatchingEvent {
+		MatchingEvent::OrderAccepted {
+			seq,
+			order_id: order_id.to_string(),
+			market: "BTC-USDT".to_string(),
+			public_key: "test_key".to_string(),
+			client_order_id: None,
+			side,
+			price,
+			size,
+			timestamp: 0,
+		}
+	}
+
+	#[test]
+	fn resting_orders_publish_level_updates() {
+		let mut book = L2Book::new("BTC-USDT");
+
+		let update = book.apply(&accepted(1, "b1", Side::Buy, 100, 5)).unwrap();
+		assert_eq!(update.sequence, 1);
+		assert_eq!(
+			update.bids,
+			vec![PriceLevel {
+				price: 100,
+				size: 5
+			}]
+		);
+		assert!(update.asks.is_empty());
+
+		let update = book.apply(&accepted(2, "b2", Side::Buy, 100, 3)).unwrap();
+		assert_eq!(update.sequence, 2);
+		assert_eq!(
+			update.bids,
+			vec![PriceLevel {
+				price: 100,
+				size: 8
+			}]
+		);
+
+		book.apply(&accepted(3, "a1", Side::Sell, 105, 2));
+		let snapshot = book.snapshot(10);
+		assert_eq!(snapshot.sequence, 3);
+		assert_eq!(snapshot.event_sequence, 3);
+		assert_eq!(
+			snapshot.bids,
+			vec![PriceLevel {
+				price: 100,
+				size: 8
+			}]
+		);
+		assert_eq!(
+			snapshot.asks,
+			vec![PriceLevel {
+				price: 105,
+				size: 2
+			}]
+		);
+	}
+
+	#[test]
+	fn fills_are_published_once_per_command() {
+		let mut book = L2Book::new("BTC-USDT");
+		book.apply(&accepted(1, "a1", Side::Sell, 100, 2));
+		book.apply(&accepted(2, "a2", Side::Sell, 101, 2));
+
+		// A taker buy sweeps a1 and half of a2
+		let trade = |seq, maker: &str, price, size| MatchingEvent::TradeExecuted {
+			seq,
+			trade: Trade {
+				trade_id: format!("t{}", seq),
+				market: "BTC-USDT".to_string(),
+				price,
+				size,
+				side: Side::Buy,
+				timestamp: 0,
+				maker_order_id: maker.to_string(),
+				taker_order_id: "b1".to_string(),
+			},
+			maker_public_key: "maker".to_string(),
+			taker_public_key: "taker".to_string(),
+			maker_client_order_id: None,
+			taker_client_order_id: None,
+			timestamp: 0,
+		};
+		let events = [
+			trade(3, "a1", 100, 2),
+			MatchingEvent::MakerOrderFilled {
+				seq: 4,
+				order_id: "a1".to_string(),
+				market: "BTC-USDT".to_string(),
+				filled_size: 2,
+				timestamp: 0,
+			},
+			trade(5, "a2", 101, 1),
+			MatchingEvent::MakerOrderPartiallyFilled {
+				seq: 6,
+				order_id: "a2".to_string(),
+				market: "BTC-USDT".to_string(),
+				filled_size: 1,
+				remaining_size: 1,
+				timestamp: 0,
+			},
+			MatchingEvent::OrderFilled {
+				seq: 7,
+				order_id: "b1".to_string(),
+				market: "BTC-USDT".to_string(),
+				filled_size: 3,
+				timestamp: 0,
+			},
+		];
+
+		let updates: Vec<_> = events.iter().filter_map(|e| book.apply(e)).collect();
+		assert_eq!(updates.len(), 1);
+		assert_eq!(updates[0].sequence, 3);
+		assert_eq!(updates[0].event_sequence, 7);
+		assert_eq!(
+			updates[0].asks,
+			vec![
+				PriceLevel {
+					price: 100,
+					size: 0
+				},
+				PriceLevel {
+					price: 101,
+					size: 1
+				},
+			]
+		);
+		assert_eq!(
+			book.snapshot(0).asks,
+			vec![PriceLevel {
+				price: 101,
+				size: 1
+			}]
+		);
+	}
+}
@@ -0,0 +1,357 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+	collections::{HashMap, VecDeque},
+	fmt,
+	str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::event::{MatchingEvent, SequenceNumber};
+
+/// Candles kept per interval; enough 1m candles to cover the 24h ticker
+pub const CANDLE_RETENTION: usize = 1440;
+
+const TICKER_WINDOW_SECS: u64 = 24 * 60 * 60;
+
+/// Unsupported candle interval string
+#[derive(Debug, Error)]
+#[error("Unsupported candle interval: {0} (expected 1m, 5m, 1h or 1d)")]
+pub struct InvalidInterval(pub String);
+
+/// Candle width
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CandleInterval {
+	#[serde(rename = "1m")]
+	OneMinute,
+	#[serde(rename = "5m")]
+	FiveMinutes,
+	#[serde(rename = "1h")]
+	OneHour,
+	#[serde(rename = "1d")]
+	OneDay,
+}
+
+impl CandleInterval {
+	pub const ALL: [CandleInterval; 4] = [
+		CandleInterval::OneMinute,
+		CandleInterval::FiveMinutes,
+		CandleInterval::OneHour,
+		CandleInterval::OneDay,
+	];
+
+	/// Interval length in seconds
+	pub fn seconds(self) -> u64 {
+		match self {
+			CandleInterval::OneMinute => 60,
+			CandleInterval::FiveMinutes => 5 * 60,
+			CandleInterval::OneHour => 60 * 60,
+			CandleInterval::OneDay => 24 * 60 * 60,
+		}
+	}
+
+	pub fn as_str(self) -> &'static str {
+		match self {
+			CandleInterval::OneMinute => "1m",
+			CandleInterval::FiveMinutes => "5m",
+			CandleInterval::OneHour => "1h",
+			CandleInterval::OneDay => "1d",
+		}
+	}
+}
+
+impl fmt::Display for CandleInterval {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+impl FromStr for CandleInterval {
+	type Err = InvalidInterval;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Self::ALL
+			.into_iter()
+			.find(|interval| interval.as_str() == s)
+			.ok_or_else(|| InvalidInterval(s.to_string()))
+	}
+}
+
+/// OHLCV bar in market units
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Candle {
+	/// Start of the interval (Unix seconds)
+	pub open_time: u64,
+	pub open: u64,
+	pub high: u64,
+	pub low: u64,
+	pub close: u64,
+	/// Traded base size
+	pub volume: u64,
+	pub trade_count: u64,
+}
+
+impl Candle {
+	fn new(open_time: u64, price: u64, size: u64) -> Self {
+		Self {
+			open_time,
+			open: price,
+			high: price,
+			low: price,
+			close: price,
+			volume: size,
+			trade_count: 1,
+		}
+	}
+
+	fn add(&mut self, price: u64, size: u64) {
+		self.high = self.high.max(price);
+		self.low = self.low.min(price);
+		self.close = price;
+		self.volume = self.volume.saturating_add(size);
+		self.trade_count += 1;
+	}
+}
+
+/// Rolling 24h statistics in market units
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ticker {
+	pub market: String,
+	/// Last traded price (0 before the first trade)
+	pub last: u64,
+	/// First traded price in the window
+	pub open: u64,
+	pub high: u64,
+	pub low: u64,
+	/// Traded base size in the window
+	pub volume: u64,
+	/// `last - open`
+	pub change: i64,
+	pub trade_count: u64,
+	/// Time the statistics were computed for (Unix seconds)
+	pub timestamp: u64,
+}
+
+/// OHLCV candles and ticker statistics derived from `TradeExecuted` events
+///
+/// Keeps the latest [`CANDLE_RETENTION`] candles per interval. The ticker
+/// window is made of whole 1m candles, so it covers 24h to the minute.
+#[derive(Debug, Clone)]
+pub struct CandleAggregator {
+	market: String,
+	series: HashMap<CandleInterval, VecDeque<Candle>>,
+	last_price: u64,
+	event_sequence: SequenceNumber,
+}
+
+impl CandleAggregator {
+	pub fn new(market: impl Into<String>) -> Self {
+		Self {
+			market: market.into(),
+			series: HashMap::new(),
+			last_price: 0,
+			event_sequence: 0,
+		}
+	}
+
+	pub fn market(&self) -> &str {
+		&self.market
+	}
+
+	/// Sequence of the last matching event applied
+	pub fn event_sequence(&self) -> SequenceNumber {
+		self.event_sequence
+	}
+
+	/// Apply a matching event; only trades change the candles
+	pub fn apply(&mut self, event: &MatchingEvent) {
+		self.event_sequence = self.event_sequence.max(event.sequence());
+		let MatchingEvent::TradeExecuted { trade, .. } = event else {
+			return;
+		};
+
+		self.last_price = trade.price;
+		for interval in CandleInterval::ALL {
+			let open_time = trade.timestamp - trade.timestamp % interval.seconds();
+			let series = self.series.entry(interval).or_default();
+			match series.back_mut() {
+				// A clock step backwards lands in the current candle
+				Some(candle) if candle.open_time >= open_time => {
+					candle.add(trade.price, trade.size)
+				}
+				_ => {
+					series.push_back(Candle::new(open_time, trade.price, trade.size));
+					if series.len() > CANDLE_RETENTION {
+						series.pop_front();
+					}
+				}
+			}
+		}
+	}
+
+	/// The latest `limit` candles of `interval`, oldest first (0 for all)
+	///
+	/// Intervals without trades have no candle.
+	pub fn candles(&self, interval: CandleInterval, limit: usize) -> Vec<Candle> {
+		let Some(series) = self.series.get(&interval) else {
+			return Vec::new();
+		};
+		let skip = if limit == 0 {
+			0
+		} else {
+			series.len().saturating_sub(limit)
+		};
+		series.iter().skip(skip).copied().collect()
+	}
+
+	/// Statistics for the 24h ending at `now` (Unix seconds)
+	pub fn ticker(&self, now: u64) -> Ticker {
+		let since = now.saturating_sub(TICKER_WINDOW_SECS);
+		let window: Vec<_> = self
+			.series
+			.get(&CandleInterval::OneMinute)
+			.into_iter()
+			.flatten()
+			.filter(|candle| candle.open_time + 60 > since && candle.open_time <= now)
+			.collect();
+
+		let mut ticker = Ticker {
+			market: self.market.clone(),
+			last: self.last_price,
+			open: self.last_price,
+			high: self.last_price,
+			low: self.last_price,
+			volume: 0,
+			change: 0,
+			trade_count: 0,
+			timestamp: now,
+		};
+		if let Some(first) = window.first() {
+			ticker.open = first.open;
+			ticker.high = window.iter().map(|c| c.high).max().unwrap_or(first.high);
+			ticker.low = window.iter().map(|c| c.low).min().unwrap_or(first.low);
+			ticker.volume = window.iter().map(|c| c.volume).sum();
+			ticker.trade_count = window.iter().map(|c| c.trade_count).sum();
+			ticker.change = ticker.last as i64 - ticker.open as i64;
+		}
+		ticker
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use anvil_sdk::types::{Side, Trade};
+
+	use super::*;
+
+	fn trade(seq: SequenceNumber, timestamp: u64, price: u64, size: u64) -> MatchingEvent {
+		MatchingEvent::TradeExecuted {
+			seq,
+			trade: Trade {
+				trade_id: format!("t{}", seq),
+				market: "BTC-USDT".to_string(),
+				price,
+				size,
+				side: Side::Buy,
+				timestamp,
+				maker_order_id: "maker".to_string(),
+				taker_order_id: "taker".to_string(),
+			},
+			maker_public_key: "maker_key".to_string(),
+			taker_public_key: "taker_key".to_string(),
+			maker_client_order_id: None,
+			taker_client_order_id: None,
+			timestamp,
+		}
+	}
+
+	#[test]
+	fn parses_intervals() {
+		assert_eq!("5m".parse::<CandleInterval>().unwrap().seconds(), 300);
+		assert!("2m".parse::<CandleInterval>().is_err());
+	}
+
+	#[test]
+	fn trades_roll_into_candles_per_interval() {
+		let mut aggregator = CandleAggregator::new("BTC-USDT");
+		aggregator.apply(&trade(1, 120, 100, 2));
+		aggregator.apply(&trade(2, 150, 105, 1));
+		aggregator.apply(&trade(3, 170, 98, 3));
+		aggregator.apply(&trade(4, 200, 101, 1));
+
+		let minutes = aggregator.candles(CandleInterval::OneMinute, 0);
+		assert_eq!(
+			minutes,
+			vec![
+				Candle {
+					open_time: 120,
+					open: 100,
+					high: 105,
+					low: 98,
+					close: 98,
+					volume: 6,
+					trade_count: 3,
+				},
+				Candle {
+					open_time: 180,
+					open: 101,
+					high: 101,
+					low: 101,
+					close: 101,
+					volume: 1,
+					trade_count: 1,
+				},
+			]
+		);
+		assert_eq!(
+			aggregator.candles(CandleInterval::OneMinute, 1)[0].open_time,
+			180
+		);
+
+		let hours = aggregator.candles(CandleInterval::OneHour, 0);
+		assert_eq!(hours.len(), 1);
+		assert_eq!(
+			(hours[0].open, hours[0].close, hours[0].volume),
+			(100, 101, 7)
+		);
+	}
+
+	#[test]
+	fn ticker_covers_the_last_24_hours() {
+		let day = TICKER_WINDOW_SECS;
+		let mut aggregator = CandleAggregator::new("BTC-USDT");
+		aggregator.apply(&trade(1, 0, 90, 5));
+		aggregator.apply(&trade(2, day, 100, 1));
+		aggregator.apply(&trade(3, day + 3600, 120, 2));
+		aggregator.apply(&trade(4, day + 7200, 110, 1));
+
+		let ticker = aggregator.ticker(day + 7200);
+		assert_eq!(ticker.last, 110);
+		assert_eq!(ticker.open, 100);
+		assert_eq!((ticker.high, ticker.low), (120, 100));
+		assert_eq!(ticker.volume, 4);
+		assert_eq!(ticker.trade_count, 3);
+		assert_eq!(ticker.change, 10);
+
+		// Without trades in the window the ticker only reports the last price
+		let quiet = aggregator.ticker(3 * day);
+		assert_eq!(
+			(quiet.last, quiet.open, quiet.volume, quiet.change),
+			(110, 110, 0, 0)
+		);
+	}
+}
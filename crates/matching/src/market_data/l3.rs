@@ -0,0 +1,143 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anvil_sdk::types::Side;
+use serde::{Deserialize, Serialize};
+
+use crate::event::{MatchingEvent, SequenceNumber};
+
+/// Order-by-order change to the book
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderFeedBody {
+	/// A new order (or the unfilled remainder of a taker) rests on the book
+	Add {
+		order_id: String,
+		side: Side,
+		price: u64,
+		size: u64,
+	},
+	/// A resting order traded; it leaves the book once fully executed
+	Execute {
+		order_id: String,
+		trade_id: String,
+		price: u64,
+		size: u64,
+	},
+	/// A resting order was removed without trading
+	Delete { order_id: String },
+}
+
+/// Level-3 feed message
+///
+/// `sequence` is the sequence of the matching event the message was derived
+/// from, so subscribers can resume with `sequence + 1`. Events that do not
+/// change the book (rejections, taker fills, balances) produce no message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderFeedMessage {
+	pub sequence: SequenceNumber,
+	pub market: String,
+	pub timestamp: u64,
+	pub body: OrderFeedBody,
+}
+
+impl OrderFeedMessage {
+	/// Derive the L3 message for a matching event, if any
+	///
+	/// The engine has no amend command, so every size change of a resting
+	/// order is an `Execute` and removals are `Execute` or `Delete`.
+	pub fn from_event(event: &MatchingEvent) -> Option<Self> {
+		let body = match event {
+			MatchingEvent::OrderAccepted {
+				order_id,
+				side,
+				price,
+				size,
+				..
+			} => OrderFeedBody::Add {
+				order_id: order_id.clone(),
+				side: *side,
+				price: *price,
+				size: *size,
+			},
+			MatchingEvent::TradeExecuted { trade, .. } => OrderFeedBody::Execute {
+				order_id: trade.maker_order_id.clone(),
+				trade_id: trade.trade_id.clone(),
+				price: trade.price,
+				size: trade.size,
+			},
+			MatchingEvent::OrderCancelled { order_id, .. } => OrderFeedBody::Delete {
+				order_id: order_id.clone(),
+			},
+			_ => return None,
+		};
+
+		let timestamp = match event {
+			MatchingEvent::OrderAccepted { timestamp, .. }
+			| MatchingEvent::TradeExecuted { timestamp, .. }
+			| MatchingEvent::OrderCancelled { timestamp, .. } => *timestamp,
+			_ => 0,
+		};
+
+		Some(Self {
+			sequence: event.sequence(),
+			market: event.market().to_string(),
+			timestamp,
+			body,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn only_book_events_produce_messages() {
+		let accepted = MatchingEvent::OrderAccepted {
+			seq: 7,
+			order_id: "o1".to_string(),
+			market: "BTC-USDT".to_string(),
+			public_key: "test_key".to_string(),
+			client_order_id: None,
+			side: Side::Sell,
+			price: 100,
+			size: 2,
+			timestamp: 42,
+		};
+		let message = OrderFeedMessage::from_event(&accepted).unwrap();
+		assert_eq!(message.sequence, 7);
+		assert_eq!(message.timestamp, 42);
+		assert_eq!(
+			message.body,
+			OrderFeedBody::Add {
+				order_id: "o1".to_string(),
+				side: Side::Sell,
+				price: 100,
+				size: 2,
+			}
+		);
+
+		let rejected = MatchingEvent::OrderRejected {
+			seq: 8,
+			order_id: "o2".to_string(),
+			market: "BTC-USDT".to_string(),
+			public_key: "alice".to_string(),
+			client_order_id: None,
+			code: Default::default(),
+			reason: "test".to_string(),
+			timestamp: 0,
+		};
+		assert!(OrderFeedMessage::from_event(&rejected).is_none());
+	}
+}
@@ -0,0 +1,48 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Market data publishing
+//!
+//! The publisher consumes committed `MatchingEvent`s on its own thread and
+//! maintains an aggregated price-level (L2) view of the book. After each
+//! ingress command it publishes the levels that changed as a sequenced
+//! [`BookUpdate`].
+//!
+//! Consumers sync by subscribing to updates first, then fetching a
+//! [`BookSnapshot`] and discarding updates whose sequence is not greater than
+//! the snapshot's. Update sequences are contiguous, so a gap means the
+//! consumer must resync.
+//!
+//! The same thread rolls trades into OHLCV candles (1m, 5m, 1h, 1d) and
+//! rolling 24h ticker statistics, see [`CandleAggregator`].
+//!
+//! [`MarketDataHandle::subscribe_all`] streams all of it as
+//! [`MarketDataUpdate`]s: book updates and trades in event order, then the
+//! ticker and current candles after each batch that contained trades.
+//!
+//! The order-by-order (L3) feed is derived per event with
+//! [`OrderFeedMessage::from_event`] from the committed event stream, which
+//! `EventHub` serves from any sequence.
+
+mod book;
+mod candles;
+mod l3;
+mod publisher;
+
+pub use book::{BookSnapshot, BookUpdate, L2Book, PriceLevel};
+pub use candles::{
+	CANDLE_RETENTION, Candle, CandleAggregator, CandleInterval, InvalidInterval, Ticker,
+};
+pub use l3::{OrderFeedBody, OrderFeedMessage};
+pub use publisher::{MarketDataHandle, MarketDataPublisher, MarketDataUpdate};
@@ -0,0 +1,326 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+	sync::{Arc, RwLock},
+	thread,
+};
+
+use anvil_sdk::types::Trade;
+use crossbeam::channel::{self, Receiver, Sender};
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+use super::book::{BookSnapshot, BookUpdate, L2Book};
+use super::candles::{Candle, CandleAggregator, CandleInterval, Ticker};
+use crate::event::{CommittedEventSink, EventStorage, MatchingEvent, SequenceNumber, StorageError};
+use crate::types::Order;
+
+/// One public market data change, in publication order
+#[derive(Debug, Clone)]
+pub enum MarketDataUpdate {
+	/// Levels changed by one command
+	Book(BookUpdate),
+	/// A committed trade
+	Trade {
+		event_sequence: SequenceNumber,
+		trade: Trade,
+	},
+	/// Ticker after a batch that contained trades
+	Ticker {
+		event_sequence: SequenceNumber,
+		ticker: Ticker,
+	},
+	/// Current candle of `interval` after a batch that contained trades
+	Candle {
+		event_sequence: SequenceNumber,
+		interval: CandleInterval,
+		candle: Candle,
+	},
+}
+
+/// Committed-event sink that feeds the market data thread
+///
+/// `on_committed` only copies the batch onto an unbounded channel, so the
+/// event writer is never held up by book aggregation or slow subscribers.
+pub struct MarketDataPublisher {
+	tx: Sender<Vec<MatchingEvent>>,
+}
+
+impl MarketDataPublisher {
+	/// Start the market data thread for `market`
+	///
+	/// `update_capacity` bounds how many updates a subscriber may fall behind
+	/// before it is told to resync. The thread exits once the publisher (and
+	/// with it the event writer) is dropped.
+	pub fn start(market: impl Into<String>, update_capacity: usize) -> (Self, MarketDataHandle) {
+		let (tx, rx) = channel::unbounded();
+		let market = market.into();
+		let handle = MarketDataHandle {
+			book: Arc::new(RwLock::new(L2Book::new(market.clone()))),
+			candles: Arc::new(RwLock::new(CandleAggregator::new(market))),
+			updates: broadcast::channel(update_capacity.max(1)).0,
+			feed: broadcast::channel(update_capacity.max(1)).0,
+		};
+
+		let thread_handle = handle.clone();
+		thread::Builder::new()
+			.name("market-data".to_string())
+			.spawn(move || Self::run(rx, thread_handle))
+			.expect("Failed to spawn market data thread");
+
+		(Self { tx }, handle)
+	}
+
+	fn run(rx: Receiver<Vec<MatchingEvent>>, handle: MarketDataHandle) {
+		info!(target: "market_data", "Market data publisher started");
+		while let Ok(events) = rx.recv() {
+			{
+				let mut candles = handle.candles.write().unwrap();
+				for event in &events {
+					candles.apply(event);
+				}
+			}
+
+			// Publish while holding the write lock so a concurrent snapshot
+			// is either before or after the whole update
+			let mut traded = None;
+			{
+				let mut book = handle.book.write().unwrap();
+				for event in &events {
+					if let MatchingEvent::TradeExecuted { seq, trade, .. } = event {
+						traded = Some(*seq);
+						let _ = handle.feed.send(MarketDataUpdate::Trade {
+							event_sequence: *seq,
+							trade: trade.clone(),
+						});
+					}
+					if let Some(update) = book.apply(event) {
+						debug!(
+							target: "market_data",
+							sequence = update.sequence,
+							event_sequence = update.event_sequence,
+							"Publishing book update"
+						);
+						// No receivers is fine; the book still advances
+						let _ = handle.feed.send(MarketDataUpdate::Book(update.clone()));
+						let _ = handle.updates.send(update);
+					}
+				}
+			}
+
+			if let Some(event_sequence) = traded {
+				let _ = handle.feed.send(MarketDataUpdate::Ticker {
+					event_sequence,
+					ticker: handle.ticker(),
+				});
+				let candles = handle.candles.read().unwrap();
+				for interval in CandleInterval::ALL {
+					if let Some(&candle) = candles.candles(interval, 1).last() {
+						let _ = handle.feed.send(MarketDataUpdate::Candle {
+							event_sequence,
+							interval,
+							candle,
+						});
+					}
+				}
+			}
+		}
+		info!(target: "market_data", "Market data publisher stopped");
+	}
+}
+
+impl CommittedEventSink for MarketDataPublisher {
+	fn on_committed(&mut self, events: &[MatchingEvent]) {
+		if self.tx.send(events.to_vec()).is_err() {
+			warn!(target: "market_data", "Market data thread stopped, dropping events");
+		}
+	}
+}
+
+/// Read side of the market data publisher
+#[derive(Clone)]
+pub struct MarketDataHandle {
+	book: Arc<RwLock<L2Book>>,
+	candles: Arc<RwLock<CandleAggregator>>,
+	updates: broadcast::Sender<BookUpdate>,
+	feed: broadcast::Sender<MarketDataUpdate>,
+}
+
+impl MarketDataHandle {
+	/// Snapshot of the top `depth` levels per side (0 for the full book)
+	pub fn snapshot(&self, depth: usize) -> BookSnapshot {
+		self.book.read().unwrap().snapshot(depth)
+	}
+
+	/// The latest `limit` candles of `interval`, oldest first (0 for all)
+	pub fn candles(&self, interval: CandleInterval, limit: usize) -> Vec<Candle> {
+		self.candles.read().unwrap().candles(interval, limit)
+	}
+
+	/// Rolling 24h ticker statistics as of now
+	pub fn ticker(&self) -> Ticker {
+		let now = std::time::SystemTime::now()
+			.duration_since(std::time::UNIX_EPOCH)
+			.unwrap_or_default()
+			.as_secs();
+		self.candles.read().unwrap().ticker(now)
+	}
+
+	/// Subscribe to book updates published after this call
+	pub fn subscribe(&self) -> broadcast::Receiver<BookUpdate> {
+		self.updates.subscribe()
+	}
+
+	/// Subscribe to every market data change published after this call
+	pub fn subscribe_all(&self) -> broadcast::Receiver<MarketDataUpdate> {
+		self.feed.subscribe()
+	}
+
+	/// Rebuild the book and candles by replaying every committed event from
+	/// `storage`
+	///
+	/// Call before the event writer starts delivering new batches.
+	pub fn rebuild(&self, storage: &dyn EventStorage) -> Result<(), StorageError> {
+		self.rebuild_from([], 0, storage)
+	}
+
+	/// Rebuild the book from the orders resting in an engine snapshot at
+	/// `seq`, then the committed events after it in `storage`
+	///
+	/// Used when events up to the snapshot may have been deleted. Candles
+	/// are rolled from whatever events `storage` still holds. Call before
+	/// the event writer starts delivering new batches.
+	pub fn rebuild_from<'a>(
+		&self,
+		orders: impl IntoIterator<Item = &'a Order>,
+		seq: SequenceNumber,
+		storage: &dyn EventStorage,
+	) -> Result<(), StorageError> {
+		let events = storage.replay_from(0)?;
+		let mut book = self.book.write().unwrap();
+		let mut candles = self.candles.write().unwrap();
+		*book = L2Book::new(book.market());
+		*candles = CandleAggregator::new(candles.market());
+		book.seed(orders, seq);
+		for event in &events {
+			if event.sequence() > seq {
+				book.apply(event);
+			}
+			candles.apply(event);
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+
+	use anvil_sdk::types::Side;
+
+	use super::*;
+	use crate::market_data::PriceLevel;
+
+	#[test]
+	fn publishes_updates_off_the_writer_thread() {
+		let (mut publisher, handle) = MarketDataPublisher::start("BTC-USDT", 16);
+		let mut updates = handle.subscribe();
+
+		publisher.on_committed(&[MatchingEvent::OrderAccepted {
+			seq: 1,
+			order_id: "b1".to_string(),
+			market: "BTC-USDT".to_string(),
+			public_key: "test_key".to_string(),
+			client_order_id: None,
+			side: Side::Buy,
+			price: 100,
+			size: 5,
+			timestamp: 0,
+		}]);
+
+		let mut update = None;
+		for _ in 0..100 {
+			if let Ok(received) = updates.try_recv() {
+				update = Some(received);
+				break;
+			}
+			thread::sleep(Duration::from_millis(10));
+		}
+		let update = update.expect("book update not published");
+		assert_eq!(update.sequence, 1);
+		assert_eq!(
+			update.bids,
+			vec![PriceLevel {
+				price: 100,
+				size: 5
+			}]
+		);
+		assert_eq!(handle.snapshot(1).sequence, 1);
+	}
+
+	#[test]
+	fn trades_are_followed_by_ticker_and_candles() {
+		let (mut publisher, handle) = MarketDataPublisher::start("BTC-USDT", 16);
+		let mut feed = handle.subscribe_all();
+
+		publisher.on_committed(&[MatchingEvent::TradeExecuted {
+			seq: 7,
+			trade: Trade {
+				trade_id: "t1".to_string(),
+				market: "BTC-USDT".to_string(),
+				price: 100,
+				size: 2,
+				side: Side::Buy,
+				timestamp: 120,
+				maker_order_id: "m1".to_string(),
+				taker_order_id: "t1".to_string(),
+			},
+			maker_public_key: "maker".to_string(),
+			taker_public_key: "taker".to_string(),
+			maker_client_order_id: None,
+			taker_client_order_id: None,
+			timestamp: 120,
+		}]);
+
+		let mut received = Vec::new();
+		for _ in 0..100 {
+			while let Ok(update) = feed.try_recv() {
+				received.push(update);
+			}
+			if received.len() == 2 + CandleInterval::ALL.len() {
+				break;
+			}
+			thread::sleep(Duration::from_millis(10));
+		}
+		assert!(matches!(
+			received[0],
+			MarketDataUpdate::Trade { event_sequence: 7, ref trade } if trade.trade_id == "t1"
+		));
+		assert!(matches!(
+			received[1],
+			MarketDataUpdate::Ticker { ref ticker, .. } if ticker.last == 100
+		));
+		let intervals: Vec<_> = received[2..]
+			.iter()
+			.filter_map(|update| match update {
+				MarketDataUpdate::Candle {
+					interval, candle, ..
+				} if candle.volume == 2 => Some(*interval),
+				_ => None,
+			})
+			.collect();
+		assert_eq!(intervals, CandleInterval::ALL);
+	}
+}
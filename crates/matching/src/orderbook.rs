@@ -12,9 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::BTreeMap;
+use std::cmp::Reverse;
 
 use anvil_sdk::types::Side;
+use imbl::{OrdMap, Vector};
 use serde::{Deserialize, Serialize};
 
 use crate::types::Order;
@@ -27,7 +28,7 @@ use crate::types::Order;
 pub struct PriceLevel {
 	price: u64,
 	/// Orders at this price level in time priority order
-	orders: Vec<Order>,
+	orders: Vector<Order>,
 	/// Total size of all orders at this level
 	total_size: u64,
 }
@@ -36,14 +37,14 @@ impl PriceLevel {
 	fn new(price: u64) -> Self {
 		Self {
 			price,
-			orders: Vec::new(),
+			orders: Vector::new(),
 			total_size: 0,
 		}
 	}
 
 	pub fn add_order(&mut self, order: Order) {
 		self.total_size += order.remaining_size;
-		self.orders.push(order);
+		self.orders.push_back(order);
 	}
 
 	pub fn remove_order(&mut self, order_id: &str) -> Option<Order> {
@@ -68,21 +69,17 @@ impl PriceLevel {
 	}
 
 	pub fn get_first_order(&self) -> Option<&Order> {
-		self.orders.first()
+		self.orders.front()
 	}
 
 	pub fn get_first_order_mut(&mut self) -> Option<&mut Order> {
-		self.orders.first_mut()
+		self.orders.front_mut()
 	}
 
 	pub fn remove_first_order(&mut self) -> Option<Order> {
-		if !self.orders.is_empty() {
-			let order = self.orders.remove(0);
-			self.total_size -= order.remaining_size;
-			Some(order)
-		} else {
-			None
-		}
+		let order = self.orders.pop_front()?;
+		self.total_size -= order.remaining_size;
+		Some(order)
 	}
 
 	pub fn is_empty(&self) -> bool {
@@ -101,7 +98,7 @@ impl PriceLevel {
 /// Limit order book maintaining buy and sell sides (single-threaded)
 ///
 /// This is a deterministic, single-threaded order book implementation
-/// using BTreeMap for price-sorted levels. All operations are designed
+/// using ordered maps for price-sorted levels. All operations are designed
 /// to be called from a single thread (the matching loop).
 ///
 /// Design characteristics:
@@ -109,15 +106,19 @@ impl PriceLevel {
 /// - Deterministic iteration order
 /// - Price-time priority enforced
 /// - Buy side: highest price first (descending order via Reverse wrapper)
-/// - Sell side: lowest price first (ascending order, natural BTreeMap order)
+/// - Sell side: lowest price first (ascending order, natural map order)
+/// - Cheap to clone: levels and their orders live in persistent
+///   collections that share structure, so a clone is a consistent view
+///   that costs nothing until the book is modified, and a modification
+///   only copies the parts of the book it touches
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderBook {
 	market: String,
 	/// Buy side: price (high to low) -> PriceLevel
-	/// We use BTreeMap in reverse order for bids
-	bids: BTreeMap<std::cmp::Reverse<u64>, PriceLevel>,
+	/// We use reversed keys for bids
+	bids: OrdMap<Reverse<u64>, PriceLevel>,
 	/// Sell side: price (low to high) -> PriceLevel
-	asks: BTreeMap<u64, PriceLevel>,
+	asks: OrdMap<u64, PriceLevel>,
 }
 
 impl OrderBook {
@@ -125,8 +126,8 @@ impl OrderBook {
 	pub fn new(market: String) -> Self {
 		Self {
 			market,
-			bids: BTreeMap::new(),
-			asks: BTreeMap::new(),
+			bids: OrdMap::new(),
+			asks: OrdMap::new(),
 		}
 	}
 
@@ -140,7 +141,7 @@ impl OrderBook {
 		match order.side {
 			Side::Buy => {
 				self.bids
-					.entry(std::cmp::Reverse(order.price))
+					.entry(Reverse(order.price))
 					.or_insert_with(|| PriceLevel::new(order.price))
 					.add_order(order);
 			}
@@ -155,75 +156,58 @@ impl OrderBook {
 
 	/// Remove an order from the book
 	pub fn remove_order(&mut self, side: Side, order_id: &str) -> Option<Order> {
-		let mut result = None;
-
 		match side {
-			Side::Buy => {
-				let mut price_to_remove = None;
-
-				for (price_key, level) in self.bids.iter_mut() {
-					if let Some(order) = level.remove_order(order_id) {
-						result = Some(order);
-						if level.is_empty() {
-							price_to_remove = Some(*price_key);
-						}
-						break;
-					}
-				}
-
-				if let Some(price) = price_to_remove {
-					self.bids.remove(&price);
-				}
-			}
-			Side::Sell => {
-				let mut price_to_remove = None;
-
-				for (price_key, level) in self.asks.iter_mut() {
-					if let Some(order) = level.remove_order(order_id) {
-						result = Some(order);
-						if level.is_empty() {
-							price_to_remove = Some(*price_key);
-						}
-						break;
-					}
-				}
-
-				if let Some(price) = price_to_remove {
-					self.asks.remove(&price);
-				}
-			}
+			Side::Buy => Self::remove_from(&mut self.bids, order_id),
+			Side::Sell => Self::remove_from(&mut self.asks, order_id),
 		}
+	}
 
-		result
+	/// Remove an order from one side, dropping its level if it empties
+	///
+	/// The level is located before it is borrowed mutably, so that only
+	/// that level is copied when it is shared with a clone of the book.
+	fn remove_from<K: Ord + Clone>(
+		levels: &mut OrdMap<K, PriceLevel>,
+		order_id: &str,
+	) -> Option<Order> {
+		let key = levels
+			.iter()
+			.find(|(_, level)| level.orders.iter().any(|o| o.order_id == order_id))
+			.map(|(key, _)| key.clone())?;
+		let level = levels.get_mut(&key)?;
+		let order = level.remove_order(order_id);
+		if level.is_empty() {
+			levels.remove(&key);
+		}
+		order
 	}
 
 	/// Get the best bid price
 	pub fn best_bid(&self) -> Option<u64> {
-		self.bids.first_key_value().map(|(key, _)| key.0)
+		self.bids.get_min().map(|(key, _)| key.0)
 	}
 
 	/// Get the best ask price
 	pub fn best_ask(&self) -> Option<u64> {
-		self.asks.first_key_value().map(|(key, _)| *key)
+		self.asks.get_min().map(|(key, _)| *key)
 	}
 
 	/// Get mutable reference to the best bid level
 	pub fn best_bid_level_mut(&mut self) -> Option<&mut PriceLevel> {
-		self.bids.first_entry().map(|entry| entry.into_mut())
+		let key = self.bids.get_min()?.0;
+		self.bids.get_mut(&key)
 	}
 
 	/// Get mutable reference to the best ask level
 	pub fn best_ask_level_mut(&mut self) -> Option<&mut PriceLevel> {
-		self.asks.first_entry().map(|entry| entry.into_mut())
+		let key = self.asks.get_min()?.0;
+		self.asks.get_mut(&key)
 	}
 
 	/// Get the level depth at a specific price level
 	pub fn get_level_depth(&self, side: Side, price: u64) -> Option<u64> {
 		match side {
-			Side::Buy => self
-				.bids
-				.get(&std::cmp::Reverse(price))
-				.map(|l| l.total_size()),
+			Side::Buy => self.bids.get(&Reverse(price)).map(|l| l.total_size()),
 			Side::Sell => self.asks.get(&price).map(|l| l.total_size()),
 		}
 	}
@@ -235,6 +219,14 @@ impl OrderBook {
 		bid_count + ask_count
 	}
 
+	/// Iterate over all orders in the book, bids then asks, each side
+	/// best price first and in time priority within a level
+	pub fn orders(&self) -> impl Iterator<Item = &Order> {
+		let bids = self.bids.values().flat_map(|l| l.orders.iter());
+		let asks = self.asks.values().flat_map(|l| l.orders.iter());
+		bids.chain(asks)
+	}
+
 	/// Clear all orders from the book
 	pub fn clear(&mut self) {
 		self.bids.clear();
@@ -247,22 +239,24 @@ impl OrderBook {
 	/// It searches both bid and ask sides to locate the order.
 	pub fn find_order_mut(&mut self, order_id: &str) -> Option<&mut Order> {
 		// Search in bids first
-		for level in self.bids.values_mut() {
-			for order in &mut level.orders {
-				if order.order_id == order_id {
-					return Some(order);
-				}
-			}
-		}
-		// Then search in asks
-		for level in self.asks.values_mut() {
-			for order in &mut level.orders {
-				if order.order_id == order_id {
-					return Some(order);
-				}
+		let bid = self
+			.bids
+			.iter()
+			.find(|(_, level)| level.orders.iter().any(|o| o.order_id == order_id))
+			.map(|(key, _)| *key);
+		let level = match bid {
+			Some(key) => self.bids.get_mut(&key)?,
+			// Then search in asks
+			None => {
+				let key = self
+					.asks
+					.iter()
+					.find(|(_, level)| level.orders.iter().any(|o| o.order_id == order_id))
+					.map(|(key, _)| *key)?;
+				self.asks.get_mut(&key)?
 			}
-		}
-		None
+		};
+		level.orders.iter_mut().find(|o| o.order_id == order_id)
 	}
 }
 
@@ -280,6 +274,7 @@ mod tests {
 			remaining_size: size,
 			timestamp: 1000,
 			public_key: "test_key".to_string(),
+			client_order_id: None,
 		}
 	}
 
@@ -342,4 +337,41 @@ mod tests {
 
 		assert_eq!(book.get_level_depth(Side::Buy, 50000), Some(6));
 	}
+
+	#[test]
+	fn test_clone_is_unaffected_by_later_changes() {
+		let mut book = OrderBook::new("BTC-USDT".to_string());
+		book.add_order(create_test_order("order_1", Side::Buy, 50000, 1));
+		book.add_order(create_test_order("order_2", Side::Sell, 51000, 2));
+		book.add_order(create_test_order("order_3", Side::Sell, 51000, 3));
+
+		let view = book.clone();
+
+		book.add_order(create_test_order("order_4", Side::Buy, 50000, 4));
+		book.remove_order(Side::Buy, "order_1");
+		let level = book.best_ask_level_mut().unwrap();
+		level.get_first_order_mut().unwrap().remaining_size = 1;
+		level.remove_first_order();
+		book.find_order_mut("order_3").unwrap().remaining_size = 0;
+
+		let ids = |book: &OrderBook| -> Vec<(String, u64)> {
+			book.orders()
+				.map(|o| (o.order_id.clone(), o.remaining_size))
+				.collect()
+		};
+		assert_eq!(
+			ids(&view),
+			[
+				("order_1".to_string(), 1),
+				("order_2".to_string(), 2),
+				("order_3".to_string(), 3)
+			]
+		);
+		assert_eq!(view.get_level_depth(Side::Buy, 50000), Some(1));
+		assert_eq!(view.get_level_depth(Side::Sell, 51000), Some(5));
+		assert_eq!(
+			ids(&book),
+			[("order_4".to_string(), 4), ("order_3".to_string(), 0)]
+		);
+	}
 }
@@ -0,0 +1,540 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Order state
+//!
+//! [`OrderView`] is a committed-event sink that follows every order from its
+//! first event to completion, for order queries. Open orders are always
+//! kept; filled, cancelled and rejected ones are kept up to a capacity,
+//! oldest completion first out. Orders with a client order ID can also be
+//! looked up by it; the ID resolves to the principal's latest such order.
+//!
+//! Events only carry an order's limit price once it rests, so orders that
+//! fill completely on arrival report a price of 0. Rejected orders report
+//! no side.
+
+use std::{
+	collections::{HashMap, VecDeque},
+	sync::{Arc, RwLock},
+};
+
+use anvil_sdk::types::{OrderStatus, Side};
+
+use crate::{
+	event::{
+		CommittedEventSink, EventStorage, MatchingEvent, RejectReason, SequenceNumber, StorageError,
+	},
+	types::Order,
+};
+
+/// Committed state of one order, in market units
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderRecord {
+	pub order_id: String,
+	pub market: String,
+	/// Owner of the order
+	pub public_key: String,
+	/// Client-assigned order ID
+	pub client_order_id: Option<String>,
+	/// `None` for rejected orders
+	pub side: Option<Side>,
+	/// Limit price; 0 if the order never rested
+	pub price: u64,
+	pub size: u64,
+	pub filled_size: u64,
+	pub remaining_size: u64,
+	pub status: OrderStatus,
+	/// Code and detail of the rejection, for rejected orders
+	pub rejection: Option<(RejectReason, String)>,
+	/// Timestamp of the order's first event
+	pub created_at: u64,
+}
+
+impl OrderRecord {
+	fn new(order_id: &str, market: &str, public_key: &str, created_at: u64) -> Self {
+		Self {
+			order_id: order_id.to_string(),
+			market: market.to_string(),
+			public_key: public_key.to_string(),
+			client_order_id: None,
+			side: None,
+			price: 0,
+			size: 0,
+			filled_size: 0,
+			remaining_size: 0,
+			status: OrderStatus::Pending,
+			rejection: None,
+			created_at,
+		}
+	}
+}
+
+/// In-memory order store keeping every open order and the latest
+/// `capacity` completed ones
+#[derive(Debug, Clone)]
+pub struct OrderStore {
+	capacity: usize,
+	orders: HashMap<String, OrderRecord>,
+	/// Completed order IDs, oldest first
+	completed: VecDeque<String>,
+	/// (public_key, client order ID) -> latest order ID
+	client_orders: HashMap<(String, String), String>,
+}
+
+impl OrderStore {
+	pub fn new(capacity: usize) -> Self {
+		Self {
+			capacity,
+			orders: HashMap::new(),
+			completed: VecDeque::new(),
+			client_orders: HashMap::new(),
+		}
+	}
+
+	pub fn get(&self, order_id: &str) -> Option<&OrderRecord> {
+		self.orders.get(order_id)
+	}
+
+	/// Latest order of `public_key` with `client_order_id`
+	pub fn get_by_client_id(
+		&self,
+		public_key: &str,
+		client_order_id: &str,
+	) -> Option<&OrderRecord> {
+		let order_id = self
+			.client_orders
+			.get(&(public_key.to_string(), client_order_id.to_string()))?;
+		self.orders.get(order_id)
+	}
+
+	/// Add an order resting in an engine snapshot
+	///
+	/// Its history before the snapshot is unknown, so it reports the
+	/// snapshot's sizes and the time it was received.
+	pub fn seed(&mut self, order: &Order) {
+		let mut record = OrderRecord::new(
+			&order.order_id,
+			&order.market,
+			&order.public_key,
+			order.timestamp,
+		);
+		record.side = Some(order.side);
+		record.price = order.price;
+		record.size = order.size;
+		record.filled_size = order.size.saturating_sub(order.remaining_size);
+		record.remaining_size = order.remaining_size;
+		record.status = if record.filled_size > 0 {
+			OrderStatus::PartiallyFilled
+		} else {
+			OrderStatus::Accepted
+		};
+		self.orders.insert(order.order_id.clone(), record);
+		self.index_client_id(&order.order_id, &order.client_order_id, true);
+	}
+
+	/// Record the order's client order ID, if it has one
+	///
+	/// With `displace` unset, the ID keeps resolving to an order that
+	/// already holds it.
+	fn index_client_id(
+		&mut self,
+		order_id: &str,
+		client_order_id: &Option<String>,
+		displace: bool,
+	) {
+		let (Some(client_order_id), Some(order)) = (client_order_id, self.orders.get_mut(order_id))
+		else {
+			return;
+		};
+		order.client_order_id = Some(client_order_id.clone());
+		let entry = self
+			.client_orders
+			.entry((order.public_key.clone(), client_order_id.clone()));
+		if displace {
+			entry.insert_entry(order_id.to_string());
+		} else {
+			entry.or_insert_with(|| order_id.to_string());
+		}
+	}
+
+	/// Apply a committed event
+	pub fn apply(&mut self, event: &MatchingEvent) {
+		match event {
+			MatchingEvent::OrderAccepted {
+				order_id,
+				market,
+				public_key,
+				client_order_id,
+				side,
+				price,
+				size,
+				timestamp,
+				..
+			} => {
+				let order = self
+					.orders
+					.entry(order_id.clone())
+					.or_insert_with(|| OrderRecord::new(order_id, market, public_key, *timestamp));
+				order.side = Some(*side);
+				order.price = *price;
+				// A taker that partially filled rests with what is left
+				order.size = order.filled_size + size;
+				order.remaining_size = *size;
+				if order.status == OrderStatus::Pending {
+					order.status = OrderStatus::Accepted;
+				}
+				self.index_client_id(order_id, client_order_id, true);
+			}
+			MatchingEvent::OrderRejected {
+				order_id,
+				market,
+				public_key,
+				client_order_id,
+				code,
+				reason,
+				timestamp,
+				..
+			} => {
+				let mut order = OrderRecord::new(order_id, market, public_key, *timestamp);
+				order.status = OrderStatus::Rejected;
+				order.rejection = Some((*code, reason.clone()));
+				self.orders.insert(order_id.clone(), order);
+				// A rejected duplicate must not hide the order holding the ID
+				self.index_client_id(order_id, client_order_id, false);
+				self.complete(order_id);
+			}
+			MatchingEvent::TradeExecuted {
+				trade,
+				taker_public_key,
+				taker_client_order_id,
+				timestamp,
+				..
+			} => {
+				self.orders
+					.entry(trade.taker_order_id.clone())
+					.or_insert_with(|| {
+						let mut order = OrderRecord::new(
+							&trade.taker_order_id,
+							&trade.market,
+							taker_public_key,
+							*timestamp,
+						);
+						order.side = Some(trade.side);
+						order
+					});
+				self.index_client_id(&trade.taker_order_id, taker_client_order_id, true);
+			}
+			MatchingEvent::OrderPartiallyFilled {
+				order_id,
+				filled_size,
+				remaining_size,
+				..
+			} => {
+				if let Some(order) = self.orders.get_mut(order_id) {
+					order.filled_size = *filled_size;
+					order.remaining_size = *remaining_size;
+					order.size = filled_size + remaining_size;
+					order.status = OrderStatus::PartiallyFilled;
+				}
+			}
+			MatchingEvent::OrderFilled {
+				order_id,
+				filled_size,
+				..
+			} => {
+				if let Some(order) = self.orders.get_mut(order_id) {
+					order.filled_size = *filled_size;
+					order.size = *filled_size;
+					order.remaining_size = 0;
+					order.status = OrderStatus::Filled;
+					self.complete(order_id);
+				}
+			}
+			MatchingEvent::MakerOrderPartiallyFilled {
+				order_id,
+				filled_size,
+				remaining_size,
+				..
+			} => {
+				if let Some(order) = self.orders.get_mut(order_id) {
+					order.filled_size += filled_size;
+					order.remaining_size = *remaining_size;
+					order.status = OrderStatus::PartiallyFilled;
+				}
+			}
+			MatchingEvent::MakerOrderFilled {
+				order_id,
+				filled_size,
+				..
+			} => {
+				if let Some(order) = self.orders.get_mut(order_id) {
+					order.filled_size += filled_size;
+					order.remaining_size = 0;
+					order.status = OrderStatus::Filled;
+					self.complete(order_id);
+				}
+			}
+			MatchingEvent::OrderCancelled { order_id, .. } => {
+				if let Some(order) = self.orders.get_mut(order_id) {
+					order.remaining_size = 0;
+					order.status = OrderStatus::Cancelled;
+					self.complete(order_id);
+				}
+			}
+			_ => {}
+		}
+	}
+
+	fn complete(&mut self, order_id: &str) {
+		self.completed.push_back(order_id.to_string());
+		while self.completed.len() > self.capacity {
+			if let Some(evicted) = self.completed.pop_front()
+				&& let Some(order) = self.orders.remove(&evicted)
+				&& let Some(client_order_id) = order.client_order_id
+			{
+				let key = (order.public_key, client_order_id);
+				if self.client_orders.get(&key) == Some(&evicted) {
+					self.client_orders.remove(&key);
+				}
+			}
+		}
+	}
+}
+
+/// Shared, committed-event fed view of the order store
+#[derive(Clone)]
+pub struct OrderView {
+	inner: Arc<RwLock<OrderStore>>,
+}
+
+impl OrderView {
+	/// Keep every open order and up to `capacity` completed ones
+	pub fn new(capacity: usize) -> Self {
+		Self {
+			inner: Arc::new(RwLock::new(OrderStore::new(capacity))),
+		}
+	}
+
+	/// Rebuild the store by replaying every committed event from `storage`
+	pub fn rebuild(&self, storage: &dyn EventStorage) -> Result<(), StorageError> {
+		self.rebuild_from([], 0, storage)
+	}
+
+	/// Rebuild the store from the orders resting in an engine snapshot at
+	/// `seq`, then the committed events after it in `storage`
+	///
+	/// Used when events up to the snapshot may have been deleted; orders
+	/// completed before the snapshot are not known.
+	pub fn rebuild_from<'a>(
+		&self,
+		orders: impl IntoIterator<Item = &'a Order>,
+		seq: SequenceNumber,
+		storage: &dyn EventStorage,
+	) -> Result<(), StorageError> {
+		let events = storage.replay_from(seq + 1)?;
+		let mut store = self.inner.write().unwrap();
+		*store = OrderStore::new(store.capacity);
+		for order in orders {
+			store.seed(order);
+		}
+		for event in &events {
+			store.apply(event);
+		}
+		Ok(())
+	}
+
+	pub fn get(&self, order_id: &str) -> Option<OrderRecord> {
+		self.inner.read().unwrap().get(order_id).cloned()
+	}
+
+	pub fn get_by_client_id(&self, public_key: &str, client_order_id: &str) -> Option<OrderRecord> {
+		self.inner
+			.read()
+			.unwrap()
+			.get_by_client_id(public_key, client_order_id)
+			.cloned()
+	}
+}
+
+impl CommittedEventSink for OrderView {
+	fn on_committed(&mut self, events: &[MatchingEvent]) {
+		let mut store = self.inner.write().unwrap();
+		for event in events {
+			store.apply(event);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use anvil_sdk::types::Trade;
+
+	use super::*;
+
+	fn accepted(order_id: &str, side: Side, size: u64) -> MatchingEvent {
+		MatchingEvent::OrderAccepted {
+			seq: 0,
+			order_id: order_id.to_string(),
+			market: "BTC-USDT".to_string(),
+			public_key: "alice".to_string(),
+			client_order_id: None,
+			side,
+			price: 100,
+			size,
+			timestamp: 1,
+		}
+	}
+
+	fn trade(maker: &str, taker: &str, size: u64) -> MatchingEvent {
+		MatchingEvent::TradeExecuted {
+			seq: 0,
+			trade: Trade {
+				trade_id: format!("{}-{}", maker, taker),
+				market: "BTC-USDT".to_string(),
+				price: 100,
+				size,
+				side: Side::Buy,
+				timestamp: 2,
+				maker_order_id: maker.to_string(),
+				taker_order_id: taker.to_string(),
+			},
+			maker_public_key: "alice".to_string(),
+			taker_public_key: "bob".to_string(),
+			maker_client_order_id: None,
+			taker_client_order_id: None,
+			timestamp: 2,
+		}
+	}
+
+	#[test]
+	fn follows_maker_and_taker_through_fills() {
+		let mut store = OrderStore::new(10);
+		store.apply(&accepted("m1", Side::Sell, 5));
+		store.apply(&trade("m1", "t1", 3));
+		store.apply(&MatchingEvent::MakerOrderPartiallyFilled {
+			seq: 0,
+			order_id: "m1".to_string(),
+			market: "BTC-USDT".to_string(),
+			filled_size: 3,
+			remaining_size: 2,
+			timestamp: 2,
+		});
+		store.apply(&MatchingEvent::OrderFilled {
+			seq: 0,
+			order_id: "t1".to_string(),
+			market: "BTC-USDT".to_string(),
+			filled_size: 3,
+			timestamp: 2,
+		});
+
+		let maker = store.get("m1").unwrap();
+		assert_eq!(maker.status, OrderStatus::PartiallyFilled);
+		assert_eq!(
+			(maker.size, maker.filled_size, maker.remaining_size),
+			(5, 3, 2)
+		);
+
+		let taker = store.get("t1").unwrap();
+		assert_eq!(taker.status, OrderStatus::Filled);
+		assert_eq!(taker.public_key, "bob");
+		assert_eq!(taker.side, Some(Side::Buy));
+		assert_eq!((taker.size, taker.filled_size, taker.price), (3, 3, 0));
+	}
+
+	#[test]
+	fn partially_filled_taker_rests_with_its_price() {
+		let mut store = OrderStore::new(10);
+		store.apply(&trade("m1", "t1", 2));
+		store.apply(&MatchingEvent::OrderPartiallyFilled {
+			seq: 0,
+			order_id: "t1".to_string(),
+			market: "BTC-USDT".to_string(),
+			filled_size: 2,
+			remaining_size: 4,
+			timestamp: 2,
+		});
+		store.apply(&accepted("t1", Side::Buy, 4));
+
+		let taker = store.get("t1").unwrap();
+		assert_eq!(taker.status, OrderStatus::PartiallyFilled);
+		assert_eq!(
+			(
+				taker.size,
+				taker.filled_size,
+				taker.remaining_size,
+				taker.price
+			),
+			(6, 2, 4, 100)
+		);
+	}
+
+	#[test]
+	fn evicts_oldest_completed_orders_only() {
+		let mut store = OrderStore::new(1);
+		store.apply(&accepted("open", Side::Buy, 1));
+		for order_id in ["c1", "c2"] {
+			store.apply(&accepted(order_id, Side::Buy, 1));
+			store.apply(&MatchingEvent::OrderCancelled {
+				seq: 0,
+				order_id: order_id.to_string(),
+				market: "BTC-USDT".to_string(),
+				public_key: "alice".to_string(),
+				client_order_id: None,
+				remaining_size: 1,
+				timestamp: 3,
+			});
+		}
+
+		assert!(store.get("open").is_some());
+		assert!(store.get("c1").is_none());
+		assert_eq!(store.get("c2").unwrap().status, OrderStatus::Cancelled);
+	}
+
+	#[test]
+	fn looks_up_latest_order_by_client_id() {
+		let with_id = |mut event: MatchingEvent| {
+			if let MatchingEvent::OrderAccepted {
+				client_order_id, ..
+			} = &mut event
+			{
+				*client_order_id = Some("cid".to_string());
+			}
+			event
+		};
+		let mut store = OrderStore::new(10);
+		store.apply(&with_id(accepted("o1", Side::Buy, 1)));
+		// A duplicate rejected while o1 rests keeps resolving to o1
+		store.apply(&MatchingEvent::OrderRejected {
+			seq: 0,
+			order_id: "o2".to_string(),
+			market: "BTC-USDT".to_string(),
+			public_key: "alice".to_string(),
+			client_order_id: Some("cid".to_string()),
+			code: RejectReason::DuplicateClientOrderId,
+			reason: "Duplicate client order ID".to_string(),
+			timestamp: 1,
+		});
+		assert_eq!(
+			store.get_by_client_id("alice", "cid").unwrap().order_id,
+			"o1"
+		);
+		assert!(store.get_by_client_id("bob", "cid").is_none());
+
+		// Once reused, the ID resolves to the newer order
+		store.apply(&with_id(accepted("o3", Side::Buy, 1)));
+		let order = store.get_by_client_id("alice", "cid").unwrap();
+		assert_eq!(order.order_id, "o3");
+		assert_eq!(order.client_order_id.as_deref(), Some("cid"));
+	}
+}
@@ -14,7 +14,7 @@
 
 use crossbeam::channel::{Receiver, Sender, TryRecvError, TrySendError, bounded};
 
-use crate::types::OrderCommand;
+use crate::types::{EngineCommand, OrderCommand};
 
 /// Ingress Queue abstraction for passing orders from RPC layer to matching loop
 ///
@@ -36,8 +36,8 @@ use crate::types::OrderCommand;
 /// When the queue is full, it signals backpressure to the RPC layer,
 /// which should reject new orders with OVERLOADED status.
 pub struct IngressQueue {
-	sender: Sender<OrderCommand>,
-	receiver: Receiver<OrderCommand>,
+	sender: Sender<EngineCommand>,
+	receiver: Receiver<EngineCommand>,
 }
 
 impl IngressQueue {
@@ -73,7 +73,7 @@ impl IngressQueue {
 /// This can be cloned and shared across multiple threads.
 #[derive(Clone)]
 pub struct QueueSender {
-	sender: Sender<OrderCommand>,
+	sender: Sender<EngineCommand>,
 }
 
 impl QueueSender {
@@ -82,12 +82,29 @@ impl QueueSender {
 	/// Returns error if the queue is full, indicating that the
 	/// matching engine is overloaded and cannot accept new orders.
 	pub fn try_enqueue(&self, cmd: OrderCommand) -> Result<(), QueueError> {
+		self.try_enqueue_command(EngineCommand::Submit(cmd))
+	}
+
+	/// Try to enqueue any engine command (non-blocking)
+	///
+	/// Cancels and balance adjustments share the queue with orders so that
+	/// the matching loop observes a single deterministic command order.
+	pub fn try_enqueue_command(&self, cmd: EngineCommand) -> Result<(), QueueError> {
 		self.sender.try_send(cmd).map_err(|e| match e {
 			TrySendError::Full(_) => QueueError::Full,
 			TrySendError::Disconnected(_) => QueueError::Disconnected,
 		})
 	}
 
+	/// Try to enqueue commands as one contiguous batch (non-blocking)
+	///
+	/// The batch takes a single queue slot and the matching loop processes
+	/// its commands back to back, so no other producer's command can land
+	/// between them.
+	pub fn try_enqueue_batch(&self, commands: Vec<EngineCommand>) -> Result<(), QueueError> {
+		self.try_enqueue_command(EngineCommand::Batch(commands))
+	}
+
 	/// Check if the queue is full
 	pub fn is_full(&self) -> bool {
 		self.sender.is_full()
@@ -98,22 +115,22 @@ impl QueueSender {
 ///
 /// This should NOT be cloned - only one matching loop should consume.
 pub struct QueueReceiver {
-	receiver: Receiver<OrderCommand>,
+	receiver: Receiver<EngineCommand>,
 }
 
 impl QueueReceiver {
-	/// Receive an order command (blocking)
+	/// Receive an engine command (blocking)
 	///
 	/// This is the main method used by the matching loop to dequeue
 	/// the next command. It blocks until a command is available.
-	pub fn recv(&self) -> Result<OrderCommand, QueueError> {
+	pub fn recv(&self) -> Result<EngineCommand, QueueError> {
 		self.receiver.recv().map_err(|_| QueueError::Disconnected)
 	}
 
-	/// Try to receive an order command (non-blocking)
+	/// Try to receive an engine command (non-blocking)
 	///
 	/// Useful for implementing graceful shutdown or polling-based loops.
-	pub fn try_recv(&self) -> Result<OrderCommand, QueueError> {
+	pub fn try_recv(&self) -> Result<EngineCommand, QueueError> {
 		self.receiver.try_recv().map_err(|e| match e {
 			TryRecvError::Empty => QueueError::Empty,
 			TryRecvError::Disconnected => QueueError::Disconnected,
@@ -146,6 +163,8 @@ mod tests {
 			size: 1,
 			timestamp: 1000,
 			public_key: "test_key".to_string(),
+			client_order_id: None,
+			nonce: String::new(),
 		}
 	}
 
@@ -158,7 +177,7 @@ mod tests {
 		sender.try_enqueue(cmd.clone()).unwrap();
 
 		let received = receiver.recv().unwrap();
-		assert_eq!(received.order_id, "order_1");
+		assert_eq!(received.order_id(), Some("order_1"));
 	}
 
 	#[test]
@@ -174,6 +193,29 @@ mod tests {
 		assert!(matches!(result, Err(QueueError::Full)));
 	}
 
+	#[test]
+	fn test_batch_is_one_contiguous_command() {
+		let queue = IngressQueue::new(2);
+		let (sender, receiver) = queue.split();
+
+		let batch = vec![
+			EngineCommand::Submit(create_test_command("order_1")),
+			EngineCommand::Submit(create_test_command("order_2")),
+			EngineCommand::Submit(create_test_command("order_3")),
+		];
+		sender.try_enqueue_batch(batch).unwrap();
+		sender.try_enqueue(create_test_command("order_4")).unwrap();
+
+		match receiver.recv().unwrap() {
+			EngineCommand::Batch(commands) => {
+				let ids: Vec<_> = commands.iter().filter_map(|c| c.order_id()).collect();
+				assert_eq!(ids, ["order_1", "order_2", "order_3"]);
+			}
+			other => panic!("expected a batch, got {:?}", other),
+		}
+		assert_eq!(receiver.recv().unwrap().order_id(), Some("order_4"));
+	}
+
 	#[test]
 	fn test_multiple_senders() {
 		let queue = IngressQueue::new(10);
@@ -188,8 +230,10 @@ mod tests {
 		let received1 = receiver.recv().unwrap();
 		let received2 = receiver.recv().unwrap();
 
-		assert!(received1.order_id == "order_1" || received1.order_id == "order_2");
-		assert!(received2.order_id == "order_1" || received2.order_id == "order_2");
-		assert_ne!(received1.order_id, received2.order_id);
+		let id1 = received1.order_id().unwrap();
+		let id2 = received2.order_id().unwrap();
+		assert!(id1 == "order_1" || id1 == "order_2");
+		assert!(id2 == "order_1" || id2 == "order_2");
+		assert_ne!(id1, id2);
 	}
 }
@@ -30,7 +30,7 @@ use tracing::{info, warn};
 
 use crate::{
 	MatchingEngine,
-	event::EventStorage,
+	event::{EventStorage, SequenceNumber},
 	journal::OrderJournal,
 	snapshot::{SnapshotError, SnapshotStorage},
 };
@@ -65,18 +65,18 @@ impl RecoveryCoordinator {
 		info!("Starting crash recovery...");
 
 		// Phase 1: Try to load latest snapshot
-		let snapshot_seq = match self.snapshot_storage.load_latest() {
-			Ok(snapshot) => {
+		let snapshot_seq = match self.snapshot_storage.open_at_seq(SequenceNumber::MAX) {
+			Ok((metadata, data)) => {
 				info!(
-					"Loaded snapshot at seq={}, size={} bytes",
-					snapshot.metadata.event_seq, snapshot.metadata.size_bytes
+					"Loading snapshot at seq={}, size={} bytes",
+					metadata.event_seq, metadata.size_bytes
 				);
 
 				engine
-					.restore_from_snapshot(snapshot.clone())
+					.restore_from_reader(&metadata, data)
 					.map_err(|e| format!("Failed to restore snapshot: {}", e))?;
 
-				Some(snapshot.metadata.event_seq)
+				Some(metadata.event_seq)
 			}
 			Err(SnapshotError::NotFound) => {
 				info!("No snapshot found, starting from empty state");
@@ -0,0 +1,210 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Follower side of replication: stream the primary's committed events and
+//! apply them to the local engine
+
+use std::{sync::Arc, time::Duration};
+
+use tokio::task::JoinHandle;
+use tonic::transport::Channel;
+use tracing::{info, warn};
+
+use super::{ReplicationRole, ReplicationState};
+use crate::{
+	MatchingEngine,
+	event::MatchingEvent,
+	server::proto::{
+		FenceRequest, ReplicatedEvent, StreamEventsRequest,
+		matching_service_client::MatchingServiceClient,
+	},
+};
+
+/// Follower settings
+#[derive(Debug, Clone)]
+pub struct FollowerConfig {
+	/// Endpoint of the primary engine to replicate
+	pub primary_endpoint: String,
+	/// Most events applied to the engine in one step
+	pub batch_size: usize,
+	/// Delay before reconnecting after the event stream failed
+	pub retry_interval: Duration,
+}
+
+impl FollowerConfig {
+	pub fn new(primary_endpoint: impl Into<String>) -> Self {
+		Self {
+			primary_endpoint: primary_endpoint.into(),
+			batch_size: 512,
+			retry_interval: Duration::from_secs(1),
+		}
+	}
+}
+
+/// Keeps a follower engine in step with the primary until it is promoted or
+/// fenced
+pub struct Follower {
+	engine: Arc<MatchingEngine>,
+	replication: ReplicationState,
+	market: String,
+	config: FollowerConfig,
+}
+
+impl Follower {
+	pub fn new(
+		engine: Arc<MatchingEngine>,
+		replication: ReplicationState,
+		market: String,
+		config: FollowerConfig,
+	) -> Self {
+		Self {
+			engine,
+			replication,
+			market,
+			config,
+		}
+	}
+
+	pub fn start(self) -> JoinHandle<()> {
+		tokio::spawn(self.run())
+	}
+
+	/// Replicate, reconnecting after failures, while the engine is a follower
+	///
+	/// Once promoted, fence the former primary so that it stops accepting
+	/// orders if it is still up.
+	async fn run(self) {
+		let mut status = self.replication.subscribe();
+		let is_follower = |status: &super::RoleStatus| status.role == ReplicationRole::Follower;
+
+		while is_follower(&status.borrow_and_update()) {
+			tokio::select! {
+				result = self.follow() => {
+					if let Err(e) = result {
+						warn!(
+							target: "replication",
+							primary = %self.config.primary_endpoint,
+							applied_sequence = self.replication.applied_sequence(),
+							error = %e,
+							"Replication interrupted"
+						);
+					}
+				}
+				_ = status.wait_for(|status| !is_follower(status)) => break,
+			}
+			tokio::select! {
+				_ = tokio::time::sleep(self.config.retry_interval) => {}
+				_ = status.changed() => {}
+			}
+		}
+
+		let status = self.replication.status();
+		info!(
+			target: "replication",
+			role = ?status.role,
+			epoch = status.epoch,
+			applied_sequence = self.replication.applied_sequence(),
+			"Replication stopped"
+		);
+		if status.role == ReplicationRole::Primary {
+			self.fence_primary(status.epoch).await;
+		}
+	}
+
+	/// Apply the primary's events from the next sequence until the stream
+	/// fails or the engine stops being a follower
+	async fn follow(&self) -> Result<(), String> {
+		let mut client = MatchingServiceClient::connect(self.config.primary_endpoint.clone())
+			.await
+			.map_err(|e| format!("Connection failed: {}", e))?;
+		let from_sequence = self.replication.applied_sequence() + 1;
+		let mut stream = client
+			.stream_events(StreamEventsRequest {
+				market: self.market.clone(),
+				from_sequence,
+			})
+			.await
+			.map_err(|status| status.message().to_string())?
+			.into_inner();
+		info!(
+			target: "replication",
+			primary = %self.config.primary_endpoint,
+			from_sequence,
+			"Replicating committed events"
+		);
+
+		while let Some(first) = stream
+			.message()
+			.await
+			.map_err(|status| status.message().to_string())?
+		{
+			// Apply whatever else has already arrived along with it
+			let mut batch = vec![first];
+			while batch.len() < self.config.batch_size {
+				match tokio::time::timeout(Duration::ZERO, stream.message()).await {
+					Ok(Ok(Some(message))) => batch.push(message),
+					Ok(Err(status)) => return Err(status.message().to_string()),
+					Ok(Ok(None)) | Err(_) => break,
+				}
+			}
+			let primary_sequence = batch.last().map_or(0, |message| message.last_sequence);
+			let events = batch.iter().map(decode).collect::<Result<Vec<_>, _>>()?;
+
+			let Some(_apply) = self.replication.begin_apply().await else {
+				return Ok(());
+			};
+			let applied = self.engine.apply_replicated(events).await?;
+			self.replication.record_progress(applied, primary_sequence);
+		}
+		Err("Primary ended the event stream".to_string())
+	}
+
+	/// Tell the former primary about the new epoch, best effort
+	async fn fence_primary(&self, epoch: u64) {
+		let result = async {
+			let channel = Channel::from_shared(self.config.primary_endpoint.clone())
+				.map_err(|e| e.to_string())?
+				.connect_timeout(self.config.retry_interval)
+				.connect()
+				.await
+				.map_err(|e| e.to_string())?;
+			MatchingServiceClient::new(channel)
+				.fence(FenceRequest { epoch })
+				.await
+				.map_err(|status| status.message().to_string())
+		}
+		.await;
+
+		match result {
+			Ok(_) => info!(
+				target: "replication",
+				primary = %self.config.primary_endpoint,
+				epoch,
+				"Fenced the former primary"
+			),
+			Err(e) => warn!(
+				target: "replication",
+				primary = %self.config.primary_endpoint,
+				epoch,
+				error = %e,
+				"Could not fence the former primary"
+			),
+		}
+	}
+}
+
+fn decode(message: &ReplicatedEvent) -> Result<MatchingEvent, String> {
+	serde_json::from_slice(&message.event)
+		.map_err(|e| format!("Invalid replicated event {}: {}", message.sequence, e))
+}
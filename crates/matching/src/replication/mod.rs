@@ -0,0 +1,284 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Hot-standby replication of the matching engine
+//!
+//! A primary engine commits events as usual and serves them over the
+//! `StreamEvents` RPC. A follower engine runs a [`Follower`] instead of
+//! taking orders: it streams the primary's committed events and applies them
+//! through the replay path, then hands them to its own event writer with
+//! their sequences unchanged. Its order book, ledger, storage and
+//! committed-event sinks stay warm, ready to take over.
+//!
+//! Roles are fenced by an epoch:
+//!
+//! - **Primary**: accepts orders at its epoch
+//! - **Follower**: refuses orders; `Promote` with a greater epoch makes it
+//!   the primary once the batch being applied is done
+//! - **Fenced**: refuses orders for good. `Fence` with a greater epoch
+//!   demotes a primary (or stops a follower) that a newer primary replaced;
+//!   a promoted follower fences the engine it followed.
+//!
+//! A fenced engine's history may have diverged from the new primary's, so it
+//! rejoins only as a freshly started follower. Its event writer goes through
+//! a [`FencedEventStorage`], which stamps batches with the epoch and refuses
+//! them once the engine is fenced.
+
+mod follower;
+mod storage;
+
+use std::sync::{
+	Arc,
+	atomic::{AtomicU64, Ordering},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::{Mutex, MutexGuard, watch};
+
+use crate::event::SequenceNumber;
+pub use follower::{Follower, FollowerConfig};
+pub use storage::FencedEventStorage;
+
+/// Role of an engine in replication
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplicationRole {
+	Primary,
+	Follower,
+	Fenced,
+}
+
+/// Role of an engine and the epoch it holds it at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoleStatus {
+	pub role: ReplicationRole,
+	pub epoch: u64,
+}
+
+/// Error types for role changes and fenced writes
+#[derive(Debug, Error)]
+pub enum ReplicationError {
+	#[error("Engine is not the primary ({role:?} at epoch {epoch})")]
+	NotPrimary { role: ReplicationRole, epoch: u64 },
+	#[error("Only a follower can be promoted ({0:?})")]
+	NotFollower(ReplicationRole),
+	#[error("Epoch {requested} is not newer than the engine's epoch {current}")]
+	StaleEpoch { requested: u64, current: u64 },
+}
+
+/// Shared replication role, epoch and follower progress of an engine
+#[derive(Clone)]
+pub struct ReplicationState {
+	inner: Arc<Inner>,
+}
+
+struct Inner {
+	status: watch::Sender<RoleStatus>,
+	/// Held while a follower applies a batch, so that a promotion never
+	/// interleaves replicated events with the engine's own
+	apply: Mutex<()>,
+	applied_sequence: AtomicU64,
+	primary_sequence: AtomicU64,
+}
+
+impl ReplicationState {
+	fn new(role: ReplicationRole, epoch: u64) -> Self {
+		Self {
+			inner: Arc::new(Inner {
+				status: watch::channel(RoleStatus { role, epoch }).0,
+				apply: Mutex::new(()),
+				applied_sequence: AtomicU64::new(0),
+				primary_sequence: AtomicU64::new(0),
+			}),
+		}
+	}
+
+	/// Primary engine at `epoch`
+	pub fn primary(epoch: u64) -> Self {
+		Self::new(ReplicationRole::Primary, epoch)
+	}
+
+	/// Follower engine at `epoch`
+	pub fn follower(epoch: u64) -> Self {
+		Self::new(ReplicationRole::Follower, epoch)
+	}
+
+	pub fn status(&self) -> RoleStatus {
+		*self.inner.status.borrow()
+	}
+
+	/// Follow role changes
+	pub fn subscribe(&self) -> watch::Receiver<RoleStatus> {
+		self.inner.status.subscribe()
+	}
+
+	/// The epoch to accept a write at, if the engine is the primary
+	pub fn check_writable(&self) -> Result<u64, ReplicationError> {
+		match self.status() {
+			RoleStatus {
+				role: ReplicationRole::Primary,
+				epoch,
+			} => Ok(epoch),
+			RoleStatus { role, epoch } => Err(ReplicationError::NotPrimary { role, epoch }),
+		}
+	}
+
+	/// Make a follower the primary at `epoch`
+	///
+	/// Waits for the batch being applied, if any; no replicated event is
+	/// applied afterwards.
+	pub async fn promote(&self, epoch: u64) -> Result<(), ReplicationError> {
+		let _apply = self.inner.apply.lock().await;
+		let status = self.status();
+		if status.role != ReplicationRole::Follower {
+			return Err(ReplicationError::NotFollower(status.role));
+		}
+		if epoch <= status.epoch {
+			return Err(ReplicationError::StaleEpoch {
+				requested: epoch,
+				current: status.epoch,
+			});
+		}
+		self.inner.status.send_replace(RoleStatus {
+			role: ReplicationRole::Primary,
+			epoch,
+		});
+		Ok(())
+	}
+
+	/// Fence the engine: a primary at `epoch` exists elsewhere
+	pub async fn fence(&self, epoch: u64) -> Result<(), ReplicationError> {
+		let _apply = self.inner.apply.lock().await;
+		let status = self.status();
+		if epoch <= status.epoch {
+			return Err(ReplicationError::StaleEpoch {
+				requested: epoch,
+				current: status.epoch,
+			});
+		}
+		self.inner.status.send_replace(RoleStatus {
+			role: ReplicationRole::Fenced,
+			epoch,
+		});
+		Ok(())
+	}
+
+	/// Fence a primary that lost its leadership without knowing its successor
+	///
+	/// The engine keeps its epoch; a later `Fence` may still raise it.
+	pub async fn step_down(&self) {
+		let _apply = self.inner.apply.lock().await;
+		self.inner.status.send_if_modified(|status| {
+			let primary = status.role == ReplicationRole::Primary;
+			if primary {
+				status.role = ReplicationRole::Fenced;
+			}
+			primary
+		});
+	}
+
+	/// Hold off role changes while replicated events are applied
+	///
+	/// Returns `None` once the engine is no longer a follower.
+	pub(crate) async fn begin_apply(&self) -> Option<MutexGuard<'_, ()>> {
+		let guard = self.inner.apply.lock().await;
+		(self.status().role == ReplicationRole::Follower).then_some(guard)
+	}
+
+	/// Last event sequence applied from the primary
+	pub fn applied_sequence(&self) -> SequenceNumber {
+		self.inner.applied_sequence.load(Ordering::Acquire)
+	}
+
+	/// Events the primary had committed that are not applied yet, as of the
+	/// last event received
+	pub fn lag(&self) -> u64 {
+		self.inner
+			.primary_sequence
+			.load(Ordering::Acquire)
+			.saturating_sub(self.applied_sequence())
+	}
+
+	/// Record events applied up to `applied`, with the primary at `primary`
+	///
+	/// Also used to start a follower from history it already holds.
+	pub fn record_progress(&self, applied: SequenceNumber, primary: SequenceNumber) {
+		self.inner
+			.applied_sequence
+			.store(applied, Ordering::Release);
+		self.inner
+			.primary_sequence
+			.fetch_max(primary, Ordering::AcqRel);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[tokio::test]
+	async fn promotion_and_fencing_need_a_newer_epoch() {
+		let follower = ReplicationState::follower(1);
+		assert!(follower.check_writable().is_err());
+		assert!(matches!(
+			follower.promote(1).await,
+			Err(ReplicationError::StaleEpoch { .. })
+		));
+		follower.promote(2).await.unwrap();
+		assert_eq!(follower.check_writable().unwrap(), 2);
+		assert!(matches!(
+			follower.promote(3).await,
+			Err(ReplicationError::NotFollower(ReplicationRole::Primary))
+		));
+		assert!(follower.begin_apply().await.is_none());
+
+		assert!(follower.fence(2).await.is_err());
+		follower.fence(3).await.unwrap();
+		assert!(matches!(
+			follower.check_writable(),
+			Err(ReplicationError::NotPrimary {
+				role: ReplicationRole::Fenced,
+				epoch: 3
+			})
+		));
+	}
+
+	#[tokio::test]
+	async fn stepping_down_fences_only_a_primary() {
+		let follower = ReplicationState::follower(1);
+		follower.step_down().await;
+		assert_eq!(follower.status().role, ReplicationRole::Follower);
+
+		follower.promote(2).await.unwrap();
+		follower.step_down().await;
+		assert_eq!(
+			follower.status(),
+			RoleStatus {
+				role: ReplicationRole::Fenced,
+				epoch: 2
+			}
+		);
+	}
+
+	#[test]
+	fn lag_is_measured_against_the_primary() {
+		let follower = ReplicationState::follower(0);
+		follower.record_progress(5, 9);
+		assert_eq!(follower.lag(), 4);
+		// An older primary sequence from a resumed stream does not count
+		follower.record_progress(9, 7);
+		assert_eq!(follower.lag(), 0);
+	}
+}
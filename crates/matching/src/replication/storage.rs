@@ -0,0 +1,130 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{ReplicationRole, ReplicationState};
+use crate::event::{EventBatch, EventStorage, MatchingEvent, SequenceNumber, StorageError};
+
+/// Event storage that only the primary or a follower may append to
+///
+/// Every batch is stamped with the engine's current epoch, and no batch is
+/// appended once the engine is fenced. Events already in storage are left
+/// out of a batch, so that history the engine replays from storage and
+/// hands to its event writer again is not written twice. Only the primary
+/// truncates storage, since a follower may share it with the primary.
+pub struct FencedEventStorage {
+	inner: Box<dyn EventStorage>,
+	replication: ReplicationState,
+}
+
+impl FencedEventStorage {
+	pub fn new(inner: Box<dyn EventStorage>, replication: ReplicationState) -> Self {
+		Self { inner, replication }
+	}
+}
+
+impl EventStorage for FencedEventStorage {
+	fn append_batch(&mut self, mut batch: EventBatch) -> Result<SequenceNumber, StorageError> {
+		let status = self.replication.status();
+		if status.role == ReplicationRole::Fenced {
+			return Err(StorageError::NotLeader(format!(
+				"engine was fenced at epoch {}",
+				status.epoch
+			)));
+		}
+
+		let last_sequence = self.inner.last_sequence();
+		batch.events.retain(|e| e.sequence() > last_sequence);
+		if batch.is_empty() {
+			return Ok(last_sequence);
+		}
+		self.inner.append_batch(batch.with_epoch(status.epoch))
+	}
+
+	fn replay_from(&self, from_seq: SequenceNumber) -> Result<Vec<MatchingEvent>, StorageError> {
+		self.inner.replay_from(from_seq)
+	}
+
+	fn last_sequence(&self) -> SequenceNumber {
+		self.inner.last_sequence()
+	}
+
+	fn event_count(&self) -> usize {
+		self.inner.event_count()
+	}
+
+	fn fence(&mut self, epoch: u64) -> Result<(), StorageError> {
+		self.inner.fence(epoch)
+	}
+
+	fn truncate_before(&mut self, before_seq: SequenceNumber) -> Result<usize, StorageError> {
+		let status = self.replication.status();
+		if status.role != ReplicationRole::Primary {
+			return Err(StorageError::NotLeader(format!(
+				"only the primary truncates events, engine is {:?}",
+				status.role
+			)));
+		}
+		self.inner.truncate_before(before_seq)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::event::MemoryEventStorage;
+	use anvil_sdk::types::Side;
+
+	fn create_test_event(seq: u64) -> MatchingEvent {
+		MatchingEvent::OrderAccepted {
+			seq,
+			order_id: format!("order_{}", seq),
+			market: "BTC-USDT".to_string(),
+			public_key: "test_key".to_string(),
+			client_order_id: None,
+			side: Side::Buy,
+			price: 50000,
+			size: 1,
+			timestamp: 1000,
+		}
+	}
+
+	fn batch(seqs: std::ops::RangeInclusive<u64>) -> EventBatch {
+		EventBatch::new(seqs.map(create_test_event).collect())
+	}
+
+	#[tokio::test]
+	async fn stamps_the_epoch_and_refuses_writes_once_fenced() {
+		let replication = ReplicationState::follower(1);
+		let mut storage =
+			FencedEventStorage::new(Box::new(MemoryEventStorage::new()), replication.clone());
+		assert_eq!(storage.append_batch(batch(2..=3)).unwrap(), 3);
+
+		// Replayed history is skipped, new events are written
+		replication.promote(2).await.unwrap();
+		assert_eq!(storage.append_batch(batch(2..=5)).unwrap(), 5);
+		assert_eq!(storage.event_count(), 4);
+		assert_eq!(storage.truncate_before(4).unwrap(), 2);
+
+		replication.step_down().await;
+		assert!(matches!(
+			storage.append_batch(batch(6..=6)),
+			Err(StorageError::NotLeader(_))
+		));
+		assert_eq!(storage.last_sequence(), 5);
+		assert!(matches!(
+			storage.truncate_before(5),
+			Err(StorageError::NotLeader(_))
+		));
+	}
+}
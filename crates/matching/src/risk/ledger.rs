@@ -0,0 +1,549 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::{Arc, RwLock};
+
+use anvil_sdk::types::Side;
+use imbl::OrdMap;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::warn;
+
+use crate::{
+	event::{CommittedEventSink, EventStorage, MatchingEvent, SequenceNumber, StorageError},
+	types::{BalanceAdjustment, BalanceCommand},
+};
+
+/// Error types for ledger checks
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum LedgerError {
+	#[error("Insufficient {asset} balance: required {required}, available {available}")]
+	InsufficientBalance {
+		asset: String,
+		required: u128,
+		available: u128,
+	},
+	#[error("Unknown asset: {0}")]
+	UnknownAsset(String),
+}
+
+/// Balance of one asset held by one principal
+///
+/// Amounts are integer asset units: base amounts are in size units and quote
+/// amounts in notional units (price units x size units).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Balance {
+	/// Funds free to back new orders or withdrawals
+	pub available: u128,
+	/// Funds reserved by resting orders
+	pub locked: u128,
+}
+
+/// Funds reserved by a resting order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct OrderLock {
+	public_key: String,
+	side: Side,
+	price: u64,
+	remaining_size: u64,
+}
+
+/// Off-chain account ledger for a single market
+///
+/// Tracks available and locked balances per principal for the market's base
+/// and quote assets. The ledger is mutated exclusively through
+/// [`Ledger::apply`], so replaying the committed event stream rebuilds it
+/// exactly:
+///
+/// - `BalanceDeposited` / `BalanceWithdrawn` credit or debit available funds
+/// - `OrderAccepted` moves the resting order's requirement from available to
+///   locked
+/// - `TradeExecuted` settles both sides: the maker pays from its lock, the
+///   taker from available funds, and each receives the counter asset
+/// - `OrderCancelled` / `MakerOrderFilled` release whatever is still locked
+///
+/// Balances are scoped to the matching engine instance; moving funds between
+/// markets is a withdrawal on one engine and a deposit on another.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ledger {
+	base_asset: String,
+	quote_asset: String,
+	/// principal -> asset -> balance
+	balances: OrdMap<String, OrdMap<String, Balance>>,
+	/// order_id -> funds reserved by that resting order
+	locks: OrdMap<String, OrderLock>,
+}
+
+impl Ledger {
+	pub fn new(base_asset: impl Into<String>, quote_asset: impl Into<String>) -> Self {
+		Self {
+			base_asset: base_asset.into(),
+			quote_asset: quote_asset.into(),
+			balances: OrdMap::new(),
+			locks: OrdMap::new(),
+		}
+	}
+
+	pub fn base_asset(&self) -> &str {
+		&self.base_asset
+	}
+
+	pub fn quote_asset(&self) -> &str {
+		&self.quote_asset
+	}
+
+	/// Asset and amount an order of `size` at `price` must be able to lock
+	pub fn required_funds(&self, side: Side, price: u64, size: u64) -> (&str, u128) {
+		match side {
+			Side::Buy => (&self.quote_asset, price as u128 * size as u128),
+			Side::Sell => (&self.base_asset, size as u128),
+		}
+	}
+
+	/// Balance of `asset` held by `public_key`
+	pub fn balance(&self, public_key: &str, asset: &str) -> Balance {
+		self.balances
+			.get(public_key)
+			.and_then(|assets| assets.get(asset))
+			.copied()
+			.unwrap_or_default()
+	}
+
+	/// All balances held by `public_key`, sorted by asset
+	pub fn balances(&self, public_key: &str) -> Vec<(String, Balance)> {
+		self.balances
+			.get(public_key)
+			.map(|assets| assets.iter().map(|(a, b)| (a.clone(), *b)).collect())
+			.unwrap_or_default()
+	}
+
+	/// Check that `public_key` can fund a new order
+	pub fn check_order(
+		&self,
+		public_key: &str,
+		side: Side,
+		price: u64,
+		size: u64,
+	) -> Result<(), LedgerError> {
+		let (asset, required) = self.required_funds(side, price, size);
+		let available = self.balance(public_key, asset).available;
+		if required > available {
+			return Err(LedgerError::InsufficientBalance {
+				asset: asset.to_string(),
+				required,
+				available,
+			});
+		}
+		Ok(())
+	}
+
+	/// Check that a deposit or withdrawal can be applied
+	pub fn check_adjustment(&self, cmd: &BalanceCommand) -> Result<(), LedgerError> {
+		if cmd.asset != self.base_asset && cmd.asset != self.quote_asset {
+			return Err(LedgerError::UnknownAsset(cmd.asset.clone()));
+		}
+		if cmd.adjustment == BalanceAdjustment::Withdrawal {
+			let available = self.balance(&cmd.public_key, &cmd.asset).available;
+			if cmd.amount as u128 > available {
+				return Err(LedgerError::InsufficientBalance {
+					asset: cmd.asset.clone(),
+					required: cmd.amount as u128,
+					available,
+				});
+			}
+		}
+		Ok(())
+	}
+
+	/// Apply a matching event to the ledger
+	pub fn apply(&mut self, event: &MatchingEvent) {
+		match event {
+			MatchingEvent::BalanceDeposited {
+				public_key,
+				asset,
+				amount,
+				..
+			} => {
+				let balance = self.balance_mut(public_key, asset);
+				balance.available = balance.available.saturating_add(*amount as u128);
+			}
+			MatchingEvent::BalanceWithdrawn {
+				public_key,
+				asset,
+				amount,
+				..
+			} => {
+				self.debit_available(public_key, asset, *amount as u128);
+			}
+			MatchingEvent::OrderAccepted {
+				order_id,
+				public_key,
+				side,
+				price,
+				size,
+				..
+			} => {
+				let (asset, amount) = self.required_funds(*side, *price, *size);
+				let asset = asset.to_string();
+				let balance = self.balance_mut(public_key, &asset);
+				if balance.available < amount {
+					warn!(
+						target: "ledger",
+						order_id = %order_id,
+						"Locking more {} than available", asset
+					);
+				}
+				balance.available = balance.available.saturating_sub(amount);
+				balance.locked = balance.locked.saturating_add(amount);
+				self.locks.insert(
+					order_id.clone(),
+					OrderLock {
+						public_key: public_key.clone(),
+						side: *side,
+						price: *price,
+						remaining_size: *size,
+					},
+				);
+			}
+			MatchingEvent::TradeExecuted {
+				trade,
+				maker_public_key,
+				taker_public_key,
+				..
+			} => {
+				let notional = trade.price as u128 * trade.size as u128;
+				let (buyer, buy_order, seller, sell_order) = match trade.side {
+					Side::Buy => (
+						taker_public_key,
+						&trade.taker_order_id,
+						maker_public_key,
+						&trade.maker_order_id,
+					),
+					Side::Sell => (
+						maker_public_key,
+						&trade.maker_order_id,
+						taker_public_key,
+						&trade.taker_order_id,
+					),
+				};
+				let (base, quote) = (self.base_asset.clone(), self.quote_asset.clone());
+
+				self.settle_leg(buyer, buy_order, &quote, notional, trade.size);
+				self.settle_leg(seller, sell_order, &base, trade.size as u128, trade.size);
+
+				let balance = self.balance_mut(buyer, &base);
+				balance.available = balance.available.saturating_add(trade.size as u128);
+				let balance = self.balance_mut(seller, &quote);
+				balance.available = balance.available.saturating_add(notional);
+			}
+			MatchingEvent::OrderCancelled { order_id, .. }
+			| MatchingEvent::MakerOrderFilled { order_id, .. } => {
+				self.release(order_id);
+			}
+			MatchingEvent::OrderRejected { .. }
+			| MatchingEvent::OrderFilled { .. }
+			| MatchingEvent::OrderPartiallyFilled { .. }
+			| MatchingEvent::MakerOrderPartiallyFilled { .. }
+			| MatchingEvent::CancelRejected { .. }
+			| MatchingEvent::BalanceRejected { .. } => {}
+		}
+	}
+
+	/// Pay `amount` of `asset` for `filled` units of `order_id`
+	///
+	/// Resting orders pay out of their lock (refunding any surplus reserved
+	/// at a better limit price); incoming orders pay from available funds.
+	fn settle_leg(
+		&mut self,
+		public_key: &str,
+		order_id: &str,
+		asset: &str,
+		amount: u128,
+		filled: u64,
+	) {
+		let Some(lock) = self.locks.get_mut(order_id) else {
+			self.debit_available(public_key, asset, amount);
+			return;
+		};
+
+		let filled = filled.min(lock.remaining_size);
+		lock.remaining_size -= filled;
+		let reserved = match lock.side {
+			Side::Buy => lock.price as u128 * filled as u128,
+			Side::Sell => filled as u128,
+		};
+		let owner = lock.public_key.clone();
+
+		let balance = self.balance_mut(&owner, asset);
+		balance.locked = balance.locked.saturating_sub(reserved);
+		balance.available = balance
+			.available
+			.saturating_add(reserved.saturating_sub(amount));
+	}
+
+	/// Return the remaining lock of `order_id` to available funds
+	fn release(&mut self, order_id: &str) {
+		let Some(lock) = self.locks.remove(order_id) else {
+			return;
+		};
+		let (asset, amount) = self.required_funds(lock.side, lock.price, lock.remaining_size);
+		let asset = asset.to_string();
+		let balance = self.balance_mut(&lock.public_key, &asset);
+		balance.locked = balance.locked.saturating_sub(amount);
+		balance.available = balance.available.saturating_add(amount);
+	}
+
+	fn debit_available(&mut self, public_key: &str, asset: &str, amount: u128) {
+		let balance = self.balance_mut(public_key, asset);
+		if balance.available < amount {
+			warn!(
+				target: "ledger",
+				public_key = %public_key,
+				"Debiting more {} than available", asset
+			);
+		}
+		balance.available = balance.available.saturating_sub(amount);
+	}
+
+	fn balance_mut(&mut self, public_key: &str, asset: &str) -> &mut Balance {
+		self.balances
+			.entry(public_key.to_string())
+			.or_default()
+			.entry(asset.to_string())
+			.or_default()
+	}
+}
+
+/// Read-side ledger built from committed events
+///
+/// Registered as a [`CommittedEventSink`] on the `EventWriter`, so it only
+/// reflects events that are durably stored. The RPC layer uses it to answer
+/// balance queries and to fast-fail orders that cannot possibly be funded;
+/// the matching loop keeps its own authoritative copy.
+#[derive(Clone)]
+pub struct LedgerView {
+	inner: Arc<RwLock<Ledger>>,
+}
+
+impl LedgerView {
+	pub fn new(ledger: Ledger) -> Self {
+		Self {
+			inner: Arc::new(RwLock::new(ledger)),
+		}
+	}
+
+	/// Rebuild the view by replaying every committed event from `storage`
+	pub fn rebuild(&self, storage: &dyn EventStorage) -> Result<(), StorageError> {
+		self.rebuild_from(None, 0, storage)
+	}
+
+	/// Rebuild the view from the ledger of a snapshot at `seq`, then the
+	/// committed events after it in `storage`
+	///
+	/// Used when events up to the snapshot may have been deleted. Like the
+	/// engine, a snapshot without a ledger starts from an empty one.
+	pub fn rebuild_from(
+		&self,
+		snapshot: Option<Ledger>,
+		seq: SequenceNumber,
+		storage: &dyn EventStorage,
+	) -> Result<(), StorageError> {
+		let events = storage.replay_from(seq + 1)?;
+		let mut ledger = self.inner.write().unwrap();
+		*ledger = snapshot
+			.unwrap_or_else(|| Ledger::new(ledger.base_asset.clone(), ledger.quote_asset.clone()));
+		for event in &events {
+			ledger.apply(event);
+		}
+		Ok(())
+	}
+
+	pub fn balances(&self, public_key: &str) -> Vec<(String, Balance)> {
+		self.inner.read().unwrap().balances(public_key)
+	}
+
+	pub fn check_order(
+		&self,
+		public_key: &str,
+		side: Side,
+		price: u64,
+		size: u64,
+	) -> Result<(), LedgerError> {
+		self.inner
+			.read()
+			.unwrap()
+			.check_order(public_key, side, price, size)
+	}
+
+	pub fn check_adjustment(&self, cmd: &BalanceCommand) -> Result<(), LedgerError> {
+		self.inner.read().unwrap().check_adjustment(cmd)
+	}
+}
+
+impl CommittedEventSink for LedgerView {
+	fn on_committed(&mut self, events: &[MatchingEvent]) {
+		let mut ledger = self.inner.write().unwrap();
+		for event in events {
+			ledger.apply(event);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anvil_sdk::types::Trade;
+
+	fn deposit(public_key: &str, asset: &str, amount: u64) -> MatchingEvent {
+		MatchingEvent::BalanceDeposited {
+			seq: 0,
+			market: "BTC-USDT".to_string(),
+			public_key: public_key.to_string(),
+			asset: asset.to_string(),
+			amount,
+			timestamp: 0,
+		}
+	}
+
+	fn accepted(
+		order_id: &str,
+		public_key: &str,
+		side: Side,
+		price: u64,
+		size: u64,
+	) -> MatchingEvent {
+		MatchingEvent::OrderAccepted {
+			seq: 0,
+			order_id: order_id.to_string(),
+			market: "BTC-USDT".to_string(),
+			public_key: public_key.to_string(),
+			client_order_id: None,
+			side,
+			price,
+			size,
+			timestamp: 0,
+		}
+	}
+
+	fn trade(
+		maker: (&str, &str),
+		taker: (&str, &str),
+		side: Side,
+		price: u64,
+		size: u64,
+	) -> MatchingEvent {
+		MatchingEvent::TradeExecuted {
+			seq: 0,
+			trade: Trade {
+				trade_id: "t".to_string(),
+				market: "BTC-USDT".to_string(),
+				price,
+				size,
+				side,
+				timestamp: 0,
+				maker_order_id: maker.0.to_string(),
+				taker_order_id: taker.0.to_string(),
+			},
+			maker_public_key: maker.1.to_string(),
+			taker_public_key: taker.1.to_string(),
+			maker_client_order_id: None,
+			taker_client_order_id: None,
+			timestamp: 0,
+		}
+	}
+
+	fn balance(available: u128, locked: u128) -> Balance {
+		Balance { available, locked }
+	}
+
+	#[test]
+	fn accepted_order_locks_funds_and_cancel_releases_them() {
+		let mut ledger = Ledger::new("BTC", "USDT");
+		ledger.apply(&deposit("alice", "USDT", 1_000));
+
+		assert!(ledger.check_order("alice", Side::Buy, 100, 11).is_err());
+		ledger.check_order("alice", Side::Buy, 100, 10).unwrap();
+
+		ledger.apply(&accepted("o1", "alice", Side::Buy, 100, 4));
+		assert_eq!(ledger.balance("alice", "USDT"), balance(600, 400));
+		assert!(ledger.check_order("alice", Side::Buy, 100, 7).is_err());
+
+		ledger.apply(&MatchingEvent::OrderCancelled {
+			seq: 0,
+			order_id: "o1".to_string(),
+			market: "BTC-USDT".to_string(),
+			public_key: "alice".to_string(),
+			client_order_id: None,
+			remaining_size: 4,
+			timestamp: 0,
+		});
+		assert_eq!(ledger.balance("alice", "USDT"), balance(1_000, 0));
+	}
+
+	#[test]
+	fn trade_settles_maker_lock_and_taker_available() {
+		let mut ledger = Ledger::new("BTC", "USDT");
+		ledger.apply(&deposit("maker", "BTC", 5));
+		ledger.apply(&deposit("taker", "USDT", 1_000));
+
+		// Maker rests a sell of 5 @ 100, taker buys 3
+		ledger.apply(&accepted("m1", "maker", Side::Sell, 100, 5));
+		ledger.apply(&trade(("m1", "maker"), ("t1", "taker"), Side::Buy, 100, 3));
+
+		assert_eq!(ledger.balance("maker", "BTC"), balance(0, 2));
+		assert_eq!(ledger.balance("maker", "USDT"), balance(300, 0));
+		assert_eq!(ledger.balance("taker", "BTC"), balance(3, 0));
+		assert_eq!(ledger.balance("taker", "USDT"), balance(700, 0));
+
+		// Remaining 2 is filled by another taker; the lock is fully consumed
+		ledger.apply(&deposit("other", "USDT", 200));
+		ledger.apply(&trade(("m1", "maker"), ("t2", "other"), Side::Buy, 100, 2));
+		ledger.apply(&MatchingEvent::MakerOrderFilled {
+			seq: 0,
+			order_id: "m1".to_string(),
+			market: "BTC-USDT".to_string(),
+			filled_size: 2,
+			timestamp: 0,
+		});
+		assert_eq!(ledger.balance("maker", "BTC"), balance(0, 0));
+		assert_eq!(ledger.balance("maker", "USDT"), balance(500, 0));
+	}
+
+	#[test]
+	fn withdrawal_requires_available_funds() {
+		let mut ledger = Ledger::new("BTC", "USDT");
+		ledger.apply(&deposit("alice", "BTC", 2));
+		ledger.apply(&accepted("o1", "alice", Side::Sell, 100, 1));
+
+		let withdraw = |amount| BalanceCommand {
+			public_key: "alice".to_string(),
+			asset: "BTC".to_string(),
+			amount,
+			adjustment: BalanceAdjustment::Withdrawal,
+			timestamp: 0,
+		};
+		ledger.check_adjustment(&withdraw(1)).unwrap();
+		assert!(matches!(
+			ledger.check_adjustment(&withdraw(2)),
+			Err(LedgerError::InsufficientBalance { .. })
+		));
+
+		let mut eth = withdraw(1);
+		eth.asset = "ETH".to_string();
+		assert_eq!(
+			ledger.check_adjustment(&eth),
+			Err(LedgerError::UnknownAsset("ETH".to_string()))
+		);
+	}
+}
@@ -0,0 +1,460 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use anvil_sdk::types::Side;
+use imbl::OrdMap;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+	event::{MatchingEvent, RejectReason},
+	types::OrderCommand,
+};
+
+/// Error types for risk limit checks
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum LimitError {
+	#[error("Order size {size} exceeds limit {limit}")]
+	MaxOrderSize { size: u64, limit: u64 },
+	#[error("Open order limit {limit} reached")]
+	MaxOpenOrders { limit: u32 },
+	#[error("Resting notional {notional} would exceed limit {limit}")]
+	MaxRestingNotional { notional: u128, limit: u128 },
+	#[error("Net position {position} would exceed limit {limit}")]
+	MaxNetPosition { position: i128, limit: u64 },
+}
+
+impl LimitError {
+	/// Machine-readable code carried on `OrderRejected`
+	pub fn code(&self) -> RejectReason {
+		match self {
+			LimitError::MaxOrderSize { .. } => RejectReason::MaxOrderSize,
+			LimitError::MaxOpenOrders { .. } => RejectReason::MaxOpenOrders,
+			LimitError::MaxRestingNotional { .. } => RejectReason::MaxRestingNotional,
+			LimitError::MaxNetPosition { .. } => RejectReason::MaxNetPosition,
+		}
+	}
+}
+
+/// Limits applied to a single principal
+///
+/// `None` means unlimited. Sizes are in size units and notional in
+/// price units x size units, matching the order book's integer scales.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskLimits {
+	/// Maximum number of resting orders
+	pub max_open_orders: Option<u32>,
+	/// Maximum notional resting on the book across both sides
+	pub max_resting_notional: Option<u128>,
+	/// Maximum size of a single order
+	pub max_order_size: Option<u64>,
+	/// Maximum absolute net base position
+	pub max_net_position: Option<u64>,
+}
+
+impl RiskLimits {
+	/// Fill unset fields from `defaults`
+	fn or(self, defaults: RiskLimits) -> RiskLimits {
+		RiskLimits {
+			max_open_orders: self.max_open_orders.or(defaults.max_open_orders),
+			max_resting_notional: self.max_resting_notional.or(defaults.max_resting_notional),
+			max_order_size: self.max_order_size.or(defaults.max_order_size),
+			max_net_position: self.max_net_position.or(defaults.max_net_position),
+		}
+	}
+}
+
+/// Default limits plus per-principal overrides
+///
+/// Override fields left unset fall back to the defaults, so an override
+/// only needs to name the limits it changes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LimitsConfig {
+	/// Limits applied to every principal
+	pub defaults: RiskLimits,
+	/// Overrides keyed on hex-encoded public key
+	pub overrides: HashMap<String, RiskLimits>,
+}
+
+impl LimitsConfig {
+	/// Effective limits for `public_key`
+	pub fn limits_for(&self, public_key: &str) -> RiskLimits {
+		match self.overrides.get(public_key) {
+			Some(limits) => limits.or(self.defaults),
+			None => self.defaults,
+		}
+	}
+}
+
+/// Exposure of a single principal
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Exposure {
+	/// Number of resting orders
+	pub open_orders: u32,
+	/// Remaining size of resting buy orders
+	pub open_buy_size: u64,
+	/// Remaining size of resting sell orders
+	pub open_sell_size: u64,
+	/// Notional of all resting orders
+	pub resting_notional: u128,
+	/// Net base position: bought minus sold
+	pub net_position: i128,
+}
+
+impl Exposure {
+	fn open_size_mut(&mut self, side: Side) -> &mut u64 {
+		match side {
+			Side::Buy => &mut self.open_buy_size,
+			Side::Sell => &mut self.open_sell_size,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RestingOrder {
+	public_key: String,
+	side: Side,
+	price: u64,
+	remaining_size: u64,
+}
+
+/// Per-principal exposure derived from matching events
+///
+/// Like the ledger, it is mutated only through [`ExposureTracker::apply`],
+/// so snapshots plus replay rebuild it exactly.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExposureTracker {
+	exposures: OrdMap<String, Exposure>,
+	/// order_id -> resting order
+	orders: OrdMap<String, RestingOrder>,
+}
+
+impl ExposureTracker {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Exposure of `public_key`
+	pub fn exposure(&self, public_key: &str) -> Exposure {
+		self.exposures.get(public_key).copied().unwrap_or_default()
+	}
+
+	/// Check `cmd` against `limits` assuming it rests or fills in full
+	///
+	/// The net position check assumes every resting order on the same side
+	/// as `cmd` fills too: the principal's current position plus their open
+	/// size on that side plus this order. Orders that reduce that absolute
+	/// position always pass.
+	pub fn check_order(&self, limits: &RiskLimits, cmd: &OrderCommand) -> Result<(), LimitError> {
+		if let Some(limit) = limits.max_order_size
+			&& cmd.size > limit
+		{
+			return Err(LimitError::MaxOrderSize {
+				size: cmd.size,
+				limit,
+			});
+		}
+
+		let exposure = self.exposure(&cmd.public_key);
+
+		if let Some(limit) = limits.max_open_orders
+			&& exposure.open_orders >= limit
+		{
+			return Err(LimitError::MaxOpenOrders { limit });
+		}
+
+		if let Some(limit) = limits.max_resting_notional {
+			let notional = exposure
+				.resting_notional
+				.saturating_add(cmd.price as u128 * cmd.size as u128);
+			if notional > limit {
+				return Err(LimitError::MaxRestingNotional { notional, limit });
+			}
+		}
+
+		if let Some(limit) = limits.max_net_position {
+			let open = match cmd.side {
+				Side::Buy => exposure.net_position + exposure.open_buy_size as i128,
+				Side::Sell => exposure.net_position - exposure.open_sell_size as i128,
+			};
+			let position = match cmd.side {
+				Side::Buy => open + cmd.size as i128,
+				Side::Sell => open - cmd.size as i128,
+			};
+			if position.unsigned_abs() > limit as u128
+				&& position.unsigned_abs() > open.unsigned_abs()
+			{
+				return Err(LimitError::MaxNetPosition { position, limit });
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Apply a matching event to the tracked exposure
+	pub fn apply(&mut self, event: &MatchingEvent) {
+		match event {
+			MatchingEvent::OrderAccepted {
+				order_id,
+				public_key,
+				side,
+				price,
+				size,
+				..
+			} => {
+				let exposure = self.exposures.entry(public_key.clone()).or_default();
+				exposure.open_orders += 1;
+				*exposure.open_size_mut(*side) += *size;
+				exposure.resting_notional += *price as u128 * *size as u128;
+				self.orders.insert(
+					order_id.clone(),
+					RestingOrder {
+						public_key: public_key.clone(),
+						side: *side,
+						price: *price,
+						remaining_size: *size,
+					},
+				);
+			}
+			MatchingEvent::TradeExecuted {
+				trade,
+				maker_public_key,
+				taker_public_key,
+				..
+			} => {
+				let (buyer, seller) = match trade.side {
+					Side::Buy => (taker_public_key, maker_public_key),
+					Side::Sell => (maker_public_key, taker_public_key),
+				};
+				self.exposures
+					.entry(buyer.clone())
+					.or_default()
+					.net_position += trade.size as i128;
+				self.exposures
+					.entry(seller.clone())
+					.or_default()
+					.net_position -= trade.size as i128;
+			}
+			MatchingEvent::MakerOrderPartiallyFilled {
+				order_id,
+				remaining_size,
+				..
+			} => {
+				if let Some(order) = self.orders.get_mut(order_id) {
+					let filled = order.remaining_size.saturating_sub(*remaining_size);
+					order.remaining_size = *remaining_size;
+					let (public_key, side, price) =
+						(order.public_key.clone(), order.side, order.price);
+					let exposure = self.exposures.entry(public_key).or_default();
+					let open_size = exposure.open_size_mut(side);
+					*open_size = open_size.saturating_sub(filled);
+					exposure.resting_notional = exposure
+						.resting_notional
+						.saturating_sub(price as u128 * filled as u128);
+				}
+			}
+			MatchingEvent::MakerOrderFilled { order_id, .. }
+			| MatchingEvent::OrderCancelled { order_id, .. } => {
+				if let Some(order) = self.orders.remove(order_id) {
+					let exposure = self.exposures.entry(order.public_key).or_default();
+					exposure.open_orders = exposure.open_orders.saturating_sub(1);
+					let open_size = exposure.open_size_mut(order.side);
+					*open_size = open_size.saturating_sub(order.remaining_size);
+					exposure.resting_notional = exposure
+						.resting_notional
+						.saturating_sub(order.price as u128 * order.remaining_size as u128);
+				}
+			}
+			MatchingEvent::OrderRejected { .. }
+			| MatchingEvent::OrderFilled { .. }
+			| MatchingEvent::OrderPartiallyFilled { .. }
+			| MatchingEvent::CancelRejected { .. }
+			| MatchingEvent::BalanceDeposited { .. }
+			| MatchingEvent::BalanceWithdrawn { .. }
+			| MatchingEvent::BalanceRejected { .. } => {}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anvil_sdk::types::Trade;
+
+	fn order(order_id: &str, side: Side, price: u64, size: u64) -> OrderCommand {
+		OrderCommand {
+			order_id: order_id.to_string(),
+			market: "BTC-USDT".to_string(),
+			side,
+			price,
+			size,
+			timestamp: 0,
+			public_key: "alice".to_string(),
+			client_order_id: None,
+			nonce: String::new(),
+		}
+	}
+
+	fn accepted(cmd: &OrderCommand) -> MatchingEvent {
+		MatchingEvent::OrderAccepted {
+			seq: 1,
+			order_id: cmd.order_id.clone(),
+			market: cmd.market.clone(),
+			public_key: cmd.public_key.clone(),
+			client_order_id: None,
+			side: cmd.side,
+			price: cmd.price,
+			size: cmd.size,
+			timestamp: 0,
+		}
+	}
+
+	#[test]
+	fn overrides_fall_back_to_defaults() {
+		let mut config = LimitsConfig {
+			defaults: RiskLimits {
+				max_open_orders: Some(10),
+				max_order_size: Some(5),
+				..Default::default()
+			},
+			overrides: HashMap::new(),
+		};
+		config.overrides.insert(
+			"alice".to_string(),
+			RiskLimits {
+				max_order_size: Some(50),
+				..Default::default()
+			},
+		);
+
+		let alice = config.limits_for("alice");
+		assert_eq!(alice.max_order_size, Some(50));
+		assert_eq!(alice.max_open_orders, Some(10));
+		assert_eq!(config.limits_for("bob").max_order_size, Some(5));
+	}
+
+	#[test]
+	fn open_orders_and_notional_track_resting_orders() {
+		let limits = RiskLimits {
+			max_open_orders: Some(1),
+			max_resting_notional: Some(1000),
+			..Default::default()
+		};
+		let mut tracker = ExposureTracker::new();
+
+		let first = order("o1", Side::Buy, 100, 5);
+		assert!(tracker.check_order(&limits, &first).is_ok());
+		tracker.apply(&accepted(&first));
+
+		let err = tracker
+			.check_order(&limits, &order("o2", Side::Buy, 100, 1))
+			.unwrap_err();
+		assert_eq!(err.code(), RejectReason::MaxOpenOrders);
+
+		tracker.apply(&MatchingEvent::OrderCancelled {
+			seq: 2,
+			order_id: "o1".to_string(),
+			market: "BTC-USDT".to_string(),
+			public_key: "alice".to_string(),
+			client_order_id: None,
+			remaining_size: 5,
+			timestamp: 0,
+		});
+		assert_eq!(tracker.exposure("alice"), Exposure::default());
+
+		let err = tracker
+			.check_order(&limits, &order("o3", Side::Buy, 100, 11))
+			.unwrap_err();
+		assert_eq!(err.code(), RejectReason::MaxRestingNotional);
+	}
+
+	#[test]
+	fn net_position_limit_allows_reducing_orders() {
+		let limits = RiskLimits {
+			max_net_position: Some(3),
+			..Default::default()
+		};
+		let mut tracker = ExposureTracker::new();
+		tracker.apply(&MatchingEvent::TradeExecuted {
+			seq: 1,
+			trade: Trade {
+				trade_id: "t1".to_string(),
+				market: "BTC-USDT".to_string(),
+				price: 100,
+				size: 3,
+				side: Side::Buy,
+				timestamp: 0,
+				maker_order_id: "m1".to_string(),
+				taker_order_id: "o1".to_string(),
+			},
+			maker_public_key: "bob".to_string(),
+			taker_public_key: "alice".to_string(),
+			maker_client_order_id: None,
+			taker_client_order_id: None,
+			timestamp: 0,
+		});
+
+		assert_eq!(tracker.exposure("alice").net_position, 3);
+		assert_eq!(tracker.exposure("bob").net_position, -3);
+
+		let err = tracker
+			.check_order(&limits, &order("o2", Side::Buy, 100, 1))
+			.unwrap_err();
+		assert_eq!(err.code(), RejectReason::MaxNetPosition);
+		assert!(
+			tracker
+				.check_order(&limits, &order("o3", Side::Sell, 100, 5))
+				.is_ok()
+		);
+	}
+
+	#[test]
+	fn net_position_limit_counts_resting_orders() {
+		let limits = RiskLimits {
+			max_net_position: Some(10),
+			..Default::default()
+		};
+		let mut tracker = ExposureTracker::new();
+
+		let first = order("o1", Side::Buy, 100, 10);
+		assert!(tracker.check_order(&limits, &first).is_ok());
+		tracker.apply(&accepted(&first));
+		assert_eq!(tracker.exposure("alice").open_buy_size, 10);
+
+		let err = tracker
+			.check_order(&limits, &order("o2", Side::Buy, 100, 1))
+			.unwrap_err();
+		assert_eq!(err.code(), RejectReason::MaxNetPosition);
+		// Resting buys do not count against sells
+		assert!(
+			tracker
+				.check_order(&limits, &order("o3", Side::Sell, 100, 10))
+				.is_ok()
+		);
+
+		tracker.apply(&MatchingEvent::MakerOrderPartiallyFilled {
+			seq: 2,
+			order_id: "o1".to_string(),
+			market: "BTC-USDT".to_string(),
+			filled_size: 4,
+			remaining_size: 6,
+			timestamp: 0,
+		});
+		assert_eq!(tracker.exposure("alice").open_buy_size, 6);
+	}
+}
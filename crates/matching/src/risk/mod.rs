@@ -0,0 +1,69 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pre-trade risk checks
+//!
+//! Risk state lives inside the single-threaded matching loop and is derived
+//! purely from `MatchingEvent`s, so it is rebuilt by the same snapshot +
+//! replay path as the order book.
+
+mod ledger;
+mod limits;
+
+pub use ledger::{Balance, Ledger, LedgerError, LedgerView};
+pub use limits::{Exposure, ExposureTracker, LimitError, LimitsConfig, RiskLimits};
+
+/// Risk configuration for a matching engine
+#[derive(Debug, Clone)]
+pub struct RiskConfig {
+	/// Reject orders that exceed the owner's available balance
+	pub enforce_balances: bool,
+	/// Base asset of the market (e.g., "BTC")
+	pub base_asset: String,
+	/// Quote asset of the market (e.g., "USDT")
+	pub quote_asset: String,
+	/// Per-principal position and open-order limits
+	pub limits: LimitsConfig,
+}
+
+impl RiskConfig {
+	/// Derive assets from a `BASE-QUOTE` market identifier
+	pub fn for_market(market: &str, enforce_balances: bool) -> Self {
+		let (base, quote) = market.split_once('-').unwrap_or((market, ""));
+		Self {
+			enforce_balances,
+			base_asset: base.to_string(),
+			quote_asset: quote.to_string(),
+			limits: LimitsConfig::default(),
+		}
+	}
+
+	/// Apply per-principal limits
+	pub fn with_limits(mut self, limits: LimitsConfig) -> Self {
+		self.limits = limits;
+		self
+	}
+
+	/// Create an empty ledger when balance enforcement is enabled
+	pub fn ledger(&self) -> Option<Ledger> {
+		self.enforce_balances
+			.then(|| Ledger::new(self.base_asset.clone(), self.quote_asset.clone()))
+	}
+}
+
+impl Default for RiskConfig {
+	fn default() -> Self {
+		Self::for_market("BTC-USDT", false)
+	}
+}
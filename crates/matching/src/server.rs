@@ -19,24 +19,41 @@
 //! - Receiving and validating order requests
 //! - Checking idempotency via Order Journal
 //! - Appending orders to Order Journal
-//! - Enqueuing orders to the matching loop
+//! - Enqueuing orders, cancels and balance adjustments to the matching loop
+//! - Enqueuing order and cancel batches as one contiguous command
+//! - Answering balance queries from the committed ledger view
+//! - Serving L2 book snapshots and update streams from the market data publisher
+//! - Streaming all public market data changes of a market in one stream
+//! - Serving OHLCV candles and 24h ticker statistics
+//! - Paging through trade and fill history
+//! - Streaming committed trades and the L3 order feed from the event hub
 //! - Returning ACK to clients
 //!
 //! The RPC layer does NOT perform matching - that happens in the
 //! single-threaded matching loop.
 
+use std::collections::HashSet;
 use std::sync::{Arc, Mutex};
 
-use anvil_sdk::types::Side;
+use anvil_sdk::types::{LiquidityRole, OrderStatus, Side};
 use opentelemetry::propagation::{Extractor, TextMapPropagator};
 use opentelemetry_sdk::propagation::TraceContextPropagator;
 use tonic::{Request, Response, Status};
-use tracing::{debug, field, info, warn};
+use tracing::{debug, error, field, info, warn};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
+use crate::event::{EventHub, MatchingEvent, RejectReason, SubscriptionError};
+use crate::execution::{ExecutionKind, ExecutionReport, ExecutionReportFilter};
+use crate::history::{HistoryQuery, TradeCursor, TradeHistory};
 use crate::journal::OrderJournal;
-use crate::queue::QueueSender;
-use crate::types::OrderCommand;
+use crate::market_data::{self, MarketDataHandle};
+use crate::orders::{OrderRecord, OrderView};
+use crate::queue::{QueueError, QueueSender};
+use crate::replication::{ReplicationRole, ReplicationState};
+use crate::risk::{LedgerError, LedgerView};
+use crate::types::{
+	BalanceAdjustment, BalanceCommand, CancelCommand, EngineCommand, OrderCommand, ReplaceCommand,
+};
 
 // Include generated gRPC code
 pub mod proto {
@@ -45,9 +62,23 @@ pub mod proto {
 
 use proto::matching_service_server::{MatchingService, MatchingServiceServer};
 use proto::{
-	CancelOrderRequest, CancelOrderResponse, GetOrderRequest, GetOrderResponse, MatchedTrade,
-	OrderSide as ProtoOrderSide, OrderStatus as ProtoOrderStatus, StreamMatchedTradesRequest,
-	SubmitDisposition, SubmitOrderRequest, SubmitOrderResponse,
+	AdjustBalanceRequest, AdjustBalanceResponse, AssetBalance,
+	BalanceAdjustment as ProtoBalanceAdjustment, BookUpdate as ProtoBookUpdate, CancelOrderRequest,
+	CancelOrderResponse, CancelOrdersRequest, CancelOrdersResponse, Candle as ProtoCandle,
+	CandleUpdate, EngineRole as ProtoEngineRole, ExecutionReport as ProtoExecutionReport,
+	ExecutionType, FenceRequest, FenceResponse, Fill as ProtoFill, GetBalancesRequest,
+	GetBalancesResponse, GetCandlesRequest, GetCandlesResponse, GetEngineStatusRequest,
+	GetEngineStatusResponse, GetOrderBookRequest, GetOrderBookResponse, GetOrderRequest,
+	GetOrderResponse, GetTickerRequest, GetTickerResponse, LiquidityRole as ProtoLiquidityRole,
+	ListFillsRequest, ListFillsResponse, ListTradesRequest, ListTradesResponse,
+	MarketDataUpdate as ProtoMarketDataUpdate, MatchedTrade, Order as ProtoOrder, OrderAdd,
+	OrderDelete, OrderExecute, OrderFeedMessage as ProtoOrderFeedMessage,
+	OrderSide as ProtoOrderSide, OrderStatus as ProtoOrderStatus, PriceLevel as ProtoPriceLevel,
+	PromoteRequest, PromoteResponse, ReplicatedEvent, StreamBookUpdatesRequest,
+	StreamEventsRequest, StreamExecutionReportsRequest, StreamMarketDataRequest,
+	StreamMatchedTradesRequest, StreamOrderFeedRequest, SubmitDisposition, SubmitOrderRequest,
+	SubmitOrderResponse, SubmitOrdersRequest, SubmitOrdersResponse, Trade as ProtoTrade,
+	market_data_update, order_feed_message,
 };
 use tokio_stream;
 
@@ -64,8 +95,29 @@ pub struct MatchingServiceImpl {
 	queue_sender: QueueSender,
 	journal: Arc<Mutex<Box<dyn OrderJournal>>>,
 	market: String,
+	/// Committed ledger view, present when balance checks are enabled
+	ledger: Option<LedgerView>,
+	/// L2 market data, present when a publisher is attached to the event writer
+	market_data: Option<MarketDataHandle>,
+	/// Committed event stream with replay, backing the trade stream and L3 feed
+	event_hub: Option<EventHub>,
+	/// Recent trades for history and fill queries
+	trade_history: Option<TradeHistory>,
+	/// Open and recently completed orders for order queries
+	order_view: Option<OrderView>,
+	/// Replication role; only a primary accepts writes
+	replication: ReplicationState,
+	/// Whether a lease decides the primary, in which case `Promote` is refused
+	leader_elected: bool,
 }
 
+/// Trades per history page when the request does not set a limit
+const DEFAULT_HISTORY_LIMIT: usize = 100;
+/// Upper bound for the trades per history page
+const MAX_HISTORY_LIMIT: usize = 1000;
+/// Upper bound for the orders or cancels of one batch request
+pub const MAX_BATCH_SIZE: usize = 100;
+
 impl MatchingServiceImpl {
 	pub fn new(
 		queue_sender: QueueSender,
@@ -76,8 +128,366 @@ impl MatchingServiceImpl {
 			queue_sender,
 			journal,
 			market,
+			ledger: None,
+			market_data: None,
+			event_hub: None,
+			trade_history: None,
+			order_view: None,
+			replication: ReplicationState::primary(0),
+			leader_elected: false,
+		}
+	}
+
+	/// Serve balance queries and pre-check orders against `ledger`
+	pub fn with_ledger(mut self, ledger: LedgerView) -> Self {
+		self.ledger = Some(ledger);
+		self
+	}
+
+	/// Serve order book snapshots and update streams from `market_data`
+	pub fn with_market_data(mut self, market_data: MarketDataHandle) -> Self {
+		self.market_data = Some(market_data);
+		self
+	}
+
+	/// Serve the trade stream and L3 order feed from `event_hub`
+	pub fn with_event_hub(mut self, event_hub: EventHub) -> Self {
+		self.event_hub = Some(event_hub);
+		self
+	}
+
+	/// Serve trade and fill history from `trade_history`
+	pub fn with_trade_history(mut self, trade_history: TradeHistory) -> Self {
+		self.trade_history = Some(trade_history);
+		self
+	}
+
+	/// Serve order queries from `order_view`
+	pub fn with_order_view(mut self, order_view: OrderView) -> Self {
+		self.order_view = Some(order_view);
+		self
+	}
+
+	/// Accept writes according to `replication` instead of as a primary at
+	/// epoch 0
+	pub fn with_replication(mut self, replication: ReplicationState) -> Self {
+		self.replication = replication;
+		self
+	}
+
+	/// Leave promotion to leader election (see [`crate::leader`])
+	pub fn with_leader_election(mut self) -> Self {
+		self.leader_elected = true;
+		self
+	}
+
+	/// Wrap the service in a tonic server
+	pub fn into_server(self) -> MatchingServiceServer<Self> {
+		MatchingServiceServer::new(self)
+	}
+
+	/// Refuse writes unless the engine is the primary
+	fn check_writable(&self) -> Result<(), Status> {
+		self.replication
+			.check_writable()
+			.map(|_| ())
+			.map_err(|e| Status::failed_precondition(e.to_string()))
+	}
+
+	fn ledger(&self) -> Result<&LedgerView, Status> {
+		self.ledger
+			.as_ref()
+			.ok_or_else(|| Status::failed_precondition("Balance checks are disabled"))
+	}
+
+	fn market_data(&self, market: &str) -> Result<&MarketDataHandle, Status> {
+		if market != self.market {
+			return Err(Status::not_found(format!(
+				"Market {} not supported",
+				market
+			)));
+		}
+		self.market_data
+			.as_ref()
+			.ok_or_else(|| Status::failed_precondition("Market data is disabled"))
+	}
+
+	fn trade_history(&self, market: &str) -> Result<&TradeHistory, Status> {
+		if market != self.market {
+			return Err(Status::not_found(format!(
+				"Market {} not supported",
+				market
+			)));
+		}
+		self.trade_history
+			.as_ref()
+			.ok_or_else(|| Status::failed_precondition("Trade history is disabled"))
+	}
+
+	fn history_query(
+		start_time: u64,
+		end_time: u64,
+		cursor: &str,
+		limit: u32,
+	) -> Result<HistoryQuery, Status> {
+		let cursor = if cursor.is_empty() {
+			None
+		} else {
+			Some(
+				cursor
+					.parse::<TradeCursor>()
+					.map_err(|e| Status::invalid_argument(e.to_string()))?,
+			)
+		};
+		let limit = match limit as usize {
+			0 => DEFAULT_HISTORY_LIMIT,
+			limit => limit.min(MAX_HISTORY_LIMIT),
+		};
+		Ok(HistoryQuery {
+			start_time: (start_time > 0).then_some(start_time),
+			end_time: (end_time > 0).then_some(end_time),
+			cursor,
+			limit,
+		})
+	}
+
+	/// Stream committed events from `from_sequence`, mapped by `map`
+	///
+	/// Each event is streamed as the items `map` returns for it, so events
+	/// mapped to `None` are skipped. Subscription errors end the stream with
+	/// an error status.
+	fn subscribe_events<T, I, F>(
+		&self,
+		market: &str,
+		from_sequence: u64,
+		mut map: F,
+	) -> Result<tokio_stream::wrappers::ReceiverStream<Result<T, Status>>, Status>
+	where
+		T: Send + 'static,
+		I: IntoIterator<Item = T>,
+		I::IntoIter: Send,
+		F: FnMut(MatchingEvent) -> I + Send + 'static,
+	{
+		if market != self.market {
+			return Err(Status::not_found(format!(
+				"Market {} not supported",
+				market
+			)));
+		}
+		let hub = self
+			.event_hub
+			.as_ref()
+			.ok_or_else(|| Status::failed_precondition("Event feed is disabled"))?;
+		if from_sequence > hub.last_sequence() + 1 {
+			return Err(Status::out_of_range(format!(
+				"Sequence {} has not been committed yet",
+				from_sequence
+			)));
+		}
+
+		let mut events = hub.subscribe_from(from_sequence);
+		let (tx, rx) = tokio::sync::mpsc::channel(128);
+
+		tokio::spawn(async move {
+			while let Some(event) = events.recv().await {
+				let status = match event {
+					Ok(event) => {
+						for item in map(event) {
+							if tx.send(Ok(item)).await.is_err() {
+								return;
+							}
+						}
+						continue;
+					}
+					Err(e @ SubscriptionError::Lagged(_)) => Status::resource_exhausted(format!(
+						"{}; resume from the last received sequence",
+						e
+					)),
+					Err(e) => Status::internal(format!("Event replay failed: {}", e)),
+				};
+				let _ = tx.send(Err(status)).await;
+				break;
+			}
+		});
+
+		Ok(tokio_stream::wrappers::ReceiverStream::new(rx))
+	}
+
+	/// Validate an order and build the command that enqueues it
+	///
+	/// Runs the checks that reject an order before it reaches the matching
+	/// loop: market, size, committed balance and duplicate order ID. Callers
+	/// answer repeated requests from `journal` before admitting them, since a
+	/// retry of an accepted order would fail the last two checks.
+	fn admit_order(
+		&self,
+		req: &SubmitOrderRequest,
+		journal: &dyn OrderJournal,
+	) -> Result<(OrderCommand, EngineCommand), (SubmitDisposition, String)> {
+		if req.market != self.market {
+			return Err((
+				SubmitDisposition::InvalidOrder,
+				format!("Market {} not supported", req.market),
+			));
+		}
+
+		if req.size == 0 {
+			return Err((
+				SubmitDisposition::InvalidOrder,
+				"Order size must be greater than 0".to_string(),
+			));
+		}
+
+		let cmd = OrderCommand {
+			order_id: req.order_id.clone(),
+			market: req.market.clone(),
+			side: match req.side() {
+				ProtoOrderSide::Buy => Side::Buy,
+				ProtoOrderSide::Sell => Side::Sell,
+			},
+			price: req.price,
+			size: req.size,
+			timestamp: req.timestamp,
+			public_key: req.public_key.clone(),
+			nonce: req.nonce.clone(),
+			client_order_id: Some(req.client_order_id.clone()).filter(|id| !id.is_empty()),
+		};
+
+		// Fast-fail orders the committed ledger cannot fund. The matching loop
+		// re-checks against its authoritative ledger, so this only rejects
+		// orders that would be rejected anyway. Replacements are left to the
+		// matching loop, which releases the replaced order's funds first.
+		if let Some(ledger) = &self.ledger
+			&& req.replaces_order_id.is_empty()
+			&& let Err(e @ LedgerError::InsufficientBalance { .. }) =
+				ledger.check_order(&cmd.public_key, cmd.side, cmd.price, cmd.size)
+		{
+			return Err((SubmitDisposition::InsufficientBalance, e.to_string()));
+		}
+
+		// Check idempotency: is this order already active?
+		if journal.is_active(&cmd.order_id) {
+			return Err((
+				SubmitDisposition::InvalidOrder,
+				"Duplicate order ID".to_string(),
+			));
+		}
+
+		let command = if req.replaces_order_id.is_empty() {
+			EngineCommand::Submit(cmd.clone())
+		} else {
+			EngineCommand::Replace(ReplaceCommand {
+				replaces: req.replaces_order_id.clone(),
+				order: cmd.clone(),
+			})
+		};
+		Ok((cmd, command))
+	}
+
+	/// An order the journal accepted that the matching loop has not processed
+	fn pending_order(cmd: OrderCommand) -> ProtoOrder {
+		ProtoOrder {
+			order_id: cmd.order_id,
+			market: cmd.market,
+			side: match cmd.side {
+				Side::Buy => ProtoOrderSide::Buy,
+				Side::Sell => ProtoOrderSide::Sell,
+			} as i32,
+			price: cmd.price,
+			size: cmd.size,
+			filled_size: 0,
+			remaining_size: cmd.size,
+			status: ProtoOrderStatus::Pending as i32,
+			created_at: cmd.timestamp,
+			public_key: cmd.public_key,
+			client_order_id: cmd.client_order_id.unwrap_or_default(),
+		}
+	}
+
+	/// Response for a repeated request, from the outcome of its original order
+	///
+	/// An original the matching loop rejected is answered with its
+	/// rejection. One not committed yet, or no longer kept by the order
+	/// view, is answered as accepted.
+	fn repeated_response(&self, order_id: String) -> SubmitOrderResponse {
+		match self
+			.order_view
+			.as_ref()
+			.and_then(|view| view.get(&order_id))
+		{
+			Some(OrderRecord {
+				rejection: Some((code, reason)),
+				..
+			}) => {
+				let disposition = match code {
+					RejectReason::InsufficientBalance => SubmitDisposition::InsufficientBalance,
+					_ => SubmitDisposition::RejectedOrder,
+				};
+				Self::order_response(order_id, disposition, reason)
+			}
+			Some(order) => SubmitOrderResponse {
+				status: ProtoOrder::from(order).status,
+				..Self::order_response(order_id, SubmitDisposition::AcceptedOk, String::new())
+			},
+			None => Self::order_response(order_id, SubmitDisposition::AcceptedOk, String::new()),
+		}
+	}
+
+	/// Disposition of a command the ingress queue did not take
+	fn queue_rejection(e: QueueError) -> (SubmitDisposition, String) {
+		match e {
+			QueueError::Full => (
+				SubmitDisposition::OverloadedEngine,
+				"Matching engine overloaded, please retry".to_string(),
+			),
+			e => (
+				SubmitDisposition::InternalError,
+				format!("Queue error: {}", e),
+			),
+		}
+	}
+
+	/// Build the cancel command, or reject a cancel for another market
+	fn admit_cancel(&self, req: CancelOrderRequest) -> Result<EngineCommand, String> {
+		if req.market != self.market {
+			return Err(format!("Market {} not supported", req.market));
+		}
+		Ok(EngineCommand::Cancel(CancelCommand {
+			order_id: req.order_id,
+			market: req.market,
+			public_key: req.public_key,
+			timestamp: Self::now_secs(),
+			client_order_id: Some(req.client_order_id).filter(|id| !id.is_empty()),
+		}))
+	}
+
+	/// Response for an order that did not reach the matching loop
+	fn order_response(
+		order_id: String,
+		disposition: SubmitDisposition,
+		reason: String,
+	) -> SubmitOrderResponse {
+		SubmitOrderResponse {
+			order_id,
+			status: if disposition == SubmitDisposition::AcceptedOk {
+				ProtoOrderStatus::Accepted as i32
+			} else {
+				ProtoOrderStatus::Rejected as i32
+			},
+			trades: Vec::new(),
+			fully_filled: false,
+			partially_filled: false,
+			disposition: disposition as i32,
+			reason,
 		}
 	}
+
+	fn now_secs() -> u64 {
+		std::time::SystemTime::now()
+			.duration_since(std::time::UNIX_EPOCH)
+			.unwrap()
+			.as_secs()
+	}
 }
 
 #[tonic::async_trait]
@@ -86,6 +496,7 @@ impl MatchingService for MatchingServiceImpl {
 		&self,
 		request: Request<SubmitOrderRequest>,
 	) -> Result<Response<SubmitOrderResponse>, Status> {
+		self.check_writable()?;
 		let start = std::time::Instant::now();
 
 		// Extract tracing context from gRPC metadata
@@ -117,113 +528,76 @@ impl MatchingService for MatchingServiceImpl {
 		// Enter the span for this request
 		let _guard = span.enter();
 
-		// Basic validation
-		if req.market != self.market {
+		// The journal stays locked from the idempotency check to the append,
+		// so a repeated request cannot be enqueued twice. Repeats are answered
+		// before admission: the original order may already hold the order ID
+		// or the funds the retry would be checked against.
+		let mut journal = self.journal.lock().unwrap();
+		if let Some(original) = journal.find_request(&req.public_key, &req.nonce) {
+			drop(journal);
+			let response = self.repeated_response(original);
 			let duration = start.elapsed();
-			tracing::Span::current().record("status", "rejected");
-			tracing::Span::current().record("disposition", "invalid_order");
-			tracing::Span::current().record("latency_ms", duration.as_millis() as u64);
-			warn!(
-				order_id = %req.order_id,
-				market = %req.market,
-				reason = "unsupported market",
-				duration_ms = duration.as_millis(),
-				"Order rejected"
+			tracing::Span::current().record("status", "duplicate");
+			tracing::Span::current().record(
+				"disposition",
+				response.disposition().as_str_name().to_lowercase(),
 			);
-			return Ok(Response::new(SubmitOrderResponse {
-				order_id: req.order_id,
-				status: ProtoOrderStatus::Rejected as i32,
-				trades: Vec::new(),
-				fully_filled: false,
-				partially_filled: false,
-				disposition: SubmitDisposition::InvalidOrder as i32,
-				reason: format!("Market {} not supported", req.market),
-			}));
-		}
-
-		if req.size == 0 {
-			let duration = start.elapsed();
-			tracing::Span::current().record("status", "rejected");
-			tracing::Span::current().record("disposition", "invalid_order");
 			tracing::Span::current().record("latency_ms", duration.as_millis() as u64);
-			warn!(
-				order_id = %req.order_id,
-				reason = "zero size",
+			info!(
+				order_id = %response.order_id,
+				nonce = %req.nonce,
+				disposition = ?response.disposition(),
 				duration_ms = duration.as_millis(),
-				"Order rejected"
+				"Duplicate request - answered with the original order"
 			);
-			return Ok(Response::new(SubmitOrderResponse {
-				order_id: req.order_id,
-				status: ProtoOrderStatus::Rejected as i32,
-				trades: Vec::new(),
-				fully_filled: false,
-				partially_filled: false,
-				disposition: SubmitDisposition::InvalidOrder as i32,
-				reason: "Order size must be greater than 0".to_string(),
-			}));
+			return Ok(Response::new(response));
 		}
 
-		// Create order command
-		let cmd = OrderCommand {
-			order_id: req.order_id.clone(),
-			market: req.market.clone(),
-			side: match req.side() {
-				ProtoOrderSide::Buy => Side::Buy,
-				ProtoOrderSide::Sell => Side::Sell,
-			},
-			price: req.price,
-			size: req.size,
-			timestamp: req.timestamp,
-			public_key: req.public_key.clone(),
-		};
-
-		// Check idempotency: is this order already active?
-		{
-			let journal = self.journal.lock().unwrap();
-			if journal.is_active(&cmd.order_id) {
+		let (cmd, command) = match self.admit_order(&req, journal.as_ref()) {
+			Ok(admitted) => admitted,
+			Err((disposition, reason)) => {
+				drop(journal);
 				let duration = start.elapsed();
 				tracing::Span::current().record("status", "rejected");
-				tracing::Span::current().record("disposition", "duplicate");
+				tracing::Span::current()
+					.record("disposition", disposition.as_str_name().to_lowercase());
 				tracing::Span::current().record("latency_ms", duration.as_millis() as u64);
-				debug!(
-					order_id = %cmd.order_id,
-					reason = "duplicate order ID",
+				info!(
+					order_id = %req.order_id,
+					reason = %reason,
 					duration_ms = duration.as_millis(),
-					"Duplicate order detected"
+					"Order rejected"
 				);
-				return Ok(Response::new(SubmitOrderResponse {
-					order_id: cmd.order_id,
-					status: ProtoOrderStatus::Rejected as i32,
-					trades: Vec::new(),
-					fully_filled: false,
-					partially_filled: false,
-					disposition: SubmitDisposition::InvalidOrder as i32,
-					reason: "Duplicate order ID".to_string(),
-				}));
+				return Ok(Response::new(Self::order_response(
+					req.order_id,
+					disposition,
+					reason,
+				)));
 			}
-		}
+		};
 
 		// Try to enqueue to matching loop first (before journal append)
 		// This ensures queue full errors don't leave orders stuck in journal
-		match self.queue_sender.try_enqueue(cmd.clone()) {
-			Ok(_) => {
-				// Successfully enqueued, now append to journal for idempotency protection
-				{
-					let mut journal = self.journal.lock().unwrap();
-					if let Err(e) = journal.append(cmd.clone()) {
-						// This is an edge case: order is in queue but journal append failed
-						// The order will be processed but without idempotency protection
-						// This should be extremely rare with in-memory journal
-						warn!(
-						order_id = %cmd.order_id,
-						error = %e,
-								"Journal append failed after successful enqueue - order will be processed without idempotency protection"
-							);
-						// We still return Accepted because the order is in the queue
-						// The warning above should trigger monitoring alerts
-					}
-				}
+		let enqueued = self.queue_sender.try_enqueue_command(command);
+		if enqueued.is_ok() {
+			// Successfully enqueued, now append to journal for idempotency protection
+			if let Err(e) = journal.append(cmd.clone()) {
+				// This is an edge case: order is in queue but journal append failed
+				// The order will be processed but without idempotency protection
+				// This should be extremely rare with in-memory journal
+				warn!(
+					order_id = %cmd.order_id,
+					error = %e,
+					"Journal append failed after successful enqueue - order will be processed without idempotency protection"
+				);
+				// We still return Accepted because the order is in the queue
+				// The warning above should trigger monitoring alerts
+			}
+		}
+		drop(journal);
 
+		match enqueued {
+			Ok(_) => {
 				// Return ACK: order has been accepted
 				let duration = start.elapsed();
 				tracing::Span::current().record("status", "accepted");
@@ -239,15 +613,11 @@ impl MatchingService for MatchingServiceImpl {
 					"Order accepted"
 				);
 
-				Ok(Response::new(SubmitOrderResponse {
-					order_id: cmd.order_id,
-					status: ProtoOrderStatus::Accepted as i32,
-					trades: Vec::new(),
-					fully_filled: false,
-					partially_filled: false,
-					disposition: SubmitDisposition::AcceptedOk as i32,
-					reason: String::new(),
-				}))
+				Ok(Response::new(Self::order_response(
+					cmd.order_id,
+					SubmitDisposition::AcceptedOk,
+					String::new(),
+				)))
 			}
 			Err(crate::queue::QueueError::Full) => {
 				// Queue full - engine overloaded
@@ -264,15 +634,11 @@ impl MatchingService for MatchingServiceImpl {
 					"Engine overloaded - order not accepted, client can retry"
 				);
 
-				Ok(Response::new(SubmitOrderResponse {
-					order_id: cmd.order_id,
-					status: ProtoOrderStatus::Rejected as i32,
-					trades: Vec::new(),
-					fully_filled: false,
-					partially_filled: false,
-					disposition: SubmitDisposition::OverloadedEngine as i32,
-					reason: "Matching engine overloaded, please retry".to_string(),
-				}))
+				Ok(Response::new(Self::order_response(
+					cmd.order_id,
+					SubmitDisposition::OverloadedEngine,
+					"Matching engine overloaded, please retry".to_string(),
+				)))
 			}
 			Err(e) => {
 				// Queue disconnected or other error
@@ -286,35 +652,533 @@ impl MatchingService for MatchingServiceImpl {
 					duration_ms = duration.as_millis(),
 					"Queue error"
 				);
-				Ok(Response::new(SubmitOrderResponse {
-					order_id: cmd.order_id,
-					status: ProtoOrderStatus::Rejected as i32,
-					trades: Vec::new(),
-					fully_filled: false,
-					partially_filled: false,
-					disposition: SubmitDisposition::InternalError as i32,
-					reason: format!("Queue error: {}", e),
-				}))
+				Ok(Response::new(Self::order_response(
+					cmd.order_id,
+					SubmitDisposition::InternalError,
+					format!("Queue error: {}", e),
+				)))
 			}
 		}
 	}
 
 	async fn get_order(
 		&self,
-		_request: Request<GetOrderRequest>,
+		request: Request<GetOrderRequest>,
 	) -> Result<Response<GetOrderResponse>, Status> {
-		// TODO: Implement order query
-		Err(Status::unimplemented("Order query not yet implemented"))
+		let req = request.into_inner();
+		let order_view = self
+			.order_view
+			.as_ref()
+			.ok_or_else(|| Status::failed_precondition("Order queries are disabled"))?;
+
+		if !req.client_order_id.is_empty() {
+			if req.public_key.is_empty() {
+				return Err(Status::invalid_argument(
+					"Client order ID lookups require a public key",
+				));
+			}
+			// Orders still waiting in the ingress queue are not indexed yet
+			let order = order_view
+				.get_by_client_id(&req.public_key, &req.client_order_id)
+				.map(ProtoOrder::from)
+				.ok_or_else(|| {
+					Status::not_found(format!(
+						"Order with client order ID {} not found",
+						req.client_order_id
+					))
+				})?;
+			return Ok(Response::new(GetOrderResponse { order: Some(order) }));
+		}
+
+		// Orders still waiting in the ingress queue are only in the journal
+		let order = order_view
+			.get(&req.order_id)
+			.map(ProtoOrder::from)
+			.or_else(|| {
+				let journal = self.journal.lock().unwrap();
+				journal.get(&req.order_id).map(Self::pending_order)
+			});
+
+		// Another principal's order is indistinguishable from an unknown one
+		let order = order
+			.filter(|order| req.public_key.is_empty() || order.public_key == req.public_key)
+			.ok_or_else(|| Status::not_found(format!("Order {} not found", req.order_id)))?;
+
+		Ok(Response::new(GetOrderResponse { order: Some(order) }))
 	}
 
 	async fn cancel_order(
 		&self,
-		_request: Request<CancelOrderRequest>,
+		request: Request<CancelOrderRequest>,
 	) -> Result<Response<CancelOrderResponse>, Status> {
-		// TODO: Implement order cancellation via OrderCommand
-		Err(Status::unimplemented(
-			"Order cancellation not yet implemented",
-		))
+		self.check_writable()?;
+		let req = request.into_inner();
+		// A cancel by client order ID is resolved when it is sequenced; the
+		// response names the order the ID refers to at admission, if known
+		let order_id = match &self.order_view {
+			Some(order_view) if req.order_id.is_empty() && !req.client_order_id.is_empty() => {
+				order_view
+					.get_by_client_id(&req.public_key, &req.client_order_id)
+					.map(|order| order.order_id)
+					.unwrap_or_default()
+			}
+			_ => req.order_id.clone(),
+		};
+
+		let (disposition, reason) = match self.admit_cancel(req) {
+			Ok(cmd) => match self.queue_sender.try_enqueue_command(cmd) {
+				Ok(_) => (SubmitDisposition::AcceptedOk, String::new()),
+				Err(e) => Self::queue_rejection(e),
+			},
+			Err(reason) => (SubmitDisposition::InvalidOrder, reason),
+		};
+		debug!(order_id = %order_id, disposition = ?disposition, "Cancel request");
+
+		Ok(Response::new(CancelOrderResponse {
+			success: disposition == SubmitDisposition::AcceptedOk,
+			order_id,
+			disposition: disposition as i32,
+			reason,
+		}))
+	}
+
+	async fn submit_orders(
+		&self,
+		request: Request<SubmitOrdersRequest>,
+	) -> Result<Response<SubmitOrdersResponse>, Status> {
+		self.check_writable()?;
+		let orders = request.into_inner().orders;
+		if orders.is_empty() || orders.len() > MAX_BATCH_SIZE {
+			return Err(Status::invalid_argument(format!(
+				"Batch must contain 1 to {} orders",
+				MAX_BATCH_SIZE
+			)));
+		}
+
+		// As for single orders, the journal stays locked from the idempotency
+		// check to the append, and repeats are answered before admission
+		let mut journal = self.journal.lock().unwrap();
+		let mut results = Vec::with_capacity(orders.len());
+		let mut admitted = Vec::new();
+		let mut seen_ids = HashSet::new();
+		let mut seen_nonces = HashSet::new();
+		let mut seen_client_order_ids = HashSet::new();
+		for (index, order) in orders.iter().enumerate() {
+			if let Some(original) = journal.find_request(&order.public_key, &order.nonce) {
+				results.push(self.repeated_response(original));
+				continue;
+			}
+			let admission = if !seen_ids.insert(order.order_id.as_str()) {
+				Err((
+					SubmitDisposition::InvalidOrder,
+					"Duplicate order ID".to_string(),
+				))
+			} else if !order.nonce.is_empty() && !seen_nonces.insert(order.nonce.as_str()) {
+				Err((
+					SubmitDisposition::InvalidOrder,
+					"Duplicate request nonce".to_string(),
+				))
+			} else if !order.client_order_id.is_empty()
+				&& !seen_client_order_ids
+					.insert((order.public_key.as_str(), order.client_order_id.as_str()))
+			{
+				Err((
+					SubmitDisposition::InvalidOrder,
+					"Duplicate client order ID".to_string(),
+				))
+			} else {
+				self.admit_order(order, journal.as_ref())
+			};
+			let (disposition, reason) = match admission {
+				Ok((cmd, command)) => {
+					admitted.push((index, cmd, command));
+					(SubmitDisposition::AcceptedOk, String::new())
+				}
+				Err(rejection) => rejection,
+			};
+			results.push(Self::order_response(
+				order.order_id.clone(),
+				disposition,
+				reason,
+			));
+		}
+
+		if !admitted.is_empty() {
+			let (admitted, commands): (Vec<_>, Vec<_>) = admitted
+				.into_iter()
+				.map(|(index, cmd, command)| ((index, cmd), command))
+				.unzip();
+
+			match self.queue_sender.try_enqueue_batch(commands) {
+				Ok(_) => {
+					for (_, cmd) in admitted {
+						let order_id = cmd.order_id.clone();
+						if let Err(e) = journal.append(cmd) {
+							warn!(
+								order_id = %order_id,
+								error = %e,
+								"Journal append failed after successful enqueue - order will be processed without idempotency protection"
+							);
+						}
+					}
+				}
+				Err(e) => {
+					let (disposition, reason) = Self::queue_rejection(e);
+					for (index, _) in admitted {
+						results[index] = Self::order_response(
+							results[index].order_id.clone(),
+							disposition,
+							reason.clone(),
+						);
+					}
+				}
+			}
+		}
+		drop(journal);
+
+		info!(
+			orders = orders.len(),
+			accepted = results
+				.iter()
+				.filter(|r| r.disposition == SubmitDisposition::AcceptedOk as i32)
+				.count(),
+			"Order batch"
+		);
+		Ok(Response::new(SubmitOrdersResponse { results }))
+	}
+
+	async fn cancel_orders(
+		&self,
+		request: Request<CancelOrdersRequest>,
+	) -> Result<Response<CancelOrdersResponse>, Status> {
+		self.check_writable()?;
+		let cancels = request.into_inner().cancels;
+		if cancels.is_empty() || cancels.len() > MAX_BATCH_SIZE {
+			return Err(Status::invalid_argument(format!(
+				"Batch must contain 1 to {} cancels",
+				MAX_BATCH_SIZE
+			)));
+		}
+
+		let mut results = Vec::with_capacity(cancels.len());
+		let mut commands = Vec::new();
+		for cancel in cancels {
+			let order_id = cancel.order_id.clone();
+			let (disposition, reason) = match self.admit_cancel(cancel) {
+				Ok(cmd) => {
+					commands.push(cmd);
+					(SubmitDisposition::AcceptedOk, String::new())
+				}
+				Err(reason) => (SubmitDisposition::InvalidOrder, reason),
+			};
+			results.push(CancelOrderResponse {
+				success: disposition == SubmitDisposition::AcceptedOk,
+				order_id,
+				disposition: disposition as i32,
+				reason,
+			});
+		}
+
+		if !commands.is_empty()
+			&& let Err(e) = self.queue_sender.try_enqueue_batch(commands)
+		{
+			let (disposition, reason) = Self::queue_rejection(e);
+			for result in results.iter_mut().filter(|r| r.success) {
+				result.success = false;
+				result.disposition = disposition as i32;
+				result.reason = reason.clone();
+			}
+		}
+		debug!(cancels = results.len(), "Cancel batch");
+
+		Ok(Response::new(CancelOrdersResponse { results }))
+	}
+
+	async fn adjust_balance(
+		&self,
+		request: Request<AdjustBalanceRequest>,
+	) -> Result<Response<AdjustBalanceResponse>, Status> {
+		self.check_writable()?;
+		let ledger = self.ledger()?;
+		let req = request.into_inner();
+
+		let cmd = BalanceCommand {
+			public_key: req.public_key.clone(),
+			asset: req.asset.clone(),
+			amount: req.amount,
+			adjustment: match req.adjustment() {
+				ProtoBalanceAdjustment::Deposit => BalanceAdjustment::Deposit,
+				ProtoBalanceAdjustment::Withdrawal => BalanceAdjustment::Withdrawal,
+			},
+			timestamp: Self::now_secs(),
+		};
+
+		if cmd.amount == 0 {
+			return Ok(Response::new(AdjustBalanceResponse {
+				disposition: SubmitDisposition::InvalidOrder as i32,
+				reason: "Amount must be greater than 0".to_string(),
+			}));
+		}
+
+		if let Err(e) = ledger.check_adjustment(&cmd) {
+			let disposition = match e {
+				LedgerError::InsufficientBalance { .. } => SubmitDisposition::InsufficientBalance,
+				LedgerError::UnknownAsset(_) => SubmitDisposition::InvalidOrder,
+			};
+			return Ok(Response::new(AdjustBalanceResponse {
+				disposition: disposition as i32,
+				reason: e.to_string(),
+			}));
+		}
+
+		let (disposition, reason) = match self
+			.queue_sender
+			.try_enqueue_command(EngineCommand::AdjustBalance(cmd))
+		{
+			Ok(_) => (SubmitDisposition::AcceptedOk, String::new()),
+			Err(crate::queue::QueueError::Full) => (
+				SubmitDisposition::OverloadedEngine,
+				"Matching engine overloaded, please retry".to_string(),
+			),
+			Err(e) => (
+				SubmitDisposition::InternalError,
+				format!("Queue error: {}", e),
+			),
+		};
+		info!(
+			public_key = %req.public_key,
+			asset = %req.asset,
+			amount = req.amount,
+			adjustment = ?req.adjustment(),
+			disposition = ?disposition,
+			"Balance adjustment"
+		);
+
+		Ok(Response::new(AdjustBalanceResponse {
+			disposition: disposition as i32,
+			reason,
+		}))
+	}
+
+	async fn get_balances(
+		&self,
+		request: Request<GetBalancesRequest>,
+	) -> Result<Response<GetBalancesResponse>, Status> {
+		let ledger = self.ledger()?;
+		let req = request.into_inner();
+
+		let balances = ledger
+			.balances(&req.public_key)
+			.into_iter()
+			.map(|(asset, balance)| AssetBalance {
+				asset,
+				available: balance.available.to_string(),
+				locked: balance.locked.to_string(),
+			})
+			.collect();
+
+		Ok(Response::new(GetBalancesResponse { balances }))
+	}
+
+	async fn get_order_book(
+		&self,
+		request: Request<GetOrderBookRequest>,
+	) -> Result<Response<GetOrderBookResponse>, Status> {
+		let req = request.into_inner();
+		let snapshot = self.market_data(&req.market)?.snapshot(req.depth as usize);
+
+		Ok(Response::new(GetOrderBookResponse {
+			market: snapshot.market,
+			sequence: snapshot.sequence,
+			event_sequence: snapshot.event_sequence,
+			bids: snapshot.bids.into_iter().map(Into::into).collect(),
+			asks: snapshot.asks.into_iter().map(Into::into).collect(),
+		}))
+	}
+
+	async fn get_candles(
+		&self,
+		request: Request<GetCandlesRequest>,
+	) -> Result<Response<GetCandlesResponse>, Status> {
+		let req = request.into_inner();
+		let market_data = self.market_data(&req.market)?;
+		let interval: market_data::CandleInterval = req
+			.interval
+			.parse()
+			.map_err(|e: market_data::InvalidInterval| Status::invalid_argument(e.to_string()))?;
+
+		Ok(Response::new(GetCandlesResponse {
+			market: req.market,
+			interval: interval.to_string(),
+			candles: market_data
+				.candles(interval, req.limit as usize)
+				.into_iter()
+				.map(Into::into)
+				.collect(),
+		}))
+	}
+
+	async fn get_ticker(
+		&self,
+		request: Request<GetTickerRequest>,
+	) -> Result<Response<GetTickerResponse>, Status> {
+		let req = request.into_inner();
+		let ticker = self.market_data(&req.market)?.ticker();
+
+		Ok(Response::new(ticker.into()))
+	}
+
+	async fn list_trades(
+		&self,
+		request: Request<ListTradesRequest>,
+	) -> Result<Response<ListTradesResponse>, Status> {
+		let req = request.into_inner();
+		let query = Self::history_query(req.start_time, req.end_time, &req.cursor, req.limit)?;
+		let page = self.trade_history(&req.market)?.trades(&req.market, &query);
+
+		Ok(Response::new(ListTradesResponse {
+			trades: page
+				.items
+				.into_iter()
+				.map(|record| record.trade.into())
+				.collect(),
+			next_cursor: page
+				.next_cursor
+				.map(|cursor| cursor.to_string())
+				.unwrap_or_default(),
+		}))
+	}
+
+	async fn list_fills(
+		&self,
+		request: Request<ListFillsRequest>,
+	) -> Result<Response<ListFillsResponse>, Status> {
+		let req = request.into_inner();
+		if req.public_key.is_empty() {
+			return Err(Status::invalid_argument("public_key is required"));
+		}
+		let query = Self::history_query(req.start_time, req.end_time, &req.cursor, req.limit)?;
+		let page = self
+			.trade_history(&req.market)?
+			.fills(&req.public_key, &req.market, &query);
+
+		Ok(Response::new(ListFillsResponse {
+			fills: page
+				.items
+				.into_iter()
+				.map(|fill| ProtoFill {
+					trade_id: fill.trade_id,
+					market: fill.market,
+					order_id: fill.order_id,
+					side: match fill.side {
+						Side::Buy => ProtoOrderSide::Buy as i32,
+						Side::Sell => ProtoOrderSide::Sell as i32,
+					},
+					price: fill.price,
+					size: fill.size,
+					role: match fill.role {
+						LiquidityRole::Maker => ProtoLiquidityRole::Maker as i32,
+						LiquidityRole::Taker => ProtoLiquidityRole::Taker as i32,
+					},
+					timestamp: fill.timestamp,
+				})
+				.collect(),
+			next_cursor: page
+				.next_cursor
+				.map(|cursor| cursor.to_string())
+				.unwrap_or_default(),
+		}))
+	}
+
+	type StreamBookUpdatesStream =
+		tokio_stream::wrappers::ReceiverStream<Result<ProtoBookUpdate, Status>>;
+
+	async fn stream_book_updates(
+		&self,
+		request: Request<StreamBookUpdatesRequest>,
+	) -> Result<Response<Self::StreamBookUpdatesStream>, Status> {
+		let req = request.into_inner();
+		let mut updates = self.market_data(&req.market)?.subscribe();
+		let (tx, rx) = tokio::sync::mpsc::channel(128);
+
+		tokio::spawn(async move {
+			loop {
+				let item = match updates.recv().await {
+					Ok(update) => Ok(update.into()),
+					Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+						// The subscriber missed updates and must resync from a snapshot
+						Err(Status::data_loss(format!(
+							"Subscriber lagged by {} updates, resync required",
+							skipped
+						)))
+					}
+					Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+				};
+				let terminal = item.is_err();
+				if tx.send(item).await.is_err() || terminal {
+					break;
+				}
+			}
+		});
+
+		Ok(Response::new(tokio_stream::wrappers::ReceiverStream::new(
+			rx,
+		)))
+	}
+
+	type StreamMarketDataStream =
+		tokio_stream::wrappers::ReceiverStream<Result<ProtoMarketDataUpdate, Status>>;
+
+	async fn stream_market_data(
+		&self,
+		request: Request<StreamMarketDataRequest>,
+	) -> Result<Response<Self::StreamMarketDataStream>, Status> {
+		let req = request.into_inner();
+		let mut updates = self.market_data(&req.market)?.subscribe_all();
+		let (tx, rx) = tokio::sync::mpsc::channel(128);
+
+		tokio::spawn(async move {
+			loop {
+				let item = match updates.recv().await {
+					Ok(update) => Ok(proto_market_data_update(&req.market, update)),
+					Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+						Err(Status::data_loss(format!(
+							"Subscriber lagged by {} updates, resync required",
+							skipped
+						)))
+					}
+					Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+				};
+				let terminal = item.is_err();
+				if tx.send(item).await.is_err() || terminal {
+					break;
+				}
+			}
+		});
+
+		Ok(Response::new(tokio_stream::wrappers::ReceiverStream::new(
+			rx,
+		)))
+	}
+
+	type StreamOrderFeedStream =
+		tokio_stream::wrappers::ReceiverStream<Result<ProtoOrderFeedMessage, Status>>;
+
+	async fn stream_order_feed(
+		&self,
+		request: Request<StreamOrderFeedRequest>,
+	) -> Result<Response<Self::StreamOrderFeedStream>, Status> {
+		let req = request.into_inner();
+		let mut prev_sequence = 0;
+		let stream = self.subscribe_events(&req.market, req.from_sequence, move |event| {
+			let mut message =
+				ProtoOrderFeedMessage::from(market_data::OrderFeedMessage::from_event(&event)?);
+			message.prev_sequence = prev_sequence;
+			prev_sequence = message.sequence;
+			Some(message)
+		})?;
+
+		Ok(Response::new(stream))
 	}
 
 	type StreamMatchedTradesStream =
@@ -322,13 +1186,381 @@ impl MatchingService for MatchingServiceImpl {
 
 	async fn stream_matched_trades(
 		&self,
-		_request: Request<StreamMatchedTradesRequest>,
+		request: Request<StreamMatchedTradesRequest>,
 	) -> Result<Response<Self::StreamMatchedTradesStream>, Status> {
-		// TODO: Implement streaming from event buffer
-		let (_tx, rx) = tokio::sync::mpsc::channel(128);
-		Ok(Response::new(tokio_stream::wrappers::ReceiverStream::new(
-			rx,
-		)))
+		let req = request.into_inner();
+		info!(
+			market = %req.market,
+			from_sequence = req.from_sequence,
+			"Matched trade subscription"
+		);
+		let stream =
+			self.subscribe_events(&req.market, req.from_sequence, |event| match event {
+				MatchingEvent::TradeExecuted {
+					seq,
+					trade,
+					maker_public_key,
+					taker_public_key,
+					..
+				} => Some(MatchedTrade {
+					market: trade.market.clone(),
+					trade: Some(trade.into()),
+					sequence: seq,
+					maker_public_key,
+					taker_public_key,
+				}),
+				_ => None,
+			})?;
+
+		Ok(Response::new(stream))
+	}
+
+	type StreamExecutionReportsStream =
+		tokio_stream::wrappers::ReceiverStream<Result<ProtoExecutionReport, Status>>;
+
+	async fn stream_execution_reports(
+		&self,
+		request: Request<StreamExecutionReportsRequest>,
+	) -> Result<Response<Self::StreamExecutionReportsStream>, Status> {
+		let req = request.into_inner();
+		if req.public_key.is_empty() {
+			return Err(Status::invalid_argument("public_key is required"));
+		}
+		info!(
+			market = %req.market,
+			public_key = %req.public_key,
+			from_sequence = req.from_sequence,
+			"Execution report subscription"
+		);
+
+		let mut filter = ExecutionReportFilter::new(req.public_key);
+		let stream = self.subscribe_events(&req.market, req.from_sequence, move |event| {
+			filter
+				.reports(&event)
+				.into_iter()
+				.map(ProtoExecutionReport::from)
+		})?;
+
+		Ok(Response::new(stream))
+	}
+
+	async fn get_engine_status(
+		&self,
+		_request: Request<GetEngineStatusRequest>,
+	) -> Result<Response<GetEngineStatusResponse>, Status> {
+		let hub = self
+			.event_hub
+			.as_ref()
+			.ok_or_else(|| Status::failed_precondition("Event feed is disabled"))?;
+
+		let status = self.replication.status();
+
+		Ok(Response::new(GetEngineStatusResponse {
+			market: self.market.clone(),
+			last_sequence: hub.last_sequence(),
+			role: ProtoEngineRole::from(status.role) as i32,
+			epoch: status.epoch,
+			replication_lag: self.replication.lag(),
+		}))
+	}
+
+	type StreamEventsStream =
+		tokio_stream::wrappers::ReceiverStream<Result<ReplicatedEvent, Status>>;
+
+	async fn stream_events(
+		&self,
+		request: Request<StreamEventsRequest>,
+	) -> Result<Response<Self::StreamEventsStream>, Status> {
+		let req = request.into_inner();
+		info!(
+			market = %req.market,
+			from_sequence = req.from_sequence,
+			"Replication subscription"
+		);
+
+		let hub = self.event_hub.clone();
+		let stream = self.subscribe_events(&req.market, req.from_sequence, move |event| {
+			let sequence = event.sequence();
+			match serde_json::to_vec(&event) {
+				Ok(event) => Some(ReplicatedEvent {
+					sequence,
+					event,
+					last_sequence: hub.as_ref().map_or(sequence, |hub| hub.last_sequence()),
+				}),
+				Err(e) => {
+					error!(seq = sequence, error = %e, "Failed to encode event for replication");
+					None
+				}
+			}
+		})?;
+
+		Ok(Response::new(stream))
+	}
+
+	async fn promote(
+		&self,
+		request: Request<PromoteRequest>,
+	) -> Result<Response<PromoteResponse>, Status> {
+		let epoch = request.into_inner().epoch;
+		if self.leader_elected {
+			return Err(Status::failed_precondition(
+				"The primary is chosen by leader election",
+			));
+		}
+		self.replication
+			.promote(epoch)
+			.await
+			.map_err(|e| Status::failed_precondition(e.to_string()))?;
+		info!(
+			epoch,
+			applied_sequence = self.replication.applied_sequence(),
+			"Promoted to primary"
+		);
+
+		Ok(Response::new(PromoteResponse {
+			epoch,
+			applied_sequence: self.replication.applied_sequence(),
+		}))
+	}
+
+	async fn fence(
+		&self,
+		request: Request<FenceRequest>,
+	) -> Result<Response<FenceResponse>, Status> {
+		let epoch = request.into_inner().epoch;
+		self.replication
+			.fence(epoch)
+			.await
+			.map_err(|e| Status::failed_precondition(e.to_string()))?;
+		warn!(
+			epoch,
+			"Fenced by a newer primary, no longer accepting orders"
+		);
+
+		Ok(Response::new(FenceResponse {}))
+	}
+}
+
+impl From<ReplicationRole> for ProtoEngineRole {
+	fn from(role: ReplicationRole) -> Self {
+		match role {
+			ReplicationRole::Primary => ProtoEngineRole::Primary,
+			ReplicationRole::Follower => ProtoEngineRole::Follower,
+			ReplicationRole::Fenced => ProtoEngineRole::Fenced,
+		}
+	}
+}
+
+impl From<ExecutionReport> for ProtoExecutionReport {
+	fn from(report: ExecutionReport) -> Self {
+		Self {
+			sequence: report.sequence,
+			market: report.market,
+			order_id: report.order_id,
+			client_order_id: report.client_order_id.unwrap_or_default(),
+			exec_type: match report.kind {
+				ExecutionKind::New => ExecutionType::Resting,
+				ExecutionKind::Rejected => ExecutionType::OrderRejected,
+				ExecutionKind::Trade => ExecutionType::Trade,
+				ExecutionKind::PartiallyFilled => ExecutionType::PartialFill,
+				ExecutionKind::Filled => ExecutionType::FullFill,
+				ExecutionKind::Cancelled => ExecutionType::OrderCancelled,
+				ExecutionKind::CancelRejected => ExecutionType::CancelRejected,
+			} as i32,
+			price: report.price,
+			size: report.size,
+			filled_size: report.filled_size,
+			remaining_size: report.remaining_size,
+			trade_id: report.trade_id,
+			role: match report.role {
+				Some(LiquidityRole::Taker) => ProtoLiquidityRole::Taker,
+				Some(LiquidityRole::Maker) | None => ProtoLiquidityRole::Maker,
+			} as i32,
+			reason: report.reason,
+			timestamp: report.timestamp,
+		}
+	}
+}
+
+impl From<OrderRecord> for ProtoOrder {
+	fn from(order: OrderRecord) -> Self {
+		Self {
+			order_id: order.order_id,
+			market: order.market,
+			side: match order.side {
+				Some(Side::Sell) => ProtoOrderSide::Sell,
+				Some(Side::Buy) | None => ProtoOrderSide::Buy,
+			} as i32,
+			price: order.price,
+			size: order.size,
+			filled_size: order.filled_size,
+			remaining_size: order.remaining_size,
+			status: match order.status {
+				OrderStatus::Pending => ProtoOrderStatus::Pending,
+				OrderStatus::Accepted => ProtoOrderStatus::Accepted,
+				OrderStatus::PartiallyFilled => ProtoOrderStatus::PartiallyFilled,
+				OrderStatus::Filled => ProtoOrderStatus::Filled,
+				OrderStatus::Cancelled => ProtoOrderStatus::Cancelled,
+				OrderStatus::Rejected => ProtoOrderStatus::Rejected,
+			} as i32,
+			created_at: order.created_at,
+			public_key: order.public_key,
+			client_order_id: order.client_order_id.unwrap_or_default(),
+		}
+	}
+}
+
+impl From<anvil_sdk::types::Trade> for ProtoTrade {
+	fn from(trade: anvil_sdk::types::Trade) -> Self {
+		Self {
+			trade_id: trade.trade_id,
+			market: trade.market,
+			price: trade.price,
+			size: trade.size,
+			side: match trade.side {
+				Side::Buy => ProtoOrderSide::Buy as i32,
+				Side::Sell => ProtoOrderSide::Sell as i32,
+			},
+			timestamp: trade.timestamp,
+			maker_order_id: trade.maker_order_id,
+			taker_order_id: trade.taker_order_id,
+		}
+	}
+}
+
+impl From<market_data::PriceLevel> for ProtoPriceLevel {
+	fn from(level: market_data::PriceLevel) -> Self {
+		Self {
+			price: level.price,
+			size: level.size,
+		}
+	}
+}
+
+impl From<market_data::Candle> for ProtoCandle {
+	fn from(candle: market_data::Candle) -> Self {
+		Self {
+			open_time: candle.open_time,
+			open: candle.open,
+			high: candle.high,
+			low: candle.low,
+			close: candle.close,
+			volume: candle.volume,
+			trade_count: candle.trade_count,
+		}
+	}
+}
+
+impl From<market_data::Ticker> for GetTickerResponse {
+	fn from(ticker: market_data::Ticker) -> Self {
+		Self {
+			market: ticker.market,
+			last: ticker.last,
+			open: ticker.open,
+			high: ticker.high,
+			low: ticker.low,
+			volume: ticker.volume,
+			change: ticker.change,
+			trade_count: ticker.trade_count,
+			timestamp: ticker.timestamp,
+		}
+	}
+}
+
+fn proto_market_data_update(
+	market: &str,
+	update: market_data::MarketDataUpdate,
+) -> ProtoMarketDataUpdate {
+	let (event_sequence, body) = match update {
+		market_data::MarketDataUpdate::Book(update) => (
+			update.event_sequence,
+			market_data_update::Body::Book(update.into()),
+		),
+		market_data::MarketDataUpdate::Trade {
+			event_sequence,
+			trade,
+		} => (
+			event_sequence,
+			market_data_update::Body::Trade(trade.into()),
+		),
+		market_data::MarketDataUpdate::Ticker {
+			event_sequence,
+			ticker,
+		} => (
+			event_sequence,
+			market_data_update::Body::Ticker(ticker.into()),
+		),
+		market_data::MarketDataUpdate::Candle {
+			event_sequence,
+			interval,
+			candle,
+		} => (
+			event_sequence,
+			market_data_update::Body::Candle(CandleUpdate {
+				interval: interval.to_string(),
+				candle: Some(candle.into()),
+			}),
+		),
+	};
+	ProtoMarketDataUpdate {
+		market: market.to_string(),
+		event_sequence,
+		body: Some(body),
+	}
+}
+
+impl From<market_data::BookUpdate> for ProtoBookUpdate {
+	fn from(update: market_data::BookUpdate) -> Self {
+		Self {
+			market: update.market,
+			sequence: update.sequence,
+			event_sequence: update.event_sequence,
+			bids: update.bids.into_iter().map(Into::into).collect(),
+			asks: update.asks.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+impl From<market_data::OrderFeedMessage> for ProtoOrderFeedMessage {
+	fn from(message: market_data::OrderFeedMessage) -> Self {
+		let body = match message.body {
+			market_data::OrderFeedBody::Add {
+				order_id,
+				side,
+				price,
+				size,
+			} => order_feed_message::Body::Add(OrderAdd {
+				order_id,
+				side: match side {
+					Side::Buy => ProtoOrderSide::Buy as i32,
+					Side::Sell => ProtoOrderSide::Sell as i32,
+				},
+				price,
+				size,
+			}),
+			market_data::OrderFeedBody::Execute {
+				order_id,
+				trade_id,
+				price,
+				size,
+			} => order_feed_message::Body::Execute(OrderExecute {
+				order_id,
+				trade_id,
+				price,
+				size,
+			}),
+			market_data::OrderFeedBody::Delete { order_id } => {
+				order_feed_message::Body::Delete(OrderDelete { order_id })
+			}
+		};
+
+		Self {
+			sequence: message.sequence,
+			prev_sequence: 0,
+			market: message.market,
+			timestamp: message.timestamp,
+			body: Some(body),
+		}
 	}
 }
 
@@ -0,0 +1,119 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+	fs,
+	path::PathBuf,
+	sync::{Arc, Mutex},
+};
+
+use super::SettlementStoreError;
+use crate::event::SequenceNumber;
+
+/// Position of the settlement forwarder in the committed event stream
+///
+/// Holds the sequence of the last trade that was settled or dead-lettered;
+/// 0 means nothing has been forwarded yet.
+pub trait SettlementCursor: Send {
+	/// Load the last settled sequence
+	fn load(&self) -> Result<SequenceNumber, SettlementStoreError>;
+
+	/// Record `sequence` as settled
+	fn store(&mut self, sequence: SequenceNumber) -> Result<(), SettlementStoreError>;
+}
+
+/// In-memory settlement cursor
+///
+/// Clones share the same position. Does not survive a restart.
+#[derive(Clone, Default)]
+pub struct MemorySettlementCursor {
+	sequence: Arc<Mutex<SequenceNumber>>,
+}
+
+impl MemorySettlementCursor {
+	pub fn new() -> Self {
+		Self::default()
+	}
+}
+
+impl SettlementCursor for MemorySettlementCursor {
+	fn load(&self) -> Result<SequenceNumber, SettlementStoreError> {
+		Ok(*self.sequence.lock().unwrap())
+	}
+
+	fn store(&mut self, sequence: SequenceNumber) -> Result<(), SettlementStoreError> {
+		*self.sequence.lock().unwrap() = sequence;
+		Ok(())
+	}
+}
+
+/// Settlement cursor persisted to a file
+///
+/// The sequence is written to a temporary file that is then renamed over the
+/// cursor file, so a crash leaves either the old or the new position.
+pub struct FileSettlementCursor {
+	path: PathBuf,
+}
+
+impl FileSettlementCursor {
+	pub fn new(path: impl Into<PathBuf>) -> Self {
+		Self { path: path.into() }
+	}
+}
+
+impl SettlementCursor for FileSettlementCursor {
+	fn load(&self) -> Result<SequenceNumber, SettlementStoreError> {
+		match fs::read_to_string(&self.path) {
+			Ok(contents) => contents.trim().parse().map_err(|_| {
+				SettlementStoreError::Corrupted(format!(
+					"invalid cursor in {}: {:?}",
+					self.path.display(),
+					contents
+				))
+			}),
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+			Err(e) => Err(e.into()),
+		}
+	}
+
+	fn store(&mut self, sequence: SequenceNumber) -> Result<(), SettlementStoreError> {
+		if let Some(parent) = self.path.parent() {
+			fs::create_dir_all(parent)?;
+		}
+		let tmp = self.path.with_extension("tmp");
+		fs::write(&tmp, sequence.to_string())?;
+		fs::rename(&tmp, &self.path)?;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn file_cursor_survives_reopen() {
+		let dir = std::env::temp_dir().join(format!("anvil-cursor-{}", uuid::Uuid::new_v4()));
+		let path = dir.join("settlement.cursor");
+
+		let mut cursor = FileSettlementCursor::new(&path);
+		assert_eq!(cursor.load().unwrap(), 0);
+		cursor.store(42).unwrap();
+
+		let reopened = FileSettlementCursor::new(&path);
+		assert_eq!(reopened.load().unwrap(), 42);
+
+		fs::remove_dir_all(dir).unwrap();
+	}
+}
@@ -0,0 +1,125 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+	fs::{self, OpenOptions},
+	io::{BufRead, BufReader, Write},
+	path::PathBuf,
+	sync::{Arc, Mutex},
+};
+
+use anvil_sdk::types::Trade;
+use serde::{Deserialize, Serialize};
+
+use super::SettlementStoreError;
+use crate::event::SequenceNumber;
+
+/// A trade batch the settlement service rejected
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetterBatch {
+	pub market: String,
+	/// Sequence of the first trade event in the batch
+	pub first_sequence: SequenceNumber,
+	/// Sequence of the last trade event in the batch
+	pub last_sequence: SequenceNumber,
+	pub trades: Vec<Trade>,
+	/// Rejection reason reported by the settlement service
+	pub reason: String,
+	/// Unix timestamp (seconds) when the batch was parked
+	pub parked_at: u64,
+}
+
+/// Storage for rejected settlement batches awaiting manual handling
+pub trait DeadLetterStore: Send {
+	/// Park a rejected batch
+	fn park(&mut self, batch: DeadLetterBatch) -> Result<(), SettlementStoreError>;
+
+	/// All parked batches, oldest first
+	fn list(&self) -> Result<Vec<DeadLetterBatch>, SettlementStoreError>;
+}
+
+/// In-memory dead-letter store
+///
+/// Clones share the same batches. Does not survive a restart.
+#[derive(Clone, Default)]
+pub struct MemoryDeadLetterStore {
+	batches: Arc<Mutex<Vec<DeadLetterBatch>>>,
+}
+
+impl MemoryDeadLetterStore {
+	pub fn new() -> Self {
+		Self::default()
+	}
+}
+
+impl DeadLetterStore for MemoryDeadLetterStore {
+	fn park(&mut self, batch: DeadLetterBatch) -> Result<(), SettlementStoreError> {
+		self.batches.lock().unwrap().push(batch);
+		Ok(())
+	}
+
+	fn list(&self) -> Result<Vec<DeadLetterBatch>, SettlementStoreError> {
+		Ok(self.batches.lock().unwrap().clone())
+	}
+}
+
+/// Dead-letter store appending one JSON batch per line to a file
+pub struct FileDeadLetterStore {
+	path: PathBuf,
+}
+
+impl FileDeadLetterStore {
+	pub fn new(path: impl Into<PathBuf>) -> Self {
+		Self { path: path.into() }
+	}
+}
+
+impl DeadLetterStore for FileDeadLetterStore {
+	fn park(&mut self, batch: DeadLetterBatch) -> Result<(), SettlementStoreError> {
+		if let Some(parent) = self.path.parent() {
+			fs::create_dir_all(parent)?;
+		}
+		let mut line = serde_json::to_string(&batch)
+			.map_err(|e| SettlementStoreError::Corrupted(e.to_string()))?;
+		line.push('\n');
+
+		let mut file = OpenOptions::new()
+			.create(true)
+			.append(true)
+			.open(&self.path)?;
+		file.write_all(line.as_bytes())?;
+		file.sync_data()?;
+		Ok(())
+	}
+
+	fn list(&self) -> Result<Vec<DeadLetterBatch>, SettlementStoreError> {
+		let file = match fs::File::open(&self.path) {
+			Ok(file) => file,
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+			Err(e) => return Err(e.into()),
+		};
+
+		let mut batches = Vec::new();
+		for line in BufReader::new(file).lines() {
+			let line = line?;
+			if line.trim().is_empty() {
+				continue;
+			}
+			let batch = serde_json::from_str(&line)
+				.map_err(|e| SettlementStoreError::Corrupted(e.to_string()))?;
+			batches.push(batch);
+		}
+		Ok(batches)
+	}
+}
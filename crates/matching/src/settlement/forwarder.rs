@@ -0,0 +1,451 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{future::Future, path::PathBuf, time::Duration};
+
+use anvil_sdk::types::Trade;
+use anvil_settlement::transaction::Chain;
+use serde::{Deserialize, Serialize};
+use tokio::{task::JoinHandle, time::Instant};
+use tracing::{error, info, warn};
+
+use super::{DeadLetterBatch, DeadLetterStore, SettlementCursor};
+use crate::client::{SettlementClientError, SettlementGrpcClient};
+use crate::event::{EventHub, MatchingEvent, SequenceNumber};
+
+/// Settlement forwarder configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SettlementForwarderConfig {
+	/// Forward committed trades to the settlement service
+	pub enabled: bool,
+	/// Maximum trades per `SubmitTrades` call
+	pub batch_size: usize,
+	/// Maximum time a trade waits for its batch to fill
+	pub batch_timeout_ms: u64,
+	/// Chain the trades settle on
+	pub chain: Chain,
+	/// Delay before the first retry of a failed submission
+	pub retry_initial_backoff_ms: u64,
+	/// Upper bound for the doubling retry delay
+	pub retry_max_backoff_ms: u64,
+	/// File holding the last settled sequence (in memory if unset)
+	pub cursor_path: Option<PathBuf>,
+	/// JSON-lines file for rejected batches (in memory if unset)
+	pub dead_letter_path: Option<PathBuf>,
+}
+
+impl Default for SettlementForwarderConfig {
+	fn default() -> Self {
+		Self {
+			enabled: false,
+			batch_size: 100,
+			batch_timeout_ms: 500,
+			chain: Chain::Solana,
+			retry_initial_backoff_ms: 100,
+			retry_max_backoff_ms: 30_000,
+			cursor_path: None,
+			dead_letter_path: None,
+		}
+	}
+}
+
+/// Destination of settlement batches
+///
+/// Implementations report batches the settlement service refuses as
+/// `SettlementClientError::Rejected`; every other error is retried.
+pub trait TradeSubmitter: Send + 'static {
+	/// Submit trades for settlement, returning the transaction hash
+	fn submit(
+		&mut self,
+		market: &str,
+		trades: Vec<Trade>,
+		chain: Chain,
+	) -> impl Future<Output = Result<String, SettlementClientError>> + Send;
+}
+
+impl TradeSubmitter for SettlementGrpcClient {
+	async fn submit(
+		&mut self,
+		market: &str,
+		trades: Vec<Trade>,
+		chain: Chain,
+	) -> Result<String, SettlementClientError> {
+		self.submit_trades(market, trades, chain).await
+	}
+}
+
+/// Forwards committed trades to settlement
+///
+/// Runs as a Tokio task subscribed to the [`EventHub`] from the cursor
+/// onwards. A batch is submitted once it holds `batch_size` trades or its
+/// oldest trade has waited `batch_timeout_ms`. Batches are settled strictly
+/// in order: the cursor only advances after a batch was accepted or parked
+/// as a dead letter, so after a restart trades are delivered at least once.
+pub struct SettlementForwarder<S> {
+	hub: EventHub,
+	submitter: S,
+	cursor: Box<dyn SettlementCursor>,
+	dead_letters: Box<dyn DeadLetterStore>,
+	config: SettlementForwarderConfig,
+}
+
+impl<S: TradeSubmitter> SettlementForwarder<S> {
+	pub fn new(
+		hub: EventHub,
+		submitter: S,
+		cursor: Box<dyn SettlementCursor>,
+		dead_letters: Box<dyn DeadLetterStore>,
+		config: SettlementForwarderConfig,
+	) -> Self {
+		Self {
+			hub,
+			submitter,
+			cursor,
+			dead_letters,
+			config,
+		}
+	}
+
+	/// Spawn the forwarder onto the current Tokio runtime
+	pub fn start(self) -> JoinHandle<()> {
+		tokio::spawn(self.run())
+	}
+
+	async fn run(mut self) {
+		let settled = match self.cursor.load() {
+			Ok(settled) => settled,
+			Err(e) => {
+				error!(target: "settlement", error = %e, "Failed to load settlement cursor");
+				return;
+			}
+		};
+		if settled > self.hub.last_sequence() {
+			warn!(
+				target: "settlement",
+				settled,
+				last_sequence = self.hub.last_sequence(),
+				"Settlement cursor is ahead of event storage"
+			);
+		}
+		info!(target: "settlement", settled, "Settlement forwarder started");
+
+		let batch_timeout = Duration::from_millis(self.config.batch_timeout_ms);
+		let batch_size = self.config.batch_size.max(1);
+		let mut next = settled + 1;
+		let mut batch: Vec<(SequenceNumber, Trade)> = Vec::new();
+
+		loop {
+			let mut events = self.hub.subscribe_from(next);
+			let mut deadline: Option<Instant> = None;
+
+			let closed = loop {
+				let received = match deadline {
+					Some(at) => match tokio::time::timeout_at(at, events.recv()).await {
+						Ok(received) => received,
+						Err(_) => {
+							self.settle(std::mem::take(&mut batch)).await;
+							deadline = None;
+							continue;
+						}
+					},
+					None => events.recv().await,
+				};
+
+				match received {
+					Some(Ok(event)) => {
+						next = event.sequence() + 1;
+						if let MatchingEvent::TradeExecuted { seq, trade, .. } = event {
+							batch.push((seq, trade));
+							if batch.len() >= batch_size {
+								self.settle(std::mem::take(&mut batch)).await;
+							}
+						}
+					}
+					Some(Err(e)) => {
+						warn!(target: "settlement", error = %e, next, "Trade subscription ended");
+						break false;
+					}
+					None => break true,
+				}
+
+				deadline = match (batch.is_empty(), deadline) {
+					(true, _) => None,
+					(false, None) => Some(Instant::now() + batch_timeout),
+					(false, deadline) => deadline,
+				};
+			};
+
+			// Everything received so far is gap-free, so settle it before
+			// resubscribing
+			self.settle(std::mem::take(&mut batch)).await;
+			if closed {
+				info!(target: "settlement", "Settlement forwarder stopped");
+				return;
+			}
+			tokio::time::sleep(Duration::from_millis(self.config.retry_initial_backoff_ms)).await;
+		}
+	}
+
+	/// Submit a batch until it is accepted or rejected, then advance the cursor
+	async fn settle(&mut self, batch: Vec<(SequenceNumber, Trade)>) {
+		let (Some(&(first_sequence, _)), Some(&(last_sequence, _))) = (batch.first(), batch.last())
+		else {
+			return;
+		};
+		let market = batch[0].1.market.clone();
+		let trades: Vec<Trade> = batch.into_iter().map(|(_, trade)| trade).collect();
+
+		let mut backoff = Duration::from_millis(self.config.retry_initial_backoff_ms);
+		let max_backoff = Duration::from_millis(self.config.retry_max_backoff_ms);
+		let mut attempt = 1u32;
+		loop {
+			match self
+				.submitter
+				.submit(&market, trades.clone(), self.config.chain)
+				.await
+			{
+				Ok(tx_hash) => {
+					info!(
+						target: "settlement",
+						first_sequence,
+						last_sequence,
+						trades = trades.len(),
+						tx_hash,
+						"Trade batch submitted for settlement"
+					);
+					break;
+				}
+				Err(SettlementClientError::Rejected(reason)) => {
+					warn!(
+						target: "settlement",
+						first_sequence,
+						last_sequence,
+						reason,
+						"Trade batch rejected, parking as dead letter"
+					);
+					let parked = DeadLetterBatch {
+						market: market.clone(),
+						first_sequence,
+						last_sequence,
+						trades: trades.clone(),
+						reason,
+						parked_at: now_secs(),
+					};
+					match self.dead_letters.park(parked) {
+						Ok(()) => break,
+						Err(e) => {
+							error!(target: "settlement", error = %e, "Failed to park rejected batch")
+						}
+					}
+				}
+				Err(e) => {
+					warn!(
+						target: "settlement",
+						first_sequence,
+						attempt,
+						error = %e,
+						retry_in_ms = backoff.as_millis() as u64,
+						"Settlement submission failed"
+					);
+				}
+			}
+			tokio::time::sleep(backoff).await;
+			backoff = (backoff * 2).min(max_backoff);
+			attempt += 1;
+		}
+
+		if let Err(e) = self.cursor.store(last_sequence) {
+			// The batch will be forwarded again after a restart
+			error!(target: "settlement", error = %e, last_sequence, "Failed to store settlement cursor");
+		}
+	}
+}
+
+fn now_secs() -> u64 {
+	std::time::SystemTime::now()
+		.duration_since(std::time::UNIX_EPOCH)
+		.unwrap_or_default()
+		.as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{
+		collections::VecDeque,
+		sync::{Arc, Mutex},
+	};
+
+	use anvil_sdk::types::Side;
+
+	use super::*;
+	use crate::event::{
+		CommittedEventSink, EventBatch, EventHubConfig, EventStorage, MemoryEventStorage,
+		SharedEventStorage,
+	};
+	use crate::settlement::{MemoryDeadLetterStore, MemorySettlementCursor};
+
+	/// Records submitted trade ids and replays scripted results (Ok when empty)
+	#[derive(Clone, Default)]
+	struct ScriptedSubmitter {
+		results: Arc<Mutex<VecDeque<Result<String, SettlementClientError>>>>,
+		submitted: Arc<Mutex<Vec<Vec<String>>>>,
+	}
+
+	impl TradeSubmitter for ScriptedSubmitter {
+		async fn submit(
+			&mut self,
+			_market: &str,
+			trades: Vec<Trade>,
+			_chain: Chain,
+		) -> Result<String, SettlementClientError> {
+			self.submitted
+				.lock()
+				.unwrap()
+				.push(trades.into_iter().map(|t| t.trade_id).collect());
+			self.results
+				.lock()
+				.unwrap()
+				.pop_front()
+				.unwrap_or_else(|| Ok("tx".to_string()))
+		}
+	}
+
+	fn trade(seq: SequenceNumber) -> MatchingEvent {
+		MatchingEvent::TradeExecuted {
+			seq,
+			trade: Trade {
+				trade_id: format!("t{}", seq),
+				market: "BTC-USDT".to_string(),
+				price: 100,
+				size: 1,
+				side: Side::Buy,
+				timestamp: 0,
+				maker_order_id: "maker".to_string(),
+				taker_order_id: "taker".to_string(),
+			},
+			maker_public_key: "maker_key".to_string(),
+			taker_public_key: "taker_key".to_string(),
+			maker_client_order_id: None,
+			taker_client_order_id: None,
+			timestamp: 0,
+		}
+	}
+
+	fn commit(storage: &mut SharedEventStorage, hub: &mut EventHub, seqs: &[SequenceNumber]) {
+		let events: Vec<_> = seqs.iter().map(|&seq| trade(seq)).collect();
+		storage
+			.append_batch(EventBatch::new(events.clone()))
+			.unwrap();
+		hub.on_committed(&events);
+	}
+
+	async fn wait_for_cursor(cursor: &MemorySettlementCursor, sequence: SequenceNumber) {
+		for _ in 0..200 {
+			if cursor.load().unwrap() == sequence {
+				return;
+			}
+			tokio::time::sleep(Duration::from_millis(10)).await;
+		}
+		panic!("settlement cursor did not reach {}", sequence);
+	}
+
+	fn test_config() -> SettlementForwarderConfig {
+		SettlementForwarderConfig {
+			enabled: true,
+			batch_size: 2,
+			batch_timeout_ms: 20,
+			retry_initial_backoff_ms: 1,
+			retry_max_backoff_ms: 5,
+			..Default::default()
+		}
+	}
+
+	#[tokio::test]
+	async fn batches_by_size_and_time_and_resumes_from_cursor() {
+		let mut storage = SharedEventStorage::new(Box::new(MemoryEventStorage::new()));
+		let mut hub = EventHub::new(storage.clone(), EventHubConfig::default());
+		commit(&mut storage, &mut hub, &[1, 2, 3, 4, 5]);
+
+		let submitter = ScriptedSubmitter::default();
+		let cursor = MemorySettlementCursor::new();
+		let forwarder = SettlementForwarder::new(
+			hub.clone(),
+			submitter.clone(),
+			Box::new(cursor.clone()),
+			Box::new(MemoryDeadLetterStore::new()),
+			test_config(),
+		)
+		.start();
+
+		// Two full batches, then the remainder once the timeout expires
+		wait_for_cursor(&cursor, 5).await;
+		forwarder.abort();
+		assert_eq!(
+			*submitter.submitted.lock().unwrap(),
+			vec![vec!["t1", "t2"], vec!["t3", "t4"], vec!["t5"]]
+		);
+
+		// A restarted forwarder only sees trades after the cursor
+		let resumed = ScriptedSubmitter::default();
+		SettlementForwarder::new(
+			hub.clone(),
+			resumed.clone(),
+			Box::new(cursor.clone()),
+			Box::new(MemoryDeadLetterStore::new()),
+			test_config(),
+		)
+		.start();
+		commit(&mut storage, &mut hub, &[6]);
+
+		wait_for_cursor(&cursor, 6).await;
+		assert_eq!(*resumed.submitted.lock().unwrap(), vec![vec!["t6"]]);
+	}
+
+	#[tokio::test]
+	async fn retries_failures_and_parks_rejected_batches() {
+		let mut storage = SharedEventStorage::new(Box::new(MemoryEventStorage::new()));
+		let mut hub = EventHub::new(storage.clone(), EventHubConfig::default());
+		commit(&mut storage, &mut hub, &[1, 2, 3, 4]);
+
+		let submitter = ScriptedSubmitter::default();
+		submitter.results.lock().unwrap().extend([
+			Err(SettlementClientError::Transport("unavailable".to_string())),
+			Ok("tx1".to_string()),
+			Err(SettlementClientError::Rejected("invalid trade".to_string())),
+		]);
+		let cursor = MemorySettlementCursor::new();
+		let dead_letters = MemoryDeadLetterStore::new();
+		SettlementForwarder::new(
+			hub.clone(),
+			submitter.clone(),
+			Box::new(cursor.clone()),
+			Box::new(dead_letters.clone()),
+			test_config(),
+		)
+		.start();
+
+		wait_for_cursor(&cursor, 4).await;
+		assert_eq!(
+			*submitter.submitted.lock().unwrap(),
+			vec![vec!["t1", "t2"], vec!["t1", "t2"], vec!["t3", "t4"]]
+		);
+
+		let parked = dead_letters.list().unwrap();
+		assert_eq!(parked.len(), 1);
+		assert_eq!(parked[0].first_sequence, 3);
+		assert_eq!(parked[0].last_sequence, 4);
+		assert_eq!(parked[0].reason, "invalid trade");
+	}
+}
@@ -0,0 +1,46 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Trade-to-settlement pipeline
+//!
+//! The [`SettlementForwarder`] follows the committed event stream through
+//! `EventHub`, batches `TradeExecuted` events by size and time and submits
+//! them to the settlement service.
+//!
+//! Progress is tracked by a [`SettlementCursor`] holding the sequence of the
+//! last settled trade, so a restarted forwarder resumes right after it.
+//! Transient failures are retried with exponential backoff; batches the
+//! settlement service rejects are parked in a [`DeadLetterStore`] and the
+//! cursor moves on.
+
+mod cursor;
+mod dead_letter;
+mod forwarder;
+
+use thiserror::Error;
+
+pub use cursor::{FileSettlementCursor, MemorySettlementCursor, SettlementCursor};
+pub use dead_letter::{
+	DeadLetterBatch, DeadLetterStore, FileDeadLetterStore, MemoryDeadLetterStore,
+};
+pub use forwarder::{SettlementForwarder, SettlementForwarderConfig, TradeSubmitter};
+
+/// Error types for settlement cursor and dead-letter persistence
+#[derive(Debug, Error)]
+pub enum SettlementStoreError {
+	#[error("I/O error: {0}")]
+	Io(#[from] std::io::Error),
+	#[error("Corrupted settlement state: {0}")]
+	Corrupted(String),
+}
@@ -0,0 +1,288 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+	fs::{self, File},
+	io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+	path::{Path, PathBuf},
+};
+
+use tracing::warn;
+
+use super::{SnapshotData, SnapshotEncoder, SnapshotError, SnapshotMetadata, SnapshotStorage};
+use crate::event::SequenceNumber;
+
+/// Length of the footer that ends a snapshot file
+const FOOTER_LEN: u64 = 4;
+
+/// Snapshot storage in a directory, one file per snapshot
+///
+/// A snapshot file holds the state data, then the metadata as JSON and the
+/// length of the metadata (`u32`, little endian). The state data is
+/// streamed to and from the file; the metadata trails it because the
+/// checksum and size are only known once the data is written. Files are
+/// written under a temporary name, synced and renamed into place, so a
+/// crash never leaves a partial snapshot behind.
+pub struct FileSnapshotStorage {
+	dir: PathBuf,
+}
+
+impl FileSnapshotStorage {
+	/// Open the snapshot directory `dir`, creating it if needed
+	pub fn open(dir: impl Into<PathBuf>) -> Result<Self, SnapshotError> {
+		let dir = dir.into();
+		fs::create_dir_all(&dir).map_err(|e| load_error(&dir, e))?;
+		Ok(Self { dir })
+	}
+
+	/// Snapshot files ordered by sequence
+	fn files(&self) -> Result<Vec<(SequenceNumber, PathBuf)>, SnapshotError> {
+		let entries = fs::read_dir(&self.dir).map_err(|e| load_error(&self.dir, e))?;
+		let mut files = Vec::new();
+		for entry in entries {
+			let path = entry.map_err(|e| load_error(&self.dir, e))?.path();
+			let seq = path
+				.file_name()
+				.and_then(|name| name.to_str())
+				.and_then(|name| name.strip_prefix("snapshot-"))
+				.and_then(|name| name.strip_suffix(".snap"))
+				.and_then(|seq| seq.parse().ok());
+			if let Some(seq) = seq {
+				files.push((seq, path));
+			}
+		}
+		files.sort_by_key(|(seq, _)| *seq);
+		Ok(files)
+	}
+
+	/// Open a snapshot file, returning its metadata and state data
+	fn open_file(path: &Path) -> Result<(SnapshotMetadata, SnapshotData), SnapshotError> {
+		let mut file = File::open(path).map_err(|e| load_error(path, e))?;
+		let (metadata, data_len) = Self::read_metadata(path, &mut file)?;
+		file.rewind().map_err(|e| load_error(path, e))?;
+		Ok((metadata, Box::new(BufReader::new(file).take(data_len))))
+	}
+
+	/// Read the metadata at the end of a snapshot file, along with the
+	/// length of the state data before it
+	fn read_metadata(
+		path: &Path,
+		file: &mut File,
+	) -> Result<(SnapshotMetadata, u64), SnapshotError> {
+		let corrupted =
+			|reason: &str| SnapshotError::Corrupted(format!("{}: {}", path.display(), reason));
+		let file_len = file.metadata().map_err(|e| load_error(path, e))?.len();
+		let mut footer = [0u8; FOOTER_LEN as usize];
+		file.seek(SeekFrom::End(-(FOOTER_LEN as i64)))
+			.and_then(|_| file.read_exact(&mut footer))
+			.map_err(|_| corrupted("missing metadata footer"))?;
+		let metadata_len = u32::from_le_bytes(footer) as u64;
+		let data_len = file_len
+			.checked_sub(FOOTER_LEN + metadata_len)
+			.ok_or_else(|| corrupted("metadata longer than the file"))?;
+
+		let mut metadata = vec![0u8; metadata_len as usize];
+		file.seek(SeekFrom::Start(data_len))
+			.and_then(|_| file.read_exact(&mut metadata))
+			.map_err(|e| load_error(path, e))?;
+		let metadata = serde_json::from_slice(&metadata).map_err(|e| corrupted(&e.to_string()))?;
+		Ok((metadata, data_len))
+	}
+
+	/// Stream the state data and metadata of a snapshot to `tmp`
+	fn write_file(
+		tmp: &Path,
+		encode: &mut SnapshotEncoder<'_>,
+	) -> Result<SnapshotMetadata, SnapshotError> {
+		let write_error =
+			|e: io::Error| SnapshotError::CreationFailed(format!("{}: {}", tmp.display(), e));
+		let mut out = BufWriter::new(File::create(tmp).map_err(write_error)?);
+		let metadata = encode(&mut out).map_err(SnapshotError::CreationFailed)?;
+
+		let footer = serde_json::to_vec(&metadata)
+			.map_err(|e| SnapshotError::CreationFailed(e.to_string()))?;
+		out.write_all(&footer)
+			.and_then(|()| out.write_all(&(footer.len() as u32).to_le_bytes()))
+			.map_err(write_error)?;
+		let file = out.into_inner().map_err(|e| write_error(e.into_error()))?;
+		file.sync_all().map_err(write_error)?;
+		Ok(metadata)
+	}
+}
+
+impl SnapshotStorage for FileSnapshotStorage {
+	fn save_with(
+		&mut self,
+		encode: &mut SnapshotEncoder<'_>,
+	) -> Result<SnapshotMetadata, SnapshotError> {
+		// The sequence, and so the file name, is known once encoded
+		let tmp = self.dir.join("snapshot.tmp");
+		let metadata = Self::write_file(&tmp, encode).inspect_err(|_| {
+			let _ = fs::remove_file(&tmp);
+		})?;
+
+		let path = self
+			.dir
+			.join(format!("snapshot-{:020}.snap", metadata.event_seq));
+		fs::rename(&tmp, &path)
+			.and_then(|()| File::open(&self.dir)?.sync_all())
+			.map_err(|e| {
+				let _ = fs::remove_file(&tmp);
+				SnapshotError::CreationFailed(format!("Failed to write {}: {}", path.display(), e))
+			})?;
+		Ok(metadata)
+	}
+
+	fn open_at_seq(
+		&self,
+		seq: SequenceNumber,
+	) -> Result<(SnapshotMetadata, SnapshotData), SnapshotError> {
+		let (_, path) = self
+			.files()?
+			.into_iter()
+			.rev()
+			.find(|(file_seq, _)| *file_seq <= seq)
+			.ok_or(SnapshotError::NotFound)?;
+		Self::open_file(&path)
+	}
+
+	fn list_snapshots(&self) -> Vec<SnapshotMetadata> {
+		let files = self.files().unwrap_or_else(|e| {
+			warn!(target: "snapshotter", error = %e, "Failed to list snapshots");
+			Vec::new()
+		});
+		files
+			.iter()
+			.filter_map(|(_, path)| {
+				let file = File::open(path).map_err(|e| load_error(path, e));
+				file.and_then(|mut file| Self::read_metadata(path, &mut file))
+					.map(|(metadata, _)| metadata)
+					.inspect_err(
+						|e| warn!(target: "snapshotter", error = %e, "Skipping unreadable snapshot"),
+					)
+					.ok()
+			})
+			.collect()
+	}
+
+	fn cleanup_before(&mut self, seq: SequenceNumber) -> Result<usize, SnapshotError> {
+		let mut deleted = 0;
+		for (_, path) in self.files()?.iter().filter(|(file_seq, _)| *file_seq < seq) {
+			fs::remove_file(path).map_err(|e| load_error(path, e))?;
+			deleted += 1;
+		}
+		Ok(deleted)
+	}
+}
+
+fn load_error(path: &Path, e: io::Error) -> SnapshotError {
+	SnapshotError::LoadFailed(format!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::snapshot::{
+		Snapshot,
+		format::{SNAPSHOT_FORMAT_VERSION, SnapshotReader, SnapshotWriter, checksum},
+	};
+
+	fn create_test_snapshot(seq: SequenceNumber) -> Snapshot {
+		let state_data = format!("state at {}\nwith a newline", seq).into_bytes();
+		Snapshot {
+			metadata: SnapshotMetadata {
+				created_at: 1000,
+				event_seq: seq,
+				size_bytes: state_data.len(),
+				market: "BTC-USDT".to_string(),
+				format_version: SNAPSHOT_FORMAT_VERSION,
+				checksum: Some(checksum(&state_data)),
+			},
+			state_data,
+		}
+	}
+
+	#[test]
+	fn snapshots_survive_reopen_and_cleanup() {
+		let dir = std::env::temp_dir().join(format!("anvil-snapshots-{}", uuid::Uuid::new_v4()));
+		let mut storage = FileSnapshotStorage::open(&dir).unwrap();
+		assert!(matches!(
+			storage.load_latest(),
+			Err(SnapshotError::NotFound)
+		));
+		for seq in [100, 300, 200] {
+			storage.save(create_test_snapshot(seq)).unwrap();
+		}
+
+		let mut storage = FileSnapshotStorage::open(&dir).unwrap();
+		let latest = storage.load_latest().unwrap();
+		assert_eq!(latest.state_data, create_test_snapshot(300).state_data);
+		assert!(latest.verify().is_ok());
+		assert_eq!(storage.load_at_seq(250).unwrap().metadata.event_seq, 200);
+		let seqs = |storage: &FileSnapshotStorage| -> Vec<u64> {
+			storage
+				.list_snapshots()
+				.iter()
+				.map(|m| m.event_seq)
+				.collect()
+		};
+		assert_eq!(seqs(&storage), [100, 200, 300]);
+
+		assert_eq!(storage.cleanup_before(200).unwrap(), 1);
+		assert_eq!(seqs(&storage), [200, 300]);
+		assert!(storage.load_at_seq(150).is_err());
+
+		fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn streamed_snapshots_are_read_back_and_verified() {
+		let dir = std::env::temp_dir().join(format!("anvil-snapshots-{}", uuid::Uuid::new_v4()));
+		let mut storage = FileSnapshotStorage::open(&dir).unwrap();
+		let saved = storage
+			.save_with(&mut |out| {
+				let mut writer = SnapshotWriter::new(out)?;
+				writer.write(&vec![7u64; 1000])?;
+				let (checksum, size_bytes) = writer.finish()?;
+				Ok(SnapshotMetadata {
+					created_at: 1000,
+					event_seq: 42,
+					size_bytes,
+					market: "BTC-USDT".to_string(),
+					format_version: SNAPSHOT_FORMAT_VERSION,
+					checksum: Some(checksum),
+				})
+			})
+			.unwrap();
+		assert_eq!(storage.verify(42).unwrap().checksum, saved.checksum);
+		assert!(matches!(storage.verify(41), Err(SnapshotError::NotFound)));
+
+		let (metadata, data) = storage.open_at_seq(100).unwrap();
+		let mut reader = SnapshotReader::new(data, metadata.format_version).unwrap();
+		assert_eq!(reader.read::<Vec<u64>>().unwrap(), vec![7u64; 1000]);
+		assert!(reader.finish(&metadata).is_ok());
+
+		// Flip a byte of the state data
+		let path = dir.join(format!("snapshot-{:020}.snap", 42));
+		let mut bytes = fs::read(&path).unwrap();
+		bytes[saved.size_bytes - 1] ^= 0xff;
+		fs::write(&path, bytes).unwrap();
+		assert!(matches!(
+			storage.verify(42),
+			Err(SnapshotError::Corrupted(_))
+		));
+
+		fs::remove_dir_all(dir).unwrap();
+	}
+}
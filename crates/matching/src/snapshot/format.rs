@@ -0,0 +1,278 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Binary encoding of snapshot state
+//!
+//! Snapshot data starts with a header, the magic bytes `ANVS` and the
+//! schema version (`u32`, little endian), followed by the state as a series
+//! of values in bincode's standard encoding. Values are written and read
+//! one at a time, straight to and from the snapshot storage, so that large
+//! collections such as the orders of a book are streamed rather than copied
+//! into an intermediate buffer. A CRC32 of the whole data is computed while
+//! writing, kept in [`SnapshotMetadata`], and checked again while reading.
+//!
+//! Versions:
+//!
+//! - **1**: the state as one JSON document (snapshots without a version)
+//! - **2**: binary header and values
+
+use std::io::{self, Read, Write};
+
+use bincode::config::{Configuration, standard};
+use serde::{Serialize, de::DeserializeOwned};
+
+use super::SnapshotMetadata;
+
+/// Schema version of snapshots taken by this build
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 2;
+
+/// Schema version of JSON snapshots, which carry no version
+pub const JSON_SNAPSHOT_VERSION: u32 = 1;
+
+const MAGIC: &[u8; 4] = b"ANVS";
+
+const CONFIG: Configuration = standard();
+
+/// CRC32 of snapshot data
+pub fn checksum(data: &[u8]) -> u32 {
+	crc32fast::hash(data)
+}
+
+/// Writes snapshot values to `inner`, computing the checksum on the way
+pub struct SnapshotWriter<W: Write> {
+	inner: W,
+	hasher: crc32fast::Hasher,
+	bytes: usize,
+}
+
+impl<W: Write> SnapshotWriter<W> {
+	/// Start data of the current version
+	pub fn new(inner: W) -> Result<Self, String> {
+		let mut writer = Self {
+			inner,
+			hasher: crc32fast::Hasher::new(),
+			bytes: 0,
+		};
+		writer
+			.write_all(MAGIC)
+			.and_then(|()| writer.write_all(&SNAPSHOT_FORMAT_VERSION.to_le_bytes()))
+			.map_err(|e| format!("Failed to write snapshot header: {}", e))?;
+		Ok(writer)
+	}
+
+	/// Append one value
+	pub fn write<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), String> {
+		bincode::serde::encode_into_std_write(value, self, CONFIG)
+			.map(|_| ())
+			.map_err(|e| format!("Failed to encode snapshot: {}", e))
+	}
+
+	/// Checksum and size of the data written
+	pub fn finish(mut self) -> Result<(u32, usize), String> {
+		self.inner
+			.flush()
+			.map_err(|e| format!("Failed to write snapshot: {}", e))?;
+		Ok((self.hasher.finalize(), self.bytes))
+	}
+}
+
+impl<W: Write> Write for SnapshotWriter<W> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		let written = self.inner.write(buf)?;
+		self.hasher.update(&buf[..written]);
+		self.bytes += written;
+		Ok(written)
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.inner.flush()
+	}
+}
+
+/// Reads snapshot data from `inner`, computing the checksum on the way
+pub struct ChecksumReader<R: Read> {
+	inner: R,
+	hasher: crc32fast::Hasher,
+}
+
+impl<R: Read> ChecksumReader<R> {
+	pub fn new(inner: R) -> Self {
+		Self {
+			inner,
+			hasher: crc32fast::Hasher::new(),
+		}
+	}
+
+	/// Check the data read against the checksum in `metadata`
+	pub fn finish(self, metadata: &SnapshotMetadata) -> Result<(), String> {
+		match metadata.checksum {
+			Some(expected) if self.hasher.finalize() != expected => Err(format!(
+				"checksum mismatch for snapshot at seq={}",
+				metadata.event_seq
+			)),
+			_ => Ok(()),
+		}
+	}
+}
+
+impl<R: Read> Read for ChecksumReader<R> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		let read = self.inner.read(buf)?;
+		self.hasher.update(&buf[..read]);
+		Ok(read)
+	}
+}
+
+/// Reads the values of binary snapshot data in order
+pub struct SnapshotReader<R: Read> {
+	inner: ChecksumReader<R>,
+}
+
+impl<R: Read> SnapshotReader<R> {
+	/// Check the header of `data`, which must be of `version`
+	pub fn new(data: R, version: u32) -> Result<Self, String> {
+		let mut inner = ChecksumReader::new(data);
+		read_header(&mut inner, version)?;
+		Ok(Self { inner })
+	}
+
+	/// Read the next value
+	pub fn read<T: DeserializeOwned>(&mut self) -> Result<T, String> {
+		bincode::serde::decode_from_std_read(&mut self.inner, CONFIG)
+			.map_err(|e| format!("Failed to decode snapshot: {}", e))
+	}
+
+	/// Check that every value has been read and that the data matches the
+	/// checksum in `metadata`
+	pub fn finish(mut self, metadata: &SnapshotMetadata) -> Result<(), String> {
+		let mut rest = [0u8; 1];
+		match self.inner.read(&mut rest) {
+			Ok(0) => self.inner.finish(metadata),
+			Ok(_) => Err("Trailing data after snapshot state".to_string()),
+			Err(e) => Err(format!("Failed to read snapshot: {}", e)),
+		}
+	}
+}
+
+/// Read `data` to the end and check it against `metadata`
+///
+/// Checks that the format version is supported, and for binary data the
+/// header, without decoding the state.
+pub fn verify(metadata: &SnapshotMetadata, data: impl Read) -> Result<(), String> {
+	let mut data = ChecksumReader::new(data);
+	match metadata.format_version {
+		JSON_SNAPSHOT_VERSION => {}
+		SNAPSHOT_FORMAT_VERSION => read_header(&mut data, SNAPSHOT_FORMAT_VERSION)?,
+		other => return Err(format!("Unsupported snapshot format version {}", other)),
+	}
+	io::copy(&mut data, &mut io::sink()).map_err(|e| format!("Failed to read snapshot: {}", e))?;
+	data.finish(metadata)
+}
+
+fn read_header(data: &mut impl Read, version: u32) -> Result<(), String> {
+	let mut header = [0u8; MAGIC.len() + 4];
+	data.read_exact(&mut header).map_err(|e| match e.kind() {
+		io::ErrorKind::UnexpectedEof => "Snapshot data is too short".to_string(),
+		_ => format!("Failed to read snapshot: {}", e),
+	})?;
+	if &header[..MAGIC.len()] != MAGIC {
+		return Err("Snapshot data has no binary header".to_string());
+	}
+	let found = u32::from_le_bytes(header[MAGIC.len()..].try_into().unwrap());
+	if found != version {
+		return Err(format!(
+			"Snapshot data is version {}, metadata says {}",
+			found, version
+		));
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn metadata(checksum: Option<u32>, format_version: u32) -> SnapshotMetadata {
+		SnapshotMetadata {
+			created_at: 0,
+			event_seq: 9,
+			size_bytes: 0,
+			market: "BTC-USDT".to_string(),
+			format_version,
+			checksum,
+		}
+	}
+
+	#[test]
+	fn values_round_trip_with_the_checksum_of_the_data() {
+		let mut data = Vec::new();
+		let mut writer = SnapshotWriter::new(&mut data).unwrap();
+		writer.write("BTC-USDT").unwrap();
+		writer.write(&Some(42u64)).unwrap();
+		let (crc, bytes) = writer.finish().unwrap();
+		assert_eq!((crc, bytes), (checksum(&data), data.len()));
+		let written = metadata(Some(crc), SNAPSHOT_FORMAT_VERSION);
+
+		let mut reader = SnapshotReader::new(data.as_slice(), SNAPSHOT_FORMAT_VERSION).unwrap();
+		assert_eq!(reader.read::<String>().unwrap(), "BTC-USDT");
+		assert_eq!(reader.read::<Option<u64>>().unwrap(), Some(42));
+		assert!(reader.finish(&written).is_ok());
+
+		// Values left unread, or read past the end
+		let mut reader = SnapshotReader::new(data.as_slice(), SNAPSHOT_FORMAT_VERSION).unwrap();
+		assert_eq!(reader.read::<String>().unwrap(), "BTC-USDT");
+		assert!(reader.finish(&written).is_err());
+		let mut reader = SnapshotReader::new(data.as_slice(), SNAPSHOT_FORMAT_VERSION).unwrap();
+		reader.read::<String>().unwrap();
+		reader.read::<Option<u64>>().unwrap();
+		assert!(reader.read::<u64>().is_err());
+
+		assert!(SnapshotReader::new(data.as_slice(), 3).is_err());
+		assert!(SnapshotReader::new(&b"{}"[..], SNAPSHOT_FORMAT_VERSION).is_err());
+	}
+
+	#[test]
+	fn verification_reads_the_data_through() {
+		let mut data = Vec::new();
+		let mut writer = SnapshotWriter::new(&mut data).unwrap();
+		writer.write(&vec![7u64; 1000]).unwrap();
+		let (crc, _) = writer.finish().unwrap();
+
+		assert!(
+			verify(
+				&metadata(Some(crc), SNAPSHOT_FORMAT_VERSION),
+				data.as_slice()
+			)
+			.is_ok()
+		);
+		assert!(verify(&metadata(None, SNAPSHOT_FORMAT_VERSION), data.as_slice()).is_ok());
+		assert!(verify(&metadata(Some(crc), 3), data.as_slice()).is_err());
+
+		let last = data.len() - 1;
+		data[last] ^= 0xff;
+		assert!(
+			verify(
+				&metadata(Some(crc), SNAPSHOT_FORMAT_VERSION),
+				data.as_slice()
+			)
+			.is_err()
+		);
+
+		// JSON data has no header to check
+		let json = br#"{"orderbook":{}}"#;
+		let crc = checksum(json);
+		assert!(verify(&metadata(Some(crc), JSON_SNAPSHOT_VERSION), &json[..]).is_ok());
+		assert!(verify(&metadata(Some(crc), SNAPSHOT_FORMAT_VERSION), &json[..]).is_err());
+	}
+}
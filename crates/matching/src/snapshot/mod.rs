@@ -12,14 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod file;
+pub mod format;
 pub mod snapshotter;
 mod storage;
 
 use thiserror::Error;
 
 use crate::event::SequenceNumber;
+pub use file::FileSnapshotStorage;
 pub use snapshotter::{SnapshotProvider, Snapshotter, SnapshotterConfig};
-pub use storage::{MemorySnapshotStorage, Snapshot, SnapshotMetadata, SnapshotStorage};
+pub use storage::{
+	MemorySnapshotStorage, Snapshot, SnapshotData, SnapshotEncoder, SnapshotMetadata,
+	SnapshotStorage,
+};
 
 /// Error types for snapshot operations
 #[derive(Debug, Error)]
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::{
+	io::Write,
 	sync::{
 		Arc,
 		atomic::{AtomicBool, Ordering},
@@ -23,7 +24,8 @@ use std::{
 
 use tracing::{debug, error, info, warn};
 
-use super::{Snapshot, SnapshotStorage};
+use super::{SnapshotMetadata, SnapshotStorage};
+use crate::event::{EventStorage, StorageError};
 
 /// Configuration for the Snapshotter
 #[derive(Debug, Clone)]
@@ -55,6 +57,9 @@ impl Default for SnapshotterConfig {
 /// - Eventually consistent: snapshots may lag slightly behind current state
 /// - Safe points: only snapshots at consistent state boundaries
 /// - Cleanup: removes old snapshots to bound storage usage
+/// - Compaction: optionally truncates the events that the oldest retained
+///   snapshot covers, after each successful save, once that snapshot is
+///   verified to read back intact
 ///
 /// The snapshotter does NOT:
 /// - Block the matching loop
@@ -69,13 +74,37 @@ pub struct Snapshotter {
 impl Snapshotter {
 	/// Start the snapshotter
 	///
-	/// In the MVP implementation, the snapshotter periodically requests
-	/// snapshots. In production, this would coordinate with the matching
-	/// loop to capture state at safe points.
+	/// The snapshotter periodically requests snapshots from the provider
+	/// on its own thread. The matching loop only hands out a copy-on-write
+	/// view of its state between commands, which is a safe point; encoding
+	/// and saving the snapshot happen here.
 	pub fn start(
+		storage: Box<dyn SnapshotStorage>,
+		config: SnapshotterConfig,
+		snapshot_provider: Arc<dyn SnapshotProvider>,
+	) -> Self {
+		Self::start_inner(storage, config, snapshot_provider, None)
+	}
+
+	/// Start the snapshotter, compacting `event_log` as snapshots are saved
+	///
+	/// Events up to the oldest retained snapshot are no longer needed for
+	/// recovery; they are truncated from `event_log`, which decides whether
+	/// they are archived or deleted.
+	pub fn start_with_event_log(
+		storage: Box<dyn SnapshotStorage>,
+		config: SnapshotterConfig,
+		snapshot_provider: Arc<dyn SnapshotProvider>,
+		event_log: Box<dyn EventStorage>,
+	) -> Self {
+		Self::start_inner(storage, config, snapshot_provider, Some(event_log))
+	}
+
+	fn start_inner(
 		mut storage: Box<dyn SnapshotStorage>,
 		config: SnapshotterConfig,
 		snapshot_provider: Arc<dyn SnapshotProvider>,
+		mut event_log: Option<Box<dyn EventStorage>>,
 	) -> Self {
 		let shutdown = Arc::new(AtomicBool::new(false));
 		let shutdown_clone = shutdown.clone();
@@ -88,6 +117,9 @@ impl Snapshotter {
 					storage.as_mut(),
 					&config,
 					snapshot_provider.as_ref(),
+					event_log
+						.as_mut()
+						.map(|log| log.as_mut() as &mut dyn EventStorage),
 					&shutdown_clone,
 				);
 				info!(target: "snapshotter", "Snapshotter stopped");
@@ -104,6 +136,7 @@ impl Snapshotter {
 		storage: &mut dyn SnapshotStorage,
 		config: &SnapshotterConfig,
 		provider: &dyn SnapshotProvider,
+		mut event_log: Option<&mut dyn EventStorage>,
 		shutdown: &Arc<AtomicBool>,
 	) {
 		let interval = Duration::from_secs(config.snapshot_interval_secs);
@@ -119,37 +152,17 @@ impl Snapshotter {
 				break;
 			}
 
-			// Request a snapshot from the matching engine
+			// Stream a snapshot from the matching engine into storage
 			let start = std::time::Instant::now();
-			match provider.create_snapshot() {
-				Ok(snapshot) => {
-					let snapshot_duration = start.elapsed();
-					let seq = snapshot.metadata.event_seq;
-					let size = snapshot.metadata.size_bytes;
-
-					match storage.save(snapshot.clone()) {
-						Ok(_) => {
-							let total_duration = start.elapsed();
-							info!(
-								target: "snapshotter",
-								seq = seq,
-								size_bytes = size,
-								snapshot_ms = snapshot_duration.as_millis(),
-								save_ms = (total_duration - snapshot_duration).as_millis(),
-								total_ms = total_duration.as_millis(),
-								"Snapshot created and saved"
-							);
-						}
-						Err(e) => {
-							error!(
-								target: "snapshotter",
-								seq = seq,
-								error = %e,
-								"Failed to save snapshot"
-							);
-							continue;
-						}
-					}
+			match storage.save_with(&mut |out| provider.write_snapshot(out)) {
+				Ok(metadata) => {
+					info!(
+						target: "snapshotter",
+						seq = metadata.event_seq,
+						size_bytes = metadata.size_bytes,
+						total_ms = start.elapsed().as_millis(),
+						"Snapshot created and saved"
+					);
 
 					// Cleanup old snapshots
 					let snapshots = storage.list_snapshots();
@@ -180,14 +193,73 @@ impl Snapshotter {
 							}
 						}
 					}
+
+					if let Some(event_log) = event_log.as_deref_mut() {
+						Self::compact_events(storage, event_log);
+					}
 				}
 				Err(e) => {
-					error!(target: "snapshotter", error = %e, "Failed to create snapshot");
+					error!(target: "snapshotter", error = %e, "Failed to save snapshot");
 				}
 			}
 		}
 	}
 
+	/// Truncate the events covered by the oldest usable retained snapshot
+	///
+	/// Restoring a snapshot replays the events after its sequence, so the
+	/// events up to and including it can go. Only a snapshot that the log
+	/// has caught up with, and that reads back intact, is relied on: once
+	/// truncated, the events it covers cannot be replayed from the log.
+	fn compact_events(storage: &dyn SnapshotStorage, event_log: &mut dyn EventStorage) {
+		let last_sequence = event_log.last_sequence();
+		let mut snapshots = storage.list_snapshots();
+		snapshots.sort_by_key(|s| s.event_seq);
+		let verified = snapshots
+			.iter()
+			.filter(|s| s.event_seq <= last_sequence)
+			.find_map(|s| {
+				storage
+					.verify(s.event_seq)
+					.inspect_err(|e| {
+						warn!(
+							target: "snapshotter",
+							snapshot_seq = s.event_seq,
+							error = %e,
+							"Snapshot failed verification, not compacting up to it"
+						)
+					})
+					.ok()
+			});
+		let Some(snapshot_seq) = verified.map(|s| s.event_seq) else {
+			return;
+		};
+
+		match event_log.truncate_before(snapshot_seq + 1) {
+			Ok(0) => {}
+			Ok(truncated) => {
+				info!(
+					target: "snapshotter",
+					truncated = truncated,
+					snapshot_seq = snapshot_seq,
+					"Event log compacted"
+				);
+			}
+			// A follower leaves compaction to the primary
+			Err(StorageError::NotLeader(reason)) => {
+				debug!(target: "snapshotter", reason = %reason, "Event log not compacted");
+			}
+			Err(e) => {
+				error!(
+					target: "snapshotter",
+					snapshot_seq = snapshot_seq,
+					error = %e,
+					"Failed to compact event log"
+				);
+			}
+		}
+	}
+
 	pub fn shutdown(mut self) {
 		info!(target: "snapshotter", "Shutting down snapshotter");
 		self.shutdown.store(true, Ordering::Relaxed);
@@ -214,8 +286,8 @@ impl Drop for Snapshotter {
 /// This abstraction allows the snapshotter to request snapshots
 /// without knowing the details of the matching engine implementation.
 pub trait SnapshotProvider: Send + Sync {
-	/// Create a snapshot of current state
-	fn create_snapshot(&self) -> Result<Snapshot, String>;
+	/// Stream a snapshot of current state to `out`, returning its metadata
+	fn write_snapshot(&self, out: &mut dyn Write) -> Result<SnapshotMetadata, String>;
 }
 
 /// Mock snapshot provider for testing
@@ -242,23 +314,104 @@ impl Default for MockSnapshotProvider {
 
 #[cfg(test)]
 impl SnapshotProvider for MockSnapshotProvider {
-	fn create_snapshot(&self) -> Result<Snapshot, String> {
+	fn write_snapshot(&self, out: &mut dyn Write) -> Result<SnapshotMetadata, String> {
 		let mut seq = self.seq.lock().unwrap();
 		*seq += 1;
 
-		use super::SnapshotMetadata;
+		let state_data = vec![0u8; 100];
+		out.write_all(&state_data).map_err(|e| e.to_string())?;
+		Ok(SnapshotMetadata {
+			created_at: std::time::SystemTime::now()
+				.duration_since(std::time::UNIX_EPOCH)
+				.unwrap()
+				.as_secs(),
+			event_seq: *seq,
+			size_bytes: state_data.len(),
+			market: "BTC-USDT".to_string(),
+			format_version: super::format::JSON_SNAPSHOT_VERSION,
+			checksum: Some(super::format::checksum(&state_data)),
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		event::{EventBatch, MatchingEvent, MemoryEventStorage},
+		snapshot::{
+			MemorySnapshotStorage, Snapshot,
+			format::{JSON_SNAPSHOT_VERSION, checksum},
+		},
+	};
 
-		Ok(Snapshot {
-			metadata: SnapshotMetadata {
-				created_at: std::time::SystemTime::now()
-					.duration_since(std::time::UNIX_EPOCH)
-					.unwrap()
-					.as_secs(),
-				event_seq: *seq,
-				size_bytes: 100,
+	fn event_log(seqs: std::ops::RangeInclusive<u64>) -> MemoryEventStorage {
+		let mut storage = MemoryEventStorage::new();
+		let events = seqs
+			.map(|seq| MatchingEvent::OrderAccepted {
+				seq,
+				order_id: format!("order_{}", seq),
 				market: "BTC-USDT".to_string(),
-			},
-			state_data: vec![0u8; 100],
-		})
+				public_key: "test_key".to_string(),
+				client_order_id: None,
+				side: anvil_sdk::types::Side::Buy,
+				price: 50000,
+				size: 1,
+				timestamp: 1000,
+			})
+			.collect();
+		storage.append_batch(EventBatch::new(events)).unwrap();
+		storage
+	}
+
+	#[test]
+	fn compaction_keeps_the_events_after_the_oldest_snapshot() {
+		let provider = MockSnapshotProvider::new();
+		let mut storage = MemorySnapshotStorage::new();
+		let mut events = event_log(1..=10);
+
+		// Nothing to compact before the first snapshot
+		Snapshotter::compact_events(&storage, &mut events);
+		assert_eq!(events.event_count(), 10);
+
+		for _ in 0..3 {
+			storage
+				.save_with(&mut |out| provider.write_snapshot(out))
+				.unwrap();
+		}
+		storage.cleanup_before(2).unwrap();
+		Snapshotter::compact_events(&storage, &mut events);
+		assert_eq!(events.replay_from(0).unwrap()[0].sequence(), 3);
+	}
+
+	#[test]
+	fn compaction_only_relies_on_verified_snapshots_the_log_covers() {
+		let snapshot = |seq: u64, intact: bool| {
+			let state_data = vec![0u8; 100];
+			let crc = checksum(&state_data);
+			Snapshot {
+				metadata: SnapshotMetadata {
+					created_at: 0,
+					event_seq: seq,
+					size_bytes: state_data.len(),
+					market: "BTC-USDT".to_string(),
+					format_version: JSON_SNAPSHOT_VERSION,
+					checksum: Some(if intact { crc } else { !crc }),
+				},
+				state_data,
+			}
+		};
+		let mut storage = MemorySnapshotStorage::new();
+		let mut events = event_log(1..=10);
+
+		// Neither a corrupted snapshot nor one past the log is used
+		storage.save(snapshot(5, false)).unwrap();
+		storage.save(snapshot(12, true)).unwrap();
+		Snapshotter::compact_events(&storage, &mut events);
+		assert_eq!(events.event_count(), 10);
+
+		storage.save(snapshot(8, true)).unwrap();
+		Snapshotter::compact_events(&storage, &mut events);
+		assert_eq!(events.replay_from(0).unwrap()[0].sequence(), 9);
 	}
 }
@@ -12,11 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::sync::{Arc, Mutex};
+use std::{
+	io::{self, Read, Write},
+	sync::{Arc, Mutex},
+};
 
 use serde::{Deserialize, Serialize};
 
-use super::SnapshotError;
+use super::{
+	SnapshotError,
+	format::{self, JSON_SNAPSHOT_VERSION, checksum},
+};
 use crate::event::SequenceNumber;
 
 /// Metadata about a snapshot
@@ -30,6 +36,16 @@ pub struct SnapshotMetadata {
 	pub size_bytes: usize,
 	/// Market covered by this snapshot
 	pub market: String,
+	/// Schema version of the state data (see [`format`](super::format))
+	#[serde(default = "json_snapshot_version")]
+	pub format_version: u32,
+	/// CRC32 of the state data, absent in snapshots that predate it
+	#[serde(default)]
+	pub checksum: Option<u32>,
+}
+
+fn json_snapshot_version() -> u32 {
+	JSON_SNAPSHOT_VERSION
 }
 
 /// Complete snapshot with metadata and data
@@ -39,6 +55,28 @@ pub struct Snapshot {
 	pub state_data: Vec<u8>,
 }
 
+impl Snapshot {
+	/// Check the state data against the checksum in the metadata
+	pub fn verify(&self) -> Result<(), SnapshotError> {
+		match self.metadata.checksum {
+			Some(expected) if checksum(&self.state_data) != expected => {
+				Err(SnapshotError::Corrupted(format!(
+					"checksum mismatch for snapshot at seq={}",
+					self.metadata.event_seq
+				)))
+			}
+			_ => Ok(()),
+		}
+	}
+}
+
+/// Writes a snapshot's state data to the writer it is given and returns
+/// the snapshot's metadata
+pub type SnapshotEncoder<'a> = dyn FnMut(&mut dyn Write) -> Result<SnapshotMetadata, String> + 'a;
+
+/// State data of a stored snapshot, read as a stream
+pub type SnapshotData = Box<dyn Read + Send>;
+
 /// Snapshot Storage trait - persistence layer for snapshots
 ///
 /// The Snapshot Storage is responsible for saving and loading
@@ -46,26 +84,76 @@ pub struct Snapshot {
 /// snapshot storage typically maintains only recent snapshots and
 /// can delete old ones.
 ///
+/// State data is streamed in and out, so a store backed by files or
+/// object storage never needs the whole snapshot in memory.
+///
 /// This abstraction allows different backing stores:
 /// - In-memory (MVP, testing)
 /// - Local filesystem (JSON or binary)
 /// - Object storage (S3, etc.)
 /// - Database (for queryable snapshots)
 pub trait SnapshotStorage: Send {
-	/// Save a snapshot
-	fn save(&mut self, snapshot: Snapshot) -> Result<(), SnapshotError>;
-
-	/// Load the latest snapshot
-	fn load_latest(&self) -> Result<Snapshot, SnapshotError>;
-
-	/// Load a snapshot at or before a given sequence number
-	fn load_at_seq(&self, seq: SequenceNumber) -> Result<Snapshot, SnapshotError>;
+	/// Save the snapshot that `encode` writes
+	///
+	/// Returns the metadata of the saved snapshot.
+	fn save_with(
+		&mut self,
+		encode: &mut SnapshotEncoder<'_>,
+	) -> Result<SnapshotMetadata, SnapshotError>;
+
+	/// Open the state data of the latest snapshot at or before a given
+	/// sequence number
+	fn open_at_seq(
+		&self,
+		seq: SequenceNumber,
+	) -> Result<(SnapshotMetadata, SnapshotData), SnapshotError>;
 
 	/// List all available snapshots
 	fn list_snapshots(&self) -> Vec<SnapshotMetadata>;
 
 	/// Delete snapshots older than the given sequence number
 	fn cleanup_before(&mut self, seq: SequenceNumber) -> Result<usize, SnapshotError>;
+
+	/// Save a snapshot held in memory
+	fn save(&mut self, snapshot: Snapshot) -> Result<(), SnapshotError> {
+		self.save_with(&mut |out| {
+			out.write_all(&snapshot.state_data)
+				.map_err(|e| format!("Failed to write snapshot: {}", e))?;
+			Ok(snapshot.metadata.clone())
+		})
+		.map(|_| ())
+	}
+
+	/// Load the latest snapshot
+	fn load_latest(&self) -> Result<Snapshot, SnapshotError> {
+		self.load_at_seq(SequenceNumber::MAX)
+	}
+
+	/// Load a snapshot at or before a given sequence number
+	fn load_at_seq(&self, seq: SequenceNumber) -> Result<Snapshot, SnapshotError> {
+		let (metadata, mut data) = self.open_at_seq(seq)?;
+		let mut state_data = Vec::with_capacity(metadata.size_bytes);
+		data.read_to_end(&mut state_data)
+			.map_err(|e| SnapshotError::LoadFailed(e.to_string()))?;
+		Ok(Snapshot {
+			metadata,
+			state_data,
+		})
+	}
+
+	/// Read back the snapshot taken at exactly `seq` and check it against
+	/// its metadata
+	///
+	/// The data is streamed through its checksum; see [`format::verify`].
+	fn verify(&self, seq: SequenceNumber) -> Result<SnapshotMetadata, SnapshotError> {
+		let (metadata, data) = self.open_at_seq(seq)?;
+		if metadata.event_seq != seq {
+			return Err(SnapshotError::NotFound);
+		}
+		format::verify(&metadata, data)
+			.map_err(|e| SnapshotError::Corrupted(format!("snapshot at seq={}: {}", seq, e)))?;
+		Ok(metadata)
+	}
 }
 
 /// In-memory snapshot storage for MVP
@@ -93,31 +181,41 @@ impl Default for MemorySnapshotStorage {
 }
 
 impl SnapshotStorage for MemorySnapshotStorage {
-	fn save(&mut self, snapshot: Snapshot) -> Result<(), SnapshotError> {
+	fn save_with(
+		&mut self,
+		encode: &mut SnapshotEncoder<'_>,
+	) -> Result<SnapshotMetadata, SnapshotError> {
+		let mut state_data = Vec::new();
+		let metadata = encode(&mut state_data).map_err(SnapshotError::CreationFailed)?;
+
 		let mut snapshots = self.snapshots.lock().unwrap();
-		snapshots.push(snapshot);
+		snapshots.push(Snapshot {
+			metadata: metadata.clone(),
+			state_data,
+		});
 
 		// Keep snapshots sorted by sequence number
 		snapshots.sort_by_key(|s| s.metadata.event_seq);
 
-		Ok(())
-	}
-
-	fn load_latest(&self) -> Result<Snapshot, SnapshotError> {
-		let snapshots = self.snapshots.lock().unwrap();
-		snapshots.last().cloned().ok_or(SnapshotError::NotFound)
+		Ok(metadata)
 	}
 
-	fn load_at_seq(&self, seq: SequenceNumber) -> Result<Snapshot, SnapshotError> {
+	fn open_at_seq(
+		&self,
+		seq: SequenceNumber,
+	) -> Result<(SnapshotMetadata, SnapshotData), SnapshotError> {
 		let snapshots = self.snapshots.lock().unwrap();
 
 		// Find the latest snapshot with event_seq <= seq
-		snapshots
+		let snapshot = snapshots
 			.iter()
 			.rev()
 			.find(|s| s.metadata.event_seq <= seq)
-			.cloned()
-			.ok_or(SnapshotError::NotFound)
+			.ok_or(SnapshotError::NotFound)?;
+		Ok((
+			snapshot.metadata.clone(),
+			Box::new(io::Cursor::new(snapshot.state_data.clone())),
+		))
 	}
 
 	fn list_snapshots(&self) -> Vec<SnapshotMetadata> {
@@ -138,6 +236,7 @@ impl SnapshotStorage for MemorySnapshotStorage {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::snapshot::format::SNAPSHOT_FORMAT_VERSION;
 
 	fn create_test_snapshot(seq: SequenceNumber) -> Snapshot {
 		Snapshot {
@@ -146,6 +245,8 @@ mod tests {
 				event_seq: seq,
 				size_bytes: 100,
 				market: "BTC-USDT".to_string(),
+				format_version: SNAPSHOT_FORMAT_VERSION,
+				checksum: None,
 			},
 			state_data: vec![0u8; 100],
 		}
@@ -195,4 +296,29 @@ mod tests {
 		assert_eq!(list.len(), 2);
 		assert_eq!(list[0].event_seq, 200);
 	}
+
+	#[test]
+	fn test_verify_checksum() {
+		let mut snapshot = create_test_snapshot(100);
+		assert!(snapshot.verify().is_ok());
+
+		snapshot.metadata.checksum = Some(checksum(&snapshot.state_data));
+		assert!(snapshot.verify().is_ok());
+
+		snapshot.state_data[0] = 1;
+		assert!(matches!(
+			snapshot.verify(),
+			Err(SnapshotError::Corrupted(_))
+		));
+	}
+
+	#[test]
+	fn test_metadata_without_version_is_json() {
+		let metadata: SnapshotMetadata = serde_json::from_str(
+			r#"{"created_at":1000,"event_seq":100,"size_bytes":100,"market":"BTC-USDT"}"#,
+		)
+		.unwrap();
+		assert_eq!(metadata.format_version, JSON_SNAPSHOT_VERSION);
+		assert_eq!(metadata.checksum, None);
+	}
 }
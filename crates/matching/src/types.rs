@@ -41,6 +41,102 @@ pub struct OrderCommand {
 	/// identity, not business user identity. The matching engine receives
 	/// the principal identifier (public key) from Gateway.
 	pub public_key: String,
+	/// Nonce of the client request that submitted the order
+	///
+	/// Together with `public_key` this is the request's idempotency key. Empty
+	/// if the submitter supplied none.
+	#[serde(default)]
+	pub nonce: String,
+	/// Client-assigned order ID, unique among the principal's open orders
+	#[serde(default)]
+	pub client_order_id: Option<String>,
+}
+
+/// Request to cancel a resting order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CancelCommand {
+	/// Order to cancel
+	pub order_id: String,
+	/// Market identifier
+	pub market: String,
+	/// Principal requesting the cancel; must own the order
+	pub public_key: String,
+	/// Timestamp when the cancel was received
+	pub timestamp: u64,
+	/// Cancel the principal's open order with this client order ID instead
+	/// of `order_id`
+	#[serde(default)]
+	pub client_order_id: Option<String>,
+}
+
+/// Request to atomically cancel a resting order and submit its replacement
+///
+/// The replacement is only submitted if the cancel succeeds; otherwise it is
+/// rejected. It joins the back of the queue at its price level.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplaceCommand {
+	/// Resting order to cancel; must be owned by `order.public_key`
+	pub replaces: String,
+	/// Replacement order
+	pub order: OrderCommand,
+}
+
+/// Direction of a balance adjustment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BalanceAdjustment {
+	Deposit,
+	Withdrawal,
+}
+
+/// Request to credit or debit a principal's available balance
+///
+/// Balance adjustments travel through the ingress queue like orders so
+/// that they are sequenced deterministically relative to matching.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceCommand {
+	/// Principal whose balance changes
+	pub public_key: String,
+	/// Asset identifier (e.g., "USDT")
+	pub asset: String,
+	/// Amount in asset units
+	pub amount: u64,
+	/// Deposit or withdrawal
+	pub adjustment: BalanceAdjustment,
+	/// Timestamp when the adjustment was received
+	pub timestamp: u64,
+}
+
+/// Command consumed by the matching loop
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum EngineCommand {
+	/// Submit a new order
+	Submit(OrderCommand),
+	/// Cancel a resting order
+	Cancel(CancelCommand),
+	/// Cancel a resting order and submit its replacement
+	Replace(ReplaceCommand),
+	/// Adjust a principal's balance in the ledger
+	AdjustBalance(BalanceCommand),
+	/// Commands processed back to back, with no other command in between
+	Batch(Vec<EngineCommand>),
+}
+
+impl EngineCommand {
+	/// Order ID targeted by this command (if any)
+	pub fn order_id(&self) -> Option<&str> {
+		match self {
+			EngineCommand::Submit(cmd) => Some(&cmd.order_id),
+			EngineCommand::Cancel(cmd) => Some(&cmd.order_id),
+			EngineCommand::Replace(cmd) => Some(&cmd.order.order_id),
+			EngineCommand::AdjustBalance(_) | EngineCommand::Batch(_) => None,
+		}
+	}
+}
+
+impl From<OrderCommand> for EngineCommand {
+	fn from(cmd: OrderCommand) -> Self {
+		EngineCommand::Submit(cmd)
+	}
 }
 
 /// Internal order representation for the matching engine
@@ -69,6 +165,9 @@ pub struct Order {
 	/// identity, not business user identity. The matching engine receives
 	/// the principal identifier (public key) from Gateway.
 	pub public_key: String,
+	/// Client-assigned order ID
+	#[serde(default)]
+	pub client_order_id: Option<String>,
 }
 
 impl From<OrderCommand> for Order {
@@ -82,6 +181,7 @@ impl From<OrderCommand> for Order {
 			remaining_size: cmd.size,
 			timestamp: cmd.timestamp,
 			public_key: cmd.public_key,
+			client_order_id: cmd.client_order_id,
 		}
 	}
 }
@@ -4,6 +4,10 @@
 //! - Matching correctness (price-time priority)
 //! - Idempotency (duplicate order handling)
 //! - Event generation
+//! - Balance ledger enforcement and rejected balance adjustments
+//! - Per-principal risk limits
+//! - Batch order entry through the RPC layer
+//! - Client order ID uniqueness and cancel by client order ID
 //! - System integration
 
 use std::{
@@ -12,9 +16,19 @@ use std::{
 	time::Duration,
 };
 
+use anvil_matching::server::{
+	MatchingServiceImpl,
+	proto::{
+		CancelOrderRequest, CancelOrdersRequest, OrderSide, OrderStatus, SubmitDisposition,
+		SubmitOrderRequest, SubmitOrdersRequest, matching_service_server::MatchingService,
+	},
+};
 use anvil_matching::{
-	EventBuffer, EventWriter, EventWriterConfig, IngressQueue, MatchingEngine, MemoryEventStorage,
-	MemoryOrderJournal, OrderCommand, OrderJournal, engine::EngineConfig,
+	BalanceAdjustment, BalanceCommand, CancelCommand, CommittedEventSink, EngineCommand,
+	EventBuffer, EventWriter, EventWriterConfig, IngressQueue, LedgerView, LimitsConfig,
+	MatchingEngine, MatchingEvent, MemoryEventStorage, MemoryOrderJournal, OrderCommand,
+	OrderJournal, OrderView, RejectReason, ReplaceCommand, RiskConfig, RiskLimits,
+	engine::EngineConfig,
 };
 use anvil_sdk::types::Side;
 
@@ -30,6 +44,8 @@ fn create_test_order(order_id: &str, side: Side, price: u64, size: u64) -> Order
 			.unwrap()
 			.as_secs(),
 		public_key: "test_key".to_string(),
+		client_order_id: None,
+		nonce: String::new(),
 	}
 }
 
@@ -57,6 +73,7 @@ fn test_single_match() {
 	let engine_config = EngineConfig {
 		market: "BTC-USDT".to_string(),
 		verbose_logging: false,
+		..Default::default()
 	};
 
 	let _engine = MatchingEngine::start(
@@ -123,3 +140,863 @@ fn test_journal_lifecycle() {
 	assert!(!journal.is_active("order_1"));
 	assert_eq!(journal.active_count(), 0);
 }
+
+#[test]
+fn test_balance_enforcement() {
+	let journal: Box<dyn OrderJournal> = Box::new(MemoryOrderJournal::new());
+	let journal = Arc::new(Mutex::new(journal));
+
+	let ingress_queue = IngressQueue::new(1000);
+	let (queue_sender, queue_receiver) = ingress_queue.split();
+
+	let event_buffer = EventBuffer::new(1000);
+	let (event_producer, event_consumer) = event_buffer.split();
+
+	let risk = RiskConfig::for_market("BTC-USDT", true);
+	let ledger_view = LedgerView::new(risk.ledger().unwrap());
+	let _event_writer = EventWriter::start_with_sinks(
+		event_consumer,
+		Box::new(MemoryEventStorage::new()),
+		journal.clone(),
+		EventWriterConfig::default(),
+		vec![Box::new(ledger_view.clone())],
+	);
+
+	let _engine = MatchingEngine::start(
+		EngineConfig {
+			market: "BTC-USDT".to_string(),
+			verbose_logging: false,
+			risk,
+		},
+		queue_receiver,
+		event_producer,
+		journal.clone(),
+	);
+
+	// Unfunded orders are rejected
+	let buy_order = create_test_order("buy_1", Side::Buy, 50000, 2);
+	assert!(
+		ledger_view
+			.check_order("test_key", Side::Buy, 50000, 2)
+			.is_err()
+	);
+	journal.lock().unwrap().append(buy_order.clone()).unwrap();
+	queue_sender.try_enqueue(buy_order).unwrap();
+
+	queue_sender
+		.try_enqueue_command(EngineCommand::AdjustBalance(BalanceCommand {
+			public_key: "test_key".to_string(),
+			asset: "USDT".to_string(),
+			amount: 150000,
+			adjustment: BalanceAdjustment::Deposit,
+			timestamp: 0,
+		}))
+		.unwrap();
+
+	// Funded orders rest and lock the quote asset
+	let buy_order = create_test_order("buy_2", Side::Buy, 50000, 2);
+	journal.lock().unwrap().append(buy_order.clone()).unwrap();
+	queue_sender.try_enqueue(buy_order).unwrap();
+
+	thread::sleep(Duration::from_millis(200));
+
+	let usdt = ledger_view
+		.balances("test_key")
+		.into_iter()
+		.find(|(asset, _)| asset == "USDT")
+		.map(|(_, balance)| balance)
+		.unwrap();
+	assert_eq!(usdt.available, 50000);
+	assert_eq!(usdt.locked, 100000);
+
+	// Cancelling releases the lock
+	queue_sender
+		.try_enqueue_command(EngineCommand::Cancel(CancelCommand {
+			order_id: "buy_2".to_string(),
+			market: "BTC-USDT".to_string(),
+			public_key: "test_key".to_string(),
+			client_order_id: None,
+			timestamp: 0,
+		}))
+		.unwrap();
+
+	thread::sleep(Duration::from_millis(200));
+
+	let usdt = ledger_view
+		.balances("test_key")
+		.into_iter()
+		.find(|(asset, _)| asset == "USDT")
+		.map(|(_, balance)| balance)
+		.unwrap();
+	assert_eq!(usdt.available, 150000);
+	assert_eq!(usdt.locked, 0);
+}
+
+/// Collects committed events for assertions
+#[derive(Clone, Default)]
+struct CollectingSink(Arc<Mutex<Vec<MatchingEvent>>>);
+
+impl CommittedEventSink for CollectingSink {
+	fn on_committed(&mut self, events: &[MatchingEvent]) {
+		self.0.lock().unwrap().extend_from_slice(events);
+	}
+}
+
+#[test]
+fn test_overdrawing_withdrawal_is_rejected() {
+	let journal: Box<dyn OrderJournal> = Box::new(MemoryOrderJournal::new());
+	let journal = Arc::new(Mutex::new(journal));
+
+	let ingress_queue = IngressQueue::new(1000);
+	let (queue_sender, queue_receiver) = ingress_queue.split();
+
+	let event_buffer = EventBuffer::new(1000);
+	let (event_producer, event_consumer) = event_buffer.split();
+
+	let risk = RiskConfig::for_market("BTC-USDT", true);
+	let ledger_view = LedgerView::new(risk.ledger().unwrap());
+	let sink = CollectingSink::default();
+	let _event_writer = EventWriter::start_with_sinks(
+		event_consumer,
+		Box::new(MemoryEventStorage::new()),
+		journal.clone(),
+		EventWriterConfig {
+			batch_timeout_ms: 10,
+			..Default::default()
+		},
+		vec![Box::new(ledger_view.clone()), Box::new(sink.clone())],
+	);
+
+	let _engine = MatchingEngine::start(
+		EngineConfig {
+			market: "BTC-USDT".to_string(),
+			verbose_logging: false,
+			risk,
+		},
+		queue_receiver,
+		event_producer,
+		journal.clone(),
+	);
+
+	for (amount, adjustment) in [
+		(100, BalanceAdjustment::Deposit),
+		(200, BalanceAdjustment::Withdrawal),
+	] {
+		queue_sender
+			.try_enqueue_command(EngineCommand::AdjustBalance(BalanceCommand {
+				public_key: "test_key".to_string(),
+				asset: "USDT".to_string(),
+				amount,
+				adjustment,
+				timestamp: 0,
+			}))
+			.unwrap();
+	}
+
+	thread::sleep(Duration::from_millis(200));
+
+	let events = sink.0.lock().unwrap().clone();
+	assert_eq!(events.len(), 2);
+	assert!(matches!(
+		&events[1],
+		MatchingEvent::BalanceRejected {
+			amount: 200,
+			adjustment: BalanceAdjustment::Withdrawal,
+			code: RejectReason::InsufficientBalance,
+			..
+		}
+	));
+	assert!(events[1].ends_command());
+
+	let usdt = ledger_view
+		.balances("test_key")
+		.into_iter()
+		.find(|(asset, _)| asset == "USDT")
+		.map(|(_, balance)| balance)
+		.unwrap();
+	assert_eq!(usdt.available, 100);
+	assert_eq!(usdt.locked, 0);
+}
+
+#[test]
+fn test_risk_limit_rejections() {
+	let journal: Box<dyn OrderJournal> = Box::new(MemoryOrderJournal::new());
+	let journal = Arc::new(Mutex::new(journal));
+
+	let ingress_queue = IngressQueue::new(1000);
+	let (queue_sender, queue_receiver) = ingress_queue.split();
+
+	let event_buffer = EventBuffer::new(1000);
+	let (event_producer, event_consumer) = event_buffer.split();
+
+	let sink = CollectingSink::default();
+	let _event_writer = EventWriter::start_with_sinks(
+		event_consumer,
+		Box::new(MemoryEventStorage::new()),
+		journal.clone(),
+		EventWriterConfig {
+			batch_timeout_ms: 10,
+			..Default::default()
+		},
+		vec![Box::new(sink.clone())],
+	);
+
+	let mut limits = LimitsConfig {
+		defaults: RiskLimits {
+			max_open_orders: Some(1),
+			max_order_size: Some(10),
+			..Default::default()
+		},
+		..Default::default()
+	};
+	limits.overrides.insert(
+		"whale".to_string(),
+		RiskLimits {
+			max_order_size: Some(100),
+			..Default::default()
+		},
+	);
+
+	let _engine = MatchingEngine::start(
+		EngineConfig {
+			market: "BTC-USDT".to_string(),
+			verbose_logging: false,
+			risk: RiskConfig::for_market("BTC-USDT", false).with_limits(limits),
+		},
+		queue_receiver,
+		event_producer,
+		journal.clone(),
+	);
+
+	let mut whale_order = create_test_order("whale_1", Side::Buy, 50000, 50);
+	whale_order.public_key = "whale".to_string();
+	for order in [
+		create_test_order("buy_1", Side::Buy, 50000, 1),
+		create_test_order("buy_2", Side::Buy, 50000, 1),
+		create_test_order("buy_3", Side::Buy, 50000, 20),
+		whale_order,
+	] {
+		journal.lock().unwrap().append(order.clone()).unwrap();
+		queue_sender.try_enqueue(order).unwrap();
+	}
+
+	thread::sleep(Duration::from_millis(200));
+
+	let events = sink.0.lock().unwrap();
+	let rejection = |id: &str| {
+		events.iter().find_map(|event| match event {
+			MatchingEvent::OrderRejected { order_id, code, .. } if order_id == id => Some(*code),
+			_ => None,
+		})
+	};
+	assert_eq!(rejection("buy_1"), None);
+	assert_eq!(rejection("buy_2"), Some(RejectReason::MaxOpenOrders));
+	assert_eq!(rejection("buy_3"), Some(RejectReason::MaxOrderSize));
+	assert_eq!(rejection("whale_1"), None);
+}
+
+#[test]
+fn test_replace_order() {
+	let journal: Box<dyn OrderJournal> = Box::new(MemoryOrderJournal::new());
+	let journal = Arc::new(Mutex::new(journal));
+
+	let ingress_queue = IngressQueue::new(1000);
+	let (queue_sender, queue_receiver) = ingress_queue.split();
+
+	let event_buffer = EventBuffer::new(1000);
+	let (event_producer, event_consumer) = event_buffer.split();
+
+	let sink = CollectingSink::default();
+	let _event_writer = EventWriter::start_with_sinks(
+		event_consumer,
+		Box::new(MemoryEventStorage::new()),
+		journal.clone(),
+		EventWriterConfig {
+			batch_timeout_ms: 10,
+			..Default::default()
+		},
+		vec![Box::new(sink.clone())],
+	);
+
+	let _engine = MatchingEngine::start(
+		EngineConfig {
+			market: "BTC-USDT".to_string(),
+			verbose_logging: false,
+			risk: RiskConfig::for_market("BTC-USDT", false),
+		},
+		queue_receiver,
+		event_producer,
+		journal.clone(),
+	);
+
+	let original = create_test_order("buy_1", Side::Buy, 50000, 2);
+	journal.lock().unwrap().append(original.clone()).unwrap();
+	queue_sender.try_enqueue(original).unwrap();
+
+	// Replacing an owned order cancels it and rests the replacement
+	let replacement = create_test_order("buy_2", Side::Buy, 49000, 3);
+	journal.lock().unwrap().append(replacement.clone()).unwrap();
+	queue_sender
+		.try_enqueue_command(EngineCommand::Replace(ReplaceCommand {
+			replaces: "buy_1".to_string(),
+			order: replacement,
+		}))
+		.unwrap();
+
+	// Replacing an order that is no longer open rejects the replacement
+	let stale = create_test_order("buy_3", Side::Buy, 48000, 1);
+	journal.lock().unwrap().append(stale.clone()).unwrap();
+	queue_sender
+		.try_enqueue_command(EngineCommand::Replace(ReplaceCommand {
+			replaces: "buy_1".to_string(),
+			order: stale,
+		}))
+		.unwrap();
+
+	thread::sleep(Duration::from_millis(200));
+
+	let events = sink.0.lock().unwrap();
+	assert!(events.iter().any(|event| matches!(
+		event,
+		MatchingEvent::OrderCancelled { order_id, .. } if order_id == "buy_1"
+	)));
+	assert!(events.iter().any(|event| matches!(
+		event,
+		MatchingEvent::OrderAccepted { order_id, price: 49000, size: 3, .. } if order_id == "buy_2"
+	)));
+	assert!(events.iter().any(|event| matches!(
+		event,
+		MatchingEvent::OrderRejected { order_id, .. } if order_id == "buy_3"
+	)));
+	assert!(!events.iter().any(|event| matches!(
+		event,
+		MatchingEvent::OrderAccepted { order_id, .. } if order_id == "buy_3"
+	)));
+}
+
+#[tokio::test]
+async fn test_batch_orders() {
+	let journal: Box<dyn OrderJournal> = Box::new(MemoryOrderJournal::new());
+	let journal = Arc::new(Mutex::new(journal));
+
+	let ingress_queue = IngressQueue::new(1000);
+	let (queue_sender, queue_receiver) = ingress_queue.split();
+
+	let event_buffer = EventBuffer::new(1000);
+	let (event_producer, event_consumer) = event_buffer.split();
+
+	let sink = CollectingSink::default();
+	let _event_writer = EventWriter::start_with_sinks(
+		event_consumer,
+		Box::new(MemoryEventStorage::new()),
+		journal.clone(),
+		EventWriterConfig {
+			batch_timeout_ms: 10,
+			..Default::default()
+		},
+		vec![Box::new(sink.clone())],
+	);
+
+	let _engine = MatchingEngine::start(
+		EngineConfig {
+			market: "BTC-USDT".to_string(),
+			verbose_logging: false,
+			..Default::default()
+		},
+		queue_receiver,
+		event_producer,
+		journal.clone(),
+	);
+
+	let service = MatchingServiceImpl::new(queue_sender, journal, "BTC-USDT".to_string());
+	let order = |order_id: &str, side: OrderSide, size: u64| SubmitOrderRequest {
+		order_id: order_id.to_string(),
+		market: "BTC-USDT".to_string(),
+		side: side as i32,
+		price: 50000,
+		size,
+		public_key: "test_key".to_string(),
+		..Default::default()
+	};
+
+	// Invalid orders are rejected individually; the rest are sequenced
+	let results = service
+		.submit_orders(tonic::Request::new(SubmitOrdersRequest {
+			orders: vec![
+				order("sell_1", OrderSide::Sell, 2),
+				order("sell_1", OrderSide::Sell, 1),
+				order("sell_2", OrderSide::Sell, 0),
+				order("buy_1", OrderSide::Buy, 1),
+			],
+		}))
+		.await
+		.unwrap()
+		.into_inner()
+		.results;
+	let dispositions: Vec<_> = results.iter().map(|r| r.disposition()).collect();
+	assert_eq!(
+		dispositions,
+		vec![
+			SubmitDisposition::AcceptedOk,
+			SubmitDisposition::InvalidOrder,
+			SubmitDisposition::InvalidOrder,
+			SubmitDisposition::AcceptedOk,
+		]
+	);
+
+	let cancel = |order_id: &str, market: &str| CancelOrderRequest {
+		order_id: order_id.to_string(),
+		market: market.to_string(),
+		public_key: "test_key".to_string(),
+		..Default::default()
+	};
+	let results = service
+		.cancel_orders(tonic::Request::new(CancelOrdersRequest {
+			cancels: vec![cancel("sell_1", "BTC-USDT"), cancel("sell_9", "ETH-USDT")],
+		}))
+		.await
+		.unwrap()
+		.into_inner()
+		.results;
+	assert!(results[0].success);
+	assert_eq!(results[1].disposition(), SubmitDisposition::InvalidOrder);
+
+	// A client order ID is unique per principal within a batch too
+	let with_client_id = |order_id: &str, public_key: &str| SubmitOrderRequest {
+		price: 60000,
+		public_key: public_key.to_string(),
+		client_order_id: "c1".to_string(),
+		..order(order_id, OrderSide::Sell, 1)
+	};
+	let results = service
+		.submit_orders(tonic::Request::new(SubmitOrdersRequest {
+			orders: vec![
+				with_client_id("sell_3", "test_key"),
+				with_client_id("sell_4", "test_key"),
+				with_client_id("sell_5", "other_key"),
+			],
+		}))
+		.await
+		.unwrap()
+		.into_inner()
+		.results;
+	let dispositions: Vec<_> = results.iter().map(|r| r.disposition()).collect();
+	assert_eq!(
+		dispositions,
+		vec![
+			SubmitDisposition::AcceptedOk,
+			SubmitDisposition::InvalidOrder,
+			SubmitDisposition::AcceptedOk,
+		]
+	);
+	assert_eq!(results[1].reason, "Duplicate client order ID");
+
+	// Empty batches are refused outright
+	assert!(
+		service
+			.submit_orders(tonic::Request::new(SubmitOrdersRequest::default()))
+			.await
+			.is_err()
+	);
+
+	tokio::time::sleep(Duration::from_millis(200)).await;
+
+	let events = sink.0.lock().unwrap();
+	assert_eq!(
+		events
+			.iter()
+			.filter(|event| matches!(event, MatchingEvent::TradeExecuted { .. }))
+			.count(),
+		1
+	);
+	assert!(events.iter().any(|event| matches!(
+		event,
+		MatchingEvent::OrderCancelled { order_id, .. } if order_id == "sell_1"
+	)));
+	assert!(
+		!events
+			.iter()
+			.any(|event| matches!(event, MatchingEvent::OrderRejected { .. }))
+	);
+}
+
+#[tokio::test]
+async fn test_duplicate_request_returns_original_order() {
+	let journal: Box<dyn OrderJournal> = Box::new(MemoryOrderJournal::new());
+	let journal = Arc::new(Mutex::new(journal));
+
+	let ingress_queue = IngressQueue::new(1000);
+	let (queue_sender, queue_receiver) = ingress_queue.split();
+
+	let event_buffer = EventBuffer::new(1000);
+	let (event_producer, event_consumer) = event_buffer.split();
+
+	let sink = CollectingSink::default();
+	let _event_writer = EventWriter::start_with_sinks(
+		event_consumer,
+		Box::new(MemoryEventStorage::new()),
+		journal.clone(),
+		EventWriterConfig {
+			batch_timeout_ms: 10,
+			..Default::default()
+		},
+		vec![Box::new(sink.clone())],
+	);
+
+	let _engine = MatchingEngine::start(
+		EngineConfig {
+			market: "BTC-USDT".to_string(),
+			verbose_logging: false,
+			..Default::default()
+		},
+		queue_receiver,
+		event_producer,
+		journal.clone(),
+	);
+
+	let service = MatchingServiceImpl::new(queue_sender, journal, "BTC-USDT".to_string());
+	let order = |order_id: &str, nonce: &str| SubmitOrderRequest {
+		order_id: order_id.to_string(),
+		market: "BTC-USDT".to_string(),
+		side: OrderSide::Buy as i32,
+		price: 50000,
+		size: 1,
+		public_key: "test_key".to_string(),
+		nonce: nonce.to_string(),
+		..Default::default()
+	};
+
+	let first = service
+		.submit_order(tonic::Request::new(order("order_1", "n1")))
+		.await
+		.unwrap()
+		.into_inner();
+	assert_eq!(first.disposition(), SubmitDisposition::AcceptedOk);
+
+	// A retry under a freshly minted order ID resolves to the original
+	let retry = service
+		.submit_order(tonic::Request::new(order("order_2", "n1")))
+		.await
+		.unwrap()
+		.into_inner();
+	assert_eq!(retry.disposition(), SubmitDisposition::AcceptedOk);
+	assert_eq!(retry.order_id, "order_1");
+
+	// A retry under the original order ID is answered the same way while
+	// the original rests, rather than rejected as a duplicate order ID
+	tokio::time::sleep(Duration::from_millis(100)).await;
+	let retry = service
+		.submit_order(tonic::Request::new(order("order_1", "n1")))
+		.await
+		.unwrap()
+		.into_inner();
+	assert_eq!(retry.disposition(), SubmitDisposition::AcceptedOk);
+	assert_eq!(retry.order_id, "order_1");
+
+	// Batches resolve repeated keys per order and reject repeats within
+	let results = service
+		.submit_orders(tonic::Request::new(SubmitOrdersRequest {
+			orders: vec![
+				order("order_3", "n1"),
+				order("order_4", "n2"),
+				order("order_5", "n2"),
+			],
+		}))
+		.await
+		.unwrap()
+		.into_inner()
+		.results;
+	assert_eq!(results[0].order_id, "order_1");
+	assert_eq!(results[0].disposition(), SubmitDisposition::AcceptedOk);
+	assert_eq!(results[1].order_id, "order_4");
+	assert_eq!(results[1].disposition(), SubmitDisposition::AcceptedOk);
+	assert_eq!(results[2].disposition(), SubmitDisposition::InvalidOrder);
+
+	tokio::time::sleep(Duration::from_millis(200)).await;
+
+	let events = sink.0.lock().unwrap();
+	let accepted: Vec<_> = events
+		.iter()
+		.filter_map(|event| match event {
+			MatchingEvent::OrderAccepted { order_id, .. } => Some(order_id.as_str()),
+			_ => None,
+		})
+		.collect();
+	assert_eq!(accepted, vec!["order_1", "order_4"]);
+}
+
+#[tokio::test]
+async fn test_duplicate_request_of_rejected_order() {
+	let journal: Box<dyn OrderJournal> = Box::new(MemoryOrderJournal::new());
+	let journal = Arc::new(Mutex::new(journal));
+
+	let ingress_queue = IngressQueue::new(1000);
+	let (queue_sender, queue_receiver) = ingress_queue.split();
+
+	let event_buffer = EventBuffer::new(1000);
+	let (event_producer, event_consumer) = event_buffer.split();
+
+	let order_view = OrderView::new(100);
+	let _event_writer = EventWriter::start_with_sinks(
+		event_consumer,
+		Box::new(MemoryEventStorage::new()),
+		journal.clone(),
+		EventWriterConfig {
+			batch_timeout_ms: 10,
+			..Default::default()
+		},
+		vec![Box::new(order_view.clone())],
+	);
+
+	// The matching loop rejects the order; admission lets it through
+	let limits = LimitsConfig {
+		defaults: RiskLimits {
+			max_order_size: Some(1),
+			..Default::default()
+		},
+		..Default::default()
+	};
+	let _engine = MatchingEngine::start(
+		EngineConfig {
+			market: "BTC-USDT".to_string(),
+			verbose_logging: false,
+			risk: RiskConfig::for_market("BTC-USDT", false).with_limits(limits),
+		},
+		queue_receiver,
+		event_producer,
+		journal.clone(),
+	);
+
+	let service = MatchingServiceImpl::new(queue_sender, journal, "BTC-USDT".to_string())
+		.with_order_view(order_view);
+	let order = |order_id: &str| SubmitOrderRequest {
+		order_id: order_id.to_string(),
+		market: "BTC-USDT".to_string(),
+		side: OrderSide::Buy as i32,
+		price: 50000,
+		size: 5,
+		public_key: "test_key".to_string(),
+		nonce: "n1".to_string(),
+		..Default::default()
+	};
+
+	let first = service
+		.submit_order(tonic::Request::new(order("order_1")))
+		.await
+		.unwrap()
+		.into_inner();
+	assert_eq!(first.disposition(), SubmitDisposition::AcceptedOk);
+
+	tokio::time::sleep(Duration::from_millis(100)).await;
+
+	// Once the rejection is committed, retries report it
+	let retry = service
+		.submit_order(tonic::Request::new(order("order_2")))
+		.await
+		.unwrap()
+		.into_inner();
+	assert_eq!(retry.order_id, "order_1");
+	assert_eq!(retry.disposition(), SubmitDisposition::RejectedOrder);
+	assert_eq!(retry.status(), OrderStatus::Rejected);
+	assert!(retry.reason.contains("exceeds limit"));
+
+	let results = service
+		.submit_orders(tonic::Request::new(SubmitOrdersRequest {
+			orders: vec![order("order_3")],
+		}))
+		.await
+		.unwrap()
+		.into_inner()
+		.results;
+	assert_eq!(results[0].order_id, "order_1");
+	assert_eq!(results[0].disposition(), SubmitDisposition::RejectedOrder);
+}
+
+#[tokio::test]
+async fn test_duplicate_request_of_funded_order() {
+	let journal: Box<dyn OrderJournal> = Box::new(MemoryOrderJournal::new());
+	let journal = Arc::new(Mutex::new(journal));
+
+	let ingress_queue = IngressQueue::new(1000);
+	let (queue_sender, queue_receiver) = ingress_queue.split();
+
+	let event_buffer = EventBuffer::new(1000);
+	let (event_producer, event_consumer) = event_buffer.split();
+
+	let risk = RiskConfig::for_market("BTC-USDT", true);
+	let ledger_view = LedgerView::new(risk.ledger().unwrap());
+	let sink = CollectingSink::default();
+	let _event_writer = EventWriter::start_with_sinks(
+		event_consumer,
+		Box::new(MemoryEventStorage::new()),
+		journal.clone(),
+		EventWriterConfig {
+			batch_timeout_ms: 10,
+			..Default::default()
+		},
+		vec![Box::new(ledger_view.clone()), Box::new(sink.clone())],
+	);
+
+	let _engine = MatchingEngine::start(
+		EngineConfig {
+			market: "BTC-USDT".to_string(),
+			verbose_logging: false,
+			risk,
+		},
+		queue_receiver,
+		event_producer,
+		journal.clone(),
+	);
+
+	// Fund exactly one order
+	queue_sender
+		.try_enqueue_command(EngineCommand::AdjustBalance(BalanceCommand {
+			public_key: "test_key".to_string(),
+			asset: "USDT".to_string(),
+			amount: 50000,
+			adjustment: BalanceAdjustment::Deposit,
+			timestamp: 0,
+		}))
+		.unwrap();
+	tokio::time::sleep(Duration::from_millis(100)).await;
+
+	let service = MatchingServiceImpl::new(queue_sender, journal, "BTC-USDT".to_string())
+		.with_ledger(ledger_view.clone());
+	let order = SubmitOrderRequest {
+		order_id: "order_1".to_string(),
+		market: "BTC-USDT".to_string(),
+		side: OrderSide::Buy as i32,
+		price: 50000,
+		size: 1,
+		public_key: "test_key".to_string(),
+		nonce: "n1".to_string(),
+		..Default::default()
+	};
+
+	let first = service
+		.submit_order(tonic::Request::new(order.clone()))
+		.await
+		.unwrap()
+		.into_inner();
+	assert_eq!(first.disposition(), SubmitDisposition::AcceptedOk);
+	tokio::time::sleep(Duration::from_millis(100)).await;
+	assert!(
+		ledger_view
+			.check_order("test_key", Side::Buy, 50000, 1)
+			.is_err()
+	);
+
+	// The original locked the whole balance; its retries still resolve to it
+	let retry = service
+		.submit_order(tonic::Request::new(order.clone()))
+		.await
+		.unwrap()
+		.into_inner();
+	assert_eq!(retry.disposition(), SubmitDisposition::AcceptedOk);
+	assert_eq!(retry.order_id, "order_1");
+
+	let results = service
+		.submit_orders(tonic::Request::new(SubmitOrdersRequest {
+			orders: vec![order],
+		}))
+		.await
+		.unwrap()
+		.into_inner()
+		.results;
+	assert_eq!(results[0].disposition(), SubmitDisposition::AcceptedOk);
+	assert_eq!(results[0].order_id, "order_1");
+
+	tokio::time::sleep(Duration::from_millis(100)).await;
+
+	let events = sink.0.lock().unwrap();
+	assert_eq!(
+		events
+			.iter()
+			.filter(|event| matches!(event, MatchingEvent::OrderAccepted { .. }))
+			.count(),
+		1
+	);
+}
+
+#[test]
+fn test_client_order_ids() {
+	let journal: Box<dyn OrderJournal> = Box::new(MemoryOrderJournal::new());
+	let journal = Arc::new(Mutex::new(journal));
+
+	let ingress_queue = IngressQueue::new(1000);
+	let (queue_sender, queue_receiver) = ingress_queue.split();
+
+	let event_buffer = EventBuffer::new(1000);
+	let (event_producer, event_consumer) = event_buffer.split();
+
+	let sink = CollectingSink::default();
+	let _event_writer = EventWriter::start_with_sinks(
+		event_consumer,
+		Box::new(MemoryEventStorage::new()),
+		journal.clone(),
+		EventWriterConfig {
+			batch_timeout_ms: 10,
+			..Default::default()
+		},
+		vec![Box::new(sink.clone())],
+	);
+
+	let _engine = MatchingEngine::start(
+		EngineConfig {
+			market: "BTC-USDT".to_string(),
+			verbose_logging: false,
+			..Default::default()
+		},
+		queue_receiver,
+		event_producer,
+		journal.clone(),
+	);
+
+	let submit = |order_id: &str| {
+		let order = OrderCommand {
+			client_order_id: Some("c1".to_string()),
+			..create_test_order(order_id, Side::Buy, 50000, 1)
+		};
+		journal.lock().unwrap().append(order.clone()).unwrap();
+		queue_sender.try_enqueue(order).unwrap();
+	};
+
+	// The client order ID is taken while buy_1 rests
+	submit("buy_1");
+	submit("buy_2");
+
+	// Cancelling by client order ID frees it for reuse
+	queue_sender
+		.try_enqueue_command(EngineCommand::Cancel(CancelCommand {
+			order_id: String::new(),
+			market: "BTC-USDT".to_string(),
+			public_key: "test_key".to_string(),
+			timestamp: 0,
+			client_order_id: Some("c1".to_string()),
+		}))
+		.unwrap();
+	submit("buy_3");
+
+	thread::sleep(Duration::from_millis(200));
+
+	let events = sink.0.lock().unwrap();
+	assert!(events.iter().any(|event| matches!(
+		event,
+		MatchingEvent::OrderRejected {
+			order_id,
+			code: RejectReason::DuplicateClientOrderId,
+			client_order_id: Some(client_order_id),
+			..
+		} if order_id == "buy_2" && client_order_id == "c1"
+	)));
+	assert!(events.iter().any(|event| matches!(
+		event,
+		MatchingEvent::OrderCancelled { order_id, client_order_id: Some(_), .. }
+			if order_id == "buy_1"
+	)));
+	assert!(events.iter().any(|event| matches!(
+		event,
+		MatchingEvent::OrderAccepted { order_id, client_order_id: Some(_), .. }
+			if order_id == "buy_3"
+	)));
+}
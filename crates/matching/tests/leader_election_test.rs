@@ -0,0 +1,275 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Leader election: two instances share an event log, and the one holding
+//! the lease is the primary
+
+use std::{
+	path::Path,
+	sync::{
+		Arc, Mutex,
+		atomic::{AtomicBool, Ordering},
+	},
+	time::Duration,
+};
+
+use anvil_matching::{
+	CommittedEventSink, EventBuffer, EventHub, EventHubConfig, EventStorage, EventWriter,
+	EventWriterConfig, FencedEventStorage, FileEventStorage, FileLease, IngressQueue, LeaderConfig,
+	LeaderElector, LeaseBackend, MatchingEngine, MatchingEvent, MemoryOrderJournal, OrderJournal,
+	ReplicationRole, ReplicationState, SharedEventStorage,
+	engine::EngineConfig,
+	leader::{Lease, LeaseError},
+	server::{
+		MatchingServiceImpl,
+		proto::{
+			OrderSide, PromoteRequest, SubmitDisposition, SubmitOrderRequest,
+			matching_service_client::MatchingServiceClient,
+		},
+	},
+};
+use tonic::{
+	Code,
+	transport::{Channel, Server, server::TcpIncoming},
+};
+
+const MARKET: &str = "BTC-USDT";
+const LEASE_TTL: Duration = Duration::from_millis(300);
+
+/// File lease whose store can be made unreachable
+struct FlakyLease {
+	inner: FileLease,
+	down: Arc<AtomicBool>,
+}
+
+impl LeaseBackend for FlakyLease {
+	fn acquire(&mut self, holder: &str, ttl: Duration) -> Result<Lease, LeaseError> {
+		if self.down.load(Ordering::Relaxed) {
+			return Err(LeaseError::Busy);
+		}
+		self.inner.acquire(holder, ttl)
+	}
+
+	fn release(&mut self, holder: &str) -> Result<(), LeaseError> {
+		self.inner.release(holder)
+	}
+}
+
+/// One engine instance on the shared log, in process
+struct Instance {
+	endpoint: String,
+	replication: ReplicationState,
+	storage: SharedEventStorage,
+	lease_down: Arc<AtomicBool>,
+	_event_writer: EventWriter,
+}
+
+impl Instance {
+	fn start(name: &str, dir: &Path) -> Self {
+		let journal: Box<dyn OrderJournal> = Box::new(MemoryOrderJournal::new());
+		let journal = Arc::new(Mutex::new(journal));
+		let (queue_sender, queue_receiver) = IngressQueue::new(1000).split();
+		let (event_producer, event_consumer) = EventBuffer::new(1000).split();
+		let replication = ReplicationState::follower(0);
+
+		let storage = SharedEventStorage::new(Box::new(
+			FileEventStorage::open(dir.join("events")).unwrap(),
+		));
+		let hub = EventHub::new(storage.clone(), EventHubConfig::default());
+		let sinks: Vec<Box<dyn CommittedEventSink>> = vec![Box::new(hub.clone())];
+		let event_writer = EventWriter::start_with_sinks(
+			event_consumer,
+			Box::new(FencedEventStorage::new(
+				Box::new(storage.clone()),
+				replication.clone(),
+			)),
+			journal.clone(),
+			EventWriterConfig {
+				batch_size: 10,
+				batch_timeout_ms: 5,
+				verbose_logging: false,
+			},
+			sinks,
+		);
+
+		let engine = Arc::new(MatchingEngine::start(
+			EngineConfig {
+				market: MARKET.to_string(),
+				..Default::default()
+			},
+			queue_receiver,
+			event_producer,
+			journal.clone(),
+		));
+		let lease_down = Arc::new(AtomicBool::new(false));
+		let mut config = LeaderConfig::new(name, LEASE_TTL);
+		config.renew_interval = Duration::from_millis(50);
+		LeaderElector::new(
+			Box::new(FlakyLease {
+				inner: FileLease::new(dir.join("matching.lease")),
+				down: lease_down.clone(),
+			}),
+			engine,
+			storage.clone(),
+			replication.clone(),
+			config,
+		)
+		.start();
+
+		let service = MatchingServiceImpl::new(queue_sender, journal, MARKET.to_string())
+			.with_event_hub(hub)
+			.with_replication(replication.clone())
+			.with_leader_election();
+		let incoming = TcpIncoming::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+		let endpoint = format!("http://{}", incoming.local_addr().unwrap());
+		tokio::spawn(
+			Server::builder()
+				.add_service(service.into_server())
+				.serve_with_incoming(incoming),
+		);
+
+		Self {
+			endpoint,
+			replication,
+			storage,
+			lease_down,
+			_event_writer: event_writer,
+		}
+	}
+
+	async fn client(&self) -> MatchingServiceClient<Channel> {
+		MatchingServiceClient::connect(self.endpoint.clone())
+			.await
+			.unwrap()
+	}
+
+	async fn submit(
+		&self,
+		order_id: &str,
+		side: OrderSide,
+		price: u64,
+		size: u64,
+	) -> Result<SubmitDisposition, Code> {
+		self.client()
+			.await
+			.submit_order(SubmitOrderRequest {
+				order_id: order_id.to_string(),
+				market: MARKET.to_string(),
+				side: side as i32,
+				price,
+				size,
+				remaining_size: size,
+				timestamp: 1,
+				public_key: format!("key_{}", order_id),
+				nonce: order_id.to_string(),
+				..Default::default()
+			})
+			.await
+			.map(|response| response.into_inner().disposition())
+			.map_err(|status| status.code())
+	}
+
+	fn role(&self) -> ReplicationRole {
+		self.replication.status().role
+	}
+}
+
+/// Poll `check` until it holds, for at most five seconds
+async fn eventually(mut check: impl FnMut() -> bool) {
+	for _ in 0..500 {
+		if check() {
+			return;
+		}
+		tokio::time::sleep(Duration::from_millis(10)).await;
+	}
+	panic!("Condition not reached in time");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn the_lease_holder_leads_and_a_successor_continues_its_history() {
+	let dir = std::env::temp_dir().join(format!("anvil-leader-{}", uuid::Uuid::new_v4()));
+	let a = Instance::start("a", &dir);
+	eventually(|| a.role() == ReplicationRole::Primary).await;
+	assert_eq!(a.replication.status().epoch, 1);
+	let b = Instance::start("b", &dir);
+
+	for (order_id, side, price, size) in [
+		("bid_1", OrderSide::Buy, 100, 5),
+		("ask_1", OrderSide::Sell, 101, 4),
+		("ask_2", OrderSide::Sell, 100, 2),
+	] {
+		assert_eq!(
+			a.submit(order_id, side, price, size).await,
+			Ok(SubmitDisposition::AcceptedOk)
+		);
+	}
+	// The last order's fill ends what A commits
+	eventually(|| {
+		a.storage.replay_from(1).unwrap().iter().any(
+			|e| matches!(e, MatchingEvent::OrderFilled { order_id, .. } if order_id == "ask_2"),
+		)
+	})
+	.await;
+	let committed = a.storage.last_sequence();
+
+	// Only the lease makes a primary
+	assert_eq!(b.role(), ReplicationRole::Follower);
+	assert_eq!(
+		b.submit("early", OrderSide::Buy, 99, 1).await,
+		Err(Code::FailedPrecondition)
+	);
+	assert_eq!(
+		b.client()
+			.await
+			.promote(PromoteRequest { epoch: 5 })
+			.await
+			.unwrap_err()
+			.code(),
+		Code::FailedPrecondition
+	);
+
+	// A cannot renew: it steps down, and B takes over once the lease expires
+	a.lease_down.store(true, Ordering::Relaxed);
+	eventually(|| a.role() == ReplicationRole::Fenced).await;
+	eventually(|| b.role() == ReplicationRole::Primary).await;
+	assert_eq!(b.replication.status().epoch, 2);
+	assert_eq!(b.replication.applied_sequence(), committed);
+	assert_eq!(
+		a.submit("stale", OrderSide::Buy, 99, 1).await,
+		Err(Code::FailedPrecondition)
+	);
+
+	// B matches against the book A left
+	assert_eq!(
+		b.submit("ask_3", OrderSide::Sell, 100, 3).await,
+		Ok(SubmitDisposition::AcceptedOk)
+	);
+	eventually(|| b.storage.last_sequence() > committed).await;
+
+	let recovered = FileEventStorage::open(dir.join("events")).unwrap();
+	let events = recovered.replay_from(1).unwrap();
+	let sequences: Vec<_> = events.iter().map(|e| e.sequence()).collect();
+	assert_eq!(
+		sequences,
+		(sequences[0]..=b.storage.last_sequence()).collect::<Vec<_>>()
+	);
+	assert!(events.iter().any(|e| matches!(
+		e,
+		MatchingEvent::MakerOrderFilled { order_id, .. } if order_id == "bid_1"
+	)));
+	assert_eq!(recovered.epoch(), 2);
+
+	// B's elector still renews its lease in the directory
+	let _ = std::fs::remove_dir_all(dir);
+}
@@ -146,6 +146,7 @@ fn test_structured_logging_in_components() {
 	let engine_config = EngineConfig {
 		market: "BTC-USDT".to_string(),
 		verbose_logging: true, // Enable verbose logging for test
+		..Default::default()
 	};
 	let matching_engine = MatchingEngine::start(
 		engine_config,
@@ -163,6 +164,8 @@ fn test_structured_logging_in_components() {
 		size: 1,
 		timestamp: 1000,
 		public_key: "test_pubkey".to_string(),
+		client_order_id: None,
+		nonce: String::new(),
 	};
 
 	// Append to journal
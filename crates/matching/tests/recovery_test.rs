@@ -3,9 +3,12 @@ use std::sync::{Arc, Mutex};
 use anvil_sdk::types::Side;
 
 use anvil_matching::{
-	EventBuffer, EventStorage, EventWriter, EventWriterConfig, IngressQueue, MatchingEngine,
-	MemoryEventStorage, MemoryOrderJournal, OrderJournal, engine::EngineConfig,
-	event::MatchingEvent, types::OrderCommand,
+	ArchivedEventStorage, BalanceAdjustment, BalanceCommand, EngineCommand, EventArchive,
+	EventBuffer, EventStorage, EventWriter, EventWriterConfig, FileEventStorage,
+	FileSnapshotStorage, IngressQueue, LedgerView, MarketDataPublisher, MatchingEngine,
+	MatchingEngineState, MemoryEventStorage, MemoryOrderJournal, OrderJournal, OrderView,
+	RiskConfig, SharedEventStorage, engine::EngineConfig, event::MatchingEvent,
+	snapshot::SnapshotStorage, types::OrderCommand,
 };
 
 #[test]
@@ -36,6 +39,7 @@ fn test_crash_recovery_with_event_replay() {
 	let engine_config = EngineConfig {
 		market: "BTC-USDT".to_string(),
 		verbose_logging: true,
+		..Default::default()
 	};
 
 	let matching_engine = MatchingEngine::start(
@@ -54,6 +58,8 @@ fn test_crash_recovery_with_event_replay() {
 		size: 10,
 		timestamp: 1000,
 		public_key: "buyer".to_string(),
+		client_order_id: None,
+		nonce: String::new(),
 	};
 
 	let sell_order = OrderCommand {
@@ -64,6 +70,8 @@ fn test_crash_recovery_with_event_replay() {
 		size: 5,
 		timestamp: 1001,
 		public_key: "seller".to_string(),
+		client_order_id: None,
+		nonce: String::new(),
 	};
 
 	queue_sender.try_enqueue(buy_order.clone()).unwrap();
@@ -156,6 +164,7 @@ fn test_event_replay_reconstructs_orderbook() {
 	let engine_config = EngineConfig {
 		market: "BTC-USDT".to_string(),
 		verbose_logging: true,
+		..Default::default()
 	};
 
 	let matching_engine = MatchingEngine::start(
@@ -171,6 +180,8 @@ fn test_event_replay_reconstructs_orderbook() {
 			seq: 1,
 			order_id: "order_1".to_string(),
 			market: "BTC-USDT".to_string(),
+			public_key: "test_key".to_string(),
+			client_order_id: None,
 			side: Side::Buy,
 			price: 50000,
 			size: 10,
@@ -180,6 +191,8 @@ fn test_event_replay_reconstructs_orderbook() {
 			seq: 2,
 			order_id: "order_2".to_string(),
 			market: "BTC-USDT".to_string(),
+			public_key: "test_key".to_string(),
+			client_order_id: None,
 			side: Side::Sell,
 			price: 51000,
 			size: 5,
@@ -239,6 +252,7 @@ fn test_maker_order_events_emitted() {
 	let engine_config = EngineConfig {
 		market: "BTC-USDT".to_string(),
 		verbose_logging: true,
+		..Default::default()
 	};
 
 	let _matching_engine = MatchingEngine::start(
@@ -257,6 +271,8 @@ fn test_maker_order_events_emitted() {
 		size: 10,
 		timestamp: 1000,
 		public_key: "maker".to_string(),
+		client_order_id: None,
+		nonce: String::new(),
 	};
 
 	queue_sender.try_enqueue(maker_order).unwrap();
@@ -271,6 +287,8 @@ fn test_maker_order_events_emitted() {
 		size: 5,
 		timestamp: 1001,
 		public_key: "taker".to_string(),
+		client_order_id: None,
+		nonce: String::new(),
 	};
 
 	queue_sender.try_enqueue(taker_order).unwrap();
@@ -290,3 +308,413 @@ fn test_maker_order_events_emitted() {
 	drop(_matching_engine);
 	drop(_event_writer);
 }
+
+#[test]
+fn test_recovery_from_snapshot_after_event_log_compaction() {
+	let dir = std::env::temp_dir().join(format!("anvil-compaction-{}", uuid::Uuid::new_v4()));
+	let open_log = || {
+		SharedEventStorage::new(Box::new(ArchivedEventStorage::new(
+			Box::new(FileEventStorage::open(dir.join("events")).unwrap()),
+			EventArchive::open(dir.join("archive")).unwrap(),
+		)))
+	};
+	let start_engine = |storage: &SharedEventStorage| {
+		let journal: Box<dyn OrderJournal> = Box::new(MemoryOrderJournal::new());
+		let journal = Arc::new(Mutex::new(journal));
+		let (queue_sender, queue_receiver) = IngressQueue::new(100).split();
+		let (event_producer, event_consumer) = EventBuffer::new(100).split();
+		let event_writer = EventWriter::start(
+			event_consumer,
+			Box::new(storage.clone()),
+			journal.clone(),
+			EventWriterConfig {
+				batch_size: 10,
+				batch_timeout_ms: 10,
+				verbose_logging: false,
+			},
+		);
+		let engine = MatchingEngine::start(
+			EngineConfig {
+				market: "BTC-USDT".to_string(),
+				..Default::default()
+			},
+			queue_receiver,
+			event_producer,
+			journal,
+		);
+		(queue_sender, engine, event_writer)
+	};
+	let order = |n: u64| OrderCommand {
+		order_id: format!("order_{}", n),
+		market: "BTC-USDT".to_string(),
+		side: Side::Buy,
+		price: 50000 - n,
+		size: 1,
+		timestamp: 1000 + n,
+		public_key: "buyer".to_string(),
+		client_order_id: None,
+		nonce: n.to_string(),
+	};
+	let wait_for_sequence = |storage: &SharedEventStorage, seq: u64| {
+		for _ in 0..500 {
+			if storage.last_sequence() >= seq {
+				return;
+			}
+			std::thread::sleep(std::time::Duration::from_millis(10));
+		}
+		panic!("event {} was not committed", seq);
+	};
+	let order_ids = |engine: &MatchingEngine| -> Vec<String> {
+		let state = engine.capture_state().unwrap();
+		state
+			.orderbook
+			.orders()
+			.map(|o| o.order_id.clone())
+			.collect()
+	};
+
+	// Snapshot after three orders, then compact what it covers
+	let storage = open_log();
+	let mut snapshots = FileSnapshotStorage::open(dir.join("snapshots")).unwrap();
+	let (queue_sender, engine, event_writer) = start_engine(&storage);
+	for n in 1..=3 {
+		queue_sender.try_enqueue(order(n)).unwrap();
+	}
+	wait_for_sequence(&storage, 4);
+	let snapshot = engine.create_snapshot().unwrap();
+	assert_eq!(snapshot.metadata.event_seq, 4);
+	snapshots.save(snapshot).unwrap();
+	queue_sender.try_enqueue(order(4)).unwrap();
+	wait_for_sequence(&storage, 5);
+
+	let mut compacting = storage.clone();
+	assert_eq!(compacting.truncate_before(5).unwrap(), 3);
+	drop(engine);
+	drop(event_writer);
+
+	// The live log holds only the tail, the archive the rest
+	let storage = open_log();
+	assert_eq!(
+		FileEventStorage::open(dir.join("events"))
+			.unwrap()
+			.event_count(),
+		1
+	);
+	let audit: Vec<u64> = storage
+		.replay_from(0)
+		.unwrap()
+		.iter()
+		.map(|e| e.sequence())
+		.collect();
+	assert_eq!(audit, [2, 3, 4, 5]);
+
+	// Restart from the snapshot and the events after it
+	let (_queue_sender, engine, _event_writer) = start_engine(&storage);
+	let snapshot = snapshots.load_at_seq(storage.last_sequence()).unwrap();
+	let from_seq = snapshot.metadata.event_seq + 1;
+	engine.restore_from_snapshot(snapshot).unwrap();
+	engine
+		.replay_events(storage.replay_from(from_seq).unwrap())
+		.unwrap();
+	assert_eq!(
+		order_ids(&engine),
+		["order_1", "order_2", "order_3", "order_4"]
+	);
+
+	drop(engine);
+	std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_ledger_view_survives_restart() {
+	let dir = std::env::temp_dir().join(format!("anvil-ledger-{}", uuid::Uuid::new_v4()));
+	let risk = RiskConfig::for_market("BTC-USDT", true);
+
+	// Fund a principal and rest an order that locks part of the deposit
+	let journal: Box<dyn OrderJournal> = Box::new(MemoryOrderJournal::new());
+	let journal = Arc::new(Mutex::new(journal));
+	let (queue_sender, queue_receiver) = IngressQueue::new(100).split();
+	let (event_producer, event_consumer) = EventBuffer::new(100).split();
+	let event_writer = EventWriter::start(
+		event_consumer,
+		Box::new(FileEventStorage::open(dir.join("events")).unwrap()),
+		journal.clone(),
+		EventWriterConfig {
+			batch_size: 10,
+			batch_timeout_ms: 10,
+			verbose_logging: false,
+		},
+	);
+	let engine = MatchingEngine::start(
+		EngineConfig {
+			market: "BTC-USDT".to_string(),
+			verbose_logging: false,
+			risk: risk.clone(),
+		},
+		queue_receiver,
+		event_producer,
+		journal,
+	);
+	queue_sender
+		.try_enqueue_command(EngineCommand::AdjustBalance(BalanceCommand {
+			public_key: "buyer".to_string(),
+			asset: "USDT".to_string(),
+			amount: 150000,
+			adjustment: BalanceAdjustment::Deposit,
+			timestamp: 0,
+		}))
+		.unwrap();
+	queue_sender
+		.try_enqueue(OrderCommand {
+			order_id: "order_1".to_string(),
+			market: "BTC-USDT".to_string(),
+			side: Side::Buy,
+			price: 50000,
+			size: 2,
+			timestamp: 1000,
+			public_key: "buyer".to_string(),
+			client_order_id: None,
+			nonce: String::new(),
+		})
+		.unwrap();
+	std::thread::sleep(std::time::Duration::from_millis(300));
+	drop(engine);
+	drop(event_writer);
+
+	// A fresh view rebuilt from the log reports the same balances
+	let storage = FileEventStorage::open(dir.join("events")).unwrap();
+	let view = LedgerView::new(risk.ledger().unwrap());
+	view.rebuild(&storage).unwrap();
+	let usdt = view
+		.balances("buyer")
+		.into_iter()
+		.find(|(asset, _)| asset == "USDT")
+		.map(|(_, balance)| balance)
+		.unwrap();
+	assert_eq!(usdt.available, 50000);
+	assert_eq!(usdt.locked, 100000);
+
+	std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_compacted_log_without_a_usable_snapshot_is_not_replayed() {
+	let dir = std::env::temp_dir().join(format!("anvil-compaction-{}", uuid::Uuid::new_v4()));
+	let start_engine = |storage: &SharedEventStorage| {
+		let journal: Box<dyn OrderJournal> = Box::new(MemoryOrderJournal::new());
+		let journal = Arc::new(Mutex::new(journal));
+		let (queue_sender, queue_receiver) = IngressQueue::new(100).split();
+		let (event_producer, event_consumer) = EventBuffer::new(100).split();
+		let event_writer = EventWriter::start(
+			event_consumer,
+			Box::new(storage.clone()),
+			journal.clone(),
+			EventWriterConfig {
+				batch_size: 10,
+				batch_timeout_ms: 10,
+				verbose_logging: false,
+			},
+		);
+		let engine = MatchingEngine::start(
+			EngineConfig {
+				market: "BTC-USDT".to_string(),
+				..Default::default()
+			},
+			queue_receiver,
+			event_producer,
+			journal,
+		);
+		(queue_sender, engine, event_writer)
+	};
+
+	// Snapshot after three orders and delete the events it covers
+	let storage = SharedEventStorage::new(Box::new(
+		FileEventStorage::open(dir.join("events")).unwrap(),
+	));
+	let mut snapshots = FileSnapshotStorage::open(dir.join("snapshots")).unwrap();
+	let (queue_sender, engine, event_writer) = start_engine(&storage);
+	for n in 1..=3 {
+		queue_sender
+			.try_enqueue(OrderCommand {
+				order_id: format!("order_{}", n),
+				market: "BTC-USDT".to_string(),
+				side: Side::Buy,
+				price: 50000 - n,
+				size: 1,
+				timestamp: 1000 + n,
+				public_key: "buyer".to_string(),
+				client_order_id: None,
+				nonce: n.to_string(),
+			})
+			.unwrap();
+	}
+	for _ in 0..500 {
+		if storage.last_sequence() >= 4 {
+			break;
+		}
+		std::thread::sleep(std::time::Duration::from_millis(10));
+	}
+	let snapshot = snapshots
+		.save_with(&mut |out| engine.write_snapshot(out))
+		.unwrap();
+	assert_eq!(snapshot.event_seq, 4);
+	let mut compacting = storage.clone();
+	assert!(compacting.truncate_before(snapshot.event_seq + 1).unwrap() > 0);
+	drop(engine);
+	drop(event_writer);
+
+	// Corrupt the only snapshot
+	let path = dir
+		.join("snapshots")
+		.join(format!("snapshot-{:020}.snap", snapshot.event_seq));
+	let mut bytes = std::fs::read(&path).unwrap();
+	bytes[snapshot.size_bytes - 1] ^= 0xff;
+	std::fs::write(&path, bytes).unwrap();
+
+	// The snapshot does not load, and the log alone no longer starts at
+	// the beginning, so neither is applied
+	let storage = SharedEventStorage::new(Box::new(
+		FileEventStorage::open(dir.join("events")).unwrap(),
+	));
+	let (_queue_sender, engine, _event_writer) = start_engine(&storage);
+	let (metadata, data) = snapshots.open_at_seq(storage.last_sequence()).unwrap();
+	assert!(engine.restore_from_reader(&metadata, data).is_err());
+	assert!(
+		engine
+			.replay_events(storage.replay_from(1).unwrap())
+			.is_err()
+	);
+	assert_eq!(engine.capture_state().unwrap().orderbook.order_count(), 0);
+
+	drop(engine);
+	std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_views_restart_from_the_snapshot_when_events_are_deleted() {
+	let dir = std::env::temp_dir().join(format!("anvil-retention-{}", uuid::Uuid::new_v4()));
+	let risk = RiskConfig::for_market("BTC-USDT", true);
+	let deposit = |amount| {
+		EngineCommand::AdjustBalance(BalanceCommand {
+			public_key: "buyer".to_string(),
+			asset: "USDT".to_string(),
+			amount,
+			adjustment: BalanceAdjustment::Deposit,
+			timestamp: 0,
+		})
+	};
+	let wait_for = |storage: &SharedEventStorage, seq| {
+		for _ in 0..500 {
+			if storage.last_sequence() >= seq {
+				break;
+			}
+			std::thread::sleep(std::time::Duration::from_millis(10));
+		}
+	};
+
+	// Fund a principal and rest two orders
+	let storage = SharedEventStorage::new(Box::new(
+		FileEventStorage::open(dir.join("events")).unwrap(),
+	));
+	let mut snapshots = FileSnapshotStorage::open(dir.join("snapshots")).unwrap();
+	let journal: Box<dyn OrderJournal> = Box::new(MemoryOrderJournal::new());
+	let journal = Arc::new(Mutex::new(journal));
+	let (queue_sender, queue_receiver) = IngressQueue::new(100).split();
+	let (event_producer, event_consumer) = EventBuffer::new(100).split();
+	let event_writer = EventWriter::start(
+		event_consumer,
+		Box::new(storage.clone()),
+		journal.clone(),
+		EventWriterConfig {
+			batch_size: 10,
+			batch_timeout_ms: 10,
+			verbose_logging: false,
+		},
+	);
+	let engine = MatchingEngine::start(
+		EngineConfig {
+			market: "BTC-USDT".to_string(),
+			verbose_logging: false,
+			risk: risk.clone(),
+		},
+		queue_receiver,
+		event_producer,
+		journal,
+	);
+	queue_sender.try_enqueue_command(deposit(150000)).unwrap();
+	for (n, price, size) in [(1, 50000, 2), (2, 49000, 1)] {
+		queue_sender
+			.try_enqueue(OrderCommand {
+				order_id: format!("order_{}", n),
+				market: "BTC-USDT".to_string(),
+				side: Side::Buy,
+				price,
+				size,
+				timestamp: 1000 + n,
+				public_key: "buyer".to_string(),
+				client_order_id: None,
+				nonce: n.to_string(),
+			})
+			.unwrap();
+	}
+	wait_for(&storage, 4);
+
+	// Snapshot, commit one more event and delete everything before it
+	let snapshot = snapshots
+		.save_with(&mut |out| engine.write_snapshot(out))
+		.unwrap();
+	assert_eq!(snapshot.event_seq, 4);
+	queue_sender.try_enqueue_command(deposit(1000)).unwrap();
+	wait_for(&storage, 5);
+	let mut compacting = storage.clone();
+	assert_eq!(
+		compacting.truncate_before(snapshot.event_seq + 1).unwrap(),
+		3
+	);
+	drop(engine);
+	drop(event_writer);
+
+	// The retained events alone lose the first deposit and both orders
+	let storage = FileEventStorage::open(dir.join("events")).unwrap();
+	let ledger_view = LedgerView::new(risk.ledger().unwrap());
+	ledger_view.rebuild(&storage).unwrap();
+	assert_eq!(ledger_view.balances("buyer")[0].1.locked, 0);
+
+	// Started from the snapshot, the views match the state before restart
+	let (metadata, data) = snapshots.open_at_seq(snapshot.event_seq).unwrap();
+	let state = MatchingEngineState::read_snapshot(&metadata, data).unwrap();
+	let seq = state.next_sequence;
+	ledger_view
+		.rebuild_from(state.ledger.clone(), seq, &storage)
+		.unwrap();
+	let usdt = ledger_view
+		.balances("buyer")
+		.into_iter()
+		.find(|(asset, _)| asset == "USDT")
+		.map(|(_, balance)| balance)
+		.unwrap();
+	assert_eq!(usdt.available, 2000);
+	assert_eq!(usdt.locked, 149000);
+
+	let (_publisher, market_data) = MarketDataPublisher::start("BTC-USDT", 16);
+	market_data
+		.rebuild_from(state.orderbook.orders(), seq, &storage)
+		.unwrap();
+	let book = market_data.snapshot(0);
+	assert_eq!(
+		book.bids
+			.iter()
+			.map(|level| (level.price, level.size))
+			.collect::<Vec<_>>(),
+		vec![(50000, 2), (49000, 1)]
+	);
+	assert_eq!(book.event_sequence, 5);
+
+	let order_view = OrderView::new(10);
+	order_view
+		.rebuild_from(state.orderbook.orders(), seq, &storage)
+		.unwrap();
+	assert_eq!(order_view.get("order_2").unwrap().remaining_size, 1);
+
+	std::fs::remove_dir_all(dir).unwrap();
+}
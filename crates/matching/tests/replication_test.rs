@@ -0,0 +1,284 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Hot-standby replication: a follower engine applies the primary's
+//! committed events over gRPC and takes over when promoted
+
+use std::{
+	sync::{Arc, Mutex},
+	time::Duration,
+};
+
+use anvil_matching::server::{
+	MatchingServiceImpl,
+	proto::{
+		EngineRole, FenceRequest, GetEngineStatusRequest, GetEngineStatusResponse, OrderSide,
+		PromoteRequest, SubmitDisposition, SubmitOrderRequest,
+		matching_service_client::MatchingServiceClient,
+	},
+};
+use anvil_matching::{
+	CommittedEventSink, EventBuffer, EventHub, EventHubConfig, EventWriter, EventWriterConfig,
+	Follower, FollowerConfig, IngressQueue, MatchingEngine, MatchingEngineState,
+	MemoryEventStorage, MemoryOrderJournal, OrderJournal, ReplicationState, SharedEventStorage,
+	engine::EngineConfig,
+};
+use tonic::{
+	Code,
+	transport::{Channel, Server, server::TcpIncoming},
+};
+
+const MARKET: &str = "BTC-USDT";
+
+/// One engine with its event writer and gRPC service, in process
+struct Node {
+	endpoint: String,
+	engine: Arc<MatchingEngine>,
+	_event_writer: EventWriter,
+}
+
+impl Node {
+	fn start(replication: ReplicationState, replicate_from: Option<&str>) -> Self {
+		let journal: Box<dyn OrderJournal> = Box::new(MemoryOrderJournal::new());
+		let journal = Arc::new(Mutex::new(journal));
+		let (queue_sender, queue_receiver) = IngressQueue::new(1000).split();
+		let (event_producer, event_consumer) = EventBuffer::new(1000).split();
+
+		let storage = SharedEventStorage::new(Box::new(MemoryEventStorage::new()));
+		let hub = EventHub::new(storage.clone(), EventHubConfig::default());
+		let sinks: Vec<Box<dyn CommittedEventSink>> = vec![Box::new(hub.clone())];
+		let event_writer = EventWriter::start_with_sinks(
+			event_consumer,
+			Box::new(storage),
+			journal.clone(),
+			EventWriterConfig {
+				batch_size: 10,
+				batch_timeout_ms: 5,
+				verbose_logging: false,
+			},
+			sinks,
+		);
+
+		let engine = Arc::new(MatchingEngine::start(
+			EngineConfig {
+				market: MARKET.to_string(),
+				..Default::default()
+			},
+			queue_receiver,
+			event_producer,
+			journal.clone(),
+		));
+		if let Some(primary) = replicate_from {
+			let mut config = FollowerConfig::new(primary);
+			config.retry_interval = Duration::from_millis(50);
+			Follower::new(
+				engine.clone(),
+				replication.clone(),
+				MARKET.to_string(),
+				config,
+			)
+			.start();
+		}
+
+		let service = MatchingServiceImpl::new(queue_sender, journal, MARKET.to_string())
+			.with_event_hub(hub)
+			.with_replication(replication);
+		let incoming = TcpIncoming::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+		let endpoint = format!("http://{}", incoming.local_addr().unwrap());
+		tokio::spawn(
+			Server::builder()
+				.add_service(service.into_server())
+				.serve_with_incoming(incoming),
+		);
+
+		Self {
+			endpoint,
+			engine,
+			_event_writer: event_writer,
+		}
+	}
+
+	async fn client(&self) -> MatchingServiceClient<Channel> {
+		MatchingServiceClient::connect(self.endpoint.clone())
+			.await
+			.unwrap()
+	}
+
+	async fn status(&self) -> GetEngineStatusResponse {
+		self.client()
+			.await
+			.get_engine_status(GetEngineStatusRequest {})
+			.await
+			.unwrap()
+			.into_inner()
+	}
+
+	async fn submit(
+		&self,
+		order_id: &str,
+		side: OrderSide,
+		price: u64,
+		size: u64,
+	) -> Result<SubmitDisposition, Code> {
+		self.client()
+			.await
+			.submit_order(SubmitOrderRequest {
+				order_id: order_id.to_string(),
+				market: MARKET.to_string(),
+				side: side as i32,
+				price,
+				size,
+				remaining_size: size,
+				timestamp: 1,
+				public_key: format!("key_{}", order_id),
+				nonce: order_id.to_string(),
+				..Default::default()
+			})
+			.await
+			.map(|response| response.into_inner().disposition())
+			.map_err(|status| status.code())
+	}
+
+	/// Resting orders and derived state, compared as JSON values
+	///
+	/// Replay rebuilds the book from committed events, so only what the
+	/// events determine is compared: each resting order's price and
+	/// remaining size, not its submitted size, timestamp or empty levels.
+	async fn state(&self) -> serde_json::Value {
+		let engine = self.engine.clone();
+		let snapshot = tokio::task::spawn_blocking(move || engine.create_snapshot())
+			.await
+			.unwrap()
+			.unwrap();
+		let state = MatchingEngineState::from_snapshot(&snapshot).unwrap();
+		let resting: serde_json::Map<_, _> = state
+			.orderbook
+			.orders()
+			.map(|order| {
+				(
+					order.order_id.clone(),
+					serde_json::json!([order.side, order.price, order.remaining_size]),
+				)
+			})
+			.collect();
+		serde_json::json!({
+			"orderbook": resting,
+			"ledger": state.ledger,
+			"exposure": state.exposure,
+			"client_orders": state.client_orders,
+		})
+	}
+}
+
+/// Poll `check` until it holds, for at most five seconds
+async fn eventually<F, Fut>(mut check: F)
+where
+	F: FnMut() -> Fut,
+	Fut: std::future::Future<Output = bool>,
+{
+	for _ in 0..500 {
+		if check().await {
+			return;
+		}
+		tokio::time::sleep(Duration::from_millis(10)).await;
+	}
+	panic!("Condition not reached in time");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn follower_replicates_and_takes_over_when_promoted() {
+	let primary = Node::start(ReplicationState::primary(0), None);
+	let follower = Node::start(ReplicationState::follower(0), Some(&primary.endpoint));
+
+	// Resting orders, a partial fill and a full fill
+	for (order_id, side, price, size) in [
+		("bid_1", OrderSide::Buy, 100, 5),
+		("bid_2", OrderSide::Buy, 99, 3),
+		("ask_1", OrderSide::Sell, 101, 4),
+		("ask_2", OrderSide::Sell, 100, 2),
+		("ask_3", OrderSide::Sell, 99, 4),
+	] {
+		assert_eq!(
+			primary.submit(order_id, side, price, size).await,
+			Ok(SubmitDisposition::AcceptedOk)
+		);
+	}
+
+	// Accepted orders are committed asynchronously, so wait for the state
+	eventually(|| async {
+		let (primary_status, follower_status) = (primary.status().await, follower.status().await);
+		primary_status.last_sequence > 0
+			&& follower_status.last_sequence == primary_status.last_sequence
+			&& follower_status.replication_lag == 0
+			&& follower.state().await == primary.state().await
+	})
+	.await;
+	let status = follower.status().await;
+	assert_eq!(status.role(), EngineRole::Follower);
+	let replicated = status.last_sequence;
+
+	// A follower refuses orders, and promotion needs a newer epoch
+	assert_eq!(
+		follower.submit("early", OrderSide::Buy, 98, 1).await,
+		Err(Code::FailedPrecondition)
+	);
+	let mut follower_client = follower.client().await;
+	assert_eq!(
+		follower_client
+			.promote(PromoteRequest { epoch: 0 })
+			.await
+			.unwrap_err()
+			.code(),
+		Code::FailedPrecondition
+	);
+	let promoted = follower_client
+		.promote(PromoteRequest { epoch: 1 })
+		.await
+		.unwrap()
+		.into_inner();
+	assert_eq!(promoted.applied_sequence, replicated);
+
+	// The promoted follower fences the primary it followed
+	eventually(|| async { primary.status().await.role() == EngineRole::Fenced }).await;
+	assert_eq!(primary.status().await.epoch, 1);
+	assert_eq!(
+		primary.submit("stale", OrderSide::Buy, 98, 1).await,
+		Err(Code::FailedPrecondition)
+	);
+	assert_eq!(
+		primary
+			.client()
+			.await
+			.fence(FenceRequest { epoch: 1 })
+			.await
+			.unwrap_err()
+			.code(),
+		Code::FailedPrecondition
+	);
+
+	// The new primary continues the sequence from the replicated state
+	let status = follower.status().await;
+	assert_eq!((status.role(), status.epoch), (EngineRole::Primary, 1));
+	assert_eq!(
+		follower.submit("bid_3", OrderSide::Buy, 101, 4).await,
+		Ok(SubmitDisposition::AcceptedOk)
+	);
+	eventually(|| async { follower.status().await.last_sequence > replicated }).await;
+	let book = &follower.state().await["orderbook"];
+	assert!(
+		book["ask_3"].is_null(),
+		"ask_3 should have filled: {}",
+		book
+	);
+}
@@ -12,8 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::signing::{SignatureAlgorithm, sign_order_request};
-use crate::types::{Order, PlaceOrderRequest, PlaceOrderResponse};
+use crate::signing::{SignatureAlgorithm, query_signing_message, sign_message, sign_order_request};
+use crate::types::{
+	CandlesResponse, FillsPage, Order, PlaceOrderRequest, PlaceOrderResponse, Ticker, TradesPage,
+};
 use reqwest::Client as ReqwestClient;
 use std::time::Duration;
 use thiserror::Error;
@@ -132,22 +134,7 @@ impl Client {
 			.map_err(|e| ClientError::Authentication(format!("Signing failed: {}", e)))?;
 
 		// Extract public key from private key
-		let public_key = match algorithm {
-			crate::signing::SignatureAlgorithm::Ed25519 => {
-				use ed25519_dalek::SigningKey;
-				let signing_key = SigningKey::from_bytes(private_key.try_into().map_err(|_| {
-					ClientError::Authentication("Invalid Ed25519 private key length".to_string())
-				})?);
-				signing_key.verifying_key().to_bytes().to_vec()
-			}
-			crate::signing::SignatureAlgorithm::Ecdsa => {
-				use k256::ecdsa::SigningKey;
-				let signing_key = SigningKey::from_bytes(private_key.into()).map_err(|e| {
-					ClientError::Authentication(format!("Invalid ECDSA private key: {}", e))
-				})?;
-				signing_key.verifying_key().to_sec1_bytes().to_vec()
-			}
-		};
+		let public_key = public_key_for(private_key, algorithm)?;
 
 		// Place order with authentication materials in headers
 		let url = format!("{}/api/v1/orders", self.base_url);
@@ -230,6 +217,158 @@ impl Client {
 		Ok(())
 	}
 
+	/// Get the latest `limit` candles of `interval` ("1m", "5m", "1h" or "1d")
+	pub async fn get_candles(
+		&self,
+		market: &str,
+		interval: &str,
+		limit: u32,
+	) -> Result<CandlesResponse, ClientError> {
+		let url = format!(
+			"{}/api/v1/markets/{}/candles?interval={}&limit={}",
+			self.base_url, market, interval, limit
+		);
+
+		let response = self
+			.client
+			.get(&url)
+			.send()
+			.await
+			.map_err(|e| ClientError::Network(format!("Request failed: {}", e)))?;
+
+		if !response.status().is_success() {
+			let status = response.status();
+			let error_text = response
+				.text()
+				.await
+				.unwrap_or_else(|_| format!("HTTP {}", status));
+			return Err(ClientError::Server(format!("{}: {}", status, error_text)));
+		}
+
+		response
+			.json()
+			.await
+			.map_err(|e| ClientError::Serialization(format!("Failed to parse response: {}", e)))
+	}
+
+	/// Get rolling 24h ticker statistics for a market
+	pub async fn get_ticker(&self, market: &str) -> Result<Ticker, ClientError> {
+		let url = format!("{}/api/v1/markets/{}/ticker", self.base_url, market);
+
+		let response = self
+			.client
+			.get(&url)
+			.send()
+			.await
+			.map_err(|e| ClientError::Network(format!("Request failed: {}", e)))?;
+
+		if !response.status().is_success() {
+			let status = response.status();
+			let error_text = response
+				.text()
+				.await
+				.unwrap_or_else(|_| format!("HTTP {}", status));
+			return Err(ClientError::Server(format!("{}: {}", status, error_text)));
+		}
+
+		response
+			.json()
+			.await
+			.map_err(|e| ClientError::Serialization(format!("Failed to parse response: {}", e)))
+	}
+
+	/// Get one page of a market's trades, newest first
+	///
+	/// Pass the previous page's `next_cursor` to continue.
+	pub async fn get_trades(
+		&self,
+		market: &str,
+		cursor: Option<&str>,
+		limit: u32,
+	) -> Result<TradesPage, ClientError> {
+		let mut url = format!(
+			"{}/api/v1/markets/{}/trades?limit={}",
+			self.base_url, market, limit
+		);
+		if let Some(cursor) = cursor {
+			url.push_str(&format!("&cursor={}", cursor));
+		}
+
+		let response = self
+			.client
+			.get(&url)
+			.send()
+			.await
+			.map_err(|e| ClientError::Network(format!("Request failed: {}", e)))?;
+
+		if !response.status().is_success() {
+			let status = response.status();
+			let error_text = response
+				.text()
+				.await
+				.unwrap_or_else(|_| format!("HTTP {}", status));
+			return Err(ClientError::Server(format!("{}: {}", status, error_text)));
+		}
+
+		response
+			.json()
+			.await
+			.map_err(|e| ClientError::Serialization(format!("Failed to parse response: {}", e)))
+	}
+
+	/// Get one page of the signing principal's fills in a market, newest first
+	pub async fn get_fills_signed(
+		&self,
+		market: &str,
+		cursor: Option<&str>,
+		limit: u32,
+		private_key: &[u8],
+		algorithm: SignatureAlgorithm,
+	) -> Result<FillsPage, ClientError> {
+		let mut path = format!("/api/v1/fills?market={}&limit={}", market, limit);
+		if let Some(cursor) = cursor {
+			path.push_str(&format!("&cursor={}", cursor));
+		}
+
+		let timestamp = std::time::SystemTime::now()
+			.duration_since(std::time::UNIX_EPOCH)
+			.map_err(|e| ClientError::Authentication(format!("Invalid system time: {}", e)))?
+			.as_secs();
+		let nonce = uuid::Uuid::new_v4().to_string();
+		let signature = sign_message(
+			&query_signing_message("GET", &path, timestamp, &nonce),
+			private_key,
+			algorithm,
+		)
+		.map_err(|e| ClientError::Authentication(format!("Signing failed: {}", e)))?;
+		let public_key = public_key_for(private_key, algorithm)?;
+
+		let response = self
+			.client
+			.get(format!("{}{}", self.base_url, path))
+			.header("X-Public-Key", hex::encode(&public_key))
+			.header("X-Signature", &signature)
+			.header("X-Timestamp", timestamp.to_string())
+			.header("X-Nonce", &nonce)
+			.send()
+			.await
+			.map_err(|e| ClientError::Network(format!("Request failed: {}", e)))?;
+
+		if !response.status().is_success() {
+			let status = response.status();
+			let error_text = response
+				.text()
+				.await
+				.unwrap_or_else(|_| format!("HTTP {}", status));
+			return Err(ClientError::Server(format!("{}: {}", status, error_text)));
+		}
+
+		response
+			.json()
+			.await
+			.map_err(|e| ClientError::Serialization(format!("Failed to parse response: {}", e)))
+	}
+
 	/// Check gateway health
 	pub async fn health_check(&self) -> Result<bool, ClientError> {
 		let url = format!("{}/health", self.base_url);
@@ -245,6 +384,29 @@ impl Client {
 	}
 }
 
+/// Derive the public key sent in `X-Public-Key` from a private key
+fn public_key_for(
+	private_key: &[u8],
+	algorithm: SignatureAlgorithm,
+) -> Result<Vec<u8>, ClientError> {
+	match algorithm {
+		SignatureAlgorithm::Ed25519 => {
+			use ed25519_dalek::SigningKey;
+			let signing_key = SigningKey::from_bytes(private_key.try_into().map_err(|_| {
+				ClientError::Authentication("Invalid Ed25519 private key length".to_string())
+			})?);
+			Ok(signing_key.verifying_key().to_bytes().to_vec())
+		}
+		SignatureAlgorithm::Ecdsa => {
+			use k256::ecdsa::SigningKey;
+			let signing_key = SigningKey::from_bytes(private_key.into()).map_err(|e| {
+				ClientError::Authentication(format!("Invalid ECDSA private key: {}", e))
+			})?;
+			Ok(signing_key.verifying_key().to_sec1_bytes().to_vec())
+		}
+	}
+}
+
 /// Synchronous client wrapper (for compatibility)
 ///
 /// This wraps the async client and runs it in a tokio runtime.
@@ -0,0 +1,490 @@
+// Copyright 2025 itscheems
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fixed-point decimal model for prices and quantities
+//!
+//! Internally every price and size is an unsigned integer number of *units*,
+//! where one unit is `10^-decimals` of the displayed value. The number of
+//! decimals is a per-market property described by [`MarketSpec`]:
+//!
+//! - `BTC-USDT` with `price_decimals = 2` stores `50000.25` as `5_000_025`
+//! - `BTC-USDT` with `size_decimals = 8` stores `0.5` as `50_000_000`
+//!
+//! Conversion between decimal strings and units is exact: a string with more
+//! significant fractional digits than the market allows is rejected rather
+//! than rounded. Notional values (`price * size`) are computed with
+//! overflow-checked 128-bit arithmetic.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Largest supported number of decimals (`10^19` no longer fits in a `u64`)
+pub const MAX_DECIMALS: u32 = 18;
+
+/// Error types for decimal conversion and arithmetic
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DecimalError {
+	#[error("Invalid decimal string: {0:?}")]
+	Invalid(String),
+	#[error("Too many decimal places in {value:?}: at most {max} allowed")]
+	TooPrecise { value: String, max: u32 },
+	#[error("Decimal value out of range: {0:?}")]
+	OutOfRange(String),
+	#[error("Unsupported number of decimals: {0}")]
+	UnsupportedScale(u32),
+	#[error("Arithmetic overflow")]
+	Overflow,
+	#[error("Scale mismatch: {left} vs {right} decimals")]
+	ScaleMismatch { left: u32, right: u32 },
+}
+
+/// Parse a decimal string into integer units at the given scale
+///
+/// Accepts plain non-negative decimals such as `"42"`, `"0.5"` or
+/// `"50000.25"`. Signs, exponents, whitespace and separators are rejected.
+/// Trailing zeros beyond the scale are accepted because they do not change
+/// the value (`"1.50"` at one decimal is `15`).
+pub fn parse_units(value: &str, decimals: u32) -> Result<u64, DecimalError> {
+	if decimals > MAX_DECIMALS {
+		return Err(DecimalError::UnsupportedScale(decimals));
+	}
+
+	let (integer, fraction) = match value.split_once('.') {
+		Some((integer, fraction)) => (integer, Some(fraction)),
+		None => (value, None),
+	};
+
+	let is_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+	if !is_digits(integer) || fraction.is_some_and(|f| !is_digits(f)) {
+		return Err(DecimalError::Invalid(value.to_string()));
+	}
+
+	let fraction = fraction.unwrap_or("");
+	let significant = fraction.trim_end_matches('0');
+	if significant.len() > decimals as usize {
+		return Err(DecimalError::TooPrecise {
+			value: value.to_string(),
+			max: decimals,
+		});
+	}
+
+	let out_of_range = || DecimalError::OutOfRange(value.to_string());
+	let scale = 10u64.pow(decimals);
+	let integer_units = integer
+		.parse::<u64>()
+		.map_err(|_| out_of_range())?
+		.checked_mul(scale)
+		.ok_or_else(out_of_range)?;
+
+	let fraction_units = if significant.is_empty() {
+		0
+	} else {
+		let padding = 10u64.pow(decimals - significant.len() as u32);
+		significant.parse::<u64>().map_err(|_| out_of_range())? * padding
+	};
+
+	integer_units
+		.checked_add(fraction_units)
+		.ok_or_else(out_of_range)
+}
+
+/// Format integer units as a decimal string with exactly `decimals` places
+pub fn format_units(units: u128, decimals: u32) -> String {
+	if decimals == 0 {
+		return units.to_string();
+	}
+	let scale = 10u128.pow(decimals);
+	format!(
+		"{}.{:0width$}",
+		units / scale,
+		units % scale,
+		width = decimals as usize
+	)
+}
+
+/// Typed fixed-point price carrying its market's number of decimals
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Price {
+	units: u64,
+	decimals: u32,
+}
+
+impl Price {
+	/// Create a price from raw units at the given scale
+	pub const fn from_units(units: u64, decimals: u32) -> Self {
+		Self { units, decimals }
+	}
+
+	/// Parse a decimal string exactly at the given scale
+	pub fn parse(value: &str, decimals: u32) -> Result<Self, DecimalError> {
+		Ok(Self::from_units(parse_units(value, decimals)?, decimals))
+	}
+
+	/// Raw integer units (what the matching engine operates on)
+	pub const fn units(&self) -> u64 {
+		self.units
+	}
+
+	/// Number of decimals of this price
+	pub const fn decimals(&self) -> u32 {
+		self.decimals
+	}
+
+	pub const fn is_zero(&self) -> bool {
+		self.units == 0
+	}
+
+	/// Compute `price * quantity` with 128-bit arithmetic
+	///
+	/// The result carries `price.decimals + quantity.decimals` decimals.
+	pub fn checked_notional(&self, quantity: Quantity) -> Result<Notional, DecimalError> {
+		let units = (self.units as u128)
+			.checked_mul(quantity.units as u128)
+			.ok_or(DecimalError::Overflow)?;
+		Ok(Notional {
+			units,
+			decimals: self.decimals + quantity.decimals,
+		})
+	}
+}
+
+impl fmt::Display for Price {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&format_units(self.units as u128, self.decimals))
+	}
+}
+
+/// Typed fixed-point quantity carrying its market's number of decimals
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Quantity {
+	units: u64,
+	decimals: u32,
+}
+
+impl Quantity {
+	/// Create a quantity from raw units at the given scale
+	pub const fn from_units(units: u64, decimals: u32) -> Self {
+		Self { units, decimals }
+	}
+
+	/// Parse a decimal string exactly at the given scale
+	pub fn parse(value: &str, decimals: u32) -> Result<Self, DecimalError> {
+		Ok(Self::from_units(parse_units(value, decimals)?, decimals))
+	}
+
+	/// Raw integer units (what the matching engine operates on)
+	pub const fn units(&self) -> u64 {
+		self.units
+	}
+
+	/// Number of decimals of this quantity
+	pub const fn decimals(&self) -> u32 {
+		self.decimals
+	}
+
+	pub const fn is_zero(&self) -> bool {
+		self.units == 0
+	}
+
+	pub fn checked_add(&self, other: Quantity) -> Result<Quantity, DecimalError> {
+		self.check_scale(other)?;
+		let units = self
+			.units
+			.checked_add(other.units)
+			.ok_or(DecimalError::Overflow)?;
+		Ok(Self::from_units(units, self.decimals))
+	}
+
+	pub fn checked_sub(&self, other: Quantity) -> Result<Quantity, DecimalError> {
+		self.check_scale(other)?;
+		let units = self
+			.units
+			.checked_sub(other.units)
+			.ok_or(DecimalError::Overflow)?;
+		Ok(Self::from_units(units, self.decimals))
+	}
+
+	fn check_scale(&self, other: Quantity) -> Result<(), DecimalError> {
+		if self.decimals != other.decimals {
+			return Err(DecimalError::ScaleMismatch {
+				left: self.decimals,
+				right: other.decimals,
+			});
+		}
+		Ok(())
+	}
+}
+
+impl fmt::Display for Quantity {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&format_units(self.units as u128, self.decimals))
+	}
+}
+
+/// Result of `price * quantity`, in quote-asset units
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Notional {
+	units: u128,
+	decimals: u32,
+}
+
+impl Notional {
+	pub const fn units(&self) -> u128 {
+		self.units
+	}
+
+	pub const fn decimals(&self) -> u32 {
+		self.decimals
+	}
+
+	pub fn checked_add(&self, other: Notional) -> Result<Notional, DecimalError> {
+		if self.decimals != other.decimals {
+			return Err(DecimalError::ScaleMismatch {
+				left: self.decimals,
+				right: other.decimals,
+			});
+		}
+		let units = self
+			.units
+			.checked_add(other.units)
+			.ok_or(DecimalError::Overflow)?;
+		Ok(Self {
+			units,
+			decimals: self.decimals,
+		})
+	}
+}
+
+impl fmt::Display for Notional {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&format_units(self.units, self.decimals))
+	}
+}
+
+/// Price or size as it appears on the wire
+///
+/// JSON numbers are taken as raw integer units (the historical format) and
+/// JSON strings as human-readable decimals that are converted exactly using
+/// the market's [`MarketSpec`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Amount {
+	/// Raw integer units, already scaled
+	Units(u64),
+	/// Human-readable decimal string (e.g. `"50000.25"`)
+	Decimal(String),
+}
+
+impl Amount {
+	/// Append the canonical signing encoding of this amount
+	///
+	/// Raw units are encoded as 8 big-endian bytes. Decimal strings are
+	/// signed as written (UTF-8) followed by a `0` terminator, so the
+	/// signature covers exactly what the client sent.
+	pub fn write_signing_bytes(&self, message: &mut Vec<u8>) {
+		match self {
+			Amount::Units(units) => message.extend_from_slice(&units.to_be_bytes()),
+			Amount::Decimal(value) => {
+				message.extend_from_slice(value.as_bytes());
+				message.push(0);
+			}
+		}
+	}
+}
+
+impl From<u64> for Amount {
+	fn from(units: u64) -> Self {
+		Amount::Units(units)
+	}
+}
+
+impl From<&str> for Amount {
+	fn from(value: &str) -> Self {
+		Amount::Decimal(value.to_string())
+	}
+}
+
+impl fmt::Display for Amount {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Amount::Units(units) => write!(f, "{}", units),
+			Amount::Decimal(value) => f.write_str(value),
+		}
+	}
+}
+
+/// Per-market description of price and size scales
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarketSpec {
+	/// Market identifier (e.g., "BTC-USDT")
+	pub market: String,
+	/// Base asset (e.g., "BTC")
+	pub base_asset: String,
+	/// Quote asset (e.g., "USDT")
+	pub quote_asset: String,
+	/// Number of decimals in a price
+	pub price_decimals: u32,
+	/// Number of decimals in a size
+	pub size_decimals: u32,
+}
+
+impl MarketSpec {
+	/// Create a spec for a `BASE-QUOTE` market identifier
+	pub fn new(
+		market: impl Into<String>,
+		price_decimals: u32,
+		size_decimals: u32,
+	) -> Result<Self, DecimalError> {
+		let market = market.into();
+		let (base, quote) = market
+			.split_once('-')
+			.filter(|(base, quote)| !base.is_empty() && !quote.is_empty())
+			.ok_or_else(|| DecimalError::Invalid(market.clone()))?;
+
+		let spec = Self {
+			base_asset: base.to_string(),
+			quote_asset: quote.to_string(),
+			market,
+			price_decimals,
+			size_decimals,
+		};
+		spec.validate()?;
+		Ok(spec)
+	}
+
+	/// Check that both scales are supported
+	pub fn validate(&self) -> Result<(), DecimalError> {
+		for decimals in [self.price_decimals, self.size_decimals] {
+			if decimals > MAX_DECIMALS {
+				return Err(DecimalError::UnsupportedScale(decimals));
+			}
+		}
+		Ok(())
+	}
+
+	/// Convert a wire amount into a price on this market
+	pub fn price(&self, amount: &Amount) -> Result<Price, DecimalError> {
+		match amount {
+			Amount::Units(units) => Ok(Price::from_units(*units, self.price_decimals)),
+			Amount::Decimal(value) => Price::parse(value, self.price_decimals),
+		}
+	}
+
+	/// Convert a wire amount into a quantity on this market
+	pub fn quantity(&self, amount: &Amount) -> Result<Quantity, DecimalError> {
+		match amount {
+			Amount::Units(units) => Ok(Quantity::from_units(*units, self.size_decimals)),
+			Amount::Decimal(value) => Quantity::parse(value, self.size_decimals),
+		}
+	}
+
+	/// Number of decimals of a notional value on this market
+	pub const fn notional_decimals(&self) -> u32 {
+		self.price_decimals + self.size_decimals
+	}
+
+	/// Notional value of raw price and size units on this market
+	pub fn notional(&self, price_units: u64, size_units: u64) -> Result<Notional, DecimalError> {
+		Price::from_units(price_units, self.price_decimals)
+			.checked_notional(Quantity::from_units(size_units, self.size_decimals))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parse_units_is_exact() {
+		assert_eq!(parse_units("50000.25", 2).unwrap(), 5_000_025);
+		assert_eq!(parse_units("42", 2).unwrap(), 4_200);
+		assert_eq!(parse_units("0.5", 8).unwrap(), 50_000_000);
+		assert_eq!(parse_units("1.50", 1).unwrap(), 15);
+		assert_eq!(parse_units("7", 0).unwrap(), 7);
+	}
+
+	#[test]
+	fn parse_units_rejects_lossy_or_malformed_input() {
+		assert!(matches!(
+			parse_units("0.123", 2),
+			Err(DecimalError::TooPrecise { max: 2, .. })
+		));
+		for bad in ["", ".5", "5.", "-1", "+1", "1e3", " 1", "1,000", "1.2.3"] {
+			assert!(
+				matches!(parse_units(bad, 2), Err(DecimalError::Invalid(_))),
+				"{bad:?} should be invalid"
+			);
+		}
+		assert!(matches!(
+			parse_units("184467440737095516.16", 2),
+			Err(DecimalError::OutOfRange(_))
+		));
+		assert!(matches!(
+			parse_units("1", 19),
+			Err(DecimalError::UnsupportedScale(19))
+		));
+	}
+
+	#[test]
+	fn format_round_trips() {
+		let price = Price::parse("50000.05", 2).unwrap();
+		assert_eq!(price.units(), 5_000_005);
+		assert_eq!(price.to_string(), "50000.05");
+		assert_eq!(Quantity::from_units(1, 8).to_string(), "0.00000001");
+		assert_eq!(format_units(12, 0), "12");
+	}
+
+	#[test]
+	fn notional_uses_128_bit_math() {
+		let price = Price::from_units(u64::MAX, 2);
+		let quantity = Quantity::from_units(u64::MAX, 8);
+		let notional = price.checked_notional(quantity).unwrap();
+		assert_eq!(notional.units(), u64::MAX as u128 * u64::MAX as u128);
+		assert_eq!(notional.decimals(), 10);
+
+		let spec = MarketSpec::new("BTC-USDT", 2, 8).unwrap();
+		let notional = spec.notional(5_000_025, 50_000_000).unwrap();
+		assert_eq!(notional.to_string(), "25000.1250000000");
+	}
+
+	#[test]
+	fn market_spec_converts_wire_amounts() {
+		let spec = MarketSpec::new("ETH-USDC", 2, 4).unwrap();
+		assert_eq!(spec.base_asset, "ETH");
+		assert_eq!(spec.quote_asset, "USDC");
+		assert_eq!(
+			spec.price(&Amount::from("3000.5")).unwrap().units(),
+			300_050
+		);
+		assert_eq!(
+			spec.price(&Amount::Units(300_050)).unwrap().units(),
+			300_050
+		);
+		assert_eq!(
+			spec.quantity(&Amount::from("1.25")).unwrap().units(),
+			12_500
+		);
+		assert!(MarketSpec::new("ETHUSDC", 2, 4).is_err());
+	}
+
+	#[test]
+	fn amount_deserializes_numbers_and_strings() {
+		let units: Amount = serde_json::from_str("50000").unwrap();
+		assert_eq!(units, Amount::Units(50_000));
+		let decimal: Amount = serde_json::from_str("\"500.25\"").unwrap();
+		assert_eq!(decimal, Amount::Decimal("500.25".to_string()));
+		assert!(serde_json::from_str::<Amount>("500.25").is_err());
+	}
+}
@@ -23,9 +23,11 @@
 //! - No environment or configuration loading
 
 pub mod client;
+pub mod decimal;
 pub mod signing;
 pub mod types;
 
 pub use client::{Client, SyncClient};
+pub use decimal::{Amount, DecimalError, MarketSpec, Notional, Price, Quantity};
 pub use signing::{SignatureAlgorithm, sign_order_request, verify_order_signature};
 pub use types::*;
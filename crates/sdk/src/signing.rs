@@ -103,6 +103,55 @@ pub fn sign_order_request<T: Serialize>(
 	}
 }
 
+/// Canonical signing message for a signed read request
+///
+/// Reads carry no body, so the method and the request path including its
+/// query string (exactly as sent, e.g. `/api/v1/fills?market=BTC-USDT`) are
+/// signed together with the `X-Timestamp` and `X-Nonce` header values.
+pub fn query_signing_message(
+	method: &str,
+	path_and_query: &str,
+	timestamp: u64,
+	nonce: &str,
+) -> Vec<u8> {
+	let mut message = Vec::new();
+	message.extend_from_slice(method.as_bytes());
+	message.push(0);
+	message.extend_from_slice(path_and_query.as_bytes());
+	message.push(0);
+	message.extend_from_slice(&timestamp.to_be_bytes());
+	message.push(0);
+	message.extend_from_slice(nonce.as_bytes());
+	message
+}
+
+/// Sign a prepared message, returning the hex-encoded signature
+///
+/// ECDSA signs the SHA-256 digest of the message, matching order signing.
+pub fn sign_message(
+	message: &[u8],
+	private_key: &[u8],
+	algorithm: SignatureAlgorithm,
+) -> Result<String, SigningError> {
+	match algorithm {
+		SignatureAlgorithm::Ed25519 => {
+			let signing_key = SigningKey::from_bytes(private_key.try_into().map_err(|_| {
+				SigningError::InvalidKey("Invalid Ed25519 private key length".to_string())
+			})?);
+			Ok(hex::encode(signing_key.sign(message).to_bytes()))
+		}
+		SignatureAlgorithm::Ecdsa => {
+			use k256::ecdsa::signature::Signer;
+
+			let signing_key = EcdsaSigningKey::from_bytes(private_key.into()).map_err(|e| {
+				SigningError::InvalidKey(format!("Invalid ECDSA private key: {}", e))
+			})?;
+			let signature: EcdsaSignature = signing_key.sign(&Sha256::digest(message)[..]);
+			Ok(hex::encode(signature.to_bytes()))
+		}
+	}
+}
+
 /// Verify an order request signature with Ed25519
 pub fn verify_order_signature_ed25519(
 	request: &PlaceOrderRequest,
@@ -221,16 +270,24 @@ fn serialize_for_signing<T: Serialize>(request: &T) -> Result<Vec<u8>, SigningEr
 			}
 		}
 
-		if let Some(price) = obj.get("price")
-			&& let Some(p) = price.as_u64()
-		{
-			message.extend_from_slice(&p.to_be_bytes());
+		if let Some(price) = obj.get("price") {
+			if let Some(p) = price.as_u64() {
+				message.extend_from_slice(&p.to_be_bytes());
+			} else if let Some(p) = price.as_str() {
+				// Decimal strings are signed as written, NUL-terminated
+				message.extend_from_slice(p.as_bytes());
+				message.push(0);
+			}
 		}
 
-		if let Some(size) = obj.get("size")
-			&& let Some(s) = size.as_u64()
-		{
-			message.extend_from_slice(&s.to_be_bytes());
+		if let Some(size) = obj.get("size") {
+			if let Some(s) = size.as_u64() {
+				message.extend_from_slice(&s.to_be_bytes());
+			} else if let Some(s) = size.as_str() {
+				// Decimal strings are signed as written, NUL-terminated
+				message.extend_from_slice(s.as_bytes());
+				message.push(0);
+			}
 		}
 
 		if let Some(client_order_id) = obj.get("client_order_id")
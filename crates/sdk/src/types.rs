@@ -14,6 +14,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::decimal::Amount;
+
 /// Order side (buy or sell)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -52,10 +54,10 @@ pub struct PlaceOrderRequest {
 	/// Order type
 	#[serde(rename = "type")]
 	pub order_type: OrderType,
-	/// Price (for limit orders)
-	pub price: Option<u64>,
-	/// Size/quantity
-	pub size: u64,
+	/// Price (for limit orders), as raw units or a decimal string
+	pub price: Option<Amount>,
+	/// Size/quantity, as raw units or a decimal string
+	pub size: Amount,
 	/// Client-provided order ID (optional)
 	pub client_order_id: Option<String>,
 }
@@ -95,6 +97,9 @@ pub struct Order {
 	pub status: OrderStatus,
 	/// Timestamp when order was created
 	pub created_at: u64,
+	/// Client order ID (if provided)
+	#[serde(default)]
+	pub client_order_id: Option<String>,
 }
 
 /// Trade execution result
@@ -117,3 +122,218 @@ pub struct Trade {
 	/// Taker order ID
 	pub taker_order_id: String,
 }
+
+/// OHLCV bar; prices and volume are decimal strings in market scale
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Candle {
+	/// Start of the interval (Unix seconds)
+	pub open_time: u64,
+	pub open: String,
+	pub high: String,
+	pub low: String,
+	pub close: String,
+	/// Traded base size
+	pub volume: String,
+	/// Number of trades in the interval
+	pub trade_count: u64,
+}
+
+/// Candles of one market and interval, oldest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandlesResponse {
+	/// Market identifier
+	pub market: String,
+	/// Candle width ("1m", "5m", "1h" or "1d")
+	pub interval: String,
+	pub candles: Vec<Candle>,
+}
+
+/// Rolling 24h market statistics; prices and volume are decimal strings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ticker {
+	/// Market identifier
+	pub market: String,
+	/// Last traded price
+	pub last: String,
+	/// First traded price in the window
+	pub open: String,
+	pub high: String,
+	pub low: String,
+	/// Traded base size in the window
+	pub volume: String,
+	/// Price change over the window (`last - open`)
+	pub change: String,
+	/// Number of trades in the window
+	pub trade_count: u64,
+	/// Time the statistics were computed for (Unix seconds)
+	pub timestamp: u64,
+}
+
+/// Whether a fill added liquidity (maker) or removed it (taker)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LiquidityRole {
+	Maker,
+	Taker,
+}
+
+/// Public trade; price and size are decimal strings in market scale
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublicTrade {
+	/// Trade ID
+	pub trade_id: String,
+	pub price: String,
+	pub size: String,
+	/// Side of the taker
+	pub side: Side,
+	/// Timestamp when the trade occurred (Unix seconds)
+	pub timestamp: u64,
+}
+
+/// One page of a market's trades, newest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradesPage {
+	/// Market identifier
+	pub market: String,
+	pub trades: Vec<PublicTrade>,
+	/// Pass as `cursor` to fetch the next (older) page; absent on the last page
+	pub next_cursor: Option<String>,
+}
+
+/// A principal's side of a trade
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fill {
+	/// Trade ID
+	pub trade_id: String,
+	/// Market identifier
+	pub market: String,
+	/// The principal's order that traded
+	pub order_id: String,
+	/// Side of the principal's order
+	pub side: Side,
+	pub price: String,
+	pub size: String,
+	pub role: LiquidityRole,
+	/// Timestamp when the trade occurred (Unix seconds)
+	pub timestamp: u64,
+}
+
+/// One page of a principal's fills, newest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FillsPage {
+	pub fills: Vec<Fill>,
+	/// Pass as `cursor` to fetch the next (older) page; absent on the last page
+	pub next_cursor: Option<String>,
+}
+
+/// Orders of one market placed under a single signature
+///
+/// The orders that pass validation are sequenced back to back, with no
+/// other order in between.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchPlaceOrderRequest {
+	pub orders: Vec<PlaceOrderRequest>,
+}
+
+/// Why one entry of a batch was not accepted, as in the single-request
+/// error body
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchItemError {
+	pub code: String,
+	pub reason: String,
+	pub retryable: bool,
+	pub unconfirmed: bool,
+}
+
+/// Outcome of one order of a batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchOrderResult {
+	/// Server-assigned order ID; absent if the order was not accepted
+	pub order_id: Option<String>,
+	pub status: OrderStatus,
+	pub client_order_id: Option<String>,
+	pub error: Option<BatchItemError>,
+}
+
+/// Per-order outcomes of a batch, in request order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchPlaceOrderResponse {
+	pub results: Vec<BatchOrderResult>,
+}
+
+/// Outcome of one cancel of a batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchCancelResult {
+	pub order_id: String,
+	/// Absent if the cancel was sequenced
+	pub error: Option<BatchItemError>,
+}
+
+/// Per-cancel outcomes of a batch cancel, in request order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchCancelResponse {
+	pub results: Vec<BatchCancelResult>,
+}
+
+/// Acknowledgement of a cancel request
+///
+/// The cancel has been sequenced by the matching engine; its outcome is
+/// reported as a `cancelled` or `cancel_rejected` execution report.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CancelOrderResponse {
+	/// Order the cancel targets; empty for a cancel by client order ID
+	/// whose order was not known when the cancel was admitted
+	pub order_id: String,
+	/// Client order ID the cancel targets, for a cancel by client order ID
+	#[serde(default)]
+	pub client_order_id: Option<String>,
+}
+
+/// What happened to an order in an execution report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionType {
+	/// The order rests on the book
+	New,
+	/// The order was rejected and never entered the book
+	Rejected,
+	/// The order traded
+	Trade,
+	/// The order has traded part of its size
+	PartiallyFilled,
+	/// The order has traded its whole size
+	Filled,
+	/// The order was removed from the book
+	Cancelled,
+	/// A cancel request for the order failed
+	CancelRejected,
+}
+
+/// Committed update to one of the principal's orders
+///
+/// Prices and sizes are decimal strings in market scale. Fields that do not
+/// apply to `exec_type` are absent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionReport {
+	/// Matching event sequence of the update
+	pub sequence: u64,
+	/// Market identifier
+	pub market: String,
+	pub order_id: String,
+	/// Client order ID of the order, if it has one
+	#[serde(default)]
+	pub client_order_id: Option<String>,
+	pub exec_type: ExecutionType,
+	/// Limit price for `new`, trade price for `trade`
+	pub price: Option<String>,
+	/// Resting size for `new`, trade size for `trade`
+	pub size: Option<String>,
+	pub filled_size: Option<String>,
+	pub remaining_size: Option<String>,
+	pub trade_id: Option<String>,
+	pub role: Option<LiquidityRole>,
+	/// Why the order or cancel was rejected
+	pub reason: Option<String>,
+	/// Timestamp of the update (Unix seconds)
+	pub timestamp: u64,
+}